@@ -0,0 +1,103 @@
+// compares owned parses with borrowed ones of a generated config, with and
+// without checking it against the directive table, counting the allocations
+// each makes:
+//     cargo bench --bench borrowed_parse
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::fmt::Write;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use rust_crossplane::{parse_borrowed, Crossplane, ParseOptions};
+
+const SERVERS: usize = 5000;
+const RUNS: usize = 5;
+
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+// the fastest of a few runs, to leave out warming up, and the allocations
+// of one run
+fn measure(run: impl Fn()) -> (Duration, usize) {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    run();
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - before;
+    let fastest = (0..RUNS)
+        .map(|_| {
+            let start = Instant::now();
+            run();
+            start.elapsed()
+        })
+        .min()
+        .unwrap();
+    (fastest, allocations)
+}
+
+fn main() {
+    let mut source = String::from("events {\n    worker_connections 1024;\n}\nhttp {\n");
+    for i in 0..SERVERS {
+        let _ = write!(
+            source,
+            "    server {{\n        listen {};\n        server_name s{}.example.com;\n        \
+             location / {{\n            proxy_pass http://127.0.0.1:{};\n            \
+             proxy_set_header Host $host;\n        }}\n    }}\n",
+            8000 + i % 1000,
+            i,
+            9000 + i % 1000
+        );
+    }
+    source.push_str("}\n");
+
+    let crossplane = Crossplane::default();
+    let options = ParseOptions::default();
+    let (owned, owned_allocations) = measure(|| {
+        let payload = crossplane.parse_str(&source, "nginx.conf");
+        assert!(payload.errors.is_empty());
+    });
+    let (borrowed, borrowed_allocations) = measure(|| {
+        let payload = parse_borrowed(&source, "nginx.conf", &options);
+        assert!(payload.errors.is_empty());
+    });
+    let strict = ParseOptions::default().strict(true);
+    let (strict_owned, strict_owned_allocations) = measure(|| {
+        let payload = Crossplane::builder()
+            .strict(true)
+            .build()
+            .parse_str(&source, "nginx.conf");
+        assert!(payload.errors.is_empty());
+    });
+    let (strict_borrowed, strict_borrowed_allocations) = measure(|| {
+        let payload = parse_borrowed(&source, "nginx.conf", &strict);
+        assert!(payload.errors.is_empty());
+    });
+
+    println!("parsing {} servers ({} bytes)", SERVERS, source.len());
+    println!("  owned:    {:?}, {} allocations", owned, owned_allocations);
+    println!(
+        "  borrowed: {:?}, {} allocations",
+        borrowed, borrowed_allocations
+    );
+    println!("strict:");
+    println!(
+        "  owned:    {:?}, {} allocations",
+        strict_owned, strict_owned_allocations
+    );
+    println!(
+        "  borrowed: {:?}, {} allocations",
+        strict_borrowed, strict_borrowed_allocations
+    );
+}
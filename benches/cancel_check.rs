@@ -0,0 +1,53 @@
+// guards the cost of checking a cancel token during parses, comparing parses
+// of a generated config with and without one:
+//     cargo bench --bench cancel_check
+use std::fmt::Write;
+use std::time::{Duration, Instant};
+
+use rust_crossplane::{parse, CancelToken, ParseOptions};
+
+const SERVERS: usize = 5000;
+const RUNS: usize = 5;
+
+fn main() {
+    let mut source = String::from("events {\n    worker_connections 1024;\n}\nhttp {\n");
+    for i in 0..SERVERS {
+        let _ = write!(
+            source,
+            "    server {{\n        listen {};\n        server_name s{}.example.com;\n        \
+             location / {{\n            proxy_pass http://127.0.0.1:{};\n        }}\n    }}\n",
+            8000 + i % 1000,
+            i,
+            9000 + i % 1000
+        );
+    }
+    source.push_str("}\n");
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("nginx.conf");
+    std::fs::write(&path, &source).unwrap();
+
+    // the fastest of a few runs, to leave out warming up
+    let fastest = |options: &ParseOptions| -> Duration {
+        (0..RUNS)
+            .map(|_| {
+                let start = Instant::now();
+                let payload = parse(&path, options);
+                let elapsed = start.elapsed();
+                assert!(payload.errors.is_empty());
+                elapsed
+            })
+            .min()
+            .unwrap()
+    };
+    let without = fastest(&ParseOptions::default());
+    let with = fastest(&ParseOptions::default().cancel_token(CancelToken::new()));
+
+    println!("parsing {} servers ({} bytes)", SERVERS, source.len());
+    println!("  without a cancel token: {:?}", without);
+    println!("  with a cancel token:    {:?}", with);
+    println!(
+        "  overhead:               {:+.1}%",
+        (with.as_secs_f64() / without.as_secs_f64() - 1.0) * 100.0
+    );
+}
@@ -0,0 +1,95 @@
+// writes a payload of 300k directives as JSON and reads it back, measuring
+// the time and the peak of heap memory on top of the payload:
+//     cargo bench --bench json_stream
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use rust_crossplane::{Crossplane, JsonStyle, Payload};
+
+const SERVERS: usize = 50_000;
+
+struct Peak;
+
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Peak {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let current = CURRENT.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+        PEAK.fetch_max(current, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        CURRENT.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Peak = Peak;
+
+// the time a run takes and the most memory it held at once beyond what was
+// allocated before it, in MB
+fn measure<T>(run: impl FnOnce() -> T) -> (T, Duration, usize) {
+    let before = CURRENT.load(Ordering::Relaxed);
+    PEAK.store(before, Ordering::Relaxed);
+    let start = Instant::now();
+    let value = run();
+    let elapsed = start.elapsed();
+    (
+        value,
+        elapsed,
+        (PEAK.load(Ordering::Relaxed) - before) >> 20,
+    )
+}
+
+fn main() {
+    let mut source = String::from("http {\n");
+    for i in 0..SERVERS {
+        let _ = write!(
+            source,
+            "    server {{\n        listen {};\n        server_name s{}.example.com;\n        \
+             location / {{\n            proxy_pass http://127.0.0.1:{};\n            \
+             proxy_set_header Host $host;\n        }}\n    }}\n",
+            8000 + i % 1000,
+            i,
+            9000 + i % 1000
+        );
+    }
+    source.push_str("}\n");
+    let payload = Crossplane::default().parse_str(&source, "nginx.conf");
+    drop(source);
+    let pretty = JsonStyle::Pretty { indent: 4 };
+
+    println!("{} directives", SERVERS * 6 + 1);
+    let (_, time, peak) = measure(|| payload.write_json(io::sink(), pretty).unwrap());
+    println!("  write_json to a sink:      {:?}, {} MB", time, peak);
+    let (json, time, peak) = measure(|| {
+        let mut json = Vec::new();
+        payload.write_json(&mut json, pretty).unwrap();
+        json
+    });
+    println!("  write_json to memory:      {:?}, {} MB", time, peak);
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("payload.json");
+    std::fs::write(&path, &json).unwrap();
+    let len = json.len() >> 20;
+    drop(json);
+    println!("read back from {} MB of JSON", len);
+    let (read, time, peak) = measure(|| Payload::read_json(File::open(&path).unwrap()).unwrap());
+    assert_eq!(read.config[0].parsed.len(), 1);
+    drop(read);
+    println!("  read_json from the file:   {:?}, {} MB", time, peak);
+    let (read, time, peak) = measure(|| {
+        let json = std::fs::read(&path).unwrap();
+        Payload::read_json(&json[..]).unwrap()
+    });
+    assert_eq!(read.config[0].parsed.len(), 1);
+    println!("  read_json after reading it: {:?}, {} MB", time, peak);
+}
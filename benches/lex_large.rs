@@ -0,0 +1,71 @@
+// lexes a generated config of 2 MB with comments, quoted strings, escapes
+// and regexes, measuring the time and the allocations per token:
+//     cargo bench --bench lex_large
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::fmt::Write as _;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use rust_crossplane::lex;
+
+const SIZE: usize = 2_000_000;
+const RUNS: usize = 5;
+
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+fn main() {
+    let mut source = String::from("http {\n");
+    let mut i = 0;
+    while source.len() < SIZE {
+        let _ = write!(
+            source,
+            "    server {{ # server {i}\n        listen {};\n        \
+             server_name \"s{i}.example.com\" 'www.s{i}.example.com';\n        \
+             location ~ ^/api/(\\d+)$ {{\n            return 200 \"id \\\"$1\\\"\\n\";\n        \
+             }}\n    }}\n",
+            8000 + i % 1000
+        );
+        i += 1;
+    }
+    source.push_str("}\n");
+
+    // the fastest of a few runs, to leave out warming up
+    let mut fastest = Duration::MAX;
+    let mut tokens = 0;
+    let mut allocations = 0;
+    for _ in 0..RUNS {
+        let before = ALLOCATIONS.load(Ordering::Relaxed);
+        let start = Instant::now();
+        let lexed = lex(source.as_bytes()).unwrap();
+        fastest = fastest.min(start.elapsed());
+        allocations = ALLOCATIONS.load(Ordering::Relaxed) - before;
+        tokens = lexed.len();
+    }
+
+    println!("lexing {} bytes into {} tokens", source.len(), tokens);
+    println!("  time:                  {:?}", fastest);
+    println!(
+        "  throughput:            {:.1} MB/s",
+        source.len() as f64 / fastest.as_secs_f64() / 1e6
+    );
+    println!(
+        "  allocations per token: {:.2}",
+        allocations as f64 / tokens as f64
+    );
+}
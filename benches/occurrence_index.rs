@@ -0,0 +1,55 @@
+// compares repeated lookups by walking the payload with lookups through an
+// occurrence index, on a generated config with a few thousand servers:
+//     cargo bench --bench occurrence_index
+use std::fmt::Write;
+use std::time::Instant;
+
+use rust_crossplane::{parse, ParseOptions};
+
+const SERVERS: usize = 2000;
+const LOOKUPS: usize = 50;
+const NAMES: [&str; 5] = ["proxy_pass", "listen", "server_name", "root", "return"];
+
+fn main() {
+    let mut source = String::from("events {\n    worker_connections 1024;\n}\nhttp {\n");
+    for i in 0..SERVERS {
+        let _ = write!(
+            source,
+            "    server {{\n        listen {};\n        server_name s{}.example.com;\n        \
+             location / {{\n            proxy_pass http://127.0.0.1:{};\n        }}\n        \
+             location /static/ {{\n            root /srv/{};\n        }}\n    }}\n",
+            8000 + i % 1000,
+            i,
+            9000 + i % 1000,
+            i
+        );
+    }
+    source.push_str("}\n");
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("nginx.conf");
+    std::fs::write(&path, source).unwrap();
+    let payload = parse(&path, &ParseOptions::default());
+
+    let start = Instant::now();
+    let mut walked = 0;
+    for i in 0..LOOKUPS {
+        walked += payload.find(NAMES[i % NAMES.len()]).len();
+    }
+    let walking = start.elapsed();
+
+    let start = Instant::now();
+    let index = payload.index();
+    let building = start.elapsed();
+    let mut indexed = 0;
+    for i in 0..LOOKUPS {
+        indexed += index.find(&payload, NAMES[i % NAMES.len()]).unwrap().len();
+    }
+    let lookups = start.elapsed() - building;
+    assert_eq!(walked, indexed);
+
+    println!("{} lookups over {} servers", LOOKUPS, SERVERS);
+    println!("  walking the payload: {:?}", walking);
+    println!("  building the index:  {:?}", building);
+    println!("  indexed lookups:     {:?}", lookups);
+}
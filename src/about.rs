@@ -0,0 +1,101 @@
+use std::fmt;
+use std::ops::Range;
+
+use serde::Serialize;
+
+use crate::analyze::{known_directives, DIRECTIVE_TABLE_REVISION};
+use crate::version::NginxVersion;
+
+/// What this build of the crate checks configs with, for tools to report
+/// alongside their results.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct About {
+    pub crate_version: &'static str,
+    /// See [`DIRECTIVE_TABLE_REVISION`].
+    pub directive_table_revision: u32,
+    /// The nginx versions the directive table describes.
+    pub supported_nginx_versions: Range<NginxVersion>,
+    /// The cargo features the crate was built with.
+    pub enabled_features: Vec<&'static str>,
+    /// The modules of the directive table, without the `ngx_` prefix and
+    /// `_module` suffix, sorted.
+    pub bundled_modules: Vec<&'static str>,
+}
+
+/// Describes this build of the crate.
+pub fn about() -> About {
+    let mut enabled_features = Vec::new();
+    if cfg!(feature = "regex") {
+        enabled_features.push("regex");
+    }
+    if cfg!(feature = "test_support") {
+        enabled_features.push("test_support");
+    }
+    let mut bundled_modules: Vec<&'static str> =
+        known_directives().iter().map(|d| d.module).collect();
+    bundled_modules.sort_unstable();
+    bundled_modules.dedup();
+    About {
+        crate_version: env!("CARGO_PKG_VERSION"),
+        directive_table_revision: DIRECTIVE_TABLE_REVISION,
+        supported_nginx_versions: NginxVersion::V1_18..NginxVersion::new(1, 29, 0),
+        enabled_features,
+        bundled_modules,
+    }
+}
+
+// the lines of `crossplane --version` after the name
+impl fmt::Display for About {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let list = |names: &[&str]| match names {
+            [] => "none".to_string(),
+            _ => names.join(", "),
+        };
+        writeln!(f, "{}", self.crate_version)?;
+        writeln!(
+            f,
+            "directive table revision {}",
+            self.directive_table_revision
+        )?;
+        writeln!(
+            f,
+            "nginx versions {} up to {}",
+            self.supported_nginx_versions.start, self.supported_nginx_versions.end
+        )?;
+        writeln!(f, "features: {}", list(&self.enabled_features))?;
+        write!(f, "modules: {}", list(&self.bundled_modules))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_about() {
+        let about = about();
+        assert_eq!(about.crate_version, env!("CARGO_PKG_VERSION"));
+        assert_eq!(about.directive_table_revision, DIRECTIVE_TABLE_REVISION);
+        assert!(about
+            .supported_nginx_versions
+            .contains(&NginxVersion::V1_28));
+        assert!(!about
+            .supported_nginx_versions
+            .contains(&NginxVersion::new(1, 16, 1)));
+        assert_eq!(
+            about.enabled_features.contains(&"test_support"),
+            cfg!(feature = "test_support")
+        );
+        for module in ["core", "http_core", "stream_core", "mail_core"] {
+            assert!(about.bundled_modules.contains(&module), "{}", module);
+        }
+        assert!(about.bundled_modules.windows(2).all(|w| w[0] < w[1]));
+
+        let text = about.to_string();
+        assert!(text.starts_with(&format!(
+            "{}\ndirective table revision {}\nnginx versions 1.18.0 up to 1.29.0\n",
+            about.crate_version, DIRECTIVE_TABLE_REVISION
+        )));
+        assert!(text.contains("modules: "));
+    }
+}
@@ -0,0 +1,1398 @@
+mod args;
+mod coverage;
+mod directives;
+mod values;
+
+use std::borrow::Cow;
+use std::sync::Arc;
+
+use crate::context::ContextId;
+use crate::error::ParseError;
+use crate::escape::arg_unescaped;
+#[cfg(feature = "regex")]
+use crate::lint::directive_regexes;
+use crate::parse::{Directive, ParseOptions};
+use crate::version::{NginxVersion, VersionRange};
+
+pub use args::ArgsError;
+pub(crate) use args::{invalid_flag_value, ListenSpec};
+use args::{invalid_number_of_arguments, ARG_VALIDATORS, ENTRY_SHAPES};
+pub use coverage::{CoverageReport, TableGaps};
+use directives::DIRECTIVES;
+pub use values::{arg_type, ArgType, ArgValueError};
+
+/// Revision of the directive table and the argument checks, bumped
+/// whenever either changes, so results can be traced to the table that
+/// produced them.
+pub const DIRECTIVE_TABLE_REVISION: u32 = 6;
+
+// bit masks for the arguments a directive takes, as in ngx_conf_file.h
+pub const NGX_CONF_NOARGS: u32 = 0x0000_0001;
+pub const NGX_CONF_TAKE1: u32 = 0x0000_0002;
+pub const NGX_CONF_TAKE2: u32 = 0x0000_0004;
+pub const NGX_CONF_TAKE3: u32 = 0x0000_0008;
+pub const NGX_CONF_TAKE4: u32 = 0x0000_0010;
+pub const NGX_CONF_TAKE5: u32 = 0x0000_0020;
+pub const NGX_CONF_TAKE6: u32 = 0x0000_0040;
+pub const NGX_CONF_TAKE7: u32 = 0x0000_0080;
+pub const NGX_CONF_BLOCK: u32 = 0x0000_0100;
+pub const NGX_CONF_FLAG: u32 = 0x0000_0200;
+pub const NGX_CONF_ANY: u32 = 0x0000_0400;
+pub const NGX_CONF_1MORE: u32 = 0x0000_0800;
+pub const NGX_CONF_2MORE: u32 = 0x0000_1000;
+
+pub const NGX_CONF_TAKE12: u32 = NGX_CONF_TAKE1 | NGX_CONF_TAKE2;
+pub const NGX_CONF_TAKE13: u32 = NGX_CONF_TAKE1 | NGX_CONF_TAKE3;
+pub const NGX_CONF_TAKE23: u32 = NGX_CONF_TAKE2 | NGX_CONF_TAKE3;
+pub const NGX_CONF_TAKE123: u32 = NGX_CONF_TAKE12 | NGX_CONF_TAKE3;
+pub const NGX_CONF_TAKE1234: u32 = NGX_CONF_TAKE123 | NGX_CONF_TAKE4;
+
+// bit masks for the contexts a directive is allowed in
+pub const NGX_MAIN_CONF: u32 = 0x0004_0000;
+pub const NGX_EVENT_CONF: u32 = 0x0008_0000;
+pub const NGX_MAIL_MAIN_CONF: u32 = 0x0010_0000;
+pub const NGX_MAIL_SRV_CONF: u32 = 0x0020_0000;
+pub const NGX_STREAM_MAIN_CONF: u32 = 0x0040_0000;
+pub const NGX_STREAM_SRV_CONF: u32 = 0x0080_0000;
+pub const NGX_STREAM_UPS_CONF: u32 = 0x0100_0000;
+pub const NGX_HTTP_MAIN_CONF: u32 = 0x0200_0000;
+pub const NGX_HTTP_SRV_CONF: u32 = 0x0400_0000;
+pub const NGX_HTTP_LOC_CONF: u32 = 0x0800_0000;
+pub const NGX_HTTP_UPS_CONF: u32 = 0x1000_0000;
+pub const NGX_HTTP_SIF_CONF: u32 = 0x2000_0000;
+pub const NGX_HTTP_LIF_CONF: u32 = 0x4000_0000;
+pub const NGX_HTTP_LMT_CONF: u32 = 0x8000_0000;
+
+pub const NGX_ANY_CONF: u32 = NGX_MAIN_CONF
+    | NGX_EVENT_CONF
+    | NGX_MAIL_MAIN_CONF
+    | NGX_MAIL_SRV_CONF
+    | NGX_STREAM_MAIN_CONF
+    | NGX_STREAM_SRV_CONF
+    | NGX_STREAM_UPS_CONF
+    | NGX_HTTP_MAIN_CONF
+    | NGX_HTTP_SRV_CONF
+    | NGX_HTTP_LOC_CONF
+    | NGX_HTTP_UPS_CONF;
+
+/// The block contexts nginx knows, as the names of the enclosing blocks,
+/// with their masks.
+pub const CONTEXTS: &[(&[&str], u32)] = &[
+    (&[], NGX_MAIN_CONF),
+    (&["events"], NGX_EVENT_CONF),
+    (&["mail"], NGX_MAIL_MAIN_CONF),
+    (&["mail", "server"], NGX_MAIL_SRV_CONF),
+    (&["stream"], NGX_STREAM_MAIN_CONF),
+    (&["stream", "server"], NGX_STREAM_SRV_CONF),
+    (&["stream", "upstream"], NGX_STREAM_UPS_CONF),
+    (&["http"], NGX_HTTP_MAIN_CONF),
+    (&["http", "server"], NGX_HTTP_SRV_CONF),
+    (&["http", "location"], NGX_HTTP_LOC_CONF),
+    (&["http", "upstream"], NGX_HTTP_UPS_CONF),
+    (&["http", "server", "if"], NGX_HTTP_SIF_CONF),
+    (&["http", "location", "if"], NGX_HTTP_LIF_CONF),
+    (&["http", "location", "limit_except"], NGX_HTTP_LMT_CONF),
+];
+
+// the bits of the numbers of args, from none to seven, as nginx's
+// argument_number
+const ARGUMENT_NUMBER: [u32; 8] = [
+    NGX_CONF_NOARGS,
+    NGX_CONF_TAKE1,
+    NGX_CONF_TAKE2,
+    NGX_CONF_TAKE3,
+    NGX_CONF_TAKE4,
+    NGX_CONF_TAKE5,
+    NGX_CONF_TAKE6,
+    NGX_CONF_TAKE7,
+];
+
+const ARG_MASKS: &[(&str, u32)] = &[
+    ("noargs", NGX_CONF_NOARGS),
+    ("take1", NGX_CONF_TAKE1),
+    ("take2", NGX_CONF_TAKE2),
+    ("take3", NGX_CONF_TAKE3),
+    ("take4", NGX_CONF_TAKE4),
+    ("take5", NGX_CONF_TAKE5),
+    ("take6", NGX_CONF_TAKE6),
+    ("take7", NGX_CONF_TAKE7),
+    ("block", NGX_CONF_BLOCK),
+    ("flag", NGX_CONF_FLAG),
+    ("any", NGX_CONF_ANY),
+    ("1more", NGX_CONF_1MORE),
+    ("2more", NGX_CONF_2MORE),
+];
+
+/// What nginx knows about a directive as defined by one module.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DirectiveSpec {
+    pub name: &'static str,
+    /// The allowed contexts and arguments, `NGX_*` bits or'ed together.
+    pub mask: u32,
+    /// The nginx module defining the directive, without the `ngx_` prefix
+    /// and `_module` suffix.
+    pub module: &'static str,
+    pub versions: VersionRange,
+    /// The value nginx uses when the directive is not given.
+    pub default: Option<&'static str>,
+    /// Whether the directive, or a form of it, still works but is on its
+    /// way out.
+    pub deprecated: Option<Deprecation>,
+    /// Whether moving the directive past others of its block changes what
+    /// nginx does, like `rewrite` or `allow`.
+    pub order_sensitive: bool,
+}
+
+/// A directive, or a form of it given by an arg, that nginx still takes
+/// but warns about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Deprecation {
+    /// The arg giving the deprecated form, None when the whole directive is
+    /// deprecated.
+    pub arg: Option<&'static str>,
+    pub since: NginxVersion,
+    /// What to use instead, like `the "http2" directive`.
+    pub replacement: &'static str,
+}
+
+impl Deprecation {
+    /// Whether the deprecation applies to `directive`.
+    pub fn applies_to(&self, directive: &Directive) -> bool {
+        self.arg
+            .is_none_or(|arg| directive.args.iter().any(|a| a == arg))
+    }
+}
+
+impl DirectiveSpec {
+    /// The contexts the directive is allowed in.
+    pub fn contexts(&self) -> impl Iterator<Item = &'static [&'static str]> + '_ {
+        CONTEXTS
+            .iter()
+            .filter(|(_, mask)| self.mask & mask != 0)
+            .map(|(ctx, _)| *ctx)
+    }
+
+    /// Whether the directive is allowed in a block context, given as the
+    /// names of the enclosing blocks like in [`CONTEXTS`].
+    pub fn allowed_in<S: AsRef<str>>(&self, ctx: &[S]) -> bool {
+        context_mask(ctx).is_some_and(|mask| self.mask & mask != 0)
+    }
+
+    /// Checks the arguments against the special forms this definition of
+    /// the directive takes, as nginx uses them, see [`arg_unescaped`].
+    pub fn validate_args(&self, args: &[String]) -> Result<(), ArgsError> {
+        self.validate_values(args)
+    }
+
+    // the args are unescaped only for the directives with a validator
+    fn validate_values<S: AsRef<str>>(&self, args: &[S]) -> Result<(), ArgsError> {
+        let mut validators = ARG_VALIDATORS
+            .iter()
+            .filter(|(name, module, _)| {
+                *name == self.name && module.is_none_or(|m| m == self.module)
+            })
+            .peekable();
+        if validators.peek().is_none() {
+            return Ok(());
+        }
+        let args = unescaped(args);
+        let args: Vec<&str> = args.iter().map(|arg| arg.as_ref()).collect();
+        validators.try_for_each(|(_, _, validator)| validator(&args))
+    }
+
+    // check the args as ngx_conf_handler does: whether the directive opens
+    // a block when known, the number of args the mask allows, the values
+    // of flags, then the special forms, the last two with escapes resolved
+    fn check_args<S: AsRef<str>>(
+        &self,
+        args: &[S],
+        opens_block: Option<bool>,
+    ) -> Result<(), ArgsError> {
+        let is_block = self.mask & NGX_CONF_BLOCK != 0;
+        match opens_block {
+            Some(false) if is_block => {
+                return Err(ArgsError::new(format!(
+                    "directive \"{}\" has no opening \"{{\"",
+                    self.name
+                )))
+            }
+            Some(true) if !is_block => {
+                return Err(ArgsError::new(format!(
+                    "directive \"{}\" is not terminated by ';'",
+                    self.name
+                )))
+            }
+            _ => {}
+        }
+
+        let n = args.len();
+        let valid = if self.mask & NGX_CONF_ANY != 0 {
+            true
+        } else if self.mask & NGX_CONF_FLAG != 0 {
+            n == 1
+        } else if self.mask & NGX_CONF_1MORE != 0 {
+            n >= 1
+        } else if self.mask & NGX_CONF_2MORE != 0 {
+            n >= 2
+        } else {
+            ARGUMENT_NUMBER
+                .get(n)
+                .is_some_and(|bits| self.mask & bits != 0)
+        };
+        if !valid {
+            return Err(invalid_number_of_arguments(self.name));
+        }
+
+        if self.mask & NGX_CONF_FLAG != 0 {
+            let value = arg_unescaped(args[0].as_ref());
+            if !value.eq_ignore_ascii_case("on") && !value.eq_ignore_ascii_case("off") {
+                return Err(ArgsError::new(invalid_flag_value(self.name, &value)).at(0));
+            }
+        }
+        self.validate_values(args)
+    }
+
+    /// Names of the argument bits of the mask, like `take12` as `take1`
+    /// and `take2`.
+    pub fn arg_kinds(&self) -> impl Iterator<Item = &'static str> + '_ {
+        ARG_MASKS
+            .iter()
+            .filter(|(_, mask)| self.mask & mask != 0)
+            .map(|(name, _)| *name)
+    }
+
+    const fn since(mut self, major: u32, minor: u32, patch: u32) -> Self {
+        self.versions.since = Some(NginxVersion::new(major, minor, patch));
+        self
+    }
+
+    const fn removed(mut self, major: u32, minor: u32, patch: u32) -> Self {
+        self.versions.removed = Some(NginxVersion::new(major, minor, patch));
+        self
+    }
+
+    const fn default(mut self, value: &'static str) -> Self {
+        self.default = Some(value);
+        self
+    }
+
+    const fn deprecated(
+        self,
+        major: u32,
+        minor: u32,
+        patch: u32,
+        replacement: &'static str,
+    ) -> Self {
+        self.deprecated_form(None, NginxVersion::new(major, minor, patch), replacement)
+    }
+
+    const fn deprecated_arg(
+        self,
+        arg: &'static str,
+        major: u32,
+        minor: u32,
+        patch: u32,
+        replacement: &'static str,
+    ) -> Self {
+        self.deprecated_form(
+            Some(arg),
+            NginxVersion::new(major, minor, patch),
+            replacement,
+        )
+    }
+
+    const fn deprecated_form(
+        mut self,
+        arg: Option<&'static str>,
+        since: NginxVersion,
+        replacement: &'static str,
+    ) -> Self {
+        self.deprecated = Some(Deprecation {
+            arg,
+            since,
+            replacement,
+        });
+        self
+    }
+
+    const fn order_sensitive(mut self) -> Self {
+        self.order_sensitive = true;
+        self
+    }
+}
+
+impl DirectiveSpec {
+    /// A directive of `module` taking the contexts and arguments of `mask`,
+    /// to extend an [`Analyzer`] with.
+    pub const fn new(name: &'static str, mask: u32, module: &'static str) -> Self {
+        spec(name, mask, module)
+    }
+}
+
+// the args as nginx uses them, each copied only when it has an escape
+fn unescaped<S: AsRef<str>>(args: &[S]) -> Vec<Cow<'_, str>> {
+    args.iter().map(|arg| arg_unescaped(arg.as_ref())).collect()
+}
+
+const fn spec(name: &'static str, mask: u32, module: &'static str) -> DirectiveSpec {
+    DirectiveSpec {
+        name,
+        mask,
+        module,
+        versions: VersionRange {
+            since: None,
+            removed: None,
+        },
+        default: None,
+        deprecated: None,
+        order_sensitive: false,
+    }
+}
+
+/// A directive table to check configs against: the directives nginx
+/// knows, extended with those of other modules. The table is shared by
+/// clones and never changes, so an analyzer can be built once per setup
+/// and used from any number of threads.
+#[derive(Debug, Clone, Default)]
+pub struct Analyzer {
+    // sorted by name; None for the table of nginx alone
+    table: Option<Arc<[DirectiveSpec]>>,
+}
+
+impl Analyzer {
+    /// The directives nginx knows.
+    pub fn new() -> Self {
+        Analyzer::default()
+    }
+
+    /// The analyzer with `specs` known as well, like the directives of a
+    /// third party module.
+    pub fn with_directives(self, specs: impl IntoIterator<Item = DirectiveSpec>) -> Self {
+        let mut table = self.known_directives().to_vec();
+        table.extend(specs);
+        table.sort_by_key(|d| d.name);
+        Analyzer {
+            table: Some(table.into()),
+        }
+    }
+
+    /// Every directive known to the analyzer, sorted by name.
+    pub fn known_directives(&self) -> &[DirectiveSpec] {
+        self.table.as_deref().unwrap_or(DIRECTIVES)
+    }
+
+    /// The definitions of a directive, one per module defining it.
+    pub fn directive_specs(&self, name: &str) -> &[DirectiveSpec] {
+        let table = self.known_directives();
+        let start = table.partition_point(|d| d.name < name);
+        let end = start + table[start..].partition_point(|d| d.name == name);
+        &table[start..end]
+    }
+
+    /// Whether a definition of the directive takes a block.
+    pub fn is_block(&self, name: &str) -> bool {
+        self.directive_specs(name)
+            .iter()
+            .any(|d| d.mask & NGX_CONF_BLOCK != 0)
+    }
+
+    /// Like [`find_directive_spec`], among the directives of the analyzer.
+    pub fn find_directive_spec<S: AsRef<str>>(
+        &self,
+        name: &str,
+        ctx: &[S],
+    ) -> Option<&DirectiveSpec> {
+        self.directive_specs(name)
+            .iter()
+            .find(|d| d.allowed_in(ctx))
+    }
+
+    /// Like [`suggest_directive`], among the directives of the analyzer.
+    pub fn suggest_directive(&self, name: &str) -> Option<&'static str> {
+        let name = name.to_ascii_lowercase();
+        // short names have little room for typos before anything matches
+        let max_distance = if name.len() <= 5 { 1 } else { 2 };
+        self.known_directives()
+            .iter()
+            .filter(|d| d.name.len().abs_diff(name.len()) <= max_distance)
+            .map(|d| (edit_distance(&name, d.name), d.name))
+            .filter(|&(distance, _)| distance <= max_distance)
+            .min_by_key(|&(distance, _)| distance)
+            .map(|(_, name)| name)
+    }
+
+    /// Like [`validate_args`], among the directives of the analyzer.
+    pub fn validate_args(&self, name: &str, args: &[String]) -> Result<(), ArgsError> {
+        let mut result = Ok(());
+        for spec in self.directive_specs(name) {
+            result = spec.validate_args(args);
+            if result.is_ok() {
+                break;
+            }
+        }
+        result
+    }
+}
+
+/// Every directive known to the analyzer, sorted by name.
+pub fn known_directives() -> &'static [DirectiveSpec] {
+    DIRECTIVES
+}
+
+/// The definitions of a directive, one per module defining it.
+pub fn directive_specs(name: &str) -> &'static [DirectiveSpec] {
+    let start = DIRECTIVES.partition_point(|d| d.name < name);
+    let end = start + DIRECTIVES[start..].partition_point(|d| d.name == name);
+    &DIRECTIVES[start..end]
+}
+
+/// The definition of a directive that applies in a block context, given as
+/// the names of the enclosing blocks like in [`CONTEXTS`]; `proxy_pass`
+/// in `["stream", "server"]` is the one of the stream proxy module.
+pub fn find_directive_spec<S: AsRef<str>>(name: &str, ctx: &[S]) -> Option<&'static DirectiveSpec> {
+    directive_specs(name).iter().find(|d| d.allowed_in(ctx))
+}
+
+// the mask of a block context, None for contexts nginx does not know
+fn context_mask<S: AsRef<str>>(ctx: &[S]) -> Option<u32> {
+    CONTEXTS
+        .iter()
+        .find(|(names, _)| names.iter().copied().eq(ctx.iter().map(AsRef::as_ref)))
+        .map(|&(_, mask)| mask)
+}
+
+/// The known directive closest to `name`: one differing only in case, or
+/// else one with a typo or two.
+pub fn suggest_directive(name: &str) -> Option<&'static str> {
+    Analyzer::new().suggest_directive(name)
+}
+
+// optimal string alignment distance: insertions, deletions, substitutions
+// and swaps of adjacent characters each count as one edit
+fn edit_distance(a: &str, b: &str) -> usize {
+    let (a, b): (Vec<char>, Vec<char>) = (a.chars().collect(), b.chars().collect());
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    rows[0] = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = distance;
+        }
+    }
+    rows[a.len()][b.len()]
+}
+
+/// Checks the arguments of directives with special argument forms, like the
+/// levels of `error_log` or the masks of `worker_cpu_affinity`. Other
+/// directives are accepted as they are. For directives defined by several
+/// modules, the arguments only have to suit one of them; see
+/// [`DirectiveSpec::validate_args`] to check against a single one.
+pub fn validate_args(name: &str, args: &[String]) -> Result<(), ArgsError> {
+    Analyzer::new().validate_args(name, args)
+}
+
+/// Checks a directive found in `ctx` at `filename:line` as the parser does
+/// with [`strict`](ParseOptions::strict),
+/// [`check_ctx`](ParseOptions::check_ctx) or
+/// [`check_args`](ParseOptions::check_args), for directives not read from
+/// a file. Nothing is checked unless one of them is set, and whether a
+/// block directive has one is not checked.
+pub fn analyze<S: AsRef<str>>(
+    filename: &str,
+    directive: &str,
+    args: &[String],
+    ctx: &[S],
+    line: usize,
+    options: &ParseOptions,
+) -> Result<(), ParseError> {
+    let stmt = Directive {
+        args: args.to_vec(),
+        ..Directive::new(directive, line)
+    };
+    analyze_stmt(&stmt, ctx, None, options).map_err(|e| e.in_file(filename))
+}
+
+// the parse error for args the table refuses, pointing at the arg at fault
+fn args_error<T>(stmt: &Directive<T>, e: ArgsError) -> ParseError {
+    let error = ParseError::new(e.message, stmt.line);
+    match e.arg_index {
+        Some(index) => error.at_arg(index, stmt.arg_spans.get(index).cloned()),
+        None => error,
+    }
+}
+
+// check a parsed directive against the table, as the parser does for every
+// directive outside args-only blocks; `opens_block` tells whether it was
+// followed by '{', when known
+pub(crate) fn analyze_stmt<S: AsRef<str>, T: AsRef<str>>(
+    stmt: &Directive<T>,
+    ctx: &[S],
+    opens_block: Option<bool>,
+    options: &ParseOptions,
+) -> Result<(), ParseError> {
+    let check_ctx = options.strict || options.check_ctx;
+    let check_args = options.strict || options.check_args;
+    // what a template fills in is not known until it is rendered
+    if !(check_ctx || check_args) || stmt.placeholder {
+        return Ok(());
+    }
+    let analyzer = &options.analyzer;
+    let specs = analyzer.directive_specs(stmt.directive.as_ref());
+    if !specs.is_empty() {
+        // blocks nginx does not know, like those of third party modules,
+        // give nothing to check the context against
+        if let ContextId::Unknown(_) = ContextId::from_path(ctx) {
+            return Ok(());
+        }
+        let spec = match specs.iter().find(|d| d.allowed_in(ctx)) {
+            Some(spec) => spec,
+            None if check_ctx => {
+                let what = format!(
+                    "\"{}\" directive is not allowed here",
+                    stmt.directive.as_ref()
+                );
+                return Err(ParseError::new(what, stmt.line));
+            }
+            // out of place, the args only have to suit one definition
+            None => {
+                let mut checked = specs.iter().map(|d| d.check_args(&stmt.args, opens_block));
+                return match checked.find(Result::is_ok) {
+                    Some(_) => Ok(()),
+                    None => {
+                        let e = specs[0].check_args(&stmt.args, opens_block).unwrap_err();
+                        Err(args_error(stmt, e))
+                    }
+                };
+            }
+        };
+        if !check_args {
+            return Ok(());
+        }
+        spec.check_args(&stmt.args, opens_block)
+            .map_err(|e| args_error(stmt, e))?;
+        // patterns nginx takes but the regex crate lacks features for are
+        // left to lint
+        #[cfg(feature = "regex")]
+        for (i, start, case_insensitive) in directive_regexes(stmt) {
+            match options
+                .regex_cache
+                .get(&stmt.args[i].as_ref()[start..], case_insensitive)
+            {
+                Err(e) if !e.unsupported => return Err(ParseError::new(e.to_string(), stmt.line)),
+                _ => {}
+            }
+        }
+        return Ok(());
+    }
+    if !options.strict {
+        return Ok(());
+    }
+    let mut what = format!("unknown directive \"{}\"", stmt.directive.as_ref());
+    if options.suggest_directives {
+        if let Some(name) = analyzer.suggest_directive(stmt.directive.as_ref()) {
+            what.push_str(&format!(", did you mean \"{}\"?", name));
+        }
+    }
+    Err(ParseError::new(what, stmt.line))
+}
+
+// the methods an upstream picks its servers by, of which it takes one
+const BALANCING_METHODS: [&str; 5] = ["hash", "ip_hash", "least_conn", "least_time", "random"];
+
+// check the children of a block together once it is parsed, in strict mode
+pub(crate) fn analyze_block<T: AsRef<str>>(
+    stmt: &Directive<T>,
+    options: &ParseOptions,
+) -> Result<(), ParseError> {
+    if !options.strict || stmt.directive.as_ref() != "upstream" {
+        return Ok(());
+    }
+    let mut methods = stmt
+        .block
+        .iter()
+        .flatten()
+        .filter(|d| BALANCING_METHODS.contains(&d.directive.as_ref()));
+    let (Some(first), Some(second)) = (methods.next(), methods.next()) else {
+        return Ok(());
+    };
+    let what = format!(
+        "load balancing method redefined, \"{}\" after \"{}\" in line {}",
+        second.directive.as_ref(),
+        first.directive.as_ref(),
+        first.line
+    );
+    Err(ParseError::new(what, second.line))
+}
+
+// check the number of tokens of an entry of an args-only block, in strict
+// mode like the directives
+pub(crate) fn analyze_entry<S: AsRef<str>, T: AsRef<str>>(
+    stmt: &Directive<T>,
+    ctx: &[S],
+    options: &ParseOptions,
+) -> Result<(), ParseError> {
+    if !options.strict || stmt.placeholder {
+        return Ok(());
+    }
+    let Some(block) = ctx.last() else {
+        return Ok(());
+    };
+    let Some(shape) = ENTRY_SHAPES.iter().find(|s| s.block == block.as_ref()) else {
+        return Ok(());
+    };
+    let tokens = 1 + stmt.args.len();
+    let (min, max) = shape.tokens;
+    let special = shape
+        .special
+        .iter()
+        .any(|&(word, n)| word == stmt.directive.as_ref() && n == tokens);
+    if (min..=max).contains(&tokens) || special {
+        return Ok(());
+    }
+    Err(ParseError::new(shape.error, stmt.line))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::parse_source;
+    use crate::span::Span;
+
+    #[test]
+    fn test_directive_table() {
+        assert!(DIRECTIVES.windows(2).all(|w| w[0].name <= w[1].name));
+        assert!(ARG_VALIDATORS
+            .windows(2)
+            .all(|w| (w[0].0, w[0].1) < (w[1].0, w[1].1)));
+        for d in DIRECTIVES {
+            assert!(d.contexts().next().is_some(), "{} has no context", d.name);
+            assert!(d.arg_kinds().next().is_some(), "{} has no args", d.name);
+        }
+
+        assert_eq!(directive_specs("no_such_directive"), []);
+        let server: Vec<&str> = directive_specs("server").iter().map(|d| d.module).collect();
+        assert_eq!(
+            server,
+            [
+                "http_core",
+                "http_upstream",
+                "stream_core",
+                "stream_upstream",
+                "mail_core"
+            ]
+        );
+
+        let [worker_connections] = directive_specs("worker_connections") else {
+            panic!("worker_connections should be defined once");
+        };
+        assert_eq!(
+            worker_connections.contexts().collect::<Vec<_>>(),
+            [["events"]]
+        );
+        assert_eq!(
+            worker_connections.arg_kinds().collect::<Vec<_>>(),
+            ["take1"]
+        );
+        assert_eq!(worker_connections.default, Some("512"));
+
+        let http2 = directive_specs("http2_push")[0];
+        assert!(http2.versions.contains(NginxVersion::V1_24));
+        assert!(!http2.versions.contains(NginxVersion::V1_26));
+
+        let listen = find_directive_spec("listen", &["http", "server"]).unwrap();
+        let deprecation = listen.deprecated.unwrap();
+        assert_eq!(deprecation.arg, Some("http2"));
+        assert_eq!(deprecation.since, NginxVersion::V1_25_1);
+        let stmt = |args: &[&str]| Directive {
+            args: args.iter().map(|a| a.to_string()).collect(),
+            ..Directive::new("listen", 1)
+        };
+        assert!(deprecation.applies_to(&stmt(&["443", "ssl", "http2"])));
+        assert!(!deprecation.applies_to(&stmt(&["443", "ssl"])));
+        for (name, order_sensitive) in [("allow", true), ("set", true), ("root", false)] {
+            let specs = directive_specs(name);
+            assert!(
+                specs.iter().all(|s| s.order_sensitive == order_sensitive),
+                "{}",
+                name
+            );
+        }
+        assert!(directive_specs("ssl").iter().all(|s| s
+            .deprecated
+            .is_some_and(|d| d.arg.is_none() && d.since == NginxVersion::new(1, 15, 0))));
+    }
+
+    #[test]
+    fn test_directive_table_revision() {
+        use sha2::{Digest, Sha256};
+
+        // changing the tables means bumping DIRECTIVE_TABLE_REVISION and
+        // this hash
+        let mut hasher = Sha256::new();
+        hasher.update(format!("{:?}", DIRECTIVES));
+        for (name, module, _) in ARG_VALIDATORS {
+            hasher.update(format!("{} {:?}\n", name, module));
+        }
+        let hex: String = hasher
+            .finalize()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+        assert_eq!(
+            (DIRECTIVE_TABLE_REVISION, hex.as_str()),
+            (
+                6,
+                "722b6c1a3b5fe20835c10c37b90ad2c929e03678a0b3728930fb40b4339f5ad6"
+            )
+        );
+    }
+
+    #[test]
+    fn test_suggest_directive() {
+        assert_eq!(suggest_directive("Listen"), Some("listen"));
+        assert_eq!(suggest_directive("PROXY_PASS"), Some("proxy_pass"));
+        assert_eq!(suggest_directive("porxy_pass"), Some("proxy_pass"));
+        assert_eq!(suggest_directive("server_nmae"), Some("server_name"));
+        assert_eq!(
+            suggest_directive("worker_connection"),
+            Some("worker_connections")
+        );
+        assert_eq!(suggest_directive("frobnicate"), None);
+        assert_eq!(suggest_directive("usr"), Some("use"));
+        assert_eq!(suggest_directive("xyz"), None);
+
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("abcd", "acbd"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+    }
+
+    #[test]
+    fn test_analyze_unknown_directives() {
+        let source = r#"events {
+    worker_connections 1024;
+}
+http {
+    server {
+        Listen 80;
+        porxy_pass http://backend;
+        frobnicate on;
+        Location / {
+            return 204;
+        }
+    }
+}
+"#;
+        let errors = |options: ParseOptions| -> Vec<String> {
+            let payload = parse_source(source, &options.catch_errors(true));
+            payload.errors.iter().map(|e| e.to_string()).collect()
+        };
+
+        assert_eq!(errors(ParseOptions::default()), Vec::<String>::new());
+        assert_eq!(
+            errors(ParseOptions::default().strict(true)),
+            [
+                "unknown directive \"Listen\", did you mean \"listen\"? in nginx.conf:6",
+                "unknown directive \"porxy_pass\", did you mean \"proxy_pass\"? in nginx.conf:7",
+                "unknown directive \"frobnicate\" in nginx.conf:8",
+                "unknown directive \"Location\", did you mean \"location\"? in nginx.conf:9",
+            ]
+        );
+        assert_eq!(
+            errors(
+                ParseOptions::default()
+                    .strict(true)
+                    .suggest_directives(false)
+            ),
+            [
+                "unknown directive \"Listen\" in nginx.conf:6",
+                "unknown directive \"porxy_pass\" in nginx.conf:7",
+                "unknown directive \"frobnicate\" in nginx.conf:8",
+                "unknown directive \"Location\" in nginx.conf:9",
+            ]
+        );
+
+        let payload = parse_source(
+            "worker_processes auto;\nerror_log logs/error.log verbose;\n",
+            &ParseOptions::default().strict(true),
+        );
+        assert_eq!(
+            payload.errors[0].to_string(),
+            "invalid error_log level \"verbose\" in nginx.conf:2"
+        );
+        assert_eq!(
+            validate_args("worker_processes", &["0x10".to_string()]),
+            Err(ArgsError::new("invalid value \"0x10\" in \"worker_processes\" directive").at(0))
+        );
+        assert_eq!(validate_args("root", &[]), Ok(()));
+
+        // the block of an unknown directive is skipped along with it
+        let payload = parse_source(
+            source,
+            &ParseOptions::default().strict(true).catch_errors(true),
+        );
+        let names: Vec<&str> = payload
+            .directives()
+            .map(|d| d.directive().directive.as_str())
+            .collect();
+        assert_eq!(names, ["events", "worker_connections", "http", "server"]);
+    }
+
+    #[test]
+    fn test_directive_contexts() {
+        let module = |name, ctx: &[&str]| find_directive_spec(name, ctx).map(|d| d.module);
+        assert_eq!(
+            module("proxy_pass", &["stream", "server"]),
+            Some("stream_proxy")
+        );
+        assert_eq!(
+            module("proxy_pass", &["http", "location"]),
+            Some("http_proxy")
+        );
+        assert_eq!(module("proxy_pass", &["http", "server"]), None);
+        assert_eq!(
+            module("server", &["http", "upstream"]),
+            Some("http_upstream")
+        );
+        assert_eq!(module("server", &["stream"]), Some("stream_core"));
+        assert_eq!(module("log_format", &["stream"]), Some("stream_log"));
+        assert_eq!(module("resolver", &["mail", "server"]), Some("mail_core"));
+        assert_eq!(module("listen", &["custom"]), None);
+
+        let backend = ["backend:12345".to_string()];
+        let url = ["http://backend".to_string()];
+        let stream = find_directive_spec("proxy_pass", &["stream", "server"]).unwrap();
+        let http = find_directive_spec("proxy_pass", &["http", "location"]).unwrap();
+        assert_eq!(stream.validate_args(&backend), Ok(()));
+        assert_eq!(
+            http.validate_args(&backend),
+            Err(ArgsError::new("invalid URL prefix in \"backend:12345\"").at(0))
+        );
+        assert_eq!(http.validate_args(&url), Ok(()));
+        assert_eq!(
+            stream.validate_args(&url),
+            Err(ArgsError::new("invalid port in upstream \"http://backend\"").at(0))
+        );
+        // without a context, any definition will do
+        assert_eq!(validate_args("proxy_pass", &backend), Ok(()));
+        assert_eq!(validate_args("proxy_pass", &url), Ok(()));
+
+        let source = "http {
+    upstream backend {
+        server 127.0.0.1:8080;
+    }
+    server {
+        proxy_pass http://backend;
+        location / {
+            proxy_pass backend:12345;
+        }
+        location /ok {
+            proxy_pass http://backend;
+        }
+    }
+}
+stream {
+    server {
+        listen 12345;
+        proxy_pass backend:12345;
+    }
+    server {
+        proxy_pass http://backend;
+    }
+}
+";
+        let options = ParseOptions::default().strict(true).catch_errors(true);
+        let errors: Vec<String> = parse_source(source, &options)
+            .errors
+            .iter()
+            .map(|e| e.to_string())
+            .collect();
+        assert_eq!(
+            errors,
+            [
+                "\"proxy_pass\" directive is not allowed here in nginx.conf:6",
+                "invalid URL prefix in \"backend:12345\" in nginx.conf:8",
+                "invalid port in upstream \"http://backend\" in nginx.conf:21",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_analyze_check_ctx() {
+        let source = "worker_connections 1024;
+listen 80;
+http {
+    frobnicate on;
+    server {
+        listen 80;
+    }
+}
+";
+        let options = ParseOptions::default().check_ctx(true).catch_errors(true);
+        let payload = parse_source(source, &options);
+        let errors: Vec<String> = payload.errors.iter().map(|e| e.to_string()).collect();
+        // unknown directives are only reported in strict mode
+        assert_eq!(
+            errors,
+            [
+                "\"worker_connections\" directive is not allowed here in nginx.conf:1",
+                "\"listen\" directive is not allowed here in nginx.conf:2",
+            ]
+        );
+
+        let options = ParseOptions::default().check_ctx(true);
+        let error = analyze(
+            "/etc/nginx/nginx.conf",
+            "worker_connections",
+            &["1024".to_string()],
+            &["http"],
+            2,
+            &options,
+        )
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "\"worker_connections\" directive is not allowed here in /etc/nginx/nginx.conf:2"
+        );
+        let args = ["1024".to_string()];
+        assert_eq!(
+            analyze(
+                "nginx.conf",
+                "worker_connections",
+                &args,
+                &["events"],
+                2,
+                &options
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            analyze("nginx.conf", "frobnicate", &[], &["http"], 2, &options),
+            Ok(())
+        );
+        let strict = ParseOptions::default().strict(true);
+        assert!(analyze("nginx.conf", "frobnicate", &[], &["http"], 2, &strict).is_err());
+        // nothing is checked by default
+        let default = ParseOptions::default();
+        assert_eq!(
+            analyze("nginx.conf", "listen", &[], &[] as &[&str], 1, &default),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_analyze_check_args() {
+        // the directive, its context, args nginx takes, and args it refuses
+        // with the error
+        type Args = &'static [&'static str];
+        let cases: [(&str, Args, &[Args], Args, &str); 15] = [
+            (
+                "worker_connections",
+                &["events"],
+                &[&["1024"]],
+                &[],
+                "invalid number of arguments in \"worker_connections\" directive",
+            ),
+            (
+                "worker_connections",
+                &["events"],
+                &[],
+                &["1024", "2048"],
+                "invalid number of arguments in \"worker_connections\" directive",
+            ),
+            (
+                "server_name",
+                &["http", "server"],
+                &[&["a"], &["a", "b", "c"]],
+                &[],
+                "invalid number of arguments in \"server_name\" directive",
+            ),
+            (
+                "sendfile",
+                &["http"],
+                &[&["on"], &["OFF"]],
+                &["maybe"],
+                "invalid value \"maybe\" in \"sendfile\" directive, it must be \"on\" or \"off\"",
+            ),
+            (
+                "gzip",
+                &["http"],
+                &[&["off"]],
+                &["on", "off"],
+                "invalid number of arguments in \"gzip\" directive",
+            ),
+            (
+                "ip_hash",
+                &["http", "upstream"],
+                &[&[]],
+                &["on"],
+                "invalid number of arguments in \"ip_hash\" directive",
+            ),
+            (
+                "server",
+                &["http", "upstream"],
+                &[&["10.0.0.1"], &["10.0.0.1", "weight=2", "backup"]],
+                &[],
+                "invalid number of arguments in \"server\" directive",
+            ),
+            (
+                "return",
+                &["http", "server"],
+                &[&["204"], &["301", "/"]],
+                &["301", "/", "x"],
+                "invalid number of arguments in \"return\" directive",
+            ),
+            (
+                "root",
+                &["http", "location"],
+                &[&["/srv"]],
+                &[],
+                "invalid number of arguments in \"root\" directive",
+            ),
+            (
+                "proxy_set_header",
+                &["http", "location"],
+                &[&["Host", "$host"]],
+                &["Host"],
+                "invalid number of arguments in \"proxy_set_header\" directive",
+            ),
+            (
+                "log_format",
+                &["http"],
+                &[
+                    &["main", "$remote_addr"],
+                    &["main", "a", "b", "c", "d", "e", "f", "g", "h"],
+                ],
+                &["main"],
+                "invalid number of arguments in \"log_format\" directive",
+            ),
+            (
+                "add_header",
+                &["http", "server"],
+                &[&["X-A", "1"], &["X-A", "1", "always"]],
+                &["X-A"],
+                "invalid number of arguments in \"add_header\" directive",
+            ),
+            (
+                "error_log",
+                &[],
+                &[&["logs/error.log"], &["stderr", "warn"]],
+                &["stderr", "loud"],
+                "invalid error_log level \"loud\"",
+            ),
+            // values are checked as nginx uses them, with escapes resolved
+            (
+                "sendfile",
+                &["http"],
+                &[],
+                &[r#"\"on\""#],
+                "invalid value \"\"on\"\" in \"sendfile\" directive, it must be \"on\" or \"off\"",
+            ),
+            (
+                "error_log",
+                &[],
+                &[],
+                &["stderr", r"w\\arn"],
+                r#"invalid error_log level "w\arn""#,
+            ),
+        ];
+
+        let options = ParseOptions::default().check_args(true);
+        let owned = |args: &[&str]| -> Vec<String> { args.iter().map(|a| a.to_string()).collect() };
+        for (name, ctx, valid, invalid, error) in cases {
+            for args in valid {
+                assert_eq!(
+                    analyze("nginx.conf", name, &owned(args), ctx, 1, &options),
+                    Ok(()),
+                    "{} {:?}",
+                    name,
+                    args
+                );
+            }
+            let e = analyze("nginx.conf", name, &owned(invalid), ctx, 1, &options).unwrap_err();
+            assert_eq!(e.what, error, "{} {:?}", name, invalid);
+        }
+
+        // contexts and unknown directives are left to the other checks
+        assert_eq!(
+            analyze(
+                "nginx.conf",
+                "worker_connections",
+                &[],
+                &["http"],
+                1,
+                &options
+            )
+            .unwrap_err()
+            .what,
+            "invalid number of arguments in \"worker_connections\" directive"
+        );
+        assert_eq!(
+            analyze(
+                "nginx.conf",
+                "worker_connections",
+                &owned(&["1"]),
+                &["http"],
+                1,
+                &options
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            analyze("nginx.conf", "frobnicate", &[], &["http"], 1, &options),
+            Ok(())
+        );
+
+        let source = "events;
+http {
+    sendfile yes;
+    server_tokens off {
+    }
+    server {
+        listen 80;
+    }
+}
+";
+        let payload = parse_source(source, &options.clone().catch_errors(true));
+        let errors: Vec<String> = payload.errors.iter().map(|e| e.to_string()).collect();
+        assert_eq!(
+            errors,
+            [
+                "directive \"events\" has no opening \"{\" in nginx.conf:1",
+                "invalid value \"yes\" in \"sendfile\" directive, it must be \"on\" or \"off\" in nginx.conf:3",
+                "directive \"server_tokens\" is not terminated by ';' in nginx.conf:4",
+            ]
+        );
+        assert_eq!(payload.find("server").len(), 1);
+        // errors about one arg point at it
+        let at: Vec<_> = payload.errors.iter().map(|e| e.arg_index).collect();
+        assert_eq!(at, [None, Some(0), None]);
+
+        let source = "error_log logs/error.log loud;\n";
+        let payload = parse_source(source, &options.clone().catch_errors(true).arg_spans(true));
+        let e = &payload.errors[0];
+        assert_eq!(e.arg_index, Some(1));
+        assert_eq!(
+            e.span,
+            Some(Span {
+                line: 1,
+                start: 25,
+                end: 29
+            })
+        );
+    }
+
+    #[test]
+    fn test_analyze_upstreams() {
+        let errors = |source: &str| -> Vec<String> {
+            let options = ParseOptions::default().strict(true).catch_errors(true);
+            let payload = parse_source(source, &options);
+            payload.errors.iter().map(|e| e.to_string()).collect()
+        };
+
+        let valid = "http {
+    upstream backend {
+        least_conn;
+        zone backend 64k;
+        keepalive 16;
+        server 10.0.0.1;
+    }
+    upstream shared {
+        zone backend;
+        hash $request_uri consistent;
+        server 10.0.0.2;
+    }
+}
+stream {
+    upstream dns {
+        random two least_conn;
+        server 10.0.0.3:53;
+    }
+}
+";
+        assert_eq!(errors(valid), Vec::<String>::new());
+
+        let source = "http {
+    upstream backend {
+        least_conn;
+        server 10.0.0.1;
+        hash $remote_addr;
+        ip_hash;
+    }
+}
+";
+        assert_eq!(
+            errors(source),
+            ["load balancing method redefined, \"hash\" after \"least_conn\" in line 3 in nginx.conf:5"]
+        );
+        // only checked in strict mode
+        let payload = parse_source(source, &ParseOptions::default());
+        assert_eq!(payload.errors, []);
+
+        let cases = [
+            ("hash;", "invalid number of arguments in \"hash\" directive"),
+            ("hash $a ring;", "invalid parameter \"ring\""),
+            (
+                "keepalive;",
+                "invalid number of arguments in \"keepalive\" directive",
+            ),
+            (
+                "keepalive 0;",
+                "invalid value \"0\" in \"keepalive\" directive",
+            ),
+            (
+                "keepalive many;",
+                "invalid value \"many\" in \"keepalive\" directive",
+            ),
+            ("random three;", "invalid parameter \"three\""),
+            ("random two ip_hash;", "invalid parameter \"ip_hash\""),
+            ("zone;", "invalid number of arguments in \"zone\" directive"),
+            ("zone backend big;", "invalid zone size \"big\""),
+        ];
+        for (directive, error) in cases {
+            let source = format!(
+                "http {{\n    upstream backend {{\n        {}\n    }}\n}}\n",
+                directive
+            );
+            assert_eq!(
+                errors(&source),
+                [format!("{} in nginx.conf:3", error)],
+                "{}",
+                directive
+            );
+        }
+    }
+
+    #[test]
+    fn test_analyze_entries() {
+        // the block, entries nginx takes, and entries it refuses with the error
+        let cases: [(&str, &[&str], &[&str], &str); 5] = [
+            (
+                "http {\n    charset_map koi8-r utf-8 {\n",
+                &["C0 D18E;"],
+                &["C0;", "C0 D18E D18F;"],
+                "invalid parameters number",
+            ),
+            (
+                "http {\n    geo $geo {\n",
+                &[
+                    "ranges;",
+                    "proxy_recursive;",
+                    "default 0;",
+                    "delete 10.0.0.0/8;",
+                ],
+                &["10.0.0.0/8;", "default;", "10.0.0.0/8 1 2;"],
+                "invalid number of the geo parameters",
+            ),
+            (
+                "http {\n    map $host $m {\n",
+                &[
+                    "hostnames;",
+                    "volatile;",
+                    "default 0;",
+                    "include maps.conf;",
+                    "foo bar;",
+                ],
+                &["foo;", "default;", "foo bar baz;"],
+                "invalid number of the map parameters",
+            ),
+            (
+                "http {\n    split_clients $remote_addr $s {\n",
+                &["50% a;", "* b;"],
+                &["50%;", "50% a b;"],
+                "invalid number of parameters",
+            ),
+            (
+                "http {\n    types {\n",
+                &["text/html html htm shtml;", "include mime.types;"],
+                &["text/html;"],
+                "invalid number of parameters",
+            ),
+        ];
+
+        let strict = ParseOptions::default().strict(true).catch_errors(true);
+        for (block, good, bad, error) in cases {
+            for entry in good {
+                let source = format!("{}        {}\n    }}\n}}\n", block, entry);
+                let payload = parse_source(&source, &strict);
+                assert_eq!(payload.errors, [], "{}", entry);
+            }
+            for entry in bad {
+                let source = format!("{}        {}\n    }}\n}}\n", block, entry);
+                let payload = parse_source(&source, &strict);
+                let errors: Vec<_> = payload.errors.iter().map(|e| e.what.as_str()).collect();
+                assert_eq!(errors, [error], "{}", entry);
+                assert_eq!(payload.errors[0].line, Some(3));
+                // entries are only checked in strict mode
+                let payload = parse_source(&source, &ParseOptions::default());
+                assert_eq!(payload.errors, [], "{}", entry);
+            }
+        }
+    }
+
+    #[test]
+    fn test_analyzers_across_threads() {
+        fn shareable<T: Send + Sync + Clone>() {}
+        shareable::<Analyzer>();
+        shareable::<ParseOptions>();
+
+        let source = "http {
+    cache_zone shared 10m;
+    server {
+        location / {
+            auth_backend http://auth;
+        }
+    }
+}
+";
+        let cache = Analyzer::new().with_directives([DirectiveSpec::new(
+            "cache_zone",
+            NGX_HTTP_MAIN_CONF | NGX_CONF_TAKE2,
+            "http_cache_zone",
+        )]);
+        let auth = Analyzer::new().with_directives([DirectiveSpec::new(
+            "auth_backend",
+            NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+            "http_auth_backend",
+        )]);
+        assert_eq!(cache.known_directives().len(), DIRECTIVES.len() + 1);
+        assert!(Analyzer::new().directive_specs("cache_zone").is_empty());
+
+        let errors = |analyzer: &Analyzer| -> Vec<String> {
+            let options = ParseOptions::default()
+                .strict(true)
+                .catch_errors(true)
+                .analyzer(analyzer.clone());
+            let payload = parse_source(source, &options);
+            payload.errors.iter().map(|e| e.to_string()).collect()
+        };
+        std::thread::scope(|scope| {
+            for i in 0..8 {
+                let (analyzer, expected) = match i % 2 {
+                    0 => (&cache, "unknown directive \"auth_backend\" in nginx.conf:5"),
+                    _ => (&auth, "unknown directive \"cache_zone\" in nginx.conf:2"),
+                };
+                scope.spawn(move || {
+                    for _ in 0..50 {
+                        assert_eq!(errors(analyzer), [expected]);
+                    }
+                });
+            }
+        });
+        assert_eq!(
+            errors(&Analyzer::new()),
+            [
+                "unknown directive \"cache_zone\" in nginx.conf:2",
+                "unknown directive \"auth_backend\" in nginx.conf:5",
+            ]
+        );
+    }
+}
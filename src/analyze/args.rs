@@ -0,0 +1,758 @@
+use std::fmt;
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+/// Why the arguments of a directive do not take a form it accepts, with
+/// the argument at fault when a single one is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArgsError {
+    pub message: String,
+    /// Index of the argument at fault, None when the error is about the
+    /// arguments as a whole, like their number.
+    pub arg_index: Option<usize>,
+}
+
+impl ArgsError {
+    pub(crate) fn new(message: impl Into<String>) -> Self {
+        ArgsError {
+            message: message.into(),
+            arg_index: None,
+        }
+    }
+
+    pub(crate) fn at(mut self, index: usize) -> Self {
+        self.arg_index = Some(index);
+        self
+    }
+}
+
+impl fmt::Display for ArgsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for ArgsError {}
+
+// validators for directives whose arguments take forms the arity bits of the
+// directive table cannot describe
+pub(super) type ArgsValidator = fn(&[&str]) -> Result<(), ArgsError>;
+
+// sorted by name, with the module of the definition they apply to or None
+// for all of them
+pub(super) const ARG_VALIDATORS: [(&str, Option<&str>, ArgsValidator); 11] = [
+    ("error_log", None, error_log),
+    ("hash", None, hash),
+    ("keepalive", None, keepalive),
+    ("listen", None, listen),
+    ("proxy_pass", Some("http_proxy"), http_proxy_pass),
+    ("proxy_pass", Some("stream_proxy"), stream_proxy_pass),
+    ("random", None, random),
+    ("resolver", None, resolver),
+    ("worker_cpu_affinity", None, worker_cpu_affinity),
+    ("worker_processes", None, worker_processes),
+    ("zone", None, zone),
+];
+
+// the number of tokens the entries of args-only blocks take, the words
+// taking another number, and the error nginx gives otherwise
+pub(super) struct EntryShape {
+    pub(super) block: &'static str,
+    pub(super) tokens: (usize, usize),
+    pub(super) special: &'static [(&'static str, usize)],
+    pub(super) error: &'static str,
+}
+
+pub(super) const ENTRY_SHAPES: [EntryShape; 5] = [
+    EntryShape {
+        block: "charset_map",
+        tokens: (2, 2),
+        special: &[],
+        error: "invalid parameters number",
+    },
+    // default, delete, include and proxy take a value like the addresses
+    EntryShape {
+        block: "geo",
+        tokens: (2, 2),
+        special: &[("proxy_recursive", 1), ("ranges", 1)],
+        error: "invalid number of the geo parameters",
+    },
+    // default and include take a value like the keys
+    EntryShape {
+        block: "map",
+        tokens: (2, 2),
+        special: &[("hostnames", 1), ("volatile", 1)],
+        error: "invalid number of the map parameters",
+    },
+    EntryShape {
+        block: "split_clients",
+        tokens: (2, 2),
+        special: &[],
+        error: "invalid number of parameters",
+    },
+    // a type without extensions maps nothing
+    EntryShape {
+        block: "types",
+        tokens: (2, usize::MAX),
+        special: &[],
+        error: "invalid number of parameters",
+    },
+];
+
+const LOG_LEVELS: [&str; 8] = [
+    "emerg", "alert", "crit", "error", "warn", "notice", "info", "debug",
+];
+
+// debug levels of single subsystems, available in debug builds
+const DEBUG_LEVELS: [&str; 7] = [
+    "debug_core",
+    "debug_alloc",
+    "debug_mutex",
+    "debug_event",
+    "debug_http",
+    "debug_mail",
+    "debug_stream",
+];
+
+// the most CPUs a mask can name, CPU_SETSIZE on Linux
+const MAX_CPUS: usize = 1024;
+
+// worker_processes auto | number
+fn worker_processes(args: &[&str]) -> Result<(), ArgsError> {
+    match args {
+        [value] if *value == "auto" || parse_number(value).is_some() => Ok(()),
+        [value] => Err(ArgsError::new(format!(
+            "invalid value \"{}\" in \"worker_processes\" directive",
+            value
+        ))
+        .at(0)),
+        _ => Err(invalid_number_of_arguments("worker_processes")),
+    }
+}
+
+// worker_cpu_affinity auto [mask] | mask ...
+fn worker_cpu_affinity(args: &[&str]) -> Result<(), ArgsError> {
+    let masks = match args {
+        [] => return Err(invalid_number_of_arguments("worker_cpu_affinity")),
+        [auto, rest @ ..] if *auto == "auto" => {
+            if rest.len() > 1 {
+                return Err(invalid_number_of_arguments("worker_cpu_affinity"));
+            }
+            rest
+        }
+        _ => args,
+    };
+    let first = args.len() - masks.len();
+    for (i, mask) in masks.iter().enumerate() {
+        if mask.len() > MAX_CPUS {
+            let message = format!(
+                "\"worker_cpu_affinity\" supports up to {} CPUs only",
+                MAX_CPUS
+            );
+            return Err(ArgsError::new(message).at(first + i));
+        }
+        if let Some(c) = mask.chars().find(|c| !matches!(c, '0' | '1')) {
+            let message = format!("invalid character \"{}\" in \"worker_cpu_affinity\"", c);
+            return Err(ArgsError::new(message).at(first + i));
+        }
+    }
+    Ok(())
+}
+
+// error_log file [level]; debug builds also take several debug_* levels
+fn error_log(args: &[&str]) -> Result<(), ArgsError> {
+    let Some((_, levels)) = args.split_first() else {
+        return Err(invalid_number_of_arguments("error_log"));
+    };
+    let mut level = None;
+    for (i, arg) in levels.iter().enumerate() {
+        if DEBUG_LEVELS.contains(arg) {
+            continue;
+        }
+        if !LOG_LEVELS.contains(arg) {
+            let message = format!("invalid error_log level \"{}\"", arg);
+            return Err(ArgsError::new(message).at(i + 1));
+        }
+        if level.replace(arg).is_some() {
+            let message = format!("duplicate error_log level \"{}\"", arg);
+            return Err(ArgsError::new(message).at(i + 1));
+        }
+    }
+    Ok(())
+}
+
+// resolver address ... [valid=time] [ipv4=on|off] [ipv6=on|off]
+// [status_zone=zone]
+fn resolver(args: &[&str]) -> Result<(), ArgsError> {
+    let mut addresses = 0;
+    let (mut ipv4, mut ipv6) = (true, true);
+    for (i, arg) in args.iter().enumerate() {
+        let invalid = |message: String| Err(ArgsError::new(message).at(i));
+        let Some((name, value)) = arg.split_once('=') else {
+            if arg.is_empty() {
+                return invalid("invalid resolver address \"\"".to_string());
+            }
+            addresses += 1;
+            continue;
+        };
+        match name {
+            "valid" if parse_time(value, false).is_some() => {}
+            "valid" => return invalid(format!("invalid time value \"{}\"", value)),
+            "ipv4" | "ipv6" => {
+                let enabled = match value {
+                    "on" => true,
+                    "off" => false,
+                    _ => return invalid(format!("invalid parameter \"{}\"", arg)),
+                };
+                if name == "ipv4" {
+                    ipv4 = enabled;
+                } else {
+                    ipv6 = enabled;
+                }
+            }
+            "status_zone" if !value.is_empty() => {}
+            _ => return invalid(format!("invalid parameter \"{}\"", arg)),
+        }
+    }
+    if addresses == 0 {
+        return Err(invalid_number_of_arguments("resolver"));
+    }
+    if !ipv4 && !ipv6 {
+        return Err(ArgsError::new(
+            "\"ipv4\" and \"ipv6\" cannot both be \"off\"",
+        ));
+    }
+    Ok(())
+}
+
+// proxy_pass URL, where the URL has an http or https scheme
+fn http_proxy_pass(args: &[&str]) -> Result<(), ArgsError> {
+    let [url] = args else {
+        return Err(invalid_number_of_arguments("proxy_pass"));
+    };
+    let scheme = url.split_once("://").map(|(scheme, _)| scheme);
+    // the scheme may come from a variable, as in "$scheme://$host"
+    let valid = url.starts_with('$')
+        || scheme
+            .is_some_and(|s| s.eq_ignore_ascii_case("http") || s.eq_ignore_ascii_case("https"));
+    if !valid {
+        return Err(ArgsError::new(format!("invalid URL prefix in \"{}\"", url)).at(0));
+    }
+    Ok(())
+}
+
+// proxy_pass address, where the address is host:port, unix:path or the
+// name of an upstream block
+fn stream_proxy_pass(args: &[&str]) -> Result<(), ArgsError> {
+    let [address] = args else {
+        return Err(invalid_number_of_arguments("proxy_pass"));
+    };
+    if address.contains('$') || address.starts_with("unix:") {
+        return Ok(());
+    }
+    let port = match address.strip_prefix('[') {
+        Some(rest) => rest
+            .split_once(']')
+            .and_then(|(_, rest)| rest.strip_prefix(':')),
+        None => address.rsplit_once(':').map(|(_, port)| port),
+    };
+    let valid_port =
+        port.is_none_or(|port| parse_number(port).is_some_and(|p| (1..=65535).contains(&p)));
+    if !valid_port {
+        let message = format!("invalid port in upstream \"{}\"", address);
+        return Err(ArgsError::new(message).at(0));
+    }
+    Ok(())
+}
+
+// hash key [consistent]
+fn hash(args: &[&str]) -> Result<(), ArgsError> {
+    match args {
+        [_] => Ok(()),
+        [_, consistent] if *consistent == "consistent" => Ok(()),
+        [_, param] => Err(ArgsError::new(format!("invalid parameter \"{}\"", param)).at(1)),
+        _ => Err(invalid_number_of_arguments("hash")),
+    }
+}
+
+// keepalive connections, more than none
+fn keepalive(args: &[&str]) -> Result<(), ArgsError> {
+    match args {
+        [n] if parse_number(n).is_some_and(|n| n > 0) => Ok(()),
+        [n] => Err(ArgsError::new(format!(
+            "invalid value \"{}\" in \"keepalive\" directive",
+            n
+        ))
+        .at(0)),
+        _ => Err(invalid_number_of_arguments("keepalive")),
+    }
+}
+
+// random [two [least_conn | least_time=header | least_time=last_byte]]
+fn random(args: &[&str]) -> Result<(), ArgsError> {
+    let Some((two, rest)) = args.split_first() else {
+        return Ok(());
+    };
+    if *two != "two" {
+        return Err(ArgsError::new(format!("invalid parameter \"{}\"", two)).at(0));
+    }
+    match rest {
+        [] => Ok(()),
+        [method]
+            if matches!(
+                *method,
+                "least_conn" | "least_time=header" | "least_time=last_byte"
+            ) =>
+        {
+            Ok(())
+        }
+        [method] => Err(ArgsError::new(format!("invalid parameter \"{}\"", method)).at(1)),
+        _ => Err(invalid_number_of_arguments("random")),
+    }
+}
+
+// zone name [size]; upstreams naming a zone without a size share one
+// defined elsewhere
+fn zone(args: &[&str]) -> Result<(), ArgsError> {
+    match args {
+        [name, ..] if name.is_empty() => {
+            Err(ArgsError::new(format!("invalid zone name \"{}\"", name)).at(0))
+        }
+        [_] => Ok(()),
+        [_, size] if parse_size(size).is_some_and(|size| size > 0) => Ok(()),
+        [_, size] => Err(ArgsError::new(format!("invalid zone size \"{}\"", size)).at(1)),
+        _ => Err(invalid_number_of_arguments("zone")),
+    }
+}
+
+// listen address[:port] | port | unix:path [default_server] [params...]
+fn listen(args: &[&str]) -> Result<(), ArgsError> {
+    // past the number of args, what listen refuses is its address
+    ListenSpec::parse(args).map(|_| ()).map_err(|message| {
+        let error = ArgsError::new(message);
+        match args.is_empty() {
+            true => error,
+            false => error.at(0),
+        }
+    })
+}
+
+/// The socket a `listen` directive binds, normalized so that equivalent
+/// forms like `80`, `*:80` and `0.0.0.0:80` compare equal.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct ListenSpec {
+    /// `0.0.0.0` for any IPv4 address, IPv6 addresses in brackets, or the
+    /// lowercased host name or `unix:` path.
+    pub(crate) address: String,
+    /// None for unix sockets.
+    pub(crate) port: Option<u16>,
+    pub(crate) default_server: bool,
+}
+
+impl ListenSpec {
+    pub(crate) fn parse<S: AsRef<str>>(args: &[S]) -> Result<Self, String> {
+        let Some((url, params)) = args.split_first() else {
+            return Err(invalid_number_of_arguments("listen").message);
+        };
+        let url = url.as_ref();
+        let invalid_port = || format!("invalid port in \"{}\" of the \"listen\" directive", url);
+        let port = |p: &str| match parse_number(p) {
+            Some(port @ 1..=65535) => Ok(Some(port as u16)),
+            _ => Err(invalid_port()),
+        };
+        // nginx listens on port 80 when none is given
+        let (address, port) = if url.starts_with("unix:") {
+            (url.to_string(), None)
+        } else if let Some(rest) = url.strip_prefix('[') {
+            let (ip, rest) = rest.split_once(']').ok_or_else(|| {
+                format!(
+                    "invalid IPv6 address in \"{}\" of the \"listen\" directive",
+                    url
+                )
+            })?;
+            let ip: Ipv6Addr = ip.parse().map_err(|_| {
+                format!(
+                    "invalid IPv6 address in \"{}\" of the \"listen\" directive",
+                    url
+                )
+            })?;
+            let port = match rest {
+                "" => Some(80),
+                _ => port(rest.strip_prefix(':').ok_or_else(invalid_port)?)?,
+            };
+            (format!("[{}]", ip), port)
+        } else if parse_number(url).is_some() {
+            ("0.0.0.0".to_string(), port(url)?)
+        } else {
+            let (host, port) = match url.rsplit_once(':') {
+                Some((host, p)) => (host, port(p)?),
+                None => (url, Some(80)),
+            };
+            let address = match host.parse::<Ipv4Addr>() {
+                Ok(ip) => ip.to_string(),
+                Err(_) if host == "*" => Ipv4Addr::UNSPECIFIED.to_string(),
+                Err(_) => host.to_ascii_lowercase(),
+            };
+            (address, port)
+        };
+        Ok(ListenSpec {
+            address,
+            port,
+            default_server: params
+                .iter()
+                .any(|p| matches!(p.as_ref(), "default_server" | "default")),
+        })
+    }
+
+    /// The socket as nginx prints it, like `0.0.0.0:80`.
+    pub(crate) fn socket(&self) -> String {
+        match self.port {
+            Some(port) => format!("{}:{}", self.address, port),
+            None => self.address.clone(),
+        }
+    }
+}
+
+// a flag like "sendfile" set to something else than "on" or "off"
+pub(crate) fn invalid_flag_value(directive: &str, value: &str) -> String {
+    format!(
+        "invalid value \"{}\" in \"{}\" directive, it must be \"on\" or \"off\"",
+        value, directive
+    )
+}
+
+pub(super) fn invalid_number_of_arguments(directive: &str) -> ArgsError {
+    ArgsError::new(format!(
+        "invalid number of arguments in \"{}\" directive",
+        directive
+    ))
+}
+
+// digits only, like ngx_atoi
+pub(crate) fn parse_number(value: &str) -> Option<u64> {
+    if value.is_empty() || !value.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    value.parse().ok()
+}
+
+// a number of bytes with an optional k, m or g suffix, like ngx_parse_size
+pub(crate) fn parse_size(value: &str) -> Option<u64> {
+    let (number, scale) = match value.as_bytes().last()? {
+        b'k' | b'K' => (&value[..value.len() - 1], 1 << 10),
+        b'm' | b'M' => (&value[..value.len() - 1], 1 << 20),
+        b'g' | b'G' => (&value[..value.len() - 1], 1 << 30),
+        _ => (value, 1),
+    };
+    parse_number(number)?.checked_mul(scale)
+}
+
+// a time like "1h 30m" in seconds, or milliseconds unless `is_sec`, like
+// ngx_parse_time: units go from the largest to the smallest, numbers
+// without one are seconds, and milliseconds take neither "y" nor "M"
+pub(crate) fn parse_time(value: &str, is_sec: bool) -> Option<u64> {
+    // the units in the order they must come, with their length in seconds
+    // or milliseconds for "ms"
+    const UNITS: [(&str, u64); 8] = [
+        ("y", 60 * 60 * 24 * 365),
+        ("M", 60 * 60 * 24 * 30),
+        ("w", 60 * 60 * 24 * 7),
+        ("d", 60 * 60 * 24),
+        ("h", 60 * 60),
+        ("m", 60),
+        ("s", 1),
+        ("ms", 1),
+    ];
+    let mut next_unit = if is_sec { 0 } else { 2 };
+    let mut total: u64 = 0;
+    let mut rest = value;
+    let mut valid = false;
+    while !rest.is_empty() {
+        let digits = rest.bytes().take_while(u8::is_ascii_digit).count();
+        let number: u64 = match digits {
+            0 => 0,
+            _ => rest[..digits].parse().ok()?,
+        };
+        valid |= digits > 0;
+        rest = &rest[digits..];
+        if rest.is_empty() {
+            // a trailing number without a unit
+            total = total.checked_add(number.checked_mul(if is_sec { 1 } else { 1000 })?)?;
+            break;
+        }
+
+        // where the unit comes in the order, where the next one may start
+        // and the unit's length; a space ends the number like "s" does but
+        // no unit may follow
+        let (at, next, len) = if rest.starts_with("ms") {
+            (7, 8, 2)
+        } else if rest.starts_with(' ') {
+            (6, UNITS.len() + 1, 1)
+        } else {
+            let at = UNITS[..7]
+                .iter()
+                .position(|(unit, _)| rest.starts_with(unit))?;
+            (at, at + 1, 1)
+        };
+        if at < next_unit || (at == 7 && is_sec) {
+            return None;
+        }
+        next_unit = next;
+        rest = &rest[len..];
+        let scale = match is_sec || at == 7 {
+            true => UNITS[at].1,
+            false => UNITS[at].1 * 1000,
+        };
+        total = total.checked_add(number.checked_mul(scale)?)?;
+        rest = rest.trim_start_matches(' ');
+    }
+    valid.then_some(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(validator: ArgsValidator, cases: &[(&str, Result<(), &str>)]) {
+        for (args, expected) in cases {
+            let args: Vec<&str> = args.split_whitespace().collect();
+            assert_eq!(
+                validator(&args).map_err(|e| e.message),
+                expected.map_err(str::to_string),
+                "{:?}",
+                args
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_values() {
+        assert_eq!(parse_number("0042"), Some(42));
+        assert_eq!(parse_number("+1"), None);
+        assert_eq!(parse_number(""), None);
+
+        assert_eq!(parse_size("512"), Some(512));
+        assert_eq!(parse_size("8k"), Some(8192));
+        assert_eq!(parse_size("1G"), Some(1 << 30));
+        assert_eq!(parse_size("k"), None);
+        assert_eq!(parse_size("1.5m"), None);
+
+        assert_eq!(parse_time("60", false), Some(60_000));
+        assert_eq!(parse_time("1h 30m", true), Some(5400));
+        assert_eq!(parse_time("1m30s", false), Some(90_000));
+        assert_eq!(parse_time("1s 500ms", false), Some(1500));
+        assert_eq!(parse_time("500ms", true), None);
+        assert_eq!(parse_time("1y", false), None);
+        assert_eq!(parse_time("1y", true), Some(31_536_000));
+        assert_eq!(parse_time("30s 1m", true), None);
+        assert_eq!(parse_time("", true), None);
+        assert_eq!(parse_time("10x", true), None);
+        assert_eq!(parse_time("1 2", true), Some(3));
+        assert_eq!(parse_time("1 500ms", false), None);
+    }
+
+    #[test]
+    fn test_worker_processes() {
+        check(
+            worker_processes,
+            &[
+                ("auto", Ok(())),
+                ("1", Ok(())),
+                ("256", Ok(())),
+                (
+                    "-1",
+                    Err("invalid value \"-1\" in \"worker_processes\" directive"),
+                ),
+                (
+                    "Auto",
+                    Err("invalid value \"Auto\" in \"worker_processes\" directive"),
+                ),
+                (
+                    "2 4",
+                    Err("invalid number of arguments in \"worker_processes\" directive"),
+                ),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_worker_cpu_affinity() {
+        let all = "1".repeat(MAX_CPUS);
+        let too_many = format!("1{}", all);
+        let too_many_error = format!(
+            "\"worker_cpu_affinity\" supports up to {} CPUs only",
+            MAX_CPUS
+        );
+        check(
+            worker_cpu_affinity,
+            &[
+                ("auto", Ok(())),
+                ("auto 01010101", Ok(())),
+                ("0001 0010 0100 1000", Ok(())),
+                (&all, Ok(())),
+                ("01 10", Ok(())),
+                (&too_many, Err(&too_many_error)),
+                (
+                    "0001 0012",
+                    Err("invalid character \"2\" in \"worker_cpu_affinity\""),
+                ),
+                (
+                    "auto 01 10",
+                    Err("invalid number of arguments in \"worker_cpu_affinity\" directive"),
+                ),
+                (
+                    "01 auto",
+                    Err("invalid character \"a\" in \"worker_cpu_affinity\""),
+                ),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_error_log() {
+        check(
+            error_log,
+            &[
+                ("logs/error.log", Ok(())),
+                ("stderr emerg", Ok(())),
+                ("syslog:server=unix:/dev/log debug", Ok(())),
+                ("memory:32m debug", Ok(())),
+                ("/var/log/nginx/error.log debug_http debug_core", Ok(())),
+                ("/var/log/nginx/error.log info debug_event", Ok(())),
+                (
+                    "/var/log/nginx/error.log verbose",
+                    Err("invalid error_log level \"verbose\""),
+                ),
+                (
+                    "/var/log/nginx/error.log WARN",
+                    Err("invalid error_log level \"WARN\""),
+                ),
+                (
+                    "/var/log/nginx/error.log warn error",
+                    Err("duplicate error_log level \"error\""),
+                ),
+            ],
+        );
+        assert_eq!(
+            error_log(&[]),
+            Err(invalid_number_of_arguments("error_log"))
+        );
+    }
+
+    #[test]
+    fn test_arg_index() {
+        let arg_index = |validator: ArgsValidator, args: &str| {
+            let args: Vec<&str> = args.split_whitespace().collect();
+            validator(&args).unwrap_err().arg_index
+        };
+        assert_eq!(arg_index(error_log, "logs/error.log warn error"), Some(2));
+        assert_eq!(arg_index(worker_cpu_affinity, "auto 0102"), Some(1));
+        assert_eq!(arg_index(resolver, "8.8.8.8 valid=soon"), Some(1));
+        assert_eq!(arg_index(listen, "*:0 default_server"), Some(0));
+        assert_eq!(arg_index(zone, "backend 0"), Some(1));
+        assert_eq!(arg_index(hash, "$a b c"), None);
+        assert_eq!(arg_index(resolver, "8.8.8.8 ipv4=off ipv6=off"), None);
+    }
+
+    #[test]
+    fn test_resolver() {
+        check(
+            resolver,
+            &[
+                ("127.0.0.1", Ok(())),
+                ("127.0.0.1 [::1]:5353 8.8.8.8:53 valid=30s", Ok(())),
+                ("8.8.8.8 valid=1h ipv6=off", Ok(())),
+                ("8.8.8.8 ipv4=off status_zone=dns", Ok(())),
+                ("8.8.8.8 valid=500ms", Ok(())),
+                (
+                    "valid=30s",
+                    Err("invalid number of arguments in \"resolver\" directive"),
+                ),
+                ("8.8.8.8 valid=soon", Err("invalid time value \"soon\"")),
+                ("8.8.8.8 ipv6=no", Err("invalid parameter \"ipv6=no\"")),
+                (
+                    "8.8.8.8 timeout=5s",
+                    Err("invalid parameter \"timeout=5s\""),
+                ),
+                (
+                    "8.8.8.8 ipv4=off ipv6=off",
+                    Err("\"ipv4\" and \"ipv6\" cannot both be \"off\""),
+                ),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_listen() {
+        check(
+            listen,
+            &[
+                ("80", Ok(())),
+                ("127.0.0.1:8080 default_server reuseport", Ok(())),
+                ("[::]:443 ssl", Ok(())),
+                ("unix:/var/run/nginx.sock", Ok(())),
+                ("localhost", Ok(())),
+                (
+                    "*:0",
+                    Err("invalid port in \"*:0\" of the \"listen\" directive"),
+                ),
+                (
+                    "127.0.0.1:http",
+                    Err("invalid port in \"127.0.0.1:http\" of the \"listen\" directive"),
+                ),
+                (
+                    "[::1",
+                    Err("invalid IPv6 address in \"[::1\" of the \"listen\" directive"),
+                ),
+            ],
+        );
+
+        let socket = |args: &str| {
+            let args: Vec<&str> = args.split_whitespace().collect();
+            ListenSpec::parse(&args).unwrap().socket()
+        };
+        assert_eq!(socket("80"), "0.0.0.0:80");
+        assert_eq!(socket("*:80"), "0.0.0.0:80");
+        assert_eq!(socket("0.0.0.0"), "0.0.0.0:80");
+        assert_eq!(socket("[0:0::0]:8080"), "[::]:8080");
+        assert_eq!(socket("[::1]"), "[::1]:80");
+        assert_eq!(socket("Example.COM:81"), "example.com:81");
+        assert_eq!(socket("unix:/tmp/nginx.sock"), "unix:/tmp/nginx.sock");
+        let listen = |args: &[&str]| ListenSpec::parse(args).unwrap().default_server;
+        assert!(listen(&["80", "default_server"]));
+        assert!(listen(&["80", "ssl", "default"]));
+        assert!(!listen(&["80", "ssl"]));
+    }
+
+    #[test]
+    fn test_proxy_pass() {
+        check(
+            http_proxy_pass,
+            &[
+                ("http://backend", Ok(())),
+                ("HTTPS://127.0.0.1:8443/api/", Ok(())),
+                ("http://unix:/tmp/backend.socket:/uri/", Ok(())),
+                ("$scheme://$host$request_uri", Ok(())),
+                ("backend", Err("invalid URL prefix in \"backend\"")),
+                (
+                    "grpc://backend",
+                    Err("invalid URL prefix in \"grpc://backend\""),
+                ),
+            ],
+        );
+        check(
+            stream_proxy_pass,
+            &[
+                ("backend", Ok(())),
+                ("127.0.0.1:12345", Ok(())),
+                ("[::1]:53", Ok(())),
+                ("unix:/tmp/stream.socket", Ok(())),
+                ("$upstream", Ok(())),
+                (
+                    "backend:http",
+                    Err("invalid port in upstream \"backend:http\""),
+                ),
+                ("[::1]:0", Err("invalid port in upstream \"[::1]:0\"")),
+            ],
+        );
+    }
+}
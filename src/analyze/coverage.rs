@@ -0,0 +1,311 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use super::*;
+use crate::parse::{is_args_only, Payload};
+
+/// How many of the directives of a config an analyzer knows, see
+/// [`Analyzer::coverage`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CoverageReport {
+    /// Directives in the config, without comments, raw directives and the
+    /// entries of blocks like `map` or `types`.
+    pub directives: usize,
+    pub known: usize,
+    /// The directives the analyzer does not know, with how many times each
+    /// is used.
+    pub unknown: BTreeMap<String, usize>,
+}
+
+impl CoverageReport {
+    /// The part of the directives the analyzer knows, 1 for a config
+    /// without any.
+    pub fn fraction(&self) -> f64 {
+        if self.directives == 0 {
+            return 1.0;
+        }
+        self.known as f64 / self.directives as f64
+    }
+}
+
+/// What a directive table lacks compared with a list of directive names,
+/// like the ones nginx documents, see [`Analyzer::table_gaps`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TableGaps {
+    /// Number of names compared, the allowed ones aside.
+    pub compared: usize,
+    /// Names the table has no definition of.
+    pub missing: Vec<String>,
+    /// Directives of the table allowed in no context.
+    pub without_context: Vec<&'static str>,
+}
+
+impl TableGaps {
+    pub fn is_empty(&self) -> bool {
+        self.missing.is_empty() && self.without_context.is_empty()
+    }
+
+    /// The part of the names compared the table has a definition of, 1
+    /// when none were.
+    pub fn fraction(&self) -> f64 {
+        if self.compared == 0 {
+            return 1.0;
+        }
+        (self.compared - self.missing.len()) as f64 / self.compared as f64
+    }
+}
+
+impl fmt::Display for TableGaps {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} of {} directives known ({:.1}%)",
+            self.compared - self.missing.len(),
+            self.compared,
+            self.fraction() * 100.0
+        )?;
+        if !self.missing.is_empty() {
+            write!(f, "\nmissing: {}", self.missing.join(", "))?;
+        }
+        if !self.without_context.is_empty() {
+            write!(f, "\nwithout context: {}", self.without_context.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+impl Analyzer {
+    /// Counts the directives of the payload the analyzer knows, whatever
+    /// the context they are in.
+    pub fn coverage(&self, payload: &Payload) -> CoverageReport {
+        let mut report = CoverageReport::default();
+        for d in payload.directives() {
+            let directive = d.directive();
+            if directive.is_comment() || directive.is_raw() || directive.placeholder {
+                continue;
+            }
+            if is_args_only(&d.context().path) {
+                continue;
+            }
+            report.directives += 1;
+            if self.directive_specs(&directive.directive).is_empty() {
+                *report
+                    .unknown
+                    .entry(directive.directive.clone())
+                    .or_default() += 1;
+            } else {
+                report.known += 1;
+            }
+        }
+        report
+    }
+
+    /// Compares the table with the directive names given, leaving out the
+    /// ones in `allowed` that the table is not meant to have.
+    pub fn table_gaps<'a>(
+        &self,
+        names: impl IntoIterator<Item = &'a str>,
+        allowed: &[&str],
+    ) -> TableGaps {
+        let mut gaps = TableGaps::default();
+        for name in names {
+            if allowed.contains(&name) {
+                continue;
+            }
+            gaps.compared += 1;
+            if self.directive_specs(name).is_empty() {
+                gaps.missing.push(name.to_string());
+            }
+        }
+        gaps.without_context = self
+            .known_directives()
+            .iter()
+            .filter(|d| d.contexts().next().is_none())
+            .map(|d| d.name)
+            .collect();
+        gaps.without_context.dedup();
+        gaps
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::{parse_source, ParseOptions};
+
+    // documented directives the table leaves out on purpose: those of the
+    // commercial subscription and of modules built outside nginx
+    const UNSUPPORTED: &[&str] = &[
+        // commercial subscription
+        "api",
+        "auth_jwt",
+        "auth_jwt_claim_set",
+        "auth_jwt_header_set",
+        "auth_jwt_key_cache",
+        "auth_jwt_key_file",
+        "auth_jwt_key_request",
+        "auth_jwt_leeway",
+        "auth_jwt_require",
+        "auth_jwt_type",
+        "f4f",
+        "f4f_buffer_size",
+        "fastcgi_cache_purge",
+        "health_check",
+        "health_check_timeout",
+        "hls",
+        "hls_buffers",
+        "hls_forward_args",
+        "hls_fragment",
+        "hls_mp4_buffer_size",
+        "hls_mp4_max_buffer_size",
+        "internal_redirect",
+        "keyval",
+        "keyval_zone",
+        "least_time",
+        "match",
+        "mp4_limit_rate",
+        "mp4_limit_rate_after",
+        "ntlm",
+        "proxy_cache_purge",
+        "queue",
+        "scgi_cache_purge",
+        "session_log",
+        "session_log_format",
+        "session_log_zone",
+        "state",
+        "status",
+        "status_format",
+        "status_zone",
+        "sticky",
+        "sticky_cookie_insert",
+        "uwsgi_cache_purge",
+        "zone_sync",
+        "zone_sync_buffers",
+        "zone_sync_connect_retry_interval",
+        "zone_sync_connect_timeout",
+        "zone_sync_interval",
+        "zone_sync_recv_buffer_size",
+        "zone_sync_server",
+        "zone_sync_ssl",
+        "zone_sync_timeout",
+        // njs
+        "js_access",
+        "js_body_filter",
+        "js_content",
+        "js_fetch_buffer_size",
+        "js_fetch_ciphers",
+        "js_fetch_max_response_buffer_size",
+        "js_fetch_protocols",
+        "js_fetch_timeout",
+        "js_fetch_trusted_certificate",
+        "js_fetch_verify",
+        "js_fetch_verify_depth",
+        "js_filter",
+        "js_header_filter",
+        "js_import",
+        "js_include",
+        "js_path",
+        "js_preload_object",
+        "js_preread",
+        "js_set",
+        "js_shared_dict_zone",
+        "js_var",
+        // opentelemetry
+        "otel_exporter",
+        "otel_service_name",
+        "otel_span_attr",
+        "otel_span_name",
+        "otel_trace",
+        "otel_trace_context",
+    ];
+
+    fn documented() -> impl Iterator<Item = &'static str> {
+        include_str!("doc_directives.txt")
+            .lines()
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+    }
+
+    #[test]
+    fn test_documented_directives() {
+        let gaps = Analyzer::new().table_gaps(documented(), UNSUPPORTED);
+        assert!(gaps.is_empty(), "{}", gaps);
+        assert_eq!(gaps.compared + UNSUPPORTED.len(), documented().count());
+
+        // the allowlist only holds what the table lacks
+        let supported: Vec<_> = UNSUPPORTED
+            .iter()
+            .filter(|name| !directive_specs(name).is_empty())
+            .collect();
+        assert_eq!(supported, Vec::<&&str>::new());
+    }
+
+    #[test]
+    fn test_table_gaps() {
+        let analyzer = Analyzer {
+            table: Some(
+                vec![
+                    spec("events", NGX_MAIN_CONF | NGX_CONF_BLOCK, "events"),
+                    spec("gzip", NGX_CONF_FLAG, "http_gzip"),
+                    spec("http", NGX_MAIN_CONF | NGX_CONF_BLOCK, "http"),
+                ]
+                .into(),
+            ),
+        };
+        let names = ["events", "gzip", "http", "listen", "server", "api"];
+        let gaps = analyzer.table_gaps(names, &["api"]);
+        assert_eq!(
+            gaps,
+            TableGaps {
+                compared: 5,
+                missing: vec!["listen".to_string(), "server".to_string()],
+                without_context: vec!["gzip"],
+            }
+        );
+        assert_eq!(gaps.fraction(), 0.6);
+        assert_eq!(
+            gaps.to_string(),
+            "3 of 5 directives known (60.0%)
+missing: listen, server
+without context: gzip"
+        );
+        assert_eq!(analyzer.table_gaps([], &[]).fraction(), 1.0);
+    }
+
+    #[test]
+    fn test_coverage() {
+        let source = "http {
+    js_import main.js;
+    map $uri $x {
+        default 0;
+    }
+    server {
+        listen 80;
+        js_content main.hello;
+        # a comment
+    }
+}
+";
+        let options = ParseOptions::default().comments(true);
+        let payload = parse_source(source, &options);
+        let report = Analyzer::new().coverage(&payload);
+        assert_eq!(report.directives, 6);
+        assert_eq!(report.known, 4);
+        assert_eq!(
+            report.unknown,
+            BTreeMap::from([("js_content".to_string(), 1), ("js_import".to_string(), 1)])
+        );
+        assert_eq!(report.fraction(), 4.0 / 6.0);
+
+        let analyzer = Analyzer::new().with_directives([
+            DirectiveSpec::new("js_import", NGX_HTTP_MAIN_CONF | NGX_CONF_TAKE13, "http_js"),
+            DirectiveSpec::new("js_content", NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1, "http_js"),
+        ]);
+        assert_eq!(analyzer.coverage(&payload).fraction(), 1.0);
+        assert_eq!(
+            Analyzer::new()
+                .coverage(&parse_source("", &options))
+                .fraction(),
+            1.0
+        );
+    }
+}
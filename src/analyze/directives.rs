@@ -0,0 +1,4141 @@
+use super::*;
+
+// sorted by name; a name has one entry per module defining it
+pub(crate) static DIRECTIVES: &[DirectiveSpec] = &[
+    spec(
+        "absolute_redirect",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_core",
+    )
+    .since(1, 11, 8)
+    .default("on"),
+    spec("accept_mutex", NGX_EVENT_CONF | NGX_CONF_FLAG, "events").default("off"),
+    spec(
+        "accept_mutex_delay",
+        NGX_EVENT_CONF | NGX_CONF_TAKE1,
+        "events",
+    )
+    .default("500ms"),
+    spec(
+        "access_log",
+        NGX_HTTP_MAIN_CONF
+            | NGX_HTTP_SRV_CONF
+            | NGX_HTTP_LOC_CONF
+            | NGX_HTTP_LIF_CONF
+            | NGX_HTTP_LMT_CONF
+            | NGX_CONF_1MORE,
+        "http_log",
+    )
+    .default("logs/access.log combined"),
+    spec(
+        "access_log",
+        NGX_STREAM_MAIN_CONF | NGX_STREAM_SRV_CONF | NGX_CONF_1MORE,
+        "stream_log",
+    )
+    .since(1, 11, 4)
+    .default("off"),
+    spec(
+        "add_after_body",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_addition",
+    ),
+    spec(
+        "add_before_body",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_addition",
+    ),
+    spec(
+        "add_header",
+        NGX_HTTP_MAIN_CONF
+            | NGX_HTTP_SRV_CONF
+            | NGX_HTTP_LOC_CONF
+            | NGX_HTTP_LIF_CONF
+            | NGX_CONF_TAKE23,
+        "http_headers",
+    )
+    .order_sensitive(),
+    spec(
+        "add_trailer",
+        NGX_HTTP_MAIN_CONF
+            | NGX_HTTP_SRV_CONF
+            | NGX_HTTP_LOC_CONF
+            | NGX_HTTP_LIF_CONF
+            | NGX_CONF_TAKE23,
+        "http_headers",
+    )
+    .since(1, 13, 2),
+    spec(
+        "addition_types",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_1MORE,
+        "http_addition",
+    )
+    .default("text/html"),
+    spec(
+        "aio",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_core",
+    )
+    .default("off"),
+    spec(
+        "aio_write",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_core",
+    )
+    .since(1, 9, 13)
+    .default("off"),
+    spec("alias", NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1, "http_core"),
+    spec(
+        "allow",
+        NGX_HTTP_MAIN_CONF
+            | NGX_HTTP_SRV_CONF
+            | NGX_HTTP_LOC_CONF
+            | NGX_HTTP_LMT_CONF
+            | NGX_CONF_TAKE1,
+        "http_access",
+    )
+    .order_sensitive(),
+    spec(
+        "allow",
+        NGX_STREAM_MAIN_CONF | NGX_STREAM_SRV_CONF | NGX_CONF_TAKE1,
+        "stream_access",
+    )
+    .order_sensitive(),
+    spec(
+        "ancient_browser",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_1MORE,
+        "http_browser",
+    ),
+    spec(
+        "ancient_browser_value",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_browser",
+    )
+    .default("1"),
+    spec(
+        "auth_basic",
+        NGX_HTTP_MAIN_CONF
+            | NGX_HTTP_SRV_CONF
+            | NGX_HTTP_LOC_CONF
+            | NGX_HTTP_LMT_CONF
+            | NGX_CONF_TAKE1,
+        "http_auth_basic",
+    )
+    .default("off"),
+    spec(
+        "auth_basic_user_file",
+        NGX_HTTP_MAIN_CONF
+            | NGX_HTTP_SRV_CONF
+            | NGX_HTTP_LOC_CONF
+            | NGX_HTTP_LMT_CONF
+            | NGX_CONF_TAKE1,
+        "http_auth_basic",
+    ),
+    spec(
+        "auth_delay",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_core",
+    )
+    .since(1, 17, 10)
+    .default("0s"),
+    spec(
+        "auth_http",
+        NGX_MAIL_MAIN_CONF | NGX_MAIL_SRV_CONF | NGX_CONF_TAKE1,
+        "mail_auth_http",
+    ),
+    spec(
+        "auth_http_header",
+        NGX_MAIL_MAIN_CONF | NGX_MAIL_SRV_CONF | NGX_CONF_TAKE2,
+        "mail_auth_http",
+    ),
+    spec(
+        "auth_http_pass_client_cert",
+        NGX_MAIL_MAIN_CONF | NGX_MAIL_SRV_CONF | NGX_CONF_FLAG,
+        "mail_auth_http",
+    )
+    .since(1, 7, 11)
+    .default("off"),
+    spec(
+        "auth_http_timeout",
+        NGX_MAIL_MAIN_CONF | NGX_MAIL_SRV_CONF | NGX_CONF_TAKE1,
+        "mail_auth_http",
+    )
+    .default("60s"),
+    spec(
+        "auth_request",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_auth_request",
+    )
+    .since(1, 5, 4)
+    .default("off"),
+    spec(
+        "auth_request_set",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE2,
+        "http_auth_request",
+    )
+    .since(1, 5, 4),
+    spec(
+        "autoindex",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_autoindex",
+    )
+    .default("off"),
+    spec(
+        "autoindex_exact_size",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_autoindex",
+    )
+    .default("on"),
+    spec(
+        "autoindex_format",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_autoindex",
+    )
+    .since(1, 7, 9)
+    .default("html"),
+    spec(
+        "autoindex_localtime",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_autoindex",
+    )
+    .default("off"),
+    spec(
+        "break",
+        NGX_HTTP_SRV_CONF
+            | NGX_HTTP_LOC_CONF
+            | NGX_HTTP_SIF_CONF
+            | NGX_HTTP_LIF_CONF
+            | NGX_CONF_NOARGS,
+        "http_rewrite",
+    )
+    .order_sensitive(),
+    spec(
+        "charset",
+        NGX_HTTP_MAIN_CONF
+            | NGX_HTTP_SRV_CONF
+            | NGX_HTTP_LOC_CONF
+            | NGX_HTTP_LIF_CONF
+            | NGX_CONF_TAKE1,
+        "http_charset",
+    )
+    .default("off"),
+    spec(
+        "charset_map",
+        NGX_HTTP_MAIN_CONF | NGX_CONF_BLOCK | NGX_CONF_TAKE2,
+        "http_charset",
+    ),
+    spec(
+        "charset_types",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_1MORE,
+        "http_charset",
+    )
+    .default(
+        "text/html text/xml text/plain text/vnd.wap.wml application/javascript application/rss+xml",
+    ),
+    spec(
+        "chunked_transfer_encoding",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_core",
+    )
+    .default("on"),
+    spec(
+        "client_body_buffer_size",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_core",
+    )
+    .default("16k"),
+    spec(
+        "client_body_in_file_only",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_core",
+    )
+    .default("off"),
+    spec(
+        "client_body_in_single_buffer",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_core",
+    )
+    .default("off"),
+    spec(
+        "client_body_temp_path",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1234,
+        "http_core",
+    )
+    .default("client_body_temp"),
+    spec(
+        "client_body_timeout",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_core",
+    )
+    .default("60s"),
+    spec(
+        "client_header_buffer_size",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_CONF_TAKE1,
+        "http_core",
+    )
+    .default("1k"),
+    spec(
+        "client_header_timeout",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_CONF_TAKE1,
+        "http_core",
+    )
+    .default("60s"),
+    spec(
+        "client_max_body_size",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_core",
+    )
+    .default("1m"),
+    spec(
+        "connection_pool_size",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_CONF_TAKE1,
+        "http_core",
+    )
+    .default("256"),
+    spec(
+        "create_full_put_path",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_dav",
+    )
+    .default("off"),
+    spec("daemon", NGX_MAIN_CONF | NGX_CONF_FLAG, "core").default("on"),
+    spec(
+        "dav_access",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE123,
+        "http_dav",
+    )
+    .default("user:rw"),
+    spec(
+        "dav_methods",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_1MORE,
+        "http_dav",
+    )
+    .default("off"),
+    spec(
+        "debug_connection",
+        NGX_EVENT_CONF | NGX_CONF_TAKE1,
+        "events",
+    ),
+    spec("debug_points", NGX_MAIN_CONF | NGX_CONF_TAKE1, "core"),
+    spec(
+        "default_type",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_core",
+    )
+    .default("text/plain"),
+    spec(
+        "deny",
+        NGX_HTTP_MAIN_CONF
+            | NGX_HTTP_SRV_CONF
+            | NGX_HTTP_LOC_CONF
+            | NGX_HTTP_LMT_CONF
+            | NGX_CONF_TAKE1,
+        "http_access",
+    )
+    .order_sensitive(),
+    spec(
+        "deny",
+        NGX_STREAM_MAIN_CONF | NGX_STREAM_SRV_CONF | NGX_CONF_TAKE1,
+        "stream_access",
+    )
+    .order_sensitive(),
+    spec(
+        "directio",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_core",
+    )
+    .default("off"),
+    spec(
+        "directio_alignment",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_core",
+    )
+    .default("512"),
+    spec(
+        "disable_symlinks",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE12,
+        "http_core",
+    )
+    .default("off"),
+    spec(
+        "empty_gif",
+        NGX_HTTP_LOC_CONF | NGX_CONF_NOARGS,
+        "http_empty_gif",
+    ),
+    spec("env", NGX_MAIN_CONF | NGX_CONF_TAKE1, "core").default("TZ"),
+    spec(
+        "error_log",
+        NGX_MAIN_CONF
+            | NGX_HTTP_MAIN_CONF
+            | NGX_HTTP_SRV_CONF
+            | NGX_HTTP_LOC_CONF
+            | NGX_MAIL_MAIN_CONF
+            | NGX_MAIL_SRV_CONF
+            | NGX_STREAM_MAIN_CONF
+            | NGX_STREAM_SRV_CONF
+            | NGX_CONF_1MORE,
+        "core",
+    )
+    .default("logs/error.log error"),
+    spec(
+        "error_page",
+        NGX_HTTP_MAIN_CONF
+            | NGX_HTTP_SRV_CONF
+            | NGX_HTTP_LOC_CONF
+            | NGX_HTTP_LIF_CONF
+            | NGX_CONF_2MORE,
+        "http_core",
+    ),
+    spec(
+        "etag",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_core",
+    )
+    .default("on"),
+    spec(
+        "events",
+        NGX_MAIN_CONF | NGX_CONF_BLOCK | NGX_CONF_NOARGS,
+        "events",
+    ),
+    spec(
+        "expires",
+        NGX_HTTP_MAIN_CONF
+            | NGX_HTTP_SRV_CONF
+            | NGX_HTTP_LOC_CONF
+            | NGX_HTTP_LIF_CONF
+            | NGX_CONF_TAKE12,
+        "http_headers",
+    )
+    .default("off"),
+    spec(
+        "fastcgi_bind",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE12,
+        "http_fastcgi",
+    ),
+    spec(
+        "fastcgi_buffer_size",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_fastcgi",
+    )
+    .default("4k"),
+    spec(
+        "fastcgi_buffering",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_fastcgi",
+    )
+    .default("on"),
+    spec(
+        "fastcgi_buffers",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE2,
+        "http_fastcgi",
+    )
+    .default("8 4k"),
+    spec(
+        "fastcgi_busy_buffers_size",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_fastcgi",
+    )
+    .default("8k"),
+    spec(
+        "fastcgi_cache",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_fastcgi",
+    )
+    .default("off"),
+    spec(
+        "fastcgi_cache_background_update",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_fastcgi",
+    )
+    .since(1, 11, 10)
+    .default("off"),
+    spec(
+        "fastcgi_cache_bypass",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_1MORE,
+        "http_fastcgi",
+    ),
+    spec(
+        "fastcgi_cache_key",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_fastcgi",
+    ),
+    spec(
+        "fastcgi_cache_lock",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_fastcgi",
+    )
+    .default("off"),
+    spec(
+        "fastcgi_cache_lock_age",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_fastcgi",
+    )
+    .default("5s"),
+    spec(
+        "fastcgi_cache_lock_timeout",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_fastcgi",
+    )
+    .default("5s"),
+    spec(
+        "fastcgi_cache_max_range_offset",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_fastcgi",
+    )
+    .since(1, 11, 6),
+    spec(
+        "fastcgi_cache_methods",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_1MORE,
+        "http_fastcgi",
+    )
+    .default("GET HEAD"),
+    spec(
+        "fastcgi_cache_min_uses",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_fastcgi",
+    )
+    .default("1"),
+    spec(
+        "fastcgi_cache_path",
+        NGX_HTTP_MAIN_CONF | NGX_CONF_2MORE,
+        "http_fastcgi",
+    ),
+    spec(
+        "fastcgi_cache_revalidate",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_fastcgi",
+    )
+    .default("off"),
+    spec(
+        "fastcgi_cache_use_stale",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_1MORE,
+        "http_fastcgi",
+    )
+    .default("off"),
+    spec(
+        "fastcgi_cache_valid",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_1MORE,
+        "http_fastcgi",
+    ),
+    spec(
+        "fastcgi_catch_stderr",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_fastcgi",
+    ),
+    spec(
+        "fastcgi_connect_timeout",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_fastcgi",
+    )
+    .default("60s"),
+    spec(
+        "fastcgi_force_ranges",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_fastcgi",
+    )
+    .default("off"),
+    spec(
+        "fastcgi_hide_header",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_fastcgi",
+    ),
+    spec(
+        "fastcgi_ignore_client_abort",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_fastcgi",
+    )
+    .default("off"),
+    spec(
+        "fastcgi_ignore_headers",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_1MORE,
+        "http_fastcgi",
+    ),
+    spec(
+        "fastcgi_index",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_fastcgi",
+    ),
+    spec(
+        "fastcgi_intercept_errors",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_fastcgi",
+    )
+    .default("off"),
+    spec(
+        "fastcgi_keep_conn",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_fastcgi",
+    )
+    .default("off"),
+    spec(
+        "fastcgi_limit_rate",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_fastcgi",
+    )
+    .default("0"),
+    spec(
+        "fastcgi_max_temp_file_size",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_fastcgi",
+    )
+    .default("1024m"),
+    spec(
+        "fastcgi_next_upstream",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_1MORE,
+        "http_fastcgi",
+    )
+    .default("error timeout"),
+    spec(
+        "fastcgi_next_upstream_timeout",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_fastcgi",
+    )
+    .default("0"),
+    spec(
+        "fastcgi_next_upstream_tries",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_fastcgi",
+    )
+    .default("0"),
+    spec(
+        "fastcgi_no_cache",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_1MORE,
+        "http_fastcgi",
+    ),
+    spec(
+        "fastcgi_param",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE23,
+        "http_fastcgi",
+    )
+    .order_sensitive(),
+    spec(
+        "fastcgi_pass",
+        NGX_HTTP_LOC_CONF | NGX_HTTP_LIF_CONF | NGX_CONF_TAKE1,
+        "http_fastcgi",
+    ),
+    spec(
+        "fastcgi_pass_header",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_fastcgi",
+    ),
+    spec(
+        "fastcgi_pass_request_body",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_fastcgi",
+    )
+    .default("on"),
+    spec(
+        "fastcgi_pass_request_headers",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_fastcgi",
+    )
+    .default("on"),
+    spec(
+        "fastcgi_read_timeout",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_fastcgi",
+    )
+    .default("60s"),
+    spec(
+        "fastcgi_request_buffering",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_fastcgi",
+    )
+    .since(1, 7, 11)
+    .default("on"),
+    spec(
+        "fastcgi_send_lowat",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_fastcgi",
+    )
+    .default("0"),
+    spec(
+        "fastcgi_send_timeout",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_fastcgi",
+    )
+    .default("60s"),
+    spec(
+        "fastcgi_socket_keepalive",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_fastcgi",
+    )
+    .since(1, 15, 6)
+    .default("off"),
+    spec(
+        "fastcgi_split_path_info",
+        NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_fastcgi",
+    ),
+    spec(
+        "fastcgi_store",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_fastcgi",
+    )
+    .default("off"),
+    spec(
+        "fastcgi_store_access",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE123,
+        "http_fastcgi",
+    )
+    .default("user:rw"),
+    spec(
+        "fastcgi_temp_file_write_size",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_fastcgi",
+    )
+    .default("8k"),
+    spec(
+        "fastcgi_temp_path",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1234,
+        "http_fastcgi",
+    )
+    .default("fastcgi_temp"),
+    spec("flv", NGX_HTTP_LOC_CONF | NGX_CONF_NOARGS, "http_flv"),
+    spec(
+        "geo",
+        NGX_HTTP_MAIN_CONF | NGX_CONF_BLOCK | NGX_CONF_TAKE12,
+        "http_geo",
+    ),
+    spec(
+        "geo",
+        NGX_STREAM_MAIN_CONF | NGX_CONF_BLOCK | NGX_CONF_TAKE12,
+        "stream_geo",
+    ),
+    spec(
+        "geoip_city",
+        NGX_HTTP_MAIN_CONF | NGX_CONF_TAKE12,
+        "http_geoip",
+    ),
+    spec(
+        "geoip_city",
+        NGX_STREAM_MAIN_CONF | NGX_CONF_TAKE12,
+        "stream_geoip",
+    ),
+    spec(
+        "geoip_country",
+        NGX_HTTP_MAIN_CONF | NGX_CONF_TAKE12,
+        "http_geoip",
+    ),
+    spec(
+        "geoip_country",
+        NGX_STREAM_MAIN_CONF | NGX_CONF_TAKE12,
+        "stream_geoip",
+    ),
+    spec(
+        "geoip_org",
+        NGX_HTTP_MAIN_CONF | NGX_CONF_TAKE12,
+        "http_geoip",
+    )
+    .since(1, 0, 3),
+    spec(
+        "geoip_org",
+        NGX_STREAM_MAIN_CONF | NGX_CONF_TAKE12,
+        "stream_geoip",
+    ),
+    spec(
+        "geoip_proxy",
+        NGX_HTTP_MAIN_CONF | NGX_CONF_TAKE1,
+        "http_geoip",
+    )
+    .since(1, 3, 0),
+    spec(
+        "geoip_proxy_recursive",
+        NGX_HTTP_MAIN_CONF | NGX_CONF_FLAG,
+        "http_geoip",
+    )
+    .since(1, 3, 0)
+    .default("off"),
+    spec(
+        "google_perftools_profiles",
+        NGX_MAIN_CONF | NGX_CONF_TAKE1,
+        "google_perftools",
+    ),
+    spec(
+        "grpc_bind",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE12,
+        "http_grpc",
+    )
+    .since(1, 13, 10),
+    spec(
+        "grpc_buffer_size",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_grpc",
+    )
+    .since(1, 13, 10)
+    .default("4k"),
+    spec(
+        "grpc_connect_timeout",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_grpc",
+    )
+    .since(1, 13, 10)
+    .default("60s"),
+    spec(
+        "grpc_hide_header",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_grpc",
+    )
+    .since(1, 13, 10),
+    spec(
+        "grpc_ignore_headers",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_1MORE,
+        "http_grpc",
+    )
+    .since(1, 13, 10),
+    spec(
+        "grpc_intercept_errors",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_grpc",
+    )
+    .since(1, 13, 10)
+    .default("off"),
+    spec(
+        "grpc_next_upstream",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_1MORE,
+        "http_grpc",
+    )
+    .since(1, 13, 10)
+    .default("error timeout"),
+    spec(
+        "grpc_next_upstream_timeout",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_grpc",
+    )
+    .since(1, 13, 10)
+    .default("0"),
+    spec(
+        "grpc_next_upstream_tries",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_grpc",
+    )
+    .since(1, 13, 10)
+    .default("0"),
+    spec(
+        "grpc_pass",
+        NGX_HTTP_LOC_CONF | NGX_HTTP_LIF_CONF | NGX_CONF_TAKE1,
+        "http_grpc",
+    )
+    .since(1, 13, 10),
+    spec(
+        "grpc_pass_header",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_grpc",
+    )
+    .since(1, 13, 10),
+    spec(
+        "grpc_read_timeout",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_grpc",
+    )
+    .since(1, 13, 10)
+    .default("60s"),
+    spec(
+        "grpc_send_timeout",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_grpc",
+    )
+    .since(1, 13, 10)
+    .default("60s"),
+    spec(
+        "grpc_set_header",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE2,
+        "http_grpc",
+    )
+    .since(1, 13, 10)
+    .order_sensitive(),
+    spec(
+        "grpc_socket_keepalive",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_grpc",
+    )
+    .since(1, 15, 6)
+    .default("off"),
+    spec(
+        "grpc_ssl_certificate",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_grpc",
+    )
+    .since(1, 13, 10),
+    spec(
+        "grpc_ssl_certificate_key",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_grpc",
+    )
+    .since(1, 13, 10),
+    spec(
+        "grpc_ssl_ciphers",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_grpc",
+    )
+    .since(1, 13, 10)
+    .default("DEFAULT"),
+    spec(
+        "grpc_ssl_conf_command",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE2,
+        "http_grpc",
+    )
+    .since(1, 19, 4),
+    spec(
+        "grpc_ssl_crl",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_grpc",
+    )
+    .since(1, 13, 10),
+    spec(
+        "grpc_ssl_name",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_grpc",
+    )
+    .since(1, 13, 10),
+    spec(
+        "grpc_ssl_password_file",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_grpc",
+    )
+    .since(1, 13, 10),
+    spec(
+        "grpc_ssl_protocols",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_1MORE,
+        "http_grpc",
+    )
+    .since(1, 13, 10)
+    .default("TLSv1.2 TLSv1.3"),
+    spec(
+        "grpc_ssl_server_name",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_grpc",
+    )
+    .since(1, 13, 10)
+    .default("off"),
+    spec(
+        "grpc_ssl_session_reuse",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_grpc",
+    )
+    .since(1, 13, 10)
+    .default("on"),
+    spec(
+        "grpc_ssl_trusted_certificate",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_grpc",
+    )
+    .since(1, 13, 10),
+    spec(
+        "grpc_ssl_verify",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_grpc",
+    )
+    .since(1, 13, 10)
+    .default("off"),
+    spec(
+        "grpc_ssl_verify_depth",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_grpc",
+    )
+    .since(1, 13, 10)
+    .default("1"),
+    spec(
+        "gunzip",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_gunzip",
+    )
+    .default("off"),
+    spec(
+        "gunzip_buffers",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE2,
+        "http_gunzip",
+    )
+    .default("32 4k"),
+    spec(
+        "gzip",
+        NGX_HTTP_MAIN_CONF
+            | NGX_HTTP_SRV_CONF
+            | NGX_HTTP_LOC_CONF
+            | NGX_HTTP_LIF_CONF
+            | NGX_CONF_FLAG,
+        "http_gzip",
+    )
+    .default("off"),
+    spec(
+        "gzip_buffers",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE2,
+        "http_gzip",
+    )
+    .default("32 4k"),
+    spec(
+        "gzip_comp_level",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_gzip",
+    )
+    .default("1"),
+    spec(
+        "gzip_disable",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_1MORE,
+        "http_gzip",
+    ),
+    spec(
+        "gzip_http_version",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_gzip",
+    )
+    .default("1.1"),
+    spec(
+        "gzip_min_length",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_gzip",
+    )
+    .default("20"),
+    spec(
+        "gzip_proxied",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_1MORE,
+        "http_gzip",
+    )
+    .default("off"),
+    spec(
+        "gzip_static",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_gzip_static",
+    )
+    .default("off"),
+    spec(
+        "gzip_types",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_1MORE,
+        "http_gzip",
+    )
+    .default("text/html"),
+    spec(
+        "gzip_vary",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_gzip",
+    )
+    .default("off"),
+    spec(
+        "hash",
+        NGX_HTTP_UPS_CONF | NGX_CONF_TAKE12,
+        "http_upstream_hash",
+    )
+    .since(1, 7, 2),
+    spec(
+        "hash",
+        NGX_STREAM_UPS_CONF | NGX_CONF_TAKE12,
+        "stream_upstream_hash",
+    ),
+    spec(
+        "http",
+        NGX_MAIN_CONF | NGX_CONF_BLOCK | NGX_CONF_NOARGS,
+        "http",
+    ),
+    spec(
+        "http2",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_CONF_FLAG,
+        "http_v2",
+    )
+    .since(1, 25, 1)
+    .default("off"),
+    spec(
+        "http2_body_preread_size",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_CONF_TAKE1,
+        "http_v2",
+    )
+    .since(1, 11, 0)
+    .default("64k"),
+    spec(
+        "http2_chunk_size",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_v2",
+    )
+    .default("8k"),
+    spec(
+        "http2_idle_timeout",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_CONF_TAKE1,
+        "http_v2",
+    )
+    .removed(1, 19, 7),
+    spec(
+        "http2_max_concurrent_pushes",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_CONF_TAKE1,
+        "http_v2",
+    )
+    .since(1, 13, 9)
+    .removed(1, 25, 1),
+    spec(
+        "http2_max_concurrent_streams",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_CONF_TAKE1,
+        "http_v2",
+    )
+    .default("128"),
+    spec(
+        "http2_max_field_size",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_CONF_TAKE1,
+        "http_v2",
+    )
+    .removed(1, 19, 7),
+    spec(
+        "http2_max_header_size",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_CONF_TAKE1,
+        "http_v2",
+    )
+    .removed(1, 19, 7),
+    spec(
+        "http2_max_requests",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_CONF_TAKE1,
+        "http_v2",
+    )
+    .since(1, 11, 6)
+    .removed(1, 19, 7),
+    spec(
+        "http2_push",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_v2",
+    )
+    .since(1, 13, 9)
+    .removed(1, 25, 1),
+    spec(
+        "http2_push_preload",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_v2",
+    )
+    .since(1, 13, 9)
+    .removed(1, 25, 1),
+    spec(
+        "http2_recv_buffer_size",
+        NGX_HTTP_MAIN_CONF | NGX_CONF_TAKE1,
+        "http_v2",
+    )
+    .default("256k"),
+    spec(
+        "http2_recv_timeout",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_CONF_TAKE1,
+        "http_v2",
+    )
+    .removed(1, 19, 7),
+    spec(
+        "http3",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_CONF_FLAG,
+        "http_v3",
+    )
+    .since(1, 25, 0)
+    .default("on"),
+    spec(
+        "http3_hq",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_CONF_FLAG,
+        "http_v3",
+    )
+    .since(1, 25, 0)
+    .default("off"),
+    spec(
+        "http3_max_concurrent_streams",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_CONF_TAKE1,
+        "http_v3",
+    )
+    .since(1, 25, 0)
+    .default("128"),
+    spec(
+        "http3_stream_buffer_size",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_CONF_TAKE1,
+        "http_v3",
+    )
+    .since(1, 25, 0)
+    .default("64k"),
+    spec(
+        "if",
+        NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_BLOCK | NGX_CONF_1MORE,
+        "http_rewrite",
+    )
+    .order_sensitive(),
+    spec(
+        "if_modified_since",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_core",
+    )
+    .default("exact"),
+    spec(
+        "ignore_invalid_headers",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_CONF_FLAG,
+        "http_core",
+    )
+    .default("on"),
+    spec(
+        "image_filter",
+        NGX_HTTP_LOC_CONF | NGX_CONF_TAKE123,
+        "http_image_filter",
+    ),
+    spec(
+        "image_filter_buffer",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_image_filter",
+    )
+    .default("1M"),
+    spec(
+        "image_filter_interlace",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_image_filter",
+    )
+    .since(1, 3, 15)
+    .default("off"),
+    spec(
+        "image_filter_jpeg_quality",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_image_filter",
+    )
+    .default("75"),
+    spec(
+        "image_filter_sharpen",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_image_filter",
+    )
+    .default("0"),
+    spec(
+        "image_filter_transparency",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_image_filter",
+    )
+    .default("on"),
+    spec(
+        "image_filter_webp_quality",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_image_filter",
+    )
+    .since(1, 11, 6)
+    .default("80"),
+    spec(
+        "imap_auth",
+        NGX_MAIL_MAIN_CONF | NGX_MAIL_SRV_CONF | NGX_CONF_1MORE,
+        "mail_imap",
+    )
+    .default("plain"),
+    spec(
+        "imap_capabilities",
+        NGX_MAIL_MAIN_CONF | NGX_MAIL_SRV_CONF | NGX_CONF_1MORE,
+        "mail_imap",
+    )
+    .default("IMAP4 IMAP4rev1 UIDPLUS"),
+    spec(
+        "imap_client_buffer",
+        NGX_MAIL_MAIN_CONF | NGX_MAIL_SRV_CONF | NGX_CONF_TAKE1,
+        "mail_imap",
+    )
+    .default("4k"),
+    spec("include", NGX_ANY_CONF | NGX_CONF_TAKE1, "core"),
+    spec(
+        "index",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_1MORE,
+        "http_index",
+    )
+    .default("index.html"),
+    spec("internal", NGX_HTTP_LOC_CONF | NGX_CONF_NOARGS, "http_core"),
+    spec(
+        "ip_hash",
+        NGX_HTTP_UPS_CONF | NGX_CONF_NOARGS,
+        "http_upstream_ip_hash",
+    ),
+    spec(
+        "keepalive",
+        NGX_HTTP_UPS_CONF | NGX_CONF_TAKE1,
+        "http_upstream_keepalive",
+    )
+    .since(1, 1, 4),
+    spec(
+        "keepalive_disable",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE12,
+        "http_core",
+    )
+    .default("msie6"),
+    spec(
+        "keepalive_requests",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_core",
+    )
+    .since(0, 8, 0)
+    .default("1000"),
+    spec(
+        "keepalive_requests",
+        NGX_HTTP_UPS_CONF | NGX_CONF_TAKE1,
+        "http_upstream_keepalive",
+    )
+    .since(1, 15, 3)
+    .default("1000"),
+    spec(
+        "keepalive_time",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_core",
+    )
+    .since(1, 19, 10)
+    .default("1h"),
+    spec(
+        "keepalive_time",
+        NGX_HTTP_UPS_CONF | NGX_CONF_TAKE1,
+        "http_upstream_keepalive",
+    )
+    .since(1, 19, 10)
+    .default("1h"),
+    spec(
+        "keepalive_timeout",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE12,
+        "http_core",
+    )
+    .default("75s"),
+    spec(
+        "keepalive_timeout",
+        NGX_HTTP_UPS_CONF | NGX_CONF_TAKE1,
+        "http_upstream_keepalive",
+    )
+    .since(1, 15, 3)
+    .default("60s"),
+    spec(
+        "large_client_header_buffers",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_CONF_TAKE2,
+        "http_core",
+    )
+    .default("4 8k"),
+    spec(
+        "least_conn",
+        NGX_HTTP_UPS_CONF | NGX_CONF_NOARGS,
+        "http_upstream_least_conn",
+    )
+    .since(1, 3, 1),
+    spec(
+        "least_conn",
+        NGX_STREAM_UPS_CONF | NGX_CONF_NOARGS,
+        "stream_upstream_least_conn",
+    ),
+    spec(
+        "limit_conn",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE2,
+        "http_limit_conn",
+    ),
+    spec(
+        "limit_conn",
+        NGX_STREAM_MAIN_CONF | NGX_STREAM_SRV_CONF | NGX_CONF_TAKE2,
+        "stream_limit_conn",
+    ),
+    spec(
+        "limit_conn_dry_run",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_limit_conn",
+    )
+    .since(1, 17, 6)
+    .default("off"),
+    spec(
+        "limit_conn_dry_run",
+        NGX_STREAM_MAIN_CONF | NGX_STREAM_SRV_CONF | NGX_CONF_FLAG,
+        "stream_limit_conn",
+    )
+    .since(1, 17, 6)
+    .default("off"),
+    spec(
+        "limit_conn_log_level",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_limit_conn",
+    )
+    .since(0, 8, 18)
+    .default("error"),
+    spec(
+        "limit_conn_log_level",
+        NGX_STREAM_MAIN_CONF | NGX_STREAM_SRV_CONF | NGX_CONF_TAKE1,
+        "stream_limit_conn",
+    )
+    .default("error"),
+    spec(
+        "limit_conn_status",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_limit_conn",
+    )
+    .since(1, 3, 15)
+    .default("503"),
+    spec(
+        "limit_conn_zone",
+        NGX_HTTP_MAIN_CONF | NGX_CONF_TAKE2,
+        "http_limit_conn",
+    ),
+    spec(
+        "limit_conn_zone",
+        NGX_STREAM_MAIN_CONF | NGX_CONF_TAKE2,
+        "stream_limit_conn",
+    ),
+    spec(
+        "limit_except",
+        NGX_HTTP_LOC_CONF | NGX_CONF_BLOCK | NGX_CONF_1MORE,
+        "http_core",
+    ),
+    spec(
+        "limit_rate",
+        NGX_HTTP_MAIN_CONF
+            | NGX_HTTP_SRV_CONF
+            | NGX_HTTP_LOC_CONF
+            | NGX_HTTP_LIF_CONF
+            | NGX_CONF_TAKE1,
+        "http_core",
+    )
+    .default("0"),
+    spec(
+        "limit_rate_after",
+        NGX_HTTP_MAIN_CONF
+            | NGX_HTTP_SRV_CONF
+            | NGX_HTTP_LOC_CONF
+            | NGX_HTTP_LIF_CONF
+            | NGX_CONF_TAKE1,
+        "http_core",
+    )
+    .default("0"),
+    spec(
+        "limit_req",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE123,
+        "http_limit_req",
+    ),
+    spec(
+        "limit_req_dry_run",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_limit_req",
+    )
+    .since(1, 17, 1)
+    .default("off"),
+    spec(
+        "limit_req_log_level",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_limit_req",
+    )
+    .since(0, 8, 18)
+    .default("error"),
+    spec(
+        "limit_req_status",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_limit_req",
+    )
+    .since(1, 3, 15)
+    .default("503"),
+    spec(
+        "limit_req_zone",
+        NGX_HTTP_MAIN_CONF | NGX_CONF_TAKE3 | NGX_CONF_TAKE4,
+        "http_limit_req",
+    ),
+    spec(
+        "lingering_close",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_core",
+    )
+    .default("on"),
+    spec(
+        "lingering_time",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_core",
+    )
+    .default("30s"),
+    spec(
+        "lingering_timeout",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_core",
+    )
+    .default("5s"),
+    spec("listen", NGX_HTTP_SRV_CONF | NGX_CONF_1MORE, "http_core")
+        .default("*:80")
+        .deprecated_arg("http2", 1, 25, 1, "the \"http2\" directive"),
+    spec(
+        "listen",
+        NGX_STREAM_SRV_CONF | NGX_CONF_1MORE,
+        "stream_core",
+    ),
+    spec("listen", NGX_MAIL_SRV_CONF | NGX_CONF_1MORE, "mail_core"),
+    spec("load_module", NGX_MAIN_CONF | NGX_CONF_TAKE1, "core").since(1, 9, 11),
+    spec(
+        "location",
+        NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_BLOCK | NGX_CONF_TAKE12,
+        "http_core",
+    ),
+    spec("lock_file", NGX_MAIN_CONF | NGX_CONF_TAKE1, "core").default("logs/nginx.lock"),
+    spec(
+        "log_format",
+        NGX_HTTP_MAIN_CONF | NGX_CONF_2MORE,
+        "http_log",
+    ),
+    spec(
+        "log_format",
+        NGX_STREAM_MAIN_CONF | NGX_CONF_2MORE,
+        "stream_log",
+    )
+    .since(1, 11, 4),
+    spec(
+        "log_not_found",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_core",
+    )
+    .default("on"),
+    spec(
+        "log_subrequest",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_core",
+    )
+    .default("off"),
+    spec(
+        "mail",
+        NGX_MAIN_CONF | NGX_CONF_BLOCK | NGX_CONF_NOARGS,
+        "mail",
+    ),
+    spec(
+        "map",
+        NGX_HTTP_MAIN_CONF | NGX_CONF_BLOCK | NGX_CONF_TAKE2,
+        "http_map",
+    ),
+    spec(
+        "map",
+        NGX_STREAM_MAIN_CONF | NGX_CONF_BLOCK | NGX_CONF_TAKE2,
+        "stream_map",
+    ),
+    spec(
+        "map_hash_bucket_size",
+        NGX_HTTP_MAIN_CONF | NGX_CONF_TAKE1,
+        "http_map",
+    )
+    .default("32|64|128"),
+    spec(
+        "map_hash_bucket_size",
+        NGX_STREAM_MAIN_CONF | NGX_CONF_TAKE1,
+        "stream_map",
+    ),
+    spec(
+        "map_hash_max_size",
+        NGX_HTTP_MAIN_CONF | NGX_CONF_TAKE1,
+        "http_map",
+    )
+    .default("2048"),
+    spec(
+        "map_hash_max_size",
+        NGX_STREAM_MAIN_CONF | NGX_CONF_TAKE1,
+        "stream_map",
+    )
+    .default("2048"),
+    spec("master_process", NGX_MAIN_CONF | NGX_CONF_FLAG, "core").default("on"),
+    spec(
+        "max_errors",
+        NGX_MAIL_MAIN_CONF | NGX_MAIL_SRV_CONF | NGX_CONF_TAKE1,
+        "mail_core",
+    )
+    .since(1, 21, 0)
+    .default("5"),
+    spec(
+        "max_ranges",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_core",
+    )
+    .since(1, 1, 2),
+    spec(
+        "memcached_bind",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE12,
+        "http_memcached",
+    )
+    .since(0, 8, 22),
+    spec(
+        "memcached_buffer_size",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_memcached",
+    )
+    .default("4k"),
+    spec(
+        "memcached_connect_timeout",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_memcached",
+    )
+    .default("60s"),
+    spec(
+        "memcached_gzip_flag",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_memcached",
+    )
+    .since(1, 3, 6),
+    spec(
+        "memcached_next_upstream",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_1MORE,
+        "http_memcached",
+    )
+    .default("error timeout"),
+    spec(
+        "memcached_next_upstream_timeout",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_memcached",
+    )
+    .since(1, 7, 5)
+    .default("0"),
+    spec(
+        "memcached_next_upstream_tries",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_memcached",
+    )
+    .since(1, 7, 5)
+    .default("0"),
+    spec(
+        "memcached_pass",
+        NGX_HTTP_LOC_CONF | NGX_HTTP_LIF_CONF | NGX_CONF_TAKE1,
+        "http_memcached",
+    ),
+    spec(
+        "memcached_read_timeout",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_memcached",
+    )
+    .default("60s"),
+    spec(
+        "memcached_send_timeout",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_memcached",
+    )
+    .default("60s"),
+    spec(
+        "memcached_socket_keepalive",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_memcached",
+    )
+    .since(1, 15, 6)
+    .default("off"),
+    spec(
+        "merge_slashes",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_CONF_FLAG,
+        "http_core",
+    )
+    .default("on"),
+    spec(
+        "min_delete_depth",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_dav",
+    )
+    .default("0"),
+    spec(
+        "mirror",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_mirror",
+    )
+    .since(1, 13, 4)
+    .default("off"),
+    spec(
+        "mirror_request_body",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_mirror",
+    )
+    .since(1, 13, 4)
+    .default("on"),
+    spec(
+        "modern_browser",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE12,
+        "http_browser",
+    ),
+    spec(
+        "modern_browser_value",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_browser",
+    )
+    .default("1"),
+    spec("mp4", NGX_HTTP_LOC_CONF | NGX_CONF_NOARGS, "http_mp4"),
+    spec(
+        "mp4_buffer_size",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_mp4",
+    )
+    .default("512K"),
+    spec(
+        "mp4_max_buffer_size",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_mp4",
+    )
+    .default("10M"),
+    spec(
+        "mp4_start_key_frame",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_mp4",
+    )
+    .since(1, 21, 4)
+    .default("off"),
+    spec(
+        "msie_padding",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_core",
+    )
+    .default("on"),
+    spec(
+        "msie_refresh",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_core",
+    )
+    .default("off"),
+    spec("multi_accept", NGX_EVENT_CONF | NGX_CONF_FLAG, "events").default("off"),
+    spec(
+        "open_file_cache",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE12,
+        "http_core",
+    )
+    .default("off"),
+    spec(
+        "open_file_cache_errors",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_core",
+    )
+    .default("off"),
+    spec(
+        "open_file_cache_min_uses",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_core",
+    )
+    .default("1"),
+    spec(
+        "open_file_cache_valid",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_core",
+    )
+    .default("60s"),
+    spec(
+        "open_log_file_cache",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1234,
+        "http_log",
+    )
+    .default("off"),
+    spec(
+        "open_log_file_cache",
+        NGX_STREAM_MAIN_CONF | NGX_STREAM_SRV_CONF | NGX_CONF_TAKE1234,
+        "stream_log",
+    )
+    .since(1, 11, 4)
+    .default("off"),
+    spec(
+        "output_buffers",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE2,
+        "http_core",
+    )
+    .default("2 32k"),
+    spec(
+        "override_charset",
+        NGX_HTTP_MAIN_CONF
+            | NGX_HTTP_SRV_CONF
+            | NGX_HTTP_LOC_CONF
+            | NGX_HTTP_LIF_CONF
+            | NGX_CONF_FLAG,
+        "http_charset",
+    )
+    .default("off"),
+    spec("pass", NGX_STREAM_SRV_CONF | NGX_CONF_TAKE1, "stream_pass").since(1, 25, 5),
+    spec("pcre_jit", NGX_MAIN_CONF | NGX_CONF_FLAG, "core")
+        .since(1, 1, 12)
+        .default("off"),
+    spec(
+        "perl",
+        NGX_HTTP_LOC_CONF | NGX_HTTP_LMT_CONF | NGX_CONF_TAKE1,
+        "http_perl",
+    ),
+    spec(
+        "perl_modules",
+        NGX_HTTP_MAIN_CONF | NGX_CONF_TAKE1,
+        "http_perl",
+    ),
+    spec(
+        "perl_require",
+        NGX_HTTP_MAIN_CONF | NGX_CONF_TAKE1,
+        "http_perl",
+    ),
+    spec("perl_set", NGX_HTTP_MAIN_CONF | NGX_CONF_TAKE2, "http_perl"),
+    spec("pid", NGX_MAIN_CONF | NGX_CONF_TAKE1, "core").default("logs/nginx.pid"),
+    spec(
+        "pop3_auth",
+        NGX_MAIL_MAIN_CONF | NGX_MAIL_SRV_CONF | NGX_CONF_1MORE,
+        "mail_pop3",
+    )
+    .default("plain"),
+    spec(
+        "pop3_capabilities",
+        NGX_MAIL_MAIN_CONF | NGX_MAIL_SRV_CONF | NGX_CONF_1MORE,
+        "mail_pop3",
+    )
+    .default("TOP USER UIDL"),
+    spec(
+        "port_in_redirect",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_core",
+    )
+    .default("on"),
+    spec(
+        "postpone_output",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_core",
+    )
+    .default("1460"),
+    spec(
+        "preread_buffer_size",
+        NGX_STREAM_MAIN_CONF | NGX_STREAM_SRV_CONF | NGX_CONF_TAKE1,
+        "stream_core",
+    )
+    .since(1, 11, 5)
+    .default("16k"),
+    spec(
+        "preread_timeout",
+        NGX_STREAM_MAIN_CONF | NGX_STREAM_SRV_CONF | NGX_CONF_TAKE1,
+        "stream_core",
+    )
+    .since(1, 11, 5)
+    .default("30s"),
+    spec("protocol", NGX_MAIL_SRV_CONF | NGX_CONF_TAKE1, "mail_core"),
+    spec(
+        "proxy_bind",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE12,
+        "http_proxy",
+    )
+    .since(0, 8, 22),
+    spec(
+        "proxy_bind",
+        NGX_STREAM_MAIN_CONF | NGX_STREAM_SRV_CONF | NGX_CONF_TAKE12,
+        "stream_proxy",
+    )
+    .since(1, 9, 2),
+    spec(
+        "proxy_buffer",
+        NGX_MAIL_MAIN_CONF | NGX_MAIL_SRV_CONF | NGX_CONF_TAKE1,
+        "mail_proxy",
+    )
+    .default("4k"),
+    spec(
+        "proxy_buffer_size",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_proxy",
+    )
+    .default("4k"),
+    spec(
+        "proxy_buffer_size",
+        NGX_STREAM_MAIN_CONF | NGX_STREAM_SRV_CONF | NGX_CONF_TAKE1,
+        "stream_proxy",
+    )
+    .since(1, 9, 4)
+    .default("16k"),
+    spec(
+        "proxy_buffering",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_proxy",
+    )
+    .default("on"),
+    spec(
+        "proxy_buffers",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE2,
+        "http_proxy",
+    )
+    .default("8 4k"),
+    spec(
+        "proxy_busy_buffers_size",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_proxy",
+    )
+    .default("8k"),
+    spec(
+        "proxy_cache",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_proxy",
+    )
+    .default("off"),
+    spec(
+        "proxy_cache_background_update",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_proxy",
+    )
+    .since(1, 11, 10)
+    .default("off"),
+    spec(
+        "proxy_cache_bypass",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_1MORE,
+        "http_proxy",
+    ),
+    spec(
+        "proxy_cache_convert_head",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_proxy",
+    )
+    .since(1, 9, 7)
+    .default("on"),
+    spec(
+        "proxy_cache_key",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_proxy",
+    )
+    .default("$scheme$proxy_host$request_uri"),
+    spec(
+        "proxy_cache_lock",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_proxy",
+    )
+    .since(1, 1, 12)
+    .default("off"),
+    spec(
+        "proxy_cache_lock_age",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_proxy",
+    )
+    .since(1, 7, 8)
+    .default("5s"),
+    spec(
+        "proxy_cache_lock_timeout",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_proxy",
+    )
+    .since(1, 1, 12)
+    .default("5s"),
+    spec(
+        "proxy_cache_max_range_offset",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_proxy",
+    )
+    .since(1, 11, 6),
+    spec(
+        "proxy_cache_methods",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_1MORE,
+        "http_proxy",
+    )
+    .default("GET HEAD"),
+    spec(
+        "proxy_cache_min_uses",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_proxy",
+    )
+    .default("1"),
+    spec(
+        "proxy_cache_path",
+        NGX_HTTP_MAIN_CONF | NGX_CONF_2MORE,
+        "http_proxy",
+    ),
+    spec(
+        "proxy_cache_revalidate",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_proxy",
+    )
+    .since(1, 5, 7)
+    .default("off"),
+    spec(
+        "proxy_cache_use_stale",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_1MORE,
+        "http_proxy",
+    )
+    .default("off"),
+    spec(
+        "proxy_cache_valid",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_1MORE,
+        "http_proxy",
+    ),
+    spec(
+        "proxy_connect_timeout",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_proxy",
+    )
+    .default("60s"),
+    spec(
+        "proxy_connect_timeout",
+        NGX_STREAM_MAIN_CONF | NGX_STREAM_SRV_CONF | NGX_CONF_TAKE1,
+        "stream_proxy",
+    )
+    .default("60s"),
+    spec(
+        "proxy_cookie_domain",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE12,
+        "http_proxy",
+    )
+    .since(1, 1, 15)
+    .default("off"),
+    spec(
+        "proxy_cookie_flags",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1234,
+        "http_proxy",
+    )
+    .since(1, 19, 3)
+    .default("off"),
+    spec(
+        "proxy_cookie_path",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE12,
+        "http_proxy",
+    )
+    .since(1, 1, 15)
+    .default("off"),
+    spec(
+        "proxy_download_rate",
+        NGX_STREAM_MAIN_CONF | NGX_STREAM_SRV_CONF | NGX_CONF_TAKE1,
+        "stream_proxy",
+    )
+    .since(1, 9, 3)
+    .default("0"),
+    spec(
+        "proxy_force_ranges",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_proxy",
+    )
+    .since(1, 7, 7)
+    .default("off"),
+    spec(
+        "proxy_half_close",
+        NGX_STREAM_MAIN_CONF | NGX_STREAM_SRV_CONF | NGX_CONF_FLAG,
+        "stream_proxy",
+    )
+    .since(1, 21, 4)
+    .default("off"),
+    spec(
+        "proxy_headers_hash_bucket_size",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_proxy",
+    )
+    .default("64"),
+    spec(
+        "proxy_headers_hash_max_size",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_proxy",
+    )
+    .default("512"),
+    spec(
+        "proxy_hide_header",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_proxy",
+    ),
+    spec(
+        "proxy_http_version",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_proxy",
+    )
+    .since(1, 1, 4)
+    .default("1.0"),
+    spec(
+        "proxy_ignore_client_abort",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_proxy",
+    )
+    .default("off"),
+    spec(
+        "proxy_ignore_headers",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_1MORE,
+        "http_proxy",
+    ),
+    spec(
+        "proxy_intercept_errors",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_proxy",
+    )
+    .default("off"),
+    spec(
+        "proxy_limit_rate",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_proxy",
+    )
+    .since(1, 7, 7)
+    .default("0"),
+    spec(
+        "proxy_max_temp_file_size",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_proxy",
+    )
+    .default("1024m"),
+    spec(
+        "proxy_method",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_proxy",
+    ),
+    spec(
+        "proxy_next_upstream",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_1MORE,
+        "http_proxy",
+    )
+    .default("error timeout"),
+    spec(
+        "proxy_next_upstream",
+        NGX_STREAM_MAIN_CONF | NGX_STREAM_SRV_CONF | NGX_CONF_FLAG,
+        "stream_proxy",
+    )
+    .default("on"),
+    spec(
+        "proxy_next_upstream_timeout",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_proxy",
+    )
+    .since(1, 7, 5)
+    .default("0"),
+    spec(
+        "proxy_next_upstream_timeout",
+        NGX_STREAM_MAIN_CONF | NGX_STREAM_SRV_CONF | NGX_CONF_TAKE1,
+        "stream_proxy",
+    )
+    .default("0"),
+    spec(
+        "proxy_next_upstream_tries",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_proxy",
+    )
+    .since(1, 7, 5)
+    .default("0"),
+    spec(
+        "proxy_next_upstream_tries",
+        NGX_STREAM_MAIN_CONF | NGX_STREAM_SRV_CONF | NGX_CONF_TAKE1,
+        "stream_proxy",
+    )
+    .default("0"),
+    spec(
+        "proxy_no_cache",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_1MORE,
+        "http_proxy",
+    ),
+    spec(
+        "proxy_pass",
+        NGX_HTTP_LOC_CONF | NGX_HTTP_LIF_CONF | NGX_HTTP_LMT_CONF | NGX_CONF_TAKE1,
+        "http_proxy",
+    ),
+    spec(
+        "proxy_pass",
+        NGX_STREAM_SRV_CONF | NGX_CONF_TAKE1,
+        "stream_proxy",
+    ),
+    spec(
+        "proxy_pass_error_message",
+        NGX_MAIL_MAIN_CONF | NGX_MAIL_SRV_CONF | NGX_CONF_FLAG,
+        "mail_proxy",
+    )
+    .default("off"),
+    spec(
+        "proxy_pass_header",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_proxy",
+    ),
+    spec(
+        "proxy_pass_request_body",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_proxy",
+    )
+    .default("on"),
+    spec(
+        "proxy_pass_request_headers",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_proxy",
+    )
+    .default("on"),
+    spec(
+        "proxy_protocol",
+        NGX_STREAM_MAIN_CONF | NGX_STREAM_SRV_CONF | NGX_CONF_FLAG,
+        "stream_proxy",
+    )
+    .since(1, 9, 2)
+    .default("off"),
+    spec(
+        "proxy_protocol",
+        NGX_MAIL_MAIN_CONF | NGX_MAIL_SRV_CONF | NGX_CONF_FLAG,
+        "mail_proxy",
+    )
+    .since(1, 19, 8)
+    .default("off"),
+    spec(
+        "proxy_protocol_timeout",
+        NGX_STREAM_MAIN_CONF | NGX_STREAM_SRV_CONF | NGX_CONF_TAKE1,
+        "stream_core",
+    )
+    .since(1, 11, 4)
+    .default("30s"),
+    spec(
+        "proxy_read_timeout",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_proxy",
+    )
+    .default("60s"),
+    spec(
+        "proxy_redirect",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE12,
+        "http_proxy",
+    )
+    .default("default"),
+    spec(
+        "proxy_request_buffering",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_proxy",
+    )
+    .since(1, 7, 11)
+    .default("on"),
+    spec(
+        "proxy_requests",
+        NGX_STREAM_MAIN_CONF | NGX_STREAM_SRV_CONF | NGX_CONF_TAKE1,
+        "stream_proxy",
+    )
+    .since(1, 15, 7)
+    .default("0"),
+    spec(
+        "proxy_responses",
+        NGX_STREAM_MAIN_CONF | NGX_STREAM_SRV_CONF | NGX_CONF_TAKE1,
+        "stream_proxy",
+    )
+    .since(1, 9, 13),
+    spec(
+        "proxy_send_lowat",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_proxy",
+    )
+    .default("0"),
+    spec(
+        "proxy_send_timeout",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_proxy",
+    )
+    .default("60s"),
+    spec(
+        "proxy_set_body",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_proxy",
+    ),
+    spec(
+        "proxy_set_header",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE2,
+        "http_proxy",
+    )
+    .default("Host $proxy_host")
+    .order_sensitive(),
+    spec(
+        "proxy_smtp_auth",
+        NGX_MAIL_MAIN_CONF | NGX_MAIL_SRV_CONF | NGX_CONF_FLAG,
+        "mail_proxy",
+    )
+    .since(1, 19, 4)
+    .default("off"),
+    spec(
+        "proxy_socket_keepalive",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_proxy",
+    )
+    .since(1, 15, 6)
+    .default("off"),
+    spec(
+        "proxy_socket_keepalive",
+        NGX_STREAM_MAIN_CONF | NGX_STREAM_SRV_CONF | NGX_CONF_FLAG,
+        "stream_proxy",
+    )
+    .since(1, 15, 6)
+    .default("off"),
+    spec(
+        "proxy_ssl",
+        NGX_STREAM_MAIN_CONF | NGX_STREAM_SRV_CONF | NGX_CONF_FLAG,
+        "stream_proxy",
+    )
+    .default("off"),
+    spec(
+        "proxy_ssl_certificate",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_proxy",
+    )
+    .since(1, 7, 8),
+    spec(
+        "proxy_ssl_certificate",
+        NGX_STREAM_MAIN_CONF | NGX_STREAM_SRV_CONF | NGX_CONF_TAKE1,
+        "stream_proxy",
+    ),
+    spec(
+        "proxy_ssl_certificate_key",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_proxy",
+    )
+    .since(1, 7, 8),
+    spec(
+        "proxy_ssl_certificate_key",
+        NGX_STREAM_MAIN_CONF | NGX_STREAM_SRV_CONF | NGX_CONF_TAKE1,
+        "stream_proxy",
+    ),
+    spec(
+        "proxy_ssl_ciphers",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_proxy",
+    )
+    .since(1, 5, 6)
+    .default("DEFAULT"),
+    spec(
+        "proxy_ssl_ciphers",
+        NGX_STREAM_MAIN_CONF | NGX_STREAM_SRV_CONF | NGX_CONF_TAKE1,
+        "stream_proxy",
+    )
+    .default("DEFAULT"),
+    spec(
+        "proxy_ssl_conf_command",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE2,
+        "http_proxy",
+    )
+    .since(1, 19, 4),
+    spec(
+        "proxy_ssl_conf_command",
+        NGX_STREAM_MAIN_CONF | NGX_STREAM_SRV_CONF | NGX_CONF_TAKE2,
+        "stream_proxy",
+    )
+    .since(1, 19, 4),
+    spec(
+        "proxy_ssl_crl",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_proxy",
+    )
+    .since(1, 7, 0),
+    spec(
+        "proxy_ssl_crl",
+        NGX_STREAM_MAIN_CONF | NGX_STREAM_SRV_CONF | NGX_CONF_TAKE1,
+        "stream_proxy",
+    ),
+    spec(
+        "proxy_ssl_name",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_proxy",
+    )
+    .since(1, 7, 0)
+    .default("$proxy_host"),
+    spec(
+        "proxy_ssl_name",
+        NGX_STREAM_MAIN_CONF | NGX_STREAM_SRV_CONF | NGX_CONF_TAKE1,
+        "stream_proxy",
+    ),
+    spec(
+        "proxy_ssl_password_file",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_proxy",
+    )
+    .since(1, 7, 8),
+    spec(
+        "proxy_ssl_password_file",
+        NGX_STREAM_MAIN_CONF | NGX_STREAM_SRV_CONF | NGX_CONF_TAKE1,
+        "stream_proxy",
+    ),
+    spec(
+        "proxy_ssl_protocols",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_1MORE,
+        "http_proxy",
+    )
+    .since(1, 5, 6)
+    .default("TLSv1.2 TLSv1.3"),
+    spec(
+        "proxy_ssl_protocols",
+        NGX_STREAM_MAIN_CONF | NGX_STREAM_SRV_CONF | NGX_CONF_1MORE,
+        "stream_proxy",
+    )
+    .default("TLSv1.2 TLSv1.3"),
+    spec(
+        "proxy_ssl_server_name",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_proxy",
+    )
+    .since(1, 7, 0)
+    .default("off"),
+    spec(
+        "proxy_ssl_server_name",
+        NGX_STREAM_MAIN_CONF | NGX_STREAM_SRV_CONF | NGX_CONF_FLAG,
+        "stream_proxy",
+    )
+    .default("off"),
+    spec(
+        "proxy_ssl_session_reuse",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_proxy",
+    )
+    .default("on"),
+    spec(
+        "proxy_ssl_session_reuse",
+        NGX_STREAM_MAIN_CONF | NGX_STREAM_SRV_CONF | NGX_CONF_FLAG,
+        "stream_proxy",
+    )
+    .default("on"),
+    spec(
+        "proxy_ssl_trusted_certificate",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_proxy",
+    )
+    .since(1, 7, 0),
+    spec(
+        "proxy_ssl_trusted_certificate",
+        NGX_STREAM_MAIN_CONF | NGX_STREAM_SRV_CONF | NGX_CONF_TAKE1,
+        "stream_proxy",
+    ),
+    spec(
+        "proxy_ssl_verify",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_proxy",
+    )
+    .since(1, 7, 0)
+    .default("off"),
+    spec(
+        "proxy_ssl_verify",
+        NGX_STREAM_MAIN_CONF | NGX_STREAM_SRV_CONF | NGX_CONF_FLAG,
+        "stream_proxy",
+    )
+    .default("off"),
+    spec(
+        "proxy_ssl_verify_depth",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_proxy",
+    )
+    .since(1, 7, 0)
+    .default("1"),
+    spec(
+        "proxy_ssl_verify_depth",
+        NGX_STREAM_MAIN_CONF | NGX_STREAM_SRV_CONF | NGX_CONF_TAKE1,
+        "stream_proxy",
+    )
+    .default("1"),
+    spec(
+        "proxy_store",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_proxy",
+    )
+    .default("off"),
+    spec(
+        "proxy_store_access",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE123,
+        "http_proxy",
+    )
+    .default("user:rw"),
+    spec(
+        "proxy_temp_file_write_size",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_proxy",
+    )
+    .default("8k"),
+    spec(
+        "proxy_temp_path",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1234,
+        "http_proxy",
+    )
+    .default("proxy_temp"),
+    spec(
+        "proxy_timeout",
+        NGX_MAIL_MAIN_CONF | NGX_MAIL_SRV_CONF | NGX_CONF_TAKE1,
+        "mail_proxy",
+    )
+    .default("24h"),
+    spec(
+        "proxy_timeout",
+        NGX_STREAM_MAIN_CONF | NGX_STREAM_SRV_CONF | NGX_CONF_TAKE1,
+        "stream_proxy",
+    )
+    .default("10m"),
+    spec(
+        "proxy_upload_rate",
+        NGX_STREAM_MAIN_CONF | NGX_STREAM_SRV_CONF | NGX_CONF_TAKE1,
+        "stream_proxy",
+    )
+    .since(1, 9, 3)
+    .default("0"),
+    spec(
+        "quic_active_connection_id_limit",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_CONF_TAKE1,
+        "http_v3",
+    )
+    .since(1, 25, 0)
+    .default("2"),
+    spec("quic_bpf", NGX_MAIN_CONF | NGX_CONF_FLAG, "http_v3")
+        .since(1, 25, 0)
+        .default("off"),
+    spec(
+        "quic_gso",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_CONF_FLAG,
+        "http_v3",
+    )
+    .since(1, 25, 0)
+    .default("off"),
+    spec(
+        "quic_host_key",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_CONF_TAKE1,
+        "http_v3",
+    )
+    .since(1, 25, 0),
+    spec(
+        "quic_retry",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_CONF_FLAG,
+        "http_v3",
+    )
+    .since(1, 25, 0)
+    .default("off"),
+    spec(
+        "random",
+        NGX_HTTP_UPS_CONF | NGX_CONF_NOARGS | NGX_CONF_TAKE12,
+        "http_upstream_random",
+    )
+    .since(1, 15, 1),
+    spec(
+        "random",
+        NGX_STREAM_UPS_CONF | NGX_CONF_NOARGS | NGX_CONF_TAKE12,
+        "stream_upstream_random",
+    )
+    .since(1, 15, 1),
+    spec(
+        "random_index",
+        NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_random_index",
+    )
+    .default("off"),
+    spec(
+        "read_ahead",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_core",
+    )
+    .default("0"),
+    spec(
+        "real_ip_header",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_realip",
+    )
+    .default("X-Real-IP"),
+    spec(
+        "real_ip_recursive",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_realip",
+    )
+    .since(1, 3, 0)
+    .default("off"),
+    spec(
+        "recursive_error_pages",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_core",
+    )
+    .default("off"),
+    spec(
+        "referer_hash_bucket_size",
+        NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_referer",
+    )
+    .since(1, 0, 5)
+    .default("64"),
+    spec(
+        "referer_hash_max_size",
+        NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_referer",
+    )
+    .since(1, 0, 5)
+    .default("2048"),
+    spec(
+        "request_pool_size",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_CONF_TAKE1,
+        "http_core",
+    )
+    .default("4k"),
+    spec(
+        "reset_timedout_connection",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_core",
+    )
+    .default("off"),
+    spec(
+        "resolver",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_1MORE,
+        "http_core",
+    ),
+    spec(
+        "resolver",
+        NGX_STREAM_MAIN_CONF | NGX_STREAM_SRV_CONF | NGX_CONF_1MORE,
+        "stream_core",
+    )
+    .since(1, 11, 3),
+    spec(
+        "resolver",
+        NGX_MAIL_MAIN_CONF | NGX_MAIL_SRV_CONF | NGX_CONF_1MORE,
+        "mail_core",
+    ),
+    spec(
+        "resolver_timeout",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_core",
+    )
+    .default("30s"),
+    spec(
+        "resolver_timeout",
+        NGX_STREAM_MAIN_CONF | NGX_STREAM_SRV_CONF | NGX_CONF_TAKE1,
+        "stream_core",
+    )
+    .since(1, 11, 3)
+    .default("30s"),
+    spec(
+        "resolver_timeout",
+        NGX_MAIL_MAIN_CONF | NGX_MAIL_SRV_CONF | NGX_CONF_TAKE1,
+        "mail_core",
+    )
+    .default("30s"),
+    spec(
+        "return",
+        NGX_HTTP_SRV_CONF
+            | NGX_HTTP_LOC_CONF
+            | NGX_HTTP_SIF_CONF
+            | NGX_HTTP_LIF_CONF
+            | NGX_CONF_TAKE12,
+        "http_rewrite",
+    )
+    .order_sensitive(),
+    spec(
+        "return",
+        NGX_STREAM_SRV_CONF | NGX_CONF_TAKE1,
+        "stream_return",
+    )
+    .since(1, 11, 2)
+    .order_sensitive(),
+    spec(
+        "rewrite",
+        NGX_HTTP_SRV_CONF
+            | NGX_HTTP_LOC_CONF
+            | NGX_HTTP_SIF_CONF
+            | NGX_HTTP_LIF_CONF
+            | NGX_CONF_TAKE23,
+        "http_rewrite",
+    )
+    .order_sensitive(),
+    spec(
+        "rewrite_log",
+        NGX_HTTP_MAIN_CONF
+            | NGX_HTTP_SRV_CONF
+            | NGX_HTTP_LOC_CONF
+            | NGX_HTTP_SIF_CONF
+            | NGX_HTTP_LIF_CONF
+            | NGX_CONF_FLAG,
+        "http_rewrite",
+    )
+    .default("off"),
+    spec(
+        "root",
+        NGX_HTTP_MAIN_CONF
+            | NGX_HTTP_SRV_CONF
+            | NGX_HTTP_LOC_CONF
+            | NGX_HTTP_LIF_CONF
+            | NGX_CONF_TAKE1,
+        "http_core",
+    )
+    .default("html"),
+    spec(
+        "satisfy",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_core",
+    )
+    .default("all"),
+    spec(
+        "scgi_bind",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE12,
+        "http_scgi",
+    ),
+    spec(
+        "scgi_buffer_size",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_scgi",
+    )
+    .default("4k"),
+    spec(
+        "scgi_buffering",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_scgi",
+    )
+    .default("on"),
+    spec(
+        "scgi_buffers",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE2,
+        "http_scgi",
+    )
+    .default("8 4k"),
+    spec(
+        "scgi_busy_buffers_size",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_scgi",
+    )
+    .default("8k"),
+    spec(
+        "scgi_cache",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_scgi",
+    )
+    .default("off"),
+    spec(
+        "scgi_cache_background_update",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_scgi",
+    )
+    .since(1, 11, 10)
+    .default("off"),
+    spec(
+        "scgi_cache_bypass",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_1MORE,
+        "http_scgi",
+    ),
+    spec(
+        "scgi_cache_key",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_scgi",
+    ),
+    spec(
+        "scgi_cache_lock",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_scgi",
+    )
+    .default("off"),
+    spec(
+        "scgi_cache_lock_age",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_scgi",
+    )
+    .default("5s"),
+    spec(
+        "scgi_cache_lock_timeout",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_scgi",
+    )
+    .default("5s"),
+    spec(
+        "scgi_cache_max_range_offset",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_scgi",
+    )
+    .since(1, 11, 6),
+    spec(
+        "scgi_cache_methods",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_1MORE,
+        "http_scgi",
+    )
+    .default("GET HEAD"),
+    spec(
+        "scgi_cache_min_uses",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_scgi",
+    )
+    .default("1"),
+    spec(
+        "scgi_cache_path",
+        NGX_HTTP_MAIN_CONF | NGX_CONF_2MORE,
+        "http_scgi",
+    ),
+    spec(
+        "scgi_cache_revalidate",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_scgi",
+    )
+    .default("off"),
+    spec(
+        "scgi_cache_use_stale",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_1MORE,
+        "http_scgi",
+    )
+    .default("off"),
+    spec(
+        "scgi_cache_valid",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_1MORE,
+        "http_scgi",
+    ),
+    spec(
+        "scgi_connect_timeout",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_scgi",
+    )
+    .default("60s"),
+    spec(
+        "scgi_force_ranges",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_scgi",
+    )
+    .default("off"),
+    spec(
+        "scgi_hide_header",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_scgi",
+    ),
+    spec(
+        "scgi_ignore_client_abort",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_scgi",
+    )
+    .default("off"),
+    spec(
+        "scgi_ignore_headers",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_1MORE,
+        "http_scgi",
+    ),
+    spec(
+        "scgi_intercept_errors",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_scgi",
+    )
+    .default("off"),
+    spec(
+        "scgi_limit_rate",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_scgi",
+    )
+    .default("0"),
+    spec(
+        "scgi_max_temp_file_size",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_scgi",
+    )
+    .default("1024m"),
+    spec(
+        "scgi_next_upstream",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_1MORE,
+        "http_scgi",
+    )
+    .default("error timeout"),
+    spec(
+        "scgi_next_upstream_timeout",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_scgi",
+    )
+    .default("0"),
+    spec(
+        "scgi_next_upstream_tries",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_scgi",
+    )
+    .default("0"),
+    spec(
+        "scgi_no_cache",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_1MORE,
+        "http_scgi",
+    ),
+    spec(
+        "scgi_param",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE23,
+        "http_scgi",
+    )
+    .order_sensitive(),
+    spec(
+        "scgi_pass",
+        NGX_HTTP_LOC_CONF | NGX_HTTP_LIF_CONF | NGX_CONF_TAKE1,
+        "http_scgi",
+    ),
+    spec(
+        "scgi_pass_header",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_scgi",
+    ),
+    spec(
+        "scgi_pass_request_body",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_scgi",
+    )
+    .default("on"),
+    spec(
+        "scgi_pass_request_headers",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_scgi",
+    )
+    .default("on"),
+    spec(
+        "scgi_read_timeout",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_scgi",
+    )
+    .default("60s"),
+    spec(
+        "scgi_request_buffering",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_scgi",
+    )
+    .default("on"),
+    spec(
+        "scgi_send_timeout",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_scgi",
+    )
+    .default("60s"),
+    spec(
+        "scgi_socket_keepalive",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_scgi",
+    )
+    .since(1, 15, 6)
+    .default("off"),
+    spec(
+        "scgi_store",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_scgi",
+    )
+    .default("off"),
+    spec(
+        "scgi_store_access",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE123,
+        "http_scgi",
+    )
+    .default("user:rw"),
+    spec(
+        "scgi_temp_file_write_size",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_scgi",
+    )
+    .default("8k"),
+    spec(
+        "scgi_temp_path",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1234,
+        "http_scgi",
+    )
+    .default("scgi_temp"),
+    spec(
+        "secure_link",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_secure_link",
+    ),
+    spec(
+        "secure_link_md5",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_secure_link",
+    ),
+    spec(
+        "secure_link_secret",
+        NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_secure_link",
+    ),
+    spec(
+        "send_lowat",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_core",
+    )
+    .default("0"),
+    spec(
+        "send_timeout",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_core",
+    )
+    .default("60s"),
+    spec(
+        "sendfile",
+        NGX_HTTP_MAIN_CONF
+            | NGX_HTTP_SRV_CONF
+            | NGX_HTTP_LOC_CONF
+            | NGX_HTTP_LIF_CONF
+            | NGX_CONF_FLAG,
+        "http_core",
+    )
+    .default("off"),
+    spec(
+        "sendfile_max_chunk",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_core",
+    )
+    .default("2m"),
+    spec(
+        "server",
+        NGX_HTTP_MAIN_CONF | NGX_CONF_BLOCK | NGX_CONF_NOARGS,
+        "http_core",
+    ),
+    spec(
+        "server",
+        NGX_HTTP_UPS_CONF | NGX_CONF_1MORE,
+        "http_upstream",
+    ),
+    spec(
+        "server",
+        NGX_STREAM_MAIN_CONF | NGX_CONF_BLOCK | NGX_CONF_NOARGS,
+        "stream_core",
+    ),
+    spec(
+        "server",
+        NGX_STREAM_UPS_CONF | NGX_CONF_1MORE,
+        "stream_upstream",
+    ),
+    spec(
+        "server",
+        NGX_MAIL_MAIN_CONF | NGX_CONF_BLOCK | NGX_CONF_NOARGS,
+        "mail_core",
+    ),
+    spec(
+        "server_name",
+        NGX_HTTP_SRV_CONF | NGX_CONF_1MORE,
+        "http_core",
+    )
+    .default("\"\""),
+    spec(
+        "server_name",
+        NGX_MAIL_MAIN_CONF | NGX_MAIL_SRV_CONF | NGX_CONF_TAKE1,
+        "mail_core",
+    )
+    .default("hostname"),
+    spec(
+        "server_name_in_redirect",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_core",
+    )
+    .default("off"),
+    spec(
+        "server_names_hash_bucket_size",
+        NGX_HTTP_MAIN_CONF | NGX_CONF_TAKE1,
+        "http_core",
+    )
+    .default("32|64|128"),
+    spec(
+        "server_names_hash_max_size",
+        NGX_HTTP_MAIN_CONF | NGX_CONF_TAKE1,
+        "http_core",
+    )
+    .default("512"),
+    spec(
+        "server_tokens",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_core",
+    )
+    .default("on"),
+    spec(
+        "set",
+        NGX_HTTP_SRV_CONF
+            | NGX_HTTP_LOC_CONF
+            | NGX_HTTP_SIF_CONF
+            | NGX_HTTP_LIF_CONF
+            | NGX_CONF_TAKE2,
+        "http_rewrite",
+    )
+    .order_sensitive(),
+    spec("set", NGX_STREAM_SRV_CONF | NGX_CONF_TAKE2, "stream_set")
+        .since(1, 19, 3)
+        .order_sensitive(),
+    spec(
+        "set_real_ip_from",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_realip",
+    ),
+    spec(
+        "set_real_ip_from",
+        NGX_STREAM_MAIN_CONF | NGX_STREAM_SRV_CONF | NGX_CONF_TAKE1,
+        "stream_realip",
+    )
+    .since(1, 11, 4),
+    spec(
+        "slice",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_slice",
+    )
+    .since(1, 9, 8)
+    .default("0"),
+    spec(
+        "smtp_auth",
+        NGX_MAIL_MAIN_CONF | NGX_MAIL_SRV_CONF | NGX_CONF_1MORE,
+        "mail_smtp",
+    )
+    .default("plain login"),
+    spec(
+        "smtp_capabilities",
+        NGX_MAIL_MAIN_CONF | NGX_MAIL_SRV_CONF | NGX_CONF_1MORE,
+        "mail_smtp",
+    ),
+    spec(
+        "smtp_client_buffer",
+        NGX_MAIL_MAIN_CONF | NGX_MAIL_SRV_CONF | NGX_CONF_TAKE1,
+        "mail_smtp",
+    )
+    .default("4k"),
+    spec(
+        "smtp_greeting_delay",
+        NGX_MAIL_MAIN_CONF | NGX_MAIL_SRV_CONF | NGX_CONF_TAKE1,
+        "mail_smtp",
+    )
+    .default("0"),
+    spec(
+        "source_charset",
+        NGX_HTTP_MAIN_CONF
+            | NGX_HTTP_SRV_CONF
+            | NGX_HTTP_LOC_CONF
+            | NGX_HTTP_LIF_CONF
+            | NGX_CONF_TAKE1,
+        "http_charset",
+    ),
+    spec(
+        "split_clients",
+        NGX_HTTP_MAIN_CONF | NGX_CONF_BLOCK | NGX_CONF_TAKE2,
+        "http_split_clients",
+    ),
+    spec(
+        "split_clients",
+        NGX_STREAM_MAIN_CONF | NGX_CONF_BLOCK | NGX_CONF_TAKE2,
+        "stream_split_clients",
+    ),
+    spec(
+        "ssi",
+        NGX_HTTP_MAIN_CONF
+            | NGX_HTTP_SRV_CONF
+            | NGX_HTTP_LOC_CONF
+            | NGX_HTTP_LIF_CONF
+            | NGX_CONF_FLAG,
+        "http_ssi",
+    )
+    .default("off"),
+    spec(
+        "ssi_last_modified",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_ssi",
+    )
+    .since(1, 5, 1)
+    .default("off"),
+    spec(
+        "ssi_min_file_chunk",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_ssi",
+    )
+    .default("1k"),
+    spec(
+        "ssi_silent_errors",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_ssi",
+    )
+    .default("off"),
+    spec(
+        "ssi_types",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_1MORE,
+        "http_ssi",
+    )
+    .default("text/html"),
+    spec(
+        "ssi_value_length",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_ssi",
+    )
+    .default("256"),
+    spec(
+        "ssl",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_CONF_FLAG,
+        "http_ssl",
+    )
+    .deprecated(1, 15, 0, "the \"ssl\" parameter of \"listen\"")
+    .removed(1, 25, 1)
+    .default("off"),
+    spec(
+        "ssl",
+        NGX_MAIL_MAIN_CONF | NGX_MAIL_SRV_CONF | NGX_CONF_FLAG,
+        "mail_ssl",
+    )
+    .deprecated(1, 15, 0, "the \"ssl\" parameter of \"listen\"")
+    .removed(1, 25, 1)
+    .default("off"),
+    spec(
+        "ssl_alpn",
+        NGX_STREAM_MAIN_CONF | NGX_STREAM_SRV_CONF | NGX_CONF_1MORE,
+        "stream_ssl",
+    )
+    .since(1, 21, 4),
+    spec(
+        "ssl_buffer_size",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_CONF_TAKE1,
+        "http_ssl",
+    )
+    .since(1, 5, 9)
+    .default("16k"),
+    spec(
+        "ssl_certificate",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_CONF_TAKE1,
+        "http_ssl",
+    ),
+    spec(
+        "ssl_certificate",
+        NGX_MAIL_MAIN_CONF | NGX_MAIL_SRV_CONF | NGX_CONF_TAKE1,
+        "mail_ssl",
+    ),
+    spec(
+        "ssl_certificate",
+        NGX_STREAM_MAIN_CONF | NGX_STREAM_SRV_CONF | NGX_CONF_TAKE1,
+        "stream_ssl",
+    ),
+    spec(
+        "ssl_certificate_key",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_CONF_TAKE1,
+        "http_ssl",
+    ),
+    spec(
+        "ssl_certificate_key",
+        NGX_MAIL_MAIN_CONF | NGX_MAIL_SRV_CONF | NGX_CONF_TAKE1,
+        "mail_ssl",
+    ),
+    spec(
+        "ssl_certificate_key",
+        NGX_STREAM_MAIN_CONF | NGX_STREAM_SRV_CONF | NGX_CONF_TAKE1,
+        "stream_ssl",
+    ),
+    spec(
+        "ssl_ciphers",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_CONF_TAKE1,
+        "http_ssl",
+    )
+    .default("HIGH:!aNULL:!MD5"),
+    spec(
+        "ssl_ciphers",
+        NGX_MAIL_MAIN_CONF | NGX_MAIL_SRV_CONF | NGX_CONF_TAKE1,
+        "mail_ssl",
+    )
+    .default("HIGH:!aNULL:!MD5"),
+    spec(
+        "ssl_ciphers",
+        NGX_STREAM_MAIN_CONF | NGX_STREAM_SRV_CONF | NGX_CONF_TAKE1,
+        "stream_ssl",
+    )
+    .default("HIGH:!aNULL:!MD5"),
+    spec(
+        "ssl_client_certificate",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_CONF_TAKE1,
+        "http_ssl",
+    ),
+    spec(
+        "ssl_client_certificate",
+        NGX_MAIL_MAIN_CONF | NGX_MAIL_SRV_CONF | NGX_CONF_TAKE1,
+        "mail_ssl",
+    )
+    .since(1, 7, 11),
+    spec(
+        "ssl_client_certificate",
+        NGX_STREAM_MAIN_CONF | NGX_STREAM_SRV_CONF | NGX_CONF_TAKE1,
+        "stream_ssl",
+    )
+    .since(1, 11, 8),
+    spec(
+        "ssl_conf_command",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_CONF_TAKE2,
+        "http_ssl",
+    )
+    .since(1, 19, 4),
+    spec(
+        "ssl_conf_command",
+        NGX_MAIL_MAIN_CONF | NGX_MAIL_SRV_CONF | NGX_CONF_TAKE2,
+        "mail_ssl",
+    )
+    .since(1, 19, 4),
+    spec(
+        "ssl_conf_command",
+        NGX_STREAM_MAIN_CONF | NGX_STREAM_SRV_CONF | NGX_CONF_TAKE2,
+        "stream_ssl",
+    )
+    .since(1, 19, 4),
+    spec(
+        "ssl_crl",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_CONF_TAKE1,
+        "http_ssl",
+    )
+    .since(0, 8, 7),
+    spec(
+        "ssl_crl",
+        NGX_MAIL_MAIN_CONF | NGX_MAIL_SRV_CONF | NGX_CONF_TAKE1,
+        "mail_ssl",
+    )
+    .since(1, 7, 11),
+    spec(
+        "ssl_crl",
+        NGX_STREAM_MAIN_CONF | NGX_STREAM_SRV_CONF | NGX_CONF_TAKE1,
+        "stream_ssl",
+    )
+    .since(1, 11, 8),
+    spec(
+        "ssl_dhparam",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_CONF_TAKE1,
+        "http_ssl",
+    )
+    .since(0, 7, 2),
+    spec(
+        "ssl_dhparam",
+        NGX_MAIL_MAIN_CONF | NGX_MAIL_SRV_CONF | NGX_CONF_TAKE1,
+        "mail_ssl",
+    ),
+    spec(
+        "ssl_dhparam",
+        NGX_STREAM_MAIN_CONF | NGX_STREAM_SRV_CONF | NGX_CONF_TAKE1,
+        "stream_ssl",
+    ),
+    spec(
+        "ssl_early_data",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_CONF_FLAG,
+        "http_ssl",
+    )
+    .since(1, 15, 3)
+    .default("off"),
+    spec(
+        "ssl_ecdh_curve",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_CONF_TAKE1,
+        "http_ssl",
+    )
+    .since(1, 1, 0)
+    .default("auto"),
+    spec(
+        "ssl_ecdh_curve",
+        NGX_MAIL_MAIN_CONF | NGX_MAIL_SRV_CONF | NGX_CONF_TAKE1,
+        "mail_ssl",
+    )
+    .default("auto"),
+    spec(
+        "ssl_ecdh_curve",
+        NGX_STREAM_MAIN_CONF | NGX_STREAM_SRV_CONF | NGX_CONF_TAKE1,
+        "stream_ssl",
+    )
+    .default("auto"),
+    spec("ssl_engine", NGX_MAIN_CONF | NGX_CONF_TAKE1, "core"),
+    spec(
+        "ssl_handshake_timeout",
+        NGX_STREAM_MAIN_CONF | NGX_STREAM_SRV_CONF | NGX_CONF_TAKE1,
+        "stream_ssl",
+    )
+    .default("60s"),
+    spec(
+        "ssl_ocsp",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_CONF_TAKE1,
+        "http_ssl",
+    )
+    .since(1, 19, 0)
+    .default("off"),
+    spec(
+        "ssl_ocsp_cache",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_CONF_TAKE1,
+        "http_ssl",
+    )
+    .since(1, 19, 0)
+    .default("off"),
+    spec(
+        "ssl_ocsp_responder",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_CONF_TAKE1,
+        "http_ssl",
+    )
+    .since(1, 19, 0),
+    spec(
+        "ssl_password_file",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_CONF_TAKE1,
+        "http_ssl",
+    )
+    .since(1, 7, 3),
+    spec(
+        "ssl_password_file",
+        NGX_MAIL_MAIN_CONF | NGX_MAIL_SRV_CONF | NGX_CONF_TAKE1,
+        "mail_ssl",
+    )
+    .since(1, 7, 3),
+    spec(
+        "ssl_password_file",
+        NGX_STREAM_MAIN_CONF | NGX_STREAM_SRV_CONF | NGX_CONF_TAKE1,
+        "stream_ssl",
+    ),
+    spec(
+        "ssl_prefer_server_ciphers",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_CONF_FLAG,
+        "http_ssl",
+    )
+    .default("off"),
+    spec(
+        "ssl_prefer_server_ciphers",
+        NGX_MAIL_MAIN_CONF | NGX_MAIL_SRV_CONF | NGX_CONF_FLAG,
+        "mail_ssl",
+    )
+    .default("off"),
+    spec(
+        "ssl_prefer_server_ciphers",
+        NGX_STREAM_MAIN_CONF | NGX_STREAM_SRV_CONF | NGX_CONF_FLAG,
+        "stream_ssl",
+    )
+    .default("off"),
+    spec(
+        "ssl_preread",
+        NGX_STREAM_MAIN_CONF | NGX_STREAM_SRV_CONF | NGX_CONF_FLAG,
+        "stream_ssl_preread",
+    )
+    .since(1, 11, 5)
+    .default("off"),
+    spec(
+        "ssl_protocols",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_CONF_1MORE,
+        "http_ssl",
+    )
+    .default("TLSv1.2 TLSv1.3"),
+    spec(
+        "ssl_protocols",
+        NGX_MAIL_MAIN_CONF | NGX_MAIL_SRV_CONF | NGX_CONF_1MORE,
+        "mail_ssl",
+    )
+    .default("TLSv1.2 TLSv1.3"),
+    spec(
+        "ssl_protocols",
+        NGX_STREAM_MAIN_CONF | NGX_STREAM_SRV_CONF | NGX_CONF_1MORE,
+        "stream_ssl",
+    )
+    .default("TLSv1.2 TLSv1.3"),
+    spec(
+        "ssl_reject_handshake",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_CONF_FLAG,
+        "http_ssl",
+    )
+    .since(1, 19, 4)
+    .default("off"),
+    spec(
+        "ssl_session_cache",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_CONF_TAKE12,
+        "http_ssl",
+    )
+    .default("none"),
+    spec(
+        "ssl_session_cache",
+        NGX_MAIL_MAIN_CONF | NGX_MAIL_SRV_CONF | NGX_CONF_TAKE12,
+        "mail_ssl",
+    )
+    .default("none"),
+    spec(
+        "ssl_session_cache",
+        NGX_STREAM_MAIN_CONF | NGX_STREAM_SRV_CONF | NGX_CONF_TAKE12,
+        "stream_ssl",
+    )
+    .default("none"),
+    spec(
+        "ssl_session_ticket_key",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_CONF_TAKE1,
+        "http_ssl",
+    )
+    .since(1, 5, 7),
+    spec(
+        "ssl_session_ticket_key",
+        NGX_MAIL_MAIN_CONF | NGX_MAIL_SRV_CONF | NGX_CONF_TAKE1,
+        "mail_ssl",
+    )
+    .since(1, 5, 7),
+    spec(
+        "ssl_session_ticket_key",
+        NGX_STREAM_MAIN_CONF | NGX_STREAM_SRV_CONF | NGX_CONF_TAKE1,
+        "stream_ssl",
+    ),
+    spec(
+        "ssl_session_tickets",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_CONF_FLAG,
+        "http_ssl",
+    )
+    .since(1, 5, 9)
+    .default("on"),
+    spec(
+        "ssl_session_tickets",
+        NGX_MAIL_MAIN_CONF | NGX_MAIL_SRV_CONF | NGX_CONF_FLAG,
+        "mail_ssl",
+    )
+    .since(1, 5, 9)
+    .default("on"),
+    spec(
+        "ssl_session_tickets",
+        NGX_STREAM_MAIN_CONF | NGX_STREAM_SRV_CONF | NGX_CONF_FLAG,
+        "stream_ssl",
+    )
+    .default("on"),
+    spec(
+        "ssl_session_timeout",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_CONF_TAKE1,
+        "http_ssl",
+    )
+    .default("5m"),
+    spec(
+        "ssl_session_timeout",
+        NGX_MAIL_MAIN_CONF | NGX_MAIL_SRV_CONF | NGX_CONF_TAKE1,
+        "mail_ssl",
+    )
+    .default("5m"),
+    spec(
+        "ssl_session_timeout",
+        NGX_STREAM_MAIN_CONF | NGX_STREAM_SRV_CONF | NGX_CONF_TAKE1,
+        "stream_ssl",
+    )
+    .default("5m"),
+    spec(
+        "ssl_stapling",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_CONF_FLAG,
+        "http_ssl",
+    )
+    .since(1, 3, 7)
+    .default("off"),
+    spec(
+        "ssl_stapling_file",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_CONF_TAKE1,
+        "http_ssl",
+    )
+    .since(1, 3, 7),
+    spec(
+        "ssl_stapling_responder",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_CONF_TAKE1,
+        "http_ssl",
+    )
+    .since(1, 3, 7),
+    spec(
+        "ssl_stapling_verify",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_CONF_FLAG,
+        "http_ssl",
+    )
+    .since(1, 3, 7)
+    .default("off"),
+    spec(
+        "ssl_trusted_certificate",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_CONF_TAKE1,
+        "http_ssl",
+    )
+    .since(1, 3, 7),
+    spec(
+        "ssl_trusted_certificate",
+        NGX_MAIL_MAIN_CONF | NGX_MAIL_SRV_CONF | NGX_CONF_TAKE1,
+        "mail_ssl",
+    )
+    .since(1, 7, 11),
+    spec(
+        "ssl_trusted_certificate",
+        NGX_STREAM_MAIN_CONF | NGX_STREAM_SRV_CONF | NGX_CONF_TAKE1,
+        "stream_ssl",
+    )
+    .since(1, 11, 8),
+    spec(
+        "ssl_verify_client",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_CONF_TAKE1,
+        "http_ssl",
+    )
+    .default("off"),
+    spec(
+        "ssl_verify_client",
+        NGX_MAIL_MAIN_CONF | NGX_MAIL_SRV_CONF | NGX_CONF_TAKE1,
+        "mail_ssl",
+    )
+    .since(1, 7, 11)
+    .default("off"),
+    spec(
+        "ssl_verify_client",
+        NGX_STREAM_MAIN_CONF | NGX_STREAM_SRV_CONF | NGX_CONF_TAKE1,
+        "stream_ssl",
+    )
+    .since(1, 11, 8)
+    .default("off"),
+    spec(
+        "ssl_verify_depth",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_CONF_TAKE1,
+        "http_ssl",
+    )
+    .default("1"),
+    spec(
+        "ssl_verify_depth",
+        NGX_MAIL_MAIN_CONF | NGX_MAIL_SRV_CONF | NGX_CONF_TAKE1,
+        "mail_ssl",
+    )
+    .since(1, 7, 11)
+    .default("1"),
+    spec(
+        "ssl_verify_depth",
+        NGX_STREAM_MAIN_CONF | NGX_STREAM_SRV_CONF | NGX_CONF_TAKE1,
+        "stream_ssl",
+    )
+    .since(1, 11, 8)
+    .default("1"),
+    spec(
+        "starttls",
+        NGX_MAIL_MAIN_CONF | NGX_MAIL_SRV_CONF | NGX_CONF_TAKE1,
+        "mail_ssl",
+    )
+    .default("off"),
+    spec(
+        "stream",
+        NGX_MAIN_CONF | NGX_CONF_BLOCK | NGX_CONF_NOARGS,
+        "stream",
+    )
+    .since(1, 9, 0),
+    spec(
+        "stub_status",
+        NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_NOARGS | NGX_CONF_TAKE1,
+        "http_stub_status",
+    ),
+    spec(
+        "sub_filter",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE2,
+        "http_sub",
+    ),
+    spec(
+        "sub_filter_last_modified",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_sub",
+    )
+    .since(1, 5, 1)
+    .default("off"),
+    spec(
+        "sub_filter_once",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_sub",
+    )
+    .default("on"),
+    spec(
+        "sub_filter_types",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_1MORE,
+        "http_sub",
+    )
+    .default("text/html"),
+    spec(
+        "subrequest_output_buffer_size",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_core",
+    )
+    .since(1, 13, 10)
+    .default("4k"),
+    spec(
+        "tcp_nodelay",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_core",
+    )
+    .default("on"),
+    spec(
+        "tcp_nodelay",
+        NGX_STREAM_MAIN_CONF | NGX_STREAM_SRV_CONF | NGX_CONF_FLAG,
+        "stream_core",
+    )
+    .since(1, 9, 4)
+    .default("on"),
+    spec(
+        "tcp_nopush",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_core",
+    )
+    .default("off"),
+    spec("thread_pool", NGX_MAIN_CONF | NGX_CONF_TAKE23, "core")
+        .since(1, 7, 11)
+        .default("default threads=32 max_queue=65536"),
+    spec(
+        "timeout",
+        NGX_MAIL_MAIN_CONF | NGX_MAIL_SRV_CONF | NGX_CONF_TAKE1,
+        "mail_core",
+    )
+    .default("60s"),
+    spec("timer_resolution", NGX_MAIN_CONF | NGX_CONF_TAKE1, "core"),
+    spec(
+        "try_files",
+        NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_2MORE,
+        "http_core",
+    ),
+    spec(
+        "types",
+        NGX_HTTP_MAIN_CONF
+            | NGX_HTTP_SRV_CONF
+            | NGX_HTTP_LOC_CONF
+            | NGX_CONF_BLOCK
+            | NGX_CONF_NOARGS,
+        "http_core",
+    ),
+    spec(
+        "types_hash_bucket_size",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_core",
+    )
+    .default("64"),
+    spec(
+        "types_hash_max_size",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_core",
+    )
+    .default("1024"),
+    spec(
+        "underscores_in_headers",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_CONF_FLAG,
+        "http_core",
+    )
+    .default("off"),
+    spec(
+        "uninitialized_variable_warn",
+        NGX_HTTP_MAIN_CONF
+            | NGX_HTTP_SRV_CONF
+            | NGX_HTTP_LOC_CONF
+            | NGX_HTTP_SIF_CONF
+            | NGX_HTTP_LIF_CONF
+            | NGX_CONF_FLAG,
+        "http_rewrite",
+    )
+    .default("on"),
+    spec(
+        "upstream",
+        NGX_HTTP_MAIN_CONF | NGX_CONF_BLOCK | NGX_CONF_TAKE1,
+        "http_upstream",
+    ),
+    spec(
+        "upstream",
+        NGX_STREAM_MAIN_CONF | NGX_CONF_BLOCK | NGX_CONF_TAKE1,
+        "stream_upstream",
+    ),
+    spec("use", NGX_EVENT_CONF | NGX_CONF_TAKE1, "events"),
+    spec("user", NGX_MAIN_CONF | NGX_CONF_TAKE12, "core").default("nobody nobody"),
+    spec(
+        "userid",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_userid",
+    )
+    .default("off"),
+    spec(
+        "userid_domain",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_userid",
+    )
+    .default("none"),
+    spec(
+        "userid_expires",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_userid",
+    )
+    .default("off"),
+    spec(
+        "userid_flags",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_1MORE,
+        "http_userid",
+    )
+    .since(1, 19, 3)
+    .default("off"),
+    spec(
+        "userid_mark",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_userid",
+    )
+    .default("off"),
+    spec(
+        "userid_name",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_userid",
+    )
+    .default("uid"),
+    spec(
+        "userid_p3p",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_userid",
+    )
+    .default("none"),
+    spec(
+        "userid_path",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_userid",
+    )
+    .default("/"),
+    spec(
+        "userid_service",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_userid",
+    ),
+    spec(
+        "uwsgi_bind",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE12,
+        "http_uwsgi",
+    ),
+    spec(
+        "uwsgi_buffer_size",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_uwsgi",
+    )
+    .default("4k"),
+    spec(
+        "uwsgi_buffering",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_uwsgi",
+    )
+    .default("on"),
+    spec(
+        "uwsgi_buffers",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE2,
+        "http_uwsgi",
+    )
+    .default("8 4k"),
+    spec(
+        "uwsgi_busy_buffers_size",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_uwsgi",
+    )
+    .default("8k"),
+    spec(
+        "uwsgi_cache",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_uwsgi",
+    )
+    .default("off"),
+    spec(
+        "uwsgi_cache_background_update",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_uwsgi",
+    )
+    .since(1, 11, 10)
+    .default("off"),
+    spec(
+        "uwsgi_cache_bypass",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_1MORE,
+        "http_uwsgi",
+    ),
+    spec(
+        "uwsgi_cache_key",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_uwsgi",
+    ),
+    spec(
+        "uwsgi_cache_lock",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_uwsgi",
+    )
+    .default("off"),
+    spec(
+        "uwsgi_cache_lock_age",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_uwsgi",
+    )
+    .default("5s"),
+    spec(
+        "uwsgi_cache_lock_timeout",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_uwsgi",
+    )
+    .default("5s"),
+    spec(
+        "uwsgi_cache_max_range_offset",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_uwsgi",
+    )
+    .since(1, 11, 6),
+    spec(
+        "uwsgi_cache_methods",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_1MORE,
+        "http_uwsgi",
+    )
+    .default("GET HEAD"),
+    spec(
+        "uwsgi_cache_min_uses",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_uwsgi",
+    )
+    .default("1"),
+    spec(
+        "uwsgi_cache_path",
+        NGX_HTTP_MAIN_CONF | NGX_CONF_2MORE,
+        "http_uwsgi",
+    ),
+    spec(
+        "uwsgi_cache_revalidate",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_uwsgi",
+    )
+    .default("off"),
+    spec(
+        "uwsgi_cache_use_stale",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_1MORE,
+        "http_uwsgi",
+    )
+    .default("off"),
+    spec(
+        "uwsgi_cache_valid",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_1MORE,
+        "http_uwsgi",
+    ),
+    spec(
+        "uwsgi_connect_timeout",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_uwsgi",
+    )
+    .default("60s"),
+    spec(
+        "uwsgi_force_ranges",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_uwsgi",
+    )
+    .default("off"),
+    spec(
+        "uwsgi_hide_header",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_uwsgi",
+    ),
+    spec(
+        "uwsgi_ignore_client_abort",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_uwsgi",
+    )
+    .default("off"),
+    spec(
+        "uwsgi_ignore_headers",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_1MORE,
+        "http_uwsgi",
+    ),
+    spec(
+        "uwsgi_intercept_errors",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_uwsgi",
+    )
+    .default("off"),
+    spec(
+        "uwsgi_limit_rate",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_uwsgi",
+    )
+    .default("0"),
+    spec(
+        "uwsgi_max_temp_file_size",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_uwsgi",
+    )
+    .default("1024m"),
+    spec(
+        "uwsgi_modifier1",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_uwsgi",
+    )
+    .default("0"),
+    spec(
+        "uwsgi_modifier2",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_uwsgi",
+    )
+    .default("0"),
+    spec(
+        "uwsgi_next_upstream",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_1MORE,
+        "http_uwsgi",
+    )
+    .default("error timeout"),
+    spec(
+        "uwsgi_next_upstream_timeout",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_uwsgi",
+    )
+    .default("0"),
+    spec(
+        "uwsgi_next_upstream_tries",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_uwsgi",
+    )
+    .default("0"),
+    spec(
+        "uwsgi_no_cache",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_1MORE,
+        "http_uwsgi",
+    ),
+    spec(
+        "uwsgi_param",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE23,
+        "http_uwsgi",
+    )
+    .order_sensitive(),
+    spec(
+        "uwsgi_pass",
+        NGX_HTTP_LOC_CONF | NGX_HTTP_LIF_CONF | NGX_CONF_TAKE1,
+        "http_uwsgi",
+    ),
+    spec(
+        "uwsgi_pass_header",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_uwsgi",
+    ),
+    spec(
+        "uwsgi_pass_request_body",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_uwsgi",
+    )
+    .default("on"),
+    spec(
+        "uwsgi_pass_request_headers",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_uwsgi",
+    )
+    .default("on"),
+    spec(
+        "uwsgi_read_timeout",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_uwsgi",
+    )
+    .default("60s"),
+    spec(
+        "uwsgi_request_buffering",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_uwsgi",
+    )
+    .default("on"),
+    spec(
+        "uwsgi_send_timeout",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_uwsgi",
+    )
+    .default("60s"),
+    spec(
+        "uwsgi_socket_keepalive",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_uwsgi",
+    )
+    .since(1, 15, 6)
+    .default("off"),
+    spec(
+        "uwsgi_ssl_certificate",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_uwsgi",
+    )
+    .since(1, 7, 8),
+    spec(
+        "uwsgi_ssl_certificate_key",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_uwsgi",
+    )
+    .since(1, 7, 8),
+    spec(
+        "uwsgi_ssl_ciphers",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_uwsgi",
+    )
+    .since(1, 5, 8)
+    .default("DEFAULT"),
+    spec(
+        "uwsgi_ssl_conf_command",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE2,
+        "http_uwsgi",
+    )
+    .since(1, 19, 4),
+    spec(
+        "uwsgi_ssl_crl",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_uwsgi",
+    )
+    .since(1, 7, 0),
+    spec(
+        "uwsgi_ssl_name",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_uwsgi",
+    )
+    .since(1, 7, 0),
+    spec(
+        "uwsgi_ssl_password_file",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_uwsgi",
+    )
+    .since(1, 7, 8),
+    spec(
+        "uwsgi_ssl_protocols",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_1MORE,
+        "http_uwsgi",
+    )
+    .since(1, 5, 8)
+    .default("TLSv1.2 TLSv1.3"),
+    spec(
+        "uwsgi_ssl_server_name",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_uwsgi",
+    )
+    .since(1, 7, 0)
+    .default("off"),
+    spec(
+        "uwsgi_ssl_session_reuse",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_uwsgi",
+    )
+    .since(1, 5, 8)
+    .default("on"),
+    spec(
+        "uwsgi_ssl_trusted_certificate",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_uwsgi",
+    )
+    .since(1, 7, 0),
+    spec(
+        "uwsgi_ssl_verify",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_uwsgi",
+    )
+    .since(1, 7, 0)
+    .default("off"),
+    spec(
+        "uwsgi_ssl_verify_depth",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_uwsgi",
+    )
+    .since(1, 7, 0)
+    .default("1"),
+    spec(
+        "uwsgi_store",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_uwsgi",
+    )
+    .default("off"),
+    spec(
+        "uwsgi_store_access",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE123,
+        "http_uwsgi",
+    )
+    .default("user:rw"),
+    spec(
+        "uwsgi_temp_file_write_size",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_uwsgi",
+    )
+    .default("8k"),
+    spec(
+        "uwsgi_temp_path",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1234,
+        "http_uwsgi",
+    )
+    .default("uwsgi_temp"),
+    spec(
+        "valid_referers",
+        NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_1MORE,
+        "http_referer",
+    ),
+    spec(
+        "variables_hash_bucket_size",
+        NGX_HTTP_MAIN_CONF | NGX_CONF_TAKE1,
+        "http_core",
+    )
+    .default("64"),
+    spec(
+        "variables_hash_bucket_size",
+        NGX_STREAM_MAIN_CONF | NGX_CONF_TAKE1,
+        "stream_core",
+    )
+    .since(1, 11, 2)
+    .default("64"),
+    spec(
+        "variables_hash_max_size",
+        NGX_HTTP_MAIN_CONF | NGX_CONF_TAKE1,
+        "http_core",
+    )
+    .default("1024"),
+    spec(
+        "variables_hash_max_size",
+        NGX_STREAM_MAIN_CONF | NGX_CONF_TAKE1,
+        "stream_core",
+    )
+    .since(1, 11, 2)
+    .default("1024"),
+    spec(
+        "worker_aio_requests",
+        NGX_EVENT_CONF | NGX_CONF_TAKE1,
+        "events",
+    )
+    .since(1, 1, 4)
+    .default("32"),
+    spec(
+        "worker_connections",
+        NGX_EVENT_CONF | NGX_CONF_TAKE1,
+        "events",
+    )
+    .default("512"),
+    spec(
+        "worker_cpu_affinity",
+        NGX_MAIN_CONF | NGX_CONF_1MORE,
+        "core",
+    ),
+    spec("worker_priority", NGX_MAIN_CONF | NGX_CONF_TAKE1, "core").default("0"),
+    spec("worker_processes", NGX_MAIN_CONF | NGX_CONF_TAKE1, "core").default("1"),
+    spec("worker_rlimit_core", NGX_MAIN_CONF | NGX_CONF_TAKE1, "core"),
+    spec(
+        "worker_rlimit_nofile",
+        NGX_MAIN_CONF | NGX_CONF_TAKE1,
+        "core",
+    ),
+    spec(
+        "worker_shutdown_timeout",
+        NGX_MAIN_CONF | NGX_CONF_TAKE1,
+        "core",
+    )
+    .since(1, 11, 11),
+    spec("working_directory", NGX_MAIN_CONF | NGX_CONF_TAKE1, "core"),
+    spec(
+        "xclient",
+        NGX_MAIL_MAIN_CONF | NGX_MAIL_SRV_CONF | NGX_CONF_FLAG,
+        "mail_proxy",
+    )
+    .default("on"),
+    spec(
+        "xml_entities",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE1,
+        "http_xslt",
+    ),
+    spec(
+        "xslt_last_modified",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_FLAG,
+        "http_xslt",
+    )
+    .since(1, 5, 1)
+    .default("off"),
+    spec(
+        "xslt_param",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE2,
+        "http_xslt",
+    )
+    .since(1, 1, 18),
+    spec(
+        "xslt_string_param",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_TAKE2,
+        "http_xslt",
+    )
+    .since(1, 1, 18),
+    spec(
+        "xslt_stylesheet",
+        NGX_HTTP_LOC_CONF | NGX_CONF_1MORE,
+        "http_xslt",
+    ),
+    spec(
+        "xslt_types",
+        NGX_HTTP_MAIN_CONF | NGX_HTTP_SRV_CONF | NGX_HTTP_LOC_CONF | NGX_CONF_1MORE,
+        "http_xslt",
+    )
+    .default("text/xml"),
+    spec("zone", NGX_HTTP_UPS_CONF | NGX_CONF_TAKE12, "http_upstream").since(1, 9, 0),
+    spec(
+        "zone",
+        NGX_STREAM_UPS_CONF | NGX_CONF_TAKE12,
+        "stream_upstream",
+    )
+    .since(1, 9, 0),
+];
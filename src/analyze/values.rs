@@ -0,0 +1,269 @@
+use std::fmt;
+use std::time::Duration;
+
+use super::args::{parse_number, parse_size, parse_time};
+use super::{directive_specs, NGX_CONF_FLAG};
+use crate::tree::DirectiveRef;
+
+/// How nginx reads a directive argument, for the arguments the table types.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArgType {
+    /// `on` or `off`.
+    Flag,
+    /// A plain number.
+    Number,
+    /// A size with an optional `k`, `m` or `g` suffix.
+    Size,
+    /// A time kept in seconds, like `ssl_session_timeout`. Numbers without
+    /// a unit are seconds, and `ms` is refused.
+    Sec,
+    /// A time kept in milliseconds, like `proxy_read_timeout`. Numbers
+    /// without a unit are still seconds, and `ms` is allowed.
+    Msec,
+}
+
+/// Why a typed argument has no value, see [`DirectiveRef::arg_as_size`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArgValueError {
+    /// The directive has no argument at the index.
+    Missing,
+    /// The argument holds a variable, which nginx does not expand there.
+    Variable,
+    /// The table gives the argument another type, or none.
+    WrongType(Option<ArgType>),
+    /// The argument is not a valid value of its type.
+    Invalid(String),
+}
+
+impl fmt::Display for ArgValueError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArgValueError::Missing => write!(f, "no such argument"),
+            ArgValueError::Variable => write!(f, "the argument holds a variable"),
+            ArgValueError::WrongType(Some(found)) => {
+                write!(f, "the argument is of type {:?}", found)
+            }
+            ArgValueError::WrongType(None) => write!(f, "the argument has no known type"),
+            ArgValueError::Invalid(value) => write!(f, "invalid value \"{}\"", value),
+        }
+    }
+}
+
+impl std::error::Error for ArgValueError {}
+
+// the types of the arguments of directives set with the size, time and
+// number slots of nginx, sorted by name and index; flags are told by their
+// NGX_CONF_FLAG bit
+const ARG_TYPES: [(&str, usize, ArgType); 64] = [
+    ("client_body_buffer_size", 0, ArgType::Size),
+    ("client_body_timeout", 0, ArgType::Msec),
+    ("client_header_buffer_size", 0, ArgType::Size),
+    ("client_header_timeout", 0, ArgType::Msec),
+    ("client_max_body_size", 0, ArgType::Size),
+    ("fastcgi_buffer_size", 0, ArgType::Size),
+    ("fastcgi_buffers", 0, ArgType::Number),
+    ("fastcgi_buffers", 1, ArgType::Size),
+    ("fastcgi_connect_timeout", 0, ArgType::Msec),
+    ("fastcgi_read_timeout", 0, ArgType::Msec),
+    ("fastcgi_send_timeout", 0, ArgType::Msec),
+    ("grpc_connect_timeout", 0, ArgType::Msec),
+    ("grpc_read_timeout", 0, ArgType::Msec),
+    ("grpc_send_timeout", 0, ArgType::Msec),
+    ("gzip_buffers", 0, ArgType::Number),
+    ("gzip_buffers", 1, ArgType::Size),
+    ("gzip_comp_level", 0, ArgType::Number),
+    ("gzip_min_length", 0, ArgType::Size),
+    ("keepalive", 0, ArgType::Number),
+    ("keepalive_requests", 0, ArgType::Number),
+    ("keepalive_time", 0, ArgType::Msec),
+    ("keepalive_timeout", 0, ArgType::Msec),
+    ("keepalive_timeout", 1, ArgType::Sec),
+    ("large_client_header_buffers", 0, ArgType::Number),
+    ("large_client_header_buffers", 1, ArgType::Size),
+    ("limit_rate", 0, ArgType::Size),
+    ("limit_rate_after", 0, ArgType::Size),
+    ("lingering_time", 0, ArgType::Msec),
+    ("lingering_timeout", 0, ArgType::Msec),
+    ("map_hash_bucket_size", 0, ArgType::Size),
+    ("map_hash_max_size", 0, ArgType::Number),
+    ("open_file_cache_min_uses", 0, ArgType::Number),
+    ("open_file_cache_valid", 0, ArgType::Sec),
+    ("output_buffers", 0, ArgType::Number),
+    ("output_buffers", 1, ArgType::Size),
+    ("postpone_output", 0, ArgType::Size),
+    ("proxy_buffer_size", 0, ArgType::Size),
+    ("proxy_buffers", 0, ArgType::Number),
+    ("proxy_buffers", 1, ArgType::Size),
+    ("proxy_cache_lock_age", 0, ArgType::Msec),
+    ("proxy_cache_lock_timeout", 0, ArgType::Msec),
+    ("proxy_connect_timeout", 0, ArgType::Msec),
+    ("proxy_headers_hash_bucket_size", 0, ArgType::Size),
+    ("proxy_next_upstream_timeout", 0, ArgType::Msec),
+    ("proxy_next_upstream_tries", 0, ArgType::Number),
+    ("proxy_read_timeout", 0, ArgType::Msec),
+    ("proxy_send_timeout", 0, ArgType::Msec),
+    ("proxy_timeout", 0, ArgType::Msec),
+    ("referer_hash_bucket_size", 0, ArgType::Size),
+    ("resolver_timeout", 0, ArgType::Msec),
+    ("send_timeout", 0, ArgType::Msec),
+    ("sendfile_max_chunk", 0, ArgType::Size),
+    ("server_names_hash_bucket_size", 0, ArgType::Size),
+    ("server_names_hash_max_size", 0, ArgType::Number),
+    ("ssl_buffer_size", 0, ArgType::Size),
+    ("ssl_session_timeout", 0, ArgType::Sec),
+    ("timer_resolution", 0, ArgType::Msec),
+    ("types_hash_bucket_size", 0, ArgType::Size),
+    ("types_hash_max_size", 0, ArgType::Number),
+    ("uwsgi_read_timeout", 0, ArgType::Msec),
+    ("variables_hash_bucket_size", 0, ArgType::Size),
+    ("worker_connections", 0, ArgType::Number),
+    ("worker_rlimit_nofile", 0, ArgType::Number),
+    ("worker_shutdown_timeout", 0, ArgType::Msec),
+];
+
+/// The type nginx reads the argument at `index` of a directive as, when the
+/// table knows it.
+pub fn arg_type(name: &str, index: usize) -> Option<ArgType> {
+    let typed = ARG_TYPES
+        .binary_search_by(|&(n, i, _)| (n, i).cmp(&(name, index)))
+        .ok()
+        .map(|i| ARG_TYPES[i].2);
+    let is_flag = || {
+        let specs = directive_specs(name);
+        index == 0 && !specs.is_empty() && specs.iter().all(|s| s.mask & NGX_CONF_FLAG != 0)
+    };
+    typed.or_else(|| is_flag().then_some(ArgType::Flag))
+}
+
+impl DirectiveRef<'_> {
+    /// The argument at `index` as a size in bytes, for the arguments the
+    /// table types as sizes.
+    pub fn arg_as_size(&self, index: usize) -> Result<u64, ArgValueError> {
+        let value = self.typed_arg(index, &[ArgType::Size])?;
+        parse_size(value).ok_or_else(|| ArgValueError::Invalid(value.to_string()))
+    }
+
+    /// The argument at `index` as a time, for the arguments the table types
+    /// as times in seconds or milliseconds.
+    pub fn arg_as_duration(&self, index: usize) -> Result<Duration, ArgValueError> {
+        let value = self.typed_arg(index, &[ArgType::Sec, ArgType::Msec])?;
+        let is_sec = arg_type(&self.directive().directive, index) == Some(ArgType::Sec);
+        let invalid = || ArgValueError::Invalid(value.to_string());
+        let time = parse_time(value, is_sec).ok_or_else(invalid)?;
+        Ok(match is_sec {
+            true => Duration::from_secs(time),
+            false => Duration::from_millis(time),
+        })
+    }
+
+    /// The argument at `index` of a flag directive, `on` as true.
+    pub fn arg_as_bool(&self, index: usize) -> Result<bool, ArgValueError> {
+        let value = self.typed_arg(index, &[ArgType::Flag])?;
+        match value.to_ascii_lowercase().as_str() {
+            "on" => Ok(true),
+            "off" => Ok(false),
+            _ => Err(ArgValueError::Invalid(value.to_string())),
+        }
+    }
+
+    /// The argument at `index` as a number, for the arguments the table
+    /// types as numbers.
+    pub fn arg_as_int(&self, index: usize) -> Result<u64, ArgValueError> {
+        let value = self.typed_arg(index, &[ArgType::Number])?;
+        parse_number(value).ok_or_else(|| ArgValueError::Invalid(value.to_string()))
+    }
+
+    fn typed_arg(&self, index: usize, types: &[ArgType]) -> Result<&str, ArgValueError> {
+        let directive = self.directive();
+        let value = directive.args.get(index).ok_or(ArgValueError::Missing)?;
+        let found = arg_type(&directive.directive, index);
+        if !found.is_some_and(|t| types.contains(&t)) {
+            return Err(ArgValueError::WrongType(found));
+        }
+        if value.contains('$') {
+            return Err(ArgValueError::Variable);
+        }
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::{parse_source, ParseOptions};
+
+    #[test]
+    fn test_arg_types() {
+        assert!(ARG_TYPES
+            .windows(2)
+            .all(|w| (w[0].0, w[0].1) < (w[1].0, w[1].1)));
+        for (name, _, _) in ARG_TYPES {
+            assert!(!directive_specs(name).is_empty(), "{} is not known", name);
+        }
+        assert_eq!(arg_type("keepalive_timeout", 1), Some(ArgType::Sec));
+        assert_eq!(arg_type("gzip", 0), Some(ArgType::Flag));
+        assert_eq!(arg_type("gzip", 1), None);
+        assert_eq!(arg_type("root", 0), None);
+    }
+
+    #[test]
+    fn test_typed_args() {
+        let source = "events {
+    worker_connections 1024;
+}
+http {
+    client_max_body_size 10m;
+    keepalive_timeout 75s 60;
+    ssl_session_timeout 1h;
+    gzip On;
+    proxy_buffers 8 4k;
+    proxy_read_timeout 500ms;
+    proxy_send_timeout 90;
+    proxy_connect_timeout $timeout;
+    ssl_session_timeout 500ms;
+}
+";
+        let payload = parse_source(source, &ParseOptions::default());
+        let arg = |name: &str, nth: usize| payload.find(name)[nth].clone();
+
+        assert_eq!(arg("worker_connections", 0).arg_as_int(0), Ok(1024));
+        assert_eq!(arg("client_max_body_size", 0).arg_as_size(0), Ok(10 << 20));
+        let keepalive = arg("keepalive_timeout", 0);
+        assert_eq!(keepalive.arg_as_duration(0), Ok(Duration::from_secs(75)));
+        assert_eq!(keepalive.arg_as_duration(1), Ok(Duration::from_secs(60)));
+        assert_eq!(keepalive.arg_as_duration(2), Err(ArgValueError::Missing));
+        assert_eq!(
+            arg("ssl_session_timeout", 0).arg_as_duration(0),
+            Ok(Duration::from_secs(3600))
+        );
+        assert_eq!(arg("gzip", 0).arg_as_bool(0), Ok(true));
+        assert_eq!(arg("proxy_buffers", 0).arg_as_int(0), Ok(8));
+        assert_eq!(arg("proxy_buffers", 0).arg_as_size(1), Ok(4096));
+        assert_eq!(
+            arg("proxy_read_timeout", 0).arg_as_duration(0),
+            Ok(Duration::from_millis(500))
+        );
+        assert_eq!(
+            arg("proxy_send_timeout", 0).arg_as_duration(0),
+            Ok(Duration::from_secs(90))
+        );
+
+        assert_eq!(
+            arg("proxy_connect_timeout", 0).arg_as_duration(0),
+            Err(ArgValueError::Variable)
+        );
+        // times kept in seconds take no milliseconds
+        assert_eq!(
+            arg("ssl_session_timeout", 1).arg_as_duration(0),
+            Err(ArgValueError::Invalid("500ms".to_string()))
+        );
+        assert_eq!(
+            arg("client_max_body_size", 0).arg_as_duration(0),
+            Err(ArgValueError::WrongType(Some(ArgType::Size)))
+        );
+        assert_eq!(
+            arg("proxy_buffers", 0).arg_as_size(0),
+            Err(ArgValueError::WrongType(Some(ArgType::Number)))
+        );
+    }
+}
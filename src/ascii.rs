@@ -0,0 +1,321 @@
+use std::fmt;
+
+use crate::lint::{Finding, Severity};
+use crate::parse::{Directive, Payload};
+
+/// A character outside printable ASCII found in a string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NonAsciiChar {
+    pub ch: char,
+    /// Byte offset of the character in the string.
+    pub offset: usize,
+}
+
+impl fmt::Display for NonAsciiChar {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "U+{:04X}", u32::from(self.ch))
+    }
+}
+
+/// The first character of `s` that is not printable ASCII: a control
+/// character, tabs and line breaks included, or anything past `~`, like
+/// a smart quote or a non-breaking hyphen.
+pub fn contains_non_ascii(s: &str) -> Option<NonAsciiChar> {
+    s.char_indices()
+        .find(|&(_, ch)| !matches!(ch, ' '..='~'))
+        .map(|(offset, ch)| NonAsciiChar { ch, offset })
+}
+
+// characters that do not show, or show as a plain space, in most editors
+// while nginx takes them as part of the value
+const INVISIBLE_CHARS: &[(char, &str)] = &[
+    ('\u{00a0}', "no-break space"),
+    ('\u{00ad}', "soft hyphen"),
+    ('\u{061c}', "arabic letter mark"),
+    ('\u{180e}', "mongolian vowel separator"),
+    ('\u{2007}', "figure space"),
+    ('\u{200b}', "zero width space"),
+    ('\u{200c}', "zero width non-joiner"),
+    ('\u{200d}', "zero width joiner"),
+    ('\u{200e}', "left-to-right mark"),
+    ('\u{200f}', "right-to-left mark"),
+    ('\u{202a}', "left-to-right embedding"),
+    ('\u{202b}', "right-to-left embedding"),
+    ('\u{202c}', "pop directional formatting"),
+    ('\u{202d}', "left-to-right override"),
+    ('\u{202e}', "right-to-left override"),
+    ('\u{202f}', "narrow no-break space"),
+    ('\u{2060}', "word joiner"),
+    ('\u{2066}', "left-to-right isolate"),
+    ('\u{2067}', "right-to-left isolate"),
+    ('\u{2068}', "first strong isolate"),
+    ('\u{2069}', "pop directional isolate"),
+    ('\u{feff}', "byte order mark"),
+];
+
+/// An invisible or space-like character found in a string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvisibleChar {
+    pub ch: char,
+    /// Byte offset of the character in the string.
+    pub offset: usize,
+    /// Lowercase Unicode name, like `zero width space`.
+    pub name: &'static str,
+}
+
+impl fmt::Display for InvisibleChar {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "U+{:04X} ({})", u32::from(self.ch), self.name)
+    }
+}
+
+/// The first character of `s` that is invisible or easily taken for a
+/// plain space, like a zero width space, a no-break space, a byte order
+/// mark or a bidirectional control.
+pub fn contains_invisible_unicode(s: &str) -> Option<InvisibleChar> {
+    s.char_indices().find_map(|(offset, ch)| {
+        let &(_, name) = INVISIBLE_CHARS.iter().find(|(c, _)| *c == ch)?;
+        Some(InvisibleChar { ch, offset, name })
+    })
+}
+
+// the first character `find` finds in the directive, with the arg it is
+// in, None for the name or the text of a comment; raw directives are not
+// looked at
+fn find_in_directive<C>(
+    directive: &Directive,
+    exempt_comments: bool,
+    find: fn(&str) -> Option<C>,
+) -> Option<(Option<usize>, C)> {
+    if directive.is_raw() {
+        return None;
+    }
+    if directive.is_comment() {
+        if exempt_comments {
+            return None;
+        }
+        let comment = directive.comment.as_deref().unwrap_or("");
+        return find(comment).map(|c| (None, c));
+    }
+    find(&directive.directive).map(|c| (None, c)).or_else(|| {
+        directive.args.iter().enumerate().find_map(|(i, arg)| {
+            let c = find(arg)?;
+            Some((Some(i), c))
+        })
+    })
+}
+
+// the first character of the directive outside printable ASCII
+pub(crate) fn directive_non_ascii(
+    directive: &Directive,
+    exempt_comments: bool,
+) -> Option<(Option<usize>, NonAsciiChar)> {
+    find_in_directive(directive, exempt_comments, contains_non_ascii)
+}
+
+// where in a directive a character was found, for messages
+fn describe_place(directive: &str, arg: Option<usize>) -> String {
+    match arg {
+        Some(i) => format!("arg {} of \"{}\"", i + 1, directive),
+        None if directive == "#" => "the comment".to_string(),
+        None => format!("the name of \"{}\"", directive),
+    }
+}
+
+// the message for a character outside printable ASCII, as lint and build
+// report it
+pub(crate) fn non_ascii_message(directive: &str, arg: Option<usize>, ch: char) -> String {
+    format!(
+        "{} contains U+{:04X}, which is not printable ASCII",
+        describe_place(directive, arg),
+        u32::from(ch)
+    )
+}
+
+// a finding of `kind` for each directive holding a character `find` finds,
+// pointing at the arg it is in
+fn check_chars<C>(
+    payload: &Payload,
+    exempt_comments: bool,
+    find: fn(&str) -> Option<C>,
+    (kind, severity): (&str, Severity),
+    message: impl Fn(&str, Option<usize>, C) -> String,
+) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    for d in payload.directives() {
+        let directive = d.directive();
+        let Some((arg, c)) = find_in_directive(directive, exempt_comments, find) else {
+            continue;
+        };
+        let finding = Finding::new(
+            kind,
+            severity,
+            message(&directive.directive, arg, c),
+            d.file_name(),
+            directive.line,
+        );
+        findings.push(match arg {
+            Some(i) => finding.at_arg(directive, i),
+            None => finding,
+        });
+    }
+    findings
+}
+
+// the first character outside printable ASCII of each directive
+pub(crate) fn check_non_ascii(payload: &Payload, exempt_comments: bool) -> Vec<Finding> {
+    check_chars(
+        payload,
+        exempt_comments,
+        contains_non_ascii,
+        ("non-ascii", Severity::Error),
+        |directive, arg, c| non_ascii_message(directive, arg, c.ch),
+    )
+}
+
+// the first invisible character of each directive, in its name (the keys of
+// map blocks and the like) or one of its args
+pub(crate) fn check_invisible_unicode(payload: &Payload) -> Vec<Finding> {
+    check_chars(
+        payload,
+        true,
+        contains_invisible_unicode,
+        ("invisible-unicode", Severity::Warning),
+        |directive, arg, c| {
+            format!(
+                "{} contains the invisible character {}",
+                describe_place(directive, arg),
+                c
+            )
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lint::LintOptions;
+    use crate::parse::{parse_source, ParseOptions};
+
+    #[test]
+    fn test_check_non_ascii() {
+        assert_eq!(contains_non_ascii("example.com ~*^/a"), None);
+        assert_eq!(
+            contains_non_ascii("a\tb"),
+            Some(NonAsciiChar {
+                ch: '\t',
+                offset: 1
+            })
+        );
+
+        let source = "# caf\u{e9} config
+http {
+    server {
+        server_name www.ex\u{2011}ample.com;
+        add_header X-Note \"don\u{2019}t\";
+    }
+}
+";
+        let options = ParseOptions::default().comments(true);
+        let payload = parse_source(source, &options);
+        let findings: Vec<String> = check_non_ascii(&payload, false)
+            .iter()
+            .map(|f| f.to_string())
+            .collect();
+        assert_eq!(
+            findings,
+            [
+                "error: the comment contains U+00E9, which is not printable ASCII \
+                 in nginx.conf:1 [non-ascii]",
+                "error: arg 1 of \"server_name\" contains U+2011, which is not printable \
+                 ASCII in nginx.conf:4 [non-ascii]",
+                "error: arg 2 of \"add_header\" contains U+2019, which is not printable \
+                 ASCII in nginx.conf:5 [non-ascii]",
+            ]
+        );
+        assert_eq!(check_non_ascii(&payload, true).len(), 2);
+
+        // the check is opt-in
+        let count = |options: LintOptions| {
+            payload
+                .lint_with(&options)
+                .iter()
+                .filter(|f| f.kind == "non-ascii")
+                .count()
+        };
+        assert_eq!(count(LintOptions::default()), 0);
+        assert_eq!(count(LintOptions::default().ascii_only(true)), 3);
+        let exempt = LintOptions::default()
+            .ascii_only(true)
+            .ascii_exempt_comments(true);
+        assert_eq!(count(exempt), 2);
+
+        let clean = parse_source(
+            "http {\n    server {\n        server_name example.com;\n    }\n}\n",
+            &options,
+        );
+        assert_eq!(check_non_ascii(&clean, false), []);
+    }
+
+    #[test]
+    fn test_contains_invisible_unicode() {
+        assert_eq!(contains_invisible_unicode("example.com"), None);
+        assert_eq!(contains_invisible_unicode("héllo wörld"), None);
+        assert_eq!(
+            contains_invisible_unicode("a\u{200b}b\u{00a0}"),
+            Some(InvisibleChar {
+                ch: '\u{200b}',
+                offset: 1,
+                name: "zero width space"
+            })
+        );
+        let bidi = contains_invisible_unicode("admin\u{202e}txt").unwrap();
+        assert_eq!(bidi.to_string(), "U+202E (right-to-left override)");
+    }
+
+    #[test]
+    fn test_check_invisible_unicode() {
+        // nginx takes a no-break space as part of the word, quoted or not
+        let source = "http {
+    map $host $backend {
+        \u{feff}example.com a;
+    }
+    server {
+        server_name example.com www.example\u{00a0}.com \"www2.example\u{00a0}.com\";
+        location / {
+            proxy_pass http://backend\u{200b}/;
+        }
+    }
+}
+";
+        let payload = parse_source(source, &ParseOptions::default());
+        let findings: Vec<String> = check_invisible_unicode(&payload)
+            .iter()
+            .map(|f| f.to_string())
+            .collect();
+        assert_eq!(
+            findings,
+            [
+                "warning: the name of \"\u{feff}example.com\" contains the invisible character \
+                 U+FEFF (byte order mark) in nginx.conf:3 [invisible-unicode]",
+                "warning: arg 2 of \"server_name\" contains the invisible character U+00A0 \
+                 (no-break space) in nginx.conf:6 [invisible-unicode]",
+                "warning: arg 1 of \"proxy_pass\" contains the invisible character U+200B \
+                 (zero width space) in nginx.conf:8 [invisible-unicode]",
+            ]
+        );
+
+        // the check is opt-in
+        let kinds = |options: LintOptions| payload.lint_with(&options).by_kind().len();
+        assert_eq!(
+            kinds(LintOptions::default().invisible_unicode(true)),
+            kinds(LintOptions::default()) + 1
+        );
+
+        let clean = parse_source(
+            "http {\n    server {\n        server_name example.com;\n    }\n}\n",
+            &ParseOptions::default(),
+        );
+        assert_eq!(check_invisible_unicode(&clean), []);
+    }
+}
@@ -0,0 +1,203 @@
+use std::borrow::Cow;
+
+use crate::error::ParseError;
+use crate::lex::lex_borrowed;
+use crate::parse::{parse_file, ConfigFile, Directive, ParseOptions, Payload, Status, TreeStr};
+
+/// A [`Payload`] whose names and args borrow from the source they were
+/// parsed from, see [`parse_borrowed`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct BorrowedPayload<'src> {
+    pub status: Status,
+    pub errors: Vec<ParseError>,
+    pub config: Vec<BorrowedConfigFile<'src>>,
+}
+
+/// A [`ConfigFile`] of a [`BorrowedPayload`].
+pub type BorrowedConfigFile<'src> = ConfigFile<Cow<'src, str>>;
+
+/// A [`Directive`] whose name, args and comment are slices of the source,
+/// unless they had to be unescaped.
+pub type BorrowedDirective<'src> = Directive<Cow<'src, str>>;
+
+impl<'src> TreeStr for Cow<'src, str> {
+    fn from_static(s: &'static str) -> Self {
+        Cow::Borrowed(s)
+    }
+
+    fn part(self, part: impl FnOnce(&str) -> &str) -> Self {
+        match self {
+            Cow::Borrowed(value) => Cow::Borrowed(part(value)),
+            Cow::Owned(value) => Cow::Owned(part(&value).to_string()),
+        }
+    }
+}
+
+impl BorrowedPayload<'_> {
+    /// The owned payload, as parsing the source with an owned parse gives.
+    pub fn to_owned(&self) -> Payload {
+        let config = self
+            .config
+            .iter()
+            .map(|file| ConfigFile {
+                file: file.file.clone(),
+                status: file.status,
+                errors: file.errors.clone(),
+                parsed: file
+                    .parsed
+                    .iter()
+                    .map(BorrowedDirective::to_owned)
+                    .collect(),
+                stubbed: file.stubbed,
+                shebang: file.shebang.clone(),
+            })
+            .collect();
+        Payload::from_config(config)
+    }
+}
+
+impl BorrowedDirective<'_> {
+    pub fn to_owned(&self) -> Directive {
+        Directive {
+            directive: self.directive.to_string(),
+            line: self.line,
+            args: self.args.iter().map(|a| a.to_string()).collect(),
+            includes: self.includes.clone(),
+            block: self
+                .block
+                .as_ref()
+                .map(|block| block.iter().map(BorrowedDirective::to_owned).collect()),
+            comment: self.comment.as_ref().map(|c| c.to_string()),
+            block_end_line: self.block_end_line,
+            in_if: self.in_if,
+            raw: self.raw.clone(),
+            raw_source: self.raw_source.clone(),
+            placeholder: self.placeholder,
+            arg_spans: self.arg_spans.clone(),
+            file: self.file.clone(),
+        }
+    }
+}
+
+/// Parses a config held in memory as the single file `file`, like
+/// [`Crossplane::parse_str`](crate::Crossplane::parse_str), without copying
+/// the names and args out of `source`. Those that are not written in the
+/// source as they are read, like quoted strings with escaped quotes, are
+/// copied.
+pub fn parse_borrowed<'src>(
+    source: &'src str,
+    file: &str,
+    options: &ParseOptions,
+) -> BorrowedPayload<'src> {
+    let tokens = lex_borrowed(source, &options.effective_lex_options());
+    let mut config = parse_file(tokens, source, file, options);
+    config.status = match config.errors.is_empty() {
+        true => Status::Ok,
+        false => Status::Failed,
+    };
+    BorrowedPayload {
+        status: config.status,
+        errors: config.errors.clone(),
+        config: vec![config],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::parse::{parse_named, UnknownBlockPolicy};
+
+    // sources the borrowed lexer and parser must agree with the owned ones
+    // on, including the quirks of the lexer
+    const SOURCES: [&str; 15] = [
+        "if ( $a = b ) { return 403; }\nif ($x) { }\nif () { }\n",
+        "set $x ${var}; set $y ${var}suffix; set $z pre${var} ;\n",
+        "set $x \"${var\";\nset $y ${var} tail;\n",
+        "return 200 \"a \\\"quoted\\\" b\"; # trailing\nreturn 200 'it\\'s';\n",
+        "add_header X \"\\\\\"; set $a \\;b; set $b a}b;\n",
+        "http { server { listen 80; # a\n location / { } } }\n",
+        "#!/usr/sbin/nginx\nevents { }\n",
+        "map $a $b { default 0; \"~^x\" 1; include m.map; }\n",
+        "server_name a # c1\n b # c2\n ;\n",
+        "",
+        "events { worker_connections 1024; }",
+        "unquoted\"quote\"inside word;\nlocation ~ \"^/a{2}\" { }\n",
+        "http { map $a $b { foo; } }\n",
+        "http { upstream u { least_conn; hash $a; } }\n",
+        "http { sendfile maybe; gzip { } }\nevents;\n",
+    ];
+
+    // sources the owned parser reports errors for
+    const INVALID: [&str; 5] = [
+        "http {\n",
+        "}\n",
+        "events { worker_connections 1024 }\n",
+        "map $a $b { ~^a{2} 1; }\n",
+        "set $x ${var",
+    ];
+
+    fn option_sets() -> Vec<ParseOptions> {
+        vec![
+            ParseOptions::default(),
+            ParseOptions::default().comments(true),
+            ParseOptions::default().catch_errors(true).comments(true),
+            ParseOptions::default().strict(true),
+            ParseOptions::default().check_ctx(true),
+            ParseOptions::default().check_args(true),
+            ParseOptions::default().unknown_block_policy(UnknownBlockPolicy::Error),
+            ParseOptions::default().raw_fallback(true),
+            ParseOptions::default()
+                .arg_spans(true)
+                .ignore(&["gzip"])
+                .capture_raw_for(&["listen"]),
+        ]
+    }
+
+    #[test]
+    fn test_parse_borrowed() {
+        let mut sources: Vec<String> = SOURCES.iter().map(|s| s.to_string()).collect();
+        sources.extend(INVALID.iter().map(|s| s.to_string()));
+        for entry in fs::read_dir("configs").unwrap() {
+            let path = entry.unwrap().path().join("nginx.conf");
+            if let Ok(source) = fs::read_to_string(path) {
+                sources.push(source);
+            }
+        }
+        for options in option_sets() {
+            for source in &sources {
+                let borrowed = parse_borrowed(source, "nginx.conf", &options);
+                let owned = parse_named(source, "nginx.conf", &options);
+                assert_eq!(borrowed.to_owned(), owned, "{:?} {:?}", source, options);
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_borrowed_borrows() {
+        let source = "http {\n    server_name example.com;\n    return 200 \"a \\\"b\\\"\";\n}\n";
+        let payload = parse_borrowed(source, "nginx.conf", &ParseOptions::default());
+        let http = &payload.config[0].parsed[0];
+        assert!(matches!(http.directive, Cow::Borrowed("http")));
+        let block = http.block.as_ref().unwrap();
+        assert!(matches!(block[0].args[0], Cow::Borrowed("example.com")));
+        // an escaped quote is unescaped into an owned arg
+        assert!(matches!(&block[1].args[1], Cow::Owned(arg) if arg == "a \"b\""));
+
+        // so are the words of a config with other line endings
+        let payload = parse_borrowed(
+            "gzip on;\r\nroot \"/a\r\nb\";\r\n",
+            "nginx.conf",
+            &ParseOptions::default(),
+        );
+        let parsed = &payload.config[0].parsed;
+        assert!(matches!(parsed[0].args[0], Cow::Borrowed("on")));
+        assert!(matches!(&parsed[1].args[0], Cow::Owned(arg) if arg == "/a\nb"));
+
+        for source in INVALID {
+            let payload = parse_borrowed(source, "nginx.conf", &ParseOptions::default());
+            assert_eq!(payload.status, Status::Failed, "{:?}", source);
+        }
+    }
+}
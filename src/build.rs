@@ -0,0 +1,1746 @@
+use std::borrow::Cow;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+use crate::analyze::directive_specs;
+use crate::ascii::{directive_non_ascii, non_ascii_message};
+use crate::parse::{ConfigFile, Directive, Payload, Status, ARGS_ONLY_BLOCKS};
+use crate::tree::DirectiveLocation;
+
+// first words of the default banner, used to recognize it in built files
+const BANNER_PREFIX: &str = " This config was built by ";
+const BANNER_SOURCE_PREFIX: &str = " Source: ";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HeaderMode {
+    None,
+    /// The crate name and version, plus the source path when known.
+    Default,
+    /// Custom text, emitted as one comment line per line of text.
+    Custom(String),
+}
+
+/// What [`build_files`] does with files of a payload built to the same path.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DuplicatePolicy {
+    /// Write nothing and fail with an `AlreadyExists` error.
+    #[default]
+    Error,
+    /// Write the last of the files.
+    Overwrite,
+    /// Write the directives of all the files one after another, those of
+    /// each file after the first following a `# merged from <file>` comment.
+    Merge,
+}
+
+#[derive(Debug, Clone)]
+pub struct BuildOptions {
+    pub(crate) indent: usize,
+    pub(crate) tabs: bool,
+    pub(crate) header: HeaderMode,
+    pub(crate) canonical_order: bool,
+    pub(crate) preserve_line_numbers: bool,
+    pub(crate) inline_includes: bool,
+    pub(crate) force: bool,
+    pub(crate) on_duplicate: DuplicatePolicy,
+    pub(crate) dedupe_identical: bool,
+    pub(crate) wrap_comments: Option<usize>,
+    pub(crate) move_long_comments: bool,
+    pub(crate) ascii_only: bool,
+    pub(crate) ascii_exempt_comments: bool,
+}
+
+impl Default for BuildOptions {
+    fn default() -> Self {
+        BuildOptions {
+            indent: 4,
+            tabs: false,
+            header: HeaderMode::None,
+            canonical_order: false,
+            preserve_line_numbers: false,
+            inline_includes: false,
+            force: false,
+            on_duplicate: DuplicatePolicy::default(),
+            dedupe_identical: false,
+            wrap_comments: None,
+            move_long_comments: false,
+            ascii_only: false,
+            ascii_exempt_comments: false,
+        }
+    }
+}
+
+impl BuildOptions {
+    /// Number of spaces per nesting level.
+    pub fn indent(mut self, indent: usize) -> Self {
+        self.indent = indent;
+        self
+    }
+
+    /// Indent with tabs instead of spaces.
+    pub fn tabs(mut self, tabs: bool) -> Self {
+        self.tabs = tabs;
+        self
+    }
+
+    /// Comment banner emitted at the top of every built file.
+    pub fn header(mut self, header: HeaderMode) -> Self {
+        self.header = header;
+        self
+    }
+
+    /// Reorder the directives of every block so equivalent configs build to
+    /// the same text: includes first, then simple directives by name, then
+    /// order-sensitive directives as they were, then blocks by name and first
+    /// arg. Comments move with the directive they precede. The directives
+    /// kept in order are those the directive table marks as order-sensitive,
+    /// see [`DirectiveSpec`](crate::DirectiveSpec), raw statements and regex
+    /// locations.
+    pub fn canonical_order(mut self, canonical_order: bool) -> Self {
+        self.canonical_order = canonical_order;
+        self
+    }
+
+    /// Pad the output with blank lines so every directive, and the `}`
+    /// closing every block, lands on the line it was parsed from. Directives
+    /// that would have to move up, because of reordering or because several
+    /// shared a line, are built right after what precedes them instead, see
+    /// [`build_with_line_report`].
+    pub fn preserve_line_numbers(mut self, preserve_line_numbers: bool) -> Self {
+        self.preserve_line_numbers = preserve_line_numbers;
+        self
+    }
+
+    /// Build the files pulled in by `include` directives in place of the
+    /// directives, between `# begin include <path>` and `# end include`
+    /// comments, when building a payload with [`build_payload`] or
+    /// [`build_files`], which then only writes the root file. Includes of
+    /// files that failed to parse are kept, after a comment saying why.
+    /// Inlined files do not keep their line numbers.
+    pub fn inline_includes(mut self, inline_includes: bool) -> Self {
+        self.inline_includes = inline_includes;
+        self
+    }
+
+    /// Let [`build_files`] write payloads that [`validate_buildable`] finds
+    /// issues in, building them as best it can.
+    pub fn force(mut self, force: bool) -> Self {
+        self.force = force;
+        self
+    }
+
+    /// What [`build_files`] does with files built to the same path, like
+    /// two entries for one file or `a.conf` and `conf/../a.conf`.
+    pub fn on_duplicate(mut self, on_duplicate: DuplicatePolicy) -> Self {
+        self.on_duplicate = on_duplicate;
+        self
+    }
+
+    /// Let [`build_files`] write files built to the same path once when
+    /// they build to the same text, whatever [`on_duplicate`] says.
+    ///
+    /// [`on_duplicate`]: BuildOptions::on_duplicate
+    pub fn dedupe_identical(mut self, dedupe_identical: bool) -> Self {
+        self.dedupe_identical = dedupe_identical;
+        self
+    }
+
+    /// Re-wrap comments on their own line at word boundaries so that lines
+    /// fit in this many columns, keeping the indentation and the spaces
+    /// after `#`. Comments that look like code or pragmas are left alone:
+    /// commented out directives, `crossplane-ignore` comments, `#!` lines and
+    /// banners. Inline comments are never wrapped.
+    pub fn wrap_comments(mut self, wrap_comments: Option<usize>) -> Self {
+        self.wrap_comments = wrap_comments;
+        self
+    }
+
+    /// Move inline comments that would take their line past
+    /// [`wrap_comments`] to a line of their own after the directive.
+    ///
+    /// [`wrap_comments`]: BuildOptions::wrap_comments
+    pub fn move_long_comments(mut self, move_long_comments: bool) -> Self {
+        self.move_long_comments = move_long_comments;
+        self
+    }
+
+    /// Have [`build_files`] refuse payloads with directive names, args or
+    /// comments holding anything but printable ASCII, see
+    /// [`validate_buildable_with`].
+    pub fn ascii_only(mut self, ascii_only: bool) -> Self {
+        self.ascii_only = ascii_only;
+        self
+    }
+
+    /// Leave comments out of [`ascii_only`](BuildOptions::ascii_only).
+    pub fn ascii_exempt_comments(mut self, exempt: bool) -> Self {
+        self.ascii_exempt_comments = exempt;
+        self
+    }
+}
+
+/// A directive that [`BuildOptions::preserve_line_numbers`] could not put on
+/// its original line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MisplacedDirective {
+    pub directive: String,
+    /// The line the directive was parsed from.
+    pub line: usize,
+    /// The line it was built on.
+    pub built_line: usize,
+}
+
+/// A directive of a payload that cannot be built as it is, see
+/// [`validate_buildable`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BuildIssue {
+    pub location: DirectiveLocation,
+    pub line: usize,
+    pub kind: BuildIssueKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BuildIssueKind {
+    /// The directive has no name, nor a comment or raw text.
+    EmptyName,
+    /// The arg at `index` holds a line break that would not read back the
+    /// same: a carriage return, read as a newline, or a line break in a
+    /// template placeholder, built unquoted.
+    LineBreakInArg { index: usize },
+    /// The directive has raw text and a block, of which only the raw text
+    /// is built.
+    RawWithBlock,
+    /// The include lists the file at `index`, which is not in the payload.
+    IncludeNotInPayload { index: usize },
+    /// A `#` directive without comment text.
+    MissingComment,
+    /// The directive holds a character outside printable ASCII, in the arg
+    /// at `index`, or in its name or comment text when None. Only reported
+    /// with [`BuildOptions::ascii_only`].
+    NotAscii {
+        directive: String,
+        index: Option<usize>,
+        ch: char,
+    },
+    /// The payload was parsed with options leaving these directives out,
+    /// or read from JSON marked lossy when empty, see
+    /// [`ParseProfile`](crate::ParseProfile). Reported for the root file.
+    LossyParse { ignored: Vec<String> },
+    /// The file failed to parse, so it holds only what could be parsed.
+    FailedFile,
+}
+
+impl fmt::Display for BuildIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let path: Vec<String> = self.location.path.iter().map(|i| i.to_string()).collect();
+        if path.is_empty() {
+            write!(f, "file {}: ", self.location.file)?;
+        } else {
+            write!(
+                f,
+                "file {} directive {} (line {}): ",
+                self.location.file,
+                path.join("."),
+                self.line
+            )?;
+        }
+        match &self.kind {
+            BuildIssueKind::EmptyName => write!(f, "directive has no name"),
+            BuildIssueKind::LineBreakInArg { index } => {
+                write!(f, "arg {} holds a line break that cannot be built", index)
+            }
+            BuildIssueKind::RawWithBlock => write!(f, "directive has both raw text and a block"),
+            BuildIssueKind::IncludeNotInPayload { index } => {
+                write!(
+                    f,
+                    "include lists file {}, which is not in the payload",
+                    index
+                )
+            }
+            BuildIssueKind::MissingComment => write!(f, "comment has no text"),
+            BuildIssueKind::NotAscii {
+                directive,
+                index,
+                ch,
+            } => write!(f, "{}", non_ascii_message(directive, *index, *ch)),
+            BuildIssueKind::LossyParse { ignored } if ignored.is_empty() => {
+                write!(
+                    f,
+                    "payload was marked lossy, building it may drop directives"
+                )
+            }
+            BuildIssueKind::LossyParse { ignored } => write!(
+                f,
+                "payload was parsed ignoring {}, building it would drop them",
+                ignored.join(", ")
+            ),
+            BuildIssueKind::FailedFile => write!(
+                f,
+                "file failed to parse, building it would drop what could not be parsed"
+            ),
+        }
+    }
+}
+
+/// Every directive or file of the payload that cannot be built as it is,
+/// so that problems are known before [`build_files`] writes anything.
+pub fn validate_buildable(payload: &Payload) -> Vec<BuildIssue> {
+    validate_buildable_with(payload, &BuildOptions::default())
+}
+
+/// Like [`validate_buildable`], also reporting the issues only some options
+/// have, like characters outside printable ASCII with
+/// [`BuildOptions::ascii_only`].
+pub fn validate_buildable_with(payload: &Payload, options: &BuildOptions) -> Vec<BuildIssue> {
+    let mut issues = Vec::new();
+    let file_issue = |file, kind| BuildIssue {
+        location: DirectiveLocation {
+            file,
+            path: Vec::new(),
+        },
+        line: 0,
+        kind,
+    };
+    let profile = payload.parse_profile();
+    if profile.drops_directives() {
+        let ignored = profile.ignored.clone();
+        issues.push(file_issue(0, BuildIssueKind::LossyParse { ignored }));
+    }
+    // included files are not built when inlined
+    let built = if options.inline_includes {
+        1
+    } else {
+        payload.config.len()
+    };
+    for (file, config) in payload.config.iter().enumerate().take(built) {
+        if config.status == Status::Failed {
+            issues.push(file_issue(file, BuildIssueKind::FailedFile));
+        }
+    }
+    for (file, config) in payload.config.iter().enumerate() {
+        let mut path = Vec::new();
+        validate_block(
+            payload,
+            options,
+            &config.parsed,
+            file,
+            &mut path,
+            &mut issues,
+        );
+    }
+    issues
+}
+
+fn validate_block(
+    payload: &Payload,
+    options: &BuildOptions,
+    block: &[Directive],
+    file: usize,
+    path: &mut Vec<usize>,
+    issues: &mut Vec<BuildIssue>,
+) {
+    for (i, stmt) in block.iter().enumerate() {
+        path.push(i);
+        let mut issue = |kind| {
+            issues.push(BuildIssue {
+                location: DirectiveLocation {
+                    file,
+                    path: path.clone(),
+                },
+                line: stmt.line,
+                kind,
+            })
+        };
+
+        if stmt.raw.is_some() {
+            if stmt.block.is_some() {
+                issue(BuildIssueKind::RawWithBlock);
+            }
+        } else if stmt.directive == "#" && stmt.comment.is_none() {
+            issue(BuildIssueKind::MissingComment);
+        } else if stmt.directive.is_empty() && stmt.comment.is_none() {
+            issue(BuildIssueKind::EmptyName);
+        }
+        for (index, arg) in stmt.args.iter().enumerate() {
+            let unquoted = stmt.placeholder && has_placeholder(arg);
+            if arg.contains('\r') || (unquoted && arg.contains('\n')) {
+                issue(BuildIssueKind::LineBreakInArg { index });
+            }
+        }
+        for &index in stmt.includes.iter().flatten() {
+            if index >= payload.config.len() {
+                issue(BuildIssueKind::IncludeNotInPayload { index });
+            }
+        }
+        if options.ascii_only {
+            if let Some((index, c)) = directive_non_ascii(stmt, options.ascii_exempt_comments) {
+                issue(BuildIssueKind::NotAscii {
+                    directive: stmt.directive.clone(),
+                    index,
+                    ch: c.ch,
+                });
+            }
+        }
+
+        if let (None, Some(children)) = (&stmt.raw, &stmt.block) {
+            validate_block(payload, options, children, file, path, issues);
+        }
+        path.pop();
+    }
+}
+
+pub fn build(directives: &[Directive], options: &BuildOptions) -> String {
+    build_source(directives, options, None, None, None).0
+}
+
+/// Builds the root file of the payload, see
+/// [`inline_includes`](BuildOptions::inline_includes).
+pub fn build_payload(payload: &Payload, options: &BuildOptions) -> String {
+    let Some(root) = payload.config.first() else {
+        return String::new();
+    };
+    let inline = options.inline_includes.then_some((payload, 0));
+    build_source(
+        &root.parsed,
+        options,
+        Some(&root.file),
+        root.shebang(),
+        inline,
+    )
+    .0
+}
+
+/// Like [`build`], also returning the directives that did not land on their
+/// original line when preserving line numbers. Directives without a line,
+/// like ones made by hand, are never reported.
+pub fn build_with_line_report(
+    directives: &[Directive],
+    options: &BuildOptions,
+) -> (String, Vec<MisplacedDirective>) {
+    build_source(directives, options, None, None, None)
+}
+
+/// Builds every file of the payload, creating missing directories. Relative
+/// file paths are resolved against `dirname`. Only the root file is built
+/// when [inlining includes](BuildOptions::inline_includes). Payloads with
+/// [issues](validate_buildable_with) are refused with an `InvalidData` error,
+/// unless [forced](BuildOptions::force). Files built to the same path are
+/// handled as [`BuildOptions::on_duplicate`] says, and nothing is written
+/// when that fails.
+pub fn build_files<P: AsRef<Path>>(
+    payload: &Payload,
+    dirname: P,
+    options: &BuildOptions,
+) -> io::Result<()> {
+    if !options.force {
+        let issues = validate_buildable_with(payload, options);
+        if !issues.is_empty() {
+            let issues: Vec<String> = issues.iter().map(|i| i.to_string()).collect();
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                issues.join("\n"),
+            ));
+        }
+    }
+    let files = if options.inline_includes {
+        &payload.config[..payload.config.len().min(1)]
+    } else {
+        &payload.config[..]
+    };
+    let build = |i: usize, config: &ConfigFile, parsed: &[Directive]| {
+        let (output, _) = build_source(
+            parsed,
+            options,
+            Some(&config.file),
+            config.shebang(),
+            options.inline_includes.then_some((payload, i)),
+        );
+        output.trim_end().to_string() + "\n"
+    };
+
+    // the files built to each path, in the order the paths first come up
+    let mut outputs: Vec<(PathBuf, PathBuf, Vec<usize>)> = Vec::new();
+    for (i, config) in files.iter().enumerate() {
+        let path = dirname.as_ref().join(&config.file);
+        let key = output_key(&path);
+        match outputs.iter_mut().find(|(_, k, _)| *k == key) {
+            Some((_, _, same)) => same.push(i),
+            None => outputs.push((path, key, vec![i])),
+        }
+    }
+
+    let mut written = Vec::with_capacity(outputs.len());
+    for (path, _, mut same) in outputs {
+        if options.dedupe_identical {
+            let mut seen = Vec::new();
+            same.retain(|&i| {
+                let output = build(i, &files[i], &files[i].parsed);
+                let new = !seen.contains(&output);
+                seen.push(output);
+                new
+            });
+        }
+        let output = match (&same[..], options.on_duplicate) {
+            (&[i], _) | (&[.., i], DuplicatePolicy::Overwrite) => {
+                build(i, &files[i], &files[i].parsed)
+            }
+            (&[first, ..], DuplicatePolicy::Merge) => {
+                let mut parsed = files[first].parsed.clone();
+                for &i in &same[1..] {
+                    let comment = format!(" merged from {}", files[i].file);
+                    parsed.push(Directive::new_comment(comment, 0));
+                    parsed.extend(files[i].parsed.iter().cloned());
+                }
+                build(first, &files[first], &parsed)
+            }
+            _ => {
+                let names: Vec<String> = same
+                    .iter()
+                    .map(|&i| format!("\"{}\"", files[i].file))
+                    .collect();
+                return Err(io::Error::new(
+                    io::ErrorKind::AlreadyExists,
+                    format!("{} are all built to {}", names.join(", "), path.display()),
+                ));
+            }
+        };
+        written.push((path, output));
+    }
+
+    for (path, output) in written {
+        // make directories that need to be made for the config to be built
+        if let Some(dirpath) = path.parent() {
+            fs::create_dir_all(dirpath)?;
+        }
+        fs::write(path, output)?;
+    }
+    Ok(())
+}
+
+// the path a file is built to with `.` and `..` resolved and the directories
+// that exist so far canonicalized, so two ways to name a file compare equal
+fn output_key(path: &Path) -> PathBuf {
+    let mut normal = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir if normal.file_name().is_some() => {
+                normal.pop();
+            }
+            _ => normal.push(component),
+        }
+    }
+    let mut rest = Vec::new();
+    let mut existing = normal.as_path();
+    loop {
+        if let Ok(canonical) = existing.canonicalize() {
+            return rest
+                .iter()
+                .rev()
+                .fold(canonical, |path, name| path.join(name));
+        }
+        match (existing.parent(), existing.file_name()) {
+            (Some(parent), Some(name)) => {
+                rest.push(name);
+                existing = parent;
+            }
+            _ => return normal,
+        }
+    }
+}
+
+fn build_source<'a>(
+    mut directives: &'a [Directive],
+    options: &BuildOptions,
+    source: Option<&str>,
+    mut shebang: Option<&'a str>,
+    // the payload whose includes are inlined and the index of the file built
+    inline: Option<(&Payload, usize)>,
+) -> (String, Vec<MisplacedDirective>) {
+    // a shebang kept as a comment is built from the comment, ahead of any
+    // banner like one known from the file
+    if let Some((first, rest)) = directives.split_first() {
+        let comment = first.comment.as_deref().unwrap_or_default();
+        if first.is_comment() && first.line <= 1 && comment.starts_with(['!', '%']) {
+            shebang = first.comment.as_deref();
+            directives = rest;
+        }
+    }
+    let header = header_lines(&options.header, source);
+
+    // a banner left over from a previous build is replaced, not repeated
+    let directives = if header.is_empty() {
+        directives
+    } else {
+        &directives[banner_len(directives, &options.header)..]
+    };
+
+    let mut output = String::new();
+    if let Some(shebang) = shebang {
+        output += "#";
+        output += shebang.strip_prefix('#').unwrap_or(shebang);
+        output += "\n";
+    }
+    for line in &header {
+        output += "#";
+        output += line;
+        output += "\n";
+    }
+    if !header.is_empty() {
+        output += "\n";
+    }
+
+    let mut builder = Builder {
+        padding: if options.tabs {
+            "\t".to_string()
+        } else {
+            " ".repeat(options.indent)
+        },
+        preserve_line_numbers: options.preserve_line_numbers,
+        wrap_comments: options.wrap_comments,
+        move_long_comments: options.move_long_comments,
+        misplaced: Vec::new(),
+        payload: inline.map(|(payload, _)| payload),
+        inlining: inline.map(|(_, file)| file).into_iter().collect(),
+    };
+    let directives = if options.canonical_order {
+        Cow::Owned(canonical_order(directives))
+    } else {
+        Cow::Borrowed(directives)
+    };
+    let mut body = String::new();
+    let first_line = output.matches('\n').count() + 1;
+    builder.build_block(&mut body, &directives, 0, 0, first_line);
+    (output + &body, builder.misplaced)
+}
+
+fn canonical_order(block: &[Directive]) -> Vec<Directive> {
+    // a directive together with the comments preceding it and trailing it
+    // on its line
+    let mut entries: Vec<Vec<Directive>> = Vec::new();
+    let mut pending: Vec<Directive> = Vec::new();
+    for stmt in block {
+        let mut stmt = stmt.clone();
+        if stmt.is_comment() {
+            match entries.last_mut() {
+                Some(entry) if pending.is_empty() && entry[entry.len() - 1].line == stmt.line => {
+                    entry.push(stmt)
+                }
+                _ => pending.push(stmt),
+            }
+            continue;
+        }
+
+        if let Some(children) = &stmt.block {
+            if !ARGS_ONLY_BLOCKS.contains(&stmt.directive.as_str()) {
+                stmt.block = Some(canonical_order(children));
+            }
+        }
+        pending.push(stmt);
+        entries.push(std::mem::take(&mut pending));
+    }
+
+    entries.sort_by(|a, b| {
+        let (a, b) = (main_directive(a), main_directive(b));
+        let rank = |d: &Directive| {
+            if d.directive == "include" {
+                0
+            } else if is_order_sensitive(d) {
+                2
+            } else if d.block.is_none() {
+                1
+            } else {
+                3
+            }
+        };
+        match (rank(a), rank(b)) {
+            (1, 1) => a.directive.cmp(&b.directive),
+            (3, 3) => (&a.directive, a.args.first()).cmp(&(&b.directive, b.args.first())),
+            (ra, rb) => ra.cmp(&rb),
+        }
+    });
+
+    // comments at the end of the block stay there
+    entries.into_iter().flatten().chain(pending).collect()
+}
+
+fn main_directive(entry: &[Directive]) -> &Directive {
+    entry.iter().find(|d| !d.is_comment()).unwrap_or(&entry[0])
+}
+
+fn is_order_sensitive(stmt: &Directive) -> bool {
+    // regex locations are matched in the order they appear
+    let regex_location = stmt.directive == "location"
+        && matches!(stmt.args.first().map(String::as_str), Some("~" | "~*"));
+    regex_location
+        || stmt.is_raw()
+        || directive_specs(&stmt.directive)
+            .iter()
+            .any(|s| s.order_sensitive)
+}
+
+// comment texts of the header, without the leading '#'
+fn header_lines(mode: &HeaderMode, source: Option<&str>) -> Vec<String> {
+    match mode {
+        HeaderMode::None => Vec::new(),
+        HeaderMode::Default => {
+            let mut lines = vec![format!(
+                "{}{} {}.",
+                BANNER_PREFIX,
+                env!("CARGO_PKG_NAME"),
+                env!("CARGO_PKG_VERSION")
+            )];
+            if let Some(source) = source {
+                lines.push(format!("{}{}", BANNER_SOURCE_PREFIX, source));
+            }
+            lines
+        }
+        HeaderMode::Custom(text) => text.lines().map(|line| format!(" {}", line)).collect(),
+    }
+}
+
+// number of leading comment directives making up an existing banner
+fn banner_len(directives: &[Directive], mode: &HeaderMode) -> usize {
+    let comments: Vec<&str> = directives
+        .iter()
+        .map_while(|d| {
+            if d.is_comment() {
+                d.comment.as_deref()
+            } else {
+                None
+            }
+        })
+        .collect();
+
+    let is_default = comments
+        .first()
+        .is_some_and(|c| c.starts_with(BANNER_PREFIX));
+    if is_default {
+        let source = comments[1..]
+            .iter()
+            .take_while(|c| c.starts_with(BANNER_SOURCE_PREFIX))
+            .count();
+        return 1 + source;
+    }
+
+    if let HeaderMode::Custom(_) = mode {
+        let lines = header_lines(mode, None);
+        if comments.len() >= lines.len() && comments.iter().zip(&lines).all(|(c, l)| c == l) {
+            return lines.len();
+        }
+    }
+    0
+}
+
+// whether a comment reads as text rather than code, a pragma or a banner
+fn is_prose(comment: &str) -> bool {
+    let text = comment.trim();
+    let banner = !text.chars().any(char::is_alphanumeric)
+        || text.starts_with("==")
+        || text.starts_with("--")
+        || text.starts_with("**")
+        || text.starts_with('#');
+    let code = text.ends_with(';') || text.ends_with('{') || text.ends_with('}');
+    !(banner
+        || code
+        || comment.starts_with('!')
+        || comment.contains("crossplane-ignore")
+        || comment.starts_with(BANNER_PREFIX))
+}
+
+// the lines of a comment wrapped at `width` columns, each starting with `#`
+// and the spaces the comment did, with words longer than a line kept whole
+fn wrap_comment(comment: &str, width: usize) -> Vec<String> {
+    let prefix = format!(
+        "#{}",
+        &comment[..comment.len() - comment.trim_start().len()]
+    );
+    let mut lines = Vec::new();
+    let mut line = prefix.clone();
+    for word in comment.split_whitespace() {
+        if line.len() > prefix.len() {
+            if line.chars().count() + 1 + word.chars().count() > width {
+                lines.push(std::mem::replace(&mut line, prefix.clone()));
+            } else {
+                line.push(' ');
+            }
+        }
+        line += word;
+    }
+    lines.push(line);
+    lines
+}
+
+struct Builder<'a> {
+    padding: String,
+    preserve_line_numbers: bool,
+    wrap_comments: Option<usize>,
+    move_long_comments: bool,
+    misplaced: Vec<MisplacedDirective>,
+    // set when inlining includes, with the files being built, outermost first
+    payload: Option<&'a Payload>,
+    inlining: Vec<usize>,
+}
+
+impl Builder<'_> {
+    // first_line is the line of the output the start of `output` goes on;
+    // returns the number of newlines in `output` once the block is added,
+    // counted as the text is built so long configs are not rescanned
+    fn build_block(
+        &mut self,
+        output: &mut String,
+        block: &[Directive],
+        depth: usize,
+        mut last_line: usize,
+        first_line: usize,
+    ) -> usize {
+        let margin = self.padding.repeat(depth);
+        let mut lines = output.matches('\n').count();
+
+        for stmt in block {
+            // comments on the line of the previous directive stay inline
+            if stmt.is_comment() && stmt.line == last_line && !output.is_empty() {
+                let comment = stmt.comment.as_deref().unwrap_or_default();
+                let inline = self.inline_comment(output, comment, depth, &margin);
+                lines += inline.matches('\n').count();
+                *output += &inline;
+                continue;
+            }
+
+            let mut newlines = usize::from(!output.is_empty());
+            let mut line = first_line;
+            if self.preserve_line_numbers {
+                line += lines + newlines;
+                if stmt.line > line {
+                    newlines += stmt.line - line;
+                    line = stmt.line;
+                } else if stmt.line > 0 && stmt.line < line {
+                    self.misplaced.push(MisplacedDirective {
+                        directive: stmt.directive.clone(),
+                        line: stmt.line,
+                        built_line: line,
+                    });
+                }
+            }
+
+            let (built, built_lines) = if let Some(inlined) = self.inline_include(stmt, depth, line)
+            {
+                inlined
+            } else if let Some(raw) = &stmt.raw {
+                (raw.clone(), raw.matches('\n').count())
+            } else if let Some(comment) = &stmt.comment {
+                let built = self.own_line_comment(comment, &margin);
+                let built_lines = built.matches('\n').count();
+                (built, built_lines)
+            } else {
+                // template placeholders go out as they came in
+                let enquote = |arg: &str| {
+                    if stmt.placeholder && has_placeholder(arg) {
+                        arg.to_string()
+                    } else {
+                        enquote(arg)
+                    }
+                };
+                let directive = enquote(&stmt.directive);
+                let args: Vec<String> = stmt.args.iter().map(|arg| enquote(arg)).collect();
+
+                let mut built = if directive == "if" {
+                    format!("if ({})", args.join(" "))
+                } else if !args.is_empty() {
+                    format!("{} {}", directive, args.join(" "))
+                } else {
+                    directive
+                };
+
+                match &stmt.block {
+                    None => {
+                        built += ";";
+                        let built_lines = built.matches('\n').count();
+                        (built, built_lines)
+                    }
+                    Some(block) => {
+                        built += " {";
+                        let block_lines =
+                            self.build_block(&mut built, block, depth + 1, stmt.line, line);
+                        // the closing brace goes back on its line too
+                        let mut closing = 1;
+                        if let (true, Some(end)) = (self.preserve_line_numbers, stmt.block_end_line)
+                        {
+                            let next = line + block_lines + 1;
+                            closing += end.saturating_sub(next);
+                        }
+                        built += &"\n".repeat(closing);
+                        built += &margin;
+                        built += "}";
+                        (built, block_lines + closing)
+                    }
+                }
+            };
+
+            *output += &"\n".repeat(newlines);
+            *output += &margin;
+            *output += &built;
+            lines += newlines + built_lines;
+            last_line = stmt.line;
+        }
+        lines
+    }
+
+    // an inline comment, or the comment on a line of its own when it would
+    // take the line past the wrapping limit
+    fn inline_comment(&self, output: &str, comment: &str, depth: usize, margin: &str) -> String {
+        let inline = format!(" #{}", comment);
+        let Some(limit) = self.wrap_comments.filter(|_| self.move_long_comments) else {
+            return inline;
+        };
+        // the first line of a block's output is its parent's, indented by
+        // the caller
+        let line = match output.rsplit_once('\n') {
+            Some((_, line)) => line.chars().count(),
+            None => depth.saturating_sub(1) * self.padding.len() + output.chars().count(),
+        };
+        match line + inline.chars().count() > limit {
+            true => format!("\n{}#{}", margin, comment),
+            false => inline,
+        }
+    }
+
+    fn own_line_comment(&self, comment: &str, margin: &str) -> String {
+        let Some(limit) = self.wrap_comments else {
+            return format!("#{}", comment);
+        };
+        let width = limit.saturating_sub(margin.chars().count());
+        if comment.chars().count() < width || !is_prose(comment) {
+            return format!("#{}", comment);
+        }
+        let separator = format!("\n{}", margin);
+        wrap_comment(comment, width).join(&separator)
+    }
+
+    // the files an include directive pulls in, each between marker comments,
+    // or the directive after a comment saying why it is not inlined, with the
+    // number of newlines in the text
+    fn inline_include(
+        &mut self,
+        stmt: &Directive,
+        depth: usize,
+        line: usize,
+    ) -> Option<(String, usize)> {
+        let payload = self.payload?;
+        let files = stmt.includes.as_ref()?;
+        let margin = self.padding.repeat(depth);
+        let arg = stmt.args.first().map(String::as_str).unwrap_or_default();
+
+        let is_pattern = arg.contains(['*', '?', '[']);
+        let problem = if files.is_empty() && !is_pattern {
+            Some("it could not be opened".to_string())
+        } else if let Some(failed) = files
+            .iter()
+            .filter_map(|&i| payload.config.get(i))
+            .find(|f| !f.errors.is_empty())
+        {
+            Some(format!("{} has parse errors", failed.file))
+        } else if files.iter().any(|i| self.inlining.contains(i)) {
+            Some("it includes itself".to_string())
+        } else if files.iter().any(|&i| i >= payload.config.len()) {
+            Some("its files are not in the payload".to_string())
+        } else {
+            None
+        };
+        if let Some(problem) = problem {
+            let args: Vec<String> = stmt.args.iter().map(|a| enquote(a)).collect();
+            let built = format!(
+                "# warning: include not inlined, {}\n{}include {};",
+                problem,
+                margin,
+                args.join(" ")
+            );
+            let built_lines = built.matches('\n').count();
+            return Some((built, built_lines));
+        }
+
+        if files.is_empty() {
+            let built = format!("# include {} matches no files", arg);
+            let built_lines = built.matches('\n').count();
+            return Some((built, built_lines));
+        }
+
+        // line numbers of other files mean nothing here
+        let preserve_line_numbers = std::mem::take(&mut self.preserve_line_numbers);
+        let mut inlined = Vec::new();
+        // the newlines joining the files
+        let mut lines = files.len() - 1;
+        for &i in files {
+            let file = &payload.config[i];
+            let mut built = format!("# begin include {}", file.file);
+            self.inlining.push(i);
+            lines += self.build_block(&mut built, &file.parsed, depth, 0, line) + 1;
+            self.inlining.pop();
+            built += "\n";
+            built += &margin;
+            built += "# end include";
+            inlined.push(built);
+        }
+        self.preserve_line_numbers = preserve_line_numbers;
+        Some((inlined.join(&format!("\n{}", margin)), lines))
+    }
+}
+
+fn has_placeholder(arg: &str) -> bool {
+    ["{{", "{%", "{#", "<%"]
+        .iter()
+        .any(|open| arg.contains(open))
+}
+
+// quote an arg that would not lex back into the same single token
+pub(crate) fn enquote(arg: &str) -> String {
+    if !needs_quotes(arg) {
+        return arg.to_string();
+    }
+    if arg.contains('"') && !arg.contains('\'') {
+        format!("'{}'", arg)
+    } else {
+        format!("\"{}\"", arg.replace('"', "\\\""))
+    }
+}
+
+fn needs_quotes(arg: &str) -> bool {
+    if arg.is_empty() {
+        return true;
+    }
+
+    let chars: Vec<char> = arg.chars().collect();
+    let first = chars[0];
+    if first.is_whitespace() || matches!(first, '{' | '}' | ';' | '"' | '\'' | '#') {
+        return true;
+    }
+
+    // a "${" opens variable expansion, which must be closed by '}'
+    let mut expanding = false;
+    let mut i = 0;
+    while i < chars.len() {
+        let ch = chars[i];
+        if ch == '\\' {
+            // escaped characters are kept as they are, but a backslash
+            // ending a line only stays in quotes
+            if chars.get(i + 1) == Some(&'\n') {
+                return true;
+            }
+            i += 2;
+            continue;
+        }
+        if ch.is_whitespace() || matches!(ch, '{' | ';' | '"' | '\'') {
+            return true;
+        }
+        if ch == '$' && chars.get(i + 1) == Some(&'{') {
+            if expanding {
+                return true;
+            }
+            expanding = true;
+            i += 2;
+            continue;
+        }
+        if ch == '}' && expanding {
+            expanding = false;
+        }
+        i += 1;
+    }
+
+    // a lone '$' is kept quoted as it is usually written to stand for itself,
+    // a trailing '\\' would escape whatever follows it
+    expanding || arg.ends_with('\\') || arg == "$"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json::JsonStyle;
+    use crate::lex::lex;
+    use crate::parse::{
+        parse, parse_source, parse_tokens, ConfigFile, ParseCtx, ParseOptions, ParseProfile,
+        RAW_DIRECTIVE,
+    };
+
+    fn parse_str(source: &str) -> Vec<Directive> {
+        let options = ParseOptions::default().comments(true);
+        let mut ctx = ParseCtx::new("nginx.conf");
+        parse_tokens(lex(source.as_bytes()).unwrap(), &mut ctx, &options).unwrap()
+    }
+
+    #[test]
+    fn test_build() {
+        let payload = parse(
+            "configs/with-comments/nginx.conf",
+            &ParseOptions::default().comments(true),
+        );
+        assert_eq!(
+            build(&payload.config[0].parsed, &BuildOptions::default()),
+            "events {
+    worker_connections 1024;
+}
+#comment
+http {
+    server {
+        listen 127.0.0.1:8080; #listen
+        server_name default_server;
+        location / { ## this is brace
+            # location /
+            return 200 \"foo bar baz\";
+        }
+    }
+}"
+        );
+    }
+
+    #[test]
+    fn test_build_round_trip() {
+        let directives = parse_str(
+            "http {
+  # served as is
+  server {
+    location /foo { }
+    return 200 \"foo bar\";
+    add_header X-Tab 'a\tb';
+    set $empty '';
+  }
+}
+",
+        );
+        let built = build(&directives, &BuildOptions::default().indent(2));
+        assert_eq!(
+            built,
+            "http {
+  # served as is
+  server {
+    location /foo {
+    }
+    return 200 \"foo bar\";
+    add_header X-Tab \"a\tb\";
+    set $empty \"\";
+  }
+}"
+        );
+        assert_eq!(strip_lines(&parse_str(&built)), strip_lines(&directives));
+    }
+
+    #[test]
+    fn test_build_wrap_comments() {
+        let doc = "lorem ipsum dolor sit amet ".repeat(11);
+        let pragma = format!("crossplane-ignore unknown-directive {}", "x".repeat(80));
+        let source = format!(
+            "http {{\n    #  {}\n    # {}\n    #{}\n    server_tokens off; # {}\n    gzip on; # {}\n}}\n",
+            doc.trim(),
+            pragma,
+            "=".repeat(90),
+            "a".repeat(35),
+            "b".repeat(60),
+        );
+        let directives = parse_str(&source);
+        let options = BuildOptions::default().wrap_comments(Some(60));
+        let built = build(&directives, &options);
+        let lines: Vec<&str> = built.lines().collect();
+
+        // the doc comment is wrapped with its indentation and spaces kept
+        let wrapped: Vec<&str> = lines[1..]
+            .iter()
+            .copied()
+            .take_while(|l| l.starts_with("    #  "))
+            .collect();
+        assert_eq!(wrapped.len(), 6);
+        assert!(wrapped.iter().all(|l| l.len() <= 60));
+        let words = wrapped.iter().flat_map(|l| l[7..].split(' '));
+        assert!(words.eq(doc.split_whitespace()));
+        assert_eq!(lines[7], format!("    # {}", pragma));
+        assert_eq!(lines[8], format!("    #{}", "=".repeat(90)));
+        // inline comments are never wrapped
+        assert_eq!(
+            lines[9],
+            format!("    server_tokens off; # {}", "a".repeat(35))
+        );
+        assert_eq!(lines[10], format!("    gzip on; # {}", "b".repeat(60)));
+
+        let built = build(&directives, &options.move_long_comments(true));
+        let lines: Vec<&str> = built.lines().collect();
+        // one fitting in the limit stays, one past it gets its own line
+        assert_eq!(lines[9].len(), 60);
+        assert_eq!(lines[10], "    gzip on;");
+        assert_eq!(lines[11], format!("    # {}", "b".repeat(60)));
+
+        assert_eq!(
+            build(&directives, &BuildOptions::default()),
+            source.trim_end()
+        );
+    }
+
+    #[test]
+    fn test_build_header() {
+        let directives = parse_str("user nginx;\n");
+        let version = env!("CARGO_PKG_VERSION");
+
+        let options = BuildOptions::default();
+        assert_eq!(build(&directives, &options), "user nginx;");
+
+        let options = BuildOptions::default().header(HeaderMode::Default);
+        let built = build(&directives, &options);
+        assert_eq!(
+            built,
+            format!(
+                "# This config was built by rust-crossplane {}.\n\nuser nginx;",
+                version
+            )
+        );
+
+        let options = BuildOptions::default().header(HeaderMode::Custom(
+            "managed by ops\ndo not edit".to_string(),
+        ));
+        let built = build(&directives, &options);
+        assert_eq!(built, "# managed by ops\n# do not edit\n\nuser nginx;");
+
+        // rebuilding a built file keeps a single banner
+        assert_eq!(build(&parse_str(&built), &options), built);
+        let options = BuildOptions::default().header(HeaderMode::Default);
+        let rebuilt = build(&parse_str(&build(&directives, &options)), &options);
+        assert_eq!(rebuilt, build(&directives, &options));
+    }
+
+    #[test]
+    fn test_build_canonical_order() {
+        let directives = parse_str(
+            "server {
+    location /b { }
+    proxy_set_header B b;
+    root /srv;
+    deny 10.0.0.1;
+    # api locations
+    location ~ ^/api { }
+    include common.conf;
+    allow 10.0.0.0/8;
+    index index.html; # default index
+    location /a { }
+    proxy_set_header A a;
+    location ~ ^/admin { }
+    deny all;
+    access_log off;
+    map $uri $x { b 1; a 2; }
+}
+",
+        );
+
+        let options = BuildOptions::default().canonical_order(true);
+        assert_eq!(
+            build(&directives, &options),
+            "server {
+    include common.conf;
+    access_log off;
+    index index.html; # default index
+    root /srv;
+    proxy_set_header B b;
+    deny 10.0.0.1;
+    # api locations
+    location ~ ^/api {
+    }
+    allow 10.0.0.0/8;
+    proxy_set_header A a;
+    location ~ ^/admin {
+    }
+    deny all;
+    location /a {
+    }
+    location /b {
+    }
+    map $uri $x {
+        b 1;
+        a 2;
+    }
+}"
+        );
+    }
+
+    #[test]
+    fn test_build_dollar_round_trip() {
+        let source = "set $a \\$literal;
+set $b $$;
+set $c foo${var}bar;
+set $d ${var}bar;
+return 200 \"$\";
+return 200 \"price: \\$5 for ${item}s\";";
+        let directives = parse_str(source);
+        let args: Vec<&str> = directives
+            .iter()
+            .map(|d| d.args.last().unwrap().as_str())
+            .collect();
+        assert_eq!(
+            args,
+            [
+                "\\$literal",
+                "$$",
+                "foo${var}bar",
+                "${var}bar",
+                "$",
+                "price: \\$5 for ${item}s"
+            ]
+        );
+
+        let built = build(&directives, &BuildOptions::default());
+        assert_eq!(
+            built,
+            "set $a \\$literal;
+set $b $$;
+set $c foo${var}bar;
+set $d ${var}bar;
+return 200 \"$\";
+return 200 \"price: \\$5 for ${item}s\";"
+        );
+        assert_eq!(parse_str(&built), directives);
+    }
+
+    #[test]
+    fn test_build_round_trip_messy() {
+        let options = ParseOptions::default().comments(true);
+        let payload = parse("configs/messy/nginx.conf", &options);
+        let directives = &payload.config[0].parsed;
+
+        let built = build(directives, &BuildOptions::default());
+        assert!(built.contains("\\$server_port"));
+        let rebuilt = parse_str(&built);
+        assert_eq!(strip_lines(&rebuilt), strip_lines(directives));
+    }
+
+    #[test]
+    fn test_build_inline_includes() {
+        // the directives of a payload with the included files in place of
+        // the includes
+        fn spliced(payload: &Payload, block: &[Directive]) -> Vec<Directive> {
+            let mut out = Vec::new();
+            for d in block {
+                match &d.includes {
+                    Some(files) => {
+                        for &i in files {
+                            out.extend(spliced(payload, &payload.config[i].parsed));
+                        }
+                    }
+                    None => out.push(Directive {
+                        block: d.block.as_ref().map(|b| spliced(payload, b)),
+                        ..d.clone()
+                    }),
+                }
+            }
+            out
+        }
+
+        let payload = parse("configs/includes/nginx.conf", &ParseOptions::default());
+        assert_eq!(payload.errors, []);
+        let before = payload.clone();
+        let options = BuildOptions::default().inline_includes(true);
+        let built = build_payload(&payload, &options);
+        assert_eq!(
+            built,
+            "events {
+    worker_connections 1024;
+}
+http {
+    # begin include configs/includes/conf.d/server.conf
+    server {
+        listen 127.0.0.1:8080;
+        server_name default_server;
+        # begin include configs/includes/locations.conf
+        location / {
+            return 200 \"foo bar baz\";
+        }
+        # end include
+    }
+    # end include
+}"
+        );
+        assert_eq!(payload, before);
+
+        let reparsed = parse_str(&built);
+        let combined = spliced(&payload, &payload.config[0].parsed);
+        assert_eq!(
+            strip_lines(&reparsed)
+                .into_iter()
+                .filter(|(name, _, _)| name != "#")
+                .collect::<Vec<_>>(),
+            strip_lines(&combined)
+        );
+
+        // only the root file is written
+        let dir = tempfile::tempdir().unwrap();
+        build_files(&payload, dir.path(), &options).unwrap();
+        let root = dir.path().join("configs/includes/nginx.conf");
+        assert_eq!(fs::read_to_string(root).unwrap(), built + "\n");
+        assert!(!dir.path().join("configs/includes/locations.conf").exists());
+    }
+
+    #[test]
+    fn test_build_inline_failed_includes() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |name: &str, source: &str| fs::write(dir.path().join(name), source).unwrap();
+        write(
+            "nginx.conf",
+            "http {\n    include broken.conf;\n    include missing.conf;\n    include none/*.conf;\n    include self.conf;\n}\n",
+        );
+        write("broken.conf", "server {\n");
+        write("self.conf", "gzip on;\ninclude self.conf;\n");
+        let options = ParseOptions::default().catch_errors(true);
+        let payload = parse(dir.path().join("nginx.conf"), &options);
+
+        let built = build_payload(&payload, &BuildOptions::default().inline_includes(true));
+        let self_conf = dir.path().join("self.conf").display().to_string();
+        assert_eq!(
+            built,
+            format!(
+                "http {{
+    # warning: include not inlined, {}/broken.conf has parse errors
+    include broken.conf;
+    # warning: include not inlined, it could not be opened
+    include missing.conf;
+    # include none/*.conf matches no files
+    # begin include {}
+    gzip on;
+    # warning: include not inlined, it includes itself
+    include self.conf;
+    # end include
+}}",
+                dir.path().display(),
+                self_conf
+            )
+        );
+    }
+
+    fn strip_lines(directives: &[Directive]) -> Vec<(String, Vec<String>, Option<String>)> {
+        let mut out = Vec::new();
+        for d in directives {
+            out.push((d.directive.clone(), d.args.clone(), d.comment.clone()));
+            if let Some(block) = &d.block {
+                out.extend(strip_lines(block));
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn test_build_files_header() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("nginx.conf");
+        fs::write(&source, "events {\n}\n").unwrap();
+
+        let options = ParseOptions::default().comments(true);
+        let payload = parse(&source, &options);
+        let build_options = BuildOptions::default().header(HeaderMode::Default);
+        build_files(&payload, dir.path(), &build_options).unwrap();
+
+        let built = fs::read_to_string(&source).unwrap();
+        let expected = format!(
+            "# This config was built by rust-crossplane {}.\n# Source: {}\n\nevents {{\n}}\n",
+            env!("CARGO_PKG_VERSION"),
+            source.display()
+        );
+        assert_eq!(built, expected);
+
+        // the banner survives a parse and a second build unchanged
+        let payload = parse(&source, &options);
+        build_files(&payload, dir.path(), &build_options).unwrap();
+        assert_eq!(fs::read_to_string(&source).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_validate_buildable() {
+        let source = "http {\n    include a.conf;\n    server {\n        listen 80;\n    }\n}\n";
+        let mut payload = parse_source(source, &ParseOptions::default());
+        assert_eq!(validate_buildable(&payload), []);
+
+        let http = payload.config[0].parsed[0].block.as_mut().unwrap();
+        http[0].includes = Some(vec![0, 3]);
+        let server = http[1].block.as_mut().unwrap();
+        server[0].args.push("a\r\nb".to_string());
+        server.push(Directive::new("", 7));
+        server.push(Directive::new("#", 8));
+        let mut raw = Directive::new(RAW_DIRECTIVE, 9);
+        raw.raw = Some("weird {".to_string());
+        raw.block = Some(vec![Directive::new("", 10)]);
+        server.push(raw);
+        let mut placeholder = Directive::new("set", 11);
+        placeholder.args = vec!["$a".to_string(), "{{ a }}\nb".to_string()];
+        placeholder.placeholder = true;
+        server.push(placeholder);
+
+        let issues: Vec<String> = validate_buildable(&payload)
+            .iter()
+            .map(|i| i.to_string())
+            .collect();
+        assert_eq!(
+            issues,
+            [
+                "file 0 directive 0.0 (line 2): include lists file 3, which is not in the payload",
+                "file 0 directive 0.1.0 (line 4): arg 1 holds a line break that cannot be built",
+                "file 0 directive 0.1.1 (line 7): directive has no name",
+                "file 0 directive 0.1.2 (line 8): comment has no text",
+                "file 0 directive 0.1.3 (line 9): directive has both raw text and a block",
+                "file 0 directive 0.1.4 (line 11): arg 1 holds a line break that cannot be built",
+            ]
+        );
+        let issue = &validate_buildable(&payload)[0];
+        assert_eq!(issue.location.path, [0, 0]);
+        assert_eq!(issue.kind, BuildIssueKind::IncludeNotInPayload { index: 3 });
+
+        let dir = tempfile::tempdir().unwrap();
+        let error = build_files(&payload, dir.path(), &BuildOptions::default()).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(error.to_string(), issues.join("\n"));
+        assert!(!dir.path().join("nginx.conf").exists());
+
+        build_files(&payload, dir.path(), &BuildOptions::default().force(true)).unwrap();
+        assert!(dir.path().join("nginx.conf").exists());
+    }
+
+    #[test]
+    fn test_validate_buildable_lossy() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("nginx.conf");
+        fs::write(
+            &root,
+            "http {\n    include ok.conf;\n    include broken.conf;\n    gzip on;\n}\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join("ok.conf"), "server {\n}\n").unwrap();
+        fs::write(dir.path().join("broken.conf"), "server {\n").unwrap();
+        let out = tempfile::tempdir().unwrap();
+        let kinds = |payload: &Payload| -> Vec<BuildIssueKind> {
+            validate_buildable(payload)
+                .into_iter()
+                .map(|i| i.kind)
+                .collect()
+        };
+
+        // the include that failed to parse
+        let payload = parse(&root, &ParseOptions::default());
+        assert_eq!(payload.parse_profile(), &ParseProfile::default());
+        assert!(payload.is_lossy() && !payload.parse_profile().drops_directives());
+        assert_eq!(kinds(&payload), [BuildIssueKind::FailedFile]);
+        assert_eq!(
+            validate_buildable(&payload)[0].to_string(),
+            "file 2: file failed to parse, building it would drop what could not be parsed"
+        );
+        let error = build_files(&payload, out.path(), &BuildOptions::default()).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        let force = BuildOptions::default().force(true);
+        build_files(&payload, out.path(), &force).unwrap();
+        // inlined, the failed include is kept as an include
+        let inline = BuildOptions::default().inline_includes(true);
+        assert_eq!(validate_buildable_with(&payload, &inline), []);
+        // which the JSON of the payload says too
+        let mut json = Vec::new();
+        payload.write_json(&mut json, JsonStyle::Compact).unwrap();
+        assert!(String::from_utf8_lossy(&json).ends_with(",\"_lossy\":true}"));
+
+        // directives ignored, which the JSON of the payload says
+        let options = ParseOptions::default().ignore(&["gzip", "include"]);
+        let payload = parse(&root, &options);
+        assert_eq!(payload.parse_profile().ignored, ["gzip", "include"]);
+        let ignored = vec!["gzip".to_string(), "include".to_string()];
+        assert_eq!(kinds(&payload), [BuildIssueKind::LossyParse { ignored }]);
+        assert_eq!(
+            validate_buildable(&payload)[0].to_string(),
+            "file 0: payload was parsed ignoring gzip, include, building it would drop them"
+        );
+        assert!(build_files(&payload, out.path(), &BuildOptions::default()).is_err());
+        build_files(&payload, out.path(), &force).unwrap();
+
+        let mut json = Vec::new();
+        payload.write_json(&mut json, JsonStyle::Compact).unwrap();
+        assert!(String::from_utf8_lossy(&json).ends_with(",\"_lossy\":true}"));
+        let read = Payload::read_json(&json[..]).unwrap();
+        assert!(read.parse_profile().marked_lossy);
+        let ignored = Vec::new();
+        assert_eq!(kinds(&read), [BuildIssueKind::LossyParse { ignored }]);
+        build_files(&read, out.path(), &force).unwrap();
+
+        // left out comments and single files build to a working config
+        let options = ParseOptions::default().single(true).comments(false);
+        let payload = parse(dir.path().join("ok.conf"), &options);
+        assert!(payload.parse_profile().single && !payload.is_lossy());
+        assert_eq!(validate_buildable(&payload), []);
+        let mut json = Vec::new();
+        payload.write_json(&mut json, JsonStyle::Compact).unwrap();
+        assert!(!String::from_utf8_lossy(&json).contains("_lossy"));
+    }
+
+    #[test]
+    fn test_build_ascii_only() {
+        let source = "# r\u{e9}sum\u{e9} server
+server {
+    server_name www.ex\u{2011}ample.com;
+    add_header X-Note \"don\u{2019}t\";
+}
+";
+        let options = ParseOptions::default().comments(true);
+        let payload = parse_source(source, &options);
+        let ascii = BuildOptions::default().ascii_only(true);
+        assert_eq!(validate_buildable(&payload), []);
+        let issues: Vec<String> = validate_buildable_with(&payload, &ascii)
+            .iter()
+            .map(|i| i.to_string())
+            .collect();
+        assert_eq!(
+            issues,
+            [
+                "file 0 directive 0 (line 1): the comment contains U+00E9, which is not printable ASCII",
+                "file 0 directive 1.0 (line 3): arg 1 of \"server_name\" contains U+2011, \
+                 which is not printable ASCII",
+                "file 0 directive 1.1 (line 4): arg 2 of \"add_header\" contains U+2019, \
+                 which is not printable ASCII",
+            ]
+        );
+        let exempt = ascii.clone().ascii_exempt_comments(true);
+        assert_eq!(validate_buildable_with(&payload, &exempt).len(), 2);
+
+        let dir = tempfile::tempdir().unwrap();
+        let error = build_files(&payload, dir.path(), &ascii).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert!(!dir.path().join("nginx.conf").exists());
+
+        let clean = parse_source("server {\n    server_name example.com;\n}\n", &options);
+        build_files(&clean, dir.path(), &ascii).unwrap();
+        assert!(dir.path().join("nginx.conf").exists());
+    }
+
+    #[test]
+    fn test_build_preserve_line_numbers() {
+        let options = ParseOptions::default().comments(true);
+        let build_options = BuildOptions::default().preserve_line_numbers(true);
+        for fixture in ["simple", "with-comments"] {
+            let payload = parse(format!("configs/{}/nginx.conf", fixture), &options);
+            let directives = &payload.config[0].parsed;
+            let (built, misplaced) = build_with_line_report(directives, &build_options);
+            assert_eq!(misplaced, [], "{}", fixture);
+            // lines and the lines of closing braces come back as they were
+            assert_eq!(parse_str(&built), *directives, "{}", fixture);
+        }
+
+        let mut directives = parse_str("events {\n    worker_connections 1024;\n}\n\nhttp {\n}\n");
+        let events = directives[0].block.as_mut().unwrap();
+        events.insert(0, Directive::new("accept_mutex", 0));
+        let (built, misplaced) = build_with_line_report(&directives, &build_options);
+        assert_eq!(
+            built,
+            "events {\n    accept_mutex;\n    worker_connections 1024;\n}\nhttp {\n}"
+        );
+        assert_eq!(
+            misplaced,
+            [MisplacedDirective {
+                directive: "worker_connections".to_string(),
+                line: 2,
+                built_line: 3,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_build_files_shebang() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("nginx.conf");
+        let build_options = BuildOptions::default().header(HeaderMode::Default);
+        let expected = format!(
+            "#!jinja2\n# This config was built by rust-crossplane {}.\n# Source: {}\n\nevents {{\n}}\n",
+            env!("CARGO_PKG_VERSION"),
+            source.display()
+        );
+
+        for comments in [false, true] {
+            fs::write(&source, "#!jinja2\nevents {\n}\n").unwrap();
+            let options = ParseOptions::default().comments(comments);
+            let payload = parse(&source, &options);
+            assert_eq!(payload.config[0].shebang(), Some("#!jinja2"));
+            build_files(&payload, dir.path(), &build_options).unwrap();
+            assert_eq!(fs::read_to_string(&source).unwrap(), expected);
+
+            // the banner stays behind it on later builds
+            let payload = parse(&source, &options);
+            build_files(&payload, dir.path(), &build_options).unwrap();
+            assert_eq!(fs::read_to_string(&source).unwrap(), expected);
+        }
+
+        let directives = parse_str("#%PRAGMA\nevents {\n}\n");
+        let built = build(
+            &directives,
+            &BuildOptions::default().header(HeaderMode::Custom("managed".to_string())),
+        );
+        assert_eq!(built, "#%PRAGMA\n# managed\n\nevents {\n}");
+    }
+
+    #[test]
+    fn test_build_files_duplicates() {
+        let file = |name: &str, source: &str| {
+            let mut config = ConfigFile::new(name);
+            config.parsed = parse_str(source);
+            config
+        };
+        let payload = |second: &str| {
+            Payload::from_config(vec![
+                file("nginx.conf", "include conf/*.conf;\n"),
+                file("conf/a.conf", "server_tokens off;\n"),
+                file("conf/../conf/./a.conf", second),
+            ])
+        };
+        let built = |options: BuildOptions, second: &str| {
+            let dir = tempfile::tempdir().unwrap();
+            build_files(&payload(second), dir.path(), &options)?;
+            fs::read_to_string(dir.path().join("conf/a.conf"))
+        };
+
+        let error = built(BuildOptions::default(), "gzip on;\n").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::AlreadyExists);
+        assert!(error
+            .to_string()
+            .starts_with("\"conf/a.conf\", \"conf/../conf/./a.conf\" are all built to "));
+        let dir = tempfile::tempdir().unwrap();
+        assert!(build_files(&payload("gzip on;\n"), dir.path(), &BuildOptions::default()).is_err());
+        assert!(!dir.path().join("nginx.conf").exists());
+
+        let overwrite = BuildOptions::default().on_duplicate(DuplicatePolicy::Overwrite);
+        assert_eq!(built(overwrite, "gzip on;\n").unwrap(), "gzip on;\n");
+        let merge = BuildOptions::default().on_duplicate(DuplicatePolicy::Merge);
+        assert_eq!(
+            built(merge.clone(), "gzip on;\n").unwrap(),
+            "server_tokens off;\n# merged from conf/../conf/./a.conf\ngzip on;\n"
+        );
+
+        // identical duplicates
+        let same = "server_tokens off;\n";
+        assert!(built(BuildOptions::default(), same).is_err());
+        assert_eq!(
+            built(merge.clone(), same).unwrap(),
+            "server_tokens off;\n# merged from conf/../conf/./a.conf\nserver_tokens off;\n"
+        );
+        for policy in [
+            DuplicatePolicy::Error,
+            DuplicatePolicy::Overwrite,
+            DuplicatePolicy::Merge,
+        ] {
+            let options = BuildOptions::default()
+                .on_duplicate(policy)
+                .dedupe_identical(true);
+            assert_eq!(built(options, same).unwrap(), same);
+        }
+    }
+
+    #[test]
+    fn test_output_key() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("conf")).unwrap();
+        let key = output_key(&dir.path().join("conf/a.conf"));
+        assert_eq!(key, output_key(&dir.path().join("./conf/x/../a.conf")));
+        assert_eq!(key, output_key(&dir.path().join("new/../conf/a.conf")));
+        assert_ne!(key, output_key(&dir.path().join("new/a.conf")));
+        assert!(key.starts_with(dir.path().canonicalize().unwrap()));
+
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(dir.path().join("conf"), dir.path().join("link")).unwrap();
+            assert_eq!(key, output_key(&dir.path().join("link/a.conf")));
+        }
+    }
+}
@@ -0,0 +1,308 @@
+use crate::lint::{Finding, Severity};
+use crate::map_blocks::{entries, Entry};
+use crate::parse::Payload;
+
+// directives setting the content handler of a location, of which only the
+// last one is used
+const CONTENT_HANDLERS: [&str; 6] = [
+    "fastcgi_pass",
+    "grpc_pass",
+    "memcached_pass",
+    "proxy_pass",
+    "scgi_pass",
+    "uwsgi_pass",
+];
+
+// the ssl_certificate and ssl_certificate_key directives in effect in a
+// block, set in it or inherited
+#[derive(Clone, Copy, Default)]
+struct Certificates<'e, 'a> {
+    certificates: &'e [&'e Entry<'a>],
+    keys: usize,
+}
+
+// directive pairs that conflict in a block, or that are missing their
+// other half: alias and root, several content handlers, content handlers
+// after return and certificates without keys
+pub(crate) fn check_conflicts(payload: &Payload) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let Some(root) = payload.config.first() else {
+        return findings;
+    };
+    let block = entries(payload, &root.parsed, &root.file, &mut vec![0]);
+    check_block(payload, &block, Certificates::default(), &mut findings);
+    findings
+}
+
+fn check_block(
+    payload: &Payload,
+    block: &[Entry],
+    inherited: Certificates,
+    findings: &mut Vec<Finding>,
+) {
+    let named = |name: &str| -> Vec<&Entry> {
+        block
+            .iter()
+            .filter(|e| e.directive.directive == name)
+            .collect()
+    };
+    check_root_alias(block, findings);
+    check_content_handlers(block, findings);
+
+    let certificates = named("ssl_certificate");
+    let keys = named("ssl_certificate_key");
+    let effective = Certificates {
+        certificates: if certificates.is_empty() {
+            inherited.certificates
+        } else {
+            &certificates
+        },
+        keys: if keys.is_empty() {
+            inherited.keys
+        } else {
+            keys.len()
+        },
+    };
+    let set_here = !certificates.is_empty() || !keys.is_empty();
+    if let Some(missing) = effective
+        .certificates
+        .get(effective.keys)
+        .filter(|_| set_here)
+    {
+        // reported where the key would be looked for
+        let at = certificates.get(effective.keys).or(keys.first()).unwrap();
+        findings.push(finding(
+            at,
+            "missing-paired-directive",
+            Severity::Error,
+            format!(
+                "no \"ssl_certificate_key\" is defined for certificate \"{}\" set in {}:{}",
+                missing.directive.args.first().map_or("", String::as_str),
+                missing.file,
+                missing.directive.line
+            ),
+        ));
+    }
+    if let Some(key) = keys.first() {
+        if effective.certificates.is_empty() && !sets_certificate(payload, block) {
+            findings.push(finding(
+                key,
+                "missing-paired-directive",
+                Severity::Warning,
+                "\"ssl_certificate_key\" is set without any \"ssl_certificate\"".to_string(),
+            ));
+        }
+    }
+
+    for entry in block {
+        if let Some(children) = &entry.directive.block {
+            let children = entries(payload, children, entry.file, &mut Vec::new());
+            check_block(payload, &children, effective, findings);
+        }
+    }
+}
+
+fn finding(entry: &Entry, kind: &str, severity: Severity, message: String) -> Finding {
+    Finding::new(kind, severity, message, entry.file, entry.directive.line)
+}
+
+// nginx refuses a location with both, whichever comes first
+fn check_root_alias(block: &[Entry], findings: &mut Vec<Finding>) {
+    let mut first: Option<&Entry> = None;
+    for entry in block {
+        let name = entry.directive.directive.as_str();
+        if name != "root" && name != "alias" {
+            continue;
+        }
+        match first {
+            Some(other) if other.directive.directive != name => findings.push(finding(
+                entry,
+                "conflicting-directives",
+                Severity::Error,
+                format!(
+                    "\"{}\" directive is duplicate, \"{}\" directive was specified earlier in \
+                     {}:{}",
+                    name, other.directive.directive, other.file, other.directive.line
+                ),
+            )),
+            Some(_) => {}
+            None => first = Some(entry),
+        }
+    }
+}
+
+// handlers overriding each other, and handlers never reached because return
+// ends the request in the rewrite phase, before the content phase whatever
+// the order they are written in
+fn check_content_handlers(block: &[Entry], findings: &mut Vec<Finding>) {
+    let ret = block.iter().find(|e| e.directive.directive == "return");
+    let mut first: Option<&Entry> = None;
+    for entry in block {
+        let name = entry.directive.directive.as_str();
+        if !CONTENT_HANDLERS.contains(&name) && name != "try_files" {
+            continue;
+        }
+        if let Some(ret) = ret {
+            findings.push(finding(
+                entry,
+                "unreachable-directive",
+                Severity::Warning,
+                format!(
+                    "\"{}\" is never used, \"return\" in {}:{} ends the request first",
+                    name, ret.file, ret.directive.line
+                ),
+            ));
+            continue;
+        }
+        if name == "try_files" {
+            continue;
+        }
+        match first {
+            Some(other) if other.directive.directive != name => findings.push(finding(
+                entry,
+                "conflicting-directives",
+                Severity::Warning,
+                format!(
+                    "\"{}\" and \"{}\" in {}:{} both set the content handler, only the last \
+                     one is used",
+                    name, other.directive.directive, other.file, other.directive.line
+                ),
+            )),
+            Some(_) => {}
+            None => first = Some(entry),
+        }
+    }
+}
+
+// whether a block or the blocks inside it set a certificate
+fn sets_certificate(payload: &Payload, block: &[Entry]) -> bool {
+    block.iter().any(|entry| {
+        entry.directive.directive == "ssl_certificate"
+            || entry.directive.block.as_ref().is_some_and(|children| {
+                sets_certificate(
+                    payload,
+                    &entries(payload, children, entry.file, &mut Vec::new()),
+                )
+            })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::{parse, parse_source, ParseOptions};
+
+    fn messages(payload: &Payload) -> Vec<String> {
+        check_conflicts(payload)
+            .iter()
+            .map(|f| format!("{}:{} {} [{}]", f.severity, f.line, f.message, f.kind))
+            .collect()
+    }
+
+    #[test]
+    fn test_check_conflicts() {
+        let source = "http {
+    server {
+        location /a {
+            root /srv;
+            alias /srv/a/;
+        }
+        location /b {
+            proxy_pass http://backend;
+            fastcgi_pass unix:/run/php.sock;
+        }
+        location /c {
+            proxy_pass http://backend;
+            return 301 /d;
+            try_files $uri =404;
+        }
+        location /d {
+            alias /srv/d/;
+        }
+        location /e {
+            root /srv/e;
+            proxy_pass http://backend;
+            if ($arg_x) {
+                return 403;
+            }
+        }
+    }
+}
+";
+        let payload = parse_source(source, &ParseOptions::default());
+        assert_eq!(payload.errors, []);
+        assert_eq!(
+            messages(&payload),
+            [
+                "error:5 \"alias\" directive is duplicate, \"root\" directive was specified \
+                 earlier in nginx.conf:4 [conflicting-directives]",
+                "warning:9 \"fastcgi_pass\" and \"proxy_pass\" in nginx.conf:8 both set the \
+                 content handler, only the last one is used [conflicting-directives]",
+                "warning:12 \"proxy_pass\" is never used, \"return\" in nginx.conf:13 ends the \
+                 request first [unreachable-directive]",
+                "warning:14 \"try_files\" is never used, \"return\" in nginx.conf:13 ends the \
+                 request first [unreachable-directive]",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_certificates() {
+        let source = "http {
+    ssl_certificate_key /etc/ssl/default.key;
+    server {
+        ssl_certificate /etc/ssl/a.crt;
+    }
+    server {
+        ssl_certificate /etc/ssl/rsa.crt;
+        ssl_certificate /etc/ssl/ecdsa.crt;
+    }
+    server {
+        ssl_certificate /etc/ssl/b.crt;
+        ssl_certificate_key /etc/ssl/b.key;
+    }
+}
+mail {
+    server {
+        ssl_certificate_key /etc/ssl/mail.key;
+    }
+}
+stream {
+    ssl_certificate /etc/ssl/stream.crt;
+    server {
+        listen 443 ssl;
+    }
+}
+";
+        let payload = parse_source(source, &ParseOptions::default());
+        assert_eq!(
+            messages(&payload),
+            [
+                "error:8 no \"ssl_certificate_key\" is defined for certificate \
+                 \"/etc/ssl/ecdsa.crt\" set in nginx.conf:8 [missing-paired-directive]",
+                "warning:17 \"ssl_certificate_key\" is set without any \"ssl_certificate\" \
+                 [missing-paired-directive]",
+                "error:21 no \"ssl_certificate_key\" is defined for certificate \
+                 \"/etc/ssl/stream.crt\" set in nginx.conf:21 [missing-paired-directive]",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_conflicts_include() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("nginx.conf"),
+            "http {\n    server {\n        location / {\n            root /srv;\n            \
+             include alias.conf;\n        }\n    }\n}\n",
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("alias.conf"), "alias /srv/a/;\n").unwrap();
+        let payload = parse(dir.path().join("nginx.conf"), &ParseOptions::default());
+        let findings = check_conflicts(&payload);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].kind, "conflicting-directives");
+        assert_eq!(findings[0].file, payload.config[1].file);
+        assert_eq!(findings[0].line, 1);
+    }
+}
@@ -0,0 +1,255 @@
+use serde::{Deserialize, Serialize};
+
+/// The kind of block a directive is in, for matching on without comparing
+/// paths of block names. Variants are only ever added, never renamed or
+/// removed.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[non_exhaustive]
+pub enum ContextId {
+    Main,
+    Events,
+    Http,
+    HttpServer,
+    HttpLocation,
+    HttpUpstream,
+    /// An `if` of a server or a location.
+    HttpIf,
+    LimitExcept,
+    Stream,
+    StreamServer,
+    StreamUpstream,
+    Mail,
+    MailServer,
+    /// A `map` of http or stream.
+    Map,
+    Types,
+    /// A `geo` of http or stream.
+    Geo,
+    /// A `split_clients` of http or stream.
+    SplitClients,
+    CharsetMap,
+    /// Any other block, like those of third party modules, with the names
+    /// of the enclosing blocks joined by spaces.
+    Unknown(String),
+}
+
+impl ContextId {
+    /// The context of a path of enclosing block names, as the parser
+    /// tracks it: locations nested in locations are `["http", "location"]`.
+    pub fn from_path<S: AsRef<str>>(path: &[S]) -> Self {
+        let names: Vec<&str> = path.iter().map(AsRef::as_ref).collect();
+        match names[..] {
+            [] => ContextId::Main,
+            ["events"] => ContextId::Events,
+            ["http"] => ContextId::Http,
+            ["http", "server"] => ContextId::HttpServer,
+            ["http", "location"] => ContextId::HttpLocation,
+            ["http", "upstream"] => ContextId::HttpUpstream,
+            ["http", "server" | "location", "if"] => ContextId::HttpIf,
+            ["http", "location", "limit_except"] => ContextId::LimitExcept,
+            ["stream"] => ContextId::Stream,
+            ["stream", "server"] => ContextId::StreamServer,
+            ["stream", "upstream"] => ContextId::StreamUpstream,
+            ["mail"] => ContextId::Mail,
+            ["mail", "server"] => ContextId::MailServer,
+            ["http" | "stream", "map"] => ContextId::Map,
+            ["http", "types"] | ["http", "server" | "location", "types"] => ContextId::Types,
+            ["http" | "stream", "geo"] => ContextId::Geo,
+            ["http" | "stream", "split_clients"] => ContextId::SplitClients,
+            ["http", "charset_map"] => ContextId::CharsetMap,
+            _ => ContextId::Unknown(names.join(" ")),
+        }
+    }
+}
+
+/// The block context of a directive: the enclosing block names and what
+/// kind of block they make.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Context {
+    pub path: Vec<String>,
+    pub id: ContextId,
+}
+
+impl Context {
+    pub fn new<S: AsRef<str>>(path: &[S]) -> Self {
+        Context {
+            path: path.iter().map(|name| name.as_ref().to_string()).collect(),
+            id: ContextId::from_path(path),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::{parse, parse_source, ParseOptions};
+
+    #[test]
+    fn test_fixture_contexts() {
+        use ContextId::*;
+        let expected: &[(&str, &[ContextId])] = &[
+            ("args-only", &[Main, Http, Types, Map]),
+            ("args-only-unquoted-brace", &[Main, Http, Map, HttpServer]),
+            (
+                "brace-adjacent",
+                &[Main, Http, Map, HttpServer, HttpLocation],
+            ),
+            ("close-brace-semicolon", &[]),
+            ("comment-backslash", &[Main, Events, Http]),
+            ("includes", &[Main, Events, Http, HttpServer, HttpLocation]),
+            ("leading-open-brace", &[Main, Events]),
+            ("leading-semicolon", &[Main, Events]),
+            ("lone-cr", &[Main, Events, Http, HttpServer, HttpLocation]),
+            ("map-include", &[Main, Http, Map, HttpServer, HttpIf]),
+            ("messy", &[Main, Events, Http, HttpServer, HttpLocation]),
+            (
+                "mixed-line-endings",
+                &[Main, Events, Http, HttpServer, HttpLocation],
+            ),
+            ("quote-behavior", &[Main]),
+            ("quoted-right-brace", &[Main, Http]),
+            ("simple", &[Main, Events, Http, HttpServer, HttpLocation]),
+            ("three-mistakes", &[Main, Events, Http, HttpServer]),
+            (
+                "with-comments",
+                &[Main, Events, Http, HttpServer, HttpLocation],
+            ),
+        ];
+        let mut fixtures: Vec<_> = std::fs::read_dir("configs")
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        fixtures.sort();
+        let names: Vec<&str> = expected.iter().map(|(name, _)| *name).collect();
+        assert_eq!(fixtures, names);
+
+        let options = ParseOptions::default().catch_errors(true);
+        for (fixture, expected) in expected {
+            let payload = parse(format!("configs/{}/nginx.conf", fixture), &options);
+            // the contexts met, in the order they first come up
+            let mut ids: Vec<ContextId> = Vec::new();
+            for d in payload.directives() {
+                let context = d.context();
+                assert_eq!(context.id, ContextId::from_path(&context.path));
+                if !ids.contains(&context.id) {
+                    ids.push(context.id);
+                }
+            }
+            assert_eq!(ids, *expected, "{}", fixture);
+        }
+
+        // the files of the includes fixture are in the blocks including them
+        let payload = parse("configs/includes/nginx.conf", &options);
+        let contexts: Vec<(usize, &str, ContextId)> = payload
+            .directives()
+            .map(|d| {
+                (
+                    d.file_index(),
+                    d.directive().directive.as_str(),
+                    d.context().id,
+                )
+            })
+            .collect();
+        assert_eq!(
+            contexts,
+            [
+                (0, "events", Main),
+                (0, "worker_connections", Events),
+                (0, "http", Main),
+                (0, "include", Http),
+                (1, "server", Http),
+                (1, "listen", HttpServer),
+                (1, "server_name", HttpServer),
+                (1, "include", HttpServer),
+                (2, "location", HttpServer),
+                (2, "return", HttpLocation),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_context_ids() {
+        let source = r#"events {}
+http {
+    map $uri $a { default 0; }
+    geo $b { default 0; }
+    split_clients $request_id $c { * x; }
+    charset_map koi8-r utf-8 { C0 D18E; }
+    types { text/html html; }
+    upstream backend { server 127.0.0.1; }
+    lua_shared { lua_thing on; }
+    server {
+        if ($a) { return 404; }
+        location / {
+            location /nested { root /srv; }
+            limit_except GET { deny all; }
+            if ($b) { return 403; }
+        }
+    }
+}
+stream {
+    map $a $d { default 0; }
+    upstream tcp { server 127.0.0.1:53; }
+    server { listen 53; }
+}
+mail {
+    server { listen 25; }
+}
+"#;
+        let payload = parse_source(source, &ParseOptions::default());
+        let contexts: Vec<(&str, ContextId)> = payload
+            .directives()
+            .filter(|d| d.directive().block.is_none())
+            .map(|d| (d.directive().directive.as_str(), d.context().id))
+            .collect();
+        assert_eq!(
+            contexts,
+            [
+                ("default", ContextId::Map),
+                ("default", ContextId::Geo),
+                ("*", ContextId::SplitClients),
+                ("C0", ContextId::CharsetMap),
+                ("text/html", ContextId::Types),
+                ("server", ContextId::HttpUpstream),
+                (
+                    "lua_thing",
+                    ContextId::Unknown("http lua_shared".to_string())
+                ),
+                ("return", ContextId::HttpIf),
+                ("root", ContextId::HttpLocation),
+                ("deny", ContextId::LimitExcept),
+                ("return", ContextId::HttpIf),
+                ("default", ContextId::Map),
+                ("server", ContextId::StreamUpstream),
+                ("listen", ContextId::StreamServer),
+                ("listen", ContextId::MailServer),
+            ]
+        );
+        let blocks: Vec<ContextId> = ["", "events", "http", "stream", "mail"]
+            .iter()
+            .map(|name| ContextId::from_path(&name.split_whitespace().collect::<Vec<_>>()))
+            .collect();
+        assert_eq!(
+            blocks,
+            [
+                ContextId::Main,
+                ContextId::Events,
+                ContextId::Http,
+                ContextId::Stream,
+                ContextId::Mail
+            ]
+        );
+
+        let context = Context::new(&["http", "lua_shared"]);
+        let json = serde_json::to_string(&context).unwrap();
+        assert_eq!(
+            json,
+            r#"{"path":["http","lua_shared"],"id":{"Unknown":"http lua_shared"}}"#
+        );
+        assert_eq!(serde_json::from_str::<Context>(&json).unwrap(), context);
+        assert_eq!(
+            serde_json::to_string(&ContextId::HttpIf).unwrap(),
+            r#""HttpIf""#
+        );
+    }
+}
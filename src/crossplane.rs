@@ -0,0 +1,320 @@
+use std::fmt;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::build::{build_payload, BuildOptions};
+use crate::error::ParseError;
+use crate::findings::Findings;
+use crate::lint::LintOptions;
+use crate::loader::ConfigLoader;
+use crate::parse::{parse, parse_named, parse_with_loader, ParseOptions, Payload};
+
+/// The options of parsing, linting and building together, set once and
+/// used by every call. Clones share the options, and can be sent to other
+/// threads.
+#[derive(Debug, Clone, Default)]
+pub struct Crossplane {
+    options: Arc<Options>,
+}
+
+#[derive(Clone, Default)]
+struct Options {
+    parse: ParseOptions,
+    lint: LintOptions,
+    build: BuildOptions,
+    loader: Option<Arc<dyn ConfigLoader + Send + Sync>>,
+}
+
+impl fmt::Debug for Options {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Options")
+            .field("parse", &self.parse)
+            .field("lint", &self.lint)
+            .field("build", &self.build)
+            .field("loader", &self.loader.as_ref().map(|_| "ConfigLoader"))
+            .finish()
+    }
+}
+
+impl Crossplane {
+    pub fn builder() -> CrossplaneBuilder {
+        CrossplaneBuilder::default()
+    }
+
+    /// Parses the config at `path` and the files it includes, like
+    /// [`parse`], reading them through the loader if one was set.
+    pub fn parse_file<P: AsRef<Path>>(&self, path: P) -> Payload {
+        match &self.options.loader {
+            Some(loader) => parse_with_loader(path, &self.options.parse, loader.as_ref()),
+            None => parse(path, &self.options.parse),
+        }
+    }
+
+    /// Parses a config held in memory as the single file `file`. Include
+    /// directives are kept without reading the files they name.
+    pub fn parse_str(&self, source: &str, file: &str) -> Payload {
+        parse_named(source, file, &self.options.parse)
+    }
+
+    /// Runs the built-in checks, like [`Payload::lint_with`].
+    pub fn lint(&self, payload: &Payload) -> Findings {
+        payload.lint_with(&self.options.lint)
+    }
+
+    /// Builds the root file of the payload, like [`build_payload`].
+    pub fn build(&self, payload: &Payload) -> String {
+        build_payload(payload, &self.options.build)
+    }
+
+    /// Parses a config held in memory and builds it back, laid out by the
+    /// build options. Comments are kept whatever the parse options say.
+    pub fn format(&self, source: &str) -> Result<String, FormatError> {
+        let options = self.options.parse.clone().comments(true);
+        let payload = parse_named(source, "", &options);
+        if !payload.errors.is_empty() {
+            return Err(FormatError {
+                errors: payload.errors,
+            });
+        }
+        Ok(self.build(&payload))
+    }
+
+    pub fn parse_options(&self) -> &ParseOptions {
+        &self.options.parse
+    }
+
+    pub fn lint_options(&self) -> &LintOptions {
+        &self.options.lint
+    }
+
+    pub fn build_options(&self) -> &BuildOptions {
+        &self.options.build
+    }
+}
+
+/// Sets the options of a [`Crossplane`], the defaults for those left out.
+#[derive(Debug, Clone, Default)]
+pub struct CrossplaneBuilder {
+    options: Options,
+    analyze: AnalyzeFlags,
+}
+
+// the checks against the directive table, kept apart from the parse options
+// so that setting those later does not reset them
+#[derive(Debug, Clone, Copy, Default)]
+struct AnalyzeFlags {
+    strict: Option<bool>,
+    check_ctx: Option<bool>,
+    check_args: Option<bool>,
+}
+
+impl CrossplaneBuilder {
+    pub fn parse(mut self, options: ParseOptions) -> Self {
+        self.options.parse = options;
+        self
+    }
+
+    pub fn lint(mut self, options: LintOptions) -> Self {
+        self.options.lint = options;
+        self
+    }
+
+    pub fn build_options(mut self, options: BuildOptions) -> Self {
+        self.options.build = options;
+        self
+    }
+
+    /// Read the files [`Crossplane::parse_file`] parses through `loader`,
+    /// like [`parse_with_loader`], instead of from the file system.
+    pub fn loader(mut self, loader: impl ConfigLoader + Send + Sync + 'static) -> Self {
+        self.options.loader = Some(Arc::new(loader));
+        self
+    }
+
+    /// Sets [`ParseOptions::strict`], whether the parse options are set
+    /// before or after.
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.analyze.strict = Some(strict);
+        self
+    }
+
+    /// Sets [`ParseOptions::check_ctx`], whether the parse options are set
+    /// before or after.
+    pub fn check_ctx(mut self, check_ctx: bool) -> Self {
+        self.analyze.check_ctx = Some(check_ctx);
+        self
+    }
+
+    /// Sets [`ParseOptions::check_args`], whether the parse options are set
+    /// before or after.
+    pub fn check_args(mut self, check_args: bool) -> Self {
+        self.analyze.check_args = Some(check_args);
+        self
+    }
+
+    pub fn build(self) -> Crossplane {
+        let mut options = self.options;
+        let analyze = self.analyze;
+        if let Some(strict) = analyze.strict {
+            options.parse = options.parse.strict(strict);
+        }
+        if let Some(check_ctx) = analyze.check_ctx {
+            options.parse = options.parse.check_ctx(check_ctx);
+        }
+        if let Some(check_args) = analyze.check_args {
+            options.parse = options.parse.check_args(check_args);
+        }
+        // strict parsing and lint compile each regex once between them
+        #[cfg(feature = "regex")]
+        let options = Options {
+            lint: options.lint.regex_cache(options.parse.regex_cache.clone()),
+            ..options
+        };
+        Crossplane {
+            options: Arc::new(options),
+        }
+    }
+}
+
+/// The parse errors of a config given to [`Crossplane::format`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct FormatError {
+    pub errors: Vec<ParseError>,
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.errors.first() {
+            Some(first) if self.errors.len() > 1 => {
+                write!(f, "{} (and {} more errors)", first, self.errors.len() - 1)
+            }
+            Some(first) => write!(f, "{}", first),
+            None => write!(f, "the config has errors"),
+        }
+    }
+}
+
+impl std::error::Error for FormatError {}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::build::build;
+    use crate::lint::Severity;
+    use crate::test_support::MemoryLoader;
+
+    const FIXTURES: [&str; 5] = ["simple", "with-comments", "messy", "includes", "args-only"];
+
+    #[test]
+    fn test_crossplane_threads() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Crossplane>();
+
+        let parse_options = ParseOptions::default().comments(true).catch_errors(true);
+        let lint_options = LintOptions::default().include_severity(Severity::Error);
+        let build_options = BuildOptions::default().indent(2).inline_includes(true);
+        let crossplane = Crossplane::builder()
+            .parse(parse_options.clone())
+            .lint(lint_options.clone())
+            .build_options(build_options.clone())
+            .build();
+
+        let threads: Vec<_> = FIXTURES
+            .iter()
+            .map(|fixture| {
+                let crossplane = crossplane.clone();
+                let path = format!("configs/{}/nginx.conf", fixture);
+                std::thread::spawn(move || {
+                    let payload = crossplane.parse_file(&path);
+                    let findings = crossplane.lint(&payload);
+                    let built = crossplane.build(&payload);
+                    (path, payload, findings, built)
+                })
+            })
+            .collect();
+        for thread in threads {
+            let (path, payload, findings, built) = thread.join().unwrap();
+            let expected = parse(&path, &parse_options);
+            assert_eq!(payload, expected, "{}", path);
+            assert_eq!(findings, expected.lint_with(&lint_options), "{}", path);
+            assert_eq!(built, build_payload(&expected, &build_options), "{}", path);
+        }
+    }
+
+    #[test]
+    fn test_crossplane_format() {
+        let crossplane = Crossplane::builder()
+            .build_options(BuildOptions::default().tabs(true))
+            .build();
+        let formatted = crossplane
+            .format("events{worker_connections 1024;}\nhttp { include mime.types; # types\n}")
+            .unwrap();
+        assert_eq!(
+            formatted,
+            "events {\n\tworker_connections 1024;\n}\nhttp {\n\tinclude mime.types; # types\n}"
+        );
+        let payload = crossplane.parse_str(&formatted, "nginx.conf");
+        assert_eq!(payload.config[0].file, "nginx.conf");
+        let formatted = formatted.replace(" # types", "");
+        assert_eq!(
+            build(
+                &payload.config[0].parsed,
+                &BuildOptions::default().tabs(true)
+            ),
+            formatted
+        );
+
+        let error = crossplane.format("http {\n    gzip on\n}\n").unwrap_err();
+        assert_eq!(error.errors.len(), 1);
+        assert_eq!(error.errors[0].line, Some(2));
+        assert_eq!(
+            error.to_string(),
+            "directive \"gzip\" is not terminated by ';'"
+        );
+    }
+
+    #[test]
+    fn test_crossplane_loader_and_checks() {
+        let loader = MemoryLoader(HashMap::from([
+            (
+                PathBuf::from("/etc/nginx/nginx.conf"),
+                "http {\n    include site.conf;\n}\n",
+            ),
+            (
+                PathBuf::from("/etc/nginx/site.conf"),
+                "server {\n    listen 80 443;\n    worker_processes 2;\n}\n",
+            ),
+        ]));
+        let crossplane = Crossplane::builder()
+            .parse(ParseOptions::default().catch_errors(true))
+            .check_ctx(true)
+            .loader(loader)
+            .build();
+        assert!(crossplane.parse_options().check_ctx);
+        let payload = crossplane.parse_file("/etc/nginx/nginx.conf");
+        assert_eq!(payload.config.len(), 2);
+        let errors: Vec<_> = payload.errors.iter().map(|e| e.what.as_str()).collect();
+        assert_eq!(
+            errors,
+            ["\"worker_processes\" directive is not allowed here"]
+        );
+
+        // the checks are kept whether set before or after the parse options
+        let crossplane = Crossplane::builder()
+            .strict(true)
+            .parse(ParseOptions::default().catch_errors(true))
+            .check_args(true)
+            .build();
+        let options = crossplane.parse_options();
+        assert!(options.strict && options.check_args && options.catch_errors);
+        let crossplane = Crossplane::builder()
+            .check_ctx(true)
+            .parse(ParseOptions::default().check_ctx(false).check_args(true))
+            .build();
+        let options = crossplane.parse_options();
+        assert!(options.check_ctx && options.check_args && !options.strict);
+    }
+}
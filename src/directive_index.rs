@@ -0,0 +1,172 @@
+use serde::{Deserialize, Serialize};
+
+use crate::analyze::{known_directives, DirectiveSpec};
+use crate::version::NginxVersion;
+
+/// Version of the layout of [`DirectiveIndex`]. It is bumped whenever a
+/// field of the index or of its entries is added, removed or changes
+/// meaning, so consumers can tell which layout they were given.
+pub const DIRECTIVE_INDEX_SCHEMA_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexFormat {
+    Json,
+    MessagePack,
+}
+
+/// Every directive known to the analyzer, for editors and shells to build
+/// completions from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DirectiveIndex {
+    pub schema_version: u32,
+    pub directives: Vec<DirectiveIndexEntry>,
+}
+
+/// A directive as defined by one module; directives defined by several
+/// modules, like `server`, have an entry for each.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DirectiveIndexEntry {
+    pub name: String,
+    pub module: String,
+    /// The contexts the directive is allowed in, as the names of the
+    /// enclosing blocks joined by spaces, with `main` for the top level.
+    pub contexts: Vec<String>,
+    /// The kinds of arguments accepted, like `take1`, `flag` or `1more`.
+    pub args: Vec<String>,
+    pub since: Option<NginxVersion>,
+    pub removed: Option<NginxVersion>,
+    pub default: Option<String>,
+}
+
+impl From<&DirectiveSpec> for DirectiveIndexEntry {
+    fn from(spec: &DirectiveSpec) -> Self {
+        let contexts = spec
+            .contexts()
+            .map(|ctx| match ctx {
+                [] => "main".to_string(),
+                _ => ctx.join(" "),
+            })
+            .collect();
+        DirectiveIndexEntry {
+            name: spec.name.to_string(),
+            module: spec.module.to_string(),
+            contexts,
+            args: spec.arg_kinds().map(str::to_string).collect(),
+            since: spec.versions.since,
+            removed: spec.versions.removed,
+            default: spec.default.map(str::to_string),
+        }
+    }
+}
+
+impl DirectiveIndex {
+    pub fn new() -> Self {
+        DirectiveIndex {
+            schema_version: DIRECTIVE_INDEX_SCHEMA_VERSION,
+            directives: known_directives().iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl Default for DirectiveIndex {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The directive index serialized as pretty JSON or as MessagePack with
+/// named fields.
+pub fn export_directive_index(format: IndexFormat) -> Vec<u8> {
+    let index = DirectiveIndex::new();
+    match format {
+        IndexFormat::Json => serde_json::to_vec_pretty(&index).unwrap(),
+        IndexFormat::MessagePack => rmp_serde::to_vec_named(&index).unwrap(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries<'a>(index: &'a DirectiveIndex, name: &str) -> Vec<&'a DirectiveIndexEntry> {
+        index.directives.iter().filter(|d| d.name == name).collect()
+    }
+
+    #[test]
+    fn test_export_directive_index() {
+        let json: DirectiveIndex =
+            serde_json::from_slice(&export_directive_index(IndexFormat::Json)).unwrap();
+        let msgpack: DirectiveIndex =
+            rmp_serde::from_slice(&export_directive_index(IndexFormat::MessagePack)).unwrap();
+        assert_eq!(json, msgpack);
+        assert_eq!(json.schema_version, DIRECTIVE_INDEX_SCHEMA_VERSION);
+        assert_eq!(json.directives.len(), known_directives().len());
+
+        let [worker_connections] = entries(&json, "worker_connections")[..] else {
+            panic!("worker_connections should have one entry");
+        };
+        assert_eq!(worker_connections.module, "events");
+        assert_eq!(worker_connections.contexts, ["events"]);
+        assert_eq!(worker_connections.args, ["take1"]);
+        assert_eq!(worker_connections.default.as_deref(), Some("512"));
+
+        let listen = entries(&json, "listen");
+        let contexts: Vec<&[String]> = listen.iter().map(|d| &d.contexts[..]).collect();
+        assert_eq!(
+            contexts,
+            [["http server"], ["stream server"], ["mail server"]]
+        );
+        assert!(listen.iter().all(|d| d.args == ["1more"]));
+
+        let [http2] = entries(&json, "http2")[..] else {
+            panic!("http2 should have one entry");
+        };
+        assert_eq!(http2.since, Some(NginxVersion::V1_25_1));
+        assert_eq!(http2.removed, None);
+        assert_eq!(http2.args, ["flag"]);
+
+        let [ssl, _] = entries(&json, "ssl")[..] else {
+            panic!("ssl should have two entries");
+        };
+        assert_eq!(ssl.removed, Some(NginxVersion::V1_25_1));
+
+        let include = &entries(&json, "include")[0];
+        assert_eq!(include.contexts[0], "main");
+        assert!(include.contexts.contains(&"http location".to_string()));
+    }
+
+    #[test]
+    fn test_directive_index_schema_version() {
+        // adding, removing or renaming a field breaks these patterns; when
+        // that happens bump DIRECTIVE_INDEX_SCHEMA_VERSION along with them
+        let DirectiveIndex {
+            schema_version: _,
+            directives: _,
+        } = DirectiveIndex::new();
+        let DirectiveIndexEntry {
+            name: _,
+            module: _,
+            contexts: _,
+            args: _,
+            since: _,
+            removed: _,
+            default: _,
+        } = DirectiveIndexEntry::from(&known_directives()[0]);
+        assert_eq!(DIRECTIVE_INDEX_SCHEMA_VERSION, 1);
+
+        // the serialized field names are part of the schema as well
+        let json: serde_json::Value =
+            serde_json::from_slice(&export_directive_index(IndexFormat::Json)).unwrap();
+        let mut keys: Vec<&str> = json["directives"][0]
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(String::as_str)
+            .collect();
+        keys.sort_unstable();
+        assert_eq!(
+            keys,
+            ["args", "contexts", "default", "module", "name", "removed", "since"]
+        );
+    }
+}
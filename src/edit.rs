@@ -0,0 +1,591 @@
+use std::fmt;
+use std::ops::Range;
+use std::path::Path;
+
+use crate::build::enquote;
+use crate::error::ParseError;
+use crate::lex::{lex, NgxToken};
+use crate::lint::{Suggestion, TreeEdit};
+use crate::parse::{ConfigFile, Directive, Payload};
+use crate::tree::DirectiveLocation;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EditError {
+    /// No directive is at the given location.
+    NotFound(DirectiveLocation),
+    /// The file to create is already an entry of the payload.
+    FileExists(String),
+    /// Two edits change overlapping parts of the source.
+    Overlapping,
+    /// The source to edit does not lex.
+    Syntax(ParseError),
+}
+
+impl fmt::Display for EditError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EditError::NotFound(location) => write!(
+                f,
+                "no directive at {:?} in file {}",
+                location.path, location.file
+            ),
+            EditError::FileExists(file) => write!(f, "\"{}\" is already in the payload", file),
+            EditError::Overlapping => write!(f, "edits overlap"),
+            EditError::Syntax(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for EditError {}
+
+impl Payload {
+    /// Moves the directive at `target` into a new payload entry for
+    /// `new_path` and puts an `include` of that file in its place.
+    /// A relative `new_path` is resolved against the directory of the main
+    /// config, and the include names the file relative to that directory
+    /// when possible, as nginx resolves includes against it. Use
+    /// [`build_files`](crate::build_files) to write the result to disk.
+    pub fn extract_to_include(
+        &mut self,
+        target: DirectiveLocation,
+        new_path: &Path,
+    ) -> Result<(), EditError> {
+        let config_dir = self
+            .config
+            .first()
+            .and_then(|c| Path::new(&c.file).parent())
+            .unwrap_or(Path::new(""))
+            .to_path_buf();
+        let file = config_dir.join(new_path);
+        let include_arg = file
+            .strip_prefix(&config_dir)
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|_| file.clone());
+
+        let file_name = file.display().to_string();
+        if self
+            .config
+            .iter()
+            .any(|c| Path::new(&c.file) == file.as_path())
+        {
+            return Err(EditError::FileExists(file_name));
+        }
+        let index = self.config.len();
+        let slot = directive_mut(self, &target).ok_or(EditError::NotFound(target))?;
+
+        let include = Directive {
+            args: vec![path_arg(&include_arg)],
+            includes: Some(vec![index]),
+            in_if: slot.in_if,
+            ..Directive::new("include", slot.line)
+        };
+        let extracted = std::mem::replace(slot, include);
+
+        let mut config = ConfigFile::new(file_name);
+        config.parsed.push(extracted);
+        self.config.push(config);
+        self.mark_changed();
+        Ok(())
+    }
+
+    /// Applies the edits of a finding's suggestion. Nothing is changed when
+    /// one of the edits points at no directive.
+    pub fn apply_suggestion(&mut self, suggestion: &Suggestion) -> Result<(), EditError> {
+        for edit in &suggestion.edits {
+            let at = edit.location();
+            let found = match edit {
+                TreeEdit::Insert { .. } => {
+                    block_mut(self, at).is_some_and(|(block, i)| i <= block.len())
+                }
+                _ => directive_mut(self, at).is_some(),
+            };
+            if !found {
+                return Err(EditError::NotFound(at.clone()));
+            }
+        }
+
+        // later locations first, so the indexes of earlier ones still hold
+        let mut edits: Vec<&TreeEdit> = suggestion.edits.iter().collect();
+        edits.sort_by(|a, b| {
+            let (a, b) = (a.location(), b.location());
+            (b.file, &b.path).cmp(&(a.file, &a.path))
+        });
+        for edit in edits {
+            let (block, i) = block_mut(self, edit.location()).unwrap();
+            match edit {
+                TreeEdit::Insert { directive, .. } => block.insert(i, directive.clone()),
+                TreeEdit::Remove(_) => {
+                    block.remove(i);
+                }
+                TreeEdit::Replace { directive, .. } => block[i] = directive.clone(),
+            }
+        }
+        self.mark_changed();
+        Ok(())
+    }
+}
+
+/// A change to one directive of a config source, see [`edit_source`]. The
+/// directive is given by its indexes in the parsed tree of the source,
+/// outermost first, with comments not counted as in a default parse.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DirectiveEdit {
+    /// Replace the arg at `index`; `if` conditions count their parentheses
+    /// as part of the first and last args.
+    ReplaceArg {
+        path: Vec<usize>,
+        index: usize,
+        value: String,
+    },
+    /// Add an arg after the last one.
+    AppendArg { path: Vec<usize>, value: String },
+    /// Remove the directive, with its block and the line it was on when
+    /// nothing else is on that line.
+    Delete { path: Vec<usize> },
+}
+
+impl DirectiveEdit {
+    fn path(&self) -> &[usize] {
+        match self {
+            DirectiveEdit::ReplaceArg { path, .. }
+            | DirectiveEdit::AppendArg { path, .. }
+            | DirectiveEdit::Delete { path } => path,
+        }
+    }
+}
+
+/// Applies `edits` to a config source by splicing only the bytes of the
+/// changed args or deleted directives, keeping the formatting, comments and
+/// everything else as it was. Args are quoted when they need to be.
+pub fn edit_source(source: &str, edits: &[DirectiveEdit]) -> Result<String, EditError> {
+    let tokens = lex(source.as_bytes()).map_err(|e| EditError::Syntax(e.into()))?;
+    let (tree, _) = spanned_block(&mut tokens.into_iter());
+
+    let mut splices = Vec::new();
+    for edit in edits {
+        let directive = spanned_directive(&tree, edit.path()).ok_or_else(|| {
+            EditError::NotFound(DirectiveLocation {
+                file: 0,
+                path: edit.path().to_vec(),
+            })
+        })?;
+        let splice = match edit {
+            DirectiveEdit::ReplaceArg { index, value, .. } => {
+                let span = directive.args.get(*index).ok_or_else(|| {
+                    EditError::NotFound(DirectiveLocation {
+                        file: 0,
+                        path: edit.path().to_vec(),
+                    })
+                })?;
+                let value = match &source[directive.name.clone()] {
+                    "if" => if_arg(&source[span.clone()], value),
+                    _ => enquote(value),
+                };
+                (span.clone(), value)
+            }
+            DirectiveEdit::AppendArg { value, .. } => {
+                let end = directive.args.last().unwrap_or(&directive.name).end;
+                (end..end, format!(" {}", enquote(value)))
+            }
+            DirectiveEdit::Delete { .. } => (deleted_span(source, &directive.span), String::new()),
+        };
+        splices.push(splice);
+    }
+
+    splices.sort_by_key(|(span, _)| (span.start, span.end));
+    if splices.windows(2).any(|w| w[0].0.end > w[1].0.start) {
+        return Err(EditError::Overlapping);
+    }
+    let mut edited = source.to_string();
+    for (span, text) in splices.iter().rev() {
+        edited.replace_range(span.clone(), text);
+    }
+    Ok(edited)
+}
+
+// where a directive and its parts are in the source
+struct SpannedDirective {
+    // from the name to the ';' or the '}' closing the block
+    span: Range<usize>,
+    name: Range<usize>,
+    args: Vec<Range<usize>>,
+    block: Vec<SpannedDirective>,
+}
+
+// the directives of a block and the end of the '}' closing it, following
+// the parser in skipping comments
+fn spanned_block(
+    tokens: &mut impl Iterator<Item = NgxToken>,
+) -> (Vec<SpannedDirective>, Option<usize>) {
+    let mut block = Vec::new();
+    while let Some(token) = tokens.next() {
+        let special = |t: &NgxToken, value: &str| !t.is_quoted && t.value == value;
+        if special(&token, "}") {
+            return (block, Some(token.span.end));
+        }
+        if token.value.starts_with('#') && !token.is_quoted {
+            continue;
+        }
+
+        let mut directive = SpannedDirective {
+            span: token.span.clone(),
+            name: token.span,
+            args: Vec::new(),
+            block: Vec::new(),
+        };
+        loop {
+            let Some(token) = tokens.next() else {
+                block.push(directive);
+                return (block, None);
+            };
+            if special(&token, ";") {
+                directive.span.end = token.span.end;
+                break;
+            }
+            if special(&token, "{") {
+                let (children, end) = spanned_block(tokens);
+                directive.block = children;
+                directive.span.end = end.unwrap_or(token.span.end);
+                break;
+            }
+            if special(&token, "}") {
+                block.push(directive);
+                return (block, Some(token.span.end));
+            }
+            if !token.value.starts_with('#') || token.is_quoted {
+                directive.span.end = token.span.end;
+                directive.args.push(token.span);
+            }
+        }
+        block.push(directive);
+    }
+    (block, None)
+}
+
+fn spanned_directive<'a>(
+    tree: &'a [SpannedDirective],
+    path: &[usize],
+) -> Option<&'a SpannedDirective> {
+    let (first, rest) = path.split_first()?;
+    let mut directive = tree.get(*first)?;
+    for &i in rest {
+        directive = directive.block.get(i)?;
+    }
+    Some(directive)
+}
+
+// an arg of an if condition replacing `old`, keeping the parentheses
+// written with it
+fn if_arg(old: &str, value: &str) -> String {
+    let open = if old.starts_with('(') { "(" } else { "" };
+    let close = if old.len() > 1 && old.ends_with(')') {
+        ")"
+    } else {
+        ""
+    };
+    format!("{}{}{}", open, enquote(value), close)
+}
+
+// the span of a deleted directive, widened to its whole line when nothing
+// else is on it
+fn deleted_span(source: &str, span: &Range<usize>) -> Range<usize> {
+    let line_start = source[..span.start].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = source[span.end..]
+        .find('\n')
+        .map_or(source.len(), |i| span.end + i + 1);
+    let alone = source[line_start..span.start].trim().is_empty()
+        && source[span.end..line_end].trim().is_empty();
+    if alone {
+        return line_start..line_end;
+    }
+    // take the blanks after it along, or before it when it ends the line
+    let is_blank = |c: char| c == ' ' || c == '\t';
+    let after = source[span.end..].len() - source[span.end..].trim_start_matches(is_blank).len();
+    if source[span.end + after..].starts_with(['\n', '\r']) {
+        source[..span.start].trim_end_matches(is_blank).len()..span.end
+    } else {
+        span.start..span.end + after
+    }
+}
+
+// the directive at a location, mutably
+pub(crate) fn directive_mut<'a>(
+    payload: &'a mut Payload,
+    location: &DirectiveLocation,
+) -> Option<&'a mut Directive> {
+    let (first, rest) = location.path.split_first()?;
+    let mut directive = payload
+        .config
+        .get_mut(location.file)?
+        .parsed
+        .get_mut(*first)?;
+    for &i in rest {
+        directive = directive.block.as_mut()?.get_mut(i)?;
+    }
+    Some(directive)
+}
+
+// the block holding the directive at a location, with its index there
+fn block_mut<'a>(
+    payload: &'a mut Payload,
+    location: &DirectiveLocation,
+) -> Option<(&'a mut Vec<Directive>, usize)> {
+    let (last, parents) = location.path.split_last()?;
+    let mut block = &mut payload.config.get_mut(location.file)?.parsed;
+    for &i in parents {
+        block = block.get_mut(i)?.block.as_mut()?;
+    }
+    Some((block, *last))
+}
+
+// include args use '/' whatever the platform
+fn path_arg(path: &Path) -> String {
+    path.components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{build_files, parse, BuildOptions, ParseOptions};
+    use std::fs;
+
+    const NGINX_CONF: &str = "http {
+    include servers.conf;
+    server {
+        listen 80;
+        server_name example.com;
+    }
+}
+";
+
+    const SERVERS_CONF: &str = "server {
+    listen 8080;
+    server_name internal;
+}
+";
+
+    fn setup() -> (tempfile::TempDir, Payload) {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("nginx.conf"), NGINX_CONF).unwrap();
+        fs::write(dir.path().join("servers.conf"), SERVERS_CONF).unwrap();
+        let payload = parse(dir.path().join("nginx.conf"), &ParseOptions::default());
+        assert_eq!(payload.errors, []);
+        (dir, payload)
+    }
+
+    fn server_names(payload: &Payload) -> Vec<(String, String)> {
+        payload
+            .directives()
+            .filter(|d| d.directive().directive == "server_name")
+            .map(|d| {
+                let file = Path::new(&d.file().file).file_name().unwrap();
+                (
+                    file.to_string_lossy().into_owned(),
+                    d.directive().args[0].clone(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_extract_to_include() {
+        let (dir, mut payload) = setup();
+        let server = payload.directive_ref(0, &[0, 1]).unwrap();
+        payload
+            .extract_to_include(server.location(), Path::new("conf.d/example.com.conf"))
+            .unwrap();
+
+        assert_eq!(payload.config.len(), 3);
+        let include = &payload.config[0].parsed[0].block.as_ref().unwrap()[1];
+        assert_eq!(include.directive, "include");
+        assert_eq!(include.args, ["conf.d/example.com.conf"]);
+        assert_eq!(include.includes, Some(vec![2]));
+        assert_eq!(include.line, 3);
+        let new_file = dir.path().join("conf.d/example.com.conf");
+        assert_eq!(payload.config[2].file, new_file.display().to_string());
+        assert_eq!(payload.config[2].parsed[0].directive, "server");
+
+        build_files(&payload, "", &BuildOptions::default()).unwrap();
+        assert_eq!(
+            fs::read_to_string(&new_file).unwrap(),
+            "server {\n    listen 80;\n    server_name example.com;\n}\n"
+        );
+        let reparsed = parse(dir.path().join("nginx.conf"), &ParseOptions::default());
+        assert_eq!(reparsed.errors, []);
+        assert_eq!(
+            server_names(&reparsed),
+            [
+                ("servers.conf".to_string(), "internal".to_string()),
+                ("example.com.conf".to_string(), "example.com".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extract_to_include_from_included_file() {
+        let (dir, mut payload) = setup();
+        let location = DirectiveLocation {
+            file: 1,
+            path: vec![0],
+        };
+        payload
+            .extract_to_include(location, &dir.path().join("conf.d/internal.conf"))
+            .unwrap();
+
+        // the include goes where the server was, naming the file relative
+        // to the main config
+        let include = &payload.config[1].parsed[0];
+        assert_eq!(include.args, ["conf.d/internal.conf"]);
+        assert_eq!(include.includes, Some(vec![2]));
+
+        build_files(&payload, "", &BuildOptions::default()).unwrap();
+        let reparsed = parse(dir.path().join("nginx.conf"), &ParseOptions::default());
+        assert_eq!(reparsed.errors, []);
+        assert_eq!(
+            server_names(&reparsed),
+            [
+                ("nginx.conf".to_string(), "example.com".to_string()),
+                ("internal.conf".to_string(), "internal".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extract_to_include_errors() {
+        let (dir, mut payload) = setup();
+        let before = payload.clone();
+        let server = DirectiveLocation {
+            file: 0,
+            path: vec![0, 1],
+        };
+
+        let err = payload
+            .extract_to_include(server.clone(), Path::new("servers.conf"))
+            .unwrap_err();
+        let existing = dir.path().join("servers.conf").display().to_string();
+        assert_eq!(err, EditError::FileExists(existing));
+
+        let missing = DirectiveLocation {
+            file: 0,
+            path: vec![0, 5],
+        };
+        let err = payload
+            .extract_to_include(missing.clone(), Path::new("new.conf"))
+            .unwrap_err();
+        assert_eq!(err, EditError::NotFound(missing));
+        assert_eq!(payload, before);
+    }
+
+    fn messy() -> String {
+        fs::read_to_string("configs/messy/nginx.conf").unwrap()
+    }
+
+    #[test]
+    fn test_edit_source_replace_arg() {
+        let source = messy();
+        let edits = [DirectiveEdit::ReplaceArg {
+            path: vec![2, 3, 0],
+            index: 0,
+            value: "9090".to_string(),
+        }];
+        let edited = edit_source(&source, &edits).unwrap();
+
+        let start = source.find("\"8083\"").unwrap();
+        let end = start + "\"8083\"".len();
+        assert_eq!(edited[..start], source[..start]);
+        assert_eq!(edited[start..start + 4], *"9090");
+        assert_eq!(edited[start + 4..], source[end..]);
+
+        let payload = crate::parse::parse_source(&edited, &ParseOptions::default());
+        let listen = &payload.config[0].parsed[2].block.as_ref().unwrap()[3]
+            .block
+            .as_ref()
+            .unwrap()[0];
+        assert_eq!(listen.args, ["9090"]);
+    }
+
+    #[test]
+    fn test_edit_source() {
+        let source = "events {
+    worker_connections 1024;
+}
+http {
+    # comments are not counted
+    server {
+        listen 80;  # keep me
+        server_name example.com; root /srv;
+    }
+    if ($host = x) { return 404; }
+}
+";
+        let edits = [
+            DirectiveEdit::Delete { path: vec![0, 0] },
+            DirectiveEdit::AppendArg {
+                path: vec![1, 0, 0],
+                value: "default_server".to_string(),
+            },
+            DirectiveEdit::ReplaceArg {
+                path: vec![1, 0, 1],
+                index: 0,
+                value: "a b".to_string(),
+            },
+            DirectiveEdit::Delete {
+                path: vec![1, 0, 2],
+            },
+            DirectiveEdit::ReplaceArg {
+                path: vec![1, 1],
+                index: 2,
+                value: "y".to_string(),
+            },
+        ];
+        assert_eq!(
+            edit_source(source, &edits).unwrap(),
+            "events {
+}
+http {
+    # comments are not counted
+    server {
+        listen 80 default_server;  # keep me
+        server_name \"a b\";
+    }
+    if ($host = y) { return 404; }
+}
+"
+        );
+
+        // only the parentheses of if conditions are kept
+        let replace = |source: &str, index: usize, value: &str| {
+            let edit = DirectiveEdit::ReplaceArg {
+                path: vec![0],
+                index,
+                value: value.to_string(),
+            };
+            edit_source(source, &[edit]).unwrap()
+        };
+        assert_eq!(replace("if ($a) {}\n", 0, "$b"), "if ($b) {}\n");
+        assert_eq!(replace("set $a (x);\n", 1, "y"), "set $a y;\n");
+
+        let missing = [DirectiveEdit::Delete { path: vec![3] }];
+        assert_eq!(
+            edit_source(source, &missing),
+            Err(EditError::NotFound(DirectiveLocation {
+                file: 0,
+                path: vec![3]
+            }))
+        );
+        let overlapping = [
+            DirectiveEdit::Delete { path: vec![1, 0] },
+            DirectiveEdit::AppendArg {
+                path: vec![1, 0, 0],
+                value: "ssl".to_string(),
+            },
+        ];
+        assert_eq!(
+            edit_source(source, &overlapping),
+            Err(EditError::Overlapping)
+        );
+    }
+}
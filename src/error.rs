@@ -0,0 +1,146 @@
+use std::fmt;
+
+use crate::span::Span;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    /// The config is not valid nginx syntax.
+    #[default]
+    Syntax,
+    /// A file could not be opened or read.
+    Io,
+    /// The files read so far exceed `ParseOptions::max_total_bytes`.
+    InputTooLarge,
+    /// The parse was stopped through its `CancelToken`.
+    Cancelled,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub what: String,
+    pub file: Option<String>,
+    pub line: Option<usize>,
+    pub kind: ParseErrorKind,
+    /// Index of the arg the error is about, when it is about one.
+    pub arg_index: Option<usize>,
+    /// Where that arg was written, when the config was parsed with
+    /// [`ParseOptions::arg_spans`](crate::ParseOptions::arg_spans).
+    pub span: Option<Span>,
+}
+
+impl ParseError {
+    pub fn new(what: impl Into<String>, line: usize) -> Self {
+        ParseError {
+            what: what.into(),
+            file: None,
+            line: Some(line),
+            kind: ParseErrorKind::Syntax,
+            arg_index: None,
+            span: None,
+        }
+    }
+
+    // an error about a whole file rather than a line of it
+    pub(crate) fn for_file(what: impl Into<String>, file: &str, kind: ParseErrorKind) -> Self {
+        ParseError {
+            what: what.into(),
+            file: Some(file.to_string()),
+            line: None,
+            kind,
+            arg_index: None,
+            span: None,
+        }
+    }
+
+    // the error of a parse stopped through its cancel token
+    pub(crate) fn cancelled(line: Option<usize>) -> Self {
+        ParseError {
+            what: "parse cancelled".to_string(),
+            file: None,
+            line,
+            kind: ParseErrorKind::Cancelled,
+            arg_index: None,
+            span: None,
+        }
+    }
+
+    pub(crate) fn at_arg(mut self, index: usize, span: Option<Span>) -> Self {
+        self.arg_index = Some(index);
+        self.span = span;
+        self
+    }
+
+    pub(crate) fn in_file(mut self, file: &str) -> Self {
+        if self.file.is_none() {
+            self.file = Some(file.to_string());
+        }
+        self
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.file, self.line) {
+            (Some(file), Some(line)) => write!(f, "{} in {}:{}", self.what, file, line),
+            (Some(file), None) => write!(f, "{} in {}", self.what, file),
+            _ => write!(f, "{}", self.what),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Why [`lex`](crate::lex::lex) could not read a config into tokens, like
+/// braces that do not balance.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LexError {
+    message: String,
+    line: usize,
+    kind: ParseErrorKind,
+}
+
+impl LexError {
+    // the error carried by the last token of an in-band token stream
+    pub(crate) fn from_token_error(error: ParseError, token_line: usize) -> Self {
+        LexError {
+            message: error.what,
+            line: error.line.unwrap_or(token_line),
+            kind: error.kind,
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// `Syntax`, `Io` for input that could not be read or is not UTF-8, or
+    /// `Cancelled` for a lex stopped through its cancel token.
+    pub fn kind(&self) -> ParseErrorKind {
+        self.kind
+    }
+}
+
+impl fmt::Display for LexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at line {}", self.message, self.line)
+    }
+}
+
+impl std::error::Error for LexError {}
+
+impl From<LexError> for ParseError {
+    fn from(error: LexError) -> Self {
+        ParseError {
+            what: error.message,
+            file: None,
+            line: Some(error.line),
+            kind: error.kind,
+            arg_index: None,
+            span: None,
+        }
+    }
+}
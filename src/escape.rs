@@ -0,0 +1,279 @@
+use std::borrow::Cow;
+use std::iter::Peekable;
+
+use smol_str::SmolStr;
+
+use crate::parse::Directive;
+
+/// A char of a config as the lexer reads it, see [`process`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcessedChar {
+    /// The char, a backslash and the char it escapes, `"\n"` for a lone
+    /// carriage return, or nothing for the backslash of a line continuation
+    /// and the carriage return of a `"\r\n"`.
+    pub text: SmolStr,
+    /// How many bytes of the source it was read from.
+    pub raw_len: usize,
+    /// How many lines it ends, 0 or 1.
+    pub line_delta: u8,
+}
+
+impl ProcessedChar {
+    fn new(text: impl Into<SmolStr>, raw_len: usize, line_delta: u8) -> Self {
+        ProcessedChar {
+            text: text.into(),
+            raw_len,
+            line_delta,
+        }
+    }
+}
+
+/// Splits a config into the chars the lexer reads.
+///
+/// A backslash is kept together with the char it escapes, so values like
+/// `"\$foo"` reach the parser unchanged, while the backslash of a line
+/// continuation is dropped. Line endings all become newlines. The
+/// `raw_len` of the chars add up to the length of `input`, mapping each one
+/// back to its bytes.
+pub fn process(input: &str) -> Vec<ProcessedChar> {
+    let mut processed = Vec::new();
+    for c in processed_chars(input.chars()) {
+        if c.is_continuation() {
+            // the backslash and a carriage return before the newline are
+            // left out
+            for _ in 1..c.raw_len {
+                processed.push(ProcessedChar::new("", 1, 0));
+            }
+            processed.push(ProcessedChar::new("\n", 1, 1));
+            continue;
+        }
+        let text = match c.ch {
+            None => SmolStr::default(),
+            Some(ch) if c.escaped => SmolStr::from_iter(['\\', ch]),
+            Some(ch) => SmolStr::from(&*ch.encode_utf8(&mut [0; 4])),
+        };
+        processed.push(ProcessedChar::new(text, c.raw_len, c.line_delta));
+    }
+    processed
+}
+
+// a char as the lexer reads it, the one of a ProcessedChar without the
+// string holding it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct LexChar {
+    // None for the carriage return of a "\r\n"
+    pub(crate) ch: Option<char>,
+    // whether a backslash comes before the char
+    pub(crate) escaped: bool,
+    pub(crate) raw_len: usize,
+    pub(crate) line_delta: u8,
+}
+
+impl LexChar {
+    // a backslash ending a line, which continues it outside quotes and
+    // comments and is kept inside them
+    pub(crate) fn is_continuation(&self) -> bool {
+        self.escaped && self.ch == Some('\n')
+    }
+
+    fn new(ch: Option<char>, escaped: bool, raw_len: usize, line_delta: u8) -> Self {
+        LexChar {
+            ch,
+            escaped,
+            raw_len,
+            line_delta,
+        }
+    }
+}
+
+pub(crate) fn processed_chars<I: Iterator<Item = char>>(chars: I) -> ProcessedChars<I> {
+    ProcessedChars {
+        chars: chars.peekable(),
+    }
+}
+
+// the chars of a source as the lexer reads them, see processed_chars
+pub(crate) struct ProcessedChars<I: Iterator<Item = char>> {
+    chars: Peekable<I>,
+}
+
+impl<I: Iterator<Item = char>> Iterator for ProcessedChars<I> {
+    type Item = LexChar;
+
+    fn next(&mut self) -> Option<LexChar> {
+        let chars = &mut self.chars;
+        let ch = chars.next()?;
+        let processed = match ch {
+            // a backslash ending a line is read with the line ending, whatever
+            // it is, as an escaped newline
+            '\\' if matches!(chars.peek(), Some('\n' | '\r')) => {
+                let mut raw_len = 2;
+                if chars.next() == Some('\r') && chars.peek() == Some(&'\n') {
+                    chars.next();
+                    raw_len += 1;
+                }
+                LexChar::new(Some('\n'), true, raw_len, 1)
+            }
+            '\\' => match chars.next() {
+                Some(next) => LexChar::new(Some(next), true, 1 + next.len_utf8(), 0),
+                None => LexChar::new(Some('\\'), false, 1, 0),
+            },
+            // a lone carriage return ends a line like the newline of other
+            // files, one followed by a newline is dropped
+            '\r' if chars.peek() == Some(&'\n') => LexChar::new(None, false, 1, 0),
+            '\r' | '\n' => LexChar::new(Some('\n'), false, 1, 1),
+            _ => LexChar::new(Some(ch), false, ch.len_utf8(), 0),
+        };
+        Some(processed)
+    }
+}
+
+/// Returns the value nginx uses for a parsed arg.
+///
+/// nginx resolves `\"`, `\'` and `\\` to the escaped character and `\t`,
+/// `\r` and `\n` to the control characters; any other backslash is kept. The
+/// same rules apply to quoted and unquoted args.
+pub fn arg_unescaped(arg: &str) -> Cow<'_, str> {
+    if !arg.contains('\\') {
+        return Cow::Borrowed(arg);
+    }
+
+    let mut value = String::with_capacity(arg.len());
+    let mut chars = arg.chars().peekable();
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            value.push(ch);
+            continue;
+        }
+        match chars.peek() {
+            Some(&next @ ('"' | '\'' | '\\')) => {
+                value.push(next);
+                chars.next();
+            }
+            Some('t') => {
+                value.push('\t');
+                chars.next();
+            }
+            Some('r') => {
+                value.push('\r');
+                chars.next();
+            }
+            Some('n') => {
+                value.push('\n');
+                chars.next();
+            }
+            _ => value.push(ch),
+        }
+    }
+    Cow::Owned(value)
+}
+
+impl Directive {
+    /// The arg at `index` as nginx uses it, see [`arg_unescaped`].
+    pub fn arg_unescaped(&self, index: usize) -> Option<Cow<'_, str>> {
+        self.args.get(index).map(|arg| arg_unescaped(arg))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::{parse, ParseOptions};
+
+    #[test]
+    fn test_arg_unescaped() {
+        let cases = [
+            ("plain", "plain"),
+            ("", ""),
+            (r#"a\"b"#, r#"a"b"#),
+            (r"a\'b", "a'b"),
+            (r"a\\b", r"a\b"),
+            (r"a\tb\rc\nd", "a\tb\rc\nd"),
+            (r"a\\nb", r"a\nb"),
+            (r"\$host", r"\$host"),
+            (r"/\{\;\}\ #\ ab", r"/\{\;\}\ #\ ab"),
+            (r"P\{O\)\###\;ST", r"P\{O\)\###\;ST"),
+            (r"trailing\", r"trailing\"),
+            (r"\\\\", r"\\"),
+        ];
+
+        for (arg, expected) in cases {
+            assert_eq!(arg_unescaped(arg), expected, "arg: {:?}", arg);
+        }
+    }
+
+    // the source of each char, with the length of its text and line delta
+    fn processed(input: &str) -> Vec<(&str, usize, u8)> {
+        let mut offset = 0;
+        process(input)
+            .into_iter()
+            .map(|ch| {
+                let raw = &input[offset..offset + ch.raw_len];
+                offset += ch.raw_len;
+                (raw, ch.text.len(), ch.line_delta)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_process() {
+        // the escapes of the messy fixture, kept whole
+        for escaped in [
+            r"\n", r"\\", r"\#", r"\$", r#"\""#, r"\'", r"\ ", r"\;", r"\{", r"\}", r"\)",
+        ] {
+            assert_eq!(processed(escaped), [(escaped, 2, 0)], "{:?}", escaped);
+        }
+        assert_eq!(processed("\\é"), [("\\é", 3, 0)]);
+        assert_eq!(processed("a\\"), [("a", 1, 0), ("\\", 1, 0)]);
+
+        // line continuations and carriage returns, the text being empty for
+        // the chars left out
+        assert_eq!(
+            processed("a\\\nb\\\r\nc\\\rd"),
+            [
+                ("a", 1, 0),
+                ("\\", 0, 0),
+                ("\n", 1, 1),
+                ("b", 1, 0),
+                ("\\", 0, 0),
+                ("\r", 0, 0),
+                ("\n", 1, 1),
+                ("c", 1, 0),
+                ("\\", 0, 0),
+                ("\r", 1, 1),
+                ("d", 1, 0),
+            ]
+        );
+        assert_eq!(
+            processed("\r\r\n"),
+            [("\r", 1, 1), ("\r", 0, 0), ("\n", 1, 1)]
+        );
+
+        let source = std::fs::read_to_string("configs/messy/nginx.conf").unwrap();
+        let chars = process(&source);
+        assert_eq!(
+            chars.iter().map(|ch| ch.raw_len).sum::<usize>(),
+            source.len()
+        );
+        let lines: usize = chars.iter().map(|ch| usize::from(ch.line_delta)).sum();
+        assert_eq!(lines, source.lines().count());
+    }
+
+    #[test]
+    fn test_arg_unescaped_messy() {
+        let payload = parse("configs/messy/nginx.conf", &ParseOptions::default());
+        let http = &payload.config[0].parsed[2];
+        let ret = &http.block.as_ref().unwrap()[3].block.as_ref().unwrap()[1];
+        assert_eq!(ret.directive, "return");
+        assert_eq!(
+            ret.args[1],
+            r#"Ser" ' ' ver\\ \ $server_addr:\$server_port\n\nTime: $time_local\n\n"#
+        );
+        assert_eq!(
+            ret.arg_unescaped(1).unwrap(),
+            "Ser\" ' ' ver\\ \\ $server_addr:\\$server_port\n\nTime: $time_local\n\n"
+        );
+        assert!(matches!(ret.arg_unescaped(0), Some(Cow::Borrowed("200"))));
+        assert_eq!(ret.arg_unescaped(2), None);
+    }
+}
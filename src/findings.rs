@@ -0,0 +1,501 @@
+use std::collections::BTreeMap;
+use std::ops::Deref;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{ParseError, ParseErrorKind};
+use crate::lint::{Finding, Severity, Suggestion};
+use crate::parse::Payload;
+
+/// How [`Findings::to_text`] writes findings, one line each.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TextStyle {
+    /// `path:line:col: level: message`, as GCC writes diagnostics and
+    /// editors read them, without the column when it is not known.
+    #[default]
+    Gcc,
+    /// Like `Gcc`, without the path when all findings are in one file.
+    Short,
+    /// Like `Gcc`, with the kind of the finding and what its suggestion
+    /// does.
+    Full,
+}
+
+/// How many annotations of each level GitHub shows for a step of a
+/// workflow, see [`Findings::to_github_annotations`].
+pub const GITHUB_ANNOTATION_LIMIT: usize = 10;
+
+/// Findings of any number of passes, kept sorted by file, line and kind,
+/// and without two findings of the same kind at the same place.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "Vec<Finding>", into = "Vec<Finding>")]
+pub struct Findings {
+    findings: Vec<Finding>,
+}
+
+impl Findings {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a finding in its place, unless one of the same kind is already
+    /// at the same place. Returns whether it was added.
+    pub fn push(&mut self, finding: Finding) -> bool {
+        match self
+            .findings
+            .binary_search_by(|f| sort_key(f).cmp(&sort_key(&finding)))
+        {
+            Ok(_) => false,
+            Err(i) => {
+                self.findings.insert(i, finding);
+                true
+            }
+        }
+    }
+
+    /// Adds the findings of another pass, skipping those already here.
+    pub fn merge(&mut self, other: Findings) {
+        self.extend(other.findings);
+    }
+
+    /// The findings of each file, by file name.
+    pub fn by_file(&self) -> BTreeMap<&str, Vec<&Finding>> {
+        let mut files: BTreeMap<&str, Vec<&Finding>> = BTreeMap::new();
+        for finding in &self.findings {
+            files.entry(&finding.file).or_default().push(finding);
+        }
+        files
+    }
+
+    /// The findings of each check, by kind.
+    pub fn by_kind(&self) -> BTreeMap<&str, Vec<&Finding>> {
+        let mut kinds: BTreeMap<&str, Vec<&Finding>> = BTreeMap::new();
+        for finding in &self.findings {
+            kinds.entry(&finding.kind).or_default().push(finding);
+        }
+        kinds
+    }
+
+    /// Number of findings of `severity` or worse.
+    pub fn count_at_least(&self, severity: Severity) -> usize {
+        self.findings
+            .iter()
+            .filter(|f| f.severity >= severity)
+            .count()
+    }
+
+    /// The findings as lines of text in their order, with line breaks in
+    /// messages escaped.
+    pub fn to_text(&self, style: TextStyle) -> String {
+        let one_file = self.findings.windows(2).all(|w| w[0].file == w[1].file);
+        let mut out = String::new();
+        for finding in &self.findings {
+            if style != TextStyle::Short || !one_file {
+                out += &format!("{}:", finding.file);
+            }
+            out += &format!("{}:", finding.line);
+            // spans are in bytes, like the columns of GCC
+            let column = finding
+                .column
+                .or_else(|| finding.span.as_ref().map(|span| span.start + 1));
+            if let Some(column) = column {
+                out += &format!("{}:", column);
+            }
+            let level = match finding.severity {
+                Severity::Error => "error",
+                Severity::Warning => "warning",
+                Severity::Info => "note",
+            };
+            out += &format!(" {}: {}", level, escape_line_breaks(&finding.message));
+            if style == TextStyle::Full {
+                out += &format!(" [{}]", finding.kind);
+                if let Some(Suggestion { summary, .. }) = &finding.suggestion {
+                    out += &format!(" (fix: {})", escape_line_breaks(summary));
+                }
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    /// The findings as GitHub Actions workflow commands, which show them
+    /// as annotations of the lines they are on. GitHub shows at most
+    /// [`GITHUB_ANNOTATION_LIMIT`] annotations of each level for a step.
+    pub fn to_github_annotations(&self) -> String {
+        self.to_github_annotations_limited(GITHUB_ANNOTATION_LIMIT)
+    }
+
+    /// Like [`to_github_annotations`], writing at most `limit` annotations
+    /// of each level and then a line telling how many were left out.
+    ///
+    /// [`to_github_annotations`]: Findings::to_github_annotations
+    pub fn to_github_annotations_limited(&self, limit: usize) -> String {
+        let mut out = String::new();
+        let mut written: BTreeMap<&str, usize> = BTreeMap::new();
+        let mut left_out = 0;
+        for finding in &self.findings {
+            let level = match finding.severity {
+                Severity::Error => "error",
+                Severity::Warning => "warning",
+                Severity::Info => "notice",
+            };
+            let count = written.entry(level).or_default();
+            if *count == limit {
+                left_out += 1;
+                continue;
+            }
+            *count += 1;
+
+            out += &format!(
+                "::{} file={},line={}",
+                level,
+                escape_property(&finding.file),
+                finding.line
+            );
+            let column = finding
+                .column
+                .or_else(|| finding.span.as_ref().map(|span| span.start + 1));
+            if let Some(column) = column {
+                out += &format!(",col={}", column);
+            }
+            out += &format!("::{}\n", escape_data(&finding.message));
+        }
+        if left_out > 0 {
+            out += &format!(
+                "{} more findings not annotated, over the limit of {} per level\n",
+                left_out, limit
+            );
+        }
+        out
+    }
+
+    pub fn into_vec(self) -> Vec<Finding> {
+        self.findings
+    }
+}
+
+// the order of findings; two findings with the same key are duplicates
+fn sort_key(finding: &Finding) -> (&str, usize, &str, Option<usize>, Option<usize>) {
+    (
+        &finding.file,
+        finding.line,
+        &finding.kind,
+        finding.column,
+        finding.arg_index,
+    )
+}
+
+fn escape_line_breaks(text: &str) -> String {
+    text.replace('\r', "\\r").replace('\n', "\\n")
+}
+
+// the message of a workflow command, as the runner unescapes it
+fn escape_data(text: &str) -> String {
+    text.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+// a property of a workflow command, where ':' and ',' are delimiters
+fn escape_property(text: &str) -> String {
+    escape_data(text).replace(':', "%3A").replace(',', "%2C")
+}
+
+impl Deref for Findings {
+    type Target = [Finding];
+
+    fn deref(&self) -> &[Finding] {
+        &self.findings
+    }
+}
+
+impl Extend<Finding> for Findings {
+    fn extend<T: IntoIterator<Item = Finding>>(&mut self, iter: T) {
+        for finding in iter {
+            self.push(finding);
+        }
+    }
+}
+
+impl FromIterator<Finding> for Findings {
+    fn from_iter<T: IntoIterator<Item = Finding>>(iter: T) -> Self {
+        let mut findings = Findings::new();
+        findings.extend(iter);
+        findings
+    }
+}
+
+impl From<Vec<Finding>> for Findings {
+    fn from(findings: Vec<Finding>) -> Self {
+        findings.into_iter().collect()
+    }
+}
+
+impl From<Findings> for Vec<Finding> {
+    fn from(findings: Findings) -> Self {
+        findings.findings
+    }
+}
+
+impl IntoIterator for Findings {
+    type Item = Finding;
+    type IntoIter = std::vec::IntoIter<Finding>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.findings.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Findings {
+    type Item = &'a Finding;
+    type IntoIter = std::slice::Iter<'a, Finding>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.findings.iter()
+    }
+}
+
+impl From<&ParseError> for Finding {
+    fn from(error: &ParseError) -> Self {
+        let kind = match error.kind {
+            ParseErrorKind::Syntax => "syntax-error",
+            ParseErrorKind::Io => "io-error",
+            ParseErrorKind::InputTooLarge => "input-too-large",
+            ParseErrorKind::Cancelled => "cancelled",
+        };
+        Finding::new(
+            kind,
+            Severity::Error,
+            &error.what,
+            error.file.as_deref().unwrap_or_default(),
+            error.line.unwrap_or_default(),
+        )
+    }
+}
+
+impl Payload {
+    /// The errors of the parse together with the findings of [`lint`].
+    ///
+    /// [`lint`]: Payload::lint
+    pub fn findings(&self) -> Findings {
+        let mut findings: Findings = self.errors.iter().map(Finding::from).collect();
+        findings.merge(self.lint());
+        findings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::{parse_source, ParseOptions};
+    use crate::span::Span;
+
+    fn finding(kind: &str, severity: Severity, file: &str, line: usize) -> Finding {
+        Finding::new(kind, severity, format!("{} here", kind), file, line)
+    }
+
+    #[test]
+    fn test_findings() {
+        let first_pass: Findings = [
+            finding("unknown-variable", Severity::Error, "conf.d/b.conf", 3),
+            finding("if-hazard", Severity::Warning, "nginx.conf", 12),
+            finding("raw-directive", Severity::Warning, "conf.d/a.conf", 7),
+            finding("if-hazard", Severity::Error, "conf.d/b.conf", 3),
+        ]
+        .into_iter()
+        .collect();
+        let second_pass: Findings = [
+            // already found by the first pass, with another message
+            Finding::new("if-hazard", Severity::Warning, "again", "nginx.conf", 12),
+            finding("unknown-log-format", Severity::Error, "nginx.conf", 2),
+            finding("raw-directive", Severity::Info, "conf.d/a.conf", 1),
+        ]
+        .into_iter()
+        .collect();
+
+        let mut findings = first_pass;
+        findings.merge(second_pass);
+        let order: Vec<(&str, usize, &str)> = findings
+            .iter()
+            .map(|f| (f.file.as_str(), f.line, f.kind.as_str()))
+            .collect();
+        assert_eq!(
+            order,
+            [
+                ("conf.d/a.conf", 1, "raw-directive"),
+                ("conf.d/a.conf", 7, "raw-directive"),
+                ("conf.d/b.conf", 3, "if-hazard"),
+                ("conf.d/b.conf", 3, "unknown-variable"),
+                ("nginx.conf", 2, "unknown-log-format"),
+                ("nginx.conf", 12, "if-hazard"),
+            ]
+        );
+        assert_eq!(findings[5].message, "if-hazard here");
+        assert!(!findings.push(finding("if-hazard", Severity::Info, "nginx.conf", 12)));
+
+        let files: Vec<(&str, usize)> = findings
+            .by_file()
+            .into_iter()
+            .map(|(file, findings)| (file, findings.len()))
+            .collect();
+        assert_eq!(
+            files,
+            [
+                ("conf.d/a.conf", 2),
+                ("conf.d/b.conf", 2),
+                ("nginx.conf", 2)
+            ]
+        );
+        let kinds: Vec<(&str, usize)> = findings
+            .by_kind()
+            .into_iter()
+            .map(|(kind, findings)| (kind, findings.len()))
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                ("if-hazard", 2),
+                ("raw-directive", 2),
+                ("unknown-log-format", 1),
+                ("unknown-variable", 1),
+            ]
+        );
+        assert_eq!(findings.count_at_least(Severity::Info), 6);
+        assert_eq!(findings.count_at_least(Severity::Warning), 5);
+        assert_eq!(findings.count_at_least(Severity::Error), 3);
+
+        let json = serde_json::to_value(&findings).unwrap();
+        assert_eq!(json[0]["severity"], "info");
+        assert_eq!(json[0]["file"], "conf.d/a.conf");
+        let back: Findings = serde_json::from_value(json).unwrap();
+        assert_eq!(back, findings);
+    }
+
+    #[test]
+    fn test_to_text() {
+        let mut unknown = finding("unknown-variable", Severity::Error, "conf.d/b.conf", 3);
+        unknown.column = Some(12);
+        let mut flag = Finding::new(
+            "invalid-flag",
+            Severity::Warning,
+            "invalid value \"yes\"\nfor \"gzip\"",
+            "nginx.conf",
+            7,
+        );
+        flag.span = Some(Span {
+            line: 7,
+            start: 9,
+            end: 12,
+        });
+        flag.suggestion = Some(Suggestion {
+            summary: "replace \"yes\" with \"on\"".to_string(),
+            edits: Vec::new(),
+        });
+        let findings: Findings = [
+            flag,
+            unknown,
+            finding("raw-directive", Severity::Info, "nginx.conf", 2),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(
+            findings.to_text(TextStyle::Gcc),
+            "conf.d/b.conf:3:12: error: unknown-variable here
+nginx.conf:2: note: raw-directive here
+nginx.conf:7:10: warning: invalid value \"yes\"\\nfor \"gzip\"
+"
+        );
+        // several files keep their paths
+        assert_eq!(
+            findings.to_text(TextStyle::Short),
+            findings.to_text(TextStyle::Gcc)
+        );
+        assert_eq!(
+            findings.to_text(TextStyle::Full),
+            "conf.d/b.conf:3:12: error: unknown-variable here [unknown-variable]
+nginx.conf:2: note: raw-directive here [raw-directive]
+nginx.conf:7:10: warning: invalid value \"yes\"\\nfor \"gzip\" [invalid-flag] \
+             (fix: replace \"yes\" with \"on\")
+"
+        );
+
+        let one_file: Findings = findings
+            .iter()
+            .filter(|f| f.file == "nginx.conf")
+            .cloned()
+            .collect();
+        assert_eq!(
+            one_file.to_text(TextStyle::Short),
+            "2: note: raw-directive here
+7:10: warning: invalid value \"yes\"\\nfor \"gzip\"
+"
+        );
+        assert_eq!(Findings::new().to_text(TextStyle::Full), "");
+    }
+
+    #[test]
+    fn test_to_github_annotations() {
+        let mut unknown = finding("unknown-variable", Severity::Error, "conf.d/b,1.conf", 3);
+        unknown.column = Some(12);
+        let mut flag = Finding::new(
+            "invalid-flag",
+            Severity::Warning,
+            "invalid value \"100%\"\r\nfor \"gzip\"",
+            "nginx.conf",
+            7,
+        );
+        flag.span = Some(Span {
+            line: 7,
+            start: 9,
+            end: 12,
+        });
+        let findings: Findings = [
+            flag,
+            unknown,
+            finding("raw-directive", Severity::Info, "nginx.conf", 2),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(
+            findings.to_github_annotations(),
+            "::error file=conf.d/b%2C1.conf,line=3,col=12::unknown-variable here
+::notice file=nginx.conf,line=2::raw-directive here
+::warning file=nginx.conf,line=7,col=10::invalid value \"100%25\"%0D%0Afor \"gzip\"
+"
+        );
+
+        // each level has its own limit
+        let many: Findings = (1..=4)
+            .map(|line| finding("if-hazard", Severity::Warning, "nginx.conf", line))
+            .chain([finding("syntax-error", Severity::Error, "nginx.conf", 9)])
+            .collect();
+        assert_eq!(
+            many.to_github_annotations_limited(2),
+            "::warning file=nginx.conf,line=1::if-hazard here
+::warning file=nginx.conf,line=2::if-hazard here
+::error file=nginx.conf,line=9::syntax-error here
+2 more findings not annotated, over the limit of 2 per level
+"
+        );
+        assert_eq!(
+            many.to_github_annotations(),
+            many.to_github_annotations_limited(10)
+        );
+        assert_eq!(Findings::new().to_github_annotations(), "");
+    }
+
+    #[test]
+    fn test_payload_findings() {
+        let source = "http {\n    if ($x) {\n        try_files $uri =404;\n    }\n";
+        let payload = parse_source(source, &ParseOptions::default().catch_errors(true));
+        let findings: Vec<String> = payload.findings().iter().map(|f| f.to_string()).collect();
+        assert_eq!(
+            findings,
+            [
+                "error: \"try_files\" is not allowed inside \"if\" in nginx.conf:3 [if-hazard]",
+                "error: unexpected end of file, expecting '}' in nginx.conf:4 [syntax-error]",
+            ]
+        );
+    }
+}
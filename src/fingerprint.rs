@@ -0,0 +1,211 @@
+use sha2::{Digest, Sha256};
+
+use crate::parse::{Directive, Payload};
+
+/// Version of the hashed encoding, fed to the hash before anything else.
+/// Fingerprints stay the same across crate versions until this changes.
+pub const FINGERPRINT_VERSION: u8 = 1;
+
+/// What a fingerprint covers besides directive names, arguments and
+/// nesting.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FingerprintOptions {
+    pub(crate) lines: bool,
+    pub(crate) files: bool,
+    pub(crate) comments: bool,
+}
+
+impl FingerprintOptions {
+    /// Hash the line of every directive.
+    pub fn lines(mut self, lines: bool) -> Self {
+        self.lines = lines;
+        self
+    }
+
+    /// Hash the path of every config file of a payload.
+    pub fn files(mut self, files: bool) -> Self {
+        self.files = files;
+        self
+    }
+
+    /// Hash comments kept in the tree as `#` directives.
+    pub fn comments(mut self, comments: bool) -> Self {
+        self.comments = comments;
+        self
+    }
+}
+
+impl Payload {
+    /// A SHA-256 hash of the structure of every config file, in payload
+    /// order. Configs that differ only in formatting, line numbers or
+    /// comments hash the same by default.
+    pub fn fingerprint(&self, options: &FingerprintOptions) -> [u8; 32] {
+        let mut hasher = Fingerprinter::new(options);
+        hasher.count(self.config.len());
+        for config in &self.config {
+            if options.files {
+                hasher.bytes(config.file.as_bytes());
+            }
+            hasher.block(&config.parsed);
+        }
+        hasher.finish()
+    }
+}
+
+impl Directive {
+    /// A SHA-256 hash of the directive and its block, see
+    /// [`Payload::fingerprint`].
+    pub fn fingerprint(&self, options: &FingerprintOptions) -> [u8; 32] {
+        let mut hasher = Fingerprinter::new(options);
+        hasher.directive(self);
+        hasher.finish()
+    }
+}
+
+// feeds a tree to the hash with every string and list length-prefixed, so
+// that different trees never encode to the same bytes
+struct Fingerprinter<'a> {
+    hasher: Sha256,
+    options: &'a FingerprintOptions,
+}
+
+impl<'a> Fingerprinter<'a> {
+    fn new(options: &'a FingerprintOptions) -> Self {
+        let mut hasher = Sha256::new();
+        hasher.update([FINGERPRINT_VERSION]);
+        Fingerprinter { hasher, options }
+    }
+
+    fn count(&mut self, n: usize) {
+        self.hasher.update((n as u64).to_le_bytes());
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) {
+        self.count(bytes.len());
+        self.hasher.update(bytes);
+    }
+
+    fn block(&mut self, block: &[Directive]) {
+        let directives: Vec<&Directive> = block
+            .iter()
+            .filter(|d| self.options.comments || !d.is_comment())
+            .collect();
+        self.count(directives.len());
+        for directive in directives {
+            self.directive(directive);
+        }
+    }
+
+    fn directive(&mut self, directive: &Directive) {
+        self.bytes(directive.directive.as_bytes());
+        for text in [&directive.comment, &directive.raw].into_iter().flatten() {
+            self.bytes(text.as_bytes());
+        }
+        if self.options.lines {
+            self.count(directive.line);
+        }
+        self.count(directive.args.len());
+        for arg in &directive.args {
+            self.bytes(arg.as_bytes());
+        }
+        match &directive.block {
+            Some(block) => {
+                self.hasher.update([1]);
+                self.block(block);
+            }
+            None => self.hasher.update([0]),
+        }
+    }
+
+    fn finish(self) -> [u8; 32] {
+        self.hasher.finalize().into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::{parse_source, ParseOptions};
+
+    const CONFIG: &str = "events { worker_connections 1024; }
+http {
+    server {
+        listen 80;
+        location / { return 200 'ok'; }
+    }
+}
+";
+
+    // the same config spread over other lines, with comments
+    const REFORMATTED: &str = "# main config
+events {
+    worker_connections   1024;
+}
+
+http {
+    server {
+        listen 80; # plain http
+        location / {
+            return 200 \"ok\";
+        }
+    }
+}
+";
+
+    #[test]
+    fn test_fingerprint() {
+        let with_comments = ParseOptions::default().comments(true);
+        let a = parse_source(CONFIG, &with_comments);
+        let b = parse_source(REFORMATTED, &with_comments);
+        let options = FingerprintOptions::default();
+        assert_eq!(a.fingerprint(&options), b.fingerprint(&options));
+        assert_eq!(
+            a.config[0].parsed[1].fingerprint(&options),
+            b.config[0].parsed[2].fingerprint(&options)
+        );
+
+        assert_ne!(
+            a.fingerprint(&options.lines(true)),
+            b.fingerprint(&options.lines(true))
+        );
+        assert_ne!(
+            a.fingerprint(&options.comments(true)),
+            b.fingerprint(&options.comments(true))
+        );
+        assert_eq!(
+            a.fingerprint(&options.files(true)),
+            b.fingerprint(&options.files(true))
+        );
+
+        let changed = parse_source(&CONFIG.replace("1024", "2048"), &with_comments);
+        assert_ne!(a.fingerprint(&options), changed.fingerprint(&options));
+        assert_ne!(
+            a.config[0].parsed[0].fingerprint(&options),
+            changed.config[0].parsed[0].fingerprint(&options)
+        );
+        assert_eq!(
+            a.config[0].parsed[1].fingerprint(&options),
+            changed.config[0].parsed[1].fingerprint(&options)
+        );
+
+        // moving an argument into the name must not hash the same
+        let split = parse_source("ab c;", &ParseOptions::default());
+        let joined = parse_source("a bc;", &ParseOptions::default());
+        assert_ne!(split.fingerprint(&options), joined.fingerprint(&options));
+    }
+
+    #[test]
+    fn test_fingerprint_is_stable() {
+        // changing the encoding means bumping FINGERPRINT_VERSION and this hash
+        let payload = parse_source(CONFIG, &ParseOptions::default());
+        let hex: String = payload
+            .fingerprint(&FingerprintOptions::default())
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+        assert_eq!(
+            hex,
+            "3704611d0143bcc8d03d8d5e56c883ae2722e18feddb8154e6f54107301f29ac"
+        );
+    }
+}
@@ -0,0 +1,451 @@
+use std::fmt;
+
+use crate::error::ParseError;
+use crate::parse::{ConfigFile, Directive, Payload, Status};
+use crate::span::Span;
+
+/// A payload as flat lists, see [`Payload::flatten`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FlatPayload {
+    /// Every file of the payload in order, those without directives too.
+    pub files: Vec<FlatFile>,
+    /// The directives of all files in document order, each file after the
+    /// other.
+    pub directives: Vec<FlatDirective>,
+}
+
+/// A file of a [`FlatPayload`], with what the payload holds of it besides
+/// its directives.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlatFile {
+    pub file: String,
+    pub status: Status,
+    pub errors: Vec<ParseError>,
+    pub stubbed: bool,
+    pub shebang: Option<String>,
+}
+
+impl FlatFile {
+    pub fn new(file: impl Into<String>) -> Self {
+        FlatFile {
+            file: file.into(),
+            status: Status::Ok,
+            errors: Vec::new(),
+            stubbed: false,
+            shebang: None,
+        }
+    }
+}
+
+/// A directive of a flat list in document order, with its nesting depth in
+/// place of a block of children.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FlatDirective {
+    /// 0 for the directives at the top of a file, one more for each block
+    /// the directive is in.
+    pub depth: usize,
+    pub name: String,
+    pub args: Vec<String>,
+    pub line: usize,
+    pub file: String,
+    /// The file the directive was read from, in payloads parsed with
+    /// `ParseOptions::combine`, see [`Directive::file`].
+    pub source_file: Option<String>,
+    /// Whether the directive has a block, which holds the directives after
+    /// it one level deeper. Blocks may be empty.
+    pub is_block: bool,
+    pub includes: Option<Vec<usize>>,
+    pub comment: Option<String>,
+    pub block_end_line: Option<usize>,
+    pub in_if: bool,
+    pub raw: Option<String>,
+    pub raw_source: Option<String>,
+    pub placeholder: bool,
+    pub arg_spans: Vec<Span>,
+}
+
+/// Why a flat list does not describe a tree of directives.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StructureError {
+    /// The directive at `index` is more than one level deeper than the one
+    /// before it, or deeper than 0 as the first directive of a file.
+    DepthJump {
+        index: usize,
+        depth: usize,
+        max_depth: usize,
+    },
+    /// The directive at `index` is one level deeper than the one before it,
+    /// which has no block.
+    NotABlock { index: usize },
+    /// The directive at `index` is in another file than the block it is in.
+    FileInBlock { index: usize, file: String },
+    /// The directives of `file` are interrupted by those of another file,
+    /// at `index`, or come after those of files listed after it.
+    FileReopened { index: usize, file: String },
+    /// The directive at `index` is in a file the list of files lacks.
+    UnknownFile { index: usize, file: String },
+}
+
+impl fmt::Display for StructureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StructureError::DepthJump {
+                index,
+                depth,
+                max_depth,
+            } => write!(
+                f,
+                "directive {} is at depth {}, deeper than {}",
+                index, depth, max_depth
+            ),
+            StructureError::NotABlock { index } => write!(
+                f,
+                "directive {} is nested in a directive without a block",
+                index
+            ),
+            StructureError::FileInBlock { index, file } => write!(
+                f,
+                "directive {} of file {} is nested in a block of another file",
+                index, file
+            ),
+            StructureError::FileReopened { index, file } => write!(
+                f,
+                "directive {} continues file {} after another file",
+                index, file
+            ),
+            StructureError::UnknownFile { index, file } => {
+                write!(f, "directive {} is in unknown file {}", index, file)
+            }
+        }
+    }
+}
+
+impl std::error::Error for StructureError {}
+
+impl Payload {
+    /// The files of the payload, and the directives of all files as one
+    /// list in document order.
+    pub fn flatten(&self) -> FlatPayload {
+        let mut flat = FlatPayload::default();
+        for config in &self.config {
+            flat.files.push(FlatFile {
+                file: config.file.clone(),
+                status: config.status,
+                errors: config.errors.clone(),
+                stubbed: config.stubbed,
+                shebang: config.shebang.clone(),
+            });
+            flatten_block(&mut flat.directives, &config.file, &config.parsed, 0);
+        }
+        flat
+    }
+
+    /// Rebuilds the payload of flat lists, as made by [`flatten`], with a
+    /// config entry for each of the files.
+    ///
+    /// [`flatten`]: Payload::flatten
+    pub fn from_flat(flat: FlatPayload) -> Result<Payload, StructureError> {
+        let mut config: Vec<ConfigFile> = flat
+            .files
+            .into_iter()
+            .map(|file| ConfigFile {
+                status: file.status,
+                errors: file.errors,
+                stubbed: file.stubbed,
+                shebang: file.shebang,
+                ..ConfigFile::new(file.file)
+            })
+            .collect();
+        // the file of the directives so far, and its directives with the
+        // open blocks on top
+        let mut current: Option<usize> = None;
+        let mut stack: Vec<Vec<Directive>> = Vec::new();
+        // whether the directive before was a block
+        let mut after_block = false;
+
+        for (index, entry) in flat.directives.into_iter().enumerate() {
+            let new_file = current.is_none_or(|i| config[i].file != entry.file);
+            if new_file {
+                if entry.depth > 0 && current.is_none() {
+                    return Err(StructureError::DepthJump {
+                        index,
+                        depth: entry.depth,
+                        max_depth: 0,
+                    });
+                }
+                if entry.depth > 0 {
+                    return Err(StructureError::FileInBlock {
+                        index,
+                        file: entry.file,
+                    });
+                }
+                let Some(next) = config.iter().position(|c| c.file == entry.file) else {
+                    return Err(StructureError::UnknownFile {
+                        index,
+                        file: entry.file,
+                    });
+                };
+                if current.is_some_and(|i| next < i) {
+                    return Err(StructureError::FileReopened {
+                        index,
+                        file: entry.file,
+                    });
+                }
+                if let Some(i) = current {
+                    config[i].parsed = close_file(&mut stack);
+                }
+                current = Some(next);
+                stack = vec![Vec::new()];
+                after_block = false;
+            }
+
+            let depth = stack.len() - 1;
+            if entry.depth > depth + 1 {
+                return Err(StructureError::DepthJump {
+                    index,
+                    depth: entry.depth,
+                    max_depth: if after_block { depth + 1 } else { depth },
+                });
+            }
+            if entry.depth == depth + 1 {
+                if !after_block {
+                    return Err(StructureError::NotABlock { index });
+                }
+                stack.push(Vec::new());
+            } else if entry.depth < depth {
+                close_blocks(&mut stack, entry.depth);
+            }
+
+            after_block = entry.is_block;
+            stack
+                .last_mut()
+                .expect("the stack holds the top of the file")
+                .push(unflatten(entry));
+        }
+        if let Some(i) = current {
+            config[i].parsed = close_file(&mut stack);
+        }
+        Ok(Payload::from_config(config))
+    }
+}
+
+fn flatten_block(flat: &mut Vec<FlatDirective>, file: &str, block: &[Directive], depth: usize) {
+    for directive in block {
+        flat.push(FlatDirective {
+            depth,
+            name: directive.directive.clone(),
+            args: directive.args.clone(),
+            line: directive.line,
+            file: file.to_string(),
+            source_file: directive.file.clone(),
+            is_block: directive.is_block(),
+            includes: directive.includes.clone(),
+            comment: directive.comment.clone(),
+            block_end_line: directive.block_end_line,
+            in_if: directive.in_if,
+            raw: directive.raw.clone(),
+            raw_source: directive.raw_source.clone(),
+            placeholder: directive.placeholder,
+            arg_spans: directive.arg_spans.clone(),
+        });
+        if let Some(children) = &directive.block {
+            flatten_block(flat, file, children, depth + 1);
+        }
+    }
+}
+
+fn unflatten(entry: FlatDirective) -> Directive {
+    Directive {
+        directive: entry.name,
+        line: entry.line,
+        args: entry.args,
+        includes: entry.includes,
+        block: entry.is_block.then(Vec::new),
+        comment: entry.comment,
+        block_end_line: entry.block_end_line,
+        in_if: entry.in_if,
+        raw: entry.raw,
+        raw_source: entry.raw_source,
+        placeholder: entry.placeholder,
+        arg_spans: entry.arg_spans,
+        file: entry.source_file,
+    }
+}
+
+// moves the open blocks deeper than `depth` into the directives owning them
+fn close_blocks(stack: &mut Vec<Vec<Directive>>, depth: usize) {
+    while stack.len() > depth + 1 {
+        let children = stack.pop().expect("checked by the loop condition");
+        let owner = stack
+            .last_mut()
+            .and_then(|block| block.last_mut())
+            .expect("an open block has a directive owning it");
+        owner.block = Some(children);
+    }
+}
+
+// closes all blocks and returns the directives at the top of the file
+fn close_file(stack: &mut Vec<Vec<Directive>>) -> Vec<Directive> {
+    close_blocks(stack, 0);
+    stack.pop().unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::parse::{parse, parse_source, ParseOptions};
+
+    #[test]
+    fn test_flat_round_trip() {
+        let options = ParseOptions::default().comments(true);
+        for fixture in [
+            "simple",
+            "messy",
+            "with-comments",
+            "brace-adjacent",
+            "quote-behavior",
+        ] {
+            let payload = parse(format!("configs/{}/nginx.conf", fixture), &options);
+            let flat = payload.flatten();
+            assert_eq!(Payload::from_flat(flat).unwrap(), payload, "{}", fixture);
+        }
+
+        // combined payloads keep the file each directive was read from
+        let combined = parse(
+            "configs/includes/nginx.conf",
+            &ParseOptions::default().combine(true),
+        );
+        let flat = combined.flatten();
+        assert!(flat.directives.iter().any(|f| f
+            .source_file
+            .as_deref()
+            .is_some_and(|s| s.ends_with("locations.conf"))));
+        assert_eq!(Payload::from_flat(flat).unwrap(), combined);
+
+        let source = r#"http {
+    # upstreams
+    server {
+        { weird block; }
+        location / {}
+        content_by_lua_block { ngx.say("hi") }
+    }
+}
+events {}
+"#;
+        let options = ParseOptions::default().raw_fallback(true).comments(true);
+        let payload = parse_source(source, &options);
+        let flat = payload.flatten();
+        let depths: Vec<(usize, &str)> = flat
+            .directives
+            .iter()
+            .map(|f| (f.depth, f.name.as_str()))
+            .collect();
+        assert_eq!(
+            depths,
+            [
+                (0, "http"),
+                (1, "#"),
+                (1, "server"),
+                (2, "<raw>"),
+                (2, "location"),
+                (2, "content_by_lua_block"),
+                (3, "<raw>"),
+                (0, "events"),
+            ]
+        );
+        assert_eq!(Payload::from_flat(flat).unwrap(), payload);
+
+        // files without directives keep their place, and files their errors
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("nginx.conf"),
+            "include empty.conf;\ninclude broken.conf;\nevents {}\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join("empty.conf"), "# nothing yet\n").unwrap();
+        fs::write(dir.path().join("broken.conf"), "gzip on\n").unwrap();
+        let options = ParseOptions::default().catch_errors(true);
+        let payload = parse(dir.path().join("nginx.conf"), &options);
+        assert_eq!(payload.status, Status::Failed);
+        let flat = payload.flatten();
+        let files: Vec<_> = flat
+            .files
+            .iter()
+            .map(|f| (f.status, f.errors.len()))
+            .collect();
+        assert_eq!(
+            files,
+            [(Status::Ok, 0), (Status::Ok, 0), (Status::Failed, 1)]
+        );
+        assert_eq!(Payload::from_flat(flat).unwrap(), payload);
+    }
+
+    #[test]
+    fn test_flat_structure_errors() {
+        let entry = |depth: usize, name: &str, is_block: bool, file: &str| FlatDirective {
+            depth,
+            name: name.to_string(),
+            file: file.to_string(),
+            is_block,
+            ..Default::default()
+        };
+        let flat = |directives: Vec<FlatDirective>| FlatPayload {
+            files: vec![FlatFile::new("nginx.conf"), FlatFile::new("conf.d/a.conf")],
+            directives,
+        };
+
+        let jump = vec![
+            entry(0, "http", true, "nginx.conf"),
+            entry(2, "listen", false, "nginx.conf"),
+        ];
+        assert_eq!(
+            Payload::from_flat(flat(jump)),
+            Err(StructureError::DepthJump {
+                index: 1,
+                depth: 2,
+                max_depth: 1
+            })
+        );
+
+        let not_a_block = vec![
+            entry(0, "http", true, "nginx.conf"),
+            entry(1, "sendfile", false, "nginx.conf"),
+            entry(2, "listen", false, "nginx.conf"),
+        ];
+        assert_eq!(
+            Payload::from_flat(flat(not_a_block)),
+            Err(StructureError::NotABlock { index: 2 })
+        );
+
+        let file_in_block = vec![
+            entry(0, "http", true, "nginx.conf"),
+            entry(1, "server", true, "nginx.conf"),
+            entry(1, "server", true, "conf.d/a.conf"),
+        ];
+        assert_eq!(
+            Payload::from_flat(flat(file_in_block)),
+            Err(StructureError::FileInBlock {
+                index: 2,
+                file: "conf.d/a.conf".to_string()
+            })
+        );
+
+        let reopened = vec![
+            entry(0, "events", true, "nginx.conf"),
+            entry(0, "server", true, "conf.d/a.conf"),
+            entry(0, "http", true, "nginx.conf"),
+        ];
+        assert_eq!(
+            Payload::from_flat(flat(reopened)).unwrap_err().to_string(),
+            "directive 2 continues file nginx.conf after another file"
+        );
+
+        let unknown = vec![entry(0, "events", true, "other.conf")];
+        assert_eq!(
+            Payload::from_flat(flat(unknown)).unwrap_err().to_string(),
+            "directive 0 is in unknown file other.conf"
+        );
+    }
+}
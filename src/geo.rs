@@ -0,0 +1,532 @@
+use std::collections::HashSet;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use crate::lint::{Finding, Severity};
+use crate::map_blocks::{entries, Entry};
+use crate::parse::Payload;
+use crate::tree::DirectiveRef;
+
+/// An address with the number of leading bits that make up the network, as
+/// written in geo blocks like `10.0.0.0/8` or a plain address.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct IpNet {
+    pub addr: IpAddr,
+    pub prefix: u8,
+}
+
+impl IpNet {
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        match (self.addr, ip) {
+            (IpAddr::V4(net), IpAddr::V4(ip)) => {
+                let mask = u32::MAX.checked_shl(32 - self.prefix as u32).unwrap_or(0);
+                u32::from(net) == u32::from(ip) & mask
+            }
+            (IpAddr::V6(net), IpAddr::V6(ip)) => {
+                let mask = u128::MAX.checked_shl(128 - self.prefix as u32).unwrap_or(0);
+                u128::from(net) == u128::from(ip) & mask
+            }
+            _ => false,
+        }
+    }
+
+    // the network of "addr" or "addr/prefix", and whether bits past the
+    // prefix were set, which nginx warns about and clears
+    fn parse(s: &str) -> Option<(IpNet, bool)> {
+        let (addr, prefix) = match s.split_once('/') {
+            Some((addr, prefix)) => (addr.parse().ok()?, Some(prefix)),
+            None => (s.parse().ok()?, None),
+        };
+        let max = if matches!(addr, IpAddr::V4(_)) {
+            32
+        } else {
+            128
+        };
+        let prefix = match prefix {
+            Some(prefix) if prefix.bytes().all(|b| b.is_ascii_digit()) => prefix.parse().ok()?,
+            Some(_) => return None,
+            None => max,
+        };
+        if prefix > max {
+            return None;
+        }
+        let masked = match addr {
+            IpAddr::V4(a) => {
+                let mask = u32::MAX.checked_shl(32 - prefix as u32).unwrap_or(0);
+                IpAddr::V4(Ipv4Addr::from(u32::from(a) & mask))
+            }
+            IpAddr::V6(a) => {
+                let mask = u128::MAX.checked_shl(128 - prefix as u32).unwrap_or(0);
+                IpAddr::V6(Ipv6Addr::from(u128::from(a) & mask))
+            }
+        };
+        Some((
+            IpNet {
+                addr: masked,
+                prefix,
+            },
+            masked != addr,
+        ))
+    }
+}
+
+/// An entry of a geo block. Entries before `ranges` are networks, those
+/// after it ranges.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GeoEntry {
+    Cidr {
+        net: IpNet,
+        value: String,
+    },
+    Range {
+        from: Ipv4Addr,
+        to: Ipv4Addr,
+        value: String,
+    },
+    Default(String),
+    /// A trusted proxy, whose `X-Forwarded-For` gives the address instead.
+    Proxy(IpNet),
+    Delete(IpNet),
+    DeleteRange {
+        from: Ipv4Addr,
+        to: Ipv4Addr,
+    },
+    /// An include that was not followed, its entries are in place of it
+    /// otherwise.
+    Include(String),
+}
+
+/// A `geo` block with its entries, including those of included files.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GeoBlock {
+    pub variable: String,
+    /// The variable holding the address, `$remote_addr` if not given.
+    pub address: Option<String>,
+    pub ranges: bool,
+    pub proxy_recursive: bool,
+    pub entries: Vec<GeoEntry>,
+    pub file: String,
+    pub line: usize,
+}
+
+impl GeoBlock {
+    /// The value the block's variable takes for the address `ip`: that of
+    /// the longest network holding it, or of the range holding it in ranges
+    /// mode, and the default otherwise. Deleted networks and ranges are left
+    /// out, and later values of the same network replace earlier ones. None
+    /// if nothing matches and there is no default, where nginx gives an
+    /// empty value. Proxies are not taken into account.
+    pub fn lookup(&self, ip: IpAddr) -> Option<&str> {
+        // mapped addresses are looked up as IPv4, like nginx does
+        let ip = match ip {
+            IpAddr::V6(v6) => v6.to_ipv4_mapped().map_or(ip, IpAddr::V4),
+            ip => ip,
+        };
+        let found = match self.ranges {
+            true => self.lookup_range(ip),
+            false => self.lookup_cidr(ip),
+        };
+        found.or_else(|| self.default())
+    }
+
+    /// The value of the last `default` entry.
+    pub fn default(&self) -> Option<&str> {
+        self.entries.iter().rev().find_map(|entry| match entry {
+            GeoEntry::Default(value) => Some(value.as_str()),
+            _ => None,
+        })
+    }
+
+    fn lookup_cidr(&self, ip: IpAddr) -> Option<&str> {
+        let mut nets: Vec<(IpNet, &str)> = Vec::new();
+        for entry in &self.entries {
+            match entry {
+                GeoEntry::Cidr { net, value } => {
+                    nets.retain(|(n, _)| n != net);
+                    nets.push((*net, value));
+                }
+                GeoEntry::Delete(net) => nets.retain(|(n, _)| n != net),
+                _ => {}
+            }
+        }
+        nets.iter()
+            .filter(|(net, _)| net.contains(ip))
+            .max_by_key(|(net, _)| net.prefix)
+            .map(|(_, value)| *value)
+    }
+
+    fn lookup_range(&self, ip: IpAddr) -> Option<&str> {
+        let IpAddr::V4(ip) = ip else {
+            return None;
+        };
+        let mut ranges: Vec<(Ipv4Addr, Ipv4Addr, &str)> = Vec::new();
+        for entry in &self.entries {
+            match entry {
+                GeoEntry::Range { from, to, value } => {
+                    ranges.retain(|(f, t, _)| (f, t) != (from, to));
+                    ranges.push((*from, *to, value));
+                }
+                GeoEntry::DeleteRange { from, to } => {
+                    ranges.retain(|(f, t, _)| (f, t) != (from, to))
+                }
+                _ => {}
+            }
+        }
+        // overlapping ranges are an error, the last one wins here
+        ranges
+            .iter()
+            .rev()
+            .find(|(from, to, _)| (*from..=*to).contains(&ip))
+            .map(|(_, _, value)| *value)
+    }
+}
+
+impl Payload {
+    /// All `geo` blocks of the payload, in config order. Malformed entries
+    /// are left out, [`lint`](Payload::lint) reports them.
+    pub fn geo_blocks(&self) -> Vec<GeoBlock> {
+        self.directives()
+            .filter_map(|d| read_geo(self, &d, &mut Vec::new()))
+            .collect()
+    }
+}
+
+// malformed entries of geo blocks and overlapping ranges
+pub(crate) fn check_geo_blocks(payload: &Payload) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    for d in payload.directives() {
+        read_geo(payload, &d, &mut findings);
+    }
+    findings
+}
+
+fn finding(entry: &Entry, severity: Severity, message: String) -> Finding {
+    Finding::new(
+        "geo-entry",
+        severity,
+        message,
+        entry.file,
+        entry.directive.line,
+    )
+}
+
+fn read_geo(payload: &Payload, d: &DirectiveRef, findings: &mut Vec<Finding>) -> Option<GeoBlock> {
+    let directive = d.directive();
+    let block = directive
+        .block
+        .as_ref()
+        .filter(|_| directive.directive == "geo")?;
+    let (address, variable) = match directive.args.as_slice() {
+        [variable] => (None, variable.clone()),
+        [address, variable] => (Some(address.clone()), variable.clone()),
+        _ => return None,
+    };
+    let file = d.file_name();
+    let mut geo = GeoBlock {
+        variable,
+        address,
+        ranges: false,
+        proxy_recursive: false,
+        entries: Vec::new(),
+        file: file.to_string(),
+        line: directive.line,
+    };
+    // the networks and ranges set so far, to warn about deleting others
+    let mut keys: HashSet<&str> = HashSet::new();
+    let mut ranges: Vec<(Ipv4Addr, Ipv4Addr, &Entry)> = Vec::new();
+
+    let block = entries(payload, block, file, &mut Vec::new());
+    for entry in &block {
+        let key = entry.directive.directive.as_str();
+        let args = &entry.directive.args;
+        let arity = match key {
+            "ranges" | "proxy_recursive" => 0,
+            _ => 1,
+        };
+        if args.len() != arity {
+            findings.push(finding(
+                entry,
+                Severity::Error,
+                format!("invalid number of the geo parameters in \"{}\"", key),
+            ));
+            continue;
+        }
+        let invalid = |what: &str, arg: &str| {
+            finding(
+                entry,
+                Severity::Error,
+                format!("invalid {} \"{}\"", what, arg),
+            )
+        };
+        let parsed = match key {
+            "ranges" => {
+                if geo
+                    .entries
+                    .iter()
+                    .any(|e| matches!(e, GeoEntry::Cidr { .. }))
+                {
+                    findings.push(finding(
+                        entry,
+                        Severity::Error,
+                        "the \"ranges\" directive must be the first directive inside \"geo\" block"
+                            .to_string(),
+                    ));
+                }
+                geo.ranges = true;
+                continue;
+            }
+            "proxy_recursive" => {
+                geo.proxy_recursive = true;
+                continue;
+            }
+            "default" => Some(GeoEntry::Default(args[0].clone())),
+            "include" => Some(GeoEntry::Include(args[0].clone())),
+            "proxy" => match IpNet::parse(&args[0]) {
+                Some((net, _)) => Some(GeoEntry::Proxy(net)),
+                None => {
+                    findings.push(invalid("proxy address", &args[0]));
+                    None
+                }
+            },
+            "delete" => {
+                let (deleted, what) = match geo.ranges {
+                    true => (
+                        parse_range(&args[0]).map(|(from, to)| GeoEntry::DeleteRange { from, to }),
+                        "range",
+                    ),
+                    false => (
+                        IpNet::parse(&args[0]).map(|(net, _)| GeoEntry::Delete(net)),
+                        "network",
+                    ),
+                };
+                match deleted {
+                    Some(_) if !keys.remove(args[0].as_str()) => {
+                        findings.push(finding(
+                            entry,
+                            Severity::Warning,
+                            format!("no {} \"{}\" to delete", what, args[0]),
+                        ));
+                    }
+                    None => findings.push(invalid(what, &args[0])),
+                    Some(_) => {}
+                }
+                deleted
+            }
+            _ if geo.ranges => match parse_range(key) {
+                Some((from, to)) => {
+                    // ranges may nest in others, but not cross them
+                    let duplicate = ranges.iter().find(|(f, t, _)| (*f, *t) == (from, to));
+                    let crossed = ranges.iter().find(|(f, t, _)| {
+                        from <= *t
+                            && *f <= to
+                            && !(*f <= from && to <= *t || from <= *f && *t <= to)
+                    });
+                    if let Some((_, _, first)) = duplicate {
+                        findings.push(finding(
+                            entry,
+                            Severity::Warning,
+                            format!(
+                                "duplicate range \"{}\", value: \"{}\", old value: \"{}\"",
+                                key, args[0], first.directive.args[0]
+                            ),
+                        ));
+                    } else if let Some((f, t, first)) = crossed {
+                        findings.push(finding(
+                            entry,
+                            Severity::Error,
+                            format!(
+                                "range \"{}\" overlaps \"{}-{}\" in {}:{}",
+                                key, f, t, first.file, first.directive.line
+                            ),
+                        ));
+                    }
+                    ranges.push((from, to, entry));
+                    keys.insert(key);
+                    Some(GeoEntry::Range {
+                        from,
+                        to,
+                        value: args[0].clone(),
+                    })
+                }
+                None => {
+                    findings.push(invalid("range", key));
+                    None
+                }
+            },
+            _ => match IpNet::parse(key) {
+                Some((net, masked)) => {
+                    if masked {
+                        findings.push(finding(
+                            entry,
+                            Severity::Warning,
+                            format!("low address bits of \"{}\" are meaningless", key),
+                        ));
+                    }
+                    keys.insert(key);
+                    Some(GeoEntry::Cidr {
+                        net,
+                        value: args[0].clone(),
+                    })
+                }
+                None => {
+                    findings.push(invalid("network", key));
+                    None
+                }
+            },
+        };
+        geo.entries.extend(parsed);
+    }
+    Some(geo)
+}
+
+// a range of IPv4 addresses like "10.0.0.0-10.0.0.255"
+fn parse_range(s: &str) -> Option<(Ipv4Addr, Ipv4Addr)> {
+    let (from, to) = s.split_once('-')?;
+    let (from, to): (Ipv4Addr, Ipv4Addr) = (from.parse().ok()?, to.parse().ok()?);
+    (from <= to).then_some((from, to))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::{parse_source, ParseOptions};
+
+    fn ip(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_geo_blocks() {
+        let source = r#"http {
+    geo $country {
+        default        ZZ;
+        proxy          192.168.100.0/24;
+        proxy_recursive;
+        10.0.0.0/8     private;
+        10.1.0.0/16    office;
+        10.1.2.0/24    lab;
+        10.1.2.0/24    lab2;
+        10.3.0.0/16    gone;
+        delete         10.3.0.0/16;
+        2001:db8::/32  docs;
+        include        extra.geo;
+    }
+    geo $arg_ip $zone {
+        ranges;
+        default                    outside;
+        127.0.0.1-127.0.0.1        loopback;
+        10.0.0.0-10.0.255.255      inside;
+        10.0.1.0-10.0.1.255        overlap;
+        10.0.1.0-10.0.1.255        nested;
+        10.0.255.0-10.1.0.255      crossing;
+        192.168.0.0-192.168.0.255  home;
+        delete 192.168.0.0-192.168.0.255;
+    }
+}
+"#;
+        let payload = parse_source(source, &ParseOptions::default());
+        let geo = payload.geo_blocks();
+        assert_eq!(geo.len(), 2);
+
+        let country = &geo[0];
+        assert_eq!(country.variable, "$country");
+        assert_eq!(country.address, None);
+        assert!(!country.ranges && country.proxy_recursive);
+        assert_eq!(country.line, 2);
+        assert_eq!(
+            country.entries[1],
+            GeoEntry::Proxy(IpNet {
+                addr: ip("192.168.100.0"),
+                prefix: 24
+            })
+        );
+        assert_eq!(
+            country.entries.last(),
+            Some(&GeoEntry::Include("extra.geo".to_string()))
+        );
+        assert_eq!(country.lookup(ip("10.9.9.9")), Some("private"));
+        assert_eq!(country.lookup(ip("10.1.9.9")), Some("office"));
+        assert_eq!(country.lookup(ip("10.1.2.3")), Some("lab2"));
+        assert_eq!(country.lookup(ip("::ffff:10.1.2.3")), Some("lab2"));
+        assert_eq!(country.lookup(ip("10.3.0.1")), Some("private"));
+        assert_eq!(country.lookup(ip("2001:db8::1")), Some("docs"));
+        assert_eq!(country.lookup(ip("8.8.8.8")), Some("ZZ"));
+
+        let zone = &geo[1];
+        assert_eq!(zone.address.as_deref(), Some("$arg_ip"));
+        assert!(zone.ranges);
+        assert_eq!(zone.lookup(ip("127.0.0.1")), Some("loopback"));
+        assert_eq!(zone.lookup(ip("10.0.0.9")), Some("inside"));
+        assert_eq!(zone.lookup(ip("10.0.1.9")), Some("nested"));
+        assert_eq!(zone.lookup(ip("192.168.0.1")), Some("outside"));
+        assert_eq!(zone.lookup(ip("::1")), Some("outside"));
+
+        let messages: Vec<_> = check_geo_blocks(&payload)
+            .iter()
+            .map(|f| format!("{}:{} {}", f.severity, f.line, f.message))
+            .collect();
+        assert_eq!(
+            messages,
+            [
+                "warning:21 duplicate range \"10.0.1.0-10.0.1.255\", value: \"nested\", \
+                 old value: \"overlap\"",
+                "error:22 range \"10.0.255.0-10.1.0.255\" overlaps \"10.0.0.0-10.0.255.255\" \
+                 in nginx.conf:19",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_geo_blocks() {
+        let source = r#"http {
+    geo $a {
+        10.0.0.1/8      low;
+        10.0.0.0/33     wide;
+        example.com     name;
+        10.0.0.0/8;
+        delete          10.2.0.0/16;
+        proxy           localhost;
+        ranges;
+        10.0.0.5-10.0.0.1  backwards;
+        10.0.0.0/24        cidr;
+        default;
+    }
+    geo $b {
+        delete 10.0.0.0-10.0.0.255;
+        ranges;
+        delete 10.0.0.0-10.0.0.255;
+    }
+}
+"#;
+        let payload = parse_source(source, &ParseOptions::default());
+        let messages: Vec<_> = check_geo_blocks(&payload)
+            .iter()
+            .map(|f| format!("{}:{} {} [{}]", f.severity, f.line, f.message, f.kind))
+            .collect();
+        assert_eq!(
+            messages,
+            [
+                "warning:3 low address bits of \"10.0.0.1/8\" are meaningless [geo-entry]",
+                "error:4 invalid network \"10.0.0.0/33\" [geo-entry]",
+                "error:5 invalid network \"example.com\" [geo-entry]",
+                "error:6 invalid number of the geo parameters in \"10.0.0.0/8\" [geo-entry]",
+                "warning:7 no network \"10.2.0.0/16\" to delete [geo-entry]",
+                "error:8 invalid proxy address \"localhost\" [geo-entry]",
+                "error:9 the \"ranges\" directive must be the first directive inside \"geo\" \
+                 block [geo-entry]",
+                "error:10 invalid range \"10.0.0.5-10.0.0.1\" [geo-entry]",
+                "error:11 invalid range \"10.0.0.0/24\" [geo-entry]",
+                "error:12 invalid number of the geo parameters in \"default\" [geo-entry]",
+                "error:15 invalid network \"10.0.0.0-10.0.0.255\" [geo-entry]",
+                "warning:17 no range \"10.0.0.0-10.0.0.255\" to delete [geo-entry]",
+            ]
+        );
+        assert_eq!(
+            payload
+                .lint()
+                .iter()
+                .filter(|f| f.kind == "geo-entry")
+                .count(),
+            12
+        );
+    }
+}
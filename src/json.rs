@@ -0,0 +1,810 @@
+use std::io::{self, BufReader, BufWriter, Read, Write};
+
+use serde::de::{Deserialize, Deserializer};
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+use serde::{Deserialize as DeriveDeserialize, Serialize as DeriveSerialize};
+use serde_json::ser::{CompactFormatter, Formatter, PrettyFormatter};
+
+use crate::about::{about, About};
+use crate::error::ParseError;
+use crate::parse::{ConfigFile, Directive, ParseProfile, Payload, Status};
+
+/// How [`Payload::write_json`] lays out its output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JsonStyle {
+    /// Everything on one line, without spaces.
+    Compact,
+    /// One value per line, nested values indented by `indent` spaces.
+    Pretty { indent: usize },
+}
+
+/// How [`Payload::write_json_with`] writes a payload.
+#[derive(Debug, Clone)]
+pub struct JsonOptions {
+    pub(crate) style: JsonStyle,
+    pub(crate) meta: bool,
+    pub(crate) ensure_ascii: bool,
+    pub(crate) fields: DirectiveFields,
+}
+
+impl Default for JsonOptions {
+    fn default() -> Self {
+        JsonOptions {
+            style: JsonStyle::Compact,
+            meta: false,
+            ensure_ascii: false,
+            fields: DirectiveFields::default(),
+        }
+    }
+}
+
+impl JsonOptions {
+    /// The layout of the output, compact by default.
+    pub fn style(mut self, style: JsonStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Add a `_meta` field after the config describing the crate that
+    /// wrote it, as [`about`] does. [`read_json`](Payload::read_json) skips
+    /// the field.
+    pub fn meta(mut self, meta: bool) -> Self {
+        self.meta = meta;
+        self
+    }
+
+    /// Escape every char outside ASCII as `\uXXXX`, as python's `json`
+    /// module does by default, so the output matches crossplane's byte for
+    /// byte.
+    pub fn ensure_ascii(mut self, ensure_ascii: bool) -> Self {
+        self.ensure_ascii = ensure_ascii;
+        self
+    }
+
+    /// Add a `block_end_line` field after the `block` of directives whose
+    /// closing `}` was found, the line of
+    /// [`Directive::block_end_line`]. Crossplane has no such field, and
+    /// [`read_json`](Payload::read_json) reads it back.
+    pub fn block_end_line(mut self, block_end_line: bool) -> Self {
+        self.fields.block_end_line = block_end_line;
+        self
+    }
+
+    /// Add a `raw_source` field last to directives with the text they were
+    /// written as, kept by `ParseOptions::capture_raw_for`. Crossplane has no
+    /// such field, and [`read_json`](Payload::read_json) reads it back.
+    pub fn raw_source(mut self, raw_source: bool) -> Self {
+        self.fields.raw_source = raw_source;
+        self
+    }
+}
+
+// the directive fields past crossplane's that JsonOptions can add
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct DirectiveFields {
+    block_end_line: bool,
+    raw_source: bool,
+}
+
+impl Payload {
+    /// Writes the payload in the JSON shape of crossplane, keys in the order
+    /// crossplane has them, one directive at a time rather than building
+    /// the whole output first. Errors are written as crossplane words them,
+    /// with the file and line, and only the fields crossplane has are kept,
+    /// but for a `_lossy: true` after the config of
+    /// [lossy](Payload::is_lossy) payloads.
+    pub fn write_json<W: Write>(&self, w: W, style: JsonStyle) -> io::Result<()> {
+        self.write_json_with(w, &JsonOptions::default().style(style))
+    }
+
+    /// Like [`write_json`](Payload::write_json), with a `_meta` field after
+    /// the config describing the crate that wrote it, as [`about`] does.
+    /// [`read_json`](Payload::read_json) skips the field.
+    pub fn write_json_with_meta<W: Write>(&self, w: W, style: JsonStyle) -> io::Result<()> {
+        self.write_json_with(w, &JsonOptions::default().style(style).meta(true))
+    }
+
+    /// Like [`write_json`](Payload::write_json), as `options` tell.
+    pub fn write_json_with<W: Write>(&self, w: W, options: &JsonOptions) -> io::Result<()> {
+        let about = options.meta.then(about);
+        let payload = PayloadJson {
+            payload: self,
+            meta: about.as_ref(),
+            fields: options.fields,
+        };
+        let mut out = BufWriter::new(w);
+        let ensure_ascii = options.ensure_ascii;
+        match options.style {
+            JsonStyle::Compact => {
+                let formatter = JsonFormatter {
+                    inner: CompactFormatter,
+                    ensure_ascii,
+                };
+                let mut ser = serde_json::Serializer::with_formatter(&mut out, formatter);
+                payload.serialize(&mut ser)?;
+            }
+            JsonStyle::Pretty { indent } => {
+                let indent = vec![b' '; indent];
+                let formatter = JsonFormatter {
+                    inner: PrettyFormatter::with_indent(&indent),
+                    ensure_ascii,
+                };
+                let mut ser = serde_json::Serializer::with_formatter(&mut out, formatter);
+                payload.serialize(&mut ser)?;
+            }
+        }
+        out.flush()
+    }
+
+    /// Reads a payload written by [`write_json`](Payload::write_json) or by
+    /// crossplane, parsing the directives as they are read rather than the
+    /// whole document first. Error kinds and the text of `<raw>` directives
+    /// are not part of the JSON, and are left to their defaults, as are the
+    /// lines blocks end on and the source directives were written as unless
+    /// written with [`JsonOptions::block_end_line`] and
+    /// [`JsonOptions::raw_source`].
+    pub fn read_json<R: Read>(r: R) -> io::Result<Payload> {
+        let mut de = serde_json::Deserializer::from_reader(BufReader::new(r));
+        let payload = Payload::deserialize(&mut de)?;
+        de.end()?;
+        Ok(payload)
+    }
+}
+
+// the shape write_json writes, also for payloads nested in other documents;
+// python's json escapes non-ASCII chars that serde_json keeps, see
+// JsonOptions::ensure_ascii
+
+impl Serialize for Payload {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        PayloadJson {
+            payload: self,
+            meta: None,
+            fields: DirectiveFields::default(),
+        }
+        .serialize(serializer)
+    }
+}
+
+// a payload with what the options of write_json add to it
+struct PayloadJson<'a> {
+    payload: &'a Payload,
+    meta: Option<&'a About>,
+    fields: DirectiveFields,
+}
+
+impl Serialize for PayloadJson<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let errors: Vec<_> = self
+            .payload
+            .errors
+            .iter()
+            .map(|error| PayloadErrorJson {
+                file: error.file.as_deref(),
+                line: error.line,
+                error: error.to_string(),
+            })
+            .collect();
+        let lossy = self.payload.is_lossy();
+        let fields = 3 + usize::from(lossy) + usize::from(self.meta.is_some());
+        let mut payload = serializer.serialize_struct("Payload", fields)?;
+        payload.serialize_field("status", &self.payload.status.to_string())?;
+        payload.serialize_field("errors", &errors)?;
+        let config: Vec<_> = self
+            .payload
+            .config
+            .iter()
+            .map(|config| ConfigJson {
+                config,
+                fields: self.fields,
+            })
+            .collect();
+        payload.serialize_field("config", &config)?;
+        if lossy {
+            payload.serialize_field("_lossy", &true)?;
+        }
+        if let Some(meta) = self.meta {
+            payload.serialize_field("_meta", meta)?;
+        }
+        payload.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for Payload {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let payload = JsonPayload::deserialize(deserializer)?;
+        Ok(Payload {
+            status: payload.status.into(),
+            errors: payload.errors.into_iter().map(Into::into).collect(),
+            config: payload.config.into_iter().map(|c| c.0).collect(),
+            generation: 0,
+            prefix: None,
+            metrics: None,
+            profile: ParseProfile {
+                marked_lossy: payload.lossy,
+                ..Default::default()
+            },
+        })
+    }
+}
+
+impl Serialize for ConfigFile {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        ConfigJson {
+            config: self,
+            fields: DirectiveFields::default(),
+        }
+        .serialize(serializer)
+    }
+}
+
+struct ConfigJson<'a> {
+    config: &'a ConfigFile,
+    fields: DirectiveFields,
+}
+
+impl Serialize for ConfigJson<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let errors: Vec<_> = self
+            .config
+            .errors
+            .iter()
+            .map(|error| FileErrorJson {
+                error: error.to_string(),
+                line: error.line,
+            })
+            .collect();
+        let mut config = serializer.serialize_struct("ConfigFile", 4)?;
+        config.serialize_field("file", &self.config.file)?;
+        config.serialize_field("status", &self.config.status.to_string())?;
+        config.serialize_field("errors", &errors)?;
+        config.serialize_field("parsed", &BlockJson::new(&self.config.parsed, self.fields))?;
+        config.end()
+    }
+}
+
+impl Serialize for Directive {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        DirectiveJson {
+            stmt: self,
+            fields: DirectiveFields::default(),
+        }
+        .serialize(serializer)
+    }
+}
+
+// the directives of a block, each with the fields asked for
+struct BlockJson<'a> {
+    block: &'a [Directive],
+    fields: DirectiveFields,
+}
+
+impl<'a> BlockJson<'a> {
+    fn new(block: &'a [Directive], fields: DirectiveFields) -> Self {
+        BlockJson { block, fields }
+    }
+}
+
+impl Serialize for BlockJson<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.block.iter().map(|stmt| DirectiveJson {
+            stmt,
+            fields: self.fields,
+        }))
+    }
+}
+
+struct DirectiveJson<'a> {
+    stmt: &'a Directive,
+    fields: DirectiveFields,
+}
+
+impl Serialize for DirectiveJson<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let stmt = self.stmt;
+        let block_end_line = stmt.block_end_line.filter(|_| self.fields.block_end_line);
+        let raw_source = stmt.raw_source.as_ref().filter(|_| self.fields.raw_source);
+        let mut json = serializer.serialize_struct("Directive", 9)?;
+        json.serialize_field("directive", &stmt.directive)?;
+        json.serialize_field("line", &stmt.line)?;
+        json.serialize_field("args", &stmt.args)?;
+        // keys crossplane leaves out rather than writing null
+        match &stmt.includes {
+            Some(includes) => json.serialize_field("includes", includes)?,
+            None => json.skip_field("includes")?,
+        }
+        match &stmt.block {
+            Some(block) => json.serialize_field("block", &BlockJson::new(block, self.fields))?,
+            None => json.skip_field("block")?,
+        }
+        match block_end_line {
+            Some(line) => json.serialize_field("block_end_line", &line)?,
+            None => json.skip_field("block_end_line")?,
+        }
+        match &stmt.comment {
+            Some(comment) => json.serialize_field("comment", comment)?,
+            None => json.skip_field("comment")?,
+        }
+        match &stmt.file {
+            Some(file) => json.serialize_field("file", file)?,
+            None => json.skip_field("file")?,
+        }
+        match raw_source {
+            Some(raw_source) => json.serialize_field("raw_source", raw_source)?,
+            None => json.skip_field("raw_source")?,
+        }
+        json.end()
+    }
+}
+
+#[derive(DeriveSerialize)]
+struct PayloadErrorJson<'a> {
+    file: Option<&'a str>,
+    line: Option<usize>,
+    error: String,
+}
+
+#[derive(DeriveSerialize)]
+struct FileErrorJson {
+    error: String,
+    line: Option<usize>,
+}
+
+// the layout of serde_json, escaping chars outside ASCII when asked to
+struct JsonFormatter<F> {
+    inner: F,
+    ensure_ascii: bool,
+}
+
+impl<F: Formatter> Formatter for JsonFormatter<F> {
+    fn write_string_fragment<W: ?Sized + Write>(
+        &mut self,
+        writer: &mut W,
+        fragment: &str,
+    ) -> io::Result<()> {
+        if !self.ensure_ascii {
+            return writer.write_all(fragment.as_bytes());
+        }
+        let mut rest = fragment;
+        while let Some(at) = rest.find(|c: char| !c.is_ascii()) {
+            writer.write_all(&rest.as_bytes()[..at])?;
+            let c = rest[at..].chars().next().unwrap_or_default();
+            for unit in c.encode_utf16(&mut [0; 2]) {
+                write!(writer, "\\u{:04x}", unit)?;
+            }
+            rest = &rest[at + c.len_utf8()..];
+        }
+        writer.write_all(rest.as_bytes())
+    }
+
+    fn begin_array<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.inner.begin_array(writer)
+    }
+
+    fn end_array<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.inner.end_array(writer)
+    }
+
+    fn begin_array_value<W: ?Sized + Write>(
+        &mut self,
+        writer: &mut W,
+        first: bool,
+    ) -> io::Result<()> {
+        self.inner.begin_array_value(writer, first)
+    }
+
+    fn end_array_value<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.inner.end_array_value(writer)
+    }
+
+    fn begin_object<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.inner.begin_object(writer)
+    }
+
+    fn end_object<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.inner.end_object(writer)
+    }
+
+    fn begin_object_key<W: ?Sized + Write>(
+        &mut self,
+        writer: &mut W,
+        first: bool,
+    ) -> io::Result<()> {
+        self.inner.begin_object_key(writer, first)
+    }
+
+    fn begin_object_value<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.inner.begin_object_value(writer)
+    }
+
+    fn end_object_value<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.inner.end_object_value(writer)
+    }
+}
+
+// the JSON shapes read back, converted as soon as each value is read so the
+// whole document is never held twice
+
+#[derive(DeriveDeserialize)]
+struct JsonPayload {
+    status: JsonStatus,
+    #[serde(default)]
+    errors: Vec<JsonError>,
+    config: Vec<Json<ConfigFile>>,
+    #[serde(default, rename = "_lossy")]
+    lossy: bool,
+}
+
+#[derive(DeriveDeserialize)]
+#[serde(rename_all = "lowercase")]
+enum JsonStatus {
+    Ok,
+    Failed,
+}
+
+impl From<JsonStatus> for Status {
+    fn from(status: JsonStatus) -> Self {
+        match status {
+            JsonStatus::Ok => Status::Ok,
+            JsonStatus::Failed => Status::Failed,
+        }
+    }
+}
+
+#[derive(DeriveDeserialize)]
+struct JsonError {
+    #[serde(default)]
+    file: Option<String>,
+    line: Option<usize>,
+    error: String,
+}
+
+impl From<JsonError> for ParseError {
+    // the file and line crossplane adds to the message are taken off again
+    fn from(error: JsonError) -> Self {
+        let suffix = match (&error.file, error.line) {
+            (Some(file), Some(line)) => format!(" in {}:{}", file, line),
+            (Some(file), None) => format!(" in {}", file),
+            _ => String::new(),
+        };
+        let what = match error.error.strip_suffix(&suffix) {
+            Some(what) if !suffix.is_empty() => what.to_string(),
+            _ => error.error,
+        };
+        ParseError {
+            what,
+            file: error.file,
+            line: error.line,
+            kind: Default::default(),
+            arg_index: None,
+            span: None,
+        }
+    }
+}
+
+#[derive(DeriveDeserialize)]
+struct JsonConfigFile {
+    file: String,
+    status: JsonStatus,
+    #[serde(default)]
+    errors: Vec<JsonError>,
+    parsed: Vec<Json<Directive>>,
+}
+
+#[derive(DeriveDeserialize)]
+struct JsonDirective {
+    directive: String,
+    line: usize,
+    #[serde(default)]
+    args: Vec<String>,
+    #[serde(default)]
+    includes: Option<Vec<usize>>,
+    #[serde(default)]
+    block: Option<Vec<Json<Directive>>>,
+    #[serde(default)]
+    block_end_line: Option<usize>,
+    #[serde(default)]
+    comment: Option<String>,
+    #[serde(default)]
+    file: Option<String>,
+    #[serde(default)]
+    raw_source: Option<String>,
+}
+
+struct Json<T>(T);
+
+impl<'de> Deserialize<'de> for Json<ConfigFile> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let config = JsonConfigFile::deserialize(deserializer)?;
+        let file = config.file;
+        Ok(Json(ConfigFile {
+            status: config.status.into(),
+            errors: config
+                .errors
+                .into_iter()
+                .map(|e| {
+                    ParseError::from(JsonError {
+                        file: Some(file.clone()),
+                        ..e
+                    })
+                })
+                .collect(),
+            parsed: config.parsed.into_iter().map(|d| d.0).collect(),
+            ..ConfigFile::new(file.clone())
+        }))
+    }
+}
+
+impl<'de> Deserialize<'de> for Json<Directive> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let stmt = JsonDirective::deserialize(deserializer)?;
+        let mut block: Option<Vec<Directive>> = stmt
+            .block
+            .map(|block| block.into_iter().map(|d| d.0).collect());
+        if stmt.directive == "if" {
+            block.iter_mut().flatten().for_each(mark_in_if);
+        }
+        Ok(Json(Directive {
+            directive: stmt.directive,
+            line: stmt.line,
+            args: stmt.args,
+            includes: stmt.includes,
+            block,
+            block_end_line: stmt.block_end_line,
+            comment: stmt.comment,
+            file: stmt.file,
+            raw_source: stmt.raw_source,
+            ..Default::default()
+        }))
+    }
+}
+
+fn mark_in_if(stmt: &mut Directive) {
+    stmt.in_if = true;
+    stmt.block.iter_mut().flatten().for_each(mark_in_if);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::{parse, parse_source, ParseOptions};
+    use std::fs;
+
+    fn fixtures() -> Vec<Payload> {
+        let options = ParseOptions::default().comments(true).catch_errors(true);
+        let mut payloads: Vec<Payload> = fs::read_dir("configs")
+            .unwrap()
+            .map(|entry| entry.unwrap().path().join("nginx.conf"))
+            .filter(|path| path.exists())
+            .map(|path| parse(path, &options))
+            .collect();
+        payloads.push(parse_source("http {\n    server {\n", &options));
+        payloads.push(parse(
+            "configs/includes/nginx.conf",
+            &options.clone().combine(true),
+        ));
+        payloads
+    }
+
+    fn written(payload: &Payload, style: JsonStyle) -> String {
+        let mut json = Vec::new();
+        payload.write_json(&mut json, style).unwrap();
+        String::from_utf8(json).unwrap()
+    }
+
+    // what the JSON keeps of a payload
+    fn json_fields(mut payload: Payload) -> Payload {
+        fn clear(block: &mut [Directive]) {
+            for stmt in block {
+                stmt.block_end_line = None;
+                clear(stmt.block.as_deref_mut().unwrap_or_default());
+            }
+        }
+        for error in &mut payload.errors {
+            error.kind = Default::default();
+        }
+        for config in &mut payload.config {
+            config.shebang = None;
+            for error in &mut config.errors {
+                error.kind = Default::default();
+            }
+            clear(&mut config.parsed);
+        }
+        payload
+    }
+
+    #[test]
+    fn test_write_json() {
+        let source = "events {}\ninclude mime.types; # types\nuser \"a\\\"b\";\n";
+        let options = ParseOptions::default().comments(true);
+        let payload = parse_source(source, &options);
+        assert_eq!(
+            written(&payload, JsonStyle::Compact),
+            r##"{"status":"ok","errors":[],"config":[{"file":"nginx.conf","status":"ok","errors":[],"parsed":[{"directive":"events","line":1,"args":[],"block":[]},{"directive":"include","line":2,"args":["mime.types"]},{"directive":"#","line":2,"args":[],"comment":" types"},{"directive":"user","line":3,"args":["a\"b"]}]}]}"##
+        );
+
+        let payload = parse_source("http {\n", &options.catch_errors(true));
+        assert_eq!(
+            written(&payload, JsonStyle::Pretty { indent: 4 }),
+            r#"{
+    "status": "failed",
+    "errors": [
+        {
+            "file": "nginx.conf",
+            "line": 1,
+            "error": "unexpected end of file, expecting '}' in nginx.conf:1"
+        }
+    ],
+    "config": [
+        {
+            "file": "nginx.conf",
+            "status": "failed",
+            "errors": [
+                {
+                    "error": "unexpected end of file, expecting '}' in nginx.conf:1",
+                    "line": 1
+                }
+            ],
+            "parsed": [
+                {
+                    "directive": "http",
+                    "line": 1,
+                    "args": [],
+                    "block": []
+                }
+            ]
+        }
+    ],
+    "_lossy": true
+}"#
+        );
+    }
+
+    #[test]
+    fn test_write_json_matches_serde() {
+        for payload in fixtures() {
+            assert_eq!(
+                written(&payload, JsonStyle::Compact),
+                serde_json::to_string(&payload).unwrap()
+            );
+            assert_eq!(
+                written(&payload, JsonStyle::Pretty { indent: 2 }),
+                serde_json::to_string_pretty(&payload).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_write_json_ensure_ascii() {
+        let payload = parse_source(
+            "add_header X-Note \"caf\u{e9} \u{1f600}\";\n",
+            &Default::default(),
+        );
+        let write = |options: &JsonOptions| {
+            let mut json = Vec::new();
+            payload.write_json_with(&mut json, options).unwrap();
+            String::from_utf8(json).unwrap()
+        };
+        let plain = write(&JsonOptions::default());
+        assert!(plain.contains("\"caf\u{e9} \u{1f600}\""));
+        // as python's json.dumps writes it
+        let ascii = write(&JsonOptions::default().ensure_ascii(true));
+        assert!(ascii.contains(r#""caf\u00e9 \ud83d\ude00""#), "{}", ascii);
+        assert!(ascii.is_ascii());
+        for json in [&plain, &ascii] {
+            assert_eq!(Payload::read_json(json.as_bytes()).unwrap(), payload);
+        }
+    }
+
+    #[test]
+    fn test_write_json_block_end_line() {
+        let source = "http {\n    server {\n    }\n\n}\nevents {\n";
+        let options = ParseOptions::default().catch_errors(true);
+        let payload = parse_source(source, &options);
+        let mut json = Vec::new();
+        let with_lines = JsonOptions::default().block_end_line(true);
+        payload.write_json_with(&mut json, &with_lines).unwrap();
+        let json = String::from_utf8(json).unwrap();
+        assert!(json.contains(
+            r#""parsed":[{"directive":"http","line":1,"args":[],"block":[{"directive":"server","line":2,"args":[],"block":[],"block_end_line":3}],"block_end_line":5},{"directive":"events","line":6,"args":[],"block":[]}]"#
+        ), "{}", json);
+        assert!(!written(&payload, JsonStyle::Compact).contains("block_end_line"));
+
+        let read = Payload::read_json(json.as_bytes()).unwrap();
+        let parsed = &read.config[0].parsed;
+        assert_eq!(parsed[0].block_end_line, Some(5));
+        assert_eq!(parsed[1].block_end_line, None);
+        let mut expected = json_fields(payload.clone());
+        expected.config = payload.config;
+        assert_eq!(read, expected);
+    }
+
+    #[test]
+    fn test_write_json_raw_source() {
+        let source = "server_name a.example  b.example; # hosts\nlisten 80;\n";
+        let options = ParseOptions::default().capture_raw_for(&["server_name"]);
+        let payload = parse_source(source, &options);
+        let mut json = Vec::new();
+        let with_source = JsonOptions::default().raw_source(true);
+        payload.write_json_with(&mut json, &with_source).unwrap();
+        let json = String::from_utf8(json).unwrap();
+        assert!(json.contains(
+            r#""parsed":[{"directive":"server_name","line":1,"args":["a.example","b.example"],"raw_source":"server_name a.example  b.example; # hosts"},{"directive":"listen","line":2,"args":["80"]}]"#
+        ), "{}", json);
+        assert!(!written(&payload, JsonStyle::Compact).contains("raw_source"));
+
+        let read = Payload::read_json(json.as_bytes()).unwrap();
+        assert_eq!(read.config[0].parsed, payload.config[0].parsed);
+    }
+
+    #[test]
+    fn test_write_json_with_meta() {
+        let payload = parse_source("events {}\n", &ParseOptions::default());
+        let plain = written(&payload, JsonStyle::Compact);
+        assert!(!plain.contains("_meta"));
+
+        let mut json = Vec::new();
+        payload
+            .write_json_with_meta(&mut json, JsonStyle::Compact)
+            .unwrap();
+        let json = String::from_utf8(json).unwrap();
+        let meta = serde_json::to_string(&about()).unwrap();
+        assert_eq!(
+            json,
+            format!("{},\"_meta\":{}}}", &plain[..plain.len() - 1], meta)
+        );
+
+        let mut pretty = Vec::new();
+        let style = JsonStyle::Pretty { indent: 2 };
+        payload.write_json_with_meta(&mut pretty, style).unwrap();
+        let value: serde_json::Value = serde_json::from_slice(&pretty).unwrap();
+        assert_eq!(value["_meta"], serde_json::to_value(about()).unwrap());
+        assert_eq!(
+            value["_meta"]["supported_nginx_versions"]["start"],
+            "1.18.0"
+        );
+        for json in [json.as_bytes(), &pretty] {
+            assert_eq!(
+                Payload::read_json(json).unwrap(),
+                json_fields(payload.clone())
+            );
+        }
+    }
+
+    #[test]
+    fn test_serde_crossplane_json() {
+        // as `crossplane parse configs/includes/nginx.conf` writes it
+        let expected = fs::read_to_string("configs/includes/crossplane.json").unwrap();
+        let payload = parse("configs/includes/nginx.conf", &ParseOptions::default());
+        assert_eq!(serde_json::to_string(&payload).unwrap() + "\n", expected);
+
+        for payload in fixtures() {
+            let json = serde_json::to_string(&payload).unwrap();
+            assert_eq!(json, written(&payload, JsonStyle::Compact));
+            let read: Payload = serde_json::from_str(&json).unwrap();
+            assert_eq!(read, json_fields(payload.clone()));
+            let config = serde_json::to_value(&payload.config[0]).unwrap();
+            assert_eq!(
+                config,
+                serde_json::from_str::<serde_json::Value>(&json).unwrap()["config"][0]
+            );
+        }
+    }
+
+    #[test]
+    fn test_read_json() {
+        for payload in fixtures() {
+            for style in [JsonStyle::Compact, JsonStyle::Pretty { indent: 4 }] {
+                let json = written(&payload, style);
+                let read = Payload::read_json(json.as_bytes()).unwrap();
+                assert_eq!(read, json_fields(payload.clone()));
+            }
+        }
+
+        let error = Payload::read_json(&b"{\"status\": \"ok\", \"config\": [{}]}"[..]).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        let error =
+            Payload::read_json(&b"{\"status\": \"ok\", \"config\": []} []"[..]).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+}
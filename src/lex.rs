@@ -1,284 +1,820 @@
-use std::fs;
-use std::io::Read;
+use std::borrow::Cow;
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use std::io::{self, BufRead, BufReader, Read};
+use std::iter::Peekable;
+use std::ops::{Deref, Range};
+use std::rc::Rc;
 
-#[derive(Debug, PartialEq)]
-pub struct NgxToken {
-    value: String,
-    line: usize,
-    is_quoted: bool,
-    error: Option<ParseError>,
+use crate::cancel::CancelToken;
+use crate::error::{LexError, ParseError, ParseErrorKind};
+use crate::escape::{processed_chars, LexChar, ProcessedChars};
+
+// how many characters are read between checks of the cancel token, not
+// counting those skipped in runs of whitespace, comments and quoted strings
+const CANCEL_CHECK_INTERVAL: usize = 4096;
+
+/// A token of a config. Its value is a `String`, or a `Cow` borrowing from
+/// the source for the tokens [`parse_borrowed`](crate::parse_borrowed)
+/// reads.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NgxToken<V = String> {
+    pub value: V,
+    pub line: usize,
+    pub is_quoted: bool,
+    /// Set on the last of the tokens the parser reads when lexing failed,
+    /// never on the tokens [`lex`] returns.
+    pub error: Option<ParseError>,
+    /// Byte range of the token in the source, quotes included.
+    pub span: Range<usize>,
+    /// Whether the token holds a template placeholder, see
+    /// [`LexOptions::template_syntax`].
+    pub is_placeholder: bool,
 }
 
-#[derive(Debug, PartialEq)]
-struct ParseError {
-    what: String,
-    line: usize,
+/// Template languages whose placeholders the lexer can pass through.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TemplateSyntax {
+    #[default]
+    None,
+    /// `{{ expression }}`, `{% statement %}` and `{# comment #}`.
+    Jinja,
+    /// `<%= expression %>`, `<% code %>` and `<%# comment %>`.
+    Erb,
+}
+
+impl TemplateSyntax {
+    // the delimiter closing a placeholder opened by `first` and `second`
+    fn closing(self, first: char, second: char) -> Option<&'static str> {
+        match (self, first, second) {
+            (TemplateSyntax::Jinja, '{', '{') => Some("}}"),
+            (TemplateSyntax::Jinja, '{', '%') => Some("%}"),
+            (TemplateSyntax::Jinja, '{', '#') => Some("#}"),
+            (TemplateSyntax::Erb, '<', '%') => Some("%>"),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct LexOptions {
+    pub(crate) template_syntax: TemplateSyntax,
+    pub(crate) cancel_token: Option<CancelToken>,
+    // leave the braces to be balanced across files by the parser
+    pub(crate) unbalanced: bool,
+}
+
+impl LexOptions {
+    /// Read the placeholders of a template language as opaque parts of
+    /// tokens, so the braces in them do not open or close blocks. Tokens
+    /// holding one are flagged with `is_placeholder`. A placeholder not
+    /// closed on its line is an error.
+    pub fn template_syntax(mut self, template_syntax: TemplateSyntax) -> Self {
+        self.template_syntax = template_syntax;
+        self
+    }
+
+    /// Stop lexing once the token is cancelled, failing with a
+    /// `ParseErrorKind::Cancelled` error.
+    pub fn cancel_token(mut self, cancel_token: CancelToken) -> Self {
+        self.cancel_token = Some(cancel_token);
+        self
+    }
 }
 
 struct CharLine {
-    char: String,
+    ch: char,
+    // whether a backslash comes before the char
+    escaped: bool,
     line: usize,
+    offset: usize,
 }
 
-#[derive(Debug, PartialEq)]
-struct TokenLine {
-    value: &'static str,
-    line: usize,
+impl CharLine {
+    // whether it is `ch`, not escaped
+    fn is(&self, ch: char) -> bool {
+        !self.escaped && self.ch == ch
+    }
+
+    // whitespace splitting words, ASCII only as in ngx_conf_read_token, so
+    // a no-break space stays part of the word it is in like nginx keeps it
+    fn is_whitespace(&self) -> bool {
+        (!self.escaped || self.is_continuation()) && self.ch.is_ascii_whitespace()
+    }
+
+    // a backslash ending a line
+    fn is_continuation(&self) -> bool {
+        self.escaped && self.ch == '\n'
+    }
+
+    // the char read outside quotes and comments, where a backslash ending
+    // a line is dropped and the line continues
+    fn continued(self) -> CharLine {
+        CharLine {
+            escaped: self.escaped && !self.is_continuation(),
+            ..self
+        }
+    }
+
+    // adds the char to a token as it was written, with its backslash
+    fn push_to<T: TokenText>(&self, token: &mut T) {
+        if self.escaped {
+            token.push_char('\\', self.offset);
+            token.push_char(self.ch, self.offset + 1);
+        } else {
+            token.push_char(self.ch, self.offset);
+        }
+    }
+}
+
+// the value of a token as the tokenizer reads it, char by char
+trait TokenText: Clone {
+    type Value: Deref<Target = str> + Default;
+
+    // adds `ch`, read at byte `offset` of the source
+    fn push_char(&mut self, ch: char, offset: usize);
+
+    fn as_str(&self) -> &str;
+
+    fn into_value(self) -> Self::Value;
 }
-struct LexFixture {
-    name: &'static str,
-    tokens: Vec<TokenLine>,
+
+impl TokenText for String {
+    type Value = String;
+
+    fn push_char(&mut self, ch: char, _offset: usize) {
+        self.push(ch);
+    }
+
+    fn as_str(&self) -> &str {
+        self
+    }
+
+    fn into_value(self) -> String {
+        self
+    }
+}
+
+// a value kept as a slice of the source as long as it is written there as
+// it is, and copied once a char differs, like an unescaped quote or a
+// carriage return read as a newline
+#[derive(Clone)]
+struct SourceText<'src> {
+    source: &'src str,
+    range: Range<usize>,
+    owned: Option<String>,
+}
+
+impl<'src> TokenText for SourceText<'src> {
+    type Value = Cow<'src, str>;
+
+    fn push_char(&mut self, ch: char, offset: usize) {
+        if let Some(owned) = &mut self.owned {
+            owned.push(ch);
+            return;
+        }
+        if self.range.is_empty() {
+            self.range = offset..offset;
+        }
+        let end = self.range.end;
+        if offset == end && self.source[end..].starts_with(ch) {
+            self.range.end += ch.len_utf8();
+        } else {
+            let mut owned = self.source[self.range.clone()].to_string();
+            owned.push(ch);
+            self.owned = Some(owned);
+        }
+    }
+
+    fn as_str(&self) -> &str {
+        match &self.owned {
+            Some(owned) => owned,
+            None => &self.source[self.range.clone()],
+        }
+    }
+
+    fn into_value(self) -> Cow<'src, str> {
+        match self.owned {
+            Some(owned) => Cow::Owned(owned),
+            None => Cow::Borrowed(&self.source[self.range]),
+        }
+    }
+}
+
+/// Splits a config into tokens, failing when its braces do not balance.
+pub fn lex<R: Read>(reader: R) -> Result<Vec<NgxToken>, LexError> {
+    lex_with(reader, &LexOptions::default())
 }
 
-pub fn lex<R: Read>(reader: R) -> Vec<NgxToken> {
-    balance_braces(tokenize(reader))
+pub fn lex_with<R: Read>(reader: R, options: &LexOptions) -> Result<Vec<NgxToken>, LexError> {
+    lex_iter_with(reader, options).collect()
 }
 
-fn balance_braces(tokens: Vec<NgxToken>) -> Vec<NgxToken> {
-    let mut balanced_tokens = Vec::new();
-    let mut depth = 0;
-    let mut line = 0;
+/// Like [`lex`], reading the config as tokens are asked for, so that
+/// dropping the iterator early reads no further than the buffered bytes.
+/// Braces are balanced as tokens go: the iterator ends with an error at
+/// the first `}` closing no block, or at the end of a config leaving
+/// blocks open.
+pub fn lex_iter<R: Read>(reader: R) -> impl Iterator<Item = Result<NgxToken, LexError>> {
+    lex_iter_with(reader, &LexOptions::default())
+}
+
+pub fn lex_iter_with<R: Read>(
+    reader: R,
+    options: &LexOptions,
+) -> impl Iterator<Item = Result<NgxToken, LexError>> {
+    let tokenizer = Tokenizer::new(reader, options, String::new());
+    Balanced::new(tokenizer, !options.unbalanced).map(|mut token| match token.error.take() {
+        Some(error) => Err(LexError::from_token_error(error, token.line)),
+        None => Ok(token),
+    })
+}
+
+// the tokens the parser reads, ending with one carrying the error, if any,
+// after the tokens read until then
+pub(crate) fn lex_tokens<R: Read>(reader: R, options: &LexOptions) -> Vec<NgxToken> {
+    let tokenizer = Tokenizer::new(reader, options, String::new());
+    Balanced::new(tokenizer, !options.unbalanced).collect()
+}
+
+// lex_tokens of a source held in memory, with values borrowing from it
+// where they are written as they are
+pub(crate) fn lex_borrowed<'src>(
+    source: &'src str,
+    options: &LexOptions,
+) -> Vec<NgxToken<Cow<'src, str>>> {
+    let empty = SourceText {
+        source,
+        range: 0..0,
+        owned: None,
+    };
+    let tokenizer = Tokenizer::new(source.as_bytes(), options, empty);
+    Balanced::new(tokenizer, !options.unbalanced).collect()
+}
 
-    for token in tokens {
-        line = token.line;
+// ends the tokens with an error token at the first '}' closing no block,
+// or after the last one when blocks are left open, unless the tokenizer
+// ended with an error of its own
+struct Balanced<I> {
+    tokens: I,
+    check: bool,
+    depth: usize,
+    line: usize,
+    end: usize,
+    // line of the '}' closing the last top level block
+    closed_at: Option<usize>,
+    done: bool,
+}
 
-        if token.value == "}" && !token.is_quoted {
-            depth -= 1;
-        } else if token.value == "{" && !token.is_quoted {
-            depth += 1;
+impl<V, I: Iterator<Item = NgxToken<V>>> Balanced<I> {
+    fn new(tokens: I, check: bool) -> Self {
+        Balanced {
+            tokens,
+            check,
+            depth: 0,
+            line: 0,
+            end: 0,
+            closed_at: None,
+            done: false,
         }
+    }
+}
 
-        if depth < 0 {
-            return vec![NgxToken {
-                value: String::new(),
-                line,
+impl<V: Deref<Target = str> + Default, I: Iterator<Item = NgxToken<V>>> Iterator for Balanced<I> {
+    type Item = NgxToken<V>;
+
+    fn next(&mut self) -> Option<NgxToken<V>> {
+        if self.done {
+            return None;
+        }
+        let Some(token) = self.tokens.next() else {
+            self.done = true;
+            return (self.check && self.depth > 0).then(|| NgxToken {
+                value: V::default(),
+                line: self.line,
                 is_quoted: false,
-                error: Some(ParseError {
-                    what: "unexpected '}'".to_string(),
-                    line,
-                }),
-            }];
+                error: Some(ParseError::new(
+                    "unexpected end of file, expecting '}'",
+                    self.line,
+                )),
+                span: self.end..self.end,
+                is_placeholder: false,
+            });
+        };
+        if token.error.is_some() {
+            self.done = true;
+            return Some(token);
+        }
+        self.line = token.line;
+        self.end = token.span.end;
+        if !self.check || token.is_quoted {
+            return Some(token);
+        }
+
+        if &*token.value == "{" {
+            self.depth += 1;
+        } else if &*token.value == "}" {
+            if self.depth == 0 {
+                self.done = true;
+                return Some(NgxToken {
+                    value: V::default(),
+                    line: token.line,
+                    is_quoted: false,
+                    error: Some(ParseError::new(
+                        unexpected_close(self.closed_at),
+                        token.line,
+                    )),
+                    span: token.span,
+                    is_placeholder: false,
+                });
+            }
+            self.depth -= 1;
+            if self.depth == 0 {
+                self.closed_at = Some(token.line);
+            }
+        }
+        Some(token)
+    }
+}
+
+// the error of a '}' closing no block, telling where the blocks were all
+// closed last to help find the extra one
+pub(crate) fn unexpected_close(closed_at: Option<usize>) -> String {
+    match closed_at {
+        Some(line) => format!(
+            "unexpected '}}', all blocks were already closed (last block closed at line {})",
+            line
+        ),
+        None => "unexpected '}'".to_string(),
+    }
+}
+
+// what has been read of a source, shared by the reader of its chars and
+// the tokenizer
+#[derive(Default)]
+struct ReadState {
+    // bytes read so far
+    offset: Cell<usize>,
+    // newlines read so far
+    newlines: Cell<usize>,
+    error: RefCell<Option<ParseError>>,
+}
+
+// the chars of a source, read as they are asked for, ending at the first
+// byte that cannot be read or is not UTF-8 with an `Io` error
+struct ReadChars<R> {
+    reader: BufReader<R>,
+    state: Rc<ReadState>,
+    done: bool,
+}
+
+impl<R: Read> ReadChars<R> {
+    fn next_byte(&mut self) -> io::Result<Option<u8>> {
+        loop {
+            match self.reader.fill_buf() {
+                Ok(buf) => {
+                    let byte = buf.first().copied();
+                    if byte.is_some() {
+                        self.reader.consume(1);
+                    }
+                    return Ok(byte);
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
         }
-        balanced_tokens.push(token);
     }
 
-    if depth > 0 {
-        balanced_tokens.push(NgxToken {
-            value: String::new(),
-            line,
-            is_quoted: false,
-            error: Some(ParseError {
-                what: "unexpected end of file, expecting '}'".to_string(),
-                line,
-            }),
+    fn fail(&mut self, what: String) -> Option<char> {
+        let line = 1 + self.state.newlines.get();
+        *self.state.error.borrow_mut() = Some(ParseError {
+            kind: ParseErrorKind::Io,
+            ..ParseError::new(what, line)
         });
+        self.done = true;
+        None
+    }
+}
+
+impl<R: Read> Iterator for ReadChars<R> {
+    type Item = char;
+
+    fn next(&mut self) -> Option<char> {
+        if self.done {
+            return None;
+        }
+        let first = match self.next_byte() {
+            Ok(Some(byte)) => byte,
+            Ok(None) => return None,
+            Err(e) => return self.fail(format!("could not read the config: {}", e)),
+        };
+        let width = match first {
+            0x00..=0x7f => 1,
+            0xc0..=0xdf => 2,
+            0xe0..=0xef => 3,
+            0xf0..=0xf7 => 4,
+            _ => 0,
+        };
+        let mut bytes = [first, 0, 0, 0];
+        for byte in bytes.iter_mut().take(width).skip(1) {
+            match self.next_byte() {
+                Ok(Some(next)) => *byte = next,
+                Ok(None) => break,
+                Err(e) => return self.fail(format!("could not read the config: {}", e)),
+            }
+        }
+        let offset = self.state.offset.get();
+        let Some(ch) = std::str::from_utf8(&bytes[..width])
+            .ok()
+            .and_then(|s| s.chars().next())
+        else {
+            return self.fail(format!("invalid UTF-8 at byte {}", offset));
+        };
+        self.state.offset.set(offset + width);
+        if ch == '\n' {
+            self.state.newlines.set(self.state.newlines.get() + 1);
+        }
+        Some(ch)
     }
+}
 
-    balanced_tokens
+// the tokens of a source, read as they are asked for, ending with one
+// carrying the error of a source that could not be read or of a cancel
+struct Tokenizer<R: Read, T: TokenText> {
+    chars: Peekable<LineCount<ProcessedChars<ReadChars<R>>>>,
+    state: Rc<ReadState>,
+    template_syntax: TemplateSyntax,
+    cancel_token: Option<CancelToken>,
+    // the word being read, with the line and byte it started at
+    token: T,
+    // an empty value, which tokens start from
+    empty: T,
+    token_line: usize,
+    token_start: usize,
+    placeholder: bool,
+    // characters read, for the checks of the cancel token
+    read: usize,
+    // tokens read but not given out yet
+    pending: VecDeque<NgxToken<T::Value>>,
+    done: bool,
 }
 
-fn tokenize<R: Read>(reader: R) -> Vec<NgxToken> {
-    let mut tokens = Vec::new();
-    let mut token = String::new();
-    let mut token_line = 1;
+impl<R: Read, T: TokenText> Tokenizer<R, T> {
+    fn new(reader: R, options: &LexOptions, empty: T) -> Self {
+        let state = Rc::new(ReadState::default());
+        let chars = ReadChars {
+            reader: BufReader::new(reader),
+            state: state.clone(),
+            done: false,
+        };
+        Tokenizer {
+            chars: line_count(processed_chars(chars)).peekable(),
+            state,
+            template_syntax: options.template_syntax,
+            cancel_token: options.cancel_token.clone(),
+            token: empty.clone(),
+            empty,
+            token_line: 1,
+            token_start: 0,
+            placeholder: false,
+            read: 0,
+            pending: VecDeque::new(),
+            done: false,
+        }
+    }
+
+    // the word read so far, leaving an empty one to read the next
+    fn take_token(&mut self) -> T::Value {
+        std::mem::replace(&mut self.token, self.empty.clone()).into_value()
+    }
+
+    // gives out the word read so far, if any
+    fn flush(&mut self, end: usize) {
+        if !self.token.as_str().is_empty() {
+            let value = self.take_token();
+            self.pending.push_back(NgxToken {
+                value,
+                line: self.token_line,
+                is_quoted: false,
+                error: None,
+                span: self.token_start..end,
+                is_placeholder: std::mem::take(&mut self.placeholder),
+            });
+        }
+    }
+
+    fn finish(&mut self) {
+        let end = self.state.offset.get();
+        self.flush(end);
+        if let Some(error) = self.state.error.borrow_mut().take() {
+            self.pending.push_back(NgxToken {
+                value: T::Value::default(),
+                line: error.line.unwrap_or(1),
+                is_quoted: false,
+                error: Some(error),
+                span: end..end,
+                is_placeholder: false,
+            });
+        }
+        self.done = true;
+    }
 
-    let mut it = line_count(escape_chars(read_chars(reader))).peekable();
+    // reads the next char, and the ones after it that belong with it
+    fn step(&mut self) {
+        let Some(cl) = self.chars.next() else {
+            self.finish();
+            return;
+        };
+        let mut cl = cl.continued();
+        self.read += 1;
+        if self.read.is_multiple_of(CANCEL_CHECK_INTERVAL)
+            && self.cancel_token.as_ref().is_some_and(|t| t.is_cancelled())
+        {
+            self.pending.push_back(NgxToken {
+                value: T::Value::default(),
+                line: cl.line,
+                is_quoted: false,
+                error: Some(ParseError::cancelled(Some(cl.line))),
+                span: cl.offset..cl.offset,
+                is_placeholder: false,
+            });
+            self.done = true;
+            return;
+        }
 
-    while let Some(mut cl) = it.next() {
         // handle whitespace
-        if cl.char.trim().is_empty() {
+        if cl.is_whitespace() {
             // if token complete yield it and reset token buffer
-            if !token.is_empty() {
-                tokens.push(NgxToken {
-                    value: token.clone(),
-                    line: token_line,
-                    is_quoted: false,
-                    error: None,
-                });
-                token.clear();
-            }
+            self.flush(cl.offset);
 
-            while let Some(next_cl) = it.peek() {
-                if !next_cl.char.trim().is_empty() {
+            while let Some(next_cl) = self.chars.peek() {
+                if !next_cl.is_whitespace() {
                     break;
                 }
-                it.next();
+                self.chars.next();
             }
-            continue;
+            return;
         }
 
         // if starting comment
-        if token.is_empty() && cl.char == "#" {
-            let line_at_start = cl.line;
-            token += &cl.char;
+        if self.token.as_str().is_empty() && cl.is('#') {
+            let mut end = None;
+            let mut comment = self.empty.clone();
+            cl.push_to(&mut comment);
 
-            for next_cl in it.by_ref() {
-                if next_cl.char != "\n" {
-                    token += &next_cl.char;
+            for next_cl in self.chars.by_ref() {
+                // a backslash ending the comment is kept, as nginx does,
+                // the next line being a statement of its own
+                if next_cl.is_continuation() {
+                    comment.push_char('\\', next_cl.offset);
+                    end = Some(next_cl.offset + 1);
+                    break;
+                }
+                if !next_cl.is('\n') {
+                    next_cl.push_to(&mut comment);
                 } else {
+                    end = Some(next_cl.offset);
                     break;
                 }
             }
-            tokens.push(NgxToken {
-                value: token.clone(),
-                line: line_at_start,
+            let end = end.unwrap_or_else(|| self.state.offset.get());
+            self.pending.push_back(NgxToken {
+                value: comment.into_value(),
+                line: cl.line,
                 is_quoted: false,
                 error: None,
+                span: cl.offset..end,
+                is_placeholder: false,
             });
-            token.clear();
-            continue;
+            return;
+        }
+
+        if self.token.as_str().is_empty() {
+            self.token_line = cl.line;
+            self.token_start = cl.offset;
         }
 
-        if token.is_empty() {
-            token_line = cl.line;
+        // a template placeholder is part of the token whatever it holds
+        let close = match self.chars.peek() {
+            Some(next) if !cl.escaped && !next.escaped => {
+                self.template_syntax.closing(cl.ch, next.ch)
+            }
+            _ => None,
+        };
+        if let Some(close) = close {
+            cl.push_to(&mut self.token);
+            self.chars.next().unwrap().push_to(&mut self.token);
+            let start = self.token.as_str().len();
+            let mut closed = false;
+            // a placeholder left open ends at the end of its line, not
+            // taking the rest of the file with it
+            while let Some(next_cl) = self.chars.next_if(|next| !next.is('\n')) {
+                next_cl.push_to(&mut self.token);
+                if self.token.as_str()[start..].ends_with(close) {
+                    closed = true;
+                    break;
+                }
+            }
+            if !closed {
+                let end = match self.chars.peek() {
+                    Some(next) => next.offset,
+                    None => self.state.offset.get(),
+                };
+                let what = format!("unterminated template placeholder, expecting \"{}\"", close);
+                self.token = self.empty.clone();
+                self.pending.push_back(NgxToken {
+                    value: T::Value::default(),
+                    line: cl.line,
+                    is_quoted: false,
+                    error: Some(ParseError::new(what, cl.line)),
+                    span: self.token_start..end,
+                    is_placeholder: false,
+                });
+                self.done = true;
+                return;
+            }
+            self.placeholder = true;
+            return;
         }
 
         // handle parameter expansion syntax (ex: "${var[@]}")s
-        if !token.is_empty() && token.ends_with('$') && cl.char == "{" {
-            token += &cl.char;
+        if self.token.as_str().ends_with('$') && cl.is('{') {
+            cl.push_to(&mut self.token);
 
-            for next_cl in it.by_ref() {
-                if !token.ends_with('}') && !next_cl.char.trim().is_empty() {
-                    token.push_str(&next_cl.char);
+            for next_cl in self.chars.by_ref() {
+                if !self.token.as_str().ends_with('}') && !next_cl.is_whitespace() {
+                    next_cl.push_to(&mut self.token);
                 } else {
-                    cl = next_cl;
+                    cl = next_cl.continued();
                     break;
                 }
             }
         }
 
         // if a quote is found, add the whole string to the token buffer
-        if cl.char == "\"" || cl.char == "'" {
+        if cl.is('"') || cl.is('\'') {
             // if a quote is inside a token, treat it like any other char
-            if !token.is_empty() {
-                token += &cl.char;
-                continue;
+            if !self.token.as_str().is_empty() {
+                cl.push_to(&mut self.token);
+                return;
             }
 
-            let quote = &cl.char;
-            for inner_cl in &mut it {
-                if inner_cl.char == *quote {
+            let quote = cl.ch;
+            let mut end = None;
+            for inner_cl in self.chars.by_ref() {
+                if inner_cl.is(quote) {
+                    end = Some(inner_cl.offset + 1);
                     break;
                 }
 
-                if inner_cl.char == "\\".to_owned() + quote {
-                    token += quote;
+                if inner_cl.escaped && inner_cl.ch == quote {
+                    self.token.push_char(quote, inner_cl.offset + 1);
                 } else {
-                    token += &inner_cl.char;
+                    inner_cl.push_to(&mut self.token);
                 }
             }
 
-            tokens.push(NgxToken {
-                value: token.clone(),
-                line: token_line,
+            let end = end.unwrap_or_else(|| self.state.offset.get());
+            let value = self.take_token();
+            self.pending.push_back(NgxToken {
+                value,
+                line: self.token_line,
                 is_quoted: true,
                 error: None,
+                span: self.token_start..end,
+                is_placeholder: false,
             });
-            token.clear();
-            continue;
+            return;
         }
 
-        // handle special characters that are treated like full tokens
-        if cl.char == "{" || cl.char == "}" || cl.char == ";" {
+        // handle special characters that are treated like full tokens,
+        // a '}' inside a word is part of it like in nginx
+        if cl.is('{') || cl.is(';') || (cl.is('}') && self.token.as_str().is_empty()) {
             // if token complete yield it and reset token buffer
-            if !token.is_empty() {
-                tokens.push(NgxToken {
-                    value: token.clone(),
-                    line: token_line,
-                    is_quoted: false,
-                    error: None,
-                });
-                token.clear();
-            }
+            self.flush(cl.offset);
 
             // this character is a full token so yield it now
-            tokens.push(NgxToken {
-                value: cl.char.clone(),
+            let mut value = self.empty.clone();
+            cl.push_to(&mut value);
+            self.pending.push_back(NgxToken {
+                value: value.into_value(),
                 line: cl.line,
                 is_quoted: false,
                 error: None,
+                span: cl.offset..cl.offset + 1,
+                is_placeholder: false,
             });
-            continue;
+            return;
         }
 
         // append char to the token buffer
-        token += &cl.char;
+        cl.push_to(&mut self.token);
     }
+}
 
-    if !token.is_empty() {
-        tokens.push(NgxToken {
-            value: token.clone(),
-            line: token_line,
-            is_quoted: false,
-            error: None,
-        });
-    }
+impl<R: Read, T: TokenText> Iterator for Tokenizer<R, T> {
+    type Item = NgxToken<T::Value>;
 
-    tokens
+    fn next(&mut self) -> Option<NgxToken<T::Value>> {
+        loop {
+            if let Some(token) = self.pending.pop_front() {
+                return Some(token);
+            }
+            if self.done {
+                return None;
+            }
+            self.step();
+        }
+    }
 }
 
-fn read_chars<R: Read>(mut reader: R) -> impl Iterator<Item = String> {
-    let mut buffer = String::new();
-    reader.read_to_string(&mut buffer).unwrap();
-    buffer
-        .chars()
-        .map(|ch| ch.to_string())
-        .collect::<Vec<_>>()
-        .into_iter()
+// the characters of the source with their lines and byte offsets, leaving
+// out line continuations and dropped carriage returns
+fn line_count<I: Iterator<Item = LexChar>>(chars: I) -> LineCount<I> {
+    LineCount {
+        chars,
+        line: 1,
+        offset: 0,
+    }
 }
 
-fn line_count(chars: impl Iterator<Item = String>) -> impl Iterator<Item = CharLine> {
-    let mut line = 1;
-    chars.map(move |ch| {
-        if ch == "\n" {
-            line += 1;
-        }
-        CharLine { char: ch, line }
-    })
+struct LineCount<I> {
+    chars: I,
+    line: usize,
+    offset: usize,
 }
 
-fn escape_chars(chars: impl Iterator<Item = String>) -> impl Iterator<Item = String> {
-    let mut chars = chars.peekable();
-    std::iter::from_fn(move || {
-        while let Some(ch) = chars.next() {
-            if ch == "\\" {
-                match chars.peek() {
-                    Some(next_char) if next_char == "\n" => {
-                        return None;
-                    }
-                    Some(_) => {
-                        return Some(ch + &chars.next().unwrap_or_default());
-                    }
-                    None => {
-                        return Some(ch);
-                    }
-                }
-            } else if ch == "\r" || ch == "\\\r" {
-                continue;
-            } else {
-                return Some(ch);
+impl<I: Iterator<Item = LexChar>> Iterator for LineCount<I> {
+    type Item = CharLine;
+
+    fn next(&mut self) -> Option<CharLine> {
+        loop {
+            let ch = self.chars.next()?;
+            let at = self.offset;
+            self.offset += ch.raw_len;
+            self.line += usize::from(ch.line_delta);
+            if let Some(c) = ch.ch {
+                return Some(CharLine {
+                    ch: c,
+                    escaped: ch.escaped,
+                    line: self.line,
+                    offset: at,
+                });
             }
         }
-        None
-    })
-}
-
-impl PartialEq<TokenLine> for NgxToken {
-    fn eq(&self, other: &TokenLine) -> bool {
-        self.value == other.value && self.line == other.line
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_support::tokens_debug_dump;
+    use std::fs;
     use std::path::Path;
 
+    #[derive(Debug, PartialEq)]
+    struct TokenLine {
+        value: &'static str,
+        line: usize,
+    }
+    struct LexFixture {
+        name: &'static str,
+        tokens: Vec<TokenLine>,
+    }
+
+    impl PartialEq<TokenLine> for NgxToken {
+        fn eq(&self, other: &TokenLine) -> bool {
+            self.value == other.value && self.line == other.line
+        }
+    }
+
+    #[test]
+    fn test_lex_simple() {
+        let content = fs::read_to_string("configs/simple/nginx.conf").unwrap();
+        crate::assert_tokens!(
+            lex(content.as_bytes()).unwrap(),
+            [
+                "events"@1, "{"@1,
+                "worker_connections"@2, "1024"@2, ";"@2,
+                "}"@3,
+                "http"@5, "{"@5,
+                "server"@6, "{"@6,
+                "listen"@7, "127.0.0.1:8080"@7, ";"@7,
+                "server_name"@8, "default_server"@8, ";"@8,
+                "location"@9, "/"@9, "{"@9,
+                "return"@10, "200"@10, "foo bar baz"@10, ";"@10,
+                "}"@11,
+                "}"@12,
+                "}"@13,
+            ]
+        );
+    }
+
     #[test]
     fn test_lex() {
         let fixtures = vec![
             LexFixture {
-                name: "simple",
+                name: "with-comments",
                 tokens: vec![
                     TokenLine {
                         value: "events",
@@ -304,6 +840,10 @@ mod tests {
                         value: "}",
                         line: 3,
                     },
+                    TokenLine {
+                        value: "#comment",
+                        line: 4,
+                    },
                     TokenLine {
                         value: "http",
                         line: 5,
@@ -332,6 +872,10 @@ mod tests {
                         value: ";",
                         line: 7,
                     },
+                    TokenLine {
+                        value: "#listen",
+                        line: 7,
+                    },
                     TokenLine {
                         value: "server_name",
                         line: 8,
@@ -357,23 +901,27 @@ mod tests {
                         line: 9,
                     },
                     TokenLine {
-                        value: "return",
+                        value: "## this is brace",
+                        line: 9,
+                    },
+                    TokenLine {
+                        value: "# location /",
                         line: 10,
                     },
+                    TokenLine {
+                        value: "return",
+                        line: 11,
+                    },
                     TokenLine {
                         value: "200",
-                        line: 10,
+                        line: 11,
                     },
                     TokenLine {
                         value: "foo bar baz",
-                        line: 10,
+                        line: 11,
                     },
                     TokenLine {
                         value: ";",
-                        line: 10,
-                    },
-                    TokenLine {
-                        value: "}",
                         line: 11,
                     },
                     TokenLine {
@@ -384,38 +932,55 @@ mod tests {
                         value: "}",
                         line: 13,
                     },
+                    TokenLine {
+                        value: "}",
+                        line: 14,
+                    },
                 ],
             },
             LexFixture {
-                name: "with-comments",
+                name: "messy",
                 tokens: vec![
                     TokenLine {
-                        value: "events",
+                        value: "user",
                         line: 1,
                     },
                     TokenLine {
-                        value: "{",
+                        value: "nobody",
                         line: 1,
                     },
                     TokenLine {
-                        value: "worker_connections",
-                        line: 2,
+                        value: ";",
+                        line: 1,
                     },
+                    // the backslash ending the comment is kept with it
                     TokenLine {
-                        value: "1024",
+                        value: "# hello\\n\\\\n\\\\\\n worlddd  \\#\\\\#\\\\\\# dfsf\\n \\\\n \\\\\\n \\",
                         line: 2,
                     },
                     TokenLine {
-                        value: ";",
-                        line: 2,
+                        value: "events",
+                        line: 3,
                     },
                     TokenLine {
-                        value: "}",
+                        value: "{",
                         line: 3,
                     },
                     TokenLine {
-                        value: "#comment",
-                        line: 4,
+                        value: "worker_connections",
+                        line: 3,
+                    },
+                    TokenLine {
+                        value: "2048",
+                        line: 3,
+                    },
+                    TokenLine {
+                        value: ";",
+                        line: 3,
+                    },
+                    TokenLine {
+                        value: "}",
+                        line: 3,
                     },
                     TokenLine {
                         value: "http",
@@ -426,19 +991,19 @@ mod tests {
                         line: 5,
                     },
                     TokenLine {
-                        value: "server",
-                        line: 6,
+                        value: "#forteen",
+                        line: 5,
                     },
                     TokenLine {
-                        value: "{",
+                        value: "# this is a comment",
                         line: 6,
                     },
                     TokenLine {
-                        value: "listen",
+                        value: "access_log",
                         line: 7,
                     },
                     TokenLine {
-                        value: "127.0.0.1:8080",
+                        value: "off",
                         line: 7,
                     },
                     TokenLine {
@@ -446,153 +1011,16 @@ mod tests {
                         line: 7,
                     },
                     TokenLine {
-                        value: "#listen",
+                        value: "default_type",
                         line: 7,
                     },
                     TokenLine {
-                        value: "server_name",
-                        line: 8,
-                    },
-                    TokenLine {
-                        value: "default_server",
-                        line: 8,
+                        value: "text/plain",
+                        line: 7,
                     },
                     TokenLine {
                         value: ";",
-                        line: 8,
-                    },
-                    TokenLine {
-                        value: "location",
-                        line: 9,
-                    },
-                    TokenLine {
-                        value: "/",
-                        line: 9,
-                    },
-                    TokenLine {
-                        value: "{",
-                        line: 9,
-                    },
-                    TokenLine {
-                        value: "## this is brace",
-                        line: 9,
-                    },
-                    TokenLine {
-                        value: "# location /",
-                        line: 10,
-                    },
-                    TokenLine {
-                        value: "return",
-                        line: 11,
-                    },
-                    TokenLine {
-                        value: "200",
-                        line: 11,
-                    },
-                    TokenLine {
-                        value: "foo bar baz",
-                        line: 11,
-                    },
-                    TokenLine {
-                        value: ";",
-                        line: 11,
-                    },
-                    TokenLine {
-                        value: "}",
-                        line: 12,
-                    },
-                    TokenLine {
-                        value: "}",
-                        line: 13,
-                    },
-                    TokenLine {
-                        value: "}",
-                        line: 14,
-                    },
-                ],
-            },
-            LexFixture {
-                name: "messy",
-                tokens: vec![
-                    TokenLine {
-                        value: "user",
-                        line: 1,
-                    },
-                    TokenLine {
-                        value: "nobody",
-                        line: 1,
-                    },
-                    TokenLine {
-                        value: ";",
-                        line: 1,
-                    },
-                    TokenLine {
-                        value: "# hello\\n\\\\n\\\\\\n worlddd  \\#\\\\#\\\\\\# dfsf\\n \\\\n \\\\\\n ",
-                        line: 2,
-                    },
-                    TokenLine {
-                        value: "events",
-                        line: 3,
-                    },
-                    TokenLine {
-                        value: "{",
-                        line: 3,
-                    },
-                    TokenLine {
-                        value: "worker_connections",
-                        line: 3,
-                    },
-                    TokenLine {
-                        value: "2048",
-                        line: 3,
-                    },
-                    TokenLine {
-                        value: ";",
-                        line: 3,
-                    },
-                    TokenLine {
-                        value: "}",
-                        line: 3,
-                    },
-                    TokenLine {
-                        value: "http",
-                        line: 5,
-                    },
-                    TokenLine {
-                        value: "{",
-                        line: 5,
-                    },
-                    TokenLine {
-                        value: "#forteen",
-                        line: 5,
-                    },
-                    TokenLine {
-                        value: "# this is a comment",
-                        line: 6,
-                    },
-                    TokenLine {
-                        value: "access_log",
-                        line: 7,
-                    },
-                    TokenLine {
-                        value: "off",
-                        line: 7,
-                    },
-                    TokenLine {
-                        value: ";",
-                        line: 7,
-                    },
-                    TokenLine {
-                        value: "default_type",
-                        line: 7,
-                    },
-                    TokenLine {
-                        value: "text/plain",
-                        line: 7,
-                    },
-                    TokenLine {
-                        value: ";",
-                        line: 7,
+                        line: 7,
                     },
                     TokenLine {
                         value: "error_log",
@@ -1062,25 +1490,757 @@ mod tests {
                     },
                 ]
             },
-        ];
-
-        for fixture in fixtures {
-            let dirname = Path::new("configs").join(fixture.name);
-            let config = dirname.join("nginx.conf");
-
-            let content = fs::read_to_string(&config).expect("Failed to read config");
-            let tokens = lex(content.as_bytes());
-
-            println!("Running test: {}", fixture.name);
-            for (i, token) in tokens.iter().enumerate() {
-                let expected = &fixture.tokens[i];
-                if token.value != expected.value || token.line != expected.line {
-                    panic!(
-                        "expected ({:?}, {:?}) but got ({:?}, {:?})",
-                        expected.value, expected.line, token.value, token.line
-                    );
-                }
-            }
-        }
+            LexFixture {
+                name: "brace-adjacent",
+                tokens: vec![
+                    TokenLine {
+                        value: "http",
+                        line: 1,
+                    },
+                    TokenLine {
+                        value: "{",
+                        line: 1,
+                    },
+                    TokenLine {
+                        value: "map",
+                        line: 2,
+                    },
+                    TokenLine {
+                        value: "$uri",
+                        line: 2,
+                    },
+                    TokenLine {
+                        value: "$dest",
+                        line: 2,
+                    },
+                    TokenLine {
+                        value: "{",
+                        line: 2,
+                    },
+                    TokenLine {
+                        value: "}",
+                        line: 3,
+                    },
+                    TokenLine {
+                        value: "close",
+                        line: 3,
+                    },
+                    TokenLine {
+                        value: ";",
+                        line: 3,
+                    },
+                    TokenLine {
+                        value: "default",
+                        line: 4,
+                    },
+                    TokenLine {
+                        value: "}",
+                        line: 4,
+                    },
+                    TokenLine {
+                        value: ";",
+                        line: 4,
+                    },
+                    TokenLine {
+                        value: "~^/a/",
+                        line: 5,
+                    },
+                    TokenLine {
+                        value: "/a}b",
+                        line: 5,
+                    },
+                    TokenLine {
+                        value: ";",
+                        line: 5,
+                    },
+                    TokenLine {
+                        value: "}",
+                        line: 6,
+                    },
+                    TokenLine {
+                        value: "server",
+                        line: 7,
+                    },
+                    TokenLine {
+                        value: "{",
+                        line: 7,
+                    },
+                    TokenLine {
+                        value: "location",
+                        line: 8,
+                    },
+                    TokenLine {
+                        value: "=",
+                        line: 8,
+                    },
+                    TokenLine {
+                        value: "/}",
+                        line: 8,
+                    },
+                    TokenLine {
+                        value: "{",
+                        line: 8,
+                    },
+                    TokenLine {
+                        value: "return",
+                        line: 9,
+                    },
+                    TokenLine {
+                        value: "200",
+                        line: 9,
+                    },
+                    TokenLine {
+                        value: "}",
+                        line: 9,
+                    },
+                    TokenLine {
+                        value: ";",
+                        line: 9,
+                    },
+                    TokenLine {
+                        value: "}",
+                        line: 10,
+                    },
+                    TokenLine {
+                        value: "location",
+                        line: 11,
+                    },
+                    TokenLine {
+                        value: "/foo",
+                        line: 11,
+                    },
+                    TokenLine {
+                        value: "{",
+                        line: 11,
+                    },
+                    TokenLine {
+                        value: "return",
+                        line: 11,
+                    },
+                    TokenLine {
+                        value: "200",
+                        line: 11,
+                    },
+                    TokenLine {
+                        value: "foo}bar",
+                        line: 11,
+                    },
+                    TokenLine {
+                        value: ";",
+                        line: 11,
+                    },
+                    TokenLine {
+                        value: "}",
+                        line: 11,
+                    },
+                    TokenLine {
+                        value: "location",
+                        line: 12,
+                    },
+                    TokenLine {
+                        value: "/bar",
+                        line: 12,
+                    },
+                    TokenLine {
+                        value: "{",
+                        line: 12,
+                    },
+                    TokenLine {
+                        value: "}",
+                        line: 12,
+                    },
+                    TokenLine {
+                        value: "}",
+                        line: 13,
+                    },
+                    TokenLine {
+                        value: "}",
+                        line: 14,
+                    },
+                ],
+            },
+            LexFixture {
+                name: "args-only",
+                tokens: vec![
+                    TokenLine {
+                        value: "http",
+                        line: 1,
+                    },
+                    TokenLine {
+                        value: "{",
+                        line: 1,
+                    },
+                    TokenLine {
+                        value: "types",
+                        line: 2,
+                    },
+                    TokenLine {
+                        value: "{",
+                        line: 2,
+                    },
+                    TokenLine {
+                        value: "text/html",
+                        line: 3,
+                    },
+                    TokenLine {
+                        value: "html",
+                        line: 3,
+                    },
+                    TokenLine {
+                        value: "htm",
+                        line: 3,
+                    },
+                    TokenLine {
+                        value: "shtml",
+                        line: 4,
+                    },
+                    TokenLine {
+                        value: ";",
+                        line: 4,
+                    },
+                    TokenLine {
+                        value: "application/x-semi;colon",
+                        line: 5,
+                    },
+                    TokenLine {
+                        value: "semi",
+                        line: 5,
+                    },
+                    TokenLine {
+                        value: ";",
+                        line: 5,
+                    },
+                    TokenLine {
+                        value: "}",
+                        line: 6,
+                    },
+                    TokenLine {
+                        value: "map",
+                        line: 7,
+                    },
+                    TokenLine {
+                        value: "$uri",
+                        line: 7,
+                    },
+                    TokenLine {
+                        value: "$backend",
+                        line: 7,
+                    },
+                    TokenLine {
+                        value: "{",
+                        line: 7,
+                    },
+                    TokenLine {
+                        value: "default",
+                        line: 8,
+                    },
+                    TokenLine {
+                        value: "/fallback",
+                        line: 8,
+                    },
+                    TokenLine {
+                        value: ";",
+                        line: 8,
+                    },
+                    TokenLine {
+                        value: "~^/api/(?<v>\\d+)$",
+                        line: 9,
+                    },
+                    TokenLine {
+                        value: "/backend/$v",
+                        line: 9,
+                    },
+                    TokenLine {
+                        value: ";",
+                        line: 9,
+                    },
+                    TokenLine {
+                        value: "~^/a{1,3}$",
+                        line: 10,
+                    },
+                    TokenLine {
+                        value: "/braces/{}",
+                        line: 10,
+                    },
+                    TokenLine {
+                        value: ";",
+                        line: 10,
+                    },
+                    TokenLine {
+                        value: "~^/b/(?<id>[0-9]+)#x",
+                        line: 11,
+                    },
+                    TokenLine {
+                        value: "/b/$id",
+                        line: 11,
+                    },
+                    TokenLine {
+                        value: ";",
+                        line: 11,
+                    },
+                    TokenLine {
+                        value: "/literal;brace",
+                        line: 12,
+                    },
+                    TokenLine {
+                        value: "v;{}",
+                        line: 12,
+                    },
+                    TokenLine {
+                        value: ";",
+                        line: 12,
+                    },
+                    TokenLine {
+                        value: "}",
+                        line: 13,
+                    },
+                    TokenLine {
+                        value: "}",
+                        line: 14,
+                    },
+                ],
+            },
+            LexFixture {
+                name: "args-only-unquoted-brace",
+                tokens: vec![
+                    TokenLine {
+                        value: "http",
+                        line: 1,
+                    },
+                    TokenLine {
+                        value: "{",
+                        line: 1,
+                    },
+                    TokenLine {
+                        value: "map",
+                        line: 2,
+                    },
+                    TokenLine {
+                        value: "$uri",
+                        line: 2,
+                    },
+                    TokenLine {
+                        value: "$redirect",
+                        line: 2,
+                    },
+                    TokenLine {
+                        value: "{",
+                        line: 2,
+                    },
+                    TokenLine {
+                        value: "~^/a",
+                        line: 3,
+                    },
+                    TokenLine {
+                        value: "{",
+                        line: 3,
+                    },
+                    TokenLine {
+                        value: "1,3}$",
+                        line: 3,
+                    },
+                    TokenLine {
+                        value: "/a",
+                        line: 3,
+                    },
+                    TokenLine {
+                        value: ";",
+                        line: 3,
+                    },
+                    TokenLine {
+                        value: "default",
+                        line: 4,
+                    },
+                    TokenLine {
+                        value: "0",
+                        line: 4,
+                    },
+                    TokenLine {
+                        value: ";",
+                        line: 4,
+                    },
+                    TokenLine {
+                        value: "}",
+                        line: 5,
+                    },
+                    TokenLine {
+                        value: "server",
+                        line: 6,
+                    },
+                    TokenLine {
+                        value: "{",
+                        line: 6,
+                    },
+                    TokenLine {
+                        value: "listen",
+                        line: 7,
+                    },
+                    TokenLine {
+                        value: "80",
+                        line: 7,
+                    },
+                    TokenLine {
+                        value: ";",
+                        line: 7,
+                    },
+                    TokenLine {
+                        value: "}",
+                        line: 8,
+                    },
+                    TokenLine {
+                        value: "}",
+                        line: 9,
+                    },
+                    TokenLine {
+                        value: "",
+                        line: 9,
+                    },
+                ],
+            },
+        ];
+
+        for fixture in fixtures {
+            let dirname = Path::new("configs").join(fixture.name);
+            let config = dirname.join("nginx.conf");
+
+            let content = fs::read_to_string(&config).expect("Failed to read config");
+            let tokens = lex_tokens(content.as_bytes(), &LexOptions::default());
+
+            // both streams are dumped when they differ, the expected one
+            // without kinds of its own
+            let matches = tokens.len() == fixture.tokens.len()
+                && tokens.iter().zip(&fixture.tokens).all(|(t, e)| t == e);
+            if !matches {
+                let expected: Vec<NgxToken> = fixture
+                    .tokens
+                    .iter()
+                    .map(|t| NgxToken {
+                        value: t.value.to_string(),
+                        line: t.line,
+                        is_quoted: false,
+                        error: None,
+                        span: 0..0,
+                        is_placeholder: false,
+                    })
+                    .collect();
+                panic!(
+                    "tokens of {} differ\nexpected:\n{}lexed:\n{}",
+                    fixture.name,
+                    tokens_debug_dump(&expected),
+                    tokens_debug_dump(&tokens)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_lex_errors() {
+        let error = lex("events {\n}\n}\nhttp {\n}\n".as_bytes()).unwrap_err();
+        assert_eq!(error.line(), 3);
+        assert_eq!(
+            error.message(),
+            "unexpected '}', all blocks were already closed (last block closed at line 2)"
+        );
+        assert_eq!(error.kind(), ParseErrorKind::Syntax);
+
+        let error = lex("http {\n    server {\n    }\n".as_bytes()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "unexpected end of file, expecting '}' at line 3"
+        );
+        let error: Box<dyn std::error::Error> = Box::new(error);
+        assert!(error.to_string().starts_with("unexpected end of file"));
+
+        assert_eq!(lex("".as_bytes()), Ok(vec![]));
+    }
+
+    // a reader failing with the given error once before giving its bytes
+    struct FailingReader<'a> {
+        error: Option<std::io::ErrorKind>,
+        bytes: &'a [u8],
+    }
+
+    impl Read for FailingReader<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            if let Some(kind) = self.error.take() {
+                return Err(kind.into());
+            }
+            self.bytes.read(buf)
+        }
+    }
+
+    #[test]
+    fn test_lex_unreadable() {
+        let error = lex(&[0x73, 0xFF, 0x65][..]).unwrap_err();
+        assert_eq!(error.kind(), ParseErrorKind::Io);
+        assert_eq!(error.to_string(), "invalid UTF-8 at byte 1 at line 1");
+        let error = lex(&b"events {\n}\nuser \xC3\xA9\xFF;"[..]).unwrap_err();
+        assert_eq!(error.line(), 3);
+
+        assert_eq!(lex(std::io::empty()), Ok(vec![]));
+
+        // interrupted reads are retried
+        let reader = FailingReader {
+            error: Some(std::io::ErrorKind::Interrupted),
+            bytes: b"events {}",
+        };
+        assert_eq!(lex(reader).unwrap().len(), 3);
+        let reader = FailingReader {
+            error: Some(std::io::ErrorKind::ConnectionReset),
+            bytes: b"events {}",
+        };
+        let error = lex(reader).unwrap_err();
+        assert_eq!(error.kind(), ParseErrorKind::Io);
+        assert!(error.message().starts_with("could not read the config"));
+
+        // the parser reports the file as failed
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nginx.conf");
+        fs::write(&path, b"events {}\nuser \xFF;\n").unwrap();
+        let payload = crate::parse::parse(&path, &Default::default());
+        assert_eq!(payload.errors.len(), 1);
+        assert_eq!(payload.errors[0].kind, ParseErrorKind::Io);
+        assert_eq!(payload.errors[0].line, Some(2));
+    }
+
+    // a reader counting the reads made of it
+    struct CountingReader<'a> {
+        reads: &'a Cell<usize>,
+        bytes: &'a [u8],
+    }
+
+    impl Read for CountingReader<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            self.reads.set(self.reads.get() + 1);
+            self.bytes.read(buf)
+        }
+    }
+
+    #[test]
+    fn test_lex_iter() {
+        let source = "http {\n".to_string() + &"    gzip on;\n".repeat(10_000) + "}\n";
+        let reads = Cell::new(0);
+        let reader = CountingReader {
+            reads: &reads,
+            bytes: source.as_bytes(),
+        };
+        let first: Vec<String> = lex_iter(reader).take(3).map(|t| t.unwrap().value).collect();
+        assert_eq!(first, ["http", "{", "gzip"]);
+        assert_eq!(reads.get(), 1);
+
+        let all: Result<Vec<_>, _> = lex_iter(source.as_bytes()).collect();
+        assert_eq!(all, lex(source.as_bytes()));
+
+        // the tokens before a stray brace are given out before the error
+        let tokens: Vec<_> = lex_iter("events {\n}\n}\nhttp {\n}\n".as_bytes()).collect();
+        assert_eq!(tokens.len(), 4);
+        assert_eq!(tokens[2].as_ref().unwrap().value, "}");
+        assert_eq!(tokens[3].as_ref().unwrap_err().line(), 3);
+
+        let tokens: Vec<_> = lex_iter("http {\n    gzip on;\n".as_bytes()).collect();
+        assert_eq!(tokens.len(), 6);
+        assert_eq!(
+            tokens[5].as_ref().unwrap_err().message(),
+            "unexpected end of file, expecting '}'"
+        );
+    }
+
+    #[test]
+    fn test_lex_line_endings() {
+        let tokens = |name: &str| -> Vec<(String, usize)> {
+            let content = fs::read(Path::new("configs").join(name).join("nginx.conf")).unwrap();
+            lex(content.as_slice())
+                .unwrap()
+                .into_iter()
+                .map(|t| (t.value, t.line))
+                .collect()
+        };
+        let lf = tokens("with-comments");
+        assert_eq!(tokens("lone-cr"), lf);
+        assert_eq!(tokens("mixed-line-endings"), lf);
+
+        // quoted values get the newline of the line ending
+        let values = |source: &str| -> Vec<(String, usize)> {
+            lex(source.as_bytes())
+                .unwrap()
+                .into_iter()
+                .map(|t| (t.value, t.line))
+                .collect()
+        };
+        let lf = values("location / {\n    return 200 \"a\nb\";\n}");
+        assert_eq!(lf[5], ("a\nb".to_string(), 2));
+        assert_eq!(lf[7], ("}".to_string(), 4));
+        assert_eq!(values("location / {\r    return 200 \"a\rb\";\r}"), lf);
+        assert_eq!(
+            values("location / {\r\n    return 200 \"a\r\nb\";\r\n}"),
+            lf
+        );
+    }
+
+    #[test]
+    fn test_lex_line_continuation() {
+        let values = |source: &str| -> Vec<(String, usize)> {
+            lex(source.as_bytes())
+                .unwrap()
+                .into_iter()
+                .map(|t| (t.value, t.line))
+                .collect()
+        };
+        // outside quotes the backslash is dropped and the newline splits
+        // the words
+        assert_eq!(
+            values("set $a b\\\nc;"),
+            [
+                ("set".to_string(), 1),
+                ("$a".to_string(), 1),
+                ("b".to_string(), 1),
+                ("c".to_string(), 2),
+                (";".to_string(), 2),
+            ]
+        );
+        // inside quotes it is kept, whatever the line ending
+        let quoted = values("set $a \"b\\\nc\";");
+        assert_eq!(quoted[2], ("b\\\nc".to_string(), 1));
+        assert_eq!(values("set $a \"b\\\r\nc\";"), quoted);
+        assert_eq!(values("set $a 'b\\\nc';")[2], quoted[2]);
+
+        // and the value builds back as it was read
+        let payload = crate::parse::parse_source("set $a \"b\\\nc\";\n", &Default::default());
+        let built = crate::build::build(&payload.config[0].parsed, &Default::default());
+        assert_eq!(values(&built)[2].0, "b\\\nc");
+    }
+
+    #[test]
+    fn test_lex_unicode_spaces() {
+        // only ASCII whitespace splits words, as nginx reads them
+        let tokens = lex("server_name a\u{00a0}b\u{3000}c\td;".as_bytes()).unwrap();
+        let values: Vec<&str> = tokens.iter().map(|t| t.value.as_str()).collect();
+        assert_eq!(values, ["server_name", "a\u{00a0}b\u{3000}c", "d", ";"]);
+    }
+
+    #[test]
+    fn test_lex_spans() {
+        let source = "# intro\r\nlog_format main 'a \\' b' \"$é\";\nmap $a $b { ~^x{2} 1; } }";
+        let tokens = lex(source.as_bytes()).unwrap();
+        let spans: Vec<(&str, &str)> = tokens
+            .iter()
+            .map(|t| (t.value.as_str(), &source[t.span.clone()]))
+            .collect();
+        assert_eq!(
+            spans,
+            [
+                ("# intro", "# intro\r"),
+                ("log_format", "log_format"),
+                ("main", "main"),
+                ("a ' b", "'a \\' b'"),
+                ("$é", "\"$é\""),
+                (";", ";"),
+                ("map", "map"),
+                ("$a", "$a"),
+                ("$b", "$b"),
+                ("{", "{"),
+                ("~^x", "~^x"),
+                ("{", "{"),
+                ("2}", "2}"),
+                ("1", "1"),
+                (";", ";"),
+                ("}", "}"),
+                ("}", "}"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_lex_cancelled() {
+        let source = "http {\n".to_string() + &"    gzip on;\n".repeat(1000) + "}\n";
+        let token = CancelToken::new();
+        let options = LexOptions::default().cancel_token(token.clone());
+        assert_eq!(
+            lex_with(source.as_bytes(), &options),
+            lex(source.as_bytes())
+        );
+
+        // stopped at the first check, without the brace balance error
+        token.cancel();
+        let error = lex_with(source.as_bytes(), &options).unwrap_err();
+        assert_eq!(error.kind(), ParseErrorKind::Cancelled);
+        let tokens = lex_tokens(source.as_bytes(), &options);
+        assert_eq!(tokens.iter().filter(|t| t.error.is_some()).count(), 1);
+        assert!(tokens.len() < lex(source.as_bytes()).unwrap().len());
+    }
+
+    #[test]
+    fn test_lex_template_syntax() {
+        let tokens = |source: &str, syntax| -> Vec<(String, bool)> {
+            lex_with(
+                source.as_bytes(),
+                &LexOptions::default().template_syntax(syntax),
+            )
+            .unwrap()
+            .into_iter()
+            .map(|t| (t.value, t.is_placeholder))
+            .collect()
+        };
+        let source = "{% for u in ups %}\nproxy_pass http://{{ u.host }}:{{u.port}}/;\n{# note #}\n{% endfor %}\n";
+        assert_eq!(
+            tokens(source, TemplateSyntax::Jinja),
+            [
+                ("{% for u in ups %}".to_string(), true),
+                ("proxy_pass".to_string(), false),
+                ("http://{{ u.host }}:{{u.port}}/".to_string(), true),
+                (";".to_string(), false),
+                ("{# note #}".to_string(), true),
+                ("{% endfor %}".to_string(), true),
+            ]
+        );
+        // braces still open blocks and ${var} is still a variable
+        assert_eq!(
+            tokens(
+                "server { listen {{ port }}; set $a ${b}; }",
+                TemplateSyntax::Jinja
+            ),
+            [
+                ("server".to_string(), false),
+                ("{".to_string(), false),
+                ("listen".to_string(), false),
+                ("{{ port }}".to_string(), true),
+                (";".to_string(), false),
+                ("set".to_string(), false),
+                ("$a".to_string(), false),
+                ("${b}".to_string(), false),
+                (";".to_string(), false),
+                ("}".to_string(), false),
+            ]
+        );
+        assert_eq!(
+            tokens("listen <%= @port %> ssl;", TemplateSyntax::Erb),
+            [
+                ("listen".to_string(), false),
+                ("<%= @port %>".to_string(), true),
+                ("ssl".to_string(), false),
+                (";".to_string(), false),
+            ]
+        );
+
+        // a placeholder left open is an error at the end of its line
+        for source in ["listen {{ port;\nserver_name a;\n", "listen {{ port"] {
+            let error = lex_with(
+                source.as_bytes(),
+                &LexOptions::default().template_syntax(TemplateSyntax::Jinja),
+            )
+            .unwrap_err();
+            assert_eq!(
+                error.to_string(),
+                "unterminated template placeholder, expecting \"}}\" at line 1"
+            );
+        }
+
+        // without template syntax the braces of placeholders open blocks
+        let plain: Vec<String> = lex("listen {{ port }};".as_bytes())
+            .unwrap()
+            .into_iter()
+            .map(|t| t.value)
+            .collect();
+        assert_eq!(plain, ["listen", "{", "{", "port", "}", "}", ";"]);
     }
 }
@@ -0,0 +1,104 @@
+pub mod about;
+pub mod analyze;
+pub mod ascii;
+pub mod borrowed;
+pub mod build;
+pub mod cancel;
+pub mod conflicts;
+pub mod context;
+pub mod crossplane;
+pub mod directive_index;
+pub mod edit;
+pub mod error;
+pub mod escape;
+pub mod findings;
+pub mod fingerprint;
+pub mod flat;
+pub mod geo;
+pub mod json;
+pub mod lex;
+pub mod limits;
+pub mod lint;
+pub mod loader;
+pub mod log_format;
+pub mod map_blocks;
+pub mod metrics;
+pub mod occurrences;
+pub mod outline;
+pub mod parse;
+pub mod paths;
+#[cfg(feature = "regex")]
+pub mod regex_cache;
+pub mod rename;
+pub mod rewrite;
+pub mod sarif;
+pub mod simple_json;
+pub mod skeleton;
+pub mod span;
+#[cfg(any(test, feature = "test_support"))]
+pub mod test_support;
+pub mod token_diff;
+pub mod tree;
+pub mod variables;
+pub mod version;
+pub mod version_diff;
+
+pub use about::{about, About};
+pub use analyze::{
+    analyze, arg_type, directive_specs, find_directive_spec, known_directives, suggest_directive,
+    validate_args, Analyzer, ArgType, ArgValueError, CoverageReport, Deprecation, DirectiveSpec,
+    TableGaps, DIRECTIVE_TABLE_REVISION,
+};
+pub use ascii::{contains_invisible_unicode, contains_non_ascii, InvisibleChar, NonAsciiChar};
+pub use borrowed::{parse_borrowed, BorrowedConfigFile, BorrowedDirective, BorrowedPayload};
+pub use build::{
+    build, build_files, build_payload, build_with_line_report, validate_buildable,
+    validate_buildable_with, BuildIssue, BuildIssueKind, BuildOptions, DuplicatePolicy, HeaderMode,
+    MisplacedDirective,
+};
+pub use cancel::CancelToken;
+pub use context::{Context, ContextId};
+pub use crossplane::{Crossplane, CrossplaneBuilder, FormatError};
+pub use directive_index::{
+    export_directive_index, DirectiveIndex, DirectiveIndexEntry, IndexFormat,
+    DIRECTIVE_INDEX_SCHEMA_VERSION,
+};
+pub use edit::{edit_source, DirectiveEdit, EditError};
+pub use error::{LexError, ParseError, ParseErrorKind};
+pub use escape::{arg_unescaped, ProcessedChar};
+pub use findings::{Findings, TextStyle, GITHUB_ANNOTATION_LIMIT};
+pub use fingerprint::{FingerprintOptions, FINGERPRINT_VERSION};
+pub use flat::{FlatDirective, FlatFile, FlatPayload, StructureError};
+pub use geo::{GeoBlock, GeoEntry, IpNet};
+pub use json::{JsonOptions, JsonStyle};
+pub use lex::{lex, lex_iter, lex_iter_with, lex_with, LexOptions, NgxToken, TemplateSyntax};
+pub use lint::{Finding, LintOptions, Severity, Suggestion, TreeEdit};
+pub use loader::{ConfigLoader, FsLoader};
+pub use log_format::{LogFormat, LogSegment};
+pub use metrics::{FileMetrics, ParseMetrics};
+pub use occurrences::{OccurrenceIndex, StaleIndex};
+pub use outline::Outline;
+pub use parse::{
+    parse, parse_reader, parse_tokens, parse_with_loader, ConfigFile, Directive, ParseCtx,
+    ParseOptions, ParseProfile, Payload, Status, UnknownBlockPolicy, RAW_DIRECTIVE,
+};
+pub use paths::{
+    path_check, path_prefix, resolve_config_path, PathCheck, PathPrefix, ReferencedPath,
+};
+#[cfg(feature = "regex")]
+pub use regex_cache::{RegexCache, RegexError};
+pub use rename::{RenameReport, UpstreamRef};
+pub use rewrite::{
+    RewriteAction, RewriteConflict, RewriteMatch, RewriteReport, RewriteRule, Selector,
+};
+pub use sarif::findings_to_sarif;
+pub use simple_json::{SimpleJsonError, SimpleJsonErrorKind};
+pub use skeleton::{generate_skeleton, LocationSpec, ServerSpec, SkeletonSpec};
+pub use span::{LspPosition, LspRange, PositionEncoding, Span};
+pub use token_diff::{diff_tokens, render_token_diff, TokenChange, TokenDiffOptions};
+pub use tree::{DirectiveLocation, DirectiveRef, Directives};
+pub use variables::{VariableSite, VariableXref};
+pub use version::{NginxVersion, VersionError, VersionRange};
+pub use version_diff::{
+    analyze_across_versions, VersionDiffReport, VersionDivergence, VersionStatus,
+};
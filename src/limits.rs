@@ -0,0 +1,203 @@
+use crate::findings::Findings;
+use crate::lint::{Finding, LintOptions, Severity};
+use crate::parse::Payload;
+
+// bytes of a pointer, by which nginx aligns the elements of hash buckets
+const POINTER_SIZE: usize = 8;
+
+// advisory findings for directives that parse fine but run into the size
+// limits of nginx at startup or at run time
+pub(crate) fn check_limits(payload: &Payload, options: &LintOptions) -> Findings {
+    let mut findings = Findings::new();
+    let bucket_size = options.server_names_hash_bucket_size.map(|default| {
+        configured_size(payload, "server_names_hash_bucket_size").unwrap_or(default)
+    });
+    let max_map_entries = options
+        .max_map_entries
+        .map(|default| configured_size(payload, "map_hash_max_size").unwrap_or(default));
+
+    for d in payload.directives() {
+        let directive = d.directive();
+        let file = d.file_name();
+        if directive.is_raw() || directive.is_comment() {
+            continue;
+        }
+
+        if let Some(max_args) = options.max_args.filter(|&max| directive.args.len() > max) {
+            findings.push(Finding::new(
+                "too-many-args",
+                Severity::Warning,
+                format!(
+                    "\"{}\" has {} args, more than {}",
+                    directive.directive,
+                    directive.args.len(),
+                    max_args
+                ),
+                file,
+                directive.line,
+            ));
+        }
+
+        if let (Some(bucket_size), "server_name") = (bucket_size, directive.directive.as_str()) {
+            // one long name is enough to stop nginx, so report the first
+            let too_long = directive
+                .args
+                .iter()
+                .find(|name| hash_bucket_size(name).is_some_and(|size| size > bucket_size));
+            if let Some(name) = too_long {
+                let needed = hash_bucket_size(name).unwrap_or_default();
+                findings.push(Finding::new(
+                    "long-server-name",
+                    Severity::Warning,
+                    format!(
+                        "server name \"{}\" does not fit in server names hash buckets of {} \
+                         bytes, \"server_names_hash_bucket_size\" needs to be at least {}",
+                        name,
+                        bucket_size,
+                        needed.next_power_of_two()
+                    ),
+                    file,
+                    directive.line,
+                ));
+            }
+        }
+
+        if let (Some(max_entries), "map") = (max_map_entries, directive.directive.as_str()) {
+            let entries = directive
+                .block
+                .iter()
+                .flatten()
+                .filter(|entry| !entry.is_comment())
+                .count();
+            if entries > max_entries {
+                findings.push(Finding::new(
+                    "large-map",
+                    Severity::Info,
+                    format!(
+                        "map has {} entries, more than the {} of \"map_hash_max_size\"; \
+                         consider raising it",
+                        entries, max_entries
+                    ),
+                    file,
+                    directive.line,
+                ));
+            }
+        }
+    }
+    findings
+}
+
+// the bucket size a server name needs in the server names hash: its element
+// with the key and its length, and the null pointer ending the bucket; None
+// for regular expressions, which are not hashed
+fn hash_bucket_size(name: &str) -> Option<usize> {
+    if name.starts_with('~') {
+        return None;
+    }
+    // wildcards are hashed without the "*." or ".*" part
+    let key = name
+        .strip_prefix("*.")
+        .or_else(|| name.strip_prefix('.'))
+        .or_else(|| name.strip_suffix(".*"))
+        .unwrap_or(name);
+    let element = POINTER_SIZE + (key.len() + 2).next_multiple_of(POINTER_SIZE);
+    Some(element + POINTER_SIZE)
+}
+
+// the value of an http level size directive set in the config
+fn configured_size(payload: &Payload, name: &str) -> Option<usize> {
+    let directive = payload
+        .directives()
+        .find(|d| d.directive().directive == name)?;
+    let size = directive
+        .arg_as_size(0)
+        .or_else(|_| directive.arg_as_int(0));
+    usize::try_from(size.ok()?).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::{parse_source, ParseOptions};
+
+    fn findings(source: &str, options: &LintOptions) -> Vec<String> {
+        let payload = parse_source(source, &ParseOptions::default());
+        check_limits(&payload, options)
+            .iter()
+            .map(|f| f.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_max_args() {
+        let options = LintOptions::default().max_args(Some(3));
+        assert_eq!(findings("server_name a b c;", &options), [] as [&str; 0]);
+        assert_eq!(
+            findings("server_name a b c d;", &options),
+            ["warning: \"server_name\" has 4 args, more than 3 in nginx.conf:1 [too-many-args]"]
+        );
+        let off = LintOptions::default().max_args(None);
+        assert_eq!(findings("server_name a b c d;", &off), [] as [&str; 0]);
+    }
+
+    #[test]
+    fn test_long_server_names() {
+        let options = LintOptions::default().max_args(None);
+        // 46 bytes is the longest name buckets of 64 bytes hold
+        let longest = format!("{}.com", "a".repeat(42));
+        let too_long = format!("{}.com", "a".repeat(43));
+        assert_eq!(hash_bucket_size(&longest), Some(64));
+        assert_eq!(hash_bucket_size(&too_long), Some(72));
+        assert_eq!(hash_bucket_size(&format!("*.{}", longest)), Some(64));
+        assert_eq!(hash_bucket_size(&format!("~^{}$", too_long)), None);
+
+        let source = |names: &str| format!("http {{\n    server {{ server_name {}; }}\n}}", names);
+        assert_eq!(findings(&source(&longest), &options), [] as [&str; 0]);
+        assert_eq!(
+            findings(&source(&format!("example.com {}", too_long)), &options),
+            [format!(
+                "warning: server name \"{}\" does not fit in server names hash buckets of 64 \
+                 bytes, \"server_names_hash_bucket_size\" needs to be at least 128 in \
+                 nginx.conf:2 [long-server-name]",
+                too_long
+            )]
+        );
+
+        // a bucket size set in the config wins over the default
+        let configured = format!(
+            "http {{\n    server_names_hash_bucket_size 128;\n    server {{ server_name {}; }}\n}}",
+            too_long
+        );
+        assert_eq!(findings(&configured, &options), [] as [&str; 0]);
+        let off = options.server_names_hash_bucket_size(None);
+        assert_eq!(findings(&source(&too_long), &off), [] as [&str; 0]);
+    }
+
+    #[test]
+    fn test_large_maps() {
+        let options = LintOptions::default().max_map_entries(Some(3));
+        let map = |entries: usize| {
+            let entries: String = (0..entries)
+                .map(|i| format!("    k{} v{};\n", i, i))
+                .collect();
+            format!("map $a $b {{\n{}}}\n", entries)
+        };
+        assert_eq!(findings(&map(3), &options), [] as [&str; 0]);
+        assert_eq!(
+            findings(&map(4), &options),
+            [
+                "info: map has 4 entries, more than the 3 of \"map_hash_max_size\"; consider \
+                 raising it in nginx.conf:1 [large-map]"
+            ]
+        );
+        let raised = format!("map_hash_max_size 4;\n{}", map(4));
+        assert_eq!(findings(&raised, &options), [] as [&str; 0]);
+        assert_eq!(
+            findings(&map(2049), &LintOptions::default()).len(),
+            1,
+            "the default follows map_hash_max_size"
+        );
+        let off = options.max_map_entries(None);
+        assert_eq!(findings(&map(4), &off), [] as [&str; 0]);
+    }
+}
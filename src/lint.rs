@@ -0,0 +1,1444 @@
+use std::collections::HashMap;
+use std::fmt;
+#[cfg(feature = "regex")]
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::analyze::{directive_specs, invalid_flag_value, ListenSpec, NGX_CONF_FLAG};
+use crate::ascii::{check_invisible_unicode, check_non_ascii};
+use crate::conflicts::check_conflicts;
+use crate::findings::Findings;
+use crate::geo::check_geo_blocks;
+use crate::limits::check_limits;
+use crate::map_blocks::check_map_blocks;
+use crate::parse::{ConfigFile, Directive, Payload};
+use crate::paths::check_paths;
+#[cfg(feature = "regex")]
+use crate::regex_cache::RegexCache;
+use crate::span::{PositionEncoding, Span};
+use crate::tree::{DirectiveLocation, DirectiveRef};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Info => write!(f, "info"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// A problem found in a parsed config that nginx itself may not reject.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Finding {
+    /// Short identifier of the check, like `unknown-variable`.
+    pub kind: String,
+    pub severity: Severity,
+    pub message: String,
+    pub file: String,
+    pub line: usize,
+    /// 1-based column, when the position within the line is known.
+    pub column: Option<usize>,
+    /// Index of the arg the finding is about, when it is about one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub arg_index: Option<usize>,
+    /// Where that arg was written, when the payload was parsed with
+    /// [`ParseOptions::arg_spans`](crate::ParseOptions::arg_spans).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub span: Option<Span>,
+    /// A change to the payload that fixes the problem, when there is an
+    /// obvious one. Not serialized, as it only applies to the payload the
+    /// finding came from.
+    #[serde(skip)]
+    pub suggestion: Option<Suggestion>,
+}
+
+/// Edits fixing a finding, applied with
+/// [`Payload::apply_suggestion`](crate::parse::Payload::apply_suggestion).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suggestion {
+    /// What the edits do, like `replace "yes" with "on"`.
+    pub summary: String,
+    pub edits: Vec<TreeEdit>,
+}
+
+/// A change to a single directive of a payload.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TreeEdit {
+    /// Insert a directive before the one at the location, or at the end of
+    /// the block when the location is one past its last directive.
+    Insert {
+        at: DirectiveLocation,
+        directive: Directive,
+    },
+    Remove(DirectiveLocation),
+    Replace {
+        at: DirectiveLocation,
+        directive: Directive,
+    },
+}
+
+impl TreeEdit {
+    pub fn location(&self) -> &DirectiveLocation {
+        match self {
+            TreeEdit::Insert { at, .. } | TreeEdit::Replace { at, .. } => at,
+            TreeEdit::Remove(at) => at,
+        }
+    }
+}
+
+impl Finding {
+    pub fn new(
+        kind: impl Into<String>,
+        severity: Severity,
+        message: impl Into<String>,
+        file: impl Into<String>,
+        line: usize,
+    ) -> Self {
+        Finding {
+            kind: kind.into(),
+            severity,
+            message: message.into(),
+            file: file.into(),
+            line,
+            column: None,
+            arg_index: None,
+            span: None,
+            suggestion: None,
+        }
+    }
+
+    /// Points the finding at the arg at `index` of `directive`.
+    pub fn at_arg(mut self, directive: &Directive, index: usize) -> Self {
+        self.arg_index = Some(index);
+        self.span = directive.arg_spans.get(index).cloned();
+        self
+    }
+
+    pub fn suggestion(mut self, suggestion: Suggestion) -> Self {
+        self.suggestion = Some(suggestion);
+        self
+    }
+}
+
+impl Finding {
+    /// The finding followed by the line it is about, as compilers show
+    /// errors, underlining the arg the finding is about when its span is
+    /// known and the rest of the line otherwise. `source` is the text of the
+    /// finding's file.
+    pub fn render_snippet(&self, source: &str) -> String {
+        let mut out = format!("{}: {} [{}]\n", self.severity, self.message, self.kind);
+        let Some(text) = self.line.checked_sub(1).and_then(|i| source.lines().nth(i)) else {
+            out += &format!("  --> {}:{}\n", self.file, self.line);
+            return out;
+        };
+        let (start, end) = match &self.span {
+            Some(span) => (span.start.min(text.len()), span.end.min(text.len())),
+            None => (text.len() - text.trim_start().len(), text.trim_end().len()),
+        };
+        let column = PositionEncoding::CodePoints.column(text, start) + 1;
+        let gutter = " ".repeat(self.line.to_string().len());
+        // tabs are kept so the carets line up however they are shown
+        let pad: String = text[..start]
+            .chars()
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        let carets = "^".repeat(text[start..end.max(start)].chars().count().max(1));
+        out += &format!("{}--> {}:{}:{}\n", gutter, self.file, self.line, column);
+        out += &format!("{} |\n", gutter);
+        out += &format!("{} | {}\n", self.line, text);
+        out += &format!("{} | {}{}\n", gutter, pad, carets);
+        out
+    }
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} in {}:{} [{}]",
+            self.severity, self.message, self.file, self.line, self.kind
+        )
+    }
+}
+
+/// Settings of the checks run by [`Payload::lint_with`].
+#[derive(Debug, Clone)]
+pub struct LintOptions {
+    pub(crate) max_args: Option<usize>,
+    pub(crate) server_names_hash_bucket_size: Option<usize>,
+    pub(crate) max_map_entries: Option<usize>,
+    pub(crate) invisible_unicode: bool,
+    pub(crate) ascii_only: bool,
+    pub(crate) ascii_exempt_comments: bool,
+    pub(crate) include_severity: Severity,
+    pub(crate) trailing_punctuation: bool,
+    pub(crate) check_paths: bool,
+    pub(crate) escaped_dollars: bool,
+    #[cfg(feature = "regex")]
+    pub(crate) regex_cache: Arc<RegexCache>,
+}
+
+impl Default for LintOptions {
+    fn default() -> Self {
+        LintOptions {
+            max_args: Some(1000),
+            server_names_hash_bucket_size: Some(64),
+            max_map_entries: Some(2048),
+            invisible_unicode: false,
+            ascii_only: false,
+            ascii_exempt_comments: false,
+            include_severity: Severity::Warning,
+            trailing_punctuation: false,
+            check_paths: false,
+            escaped_dollars: false,
+            #[cfg(feature = "regex")]
+            regex_cache: Arc::default(),
+        }
+    }
+}
+
+impl LintOptions {
+    /// Warn about directives with more args than this, like giant
+    /// `server_name` lists. 1000 by default, None turns the check off.
+    pub fn max_args(mut self, max_args: Option<usize>) -> Self {
+        self.max_args = max_args;
+        self
+    }
+
+    /// Warn about server names too long for hash buckets of this size,
+    /// unless the config sets `server_names_hash_bucket_size` itself. 64 by
+    /// default, the bucket size on most machines; None turns the check off.
+    pub fn server_names_hash_bucket_size(mut self, bucket_size: Option<usize>) -> Self {
+        self.server_names_hash_bucket_size = bucket_size;
+        self
+    }
+
+    /// Point out map blocks with more entries than this, unless the config
+    /// sets `map_hash_max_size` itself. 2048 by default, the default of
+    /// `map_hash_max_size`; None turns the check off.
+    pub fn max_map_entries(mut self, max_map_entries: Option<usize>) -> Self {
+        self.max_map_entries = max_map_entries;
+        self
+    }
+
+    /// Warn about invisible characters, like zero width or no-break spaces,
+    /// in directive names and args, see
+    /// [`contains_invisible_unicode`](crate::contains_invisible_unicode).
+    pub fn invisible_unicode(mut self, invisible_unicode: bool) -> Self {
+        self.invisible_unicode = invisible_unicode;
+        self
+    }
+
+    /// Report directive names, args and comments holding anything but
+    /// printable ASCII as errors, like smart quotes pasted from a document,
+    /// as [`BuildOptions::ascii_only`](crate::BuildOptions::ascii_only)
+    /// refuses to build them, see
+    /// [`contains_non_ascii`](crate::ascii::contains_non_ascii).
+    pub fn ascii_only(mut self, ascii_only: bool) -> Self {
+        self.ascii_only = ascii_only;
+        self
+    }
+
+    /// Leave comments out of [`ascii_only`](LintOptions::ascii_only).
+    pub fn ascii_exempt_comments(mut self, exempt: bool) -> Self {
+        self.ascii_exempt_comments = exempt;
+        self
+    }
+
+    /// The severity of the findings about includes that pull in nothing
+    /// useful: patterns matching no files or only empty ones, and files
+    /// with parse errors. Warning by default, as nginx accepts the first
+    /// two; Error makes them fail a deploy.
+    pub fn include_severity(mut self, severity: Severity) -> Self {
+        self.include_severity = severity;
+        self
+    }
+
+    /// Warn about args of names, addresses and paths ending in `;`, `{` or
+    /// `}`: usually a terminator pasted into quotes, like
+    /// `server_name "example.com;"`, or a brace written against the arg,
+    /// like `root /srv/www};`.
+    pub fn trailing_punctuation(mut self, trailing_punctuation: bool) -> Self {
+        self.trailing_punctuation = trailing_punctuation;
+        self
+    }
+
+    /// Report the certificates, keys and other files named in the config
+    /// that nginx would not find, and the log directories it could not
+    /// create logs in, see [`Payload::referenced_paths`]. Missing roots are
+    /// only noted, as nginx does not look for them until serving. Off by
+    /// default, as the paths are those of the machine linting.
+    pub fn check_paths(mut self, check_paths: bool) -> Self {
+        self.check_paths = check_paths;
+        self
+    }
+
+    /// Warn about args holding `\$`, which nginx reads as a backslash
+    /// followed by a variable rather than a literal `$`. Regexes, where it
+    /// matches a `$`, are left alone.
+    pub fn escaped_dollars(mut self, escaped_dollars: bool) -> Self {
+        self.escaped_dollars = escaped_dollars;
+        self
+    }
+
+    /// The cache the regexes of the config are compiled through, see
+    /// [`Payload::check_regexes`].
+    #[cfg(feature = "regex")]
+    pub fn regex_cache(mut self, regex_cache: Arc<RegexCache>) -> Self {
+        self.regex_cache = regex_cache;
+        self
+    }
+}
+
+impl Payload {
+    /// Runs the built-in checks over the whole payload.
+    pub fn lint(&self) -> Findings {
+        self.lint_with(&LintOptions::default())
+    }
+
+    /// Runs the built-in checks with the given settings.
+    pub fn lint_with(&self, options: &LintOptions) -> Findings {
+        let mut findings = self.check_log_formats();
+        findings.extend(check_limits(self, options));
+        if options.invisible_unicode {
+            findings.extend(check_invisible_unicode(self));
+        }
+        if options.ascii_only {
+            findings.extend(check_non_ascii(self, options.ascii_exempt_comments));
+        }
+        if options.trailing_punctuation {
+            findings.extend(check_trailing_punctuation(self));
+        }
+        if options.check_paths {
+            findings.extend(check_paths(self));
+        }
+        if options.escaped_dollars {
+            findings.extend(check_escaped_dollars(self));
+        }
+        findings.extend(check_includes(self, options.include_severity));
+        findings.extend(check_map_blocks(self));
+        findings.extend(check_geo_blocks(self));
+        findings.extend(check_conflicts(self));
+        findings.extend(check_if_hazards(self));
+        findings.extend(check_raw_directives(self));
+        findings.extend(check_main_blocks(self));
+        findings.extend(check_default_servers(self));
+        findings.extend(check_deprecated_ssl(self));
+        findings.extend(check_flag_values(self));
+        #[cfg(feature = "regex")]
+        findings.merge(self.check_regexes(&options.regex_cache));
+        findings
+    }
+}
+
+// statements kept as raw text by ParseOptions::raw_fallback
+fn check_raw_directives(payload: &Payload) -> Vec<Finding> {
+    payload
+        .directives()
+        .filter(|d| d.directive().is_raw() && !d.directive().placeholder)
+        .map(|d| {
+            Finding::new(
+                "raw-directive",
+                Severity::Warning,
+                "statement could not be parsed and was kept as raw text",
+                d.file_name(),
+                d.directive().line,
+            )
+        })
+        .collect()
+}
+
+// include directives whose pattern matches no files or only empty ones, or
+// that pull in files with parse errors
+fn check_includes(payload: &Payload, severity: Severity) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    for d in payload.directives() {
+        let directive = d.directive();
+        let (Some(files), Some(arg)) = (&directive.includes, directive.args.first()) else {
+            continue;
+        };
+        let files: Vec<&ConfigFile> = files
+            .iter()
+            .filter_map(|&i| payload.config.get(i))
+            .collect();
+        let is_pattern = arg.contains(['*', '?', '[']);
+        let finding = |kind: &str, message: String| {
+            Finding::new(kind, severity, message, d.file_name(), directive.line)
+        };
+
+        if is_pattern && files.is_empty() {
+            findings.push(finding(
+                "include-no-match",
+                format!("\"include {}\" matches no files", arg),
+            ));
+        } else if is_pattern
+            && files
+                .iter()
+                .all(|f| f.parsed.is_empty() && f.errors.is_empty())
+        {
+            findings.push(finding(
+                "include-only-empty",
+                format!("\"include {}\" matches only empty files", arg),
+            ));
+        }
+
+        // the first error of each failed file, which has them all
+        let errors: Vec<String> = files
+            .iter()
+            .filter_map(|f| {
+                let first = f.errors.first()?;
+                Some(match f.errors.len() {
+                    1 => first.to_string(),
+                    n => format!("{} (and {} more)", first, n - 1),
+                })
+            })
+            .collect();
+        if !errors.is_empty() {
+            findings.push(finding(
+                "include-errors",
+                format!(
+                    "\"include {}\" pulls in files with errors: {}",
+                    arg,
+                    errors.join("; ")
+                ),
+            ));
+        }
+    }
+    findings
+}
+
+const MAIN_BLOCKS: [&str; 4] = ["events", "http", "mail", "stream"];
+
+// blocks nginx allows only once, and only in the main context, which
+// includes can break across files
+fn check_main_blocks(payload: &Payload) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let mut first: HashMap<&str, (&str, usize)> = HashMap::new();
+    for d in payload.directives() {
+        let directive = d.directive();
+        let name = directive.directive.as_str();
+        if !MAIN_BLOCKS.contains(&name) {
+            continue;
+        }
+        let file = d.file_name();
+        let context = d.context();
+        if !context.path.is_empty() {
+            findings.push(Finding::new(
+                "misplaced-main-block",
+                Severity::Error,
+                format!(
+                    "\"{}\" is only allowed in the main context, not in \"{}\"",
+                    name,
+                    context.path.join(" ")
+                ),
+                file,
+                directive.line,
+            ));
+            continue;
+        }
+        match first.get(name) {
+            Some((first_file, first_line)) => findings.push(Finding::new(
+                "duplicate-main-block",
+                Severity::Error,
+                format!(
+                    "\"{}\" is duplicate, first declared in {}:{}",
+                    name, first_file, first_line
+                ),
+                file,
+                directive.line,
+            )),
+            None => {
+                first.insert(name, (file, directive.line));
+            }
+        }
+    }
+    findings
+}
+
+// listen sockets claimed as the default server by more than one server
+// block, which nginx refuses to start with
+fn check_default_servers(payload: &Payload) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let mut defaults = HashMap::new();
+    for d in payload.directives() {
+        let directive = d.directive();
+        if directive.directive != "listen" {
+            continue;
+        }
+        let Ok(listen) = ListenSpec::parse(&directive.args) else {
+            continue;
+        };
+        if !listen.default_server {
+            continue;
+        }
+        let socket = listen.socket();
+        let first = (d.file_name(), directive.line);
+        // http and stream servers listen on sockets of their own
+        let module = outermost_block(payload, &d);
+        match defaults.get(&(module, socket.clone())) {
+            Some((file, line)) => {
+                let args = directive
+                    .args
+                    .iter()
+                    .filter(|a| *a != "default_server" && *a != "default")
+                    .cloned()
+                    .collect();
+                let fix = Suggestion {
+                    summary: format!("remove \"default_server\" from \"listen {}\"", socket),
+                    edits: vec![TreeEdit::Replace {
+                        at: d.location(),
+                        directive: Directive {
+                            args,
+                            // the new args were not written anywhere
+                            arg_spans: Vec::new(),
+                            ..directive.clone()
+                        },
+                    }],
+                };
+                let default = directive
+                    .args
+                    .iter()
+                    .position(|a| a == "default_server" || a == "default")
+                    .unwrap_or_default();
+                findings.push(
+                    Finding::new(
+                        "duplicate-default-server",
+                        Severity::Error,
+                        format!(
+                            "a duplicate default server for {}, first declared in {}:{}",
+                            socket, file, line
+                        ),
+                        d.file_name(),
+                        directive.line,
+                    )
+                    .at_arg(directive, default)
+                    .suggestion(fix),
+                )
+            }
+            None => {
+                defaults.insert((module, socket), first);
+            }
+        }
+    }
+    findings
+}
+
+// "ssl on" in servers, deprecated since 1.15.0 and gone in 1.25.1 in favour
+// of the ssl parameter of listen
+fn check_deprecated_ssl(payload: &Payload) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    for d in payload.directives() {
+        let directive = d.directive();
+        if directive.directive != "ssl" || directive.args.len() != 1 {
+            continue;
+        }
+        let Some(server) = d.parent().filter(|p| p.directive().directive == "server") else {
+            continue;
+        };
+        let mut fix = Suggestion {
+            summary: "remove \"ssl\"".to_string(),
+            edits: vec![TreeEdit::Remove(d.location())],
+        };
+        if directive.args[0] == "on" {
+            fix.summary = "move \"ssl\" to the listen directives of the server".to_string();
+            for (i, listen) in server.directive().block.iter().flatten().enumerate() {
+                if listen.directive != "listen" || listen.args.iter().any(|a| a == "ssl") {
+                    continue;
+                }
+                let mut at = server.location();
+                at.path.push(i);
+                let mut listen = listen.clone();
+                listen.args.push("ssl".to_string());
+                listen.arg_spans.clear();
+                fix.edits.push(TreeEdit::Replace {
+                    at,
+                    directive: listen,
+                });
+            }
+        }
+        findings.push(
+            Finding::new(
+                "deprecated-ssl",
+                Severity::Warning,
+                "the \"ssl\" directive is deprecated, use the \"listen ... ssl\" directive \
+                 instead",
+                d.file_name(),
+                directive.line,
+            )
+            .suggestion(fix),
+        );
+    }
+    findings
+}
+
+// directives whose args are names, addresses or paths, where a trailing
+// ';', '{' or '}' makes no sense, and the block they must be in if any
+const PUNCTUATION_FREE_ARGS: [(&str, Option<&str>); 5] = [
+    ("alias", None),
+    ("proxy_pass", None),
+    ("root", None),
+    ("server", Some("upstream")),
+    ("server_name", None),
+];
+
+// args ending in a ';' or '{', which only get into an arg quoted, or in a
+// '}', quoted or not, other than one closing a "${var}" or a quantifier of a
+// regex server name
+fn check_trailing_punctuation(payload: &Payload) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    for d in payload.directives() {
+        let directive = d.directive();
+        let Some((_, block)) = PUNCTUATION_FREE_ARGS
+            .iter()
+            .find(|(name, _)| *name == directive.directive)
+        else {
+            continue;
+        };
+        if block.is_some_and(|block| {
+            d.parent()
+                .is_none_or(|parent| parent.directive().directive != block)
+        }) {
+            continue;
+        }
+        for (i, arg) in directive.args.iter().enumerate() {
+            let Some(last) = arg.chars().last().filter(|c| matches!(c, ';' | '{' | '}')) else {
+                continue;
+            };
+            let closes_variable = arg
+                .rfind("${")
+                .is_some_and(|open| arg[open..].find('}') == Some(arg.len() - open - 1));
+            let closes_quantifier = directive.directive == "server_name"
+                && arg.starts_with('~')
+                && ends_with_quantifier(arg);
+            if last == '}' && (closes_variable || closes_quantifier) {
+                continue;
+            }
+            findings.push(
+                Finding::new(
+                    "trailing-punctuation",
+                    Severity::Warning,
+                    format!(
+                        "\"{}\" in \"{}\" directive ends with '{}', which likely belongs \
+                         after the arg",
+                        arg, directive.directive, last
+                    ),
+                    d.file_name(),
+                    directive.line,
+                )
+                .at_arg(directive, i),
+            );
+        }
+    }
+    findings
+}
+
+// whether a regex ends in a counted repetition, like "{2}", "{2,}" or
+// "{2,5}"
+fn ends_with_quantifier(pattern: &str) -> bool {
+    let Some((_, counts)) = pattern
+        .strip_suffix('}')
+        .and_then(|rest| rest.rsplit_once('{'))
+    else {
+        return false;
+    };
+    let (min, max) = counts.split_once(',').unwrap_or((counts, "0"));
+    let is_count = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+    !min.is_empty() && is_count(min) && is_count(max)
+}
+
+// "\$" outside regexes, which nginx does not take as an escape
+fn check_escaped_dollars(payload: &Payload) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    for d in payload.directives() {
+        let directive = d.directive();
+        if directive.placeholder {
+            continue;
+        }
+        let regexes = directive_regexes(directive);
+        for (i, arg) in directive.args.iter().enumerate() {
+            if !escapes_dollar(arg) || regexes.iter().any(|&(regex, ..)| regex == i) {
+                continue;
+            }
+            findings.push(
+                Finding::new(
+                    "escaped-dollar",
+                    Severity::Warning,
+                    format!(
+                        "\"\\$\" in \"{}\" directive does not escape '$', nginx keeps the \
+                         backslash and reads a variable after it",
+                        directive.directive
+                    ),
+                    d.file_name(),
+                    directive.line,
+                )
+                .at_arg(directive, i),
+            );
+        }
+    }
+    findings
+}
+
+// whether a backslash comes before a '$', one not itself escaped
+fn escapes_dollar(arg: &str) -> bool {
+    let mut chars = arg.chars();
+    while let Some(ch) = chars.next() {
+        if ch == '\\' && chars.next() == Some('$') {
+            return true;
+        }
+    }
+    false
+}
+
+// the regexes of a directive, as the index of the arg holding each, where
+// it starts in the arg, and whether it matches case-insensitively
+pub(crate) fn directive_regexes<S: AsRef<str>>(stmt: &Directive<S>) -> Vec<(usize, usize, bool)> {
+    let args = &stmt.args;
+    let arg = |i: usize| args.get(i).map(S::as_ref);
+    match stmt.directive.as_ref() {
+        "location" => match arg(0) {
+            Some("~") if args.len() > 1 => vec![(1, 0, false)],
+            Some("~*") if args.len() > 1 => vec![(1, 0, true)],
+            _ => Vec::new(),
+        },
+        "if" => match arg(1) {
+            Some("~" | "!~") if args.len() > 2 => vec![(2, 0, false)],
+            Some("~*" | "!~*") if args.len() > 2 => vec![(2, 0, true)],
+            _ => Vec::new(),
+        },
+        "rewrite" if !args.is_empty() => vec![(0, 0, false)],
+        // nginx matches regex server names without case
+        "server_name" => args
+            .iter()
+            .enumerate()
+            .filter(|(_, a)| a.as_ref().len() > 1 && a.as_ref().starts_with('~'))
+            .map(|(i, _)| (i, 1, true))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+// on/off directives given anything else
+fn check_flag_values(payload: &Payload) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    for d in payload.directives() {
+        let directive = d.directive();
+        let specs = directive_specs(&directive.directive);
+        if specs.is_empty() || specs.iter().any(|s| s.mask & NGX_CONF_FLAG == 0) {
+            continue;
+        }
+        let [value] = &directive.args[..] else {
+            continue;
+        };
+        // what a template fills in is not known until it is rendered
+        if directive.placeholder {
+            continue;
+        }
+        let flag = match value.to_ascii_lowercase().as_str() {
+            "on" | "off" => continue,
+            "no" | "false" | "0" | "disable" | "disabled" => "off",
+            _ => "on",
+        };
+        let fix = Suggestion {
+            summary: format!("replace \"{}\" with \"{}\"", value, flag),
+            edits: vec![TreeEdit::Replace {
+                at: d.location(),
+                directive: Directive {
+                    args: vec![flag.to_string()],
+                    // the new args were not written anywhere
+                    arg_spans: Vec::new(),
+                    ..directive.clone()
+                },
+            }],
+        };
+        findings.push(
+            Finding::new(
+                "invalid-flag",
+                Severity::Error,
+                invalid_flag_value(&directive.directive, value),
+                d.file_name(),
+                directive.line,
+            )
+            .at_arg(directive, 0)
+            .suggestion(fix),
+        );
+    }
+    findings
+}
+
+// the name of the top level block around a directive, looking through the
+// includes that pulled its file in
+fn outermost_block<'a>(payload: &'a Payload, d: &DirectiveRef<'a>) -> Option<&'a str> {
+    let mut current = d.clone();
+    let mut seen = Vec::new();
+    loop {
+        let file = current.file_index();
+        let include = payload.directives().find(|i| {
+            i.directive()
+                .includes
+                .as_ref()
+                .is_some_and(|files| files.contains(&file))
+        });
+        match include {
+            Some(include) if !seen.contains(&file) => {
+                seen.push(file);
+                current = include;
+            }
+            _ => {
+                let top = current.ancestors().last()?;
+                return Some(top.directive().directive.as_str());
+            }
+        }
+    }
+}
+
+// directives nginx rejects, or handles in surprising ways, inside if blocks
+fn check_if_hazards(payload: &Payload) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    for d in payload.directives() {
+        let directive = d.directive();
+        if !directive.in_if {
+            continue;
+        }
+        let arg = directive
+            .args
+            .first()
+            .map(String::as_str)
+            .unwrap_or_default();
+        let (severity, message) = match directive.directive.as_str() {
+            "try_files" => (
+                Severity::Error,
+                "\"try_files\" is not allowed inside \"if\"",
+            ),
+            "proxy_pass" if has_uri_part(arg) => (
+                Severity::Error,
+                "\"proxy_pass\" cannot have URI part inside \"if\"",
+            ),
+            "add_header" if enclosing_add_header(&d) => (
+                Severity::Warning,
+                "\"add_header\" inside \"if\" stops the headers of enclosing blocks \
+                 from being inherited",
+            ),
+            _ => continue,
+        };
+        let finding = Finding::new(
+            "if-hazard",
+            severity,
+            message,
+            d.file_name(),
+            directive.line,
+        );
+        findings.push(match directive.directive.as_str() {
+            "proxy_pass" => finding.at_arg(directive, 0),
+            _ => finding,
+        });
+    }
+    findings
+}
+
+// whether a proxied URL like "http://backend/path" has a URI after the host;
+// nginx only checks this when there are no variables
+fn has_uri_part(url: &str) -> bool {
+    if url.contains('$') {
+        return false;
+    }
+    let host = url.split_once("://").map_or(url, |(_, rest)| rest);
+    host.contains('/')
+}
+
+// whether a block around the enclosing if adds headers of its own
+fn enclosing_add_header(d: &DirectiveRef<'_>) -> bool {
+    d.ancestors()
+        .filter(|a| a.directive().directive != "if")
+        .any(|a| {
+            a.directive()
+                .block
+                .iter()
+                .flatten()
+                .any(|child| child.directive == "add_header")
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::Path;
+
+    use super::*;
+    use crate::build::{build, BuildOptions};
+    use crate::lex::{LexOptions, TemplateSyntax};
+    use crate::parse::{parse, parse_source, ParseOptions};
+
+    #[test]
+    fn test_if_hazards() {
+        let source = r#"http {
+    server {
+        add_header X-Frame-Options DENY;
+        location / {
+            if ($http_x_debug) {
+                proxy_pass http://backend;
+                add_header X-Debug 1;
+            }
+            if ($arg_v2) {
+                proxy_pass http://backend/v2/;
+                if ($arg_retry) {
+                    try_files $uri =404;
+                    proxy_pass http://$host/retry;
+                }
+            }
+        }
+    }
+}
+"#;
+        let payload = parse_source(source, &ParseOptions::default());
+        let findings: Vec<String> = payload.lint().iter().map(|f| f.to_string()).collect();
+        assert_eq!(
+            findings,
+            [
+                "warning: \"add_header\" inside \"if\" stops the headers of enclosing \
+                 blocks from being inherited in nginx.conf:7 [if-hazard]",
+                "error: \"proxy_pass\" cannot have URI part inside \"if\" in nginx.conf:10 \
+                 [if-hazard]",
+                "error: \"try_files\" is not allowed inside \"if\" in nginx.conf:12 [if-hazard]",
+            ]
+        );
+
+        let try_files = payload
+            .directives()
+            .find(|d| d.directive().directive == "try_files")
+            .unwrap();
+        assert!(try_files.directive().in_if);
+        assert_eq!(try_files.path(), [0, 0, 1, 1, 1, 0]);
+        let nearest = try_files.enclosing_if().unwrap();
+        assert_eq!(nearest.directive().args, ["$arg_retry"]);
+        let outer = nearest.enclosing_if().unwrap();
+        assert_eq!(outer.directive().args, ["$arg_v2"]);
+        assert_eq!(outer.enclosing_if(), None);
+        assert_eq!(outer.parent().unwrap().directive().directive, "location");
+
+        let location = outer.parent().unwrap();
+        assert!(!location.directive().in_if);
+        assert_eq!(location.enclosing_if(), None);
+    }
+
+    #[test]
+    fn test_ends_with_quantifier() {
+        for pattern in ["a{2}", "a{2,}", "a{2,5}", "~^www\\d{12}"] {
+            assert!(ends_with_quantifier(pattern), "{}", pattern);
+        }
+        for pattern in ["a{}", "a{,5}", "a{x}", "a{2,x}", "${host}", "a}", "a"] {
+            assert!(!ends_with_quantifier(pattern), "{}", pattern);
+        }
+    }
+
+    #[test]
+    fn test_escaped_dollars() {
+        let source = r#"set $a \$literal;
+set $b \\$host;
+return 200 "price: \$5 for ${item}s";
+location ~ ^/a\$ {
+    rewrite ^/b\$ /c;
+}
+"#;
+        let payload = parse_source(source, &ParseOptions::default());
+        let escaped = |options: &LintOptions| -> Vec<(usize, Option<usize>)> {
+            payload
+                .lint_with(options)
+                .iter()
+                .filter(|f| f.kind == "escaped-dollar")
+                .map(|f| (f.line, f.arg_index))
+                .collect()
+        };
+        assert!(escaped(&LintOptions::default()).is_empty());
+        let options = LintOptions::default().escaped_dollars(true);
+        assert_eq!(escaped(&options), [(1, Some(1)), (3, Some(1))]);
+        let findings = payload.lint_with(&options);
+        let finding = findings
+            .iter()
+            .find(|f| f.kind == "escaped-dollar")
+            .unwrap();
+        assert_eq!(
+            finding.message,
+            "\"\\$\" in \"set\" directive does not escape '$', nginx keeps the backslash and \
+             reads a variable after it"
+        );
+    }
+
+    #[test]
+    fn test_trailing_punctuation() {
+        let source = r#"http {
+    upstream backend {
+        server "127.0.0.1:8080;";
+    }
+    server {
+        server_name "example.com;" www.example.com;
+        root "/srv/www{";
+        location / {
+            proxy_pass "http://backend;";
+            alias "/srv/${name}";
+            return 200 "ok;";
+            add_header X-Css "a { color: red; }";
+        }
+        location /static {
+            alias "/srv/static/}";
+        }
+    }
+    map $uri $x {
+        "~^/a;" 1;
+    }
+    server {
+        server "not in upstream;";
+        server_name "~^www\d{3}" "~^(a|b){2,}\.example" "~^x{1,12}" "~^{www}";
+        root /srv/www};
+    }
+}
+"#;
+        let payload = parse_source(source, &ParseOptions::default());
+        let options = LintOptions::default().trailing_punctuation(true);
+        let findings: Vec<String> = payload
+            .lint_with(&options)
+            .iter()
+            .filter(|f| f.kind == "trailing-punctuation")
+            .map(|f| format!("{}:{} {}", f.severity, f.line, f.message))
+            .collect();
+        assert_eq!(
+            findings,
+            [
+                "warning:3 \"127.0.0.1:8080;\" in \"server\" directive ends with ';', which likely \
+                 belongs after the arg",
+                "warning:6 \"example.com;\" in \"server_name\" directive ends with ';', which \
+                 likely belongs after the arg",
+                "warning:7 \"/srv/www{\" in \"root\" directive ends with '{', which likely \
+                 belongs after the arg",
+                "warning:9 \"http://backend;\" in \"proxy_pass\" directive ends with ';', \
+                 which likely belongs after the arg",
+                "warning:15 \"/srv/static/}\" in \"alias\" directive ends with '}', which likely \
+                 belongs after the arg",
+                "warning:23 \"~^{www}\" in \"server_name\" directive ends with '}', which likely \
+                 belongs after the arg",
+                // unquoted, the '}' is part of the arg, as in nginx
+                "warning:24 \"/srv/www}\" in \"root\" directive ends with '}', which likely \
+                 belongs after the arg",
+            ]
+        );
+        assert!(payload
+            .lint()
+            .iter()
+            .all(|f| f.kind != "trailing-punctuation"));
+    }
+
+    #[test]
+    fn test_default_servers() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |name: &str, source: &str| fs::write(dir.path().join(name), source).unwrap();
+        write(
+            "nginx.conf",
+            "http {
+    include a.conf;
+    include b.conf;
+    server {
+        listen 8080 default_server;
+    }
+}
+stream {
+    server {
+        listen 80 default_server;
+    }
+}
+",
+        );
+        write(
+            "a.conf",
+            "server {
+    listen *:80 default_server;
+    listen [::]:80 default_server;
+}
+",
+        );
+        write(
+            "b.conf",
+            "server {
+    listen 80 default_server;
+    listen [::]:8080 default_server;
+}
+server {
+    listen 0.0.0.0:8080 default;
+}
+",
+        );
+        let payload = parse(dir.path().join("nginx.conf"), &ParseOptions::default());
+        assert_eq!(payload.errors, []);
+
+        let findings: Vec<(String, usize, String)> = payload
+            .lint()
+            .into_iter()
+            .map(|f| {
+                let file = Path::new(&f.file).file_name().unwrap();
+                (file.to_string_lossy().into_owned(), f.line, f.message)
+            })
+            .collect();
+        let a_conf = dir.path().join("a.conf").display().to_string();
+        assert_eq!(
+            findings,
+            [
+                (
+                    "b.conf".to_string(),
+                    2,
+                    format!(
+                        "a duplicate default server for 0.0.0.0:80, first declared in {}:2",
+                        a_conf
+                    )
+                ),
+                (
+                    "b.conf".to_string(),
+                    6,
+                    format!(
+                        "a duplicate default server for 0.0.0.0:8080, first declared in {}:5",
+                        dir.path().join("nginx.conf").display()
+                    )
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_main_blocks() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |name: &str, source: &str| fs::write(dir.path().join(name), source).unwrap();
+        write(
+            "nginx.conf",
+            "events {
+}
+include a.conf;
+include b.conf;
+",
+        );
+        write(
+            "a.conf",
+            "http {
+    events {
+    }
+    server {
+        include c.conf;
+    }
+}
+",
+        );
+        write("b.conf", "http {\n}\nstream {\n}\n");
+        write("c.conf", "stream {\n}\n");
+        let options = ParseOptions::default().catch_errors(true);
+        let payload = parse(dir.path().join("nginx.conf"), &options);
+
+        let findings: Vec<(String, usize, String)> = payload
+            .lint()
+            .into_iter()
+            .filter(|f| f.kind.ends_with("main-block"))
+            .map(|f| {
+                let file = Path::new(&f.file).file_name().unwrap();
+                (file.to_string_lossy().into_owned(), f.line, f.message)
+            })
+            .collect();
+        assert_eq!(
+            findings,
+            [
+                (
+                    "a.conf".to_string(),
+                    2,
+                    "\"events\" is only allowed in the main context, not in \"http\"".to_string()
+                ),
+                (
+                    "b.conf".to_string(),
+                    1,
+                    format!(
+                        "\"http\" is duplicate, first declared in {}:1",
+                        dir.path().join("a.conf").display()
+                    )
+                ),
+                (
+                    "c.conf".to_string(),
+                    1,
+                    "\"stream\" is only allowed in the main context, not in \"http server\""
+                        .to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_include_findings() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |name: &str, source: &str| fs::write(dir.path().join(name), source).unwrap();
+        for sub in ["empty", "blank", "broken"] {
+            fs::create_dir(dir.path().join(sub)).unwrap();
+        }
+        write(
+            "nginx.conf",
+            "http {
+    include empty/*.conf;
+    include blank/*.conf;
+    include broken/*.conf;
+}
+",
+        );
+        write("blank/a.conf", "");
+        write("blank/b.conf", "\n# nothing yet\n");
+        write("broken/a.conf", "server {\n    listen 80;\n}\n");
+        write("broken/b.conf", "server {\n    listen 81;\n}\n}\n");
+
+        let payload = parse(dir.path().join("nginx.conf"), &ParseOptions::default());
+        let broken = dir.path().join("broken/b.conf").display().to_string();
+        assert_eq!(payload.errors.len(), 1);
+        assert_eq!(payload.errors[0].file.as_deref(), Some(broken.as_str()));
+
+        let findings = payload.lint();
+        let findings: Vec<String> = findings
+            .iter()
+            .filter(|f| f.kind.starts_with("include-"))
+            .map(|f| format!("{}:{} {}", f.severity, f.line, f.message))
+            .collect();
+        assert_eq!(
+            findings,
+            [
+                "warning:2 \"include empty/*.conf\" matches no files".to_string(),
+                "warning:3 \"include blank/*.conf\" matches only empty files".to_string(),
+                format!(
+                    "warning:4 \"include broken/*.conf\" pulls in files with errors: \
+                     unexpected '}}', all blocks were already closed (last block closed \
+                     at line 3) in {}:4",
+                    broken
+                ),
+            ]
+        );
+
+        // the findings can be made to fail a deploy
+        let options = LintOptions::default().include_severity(Severity::Error);
+        assert_eq!(
+            payload.lint_with(&options).count_at_least(Severity::Error),
+            3
+        );
+    }
+
+    #[test]
+    fn test_combined_findings() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("nginx.conf"),
+            "http {\n    include inc.conf;\n}\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("inc.conf"),
+            "server {\n    listen 80;\n}\n\ngzip yes;\n",
+        )
+        .unwrap();
+        let inc = dir.path().join("inc.conf").display().to_string();
+
+        // findings of included directives name their file, combined or not
+        for options in [
+            ParseOptions::default(),
+            ParseOptions::default().combine(true),
+        ] {
+            let payload = parse(dir.path().join("nginx.conf"), &options);
+            let findings = payload.lint();
+            let flag: Vec<_> = findings
+                .iter()
+                .filter(|f| f.kind == "invalid-flag")
+                .map(|f| (f.file.as_str(), f.line))
+                .collect();
+            assert_eq!(flag, [(inc.as_str(), 5)], "{:?}", options);
+            let gzip = payload
+                .directives()
+                .find(|d| d.directive().directive == "gzip")
+                .unwrap();
+            assert_eq!(gzip.file_name(), inc);
+        }
+    }
+
+    #[test]
+    fn test_suggestions() {
+        let source = "http {
+    sendfile yes;
+    tcp_nopush Off;
+    server {
+        listen 443 default_server;
+        listen [::]:443;
+        ssl on;
+        gzip 0;
+    }
+    server {
+        listen *:443 default_server ssl;
+        ssl off;
+    }
+}
+";
+        let mut payload = parse_source(source, &ParseOptions::default());
+        let findings = payload.lint();
+        let summaries: Vec<(&str, &str)> = findings
+            .iter()
+            .map(|f| {
+                (
+                    f.kind.as_str(),
+                    f.suggestion.as_ref().unwrap().summary.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summaries,
+            [
+                ("invalid-flag", "replace \"yes\" with \"on\""),
+                (
+                    "deprecated-ssl",
+                    "move \"ssl\" to the listen directives of the server"
+                ),
+                ("invalid-flag", "replace \"0\" with \"off\""),
+                (
+                    "duplicate-default-server",
+                    "remove \"default_server\" from \"listen 0.0.0.0:443\""
+                ),
+                ("deprecated-ssl", "remove \"ssl\""),
+            ]
+        );
+        assert_eq!(
+            findings[0].message,
+            "invalid value \"yes\" in \"sendfile\" directive, it must be \"on\" or \"off\""
+        );
+
+        // each fix moves what comes after it, so lint again after applying one
+        while let Some(finding) = payload.lint().into_iter().find(|f| f.suggestion.is_some()) {
+            payload
+                .apply_suggestion(&finding.suggestion.unwrap())
+                .unwrap();
+        }
+        assert!(payload.lint().is_empty());
+        let built = build(&payload.config[0].parsed, &BuildOptions::default());
+        let fixed = parse_source(&built, &ParseOptions::default().strict(true));
+        assert_eq!(fixed.errors, []);
+        assert_eq!(
+            built,
+            "http {
+    sendfile on;
+    tcp_nopush Off;
+    server {
+        listen 443 default_server ssl;
+        listen [::]:443 ssl;
+        gzip off;
+    }
+    server {
+        listen *:443 ssl;
+    }
+}"
+        );
+
+        let mut stale = findings[4].suggestion.clone().unwrap();
+        stale.edits.push(TreeEdit::Remove(DirectiveLocation {
+            file: 0,
+            path: vec![0, 9],
+        }));
+        let before = payload.clone();
+        assert!(payload.apply_suggestion(&stale).is_err());
+        assert_eq!(payload, before);
+
+        // the values of template placeholders are not known
+        let options = ParseOptions::default()
+            .lex_options(LexOptions::default().template_syntax(TemplateSyntax::Jinja));
+        let payload = parse_source("gzip {{ gzip }};\nsendfile {{ on }}x;\n", &options);
+        assert!(payload.lint().iter().all(|f| f.kind != "invalid-flag"));
+    }
+
+    #[test]
+    fn test_render_snippet() {
+        let source = "http {
+    sendfile   yes;
+    server {
+        listen 80 default_server;
+        server_name \"тест.example;\" example.com;
+    }
+    server {
+\tlisten 80 backlog=10 default_server;
+        location / {
+            if ($arg_v2) {
+                proxy_pass http://backend/v2/;
+            }
+        }
+    }
+}
+";
+        let options = ParseOptions::default().arg_spans(true);
+        let payload = parse_source(source, &options);
+        assert_eq!(
+            payload.config[0].parsed[0].block.as_ref().unwrap()[0].arg_spans,
+            [Span {
+                line: 2,
+                start: 15,
+                end: 18
+            }]
+        );
+        // the spans are all that changes
+        let plain = parse_source(source, &ParseOptions::default());
+        let without_spans = payload
+            .lint()
+            .into_iter()
+            .map(|f| Finding { span: None, ..f });
+        assert!(without_spans.eq(plain.lint()));
+
+        let lint = LintOptions::default().trailing_punctuation(true);
+        let snippets: Vec<String> = payload
+            .lint_with(&lint)
+            .iter()
+            .map(|f| f.render_snippet(source))
+            .collect();
+        assert_eq!(
+            snippets,
+            [
+                "error: invalid value \"yes\" in \"sendfile\" directive, it must be \"on\" or \
+                 \"off\" [invalid-flag]
+ --> nginx.conf:2:16
+  |
+2 |     sendfile   yes;
+  |                ^^^
+",
+                "warning: \"тест.example;\" in \"server_name\" directive ends with ';', which \
+                 likely belongs after the arg [trailing-punctuation]
+ --> nginx.conf:5:21
+  |
+5 |         server_name \"тест.example;\" example.com;
+  |                     ^^^^^^^^^^^^^^^
+",
+                "error: a duplicate default server for 0.0.0.0:80, first declared in nginx.conf:4 \
+                 [duplicate-default-server]
+ --> nginx.conf:8:23
+  |
+8 | \tlisten 80 backlog=10 default_server;
+  | \t                     ^^^^^^^^^^^^^^
+",
+                "error: \"proxy_pass\" cannot have URI part inside \"if\" [if-hazard]
+  --> nginx.conf:11:28
+   |
+11 |                 proxy_pass http://backend/v2/;
+   |                            ^^^^^^^^^^^^^^^^^^
+",
+            ]
+        );
+
+        // without spans the whole line is underlined
+        let finding = Finding::new("kind", Severity::Warning, "message", "nginx.conf", 3);
+        assert_eq!(
+            finding.render_snippet(source),
+            "warning: message [kind]
+ --> nginx.conf:3:5
+  |
+3 |     server {
+  |     ^^^^^^^^
+"
+        );
+    }
+}
@@ -0,0 +1,309 @@
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+use crate::edit::directive_mut;
+use crate::error::{ParseError, ParseErrorKind};
+use crate::parse::{
+    enter_block_ctx, has_variables, load_files, partial_payload, Includes, ParseOptions, Payload,
+    INCLUDE_VARIABLES,
+};
+use crate::tree::{DirectiveLocation, DirectiveRef};
+
+/// Where the files of a config are read from, see [`parse_with_loader`](crate::parse_with_loader).
+pub trait ConfigLoader {
+    /// The contents of the file at `path`, or at most `limit` bytes of them.
+    fn read(&self, path: &Path, limit: Option<u64>) -> io::Result<Vec<u8>>;
+
+    /// Whether the file at `path` can be opened, checked for includes naming
+    /// a single file.
+    fn check(&self, path: &Path) -> io::Result<()>;
+
+    /// The paths of the files matching an include pattern relative to
+    /// `config_dir`, sorted.
+    fn glob(&self, config_dir: &Path, pattern: &str) -> Vec<PathBuf>;
+}
+
+/// Reads configs from the file system, as [`parse`](fn@crate::parse) does.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FsLoader;
+
+impl ConfigLoader for FsLoader {
+    fn read(&self, path: &Path, limit: Option<u64>) -> io::Result<Vec<u8>> {
+        let mut source = Vec::new();
+        let Some(limit) = limit else {
+            File::open(path)?.read_to_end(&mut source)?;
+            return Ok(source);
+        };
+
+        // devices and fifos can produce endless input
+        if !fs::metadata(path)?.is_file() {
+            return Err(io::Error::other("not a regular file"));
+        }
+        File::open(path)?.take(limit).read_to_end(&mut source)?;
+        Ok(source)
+    }
+
+    fn check(&self, path: &Path) -> io::Result<()> {
+        File::open(path).map(|_| ())
+    }
+
+    // hidden files are left out unless the pattern names them, like glob(3)
+    // does for nginx, and directories are skipped rather than failing to read
+    fn glob(&self, config_dir: &Path, pattern: &str) -> Vec<PathBuf> {
+        let dir = glob::Pattern::escape(&config_dir.to_string_lossy());
+        let pattern = Path::new(&dir).join(pattern);
+        let options = glob::MatchOptions {
+            require_literal_leading_dot: true,
+            ..Default::default()
+        };
+        let Ok(paths) = glob::glob_with(&pattern.to_string_lossy(), options) else {
+            return Vec::new();
+        };
+        let mut paths: Vec<PathBuf> = paths.flatten().filter(|p| !p.is_dir()).collect();
+        paths.sort();
+        paths
+    }
+}
+
+// serves the config read from a reader as the file at `path`, and the files
+// it includes from the file system
+pub(crate) struct ReaderLoader {
+    pub(crate) path: PathBuf,
+    pub(crate) source: io::Result<Vec<u8>>,
+}
+
+impl ConfigLoader for ReaderLoader {
+    fn read(&self, path: &Path, limit: Option<u64>) -> io::Result<Vec<u8>> {
+        if path != self.path {
+            return FsLoader.read(path, limit);
+        }
+        match &self.source {
+            Ok(source) => {
+                let len = limit.map_or(source.len(), |limit| source.len().min(limit as usize));
+                Ok(source[..len].to_vec())
+            }
+            Err(e) => Err(io::Error::new(e.kind(), e.to_string())),
+        }
+    }
+
+    fn check(&self, path: &Path) -> io::Result<()> {
+        match path == self.path {
+            true => Ok(()),
+            false => FsLoader.check(path),
+        }
+    }
+
+    fn glob(&self, config_dir: &Path, pattern: &str) -> Vec<PathBuf> {
+        FsLoader.glob(config_dir, pattern)
+    }
+}
+
+impl Payload {
+    /// Parses the files pulled in by the include directive at `at`, for
+    /// payloads parsed without following includes. The files are added
+    /// after the others, with those they include in turn, and the
+    /// directive's `includes` set to them. Once resolved, the indexes are
+    /// returned again without reading anything.
+    pub fn resolve_include(
+        &mut self,
+        at: &DirectiveLocation,
+        loader: &dyn ConfigLoader,
+        options: &ParseOptions,
+    ) -> Result<Vec<usize>, ParseError> {
+        let Some(d) = self.directive_ref(at.file, &at.path) else {
+            return Err(ParseError {
+                what: "no directive at the given location".to_string(),
+                file: None,
+                line: None,
+                kind: ParseErrorKind::Syntax,
+                arg_index: None,
+                span: None,
+            });
+        };
+        let directive = d.directive();
+        let file = d.file_name().to_string();
+        let arg = match directive.args.as_slice() {
+            [arg] if directive.directive == "include" => arg,
+            _ => {
+                let what = format!("\"{}\" is not an include directive", directive.directive);
+                return Err(ParseError::new(what, directive.line).in_file(&file));
+            }
+        };
+        if let Some(indexes) = &directive.includes {
+            return Ok(indexes.clone());
+        }
+
+        // not a file that could not be read
+        if has_variables(arg) {
+            return Err(ParseError::new(INCLUDE_VARIABLES, directive.line).in_file(&file));
+        }
+
+        let ctx = parse_context(self, &d);
+        let mut includes = Includes::new(self.config_dir(), loader, options);
+        for existing in &self.config {
+            includes.add(PathBuf::from(&existing.file), &[], None);
+        }
+        if includes.files.len() < self.config.len() {
+            let what = "the payload has several files of the same name";
+            return Err(ParseError::new(what, directive.line).in_file(&file));
+        }
+        let line = directive.line;
+        let indexes = includes.resolve(arg, &ctx).map_err(|what| ParseError {
+            kind: ParseErrorKind::Io,
+            ..ParseError::new(what, line).in_file(&file)
+        })?;
+
+        if let Some(directive) = directive_mut(self, at) {
+            directive.includes = Some(indexes);
+        }
+        let mut config = std::mem::take(&mut self.config);
+        // files left unparsed by an error stopping the parse are dropped
+        // from the includes, as when parsing
+        load_files(&mut config, &mut includes, options);
+        let generation = self.generation();
+        let metrics = self.metrics.take();
+        *self = partial_payload(config);
+        self.generation = generation;
+        self.metrics = metrics;
+        self.mark_changed();
+
+        let resolved = self.directive_ref(at.file, &at.path);
+        Ok(resolved
+            .and_then(|d| d.directive().includes.clone())
+            .unwrap_or_default())
+    }
+}
+
+// the parse context of the block holding `d`, including the contexts of the
+// includes its file is pulled in by
+pub(crate) fn parse_context(payload: &Payload, d: &DirectiveRef) -> Vec<String> {
+    block_context(d, &file_contexts(payload))
+}
+
+// the contexts the included files of a payload are parsed in, by index
+pub(crate) fn file_contexts(payload: &Payload) -> HashMap<usize, Vec<String>> {
+    let mut contexts: HashMap<usize, Vec<String>> = HashMap::new();
+    // included files come after the files including them
+    for i in payload.directives() {
+        let Some(files) = &i.directive().includes else {
+            continue;
+        };
+        let ctx = block_context(&i, &contexts);
+        for &file in files {
+            contexts.entry(file).or_insert_with(|| ctx.clone());
+        }
+    }
+    contexts
+}
+
+fn block_context(d: &DirectiveRef, contexts: &HashMap<usize, Vec<String>>) -> Vec<String> {
+    let mut ctx = contexts.get(&d.file_index()).cloned().unwrap_or_default();
+    let ancestors: Vec<_> = d.ancestors().collect();
+    for ancestor in ancestors.iter().rev() {
+        ctx = enter_block_ctx(&ancestor.directive().directive, &ctx);
+    }
+    ctx
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::{parse, parse_named, parse_with_loader};
+    use crate::test_support::MemoryLoader;
+
+    #[test]
+    fn test_resolve_include() {
+        let root = "configs/includes/nginx.conf";
+        let source = std::fs::read_to_string(root).unwrap();
+        let options = ParseOptions::default();
+        let mut payload = parse_named(&source, root, &options);
+        assert_eq!(payload.config.len(), 1);
+        let at = payload.find("include")[0].location();
+        let generation = payload.generation();
+
+        let indexes = payload.resolve_include(&at, &FsLoader, &options).unwrap();
+        assert_eq!(indexes, [1]);
+        assert_eq!(payload, parse(root, &options));
+        assert!(payload.generation() > generation);
+
+        // resolving it again changes nothing
+        let resolved = payload.clone();
+        let indexes = payload.resolve_include(&at, &FsLoader, &options).unwrap();
+        assert_eq!(indexes, [1]);
+        assert_eq!(payload, resolved);
+
+        // the nested include was followed, and is resolved already too
+        let nested = payload.find("include")[1].location();
+        let indexes = payload
+            .resolve_include(&nested, &FsLoader, &options)
+            .unwrap();
+        assert_eq!(indexes, [2]);
+        assert_eq!(payload.config.len(), 3);
+
+        let server = payload.find("server")[0].location();
+        let error = payload
+            .resolve_include(&server, &FsLoader, &options)
+            .unwrap_err();
+        assert_eq!(error.what, "\"server\" is not an include directive");
+    }
+
+    #[test]
+    fn test_resolve_include_with_loader() {
+        let loader = MemoryLoader(HashMap::from([
+            (
+                PathBuf::from("/etc/nginx/nginx.conf"),
+                "http {\n    include sites/*.conf;\n    include missing.conf;\n}\n",
+            ),
+            (
+                PathBuf::from("/etc/nginx/sites/a.conf"),
+                "server {\n    listen 80;\n}\n",
+            ),
+            (
+                PathBuf::from("/etc/nginx/sites/b.conf"),
+                "location / {\n}\n",
+            ),
+        ]));
+        let options = ParseOptions::default();
+        let payload = parse_with_loader(
+            "/etc/nginx/nginx.conf",
+            &options.clone().catch_errors(true),
+            &loader,
+        );
+        let files: Vec<_> = payload.config.iter().map(|c| c.file.as_str()).collect();
+        assert_eq!(
+            files,
+            [
+                "/etc/nginx/nginx.conf",
+                "/etc/nginx/sites/a.conf",
+                "/etc/nginx/sites/b.conf"
+            ]
+        );
+        assert_eq!(payload.errors.len(), 1);
+        assert_eq!(
+            payload.errors[0].what,
+            "entity not found: '/etc/nginx/missing.conf'"
+        );
+        assert_eq!(payload.config[2].parsed[0].directive, "location");
+
+        let mut payload = parse_named(
+            loader.0[Path::new("/etc/nginx/nginx.conf")],
+            "/etc/nginx/nginx.conf",
+            &options,
+        );
+        let missing = payload.find("include")[1].location();
+        let error = payload
+            .resolve_include(&missing, &loader, &options)
+            .unwrap_err();
+        assert_eq!(error.kind, ParseErrorKind::Io);
+        assert_eq!(error.line, Some(3));
+        assert_eq!(payload.config.len(), 1);
+
+        let sites = payload.find("include")[0].location();
+        let indexes = payload.resolve_include(&sites, &loader, &options).unwrap();
+        assert_eq!(indexes, [1, 2]);
+        assert_eq!(payload.config[1].file, "/etc/nginx/sites/a.conf");
+        assert_eq!(payload.errors, []);
+    }
+}
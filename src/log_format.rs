@@ -0,0 +1,273 @@
+use std::collections::HashSet;
+
+use crate::findings::Findings;
+use crate::lint::{Finding, Severity};
+use crate::parse::Payload;
+use crate::variables::{defined_variables, is_builtin_variable};
+
+// the format access_log uses when none is given, always defined
+const COMBINED: &str = "combined";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LogSegment {
+    Literal(String),
+    Variable(String),
+}
+
+/// A `log_format` directive with its template split into literal text and
+/// variables.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogFormat {
+    pub name: String,
+    /// The value of the `escape=` parameter, if given.
+    pub escape: Option<String>,
+    pub template: Vec<LogSegment>,
+    pub file: String,
+    pub line: usize,
+}
+
+impl LogFormat {
+    pub fn variables(&self) -> impl Iterator<Item = &str> {
+        self.template.iter().filter_map(|segment| match segment {
+            LogSegment::Variable(name) => Some(name.as_str()),
+            LogSegment::Literal(_) => None,
+        })
+    }
+}
+
+impl Payload {
+    /// All `log_format` directives of the payload, in config order.
+    pub fn log_formats(&self) -> Vec<LogFormat> {
+        let mut formats = Vec::new();
+        for directive in self.directives() {
+            let (file, directive) = (directive.file_name(), directive.directive());
+            if directive.directive != "log_format" || directive.args.is_empty() {
+                continue;
+            }
+            let mut args = &directive.args[1..];
+            let escape = args
+                .first()
+                .and_then(|arg| arg.strip_prefix("escape="))
+                .map(str::to_string);
+            if escape.is_some() {
+                args = &args[1..];
+            }
+            formats.push(LogFormat {
+                name: directive.args[0].clone(),
+                escape,
+                // nginx joins the strings of a format without a separator
+                template: parse_template(&args.concat()),
+                file: file.to_string(),
+                line: directive.line,
+            });
+        }
+        formats
+    }
+
+    /// Checks the log formats for variables that are neither builtin nor
+    /// defined by the config, and `access_log` directives naming a format
+    /// that does not exist.
+    pub fn check_log_formats(&self) -> Findings {
+        let formats = self.log_formats();
+        let defined = defined_variables(self);
+        let mut findings = Findings::new();
+
+        for format in &formats {
+            for name in format.variables() {
+                if !is_builtin_variable(name) && !defined.contains(name) {
+                    findings.push(Finding::new(
+                        "unknown-variable",
+                        Severity::Error,
+                        format!(
+                            "unknown variable \"${}\" in log format \"{}\"",
+                            name, format.name
+                        ),
+                        &format.file,
+                        format.line,
+                    ));
+                }
+            }
+        }
+
+        let names: HashSet<&str> = formats.iter().map(|f| f.name.as_str()).collect();
+        for directive in self.directives() {
+            let (file, directive) = (directive.file_name(), directive.directive());
+            if directive.directive != "access_log" {
+                continue;
+            }
+            // access_log path [format [buffer=size] ...]
+            let Some(format) = directive.args.get(1) else {
+                continue;
+            };
+            if format.contains('=') || format == COMBINED || names.contains(format.as_str()) {
+                continue;
+            }
+            findings.push(Finding::new(
+                "unknown-log-format",
+                Severity::Error,
+                format!("unknown log format \"{}\"", format),
+                file,
+                directive.line,
+            ));
+        }
+
+        findings
+    }
+}
+
+// split a log format into literal text and "$name" or "${name}" variables
+fn parse_template(template: &str) -> Vec<LogSegment> {
+    let mut segments = Vec::new();
+    let mut literal = String::new();
+    let mut rest = template;
+
+    while let Some(i) = rest.find('$') {
+        literal.push_str(&rest[..i]);
+        rest = &rest[i + 1..];
+
+        let (name, len) = match rest.strip_prefix('{') {
+            Some(braced) => match braced.find('}') {
+                Some(end) => (&braced[..end], end + 2),
+                None => ("", 0),
+            },
+            None => {
+                let end = rest
+                    .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+                    .unwrap_or(rest.len());
+                (&rest[..end], end)
+            }
+        };
+
+        if name.is_empty() {
+            literal.push('$');
+            continue;
+        }
+        if !literal.is_empty() {
+            segments.push(LogSegment::Literal(std::mem::take(&mut literal)));
+        }
+        segments.push(LogSegment::Variable(name.to_string()));
+        rest = &rest[len..];
+    }
+
+    literal.push_str(rest);
+    if !literal.is_empty() {
+        segments.push(LogSegment::Literal(literal));
+    }
+    segments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::{parse_source, ParseOptions};
+
+    fn lit(text: &str) -> LogSegment {
+        LogSegment::Literal(text.to_string())
+    }
+
+    fn var(name: &str) -> LogSegment {
+        LogSegment::Variable(name.to_string())
+    }
+
+    #[test]
+    fn test_log_formats() {
+        let source = r#"http {
+    log_format main '$remote_addr - $remote_user [$time_local] '
+                    '"$request" $status $body_bytes_sent '
+                    '"$http_referer" "$http_user_agent"';
+    log_format json escape=json '{"host":"${host}","upstream":"$upstream_addr",'
+                                '"route":"$route"}';
+    map $uri $route {
+        default main;
+    }
+}
+"#;
+        let payload = parse_source(source, &ParseOptions::default());
+        let formats = payload.log_formats();
+        assert_eq!(formats.len(), 2);
+
+        assert_eq!(formats[0].name, "main");
+        assert_eq!(formats[0].escape, None);
+        assert_eq!(
+            formats[0].template,
+            [
+                var("remote_addr"),
+                lit(" - "),
+                var("remote_user"),
+                lit(" ["),
+                var("time_local"),
+                lit("] \""),
+                var("request"),
+                lit("\" "),
+                var("status"),
+                lit(" "),
+                var("body_bytes_sent"),
+                lit(" \""),
+                var("http_referer"),
+                lit("\" \""),
+                var("http_user_agent"),
+                lit("\""),
+            ]
+        );
+
+        assert_eq!(formats[1].name, "json");
+        assert_eq!(formats[1].escape.as_deref(), Some("json"));
+        assert_eq!(
+            (formats[1].file.as_str(), formats[1].line),
+            ("nginx.conf", 5)
+        );
+        assert_eq!(
+            formats[1].template,
+            [
+                lit("{\"host\":\""),
+                var("host"),
+                lit("\",\"upstream\":\""),
+                var("upstream_addr"),
+                lit("\",\"route\":\""),
+                var("route"),
+                lit("\"}"),
+            ]
+        );
+
+        assert!(payload.check_log_formats().is_empty());
+    }
+
+    #[test]
+    fn test_parse_template() {
+        assert_eq!(parse_template(""), []);
+        assert_eq!(parse_template("$$ $ ${"), [lit("$$ $ ${")]);
+        assert_eq!(
+            parse_template("${a}b$c-d"),
+            [var("a"), lit("b"), var("c"), lit("-d")]
+        );
+    }
+
+    #[test]
+    fn test_check_log_formats() {
+        let source = r#"http {
+    log_format main '$remote_addr [$time_local] "$requets" $status';
+    access_log /var/log/nginx/access.log main buffer=32k;
+    server {
+        access_log /var/log/nginx/a.log;
+        access_log /var/log/nginx/b.log combined;
+        access_log /var/log/nginx/c.log mian;
+        access_log off;
+    }
+}
+"#;
+        let payload = parse_source(source, &ParseOptions::default());
+        let findings: Vec<String> = payload
+            .check_log_formats()
+            .iter()
+            .map(|f| f.to_string())
+            .collect();
+        assert_eq!(
+            findings,
+            [
+                "error: unknown variable \"$requets\" in log format \"main\" \
+                 in nginx.conf:2 [unknown-variable]",
+                "error: unknown log format \"mian\" in nginx.conf:7 [unknown-log-format]",
+            ]
+        );
+    }
+}
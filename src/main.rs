@@ -1,4 +1,198 @@
-mod lex;
-fn main() {
-    println!("Hello, world!");
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
+use rust_crossplane::{
+    about, build_files, export_directive_index, findings_to_sarif, parse, BuildOptions,
+    IndexFormat, LintOptions, ParseOptions, Payload, Severity, TextStyle, GITHUB_ANNOTATION_LIMIT,
+};
+
+#[derive(Parser)]
+#[command(
+    name = "crossplane",
+    version,
+    about = "Utilities for nginx config files"
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Parses a config and reports problems found by the built-in checks
+    Lint {
+        /// the nginx config file
+        filename: PathBuf,
+        /// how findings are printed, github when run in GitHub Actions and
+        /// text otherwise
+        #[arg(long, value_enum)]
+        format: Option<Format>,
+        /// annotations of each level printed with --format github
+        #[arg(long, default_value_t = GITHUB_ANNOTATION_LIMIT)]
+        annotation_limit: usize,
+        /// apply the suggested fixes and rebuild the config files
+        #[arg(long)]
+        fix: bool,
+        /// lint the file alone, without reading the files it includes
+        #[arg(long)]
+        single_file: bool,
+        /// report certificates, keys and log directories that do not exist
+        #[arg(long)]
+        check_paths: bool,
+        /// resolve relative log and root paths against this directory, like
+        /// nginx's -p, instead of the directory of the config
+        #[arg(long)]
+        prefix: Option<PathBuf>,
+    },
+    /// Prints every known directive with its contexts, arguments and defaults
+    DumpDirectives {
+        #[arg(long, value_enum, default_value_t = DumpFormat::Json)]
+        format: DumpFormat,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    Text,
+    /// path:line:col: level: message lines, as GCC writes them
+    Gcc,
+    Sarif,
+    /// GitHub Actions workflow commands, annotating the lines of the files
+    Github,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum DumpFormat {
+    Json,
+    Msgpack,
+}
+
+fn main() -> ExitCode {
+    // --version tells what configs are checked against, -V just the version
+    let long_version: &'static str = about().to_string().leak();
+    let matches = Cli::command().long_version(long_version).get_matches();
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    match cli.command {
+        Command::Lint {
+            filename,
+            format,
+            annotation_limit,
+            fix,
+            single_file,
+            check_paths,
+            prefix,
+        } => {
+            let format = format.unwrap_or_else(|| {
+                if std::env::var("GITHUB_ACTIONS").is_ok_and(|v| v == "true") {
+                    Format::Github
+                } else {
+                    Format::Text
+                }
+            });
+            let mut options = ParseOptions::default()
+                .catch_errors(true)
+                .arg_spans(true)
+                .single(single_file);
+            if let Some(prefix) = prefix {
+                options = options.prefix(prefix);
+            }
+            let lint_options = LintOptions::default().check_paths(check_paths);
+            lint(
+                &filename,
+                options,
+                &lint_options,
+                format,
+                annotation_limit,
+                fix,
+            )
+        }
+        Command::DumpDirectives { format } => dump_directives(format),
+    }
+}
+
+fn lint(
+    filename: &Path,
+    mut options: ParseOptions,
+    lint_options: &LintOptions,
+    format: Format,
+    annotation_limit: usize,
+    fix: bool,
+) -> ExitCode {
+    if fix {
+        // keep the comments of rebuilt files, and never write stubs out
+        options = options.comments(true).stub_known_includes(false);
+    }
+    let mut payload = parse(filename, &options);
+    for error in &payload.errors {
+        eprintln!("{}", error);
+    }
+    if fix && payload.errors.is_empty() {
+        if let Err(e) = apply_fixes(&mut payload) {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    }
+
+    let findings = payload.lint_with(lint_options);
+    match format {
+        Format::Text => {
+            for finding in &findings {
+                println!("{}", finding);
+            }
+        }
+        Format::Gcc => print!("{}", findings.to_text(TextStyle::Gcc)),
+        Format::Sarif => println!("{}", findings_to_sarif(&findings)),
+        Format::Github => print!(
+            "{}",
+            findings.to_github_annotations_limited(annotation_limit)
+        ),
+    }
+
+    let failed = !payload.errors.is_empty() || findings.count_at_least(Severity::Error) > 0;
+    if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+// applies suggestions one at a time, linting again after each as a fix moves
+// the directives after it, and writes the files back when anything changed
+fn apply_fixes(payload: &mut Payload) -> Result<(), Box<dyn std::error::Error>> {
+    let mut remaining = payload.lint().len();
+    let mut fixed = false;
+    while remaining > 0 {
+        remaining -= 1;
+        let findings = payload.lint();
+        let Some(finding) = findings.iter().find(|f| f.suggestion.is_some()) else {
+            break;
+        };
+        let suggestion = finding.suggestion.as_ref().unwrap();
+        payload.apply_suggestion(suggestion)?;
+        eprintln!("fixed: {} ({})", finding, suggestion.summary);
+        fixed = true;
+    }
+    if fixed {
+        build_files(payload, "", &BuildOptions::default())?;
+    }
+    Ok(())
+}
+
+fn dump_directives(format: DumpFormat) -> ExitCode {
+    let mut bytes = match format {
+        DumpFormat::Json => export_directive_index(IndexFormat::Json),
+        DumpFormat::Msgpack => export_directive_index(IndexFormat::MessagePack),
+    };
+    if let DumpFormat::Json = format {
+        bytes.push(b'\n');
+    }
+    match std::io::stdout().write_all(&bytes) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
 }
@@ -0,0 +1,443 @@
+use std::collections::HashMap;
+
+use crate::lint::{Finding, Severity};
+use crate::parse::{Directive, Payload};
+
+// entries of geo blocks that are settings rather than networks, and may
+// be repeated
+const GEO_PARAMETERS: [&str; 5] = ["delete", "include", "proxy", "proxy_recursive", "ranges"];
+
+// an entry of an args-only block and the file it is in, which is another
+// one than the block's for entries pulled in with include
+pub(crate) struct Entry<'a> {
+    pub(crate) directive: &'a Directive,
+    pub(crate) file: &'a str,
+}
+
+// checks of the entries of map, geo and split_clients blocks that nginx
+// warns about or refuses to start with
+pub(crate) fn check_map_blocks(payload: &Payload) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    for d in payload.directives() {
+        let directive = d.directive();
+        let Some(block) = &directive.block else {
+            continue;
+        };
+        let file = d.file_name();
+        let block = entries(payload, block, file, &mut Vec::new());
+        match directive.directive.as_str() {
+            "map" => check_map(&block, &mut findings),
+            "geo" => check_geo(&block, &mut findings),
+            "split_clients" => check_split_clients(&block, &mut findings),
+            _ => {}
+        }
+    }
+    findings
+}
+
+// the entries of a block, with those of included files in place of the
+// include entries
+pub(crate) fn entries<'a>(
+    payload: &'a Payload,
+    block: &'a [Directive],
+    file: &'a str,
+    seen: &mut Vec<usize>,
+) -> Vec<Entry<'a>> {
+    let mut out = Vec::new();
+    for directive in block {
+        if directive.is_comment() || directive.is_raw() {
+            continue;
+        }
+        let Some(files) = &directive.includes else {
+            // combined payloads keep the file of each directive
+            let file = directive.file.as_deref().unwrap_or(file);
+            out.push(Entry { directive, file });
+            continue;
+        };
+        for &i in files {
+            let Some(included) = payload.config.get(i).filter(|_| !seen.contains(&i)) else {
+                continue;
+            };
+            seen.push(i);
+            out.extend(entries(payload, &included.parsed, &included.file, seen));
+            seen.pop();
+        }
+    }
+    out
+}
+
+fn finding(entry: &Entry, kind: &str, severity: Severity, message: String) -> Finding {
+    Finding::new(kind, severity, message, entry.file, entry.directive.line)
+}
+
+fn check_map(entries: &[Entry], findings: &mut Vec<Finding>) {
+    let mut keys: HashMap<String, &Entry> = HashMap::new();
+    let mut default: Option<&Entry> = None;
+    let mut seen_wildcard = false;
+    for entry in entries {
+        let key = entry.directive.directive.as_str();
+        match key {
+            "hostnames" | "volatile" => {
+                if !entry.directive.args.is_empty() {
+                    findings.push(finding(
+                        entry,
+                        "map-parameter",
+                        Severity::Error,
+                        format!("\"{}\" takes no value", key),
+                    ));
+                } else if key == "hostnames" && seen_wildcard {
+                    findings.push(finding(
+                        entry,
+                        "map-parameter",
+                        Severity::Warning,
+                        "\"hostnames\" does not apply to the wildcard keys before it".to_string(),
+                    ));
+                }
+                continue;
+            }
+            "default" => {
+                match default {
+                    Some(first) => findings.push(finding(
+                        entry,
+                        "duplicate-map-default",
+                        Severity::Error,
+                        format!(
+                            "duplicate default map parameter, first set in {}:{}",
+                            first.file, first.directive.line
+                        ),
+                    )),
+                    None => default = Some(entry),
+                }
+                continue;
+            }
+            _ => {}
+        }
+
+        // a leading backslash escapes keys spelled like the parameters,
+        // string keys are matched case-insensitively unlike regexes
+        let key = key.strip_prefix('\\').unwrap_or(key);
+        let key = if key.starts_with('~') {
+            key.to_string()
+        } else {
+            seen_wildcard |= key.starts_with('.') || key.starts_with('*') || key.ends_with('*');
+            key.to_lowercase()
+        };
+        match keys.get(&key) {
+            Some(first) => findings.push(finding(
+                entry,
+                "duplicate-map-key",
+                Severity::Warning,
+                format!(
+                    "duplicate key \"{}\", first mapped in {}:{}",
+                    entry.directive.directive, first.file, first.directive.line
+                ),
+            )),
+            None => {
+                keys.insert(key, entry);
+            }
+        }
+    }
+}
+
+fn check_geo(entries: &[Entry], findings: &mut Vec<Finding>) {
+    let mut networks: HashMap<&str, &Entry> = HashMap::new();
+    for entry in entries {
+        let key = entry.directive.directive.as_str();
+        if GEO_PARAMETERS.contains(&key) {
+            continue;
+        }
+        match networks.get(key) {
+            Some(first) if key == "default" => findings.push(finding(
+                entry,
+                "duplicate-map-default",
+                Severity::Error,
+                format!(
+                    "duplicate default geo parameter, first set in {}:{}",
+                    first.file, first.directive.line
+                ),
+            )),
+            Some(first) => findings.push(finding(
+                entry,
+                "duplicate-map-key",
+                Severity::Warning,
+                format!(
+                    "duplicate network \"{}\", first mapped in {}:{}",
+                    key, first.file, first.directive.line
+                ),
+            )),
+            None => {
+                networks.insert(key, entry);
+            }
+        }
+    }
+}
+
+fn check_split_clients(entries: &[Entry], findings: &mut Vec<Finding>) {
+    // in hundredths of a percent, like nginx
+    let mut total = 0;
+    let mut remainder: Option<&Entry> = None;
+    for entry in entries {
+        let key = entry.directive.directive.as_str();
+        if let Some(rest) = remainder {
+            findings.push(finding(
+                entry,
+                "split-clients-percent",
+                Severity::Warning,
+                format!(
+                    "\"{}\" is never used as \"*\" in {}:{} takes the remaining clients",
+                    key, rest.file, rest.directive.line
+                ),
+            ));
+            continue;
+        }
+        if key == "*" {
+            remainder = Some(entry);
+            continue;
+        }
+        let Some(percent) = parse_percent(key) else {
+            findings.push(finding(
+                entry,
+                "split-clients-percent",
+                Severity::Error,
+                format!("invalid percent value \"{}\"", key),
+            ));
+            continue;
+        };
+        total += percent;
+        if total > 10000 && total - percent <= 10000 {
+            findings.push(finding(
+                entry,
+                "split-clients-percent",
+                Severity::Error,
+                "percent total is greater than 100%".to_string(),
+            ));
+        }
+    }
+}
+
+// a percentage like "12.5%" in hundredths of a percent, with at most two
+// decimals as nginx reads them
+fn parse_percent(value: &str) -> Option<u32> {
+    let number = value.strip_suffix('%')?;
+    let (whole, fraction) = number.split_once('.').unwrap_or((number, ""));
+    let digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+    if whole.is_empty() && fraction.is_empty() || !digits(whole) || !digits(fraction) {
+        return None;
+    }
+    if fraction.len() > 2 || whole.len() > 5 {
+        return None;
+    }
+    let whole: u32 = if whole.is_empty() {
+        0
+    } else {
+        whole.parse().ok()?
+    };
+    let fraction: u32 = format!("{:0<2}", fraction).parse().ok()?;
+    Some(whole * 100 + fraction)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::build::{build_files, build_payload, BuildOptions};
+    use crate::parse::{parse, parse_source, ParseOptions};
+
+    fn messages(payload: &Payload) -> Vec<String> {
+        check_map_blocks(payload)
+            .iter()
+            .map(|f| format!("{}:{} {} [{}]", f.severity, f.line, f.message, f.kind))
+            .collect()
+    }
+
+    #[test]
+    fn test_check_map() {
+        let source = r#"http {
+    map $http_host $site {
+        hostnames;
+        default            main;
+        example.com        example;
+        *.example.org      org;
+        EXAMPLE.com        example2;
+        ~^www\.(?<n>.+)$   $n;
+        ~^WWW\.(?<n>.+)$   $n;
+        \default           literal;
+        default            other;
+    }
+    map $uri $late {
+        .example.net       net;
+        hostnames;
+        volatile on;
+    }
+}
+"#;
+        let payload = parse_source(source, &ParseOptions::default());
+        assert_eq!(payload.errors, []);
+        assert_eq!(
+            messages(&payload),
+            [
+                "warning:7 duplicate key \"EXAMPLE.com\", first mapped in nginx.conf:5 \
+                 [duplicate-map-key]",
+                "error:11 duplicate default map parameter, first set in nginx.conf:4 \
+                 [duplicate-map-default]",
+                "warning:15 \"hostnames\" does not apply to the wildcard keys before it \
+                 [map-parameter]",
+                "error:16 \"volatile\" takes no value [map-parameter]",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_map_include() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("nginx.conf"),
+            "http {\n    map $uri $redirect {\n        default 0;\n        /old /new;\n        \
+             include redirects.map;\n    }\n}\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("redirects.map"),
+            "/a /b;\n/old /newer;\n\"~^/c\" /d;\n",
+        )
+        .unwrap();
+        let payload = parse(dir.path().join("nginx.conf"), &ParseOptions::default());
+        assert_eq!(payload.errors, []);
+        // the included entries are parsed as entries of the map
+        let included = &payload.config[1].parsed;
+        assert_eq!(included[2].directive, "~^/c");
+        assert_eq!(included[2].args, ["/d"]);
+
+        let findings = check_map_blocks(&payload);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].kind, "duplicate-map-key");
+        assert_eq!(findings[0].file, payload.config[1].file);
+        assert_eq!(findings[0].line, 2);
+    }
+
+    #[test]
+    fn test_map_include_fixture() {
+        let options = ParseOptions::default().comments(true);
+        let root = "configs/map-include/nginx.conf";
+        let payload = parse(root, &options);
+        assert_eq!(payload.errors, []);
+        let map = &payload.config[0].parsed[0].block.as_ref().unwrap()[0];
+        let keys = |payload: &Payload, block: &[Directive]| -> Vec<String> {
+            entries(payload, block, "", &mut Vec::new())
+                .iter()
+                .filter(|e| !e.directive.is_comment())
+                .map(|e| e.directive.directive.clone())
+                .collect()
+        };
+        let expected = [
+            "default",
+            "/old-home",
+            "/about-us",
+            "/blog/feed.xml",
+            "/contact us",
+            "~^/docs/v1/(.*)$",
+            "/old-home",
+        ];
+        assert_eq!(keys(&payload, map.block.as_ref().unwrap()), expected);
+
+        // the duplicate is found across the include, in the file it is in,
+        // whether the files are kept apart or combined
+        let duplicate = |payload: &Payload| {
+            let findings = check_map_blocks(payload);
+            assert_eq!(findings.len(), 1);
+            assert_eq!(findings[0].kind, "duplicate-map-key");
+            (findings[0].file.clone(), findings[0].line)
+        };
+        let included = payload.config[1].file.clone();
+        assert!(included.ends_with("redirects.map"));
+        assert_eq!(duplicate(&payload), (included.clone(), 6));
+        let combined = parse(root, &options.clone().combine(true));
+        assert_eq!(combined.config.len(), 1);
+        let map = &combined.config[0].parsed[0].block.as_ref().unwrap()[0];
+        assert_eq!(keys(&combined, map.block.as_ref().unwrap()), expected);
+        assert_eq!(duplicate(&combined), (included, 6));
+
+        // inlined, the entries build in place of the include and read back
+        // as the same map
+        let inline = BuildOptions::default().inline_includes(true);
+        let rebuilt = parse_source(&build_payload(&payload, &inline), &options);
+        assert_eq!(rebuilt.errors, []);
+        let map = &rebuilt.config[0].parsed[0].block.as_ref().unwrap()[0];
+        assert_eq!(keys(&rebuilt, map.block.as_ref().unwrap()), expected);
+
+        // built apart, the included file keeps its entries
+        let dir = tempfile::tempdir().unwrap();
+        build_files(&payload, dir.path(), &BuildOptions::default()).unwrap();
+        let reparsed = parse(dir.path().join(root), &options);
+        assert_eq!(reparsed.errors, []);
+        let included_entries = |payload: &Payload| -> Vec<(String, Vec<String>)> {
+            payload.config[1]
+                .parsed
+                .iter()
+                .map(|d| (d.directive.clone(), d.args.clone()))
+                .collect()
+        };
+        assert_eq!(included_entries(&reparsed), included_entries(&payload));
+        assert_eq!(check_map_blocks(&reparsed).len(), 1);
+    }
+
+    #[test]
+    fn test_check_geo() {
+        let source = "http {
+    geo $remote_addr $geo {
+        ranges;
+        default        0;
+        proxy          10.0.0.1;
+        proxy          10.0.0.2;
+        127.0.0.1-127.0.0.255 1;
+        127.0.0.1-127.0.0.255 2;
+        default        1;
+    }
+}
+";
+        let payload = parse_source(source, &ParseOptions::default());
+        assert_eq!(
+            messages(&payload),
+            [
+                "warning:8 duplicate network \"127.0.0.1-127.0.0.255\", first mapped in \
+                 nginx.conf:7 [duplicate-map-key]",
+                "error:9 duplicate default geo parameter, first set in nginx.conf:4 \
+                 [duplicate-map-default]",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_split_clients() {
+        let source = "http {
+    split_clients \"${remote_addr}AAA\" $variant {
+        0.5%           .one;
+        49.5%          .two;
+        *              \"\";
+        10%            .three;
+    }
+    split_clients $request_id $bucket {
+        60%            a;
+        40.01%         b;
+        5.125%         c;
+        ten%           d;
+    }
+}
+";
+        let payload = parse_source(source, &ParseOptions::default());
+        assert_eq!(
+            messages(&payload),
+            [
+                "warning:6 \"10%\" is never used as \"*\" in nginx.conf:5 takes the remaining \
+                 clients [split-clients-percent]",
+                "error:10 percent total is greater than 100% [split-clients-percent]",
+                "error:11 invalid percent value \"5.125%\" [split-clients-percent]",
+                "error:12 invalid percent value \"ten%\" [split-clients-percent]",
+            ]
+        );
+        assert_eq!(parse_percent("0.5%"), Some(50));
+        assert_eq!(parse_percent("100%"), Some(10000));
+        assert_eq!(parse_percent(".25%"), Some(25));
+        assert_eq!(parse_percent("%"), None);
+        assert_eq!(parse_percent("50"), None);
+    }
+}
@@ -0,0 +1,115 @@
+use std::time::Duration;
+
+use serde::Serialize;
+
+use crate::parse::{Directive, Payload};
+
+/// What parsing one file took, see [`ParseOptions::collect_metrics`].
+///
+/// [`ParseOptions::collect_metrics`]: crate::ParseOptions::collect_metrics
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct FileMetrics {
+    pub file: String,
+    /// Bytes read, or taken from a bundled stub.
+    pub bytes: u64,
+    pub tokens: usize,
+    /// Include directives followed.
+    pub includes: usize,
+    pub lex_time: Duration,
+    /// Time spent assembling directives, without analyzing them.
+    pub parse_time: Duration,
+    /// Time spent checking directives against the analyzer's table, in
+    /// strict mode.
+    pub analyze_time: Duration,
+}
+
+/// What parsing a config took, per file and summed over them.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ParseMetrics {
+    pub files: Vec<FileMetrics>,
+    pub bytes: u64,
+    pub tokens: usize,
+    pub includes: usize,
+    pub lex_time: Duration,
+    pub parse_time: Duration,
+    pub analyze_time: Duration,
+    /// Time from the start of the parse to the payload, reading files
+    /// included.
+    pub wall_time: Duration,
+}
+
+impl ParseMetrics {
+    pub(crate) fn new(files: Vec<FileMetrics>, wall_time: Duration) -> Self {
+        let mut metrics = ParseMetrics {
+            wall_time,
+            ..Default::default()
+        };
+        for file in &files {
+            metrics.bytes += file.bytes;
+            metrics.tokens += file.tokens;
+            metrics.includes += file.includes;
+            metrics.lex_time += file.lex_time;
+            metrics.parse_time += file.parse_time;
+            metrics.analyze_time += file.analyze_time;
+        }
+        metrics.files = files;
+        metrics
+    }
+}
+
+impl Payload {
+    /// What the parse took, when parsed with
+    /// [`collect_metrics`](crate::ParseOptions::collect_metrics).
+    pub fn metrics(&self) -> Option<&ParseMetrics> {
+        self.metrics.as_ref()
+    }
+}
+
+// the include directives of a file that were followed
+pub(crate) fn count_includes(block: &[Directive]) -> usize {
+    block
+        .iter()
+        .map(|d| usize::from(d.includes.is_some()) + d.block.as_deref().map_or(0, count_includes))
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::{parse, ParseOptions};
+
+    #[test]
+    fn test_parse_metrics() {
+        let root = "configs/includes/nginx.conf";
+        assert_eq!(parse(root, &ParseOptions::default()).metrics(), None);
+
+        let options = ParseOptions::default().strict(true).collect_metrics(true);
+        let payload = parse(root, &options);
+        let metrics = payload.metrics().unwrap();
+        let files: Vec<_> = metrics.files.iter().map(|f| f.file.as_str()).collect();
+        let parsed: Vec<_> = payload.config.iter().map(|c| c.file.as_str()).collect();
+        assert_eq!(files, parsed);
+
+        for file in &metrics.files {
+            let len = std::fs::metadata(&file.file).unwrap().len();
+            assert_eq!(file.bytes, len);
+            assert!(file.tokens > 0);
+        }
+        let includes: Vec<_> = metrics.files.iter().map(|f| f.includes).collect();
+        assert_eq!(includes, [1, 1, 0]);
+
+        let sum = |f: fn(&FileMetrics) -> u64| metrics.files.iter().map(f).sum::<u64>();
+        assert_eq!(metrics.bytes, sum(|f| f.bytes));
+        assert_eq!(metrics.tokens as u64, sum(|f| f.tokens as u64));
+        assert_eq!(metrics.includes, 2);
+        let time = |f: fn(&FileMetrics) -> Duration| metrics.files.iter().map(f).sum::<Duration>();
+        assert_eq!(metrics.lex_time, time(|f| f.lex_time));
+        assert_eq!(metrics.parse_time, time(|f| f.parse_time));
+        assert_eq!(metrics.analyze_time, time(|f| f.analyze_time));
+        assert!(metrics.wall_time >= metrics.lex_time + metrics.parse_time + metrics.analyze_time);
+
+        let json = serde_json::to_value(metrics).unwrap();
+        assert_eq!(json["files"][0]["file"], root);
+        assert_eq!(json["includes"], 2);
+    }
+}
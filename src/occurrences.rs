@@ -0,0 +1,274 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::parse::Payload;
+use crate::tree::{DirectiveLocation, DirectiveRef};
+
+/// Where every directive of a payload is, by name and by block context,
+/// built once by [`Payload::index`] for repeated lookups.
+///
+/// The index does not borrow the payload, so it can be kept while the
+/// payload changes; it is then stale and its queries fail with
+/// [`StaleIndex`]. Changes are noticed through [`Payload::generation`],
+/// which counts those made by methods like
+/// [`apply_suggestion`](Payload::apply_suggestion) but not direct changes
+/// to the fields, unless followed by [`Payload::mark_changed`].
+#[derive(Debug, Clone, Default)]
+pub struct OccurrenceIndex {
+    generation: u64,
+    by_name: HashMap<String, Vec<DirectiveLocation>>,
+    // by the names of the enclosing blocks and then the directive name
+    by_context: HashMap<(Vec<String>, String), Vec<DirectiveLocation>>,
+}
+
+/// The payload changed since the index was built.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StaleIndex;
+
+impl fmt::Display for StaleIndex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the payload changed since the index was built")
+    }
+}
+
+impl std::error::Error for StaleIndex {}
+
+impl Payload {
+    /// The directives named `name`, in document order. Walks the whole
+    /// payload; see [`index`](Payload::index) for repeated lookups.
+    pub fn find(&self, name: &str) -> Vec<DirectiveRef<'_>> {
+        self.directives()
+            .filter(|d| d.directive().directive == name)
+            .collect()
+    }
+
+    /// The directives named `name` in a block context, given as the names
+    /// of the enclosing blocks like `["http", "server"]`. The blocks around
+    /// the `include` of a file count as enclosing its directives.
+    pub fn find_in<S: AsRef<str>>(&self, ctx: &[S], name: &str) -> Vec<DirectiveRef<'_>> {
+        let prefixes = include_contexts(self);
+        self.directives()
+            .filter(|d| d.directive().directive == name)
+            .filter(|d| {
+                context_of(d, &prefixes)
+                    .iter()
+                    .map(String::as_str)
+                    .eq(ctx.iter().map(AsRef::as_ref))
+            })
+            .collect()
+    }
+
+    /// Indexes the directives for lookups without walking the payload.
+    pub fn index(&self) -> OccurrenceIndex {
+        let prefixes = include_contexts(self);
+        let mut index = OccurrenceIndex {
+            generation: self.generation,
+            ..Default::default()
+        };
+        for d in self.directives() {
+            let directive = d.directive();
+            if directive.is_comment() {
+                continue;
+            }
+            index
+                .by_name
+                .entry(directive.directive.clone())
+                .or_default()
+                .push(d.location());
+            index
+                .by_context
+                .entry((context_of(&d, &prefixes), directive.directive.clone()))
+                .or_default()
+                .push(d.location());
+        }
+        index
+    }
+}
+
+impl OccurrenceIndex {
+    /// Whether the payload changed since the index was built from it.
+    pub fn is_stale(&self, payload: &Payload) -> bool {
+        payload.generation != self.generation
+    }
+
+    /// Like [`Payload::find`].
+    pub fn find<'a>(
+        &self,
+        payload: &'a Payload,
+        name: &str,
+    ) -> Result<Vec<DirectiveRef<'a>>, StaleIndex> {
+        self.resolve(payload, self.by_name.get(name))
+    }
+
+    /// Like [`Payload::find_in`].
+    pub fn find_in<'a, S: AsRef<str>>(
+        &self,
+        payload: &'a Payload,
+        ctx: &[S],
+        name: &str,
+    ) -> Result<Vec<DirectiveRef<'a>>, StaleIndex> {
+        let key = (
+            ctx.iter().map(|c| c.as_ref().to_string()).collect(),
+            name.to_string(),
+        );
+        self.resolve(payload, self.by_context.get(&key))
+    }
+
+    /// Number of directives named `name`.
+    pub fn count(&self, name: &str) -> usize {
+        self.by_name.get(name).map_or(0, Vec::len)
+    }
+
+    fn resolve<'a>(
+        &self,
+        payload: &'a Payload,
+        locations: Option<&Vec<DirectiveLocation>>,
+    ) -> Result<Vec<DirectiveRef<'a>>, StaleIndex> {
+        if self.is_stale(payload) {
+            return Err(StaleIndex);
+        }
+        locations
+            .into_iter()
+            .flatten()
+            .map(|loc| payload.directive_ref(loc.file, &loc.path).ok_or(StaleIndex))
+            .collect()
+    }
+}
+
+// the enclosing blocks of the include of each file, by file index, for the
+// files included from a block
+fn include_contexts(payload: &Payload) -> HashMap<usize, Vec<String>> {
+    let mut prefixes: HashMap<usize, Vec<String>> = HashMap::new();
+    // included files come after the files including them
+    for d in payload.directives() {
+        let Some(files) = &d.directive().includes else {
+            continue;
+        };
+        let ctx = context_of(&d, &prefixes);
+        for &file in files {
+            prefixes.entry(file).or_insert_with(|| ctx.clone());
+        }
+    }
+    prefixes
+}
+
+fn context_of(d: &DirectiveRef<'_>, prefixes: &HashMap<usize, Vec<String>>) -> Vec<String> {
+    let mut ctx = prefixes.get(&d.file_index()).cloned().unwrap_or_default();
+    let mut blocks: Vec<String> = d
+        .ancestors()
+        .map(|a| a.directive().directive.clone())
+        .collect();
+    blocks.reverse();
+    ctx.extend(blocks);
+    ctx
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lint::{Suggestion, TreeEdit};
+    use crate::parse::{parse, parse_source, ParseOptions};
+
+    #[test]
+    fn test_occurrence_index() {
+        let source = r#"events {
+    worker_connections 1024;
+}
+http {
+    upstream backend {
+        server 127.0.0.1:8080;
+    }
+    server {
+        listen 80;
+        location / {
+            proxy_pass http://backend;
+        }
+    }
+    server {
+        listen 8080;
+        # proxy_pass
+        location /api/ {
+            proxy_pass http://backend/api/;
+        }
+    }
+}
+"#;
+        let payload = parse_source(source, &ParseOptions::default().comments(true));
+        let index = payload.index();
+        let lines = |refs: Vec<DirectiveRef>| -> Vec<usize> {
+            refs.iter().map(|d| d.directive().line).collect()
+        };
+        assert_eq!(lines(index.find(&payload, "proxy_pass").unwrap()), [11, 18]);
+        assert_eq!(
+            index.find(&payload, "proxy_pass"),
+            Ok(payload.find("proxy_pass"))
+        );
+        assert_eq!(index.count("listen"), 2);
+        assert_eq!(index.count("#"), 0);
+        assert_eq!(index.find(&payload, "root"), Ok(vec![]));
+
+        assert_eq!(
+            lines(
+                index
+                    .find_in(&payload, &["http", "upstream"], "server")
+                    .unwrap()
+            ),
+            [6]
+        );
+        assert_eq!(
+            lines(index.find_in(&payload, &["http"], "server").unwrap()),
+            [8, 14]
+        );
+        assert_eq!(
+            index.find_in(&payload, &["http"], "server"),
+            Ok(payload.find_in(&["http"], "server"))
+        );
+
+        // an edit makes the index stale
+        let mut payload = payload;
+        let listen = payload.find("listen")[0].location();
+        payload
+            .apply_suggestion(&Suggestion {
+                summary: "remove listen".to_string(),
+                edits: vec![TreeEdit::Remove(listen)],
+            })
+            .unwrap();
+        assert!(index.is_stale(&payload));
+        assert_eq!(index.find(&payload, "listen"), Err(StaleIndex));
+        assert_eq!(
+            index.find_in(&payload, &["http", "server"], "listen"),
+            Err(StaleIndex)
+        );
+        let index = payload.index();
+        assert_eq!(lines(index.find(&payload, "listen").unwrap()), [15]);
+
+        // so does a change to the fields once marked
+        payload.config[0].parsed.clear();
+        assert!(!index.is_stale(&payload));
+        payload.mark_changed();
+        assert!(index.is_stale(&payload));
+    }
+
+    #[test]
+    fn test_find_in_includes() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("nginx.conf"),
+            "http {\n    include site.conf;\n}\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("site.conf"),
+            "server {\n    listen 80;\n}\n",
+        )
+        .unwrap();
+        let payload = parse(dir.path().join("nginx.conf"), &ParseOptions::default());
+        let index = payload.index();
+        let found = index
+            .find_in(&payload, &["http", "server"], "listen")
+            .unwrap();
+        assert_eq!(found.len(), 1);
+        assert!(found[0].file().file.ends_with("site.conf"));
+        assert_eq!(payload.find_in(&["http", "server"], "listen"), found);
+    }
+}
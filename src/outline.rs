@@ -0,0 +1,331 @@
+use std::fmt;
+
+use crate::parse::{Directive, Payload};
+
+// how many args are shown per directive and how long each may be
+const MAX_ARGS: usize = 2;
+const MAX_ARG_LEN: usize = 32;
+
+/// A compact, indented summary of a payload: one line per directive with its
+/// name, first args and location. The code of lua blocks like
+/// `content_by_lua_block` is shown as `{…N bytes…}`, or `{…}` when its size
+/// is not known.
+pub struct Outline<'a> {
+    payload: &'a Payload,
+    max_depth: usize,
+    comments: bool,
+}
+
+impl Outline<'_> {
+    /// Show comment directives, which are elided by default.
+    pub fn comments(mut self, comments: bool) -> Self {
+        self.comments = comments;
+        self
+    }
+}
+
+impl Payload {
+    /// Outline limited to `max_depth` levels of directives; blocks whose
+    /// children are cut off are marked with `{…}`.
+    pub fn outline(&self, max_depth: usize) -> Outline<'_> {
+        Outline {
+            payload: self,
+            max_depth,
+            comments: false,
+        }
+    }
+}
+
+impl fmt::Display for Outline<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for config in &self.payload.config {
+            writeln!(f, "{} [{}]", config.file, config.status)?;
+            for error in &config.errors {
+                writeln!(f, "  ! {}", error)?;
+            }
+            let writer = OutlineWriter {
+                file: Some(&config.file),
+                max_depth: self.max_depth,
+                comments: self.comments,
+            };
+            writer.write_block(f, &config.parsed, 1)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Payload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.outline(usize::MAX))
+    }
+}
+
+impl fmt::Display for Directive {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let writer = OutlineWriter {
+            file: None,
+            max_depth: usize::MAX,
+            comments: false,
+        };
+        writer.write_directive(f, self, 0)
+    }
+}
+
+struct OutlineWriter<'a> {
+    file: Option<&'a str>,
+    max_depth: usize,
+    comments: bool,
+}
+
+impl OutlineWriter<'_> {
+    fn write_block(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        block: &[Directive],
+        depth: usize,
+    ) -> fmt::Result {
+        for directive in block {
+            if directive.is_comment() && !self.comments {
+                continue;
+            }
+            self.write_directive(f, directive, depth)?;
+        }
+        Ok(())
+    }
+
+    fn write_directive(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        directive: &Directive,
+        depth: usize,
+    ) -> fmt::Result {
+        write!(f, "{:indent$}", "", indent = depth * 2)?;
+
+        if is_lua_block(directive) {
+            write!(f, "{}", directive.directive)?;
+            match lua_code_len(directive) {
+                Some(len) => write!(f, " {{…{} bytes…}}", len)?,
+                None => write!(f, " {{…}}")?,
+            }
+            return match self.file {
+                Some(file) => writeln!(f, " ({}:{})", file, directive.line),
+                None => writeln!(f, " (line {})", directive.line),
+            };
+        }
+
+        if let Some(comment) = &directive.comment {
+            write!(f, "#{}", summarize(comment, false))?;
+        } else {
+            write!(f, "{}", directive.directive)?;
+            for arg in directive.args.iter().take(MAX_ARGS) {
+                write!(f, " {}", summarize(arg, true))?;
+            }
+            if directive.args.len() > MAX_ARGS {
+                write!(f, " …")?;
+            }
+        }
+
+        // the depth of the payload's top level directives is 1
+        let shown_depth = if self.file.is_some() {
+            depth
+        } else {
+            depth + 1
+        };
+        let children = directive.block.as_deref().unwrap_or_default();
+        let collapsed = !children.is_empty() && shown_depth >= self.max_depth;
+        if collapsed {
+            write!(f, " {{…}}")?;
+        }
+
+        match self.file {
+            Some(file) => writeln!(f, " ({}:{})", file, directive.line)?,
+            None => writeln!(f, " (line {})", directive.line)?,
+        }
+
+        if !collapsed {
+            self.write_block(f, children, depth + 1)?;
+        }
+        Ok(())
+    }
+}
+
+// directives holding lua code, like content_by_lua_block, whose code is
+// shown by its size only
+fn is_lua_block(directive: &Directive) -> bool {
+    directive.directive.ends_with("_by_lua_block")
+}
+
+// the size of the code of a lua block: the one arg crossplane's lua
+// extension keeps it as, or the text between the braces when the source of
+// the directive was captured, as this parser reads the code as statements
+fn lua_code_len(directive: &Directive) -> Option<usize> {
+    if directive.block.is_none() {
+        return match &directive.args[..] {
+            [code] => Some(code.len()),
+            _ => None,
+        };
+    }
+    let source = directive.raw_source.as_deref()?;
+    let open = source.find('{')?;
+    let close = source.rfind('}').filter(|&close| close > open)?;
+    Some(close - open - 1)
+}
+
+// escape control characters, quote args that would not read as one word and
+// truncate long values
+fn summarize(value: &str, quote: bool) -> String {
+    let needs_quotes = quote
+        && (value.is_empty()
+            || value
+                .chars()
+                .any(|c| c.is_whitespace() || c == '"' || c == '\''));
+
+    let mut out = String::new();
+    for (i, c) in value.chars().enumerate() {
+        if i == MAX_ARG_LEN {
+            out.push('…');
+            break;
+        }
+        match c {
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '"' if needs_quotes => out.push_str("\\\""),
+            c if c.is_control() => out.push_str(&c.escape_default().to_string()),
+            c => out.push(c),
+        }
+    }
+
+    if needs_quotes {
+        format!("\"{}\"", out)
+    } else {
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parse::{parse, parse_source, Directive, ParseOptions};
+
+    #[test]
+    fn test_outline() {
+        let payload = parse("configs/simple/nginx.conf", &ParseOptions::default());
+        assert_eq!(
+            payload.to_string(),
+            "configs/simple/nginx.conf [ok]
+  events (configs/simple/nginx.conf:1)
+    worker_connections 1024 (configs/simple/nginx.conf:2)
+  http (configs/simple/nginx.conf:5)
+    server (configs/simple/nginx.conf:6)
+      listen 127.0.0.1:8080 (configs/simple/nginx.conf:7)
+      server_name default_server (configs/simple/nginx.conf:8)
+      location / (configs/simple/nginx.conf:9)
+        return 200 \"foo bar baz\" (configs/simple/nginx.conf:10)
+"
+        );
+        assert_eq!(
+            payload.outline(2).to_string(),
+            "configs/simple/nginx.conf [ok]
+  events (configs/simple/nginx.conf:1)
+    worker_connections 1024 (configs/simple/nginx.conf:2)
+  http (configs/simple/nginx.conf:5)
+    server {…} (configs/simple/nginx.conf:6)
+"
+        );
+
+        let location = &payload.config[0].parsed[1].block.as_ref().unwrap()[0]
+            .block
+            .as_ref()
+            .unwrap()[2];
+        assert_eq!(
+            location.to_string(),
+            "location / (line 9)\n  return 200 \"foo bar baz\" (line 10)\n"
+        );
+    }
+
+    #[test]
+    fn test_outline_lua_blocks() {
+        let source = "location / {\n    access_by_lua_block { ngx.exit(403) }\n    \
+                      content_by_lua_block {\n        ngx.say(\"hi\")\n    }\n}\n";
+        let options = ParseOptions::default()
+            .raw_fallback(true)
+            .capture_raw_for(&["content_by_lua_block"]);
+        let payload = parse_source(source, &options);
+        assert_eq!(
+            payload.to_string(),
+            "nginx.conf [ok]
+  location / (nginx.conf:1)
+    access_by_lua_block {…} (nginx.conf:2)
+    content_by_lua_block {…27 bytes…} (nginx.conf:3)
+"
+        );
+
+        // as crossplane's lua extension keeps the code
+        let mut directive = Directive::new("content_by_lua_block", 3);
+        directive.args = vec![" ngx.say(\"hi\") ".to_string()];
+        assert_eq!(
+            directive.to_string(),
+            "content_by_lua_block {…15 bytes…} (line 3)\n"
+        );
+    }
+
+    #[test]
+    fn test_outline_comments_and_long_args() {
+        let options = ParseOptions::default().comments(true);
+        let payload = parse("configs/with-comments/nginx.conf", &options);
+        assert_eq!(
+            payload.outline(2).to_string(),
+            "configs/with-comments/nginx.conf [ok]
+  events (configs/with-comments/nginx.conf:1)
+    worker_connections 1024 (configs/with-comments/nginx.conf:2)
+  http (configs/with-comments/nginx.conf:5)
+    server {…} (configs/with-comments/nginx.conf:6)
+"
+        );
+        assert_eq!(
+            payload.outline(usize::MAX).comments(true).to_string(),
+            "configs/with-comments/nginx.conf [ok]
+  events (configs/with-comments/nginx.conf:1)
+    worker_connections 1024 (configs/with-comments/nginx.conf:2)
+  #comment (configs/with-comments/nginx.conf:4)
+  http (configs/with-comments/nginx.conf:5)
+    server (configs/with-comments/nginx.conf:6)
+      listen 127.0.0.1:8080 (configs/with-comments/nginx.conf:7)
+      #listen (configs/with-comments/nginx.conf:7)
+      server_name default_server (configs/with-comments/nginx.conf:8)
+      location / (configs/with-comments/nginx.conf:9)
+        ## this is brace (configs/with-comments/nginx.conf:9)
+        # location / (configs/with-comments/nginx.conf:10)
+        return 200 \"foo bar baz\" (configs/with-comments/nginx.conf:11)
+"
+        );
+
+        let payload = parse(
+            "configs/quoted-right-brace/nginx.conf",
+            &ParseOptions::default(),
+        );
+        assert_eq!(
+            payload.outline(usize::MAX).to_string(),
+            "configs/quoted-right-brace/nginx.conf [ok]
+  events (configs/quoted-right-brace/nginx.conf:1)
+  http (configs/quoted-right-brace/nginx.conf:2)
+    log_format main escape=json … (configs/quoted-right-brace/nginx.conf:3)
+"
+        );
+
+        let mut directive = payload.config[0].parsed[1].block.as_ref().unwrap()[0].clone();
+        directive.args.drain(..2);
+        assert_eq!(
+            directive.to_string(),
+            "log_format \"{ \\\"@timestamp\\\": \\\"$time_iso8601\\\",…\" \
+             \"\\\"server_name\\\": \\\"$server_name\\\", \" … (line 3)\n"
+        );
+
+        let mut directive = Directive::new("return", 7);
+        directive.args = vec!["200".to_string(), "line one\nline two".to_string()];
+        assert_eq!(
+            directive.to_string(),
+            "return 200 \"line one\\nline two\" (line 7)\n"
+        );
+    }
+}
@@ -0,0 +1,3425 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt;
+use std::io::Read;
+use std::iter::Peekable;
+use std::ops::{Deref, Range};
+use std::path::{Path, PathBuf};
+#[cfg(feature = "regex")]
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::analyze::{analyze_block, analyze_entry, analyze_stmt, Analyzer};
+use crate::cancel::CancelToken;
+use crate::error::{ParseError, ParseErrorKind};
+use crate::lex::{lex_tokens, unexpected_close, LexOptions, NgxToken};
+use crate::loader::{ConfigLoader, FsLoader, ReaderLoader};
+use crate::metrics::{count_includes, FileMetrics, ParseMetrics};
+#[cfg(feature = "regex")]
+use crate::regex_cache::RegexCache;
+use crate::span::Span;
+
+// the name of configs read by parse_reader
+const READER_FILE: &str = "-";
+
+// blocks whose contents are key/value entries rather than directives
+pub(crate) const ARGS_ONLY_BLOCKS: [&str; 5] =
+    ["charset_map", "geo", "map", "split_clients", "types"];
+
+// args-only blocks whose entries can pull in files of entries with include
+const INCLUDING_ARGS_ONLY_BLOCKS: [&str; 3] = ["geo", "map", "types"];
+
+// deeper blocks are rejected instead of growing the stack with the input
+pub(crate) const MAX_BLOCK_DEPTH: usize = 128;
+
+/// Name of the directives holding statements kept as raw text.
+pub const RAW_DIRECTIVE: &str = "<raw>";
+
+/// A directive of a config. Its name, args and comment are `String`s, or
+/// `Cow`s borrowing from the source in a
+/// [`BorrowedDirective`](crate::BorrowedDirective).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Directive<S = String> {
+    pub directive: S,
+    pub line: usize,
+    pub args: Vec<S>,
+    /// For `include` directives, the indexes in the payload's `config` of
+    /// the files pulled in, as crossplane lists them.
+    pub includes: Option<Vec<usize>>,
+    pub block: Option<Vec<Directive<S>>>,
+    pub comment: Option<S>,
+    /// Line of the `}` closing the block, `None` for simple directives and
+    /// blocks left unclosed.
+    pub block_end_line: Option<usize>,
+    /// Whether the directive is inside an `if` block, directly or nested.
+    pub in_if: bool,
+    /// Source text of a statement the parser could not make sense of, for
+    /// `<raw>` directives kept by `ParseOptions::raw_fallback`.
+    pub raw: Option<String>,
+    /// Source text of the directive as written, from its name to the `;`
+    /// or closing `}` and any comment after it on the same line, for the
+    /// directives listed in `ParseOptions::capture_raw_for`.
+    pub raw_source: Option<String>,
+    /// Whether the name or an arg holds a template placeholder, see
+    /// `LexOptions::template_syntax`. Template statements on lines of their
+    /// own, like `{% endfor %}`, are `<raw>` directives with this set.
+    pub placeholder: bool,
+    /// Where each arg was written, quotes included, when parsed with
+    /// `ParseOptions::arg_spans`; empty otherwise.
+    pub arg_spans: Vec<Span>,
+    /// The file the directive was read from, in payloads parsed with
+    /// `ParseOptions::combine`; `None` otherwise.
+    pub file: Option<String>,
+}
+
+impl Directive {
+    pub fn new(directive: impl Into<String>, line: usize) -> Self {
+        Directive {
+            directive: directive.into(),
+            line,
+            ..Default::default()
+        }
+    }
+
+    pub fn new_comment(comment: impl Into<String>, line: usize) -> Self {
+        Directive {
+            directive: "#".to_string(),
+            line,
+            comment: Some(comment.into()),
+            ..Default::default()
+        }
+    }
+
+    pub fn new_raw(raw: impl Into<String>, line: usize) -> Self {
+        Directive {
+            directive: RAW_DIRECTIVE.to_string(),
+            line,
+            raw: Some(raw.into()),
+            ..Default::default()
+        }
+    }
+
+    pub fn is_raw(&self) -> bool {
+        self.raw.is_some()
+    }
+
+    pub fn is_comment(&self) -> bool {
+        self.directive == "#" && self.comment.is_some()
+    }
+
+    pub fn is_block(&self) -> bool {
+        self.block.is_some()
+    }
+}
+
+// the strings the parser makes directives of: owned, or borrowed from the
+// source as far as the lexer could
+pub(crate) trait TreeStr:
+    Deref<Target = str> + AsRef<str> + Clone + Default + fmt::Display
+{
+    fn from_static(s: &'static str) -> Self;
+
+    // a part of the string, still borrowed if it was
+    fn part(self, part: impl FnOnce(&str) -> &str) -> Self;
+}
+
+impl TreeStr for String {
+    fn from_static(s: &'static str) -> Self {
+        s.to_string()
+    }
+
+    fn part(self, part: impl FnOnce(&str) -> &str) -> Self {
+        part(&self).to_string()
+    }
+}
+
+// Directive::new, new_comment and new_raw of the parser, whatever strings
+// the directive holds
+fn named<S: TreeStr>(directive: S, line: usize) -> Directive<S> {
+    Directive {
+        directive,
+        line,
+        ..Default::default()
+    }
+}
+
+// the comment of a token starting with "#"
+fn comment_token<S: TreeStr>(token: S, line: usize) -> Directive<S> {
+    Directive {
+        comment: Some(token.part(|t| &t[1..])),
+        ..named(S::from_static("#"), line)
+    }
+}
+
+fn raw_text<S: TreeStr>(raw: impl Into<String>, line: usize) -> Directive<S> {
+    Directive {
+        raw: Some(raw.into()),
+        ..named(S::from_static(RAW_DIRECTIVE), line)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Ok,
+    Failed,
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Status::Ok => write!(f, "ok"),
+            Status::Failed => write!(f, "failed"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigFile<S = String> {
+    pub file: String,
+    pub status: Status,
+    pub errors: Vec<ParseError>,
+    pub parsed: Vec<Directive<S>>,
+    /// Parsed from the copy of a well-known distribution file bundled with
+    /// the crate because the include target could not be opened.
+    pub stubbed: bool,
+    pub(crate) shebang: Option<String>,
+}
+
+impl<S> ConfigFile<S> {
+    pub fn new(file: impl Into<String>) -> Self {
+        ConfigFile {
+            file: file.into(),
+            status: Status::Ok,
+            errors: Vec::new(),
+            parsed: Vec::new(),
+            stubbed: false,
+            shebang: None,
+        }
+    }
+
+    /// The marker line of templating systems, like `#!jinja2` or
+    /// `#%PRAGMA`, when the file starts with one. It is kept whether
+    /// comments are or not, and built back as the first line of the file.
+    pub fn shebang(&self) -> Option<&str> {
+        self.shebang.as_deref()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Payload {
+    pub status: Status,
+    pub errors: Vec<ParseError>,
+    pub config: Vec<ConfigFile>,
+    // bumped by the methods changing the tree, see `generation`
+    pub(crate) generation: u64,
+    // set by `ParseOptions::prefix`, see `Payload::prefix`
+    pub(crate) prefix: Option<PathBuf>,
+    pub(crate) metrics: Option<ParseMetrics>,
+    pub(crate) profile: ParseProfile,
+}
+
+/// The options a payload was parsed with that decide how much of the
+/// config it holds, see [`Payload::parse_profile`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParseProfile {
+    pub comments: bool,
+    pub single: bool,
+    /// The directives left out with [`ParseOptions::ignore`].
+    pub ignored: Vec<String>,
+    /// Whether the payload was read from JSON written with the `_lossy`
+    /// hint, the options it was parsed with being unknown.
+    pub marked_lossy: bool,
+}
+
+impl ParseProfile {
+    /// Whether the options left directives of the config out of the
+    /// payload, which building it would drop. Files failing to parse are
+    /// counted by [`Payload::is_lossy`].
+    pub fn drops_directives(&self) -> bool {
+        !self.ignored.is_empty() || self.marked_lossy
+    }
+}
+
+// payloads are compared by content, however often they were changed
+impl PartialEq for Payload {
+    fn eq(&self, other: &Self) -> bool {
+        self.status == other.status && self.errors == other.errors && self.config == other.config
+    }
+}
+
+impl Payload {
+    /// Builds a payload from parsed files, deriving the statuses and the
+    /// aggregated error list from the per-file errors.
+    pub fn from_config(mut config: Vec<ConfigFile>) -> Self {
+        let mut errors = Vec::new();
+        for file in &mut config {
+            if file.errors.is_empty() {
+                file.status = Status::Ok;
+            } else {
+                file.status = Status::Failed;
+                errors.extend(file.errors.iter().cloned());
+            }
+        }
+
+        Payload {
+            status: if errors.is_empty() {
+                Status::Ok
+            } else {
+                Status::Failed
+            },
+            errors,
+            config,
+            generation: 0,
+            prefix: None,
+            metrics: None,
+            profile: ParseProfile::default(),
+        }
+    }
+
+    /// The options the payload was parsed with, as far as they decide what
+    /// of the config it holds. Payloads made otherwise than by parsing
+    /// have the default profile.
+    pub fn parse_profile(&self) -> &ParseProfile {
+        &self.profile
+    }
+
+    /// Whether building the payload would drop some of the config: it was
+    /// parsed ignoring directives, or some of its files failed to parse.
+    /// Comments left out and includes not followed are not counted, as
+    /// building keeps the config working without them.
+    pub fn is_lossy(&self) -> bool {
+        self.profile.drops_directives() || self.config.iter().any(|c| c.status == Status::Failed)
+    }
+
+    /// A counter of the changes made through the methods editing the tree,
+    /// like [`apply_suggestion`](Payload::apply_suggestion), for telling
+    /// whether an [`OccurrenceIndex`](crate::OccurrenceIndex) is stale.
+    /// Changes made to the fields directly are not counted unless followed
+    /// by [`mark_changed`](Payload::mark_changed).
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Records a change made to the tree through the fields.
+    pub fn mark_changed(&mut self) {
+        self.generation += 1;
+    }
+}
+
+/// What to do with a directive followed by `{` that is not a block
+/// directive of [`ParseOptions::analyzer`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UnknownBlockPolicy {
+    /// Report the directive as an unknown block directive.
+    Error,
+    /// Parse the braces as the directive's block.
+    #[default]
+    TreatAsBlock,
+    /// Keep the directive without a block and report the `{` as unexpected.
+    TreatAsSimple,
+}
+
+#[derive(Debug, Clone)]
+pub struct ParseOptions {
+    pub(crate) catch_errors: bool,
+    pub(crate) comments: bool,
+    pub(crate) unknown_block_policy: UnknownBlockPolicy,
+    pub(crate) stub_known_includes: bool,
+    pub(crate) single: bool,
+    pub(crate) combine: bool,
+    pub(crate) prefix: Option<PathBuf>,
+    pub(crate) max_total_bytes: Option<u64>,
+    pub(crate) strict: bool,
+    pub(crate) check_ctx: bool,
+    pub(crate) check_args: bool,
+    pub(crate) analyzer: Analyzer,
+    pub(crate) suggest_directives: bool,
+    pub(crate) raw_fallback: bool,
+    pub(crate) capture_raw_for: Vec<String>,
+    pub(crate) ignore: Vec<String>,
+    pub(crate) arg_spans: bool,
+    pub(crate) cross_file_braces: bool,
+    pub(crate) lex_options: LexOptions,
+    pub(crate) cancel_token: Option<CancelToken>,
+    pub(crate) collect_metrics: bool,
+    #[cfg(feature = "regex")]
+    pub(crate) regex_cache: Arc<RegexCache>,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions {
+            catch_errors: false,
+            comments: false,
+            unknown_block_policy: UnknownBlockPolicy::default(),
+            stub_known_includes: true,
+            single: false,
+            combine: false,
+            prefix: None,
+            max_total_bytes: None,
+            strict: false,
+            check_ctx: false,
+            check_args: false,
+            analyzer: Analyzer::default(),
+            suggest_directives: true,
+            raw_fallback: false,
+            capture_raw_for: Vec::new(),
+            ignore: Vec::new(),
+            arg_spans: false,
+            cross_file_braces: false,
+            lex_options: LexOptions::default(),
+            cancel_token: None,
+            collect_metrics: false,
+            #[cfg(feature = "regex")]
+            regex_cache: Arc::default(),
+        }
+    }
+}
+
+impl ParseOptions {
+    /// Record errors and keep parsing instead of stopping at the first one.
+    /// A statement in error is skipped up to its `;` or `}`, and a `}`
+    /// closing no block is dropped.
+    pub fn catch_errors(mut self, catch_errors: bool) -> Self {
+        self.catch_errors = catch_errors;
+        self
+    }
+
+    /// Keep comments in the tree as `#` directives.
+    pub fn comments(mut self, comments: bool) -> Self {
+        self.comments = comments;
+        self
+    }
+
+    /// How an unknown directive followed by `{` is parsed.
+    pub fn unknown_block_policy(mut self, policy: UnknownBlockPolicy) -> Self {
+        self.unknown_block_policy = policy;
+        self
+    }
+
+    /// Parse a bundled copy of well-known distribution files like
+    /// `mime.types` or `fastcgi_params` when an include of them cannot be
+    /// opened. Enabled by default.
+    pub fn stub_known_includes(mut self, stub_known_includes: bool) -> Self {
+        self.stub_known_includes = stub_known_includes;
+        self
+    }
+
+    /// Parse only the root file, keeping `include` directives as they are
+    /// without reading the files they name, like crossplane's
+    /// `--single-file`. For linting fragments whose includes are not on the
+    /// machine.
+    pub fn single(mut self, single: bool) -> Self {
+        self.single = single;
+        self
+    }
+
+    /// Give one config entry, named after the root file, with the
+    /// directives of the included files in place of the `include`
+    /// directives, like crossplane's `--combine`. Each directive tells the
+    /// file it comes from. Only [`parse`] and the functions like it
+    /// combine the files.
+    pub fn combine(mut self, combine: bool) -> Self {
+        self.combine = combine;
+        self
+    }
+
+    /// The directory relative paths like logs and `root` are resolved
+    /// against, like nginx's `-p`, see [`Payload::prefix`]. Includes and
+    /// certificates stay relative to the directory of the root file. That
+    /// directory by default.
+    pub fn prefix(mut self, prefix: impl Into<PathBuf>) -> Self {
+        self.prefix = Some(prefix.into());
+        self
+    }
+
+    /// Stop parsing once the config and its includes add up to more than
+    /// `max_total_bytes`, and refuse to read files that are not regular
+    /// files. The payload then keeps the files parsed so far when catching
+    /// errors, and only the error otherwise.
+    pub fn max_total_bytes(mut self, max_total_bytes: u64) -> Self {
+        self.max_total_bytes = Some(max_total_bytes);
+        self
+    }
+
+    /// Report directives that are not in the analyzer's directive table as
+    /// unknown. Names are matched case-sensitively, as nginx does.
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Report directives that the analyzer's directive table does not allow
+    /// where they are, like `worker_connections` outside `events`, without
+    /// the other checks of [`strict`](ParseOptions::strict), which checks
+    /// contexts too. Unknown directives are let through.
+    pub fn check_ctx(mut self, check_ctx: bool) -> Self {
+        self.check_ctx = check_ctx;
+        self
+    }
+
+    /// Report directives whose args nginx would refuse: the wrong number
+    /// of them for the analyzer's directive table, flags other than `on`
+    /// or `off`, block directives without a block and the other way round,
+    /// and the special forms of args like `error_log` levels, without the
+    /// other checks of [`strict`](ParseOptions::strict), which checks args
+    /// too.
+    pub fn check_args(mut self, check_args: bool) -> Self {
+        self.check_args = check_args;
+        self
+    }
+
+    /// The directive table strict parsing checks against and
+    /// [`unknown_block_policy`](ParseOptions::unknown_block_policy) tells
+    /// block directives by, the directives nginx knows by default.
+    pub fn analyzer(mut self, analyzer: Analyzer) -> Self {
+        self.analyzer = analyzer;
+        self
+    }
+
+    /// The cache strict parsing compiles the regexes of locations, ifs,
+    /// rewrites and server names through, reporting those nginx would
+    /// refuse. Clones of the options share one cache by default.
+    #[cfg(feature = "regex")]
+    pub fn regex_cache(mut self, regex_cache: Arc<RegexCache>) -> Self {
+        self.regex_cache = regex_cache;
+        self
+    }
+
+    /// Suggest a close known directive name in unknown directive errors,
+    /// like `did you mean "listen"?` for `Listen`. Enabled by default.
+    pub fn suggest_directives(mut self, suggest_directives: bool) -> Self {
+        self.suggest_directives = suggest_directives;
+        self
+    }
+
+    /// Keep statements that cannot be parsed, like a directive cut short by
+    /// `}` or a block without a name, as `<raw>` directives holding their
+    /// source text instead of reporting them, so that syntax added by newer
+    /// nginx versions does not fail the whole file. Needs the source text,
+    /// so it has no effect on [`parse_tokens`].
+    pub fn raw_fallback(mut self, raw_fallback: bool) -> Self {
+        self.raw_fallback = raw_fallback;
+        self
+    }
+
+    /// Keep the source text of the directives with these names as they were
+    /// written, in [`Directive::raw_source`], like evidence of the access
+    /// rules for audit logs. Needs the source text, so it has no effect on
+    /// [`parse_tokens`].
+    pub fn capture_raw_for(mut self, names: &[&str]) -> Self {
+        self.capture_raw_for = names.iter().map(|name| name.to_string()).collect();
+        self
+    }
+
+    /// Leave the directives with these names out of the payload, with the
+    /// blocks they open, like crossplane's `--ignore`. They are not
+    /// analyzed, nor are the files they include read.
+    pub fn ignore(mut self, names: &[&str]) -> Self {
+        self.ignore = names.iter().map(|name| name.to_string()).collect();
+        self
+    }
+
+    /// Record where the args of every directive were written, in
+    /// [`Directive::arg_spans`], so findings about an arg can point at it.
+    /// Needs the source text, so it has no effect on [`parse_tokens`].
+    pub fn arg_spans(mut self, arg_spans: bool) -> Self {
+        self.arg_spans = arg_spans;
+        self
+    }
+
+    /// Balance braces across the files of a config, as if every include
+    /// was read in its place, instead of within each file as crossplane
+    /// does. A block opened in one file can then be closed in another: each
+    /// file keeps the directives written in it, with blocks it leaves open
+    /// ending at its end and braces closing blocks of other files left out.
+    /// Braces that do not balance once the includes are read are reported
+    /// on the file they are in.
+    pub fn cross_file_braces(mut self, cross_file_braces: bool) -> Self {
+        self.cross_file_braces = cross_file_braces;
+        self
+    }
+
+    /// How the config files are split into tokens.
+    pub fn lex_options(mut self, lex_options: LexOptions) -> Self {
+        self.lex_options = lex_options;
+        self
+    }
+
+    /// Stop once the token is cancelled, checking it before each file, every
+    /// few thousand characters while lexing and before each directive. The
+    /// payload then keeps the files finished so far and the file the parse
+    /// stopped in, without its directives, with a `ParseErrorKind::Cancelled`
+    /// error, whether errors are caught or not.
+    pub fn cancel_token(mut self, cancel_token: CancelToken) -> Self {
+        self.cancel_token = Some(cancel_token);
+        self
+    }
+
+    /// Time the lexing, parsing and analyzing of each file and count what
+    /// is read, for [`Payload::metrics`]. Only [`parse`] and the functions
+    /// like it collect them.
+    pub fn collect_metrics(mut self, collect_metrics: bool) -> Self {
+        self.collect_metrics = collect_metrics;
+        self
+    }
+
+    // the lex options, with the cancel token of the parse. When catching
+    // errors the parser balances the braces, so that a '}' closing nothing
+    // does not drop the rest of the file
+    pub(crate) fn effective_lex_options(&self) -> LexOptions {
+        let mut lex_options = self.lex_options.clone();
+        lex_options.unbalanced = self.catch_errors;
+        if let Some(token) = &self.cancel_token {
+            lex_options.cancel_token = Some(token.clone());
+        }
+        lex_options
+    }
+
+    pub(crate) fn is_cancelled(&self) -> bool {
+        self.cancel_token.as_ref().is_some_and(|t| t.is_cancelled())
+    }
+}
+
+/// Where a token stream is being parsed: the file name used for error
+/// locations, the enclosing block context, and the errors recorded so far
+/// when catching errors.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParseCtx {
+    file: String,
+    context: Vec<String>,
+    errors: Vec<ParseError>,
+    // the braces met, when they are balanced across files
+    braces: Vec<BraceEvent>,
+    // the contexts around `context` of blocks left open by other files,
+    // innermost last, which a '}' of the file can close
+    outer: Vec<Vec<String>>,
+    // the time spent analyzing directives, when collecting metrics
+    analyze_time: Option<Duration>,
+}
+
+// a brace or include of a file, in the order nginx reads them
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum BraceEvent {
+    Open { directive: String, line: usize },
+    Close { line: usize },
+    Include(Vec<usize>),
+}
+
+impl ParseCtx {
+    pub fn new(file: impl Into<String>) -> Self {
+        ParseCtx {
+            file: file.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_context<S: AsRef<str>>(mut self, context: &[S]) -> Self {
+        self.context = context.iter().map(|c| c.as_ref().to_string()).collect();
+        self
+    }
+
+    pub fn file(&self) -> &str {
+        &self.file
+    }
+
+    pub fn context(&self) -> &[String] {
+        &self.context
+    }
+
+    pub fn errors(&self) -> &[ParseError] {
+        &self.errors
+    }
+
+    pub fn into_errors(self) -> Vec<ParseError> {
+        self.errors
+    }
+}
+
+/// Parses the config at `path` and the files it includes; every file gets
+/// its own entry in the payload's `config`, the root file first.
+///
+/// Relative include paths resolve against the directory of `path`, as nginx
+/// resolves them against its prefix. An include of a file that does not
+/// exist is an error of the including file, like "No such file or
+/// directory", and the other files are still parsed when catching errors.
+pub fn parse<P: AsRef<Path>>(path: P, options: &ParseOptions) -> Payload {
+    parse_with_loader(path, options, &FsLoader)
+}
+
+/// Like [`parse`], reading the config and the files it includes through
+/// `loader`.
+pub fn parse_with_loader<P: AsRef<Path>>(
+    path: P,
+    options: &ParseOptions,
+    loader: &dyn ConfigLoader,
+) -> Payload {
+    let started = options.collect_metrics.then(Instant::now);
+    let path = path.as_ref();
+    let config_dir = path.parent().unwrap_or(Path::new("")).to_path_buf();
+    let mut includes = Includes::new(config_dir, loader, options);
+    includes.add(path.to_path_buf(), &[], None);
+
+    let mut config = Vec::new();
+    let mut payload = match load_files(&mut config, &mut includes, options) {
+        Some(ParseErrorKind::InputTooLarge) => abort_parse(config, options),
+        Some(_) => partial_payload(config),
+        None => {
+            if options.cross_file_braces {
+                check_braces(&mut config, &mut includes, options);
+            }
+            Payload::from_config(config)
+        }
+    };
+    if options.combine {
+        payload = combine_files(payload);
+    }
+    payload.prefix = options.prefix.clone();
+    payload.profile = ParseProfile {
+        comments: options.comments,
+        single: options.single,
+        ignored: options.ignore.clone(),
+        marked_lossy: false,
+    };
+    if let (Some(started), Some(files)) = (started, includes.metrics) {
+        payload.metrics = Some(ParseMetrics::new(files, started.elapsed()));
+    }
+    payload
+}
+
+// the payload with the files spliced into the root one, in place of the
+// include directives pulling them in
+fn combine_files(payload: Payload) -> Payload {
+    // `active` holds the files being spliced, so that a file including
+    // itself is left out the second time
+    fn splice(
+        config: &[ConfigFile],
+        file: usize,
+        block: &[Directive],
+        active: &mut Vec<usize>,
+    ) -> Vec<Directive> {
+        let mut spliced = Vec::new();
+        for stmt in block {
+            if let Some(indexes) = &stmt.includes {
+                for &i in indexes {
+                    if !active.contains(&i) {
+                        active.push(i);
+                        spliced.extend(splice(config, i, &config[i].parsed, active));
+                        active.pop();
+                    }
+                }
+                continue;
+            }
+            spliced.push(Directive {
+                file: Some(config[file].file.clone()),
+                block: stmt
+                    .block
+                    .as_ref()
+                    .map(|block| splice(config, file, block, active)),
+                ..stmt.clone()
+            });
+        }
+        spliced
+    }
+
+    let Some(root) = payload.config.first() else {
+        return payload;
+    };
+    let mut combined = ConfigFile {
+        shebang: root.shebang.clone(),
+        stubbed: root.stubbed,
+        ..ConfigFile::new(&root.file)
+    };
+    for config in &payload.config {
+        combined.errors.extend(config.errors.iter().cloned());
+    }
+    if !combined.errors.is_empty() {
+        combined.status = Status::Failed;
+    }
+    combined.parsed = splice(&payload.config, 0, &root.parsed, &mut vec![0]);
+    Payload {
+        config: vec![combined],
+        ..payload
+    }
+}
+
+/// Like [`parse`], for a config read from `reader`. The config is named
+/// `-`, and the files it includes are read relative to the working
+/// directory.
+pub fn parse_reader<R: Read>(mut reader: R, options: &ParseOptions) -> Payload {
+    let mut source = Vec::new();
+    let loader = ReaderLoader {
+        path: PathBuf::from(READER_FILE),
+        source: reader.read_to_end(&mut source).map(|_| source),
+    };
+    parse_with_loader(READER_FILE, options, &loader)
+}
+
+// check that the braces of the files balance with the includes read in
+// their place, reporting those that do not on the file they are in; files
+// read inside blocks other files leave open are parsed again in the context
+// of those blocks
+fn check_braces(config: &mut [ConfigFile], includes: &mut Includes, options: &ParseOptions) {
+    struct Walk<'b> {
+        braces: &'b HashMap<usize, Vec<BraceEvent>>,
+        names: &'b [String],
+        // the file and line of the '}' that closed the last top level block
+        closed_at: Option<(usize, usize)>,
+        // the blocks open, as the file, directive and line opening them
+        open: Vec<(usize, &'b str, usize)>,
+        // the contexts of the blocks open, the root one first
+        contexts: Vec<Vec<String>>,
+        // the contexts each file is first read in
+        entered: HashMap<usize, Vec<Vec<String>>>,
+        // the files being read, to stop at include loops
+        reading: Vec<usize>,
+        errors: Vec<(usize, ParseError)>,
+    }
+
+    impl<'b> Walk<'b> {
+        fn file(&mut self, file: usize) {
+            if self.reading.contains(&file) {
+                return;
+            }
+            self.reading.push(file);
+            for event in self.braces.get(&file).into_iter().flatten() {
+                match event {
+                    BraceEvent::Open { directive, line } => {
+                        self.open.push((file, directive, *line));
+                        let inner =
+                            enter_block_ctx(directive, &self.contexts[self.contexts.len() - 1]);
+                        self.contexts.push(inner);
+                    }
+                    BraceEvent::Close { line } => {
+                        if self.open.pop().is_none() {
+                            let what = match self.closed_at {
+                                Some((closed, at)) if closed != file => format!(
+                                    "{} of {})",
+                                    unexpected_close(Some(at)).trim_end_matches(')'),
+                                    self.names[closed]
+                                ),
+                                closed_at => unexpected_close(closed_at.map(|(_, at)| at)),
+                            };
+                            self.errors.push((file, ParseError::new(what, *line)));
+                        } else {
+                            self.contexts.pop();
+                            if self.open.is_empty() {
+                                self.closed_at = Some((file, *line));
+                            }
+                        }
+                    }
+                    BraceEvent::Include(files) => {
+                        for &included in files {
+                            if !self.entered.contains_key(&included) {
+                                self.entered.insert(included, self.contexts.clone());
+                            }
+                            self.file(included);
+                        }
+                    }
+                }
+            }
+            self.reading.pop();
+        }
+    }
+
+    fn walk<'b>(braces: &'b HashMap<usize, Vec<BraceEvent>>, names: &'b [String]) -> Walk<'b> {
+        let mut walk = Walk {
+            braces,
+            names,
+            closed_at: None,
+            open: Vec::new(),
+            contexts: vec![Vec::new()],
+            entered: HashMap::new(),
+            reading: Vec::new(),
+            errors: Vec::new(),
+        };
+        walk.file(0);
+        walk
+    }
+
+    // whether the braces of a file close blocks it did not open
+    fn closes_outer(braces: Option<&Vec<BraceEvent>>) -> bool {
+        let mut depth = 0;
+        for event in braces.into_iter().flatten() {
+            match event {
+                BraceEvent::Open { .. } => depth += 1,
+                BraceEvent::Close { .. } if depth == 0 => return true,
+                BraceEvent::Close { .. } => depth -= 1,
+                BraceEvent::Include(_) => {}
+            }
+        }
+        false
+    }
+
+    // the contexts files were parsed in again, files being first parsed in
+    // the context of their include alone; a file parsed again can leave
+    // other blocks open, so this goes on until the contexts settle
+    let names: Vec<String> = config.iter().map(|c| c.file.clone()).collect();
+    let mut parsed_in: Vec<Option<Vec<Vec<String>>>> = vec![None; config.len()];
+    for _ in 0..config.len() {
+        let entered = walk(&includes.braces, &names).entered;
+        let mut moved: Vec<(usize, Vec<Vec<String>>)> = entered
+            .into_iter()
+            .filter(|(file, contexts)| match parsed_in.get(*file) {
+                Some(Some(parsed)) => parsed != contexts,
+                Some(None) => {
+                    contexts.last() != Some(&includes.files[*file].context)
+                        || closes_outer(includes.braces.get(file))
+                }
+                None => false,
+            })
+            .collect();
+        if moved.is_empty() {
+            break;
+        }
+        moved.sort();
+        for (file, contexts) in moved {
+            reparse(config, file, &contexts, includes, options);
+            parsed_in[file] = Some(contexts);
+        }
+    }
+
+    let Walk {
+        open, mut errors, ..
+    } = walk(&includes.braces, &names);
+    for (file, directive, line) in open {
+        let what = format!("\"{}\" block is not closed", directive);
+        errors.push((file, ParseError::new(what, line)));
+    }
+    if !options.catch_errors {
+        errors.truncate(1);
+    }
+    for (file, error) in errors {
+        let error = error.in_file(&config[file].file);
+        config[file].errors.push(error);
+    }
+}
+
+// parses a file again in `contexts`, the innermost last, when braces are
+// balanced across files
+fn reparse(
+    config: &mut [ConfigFile],
+    file: usize,
+    contexts: &[Vec<String>],
+    includes: &mut Includes,
+    options: &ParseOptions,
+) {
+    let include = includes.files[file].clone();
+    let source = match include.stub {
+        Some(stub) => stub.as_bytes().to_vec(),
+        None => match includes.loader.read(&include.path, None) {
+            Ok(source) => source,
+            // the file is left as it was read the first time
+            Err(_) => return,
+        },
+    };
+    let (context, outer) = contexts.split_last().expect("the root context comes first");
+    let mut ctx = ParseCtx::new(&config[file].file).with_context(context);
+    ctx.outer = outer.to_vec();
+    let mut lex_options = options.effective_lex_options();
+    lex_options.unbalanced = true;
+    let tokens = lex_tokens(&source[..], &lex_options);
+    match parse_with(tokens, Some(&source), &mut ctx, options, Some(includes)) {
+        Ok(parsed) => config[file].parsed = parsed,
+        Err(e) => ctx.errors.push(e),
+    }
+    includes
+        .braces
+        .insert(file, std::mem::take(&mut ctx.braces));
+    config[file].errors = ctx.into_errors();
+}
+
+// parse the queued files that `config` has no entry for yet, following their
+// includes, and return the kind of the error stopping it early if any, whose
+// file is the last one pushed
+pub(crate) fn load_files(
+    config: &mut Vec<ConfigFile>,
+    includes: &mut Includes,
+    options: &ParseOptions,
+) -> Option<ParseErrorKind> {
+    let mut lex_options = options.effective_lex_options();
+    lex_options.unbalanced |= options.cross_file_braces;
+    let mut total_bytes = 0;
+    while config.len() < includes.files.len() {
+        let include = includes.files[config.len()].clone();
+        let fname = include.path.display().to_string();
+        let mut file = ConfigFile::new(&fname);
+        file.stubbed = include.stub.is_some();
+
+        if options.is_cancelled() {
+            file.errors
+                .push(ParseError::cancelled(None).in_file(&fname));
+            config.push(file);
+            return Some(ParseErrorKind::Cancelled);
+        }
+
+        let source = match include.stub {
+            Some(stub) => Ok(Cow::Borrowed(stub.as_bytes())),
+            None => {
+                // one byte more than the budget, to detect going over it
+                // without reading everything
+                let limit = options
+                    .max_total_bytes
+                    .map(|max| max.saturating_sub(total_bytes) + 1);
+                includes
+                    .loader
+                    .read(&include.path, limit)
+                    .map(Cow::Owned)
+                    .map_err(|e| e.to_string())
+            }
+        };
+        match source {
+            Ok(source) => {
+                total_bytes += source.len() as u64;
+                if let Some(max) = options.max_total_bytes.filter(|&max| total_bytes > max) {
+                    let what = format!("input size limit of {} bytes exceeded", max);
+                    file.errors.push(ParseError::for_file(
+                        what,
+                        &fname,
+                        ParseErrorKind::InputTooLarge,
+                    ));
+                    config.push(file);
+                    return Some(ParseErrorKind::InputTooLarge);
+                }
+
+                let mut ctx = ParseCtx::new(&fname).with_context(&include.context);
+                let lexing = includes.metrics.is_some().then(Instant::now);
+                let tokens = lex_tokens(&source[..], &lex_options);
+                let parsing = lexing.map(|_| Instant::now());
+                let mut metrics = lexing.map(|lexing| FileMetrics {
+                    file: fname.clone(),
+                    bytes: source.len() as u64,
+                    tokens: tokens.len(),
+                    lex_time: lexing.elapsed(),
+                    ..Default::default()
+                });
+                if metrics.is_some() {
+                    ctx.analyze_time = Some(Duration::ZERO);
+                }
+                file.shebang = shebang(&tokens);
+                let following = Some(&mut *includes).filter(|_| !options.single);
+                match parse_with(tokens, Some(&source), &mut ctx, options, following) {
+                    Ok(parsed) => file.parsed = parsed,
+                    Err(e) => ctx.errors.push(e),
+                }
+                if let (Some(m), Some(parsing)) = (&mut metrics, parsing) {
+                    m.analyze_time = ctx.analyze_time.unwrap_or_default();
+                    m.parse_time = parsing.elapsed().saturating_sub(m.analyze_time);
+                    m.includes = count_includes(&file.parsed);
+                }
+                if let (Some(files), Some(m)) = (&mut includes.metrics, metrics) {
+                    files.push(m);
+                }
+                if options.cross_file_braces {
+                    let braces = std::mem::take(&mut ctx.braces);
+                    includes.braces.insert(config.len(), braces);
+                }
+                file.errors = ctx.into_errors();
+                if file
+                    .errors
+                    .iter()
+                    .any(|e| e.kind == ParseErrorKind::Cancelled)
+                {
+                    config.push(file);
+                    return Some(ParseErrorKind::Cancelled);
+                }
+            }
+            Err(e) => file
+                .errors
+                .push(ParseError::for_file(e, &fname, ParseErrorKind::Io)),
+        }
+        config.push(file);
+    }
+    None
+}
+
+// the payload of a parse stopped by the size limit, whose last file has the
+// error: with catch_errors the files parsed so far are kept
+fn abort_parse(mut config: Vec<ConfigFile>, options: &ParseOptions) -> Payload {
+    if !options.catch_errors {
+        // only the error is kept, on the root file's entry
+        let last = config.pop().unwrap();
+        let mut root = ConfigFile::new(config.first().map_or(&last.file, |root| &root.file));
+        root.errors = last.errors;
+        return Payload::from_config(vec![root]);
+    }
+    partial_payload(config)
+}
+
+// the payload of the files parsed so far, minus the includes of files that
+// were not reached
+pub(crate) fn partial_payload(mut config: Vec<ConfigFile>) -> Payload {
+    fn drop_unparsed(block: &mut [Directive], parsed_files: usize) {
+        for directive in block {
+            if let Some(includes) = &mut directive.includes {
+                includes.retain(|&i| i < parsed_files);
+            }
+            if let Some(children) = &mut directive.block {
+                drop_unparsed(children, parsed_files);
+            }
+        }
+    }
+    let parsed_files = config.len();
+    for file in &mut config {
+        drop_unparsed(&mut file.parsed, parsed_files);
+    }
+    Payload::from_config(config)
+}
+
+/// Assembles a token stream into directives.
+///
+/// This is the statement assembly used by [`parse`], without any IO:
+/// `include` directives are kept as plain directives. Errors carried by
+/// error tokens or found while assembling are returned, or recorded in `ctx`
+/// when `catch_errors` is set.
+pub fn parse_tokens<I>(
+    tokens: I,
+    ctx: &mut ParseCtx,
+    options: &ParseOptions,
+) -> Result<Vec<Directive>, ParseError>
+where
+    I: IntoIterator<Item = NgxToken>,
+{
+    parse_with(tokens, None, ctx, options, None)
+}
+
+// parse_tokens, with the source the tokens come from for raw_fallback, and
+// queueing the files pulled in by include directives when following them
+fn parse_with<S, I>(
+    tokens: I,
+    source: Option<&[u8]>,
+    ctx: &mut ParseCtx,
+    options: &ParseOptions,
+    includes: Option<&mut Includes<'_>>,
+) -> Result<Vec<Directive<S>>, ParseError>
+where
+    S: TreeStr,
+    I: IntoIterator<Item = NgxToken<S>>,
+{
+    let context = ctx.context.clone();
+    let defer_braces = options.cross_file_braces && includes.is_some();
+    let mut parser = Parser {
+        tokens: tokens.into_iter().peekable(),
+        source,
+        ctx,
+        options,
+        includes,
+        line: 1,
+        last_end: 0,
+        lex_failed: false,
+        closed_at: None,
+        top_closed_at: None,
+        defer_braces,
+        left_block: false,
+    };
+    let mut context = context;
+    let mut parsed = parser.parse_block(&context, 0)?;
+    // a '}' closing a block of another file goes back to the context around
+    // that block
+    while std::mem::take(&mut parser.left_block) {
+        context = parser.ctx.outer.pop().expect("only set with outer blocks");
+        parsed.extend(parser.parse_block(&context, 0)?);
+    }
+    Ok(parsed)
+}
+
+// a "#!" or "#%" comment first in a file
+fn shebang<S: TreeStr>(tokens: &[NgxToken<S>]) -> Option<String> {
+    tokens
+        .first()
+        .filter(|t| t.line == 1 && !t.is_quoted)
+        .filter(|t| t.value.starts_with("#!") || t.value.starts_with("#%"))
+        .map(|t| t.value.to_string())
+}
+
+// well-known files of the nginx distribution, bundled for stub_known_includes
+const KNOWN_INCLUDES: [(&str, &str); 7] = [
+    ("fastcgi.conf", include_str!("stubs/fastcgi.conf")),
+    ("fastcgi_params", include_str!("stubs/fastcgi_params")),
+    ("koi-utf", include_str!("stubs/koi-utf")),
+    ("mime.types", include_str!("stubs/mime.types")),
+    ("scgi_params", include_str!("stubs/scgi_params")),
+    ("uwsgi_params", include_str!("stubs/uwsgi_params")),
+    ("win-utf", include_str!("stubs/win-utf")),
+];
+
+#[derive(Debug, Clone)]
+pub(crate) struct Include {
+    pub(crate) path: PathBuf,
+    context: Vec<String>,
+    stub: Option<&'static str>,
+}
+
+pub(crate) const INCLUDE_VARIABLES: &str =
+    "include argument contains variables which nginx does not support";
+
+// nginx reads include args as they are, so a "$" starting a variable name is
+// taken literally where the config meant it to be expanded; "\$" is a
+// literal dollar in any case
+pub(crate) fn has_variables(arg: &str) -> bool {
+    let bytes = arg.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'$' if bytes
+                .get(i + 1)
+                .is_some_and(|&b| b == b'{' || b == b'_' || b.is_ascii_alphanumeric()) =>
+            {
+                return true
+            }
+            _ => i += 1,
+        }
+    }
+    false
+}
+
+// the files to parse, in payload order, and where each one is in that list
+pub(crate) struct Includes<'l> {
+    config_dir: PathBuf,
+    pub(crate) files: Vec<Include>,
+    indexes: HashMap<PathBuf, usize>,
+    // the braces of each file parsed, when balanced across files
+    pub(crate) braces: HashMap<usize, Vec<BraceEvent>>,
+    // the metrics of each file parsed, when collecting them
+    pub(crate) metrics: Option<Vec<FileMetrics>>,
+    stub_known_includes: bool,
+    loader: &'l dyn ConfigLoader,
+}
+
+impl<'l> Includes<'l> {
+    pub(crate) fn new(
+        config_dir: PathBuf,
+        loader: &'l dyn ConfigLoader,
+        options: &ParseOptions,
+    ) -> Self {
+        Includes {
+            config_dir,
+            files: Vec::new(),
+            indexes: HashMap::new(),
+            braces: HashMap::new(),
+            metrics: options.collect_metrics.then(Vec::new),
+            stub_known_includes: options.stub_known_includes,
+            loader,
+        }
+    }
+
+    pub(crate) fn add(
+        &mut self,
+        path: PathBuf,
+        context: &[String],
+        stub: Option<&'static str>,
+    ) -> usize {
+        if let Some(&index) = self.indexes.get(&path) {
+            return index;
+        }
+        let index = self.files.len();
+        self.indexes.insert(path.clone(), index);
+        self.files.push(Include {
+            path,
+            context: context.to_vec(),
+            stub,
+        });
+        index
+    }
+
+    // queue the files an include argument names, parsed in context `ctx`,
+    // returning their indexes or why the named file cannot be opened
+    pub(crate) fn resolve(&mut self, arg: &str, ctx: &[String]) -> Result<Vec<usize>, String> {
+        if has_variables(arg) {
+            return Err(INCLUDE_VARIABLES.to_string());
+        }
+        if arg.contains(['*', '?', '[']) {
+            // nginx accepts patterns matching nothing, see check_includes
+            let paths = self.loader.glob(&self.config_dir, arg);
+            return Ok(paths.into_iter().map(|p| self.add(p, ctx, None)).collect());
+        }
+
+        // nginx checks that an explicitly named file can be opened
+        let path = self.config_dir.join(arg);
+        let Err(e) = self.loader.check(&path) else {
+            return Ok(vec![self.add(path, ctx, None)]);
+        };
+        let stub = path.file_name().and_then(|name| {
+            KNOWN_INCLUDES
+                .iter()
+                .find(|(known, _)| name == *known)
+                .map(|(_, source)| *source)
+        });
+        match stub {
+            Some(source) if self.stub_known_includes => Ok(vec![self.add(path, ctx, Some(source))]),
+            _ => Err(format!("{}: '{}'", e, path.display())),
+        }
+    }
+}
+
+struct Parser<'a, 'l, I: Iterator> {
+    tokens: Peekable<I>,
+    // set when the source text is known
+    source: Option<&'a [u8]>,
+    ctx: &'a mut ParseCtx,
+    options: &'a ParseOptions,
+    // set when include directives are followed
+    includes: Option<&'a mut Includes<'l>>,
+    line: usize,
+    // end of the last token read
+    last_end: usize,
+    lex_failed: bool,
+    // line of the '}' that closed the block parsed last
+    closed_at: Option<usize>,
+    // line of the '}' that closed the last top level block
+    top_closed_at: Option<usize>,
+    // whether braces are balanced across files, after the parse
+    defer_braces: bool,
+    // set when a '}' closed a block opened by another file
+    left_block: bool,
+}
+
+// what is left to parse_block after a statement is read
+enum Step<S> {
+    // go on with the next token
+    Next,
+    // the block being parsed is closed
+    End,
+    // a directive pushed last to the block, with the block it opens still
+    // to parse
+    Stmt {
+        opens_block: bool,
+        start: usize,
+        comments_in_args: Vec<S>,
+    },
+}
+
+impl<S: TreeStr, I: Iterator<Item = NgxToken<S>>> Parser<'_, '_, I> {
+    fn parse_block(
+        &mut self,
+        ctx: &[String],
+        depth: usize,
+    ) -> Result<Vec<Directive<S>>, ParseError> {
+        let mut parsed = Vec::new();
+
+        while let Some(token) = self.next_token()? {
+            let (opens_block, start, comments_in_args) =
+                match self.parse_stmt(token, ctx, depth, &mut parsed)? {
+                    Step::Next => continue,
+                    Step::End => return Ok(parsed),
+                    Step::Stmt {
+                        opens_block,
+                        start,
+                        comments_in_args,
+                    } => (opens_block, start, comments_in_args),
+                };
+            let stmt = parsed.last_mut().expect("the statement was pushed");
+
+            // if this statement terminated with '{' then it is a block
+            if opens_block {
+                self.brace(BraceEvent::Open {
+                    directive: stmt.directive.to_string(),
+                    line: stmt.line,
+                });
+                let inner = enter_block_ctx(&stmt.directive, ctx);
+                stmt.block = Some(self.parse_block(&inner, depth + 1)?);
+                stmt.block_end_line = self.closed_at.take();
+                if depth == 0 {
+                    self.top_closed_at = stmt.block_end_line;
+                }
+                if let Err(e) = self.analyze(|| analyze_block(stmt, self.options)) {
+                    self.error(e)?;
+                }
+            }
+
+            if self
+                .options
+                .capture_raw_for
+                .iter()
+                .any(|n| *n == *stmt.directive)
+            {
+                stmt.raw_source = self.raw_source(start..self.last_end);
+            }
+            let line = stmt.line;
+
+            // add all comments found inside args after stmt is added
+            if self.options.comments {
+                for comment in comments_in_args {
+                    parsed.push(comment_token(comment, line));
+                }
+            }
+        }
+
+        if depth > 0 && !self.defer_braces {
+            self.eof_error("unexpected end of file, expecting '}'")?;
+        }
+        Ok(parsed)
+    }
+
+    // read a statement from its first token up to the block it opens, if
+    // any, pushing it and what is kept as it is to `parsed`. Kept out of
+    // parse_block, which recurses, so its frames stay small
+    fn parse_stmt(
+        &mut self,
+        token: NgxToken<S>,
+        ctx: &[String],
+        depth: usize,
+        parsed: &mut Vec<Directive<S>>,
+    ) -> Result<Step<S>, ParseError> {
+        if self.options.is_cancelled() {
+            return Err(self.in_file(ParseError::cancelled(Some(token.line))));
+        }
+
+        // we are parsing a block, so break if it's closing
+        if &*token.value == "}" && !token.is_quoted {
+            self.brace(BraceEvent::Close { line: token.line });
+            if depth > 0 {
+                self.closed_at = Some(token.line);
+                return Ok(Step::End);
+            }
+            if self.leaves_block() {
+                return Ok(Step::End);
+            }
+            if !self.defer_braces {
+                let what = unexpected_close(self.top_closed_at);
+                self.error(ParseError::new(what, token.line))?;
+            }
+            return Ok(Step::Next);
+        }
+
+        // if token is comment
+        if token.value.starts_with('#') && !token.is_quoted {
+            if self.options.comments {
+                parsed.push(comment_token(token.value, token.line));
+            }
+            return Ok(Step::Next);
+        }
+
+        // a template statement on a line of its own, like "{% endfor %}"
+        if token.is_placeholder && self.ends_line(&token) {
+            parsed.push(Directive {
+                placeholder: true,
+                in_if: ctx.iter().any(|c| c == "if"),
+                ..raw_text(&*token.value, token.line)
+            });
+            return Ok(Step::Next);
+        }
+
+        // the first token should always be an nginx directive
+        let start = token.span.start;
+        let mut end = token.span.end;
+        let opens_anonymous = !token.is_quoted && matches!(&*token.value, "{" | ";");
+        let mut stmt = named(token.value, token.line);
+        stmt.in_if = ctx.iter().any(|c| c == "if");
+        stmt.placeholder = token.is_placeholder;
+        let mut comments_in_args = Vec::new();
+
+        // a block or a terminator without a directive in front of it
+        if opens_anonymous {
+            if !self.keeps_raw() {
+                let what = format!("unexpected \"{}\"", stmt.directive);
+                self.error(ParseError::new(what, stmt.line))?;
+            }
+            if &*stmt.directive == "{" {
+                self.skip_block()?;
+            }
+            if self.keeps_raw() {
+                parsed.push(self.raw_directive(start..self.last_end, &stmt));
+            }
+            return Ok(Step::Next);
+        }
+
+        // parse arguments by reading tokens
+        let term = loop {
+            let Some(token) = self.next_token()? else {
+                if self.keeps_raw() {
+                    parsed.push(self.raw_directive(start..end, &stmt));
+                } else {
+                    self.eof_error("unexpected end of file, expecting ';' or '}'")?;
+                }
+                return Ok(Step::End);
+            };
+            if !token.is_quoted && matches!(&*token.value, "{" | ";" | "}") {
+                break token;
+            }
+            end = token.span.end;
+            stmt.placeholder |= token.is_placeholder;
+            if token.value.starts_with('#') && !token.is_quoted {
+                comments_in_args.push(token.value);
+                continue;
+            }
+            if let (true, Some(source)) = (self.options.arg_spans, self.source) {
+                stmt.arg_spans
+                    .push(Span::on_line(source, token.line, token.span.clone()));
+            }
+            stmt.args.push(token.value);
+        };
+
+        if &*term.value == "}" {
+            if self.keeps_raw() {
+                parsed.push(self.raw_directive(start..end, &stmt));
+            } else {
+                self.error(ParseError::new(
+                    format!("directive \"{}\" is not terminated by ';'", stmt.directive),
+                    stmt.line,
+                ))?;
+            }
+            // the brace still closes the enclosing block
+            self.brace(BraceEvent::Close { line: term.line });
+            if depth > 0 {
+                self.closed_at = Some(term.line);
+                return Ok(Step::End);
+            }
+            if self.leaves_block() {
+                return Ok(Step::End);
+            }
+            return Ok(Step::Next);
+        }
+
+        let opens_block = if is_args_only(ctx) {
+            // entries of args-only blocks are kept as they are
+            if &*term.value == "{" {
+                if !self.keeps_raw() {
+                    self.error(ParseError::new("unexpected '{'", term.line))?;
+                }
+                // such a '{' is usually an unquoted regex quantifier, so
+                // recover at the end of the entry instead of skipping a block
+                let (close, entry_end) = self.skip_entry()?;
+                if self.keeps_raw() {
+                    parsed.push(self.raw_directive(start..entry_end, &stmt));
+                }
+                if let Some(close) = close {
+                    self.brace(BraceEvent::Close { line: close.line });
+                    if depth > 0 {
+                        self.closed_at = Some(close.line);
+                        return Ok(Step::End);
+                    }
+                    if self.leaves_block() {
+                        return Ok(Step::End);
+                    }
+                    if !self.defer_braces {
+                        let what = unexpected_close(self.top_closed_at);
+                        self.error(ParseError::new(what, close.line))?;
+                    }
+                }
+                return Ok(Step::Next);
+            }
+            if let Err(e) = self.analyze(|| analyze_entry(&stmt, ctx, self.options)) {
+                self.error(e)?;
+                return Ok(Step::Next);
+            }
+            let in_including_block = ctx
+                .last()
+                .is_some_and(|c| INCLUDING_ARGS_ONLY_BLOCKS.contains(&c.as_str()));
+            if &*stmt.directive == "include" && in_including_block {
+                self.resolve_include(&mut stmt, ctx)?;
+            }
+            false
+        } else {
+            if self.options.ignore.iter().any(|n| *n == *stmt.directive) {
+                if &*term.value == "{" {
+                    self.skip_block()?;
+                }
+                return Ok(Step::Next);
+            }
+
+            // prepare arguments
+            if &*stmt.directive == "if" {
+                prepare_if_args(&mut stmt);
+            }
+
+            let mut opens_block = &*term.value == "{";
+            if let Err(e) =
+                self.analyze(|| analyze_stmt(&stmt, ctx, Some(opens_block), self.options))
+            {
+                self.error(e)?;
+                if opens_block {
+                    self.skip_block()?;
+                }
+                return Ok(Step::Next);
+            }
+
+            if opens_block && !self.options.analyzer.is_block(&stmt.directive) {
+                match self.options.unknown_block_policy {
+                    UnknownBlockPolicy::TreatAsBlock => {}
+                    UnknownBlockPolicy::TreatAsSimple => {
+                        self.error(ParseError::new("unexpected '{'", term.line))?;
+                        self.skip_block()?;
+                        opens_block = false;
+                    }
+                    UnknownBlockPolicy::Error => {
+                        self.error(ParseError::new(
+                            format!("unknown block directive \"{}\"", stmt.directive),
+                            stmt.line,
+                        ))?;
+                        self.skip_block()?;
+                        return Ok(Step::Next);
+                    }
+                }
+            }
+
+            if opens_block && depth >= MAX_BLOCK_DEPTH {
+                self.error(ParseError::new("configuration nesting too deep", stmt.line))?;
+                self.skip_block()?;
+                return Ok(Step::Next);
+            }
+
+            if &*stmt.directive == "include" && !opens_block {
+                self.resolve_include(&mut stmt, ctx)?;
+            }
+
+            opens_block
+        };
+        parsed.push(stmt);
+        Ok(Step::Stmt {
+            opens_block,
+            start,
+            comments_in_args,
+        })
+    }
+
+    fn brace(&mut self, event: BraceEvent) {
+        if self.defer_braces {
+            self.ctx.braces.push(event);
+        }
+    }
+
+    // whether a '}' at the top of the file closes a block of another file,
+    // ending the directives of the context the file started in
+    fn leaves_block(&mut self) -> bool {
+        self.left_block = self.defer_braces && !self.ctx.outer.is_empty();
+        self.left_block
+    }
+
+    // queue the files an include directive pulls in and record their indexes
+    fn resolve_include(
+        &mut self,
+        stmt: &mut Directive<S>,
+        ctx: &[String],
+    ) -> Result<(), ParseError> {
+        let Some(includes) = self.includes.as_deref_mut() else {
+            return Ok(());
+        };
+        let Some(arg) = stmt.args.first() else {
+            return Ok(());
+        };
+
+        match includes.resolve(arg, ctx) {
+            Ok(indexes) => {
+                self.brace(BraceEvent::Include(indexes.clone()));
+                stmt.includes = Some(indexes);
+            }
+            Err(what) => {
+                self.error(ParseError::new(what, stmt.line))?;
+                stmt.includes = Some(Vec::new());
+            }
+        }
+        Ok(())
+    }
+
+    // consume tokens up to and including the '}' closing the current block
+    fn skip_block(&mut self) -> Result<(), ParseError> {
+        let mut depth = 1;
+        while let Some(token) = self.next_token()? {
+            if token.is_quoted {
+                continue;
+            }
+            if &*token.value == "{" {
+                depth += 1;
+            } else if &*token.value == "}" {
+                depth -= 1;
+                if depth == 0 {
+                    return Ok(());
+                }
+            }
+        }
+        self.eof_error("unexpected end of file, expecting '}'")
+    }
+
+    // consume the rest of an args-only block entry up to its ';', or return the
+    // '}' closing the block when the entry has no terminator; also returns
+    // where the entry ends in the source
+    fn skip_entry(&mut self) -> Result<(Option<NgxToken<S>>, usize), ParseError> {
+        let mut end = self.last_end;
+        while let Some(token) = self.next_token()? {
+            if !token.is_quoted {
+                match &*token.value {
+                    ";" => return Ok((None, token.span.end)),
+                    "}" => return Ok((Some(token), end)),
+                    _ => {}
+                }
+            }
+            end = token.span.end;
+        }
+        Ok((None, end))
+    }
+
+    fn keeps_raw(&self) -> bool {
+        self.options.raw_fallback && self.source.is_some()
+    }
+
+    // a <raw> directive in place of `stmt`, holding the source text at `span`
+    fn raw_directive(&self, span: Range<usize>, stmt: &Directive<S>) -> Directive<S> {
+        let source = self.source.unwrap_or_default();
+        let raw = String::from_utf8_lossy(&source[span]);
+        Directive {
+            in_if: stmt.in_if,
+            ..raw_text(raw, stmt.line)
+        }
+    }
+
+    // the source text at `span` and the comment following it on its line
+    fn raw_source(&self, span: Range<usize>) -> Option<String> {
+        let source = self.source?;
+        let rest = &source[span.end..];
+        let blanks = rest
+            .iter()
+            .take_while(|&&b| b == b' ' || b == b'\t')
+            .count();
+        let mut end = span.end;
+        if rest.get(blanks) == Some(&b'#') {
+            end += rest.iter().position(|&b| b == b'\n').unwrap_or(rest.len());
+            // keep the \r of a \r\n line ending out
+            if source[..end].ends_with(b"\r") {
+                end -= 1;
+            }
+        }
+        Some(String::from_utf8_lossy(&source[span.start..end]).into_owned())
+    }
+
+    // whether nothing follows a token on its line but a '}'
+    fn ends_line(&mut self, token: &NgxToken<S>) -> bool {
+        self.tokens
+            .peek()
+            .is_none_or(|next| next.line > token.line || (!next.is_quoted && &*next.value == "}"))
+    }
+
+    fn next_token(&mut self) -> Result<Option<NgxToken<S>>, ParseError> {
+        while let Some(token) = self.tokens.next() {
+            self.line = token.line;
+            self.last_end = token.span.end;
+            match token.error {
+                // a cancelled parse stops even when catching errors
+                Some(e) if e.kind == ParseErrorKind::Cancelled => {
+                    return Err(self.in_file(e));
+                }
+                Some(e) => {
+                    self.lex_failed = true;
+                    self.error(e)?;
+                }
+                None => return Ok(Some(token)),
+            }
+        }
+        Ok(None)
+    }
+
+    // errors at the end of the stream are already reported by the lexer
+    fn eof_error(&mut self, what: &str) -> Result<(), ParseError> {
+        if self.lex_failed {
+            return Ok(());
+        }
+        self.error(ParseError::new(what, self.line))
+    }
+
+    fn in_file(&self, e: ParseError) -> ParseError {
+        if self.ctx.file.is_empty() {
+            return e;
+        }
+        e.in_file(&self.ctx.file)
+    }
+
+    // run an analysis, adding the time it takes to the metrics; the
+    // analyzers check nothing unless asked to
+    fn analyze(
+        &mut self,
+        analysis: impl FnOnce() -> Result<(), ParseError>,
+    ) -> Result<(), ParseError> {
+        let options = self.options;
+        if !(options.strict || options.check_ctx || options.check_args) {
+            return Ok(());
+        }
+        let Some(analyze_time) = self.ctx.analyze_time else {
+            return analysis();
+        };
+        let started = Instant::now();
+        let result = analysis();
+        self.ctx.analyze_time = Some(analyze_time + started.elapsed());
+        result
+    }
+
+    fn error(&mut self, e: ParseError) -> Result<(), ParseError> {
+        let e = self.in_file(e);
+        if self.options.catch_errors {
+            self.ctx.errors.push(e);
+            Ok(())
+        } else {
+            Err(e)
+        }
+    }
+}
+
+pub(crate) fn is_args_only(ctx: &[String]) -> bool {
+    ctx.last()
+        .is_some_and(|c| ARGS_ONLY_BLOCKS.contains(&c.as_str()))
+}
+
+pub(crate) fn enter_block_ctx(name: &str, ctx: &[String]) -> Vec<String> {
+    // location blocks nested in other locations keep the same context
+    if ctx.first().is_some_and(|c| c == "http") && name == "location" {
+        return vec!["http".to_string(), "location".to_string()];
+    }
+    let mut inner = ctx.to_vec();
+    inner.push(name.to_string());
+    inner
+}
+
+// strip the parentheses around an if condition
+fn prepare_if_args<S: TreeStr>(stmt: &mut Directive<S>) {
+    let args = &mut stmt.args;
+    if args.is_empty() || !args[0].starts_with('(') || !args[args.len() - 1].ends_with(')') {
+        return;
+    }
+
+    let first = std::mem::take(&mut args[0]);
+    args[0] = first.part(|a| a[1..].trim_start());
+    let last = args.len() - 1;
+    let value = std::mem::take(&mut args[last]);
+    args[last] = value.part(|a| a[..a.len() - 1].trim_end());
+
+    // spans of parens written apart go with them
+    if args[last].is_empty() {
+        args.pop();
+        stmt.arg_spans.truncate(args.len());
+    }
+    if args.first().is_some_and(|a| a.is_empty()) {
+        args.remove(0);
+        if !stmt.arg_spans.is_empty() {
+            stmt.arg_spans.remove(0);
+        }
+    }
+}
+
+// parse a config held in memory as the payload of a single "nginx.conf"
+#[cfg(test)]
+pub(crate) fn parse_source(source: &str, options: &ParseOptions) -> Payload {
+    parse_named(source, "nginx.conf", options)
+}
+
+// parse a config held in memory as the payload of a single file named
+// `file`, keeping include directives as they are
+pub(crate) fn parse_named(source: &str, file: &str, options: &ParseOptions) -> Payload {
+    let tokens = lex_tokens(source.as_bytes(), &options.effective_lex_options());
+    Payload::from_config(vec![parse_file(tokens, source, file, options)])
+}
+
+// the file of parse_named, from the tokens of its source; the status is
+// left to the payload to set
+pub(crate) fn parse_file<S: TreeStr>(
+    tokens: Vec<NgxToken<S>>,
+    source: &str,
+    file: &str,
+    options: &ParseOptions,
+) -> ConfigFile<S> {
+    let mut ctx = ParseCtx::new(file);
+    let mut config = ConfigFile::new(file);
+    config.shebang = shebang(&tokens);
+    match parse_with(tokens, Some(source.as_bytes()), &mut ctx, options, None) {
+        Ok(parsed) => config.parsed = parsed,
+        Err(e) => ctx.errors.push(e),
+    }
+    config.errors = ctx.into_errors();
+    config
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::analyze::{DirectiveSpec, NGX_CONF_BLOCK, NGX_CONF_TAKE1, NGX_MAIN_CONF};
+    use crate::lex::{lex, lex_with, TemplateSyntax};
+
+    fn tok(value: &str, line: usize) -> NgxToken {
+        NgxToken {
+            value: value.to_string(),
+            line,
+            is_quoted: false,
+            error: None,
+            span: 0..0,
+            is_placeholder: false,
+        }
+    }
+
+    fn quoted(value: &str, line: usize) -> NgxToken {
+        NgxToken {
+            is_quoted: true,
+            ..tok(value, line)
+        }
+    }
+
+    fn error_tok(what: &str, line: usize) -> NgxToken {
+        NgxToken {
+            error: Some(ParseError::new(what, line)),
+            ..tok("", line)
+        }
+    }
+
+    fn simple(name: &str, args: &[&str], line: usize) -> Directive {
+        Directive {
+            args: args.iter().map(|a| a.to_string()).collect(),
+            ..Directive::new(name, line)
+        }
+    }
+
+    fn block(
+        name: &str,
+        args: &[&str],
+        (line, end): (usize, usize),
+        children: Vec<Directive>,
+    ) -> Directive {
+        Directive {
+            block: Some(children),
+            block_end_line: Some(end),
+            ..simple(name, args, line)
+        }
+    }
+
+    #[test]
+    fn test_parse_tokens() {
+        let tokens = vec![
+            tok("events", 1),
+            tok("{", 1),
+            tok("worker_connections", 2),
+            tok("1024", 2),
+            tok(";", 2),
+            tok("}", 3),
+            tok("http", 4),
+            tok("{", 4),
+            tok("include", 5),
+            tok("mime.types", 5),
+            tok(";", 5),
+            tok("location", 6),
+            tok("/", 6),
+            tok("{", 6),
+            tok("return", 7),
+            tok("200", 7),
+            quoted("foo bar", 7),
+            tok(";", 7),
+            tok("}", 8),
+            tok("}", 9),
+        ];
+
+        let mut ctx = ParseCtx::new("nginx.conf");
+        let parsed = parse_tokens(tokens, &mut ctx, &ParseOptions::default()).unwrap();
+        assert_eq!(
+            parsed,
+            vec![
+                block(
+                    "events",
+                    &[],
+                    (1, 3),
+                    vec![simple("worker_connections", &["1024"], 2)]
+                ),
+                block(
+                    "http",
+                    &[],
+                    (4, 9),
+                    vec![
+                        simple("include", &["mime.types"], 5),
+                        block(
+                            "location",
+                            &["/"],
+                            (6, 8),
+                            vec![simple("return", &["200", "foo bar"], 7)]
+                        ),
+                    ]
+                ),
+            ]
+        );
+        assert!(ctx.errors().is_empty());
+    }
+
+    #[test]
+    fn test_parse_tokens_comments() {
+        let tokens = vec![
+            tok("#comment", 1),
+            tok("listen", 2),
+            tok("80", 2),
+            tok("#inline", 2),
+            tok(";", 2),
+            quoted("#not a comment", 3),
+            tok(";", 3),
+        ];
+
+        let mut ctx = ParseCtx::new("nginx.conf");
+        let parsed = parse_tokens(tokens.clone(), &mut ctx, &ParseOptions::default()).unwrap();
+        assert_eq!(
+            parsed,
+            vec![
+                simple("listen", &["80"], 2),
+                simple("#not a comment", &[], 3)
+            ]
+        );
+
+        let options = ParseOptions::default().comments(true);
+        let parsed = parse_tokens(tokens, &mut ctx, &options).unwrap();
+        assert_eq!(
+            parsed,
+            vec![
+                Directive::new_comment("comment", 1),
+                simple("listen", &["80"], 2),
+                Directive::new_comment("inline", 2),
+                simple("#not a comment", &[], 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_tokens_if_and_args_only() {
+        let tokens = vec![
+            tok("server", 1),
+            tok("{", 1),
+            tok("if", 2),
+            tok("($request_method", 2),
+            tok("=", 2),
+            tok("POST)", 2),
+            tok("{", 2),
+            tok("return", 3),
+            tok("405", 3),
+            tok(";", 3),
+            tok("}", 4),
+            tok("if", 5),
+            tok("(", 5),
+            tok("-f", 5),
+            tok("$request_filename", 5),
+            tok(")", 5),
+            tok("{", 5),
+            tok("}", 5),
+            tok("}", 6),
+            tok("map", 7),
+            tok("$a", 7),
+            tok("$b", 7),
+            tok("{", 7),
+            tok("if", 8),
+            tok("(x)", 8),
+            tok(";", 8),
+            tok("default", 9),
+            quoted("}", 9),
+            tok(";", 9),
+            tok("}", 10),
+        ];
+
+        let mut ctx = ParseCtx::new("nginx.conf").with_context(&["http"]);
+        let parsed = parse_tokens(tokens, &mut ctx, &ParseOptions::default()).unwrap();
+        assert_eq!(
+            parsed,
+            vec![
+                block(
+                    "server",
+                    &[],
+                    (1, 6),
+                    vec![
+                        block(
+                            "if",
+                            &["$request_method", "=", "POST"],
+                            (2, 4),
+                            vec![Directive {
+                                in_if: true,
+                                ..simple("return", &["405"], 3)
+                            }]
+                        ),
+                        block("if", &["-f", "$request_filename"], (5, 5), vec![]),
+                    ]
+                ),
+                block(
+                    "map",
+                    &["$a", "$b"],
+                    (7, 10),
+                    vec![simple("if", &["(x)"], 8), simple("default", &["}"], 9)]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_tokens_errors() {
+        let tokens = vec![
+            tok("events", 1),
+            tok("{", 1),
+            tok("worker_connections", 2),
+            tok("1024", 2),
+            tok("}", 3),
+            tok("map", 4),
+            tok("$a", 4),
+            tok("$b", 4),
+            tok("{", 4),
+            tok("~^a", 5),
+            tok("{", 5),
+            tok("1,3}$", 5),
+            tok("1", 5),
+            tok(";", 5),
+            tok("}", 6),
+            tok("user", 7),
+            tok("nobody", 7),
+            tok(";", 7),
+            error_tok("unexpected end of file, expecting '}'", 8),
+        ];
+
+        let mut ctx = ParseCtx::new("nginx.conf");
+        let err = parse_tokens(tokens.clone(), &mut ctx, &ParseOptions::default()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "directive \"worker_connections\" is not terminated by ';' in nginx.conf:2"
+        );
+
+        let options = ParseOptions::default().catch_errors(true);
+        let parsed = parse_tokens(tokens, &mut ctx, &options).unwrap();
+        assert_eq!(
+            parsed,
+            vec![
+                block("events", &[], (1, 3), vec![]),
+                block("map", &["$a", "$b"], (4, 6), vec![]),
+                simple("user", &["nobody"], 7),
+            ]
+        );
+        let errors: Vec<_> = ctx
+            .errors()
+            .iter()
+            .map(|e| (e.what.as_str(), e.line))
+            .collect();
+        assert_eq!(
+            errors,
+            vec![
+                (
+                    "directive \"worker_connections\" is not terminated by ';'",
+                    Some(2)
+                ),
+                ("unexpected '{'", Some(5)),
+                ("unexpected end of file, expecting '}'", Some(8)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_tokens_unknown_block_policy() {
+        let source = "mystery_directive arg { inner x; }\nuser nobody;\n";
+        let parse_with = |policy| {
+            let options = ParseOptions::default()
+                .catch_errors(true)
+                .unknown_block_policy(policy);
+            let mut ctx = ParseCtx::new("nginx.conf");
+            let parsed = parse_tokens(lex(source.as_bytes()).unwrap(), &mut ctx, &options).unwrap();
+            (parsed, ctx.into_errors())
+        };
+
+        let (parsed, errors) = parse_with(UnknownBlockPolicy::TreatAsBlock);
+        assert_eq!(
+            parsed,
+            vec![
+                block(
+                    "mystery_directive",
+                    &["arg"],
+                    (1, 1),
+                    vec![simple("inner", &["x"], 1)]
+                ),
+                simple("user", &["nobody"], 2),
+            ]
+        );
+        assert!(errors.is_empty());
+
+        let (parsed, errors) = parse_with(UnknownBlockPolicy::TreatAsSimple);
+        assert_eq!(
+            parsed,
+            vec![
+                simple("mystery_directive", &["arg"], 1),
+                simple("user", &["nobody"], 2),
+            ]
+        );
+        assert_eq!(
+            errors,
+            [ParseError::new("unexpected '{'", 1).in_file("nginx.conf")]
+        );
+
+        let (parsed, errors) = parse_with(UnknownBlockPolicy::Error);
+        assert_eq!(parsed, vec![simple("user", &["nobody"], 2)]);
+        assert_eq!(
+            errors,
+            [
+                ParseError::new("unknown block directive \"mystery_directive\"", 1)
+                    .in_file("nginx.conf")
+            ]
+        );
+
+        // known block directives are not affected
+        let options = ParseOptions::default().unknown_block_policy(UnknownBlockPolicy::Error);
+        let mut ctx = ParseCtx::new("nginx.conf");
+        let parsed =
+            parse_tokens(lex("events { }".as_bytes()).unwrap(), &mut ctx, &options).unwrap();
+        assert_eq!(parsed, vec![block("events", &[], (1, 1), vec![])]);
+
+        // nor are the blocks of the modules the analyzer knows
+        let mystery = DirectiveSpec::new(
+            "mystery_directive",
+            NGX_MAIN_CONF | NGX_CONF_BLOCK | NGX_CONF_TAKE1,
+            "mystery",
+        );
+        let options = options.analyzer(Analyzer::new().with_directives([mystery]));
+        let mut ctx = ParseCtx::new("nginx.conf");
+        let parsed = parse_tokens(lex(source.as_bytes()).unwrap(), &mut ctx, &options).unwrap();
+        assert_eq!(parsed.len(), 2);
+        assert!(ctx.into_errors().is_empty());
+    }
+
+    #[test]
+    fn test_parse_tokens_unclosed_block() {
+        let source = "events {\n}\nhttp {\n    server {\n        listen 80;\n";
+        let options = ParseOptions::default().catch_errors(true);
+        let mut ctx = ParseCtx::new("nginx.conf");
+        let parsed = parse_tokens(
+            lex_tokens(source.as_bytes(), &LexOptions::default()),
+            &mut ctx,
+            &options,
+        )
+        .unwrap();
+
+        assert_eq!(parsed[0].block_end_line, Some(2));
+        assert_eq!(parsed[1].block_end_line, None);
+        let server = &parsed[1].block.as_ref().unwrap()[0];
+        assert_eq!(server.block_end_line, None);
+        assert_eq!(server.block.as_ref().unwrap()[0].block_end_line, None);
+        assert_eq!(
+            ctx.errors(),
+            [ParseError::new("unexpected end of file, expecting '}'", 5).in_file("nginx.conf")]
+        );
+    }
+
+    #[test]
+    fn test_parse_line_endings() {
+        let options = ParseOptions::default().comments(true);
+        let lf = parse("configs/with-comments/nginx.conf", &options);
+        for name in ["lone-cr", "mixed-line-endings"] {
+            let payload = parse(format!("configs/{}/nginx.conf", name), &options);
+            assert_eq!(payload.config[0].parsed, lf.config[0].parsed, "{}", name);
+        }
+
+        let payload = parse_source("http {\r    server {\r\r}\r", &options.catch_errors(true));
+        assert_eq!(
+            payload.errors,
+            [ParseError::new("unexpected end of file, expecting '}'", 4).in_file("nginx.conf")]
+        );
+    }
+
+    #[test]
+    fn test_parse_raw_fallback() {
+        let source = r#"http {
+    server {
+        listen 80;
+        { weird block; }
+        ;
+        route /api -> backend # not a directive yet
+    }
+    map $a $b {
+        default 0;
+    }
+    server_tokens off
+}
+"#;
+        let options = ParseOptions::default().raw_fallback(true).comments(true);
+        let payload = parse_source(source, &options);
+        assert_eq!(payload.errors, []);
+        let raw: Vec<(&str, usize)> = payload
+            .directives()
+            .filter_map(|d| Some((d.directive().raw.as_deref()?, d.directive().line)))
+            .collect();
+        assert_eq!(
+            raw,
+            [
+                ("{ weird block; }", 4),
+                (";", 5),
+                ("route /api -> backend # not a directive yet", 6),
+                ("server_tokens off", 11),
+            ]
+        );
+        let names: Vec<&str> = payload
+            .directives()
+            .map(|d| d.directive().directive.as_str())
+            .collect();
+        assert_eq!(
+            names,
+            ["http", "server", "listen", "<raw>", "<raw>", "<raw>", "map", "default", "<raw>"]
+        );
+        let findings = payload.lint();
+        assert_eq!(findings.len(), 4);
+        assert!(findings.iter().all(|f| f.kind == "raw-directive"));
+
+        // raw directives build back to their source text
+        let built = crate::build(&payload.config[0].parsed, &Default::default());
+        assert_eq!(
+            built,
+            r#"http {
+    server {
+        listen 80;
+        { weird block; }
+        ;
+        route /api -> backend # not a directive yet
+    }
+    map $a $b {
+        default 0;
+    }
+    server_tokens off
+}"#
+        );
+        let rebuilt = parse_source(&built, &options);
+        assert_eq!(rebuilt.config[0].parsed, payload.config[0].parsed);
+
+        // without the source text there is nothing to keep
+        let mut ctx = ParseCtx::new("nginx.conf");
+        assert!(parse_tokens(lex(source.as_bytes()).unwrap(), &mut ctx, &options).is_err());
+    }
+
+    #[test]
+    fn test_parse_tokens_deep_nesting() {
+        let levels = 10_000;
+        let source = format!(
+            "http {{\n{}{}}}\nevents {{\n}}\n",
+            "location / {\n".repeat(levels),
+            "}\n".repeat(levels)
+        );
+
+        let mut ctx = ParseCtx::new("nginx.conf");
+        let err = parse_tokens(
+            lex(source.as_bytes()).unwrap(),
+            &mut ctx,
+            &ParseOptions::default(),
+        );
+        assert_eq!(
+            err,
+            Err(
+                ParseError::new("configuration nesting too deep", MAX_BLOCK_DEPTH + 1)
+                    .in_file("nginx.conf")
+            )
+        );
+
+        // the whole overly deep block is skipped and parsing carries on after it
+        let options = ParseOptions::default().catch_errors(true);
+        let mut ctx = ParseCtx::new("nginx.conf");
+        let parsed = parse_tokens(lex(source.as_bytes()).unwrap(), &mut ctx, &options).unwrap();
+        assert_eq!(ctx.errors().len(), 1);
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[1].directive, "events");
+
+        let mut depth = 0;
+        let mut block = &parsed[0];
+        while let Some(child) = block.block.as_ref().and_then(|b| b.first()) {
+            block = child;
+            depth += 1;
+        }
+        assert_eq!(depth, MAX_BLOCK_DEPTH - 1);
+        assert_eq!(block.block, Some(vec![]));
+    }
+
+    #[test]
+    fn test_parse() {
+        let payload = parse("configs/simple/nginx.conf", &ParseOptions::default());
+        assert_eq!(payload.status, Status::Ok);
+        assert_eq!(payload.config.len(), 1);
+
+        let config = &payload.config[0];
+        assert_eq!(config.file, "configs/simple/nginx.conf");
+        assert_eq!(
+            config.parsed,
+            vec![
+                block(
+                    "events",
+                    &[],
+                    (1, 3),
+                    vec![simple("worker_connections", &["1024"], 2)]
+                ),
+                block(
+                    "http",
+                    &[],
+                    (5, 13),
+                    vec![block(
+                        "server",
+                        &[],
+                        (6, 12),
+                        vec![
+                            simple("listen", &["127.0.0.1:8080"], 7),
+                            simple("server_name", &["default_server"], 8),
+                            block(
+                                "location",
+                                &["/"],
+                                (9, 11),
+                                vec![simple("return", &["200", "foo bar baz"], 10)]
+                            ),
+                        ]
+                    )]
+                ),
+            ]
+        );
+
+        let payload = parse("configs/missing/nginx.conf", &ParseOptions::default());
+        assert_eq!(payload.status, Status::Failed);
+        assert_eq!(payload.errors.len(), 1);
+        assert_eq!(payload.config[0].status, Status::Failed);
+    }
+
+    #[test]
+    fn test_parse_with_comments() {
+        let file = "configs/with-comments/nginx.conf";
+        let payload = parse(file, &ParseOptions::default().comments(true));
+        assert_eq!(payload.status, Status::Ok);
+        // comments keep their place among the directives, inline ones after
+        // the directive or the brace they follow
+        assert_eq!(
+            payload.config[0].parsed,
+            vec![
+                block(
+                    "events",
+                    &[],
+                    (1, 3),
+                    vec![simple("worker_connections", &["1024"], 2)]
+                ),
+                Directive::new_comment("comment", 4),
+                block(
+                    "http",
+                    &[],
+                    (5, 14),
+                    vec![block(
+                        "server",
+                        &[],
+                        (6, 13),
+                        vec![
+                            simple("listen", &["127.0.0.1:8080"], 7),
+                            Directive::new_comment("listen", 7),
+                            simple("server_name", &["default_server"], 8),
+                            block(
+                                "location",
+                                &["/"],
+                                (9, 12),
+                                vec![
+                                    Directive::new_comment("# this is brace", 9),
+                                    Directive::new_comment(" location /", 10),
+                                    simple("return", &["200", "foo bar baz"], 11),
+                                ]
+                            ),
+                        ]
+                    )]
+                ),
+            ]
+        );
+        let comment = &payload.config[0].parsed[1];
+        assert_eq!(comment.directive, "#");
+        assert!(comment.args.is_empty());
+
+        // and are dropped by default
+        let payload = parse(file, &ParseOptions::default());
+        assert_eq!(payload.find("#").len(), 0);
+    }
+    #[test]
+    fn test_parse_reader() {
+        let options = ParseOptions::default();
+        let source = fs::read("configs/simple/nginx.conf").unwrap();
+        let payload = parse_reader(&source[..], &options);
+        assert_eq!(payload.status, Status::Ok);
+        assert_eq!(payload.config[0].file, "-");
+        let simple = parse("configs/simple/nginx.conf", &options);
+        assert_eq!(payload.config[0].parsed, simple.config[0].parsed);
+        let location = &payload.find("location")[0];
+        let path: Vec<_> = location
+            .ancestors()
+            .map(|d| d.directive().directive.as_str())
+            .collect();
+        assert_eq!(path, ["server", "http"]);
+
+        // includes are relative to the working directory
+        let payload = parse_reader("include configs/simple/nginx.conf;\n".as_bytes(), &options);
+        assert_eq!(payload.errors, []);
+        assert_eq!(payload.config[1].file, "configs/simple/nginx.conf");
+        assert_eq!(payload.config[1].parsed, simple.config[0].parsed);
+
+        let payload = parse_reader("http {\n    server_tokens off\n}\n".as_bytes(), &options);
+        assert_eq!(payload.status, Status::Failed);
+        assert_eq!(payload.errors[0].file.as_deref(), Some("-"));
+
+        struct Broken;
+        impl Read for Broken {
+            fn read(&mut self, _: &mut [u8]) -> std::io::Result<usize> {
+                Err(std::io::Error::other("broken pipe"))
+            }
+        }
+        let payload = parse_reader(Broken, &options);
+        assert_eq!(payload.errors.len(), 1);
+        assert_eq!(payload.errors[0].kind, ParseErrorKind::Io);
+        assert_eq!(payload.errors[0].what, "broken pipe");
+    }
+
+    #[test]
+    fn test_parse_args_only() {
+        let payload = parse("configs/args-only/nginx.conf", &ParseOptions::default());
+        assert_eq!(payload.status, Status::Ok);
+        assert_eq!(
+            payload.config[0].parsed,
+            vec![block(
+                "http",
+                &[],
+                (1, 14),
+                vec![
+                    block(
+                        "types",
+                        &[],
+                        (2, 6),
+                        vec![
+                            simple("text/html", &["html", "htm", "shtml"], 3),
+                            simple("application/x-semi;colon", &["semi"], 5),
+                        ]
+                    ),
+                    block(
+                        "map",
+                        &["$uri", "$backend"],
+                        (7, 13),
+                        vec![
+                            simple("default", &["/fallback"], 8),
+                            simple("~^/api/(?<v>\\d+)$", &["/backend/$v"], 9),
+                            simple("~^/a{1,3}$", &["/braces/{}"], 10),
+                            simple("~^/b/(?<id>[0-9]+)#x", &["/b/$id"], 11),
+                            simple("/literal;brace", &["v;{}"], 12),
+                        ]
+                    ),
+                ]
+            )]
+        );
+
+        // like nginx, an unquoted '{' ends the key and is rejected
+        let options = ParseOptions::default().catch_errors(true);
+        let payload = parse("configs/args-only-unquoted-brace/nginx.conf", &options);
+        assert_eq!(
+            payload.config[0].parsed,
+            vec![block(
+                "http",
+                &[],
+                (1, 9),
+                vec![
+                    block(
+                        "map",
+                        &["$uri", "$redirect"],
+                        (2, 5),
+                        vec![simple("default", &["0"], 4)]
+                    ),
+                    block("server", &[], (6, 8), vec![simple("listen", &["80"], 7)]),
+                ]
+            )]
+        );
+        let errors: Vec<_> = payload
+            .errors
+            .iter()
+            .map(|e| (e.what.as_str(), e.line))
+            .collect();
+        assert_eq!(errors, vec![("unexpected '{'", Some(3))]);
+    }
+
+    #[test]
+    fn test_parse_includes() {
+        let payload = parse("configs/includes/nginx.conf", &ParseOptions::default());
+        assert_eq!(payload.status, Status::Ok);
+        let files: Vec<_> = payload.config.iter().map(|c| c.file.as_str()).collect();
+        assert_eq!(
+            files,
+            [
+                "configs/includes/nginx.conf",
+                "configs/includes/conf.d/server.conf",
+                "configs/includes/locations.conf",
+            ]
+        );
+        let includes: Vec<_> = payload
+            .find("include")
+            .iter()
+            .map(|d| (d.file_index(), d.directive().includes.clone()))
+            .collect();
+        // included files resolve against the root config's directory
+        assert_eq!(includes, [(0, Some(vec![1])), (1, Some(vec![2]))]);
+
+        // a missing file is an error of the including file, and the rest
+        // is parsed when catching errors
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("nginx.conf");
+        fs::write(
+            &root,
+            "http {\n    include missing.conf;\n    include present.conf;\n}\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join("present.conf"), "server_tokens off;\n").unwrap();
+        let payload = parse(&root, &ParseOptions::default().catch_errors(true));
+        assert_eq!(payload.config.len(), 2);
+        assert_eq!(payload.config[0].errors.len(), 1);
+        assert!(payload.config[0].errors[0]
+            .what
+            .starts_with("No such file or directory"));
+        assert_eq!(payload.config[0].errors[0].line, Some(2));
+        assert_eq!(payload.config[1].status, Status::Ok);
+        assert_eq!(payload.config[1].parsed[0].directive, "server_tokens");
+    }
+
+    #[test]
+    fn test_parse_include_variables() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nginx.conf");
+        fs::write(
+            &path,
+            "http {\n    include $host.conf;\n    include ${host}.conf;\n    \
+             include conf.d/$dir/*.conf;\n    include \\$host.conf;\n}\n",
+        )
+        .unwrap();
+        // neither is read, even though the file exists
+        fs::write(dir.path().join("$host.conf"), "gzip on;\n").unwrap();
+        let escaped = format!("{}/\\$host.conf", dir.path().display());
+        fs::write(escaped, "gzip off;\n").unwrap();
+
+        let payload = parse(&path, &ParseOptions::default().catch_errors(true));
+        let errors: Vec<_> = payload
+            .errors
+            .iter()
+            .map(|e| (e.what.as_str(), e.line))
+            .collect();
+        assert_eq!(
+            errors,
+            [
+                (INCLUDE_VARIABLES, Some(2)),
+                (INCLUDE_VARIABLES, Some(3)),
+                (INCLUDE_VARIABLES, Some(4)),
+            ]
+        );
+        let http = payload.config[0].parsed[0].block.as_ref().unwrap();
+        let includes: Vec<_> = http.iter().map(|d| d.includes.clone()).collect();
+        assert_eq!(
+            includes,
+            [Some(vec![]), Some(vec![]), Some(vec![]), Some(vec![1])]
+        );
+        assert_eq!(payload.config[1].parsed[0].args, ["off"]);
+
+        let source = fs::read_to_string(&path).unwrap();
+        let mut payload = parse_named(&source, &path.to_string_lossy(), &ParseOptions::default());
+        let at = payload.find("include")[0].location();
+        let error = payload
+            .resolve_include(&at, &crate::FsLoader, &ParseOptions::default())
+            .unwrap_err();
+        assert_eq!(error.what, INCLUDE_VARIABLES);
+        assert_eq!(error.kind, ParseErrorKind::Syntax);
+    }
+
+    #[test]
+    fn test_parse_single() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("nginx.conf");
+        fs::write(&root, "http {\n    include missing.conf;\n}\n").unwrap();
+
+        let payload = parse(&root, &ParseOptions::default().single(true));
+        assert_eq!(payload.status, Status::Ok);
+        assert_eq!(payload.config.len(), 1);
+        let include = payload.find("include")[0].directive();
+        assert_eq!(include.args, ["missing.conf"]);
+        assert_eq!(include.includes, None);
+
+        let payload = parse(&root, &ParseOptions::default());
+        assert_eq!(payload.status, Status::Failed);
+        assert!(payload.errors[0]
+            .what
+            .starts_with("No such file or directory"));
+    }
+
+    #[test]
+    fn test_parse_combine() {
+        let root = "configs/includes/nginx.conf";
+        let payload = parse(root, &ParseOptions::default().combine(true));
+        assert_eq!(payload.status, Status::Ok);
+        assert_eq!(payload.config.len(), 1);
+        assert_eq!(payload.config[0].file, root);
+        let directives: Vec<_> = payload
+            .directives()
+            .map(|d| {
+                let d = d.directive();
+                (d.directive.as_str(), d.line, d.file.as_deref().unwrap())
+            })
+            .collect();
+        let server = "configs/includes/conf.d/server.conf";
+        let locations = "configs/includes/locations.conf";
+        assert_eq!(
+            directives,
+            [
+                ("events", 1, root),
+                ("worker_connections", 2, root),
+                ("http", 5, root),
+                ("server", 1, server),
+                ("listen", 2, server),
+                ("server_name", 3, server),
+                ("location", 1, locations),
+                ("return", 2, locations),
+            ]
+        );
+
+        // the files stay apart without the option
+        let payload = parse(root, &ParseOptions::default());
+        assert_eq!(payload.config.len(), 3);
+        assert!(payload.directives().all(|d| d.directive().file.is_none()));
+
+        // the errors of every file are kept, and a file including itself
+        // is spliced once
+        let dir = tempfile::tempdir().unwrap();
+        let write = |name: &str, source: &str| fs::write(dir.path().join(name), source).unwrap();
+        write(
+            "nginx.conf",
+            "include self.conf;
+include broken.conf;
+",
+        );
+        write(
+            "self.conf",
+            "gzip on;
+include self.conf;
+",
+        );
+        write(
+            "broken.conf",
+            "server_tokens off;
+}
+",
+        );
+        let options = ParseOptions::default().catch_errors(true).combine(true);
+        let payload = parse(dir.path().join("nginx.conf"), &options);
+        assert_eq!(payload.status, Status::Failed);
+        assert_eq!(payload.config[0].status, Status::Failed);
+        assert_eq!(payload.config[0].errors, payload.errors);
+        assert_eq!(payload.errors.len(), 1);
+        let names: Vec<_> = payload
+            .directives()
+            .map(|d| d.directive().directive.as_str())
+            .collect();
+        assert_eq!(names, ["gzip", "server_tokens"]);
+    }
+
+    #[test]
+    fn test_parse_glob_includes() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("conf.d")).unwrap();
+        fs::write(
+            dir.path().join("nginx.conf"),
+            "http {\n    include conf.d/*.conf;\n    include missing/*.conf;\n}\n",
+        )
+        .unwrap();
+        for name in ["b.conf", "a.conf", ".hidden.conf", "c.conf.bak"] {
+            fs::write(dir.path().join("conf.d").join(name), "server_tokens off;\n").unwrap();
+        }
+        fs::create_dir(dir.path().join("conf.d").join("old.conf")).unwrap();
+
+        let payload = parse(dir.path().join("nginx.conf"), &ParseOptions::default());
+        assert_eq!(payload.errors, []);
+        let files: Vec<&str> = payload.config[1..]
+            .iter()
+            .map(|c| c.file.rsplit('/').next().unwrap())
+            .collect();
+        assert_eq!(files, ["a.conf", "b.conf"]);
+        let http = payload.config[0].parsed[0].block.as_ref().unwrap();
+        assert_eq!(http[0].includes, Some(vec![1, 2]));
+        assert_eq!(http[1].includes, Some(vec![]));
+    }
+
+    #[test]
+    fn test_parse_stub_known_includes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nginx.conf");
+        std::fs::write(
+            &path,
+            "http {\n    include mime.types;\n    server {\n        include fastcgi_params;\n    }\n}\n",
+        )
+        .unwrap();
+
+        let payload = parse(&path, &ParseOptions::default());
+        assert_eq!(payload.status, Status::Ok);
+        assert_eq!(payload.config.len(), 3);
+        assert!(!payload.config[0].stubbed);
+
+        let http = &payload.config[0].parsed[0];
+        let mime = &http.block.as_ref().unwrap()[0];
+        assert_eq!(mime.includes, Some(vec![1]));
+        let mime_types = &payload.config[1];
+        assert!(mime_types.stubbed);
+        assert_eq!(
+            mime_types.file,
+            dir.path().join("mime.types").display().to_string()
+        );
+        assert_eq!(mime_types.parsed[0].directive, "types");
+        assert!(mime_types.parsed[0].block.as_ref().unwrap().len() > 50);
+
+        let params = &payload.config[2];
+        assert!(params.stubbed);
+        assert_eq!(params.parsed[0].directive, "fastcgi_param");
+
+        // a file that exists is parsed instead of the bundled copy
+        std::fs::write(dir.path().join("mime.types"), "types {\n}\n").unwrap();
+        let payload = parse(&path, &ParseOptions::default());
+        assert!(!payload.config[1].stubbed);
+        assert_eq!(payload.config[1].parsed[0].block, Some(vec![]));
+        std::fs::remove_file(dir.path().join("mime.types")).unwrap();
+
+        let options = ParseOptions::default()
+            .stub_known_includes(false)
+            .catch_errors(true);
+        let payload = parse(&path, &options);
+        assert_eq!(payload.status, Status::Failed);
+        assert_eq!(payload.config.len(), 1);
+        let errors = &payload.config[0].errors;
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].line, Some(2));
+        assert!(errors[0]
+            .what
+            .ends_with(&format!(": '{}'", dir.path().join("mime.types").display())));
+        assert_eq!(errors[1].line, Some(4));
+
+        for (name, source) in KNOWN_INCLUDES {
+            let mut ctx = ParseCtx::new(name).with_context(&["http"]);
+            let parsed = parse_tokens(lex(source.as_bytes()).unwrap(), &mut ctx, &options);
+            assert!(
+                !parsed.unwrap().is_empty() && ctx.errors().is_empty(),
+                "{}",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_max_total_bytes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nginx.conf");
+        std::fs::write(
+            &path,
+            "http {\n    include big.conf;\n    include small.conf;\n}\n",
+        )
+        .unwrap();
+        let big = dir.path().join("big.conf");
+        std::fs::write(&big, "server_name example.com;\n".repeat(1000)).unwrap();
+        std::fs::write(dir.path().join("small.conf"), "gzip on;\n").unwrap();
+
+        let payload = parse(&path, &ParseOptions::default());
+        assert_eq!(payload.status, Status::Ok);
+        assert_eq!(payload.config.len(), 3);
+
+        let too_large = ParseError {
+            what: "input size limit of 4096 bytes exceeded".to_string(),
+            file: Some(big.display().to_string()),
+            line: None,
+            kind: ParseErrorKind::InputTooLarge,
+            arg_index: None,
+            span: None,
+        };
+
+        let options = ParseOptions::default().max_total_bytes(4096);
+        let payload = parse(&path, &options);
+        assert_eq!(payload.errors, std::slice::from_ref(&too_large));
+        assert_eq!(payload.config.len(), 1);
+        assert_eq!(payload.config[0].file, path.display().to_string());
+        assert_eq!(payload.config[0].parsed, []);
+
+        // the files parsed before the limit was crossed are kept
+        let payload = parse(&path, &options.clone().catch_errors(true));
+        assert_eq!(payload.errors, [too_large]);
+        assert_eq!(payload.config.len(), 2);
+        assert_eq!(payload.config[1].parsed, []);
+        let http = payload.config[0].parsed[0].block.as_ref().unwrap();
+        assert_eq!(http[0].includes, Some(vec![1]));
+        assert_eq!(http[1].includes, Some(vec![]));
+
+        std::fs::write(&path, "include /dev/zero;\n").unwrap();
+        let payload = parse(&path, &options);
+        assert_eq!(payload.errors.len(), 1);
+        assert_eq!(payload.errors[0].what, "not a regular file");
+        assert_eq!(payload.errors[0].kind, ParseErrorKind::Io);
+        assert_eq!(payload.errors[0].file.as_deref(), Some("/dev/zero"));
+    }
+
+    #[test]
+    fn test_parse_cancelled() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nginx.conf");
+        std::fs::write(
+            &path,
+            "http {\n    include small.conf;\n    include big.conf;\n    include last.conf;\n}\n",
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("small.conf"), "gzip on;\n").unwrap();
+        std::fs::write(dir.path().join("last.conf"), "gzip off;\n").unwrap();
+        let big = dir.path().join("big.conf");
+        let server = "server {\n    listen 80;\n    location / {\n        return 204;\n    }\n}\n";
+        std::fs::write(&big, server.repeat(200_000)).unwrap();
+
+        // a token cancelled up front stops the parse before the root file
+        let token = CancelToken::new();
+        token.cancel();
+        let payload = parse(&path, &ParseOptions::default().cancel_token(token));
+        assert_eq!(payload.config.len(), 1);
+        assert_eq!(payload.config[0].parsed, []);
+        assert_eq!(payload.errors.len(), 1);
+        assert_eq!(payload.errors[0].kind, ParseErrorKind::Cancelled);
+
+        // cancelled from another thread while lexing or parsing big.conf
+        let token = CancelToken::new();
+        let canceller = {
+            let token = token.clone();
+            std::thread::spawn(move || {
+                std::thread::sleep(std::time::Duration::from_millis(50));
+                token.cancel();
+                std::time::Instant::now()
+            })
+        };
+        let options = ParseOptions::default()
+            .catch_errors(true)
+            .cancel_token(token);
+        let payload = parse(&path, &options);
+        let returned = std::time::Instant::now();
+        let cancelled = canceller.join().unwrap();
+        assert!(returned.duration_since(cancelled) < std::time::Duration::from_millis(500));
+
+        let cancelled_error = ParseError {
+            what: "parse cancelled".to_string(),
+            file: Some(big.display().to_string()),
+            line: payload.errors.first().and_then(|e| e.line),
+            kind: ParseErrorKind::Cancelled,
+            arg_index: None,
+            span: None,
+        };
+        assert_eq!(payload.errors, [cancelled_error]);
+        // the files before it are kept, those after it are left out
+        assert_eq!(payload.config.len(), 3);
+        assert_eq!(payload.config[1].parsed.len(), 1);
+        assert_eq!(payload.config[2].parsed, []);
+        let http = payload.config[0].parsed[0].block.as_ref().unwrap();
+        assert_eq!(http[1].includes, Some(vec![2]));
+        assert_eq!(http[2].includes, Some(vec![]));
+    }
+
+    #[test]
+    fn test_parse_braces() {
+        let payload = parse(
+            "configs/quoted-right-brace/nginx.conf",
+            &ParseOptions::default(),
+        );
+        assert_eq!(payload.status, Status::Ok);
+        assert_eq!(
+            payload.config[0].parsed,
+            vec![
+                block("events", &[], (1, 1), vec![]),
+                block(
+                    "http",
+                    &[],
+                    (2, 16),
+                    vec![simple(
+                        "log_format",
+                        &[
+                            "main",
+                            "escape=json",
+                            "{ \"@timestamp\": \"$time_iso8601\", ",
+                            "\"server_name\": \"$server_name\", ",
+                            "\"host\": \"$host\", ",
+                            "\"status\": \"$status\", ",
+                            "\"request\": \"$request\", ",
+                            "\"uri\": \"$uri\", ",
+                            "\"args\": \"$args\", ",
+                            "\"https\": \"$https\", ",
+                            "\"request_method\": \"$request_method\", ",
+                            "\"referer\": \"$http_referer\", ",
+                            "\"agent\": \"$http_user_agent\"",
+                            "}",
+                        ],
+                        3
+                    )]
+                ),
+            ]
+        );
+
+        let payload = parse(
+            "configs/brace-adjacent/nginx.conf",
+            &ParseOptions::default(),
+        );
+        assert_eq!(payload.status, Status::Ok);
+        assert_eq!(
+            payload.config[0].parsed,
+            vec![block(
+                "http",
+                &[],
+                (1, 14),
+                vec![
+                    block(
+                        "map",
+                        &["$uri", "$dest"],
+                        (2, 6),
+                        vec![
+                            simple("}", &["close"], 3),
+                            simple("default", &["}"], 4),
+                            simple("~^/a/", &["/a}b"], 5),
+                        ]
+                    ),
+                    block(
+                        "server",
+                        &[],
+                        (7, 13),
+                        vec![
+                            block(
+                                "location",
+                                &["=", "/}"],
+                                (8, 10),
+                                vec![simple("return", &["200", "}"], 9)]
+                            ),
+                            block(
+                                "location",
+                                &["/foo"],
+                                (11, 11),
+                                vec![simple("return", &["200", "foo}bar"], 11)]
+                            ),
+                            block("location", &["/bar"], (12, 12), vec![]),
+                        ]
+                    ),
+                ]
+            )]
+        );
+    }
+
+    #[test]
+    fn test_parse_extra_brace() {
+        let options = ParseOptions::default().catch_errors(true);
+        let extra = "unexpected '}', all blocks were already closed (last block closed at line";
+        let at_end = "events {\n}\nhttp {\n    server {\n    }\n}\n}\n";
+        let payload = parse_source(at_end, &options);
+        assert_eq!(
+            payload.errors,
+            [ParseError::new(format!("{} 6)", extra), 7).in_file("nginx.conf")]
+        );
+
+        // the parser reports it the same, keeping what comes after
+        let in_middle = "events {\n}\n}\nhttp {\n}\n";
+        let lex_options = LexOptions {
+            unbalanced: true,
+            ..Default::default()
+        };
+        let mut ctx = ParseCtx::new("nginx.conf");
+        let tokens = lex_with(in_middle.as_bytes(), &lex_options).unwrap();
+        let parsed = parse_tokens(tokens, &mut ctx, &options).unwrap();
+        assert_eq!(
+            ctx.errors(),
+            [ParseError::new(format!("{} 2)", extra), 3).in_file("nginx.conf")]
+        );
+        let names: Vec<_> = parsed.iter().map(|d| d.directive.as_str()).collect();
+        assert_eq!(names, ["events", "http"]);
+
+        // without any block before it, there is nothing to point at
+        let payload = parse_source("}\nevents {\n}\n", &options);
+        assert_eq!(payload.errors[0].what, "unexpected '}'");
+    }
+
+    #[test]
+    fn test_parse_catch_errors_recovers() {
+        // each mistake is reported and the parse goes on past it
+        let file = "configs/three-mistakes/nginx.conf";
+        let payload = parse(file, &ParseOptions::default().catch_errors(true));
+        let errors: Vec<_> = payload
+            .errors
+            .iter()
+            .map(|e| (e.what.as_str(), e.line))
+            .collect();
+        assert_eq!(
+            errors,
+            [
+                (
+                    "unexpected '}', all blocks were already closed (last block closed at line 3)",
+                    Some(4)
+                ),
+                ("unexpected \";\"", Some(6)),
+                ("directive \"root\" is not terminated by ';'", Some(10)),
+            ]
+        );
+        assert_eq!(payload.errors, payload.config[0].errors);
+        let listens: Vec<_> = payload
+            .find("listen")
+            .iter()
+            .map(|d| d.directive().line)
+            .collect();
+        assert_eq!(listens, [8, 14]);
+
+        // without catching errors the first one stops the parse
+        let payload = parse(file, &ParseOptions::default());
+        assert_eq!(payload.status, Status::Failed);
+        assert_eq!(payload.errors.len(), 1);
+        assert_eq!(payload.errors[0].line, Some(4));
+    }
+
+    #[test]
+    fn test_parse_leading_brace_or_semicolon() {
+        let options = ParseOptions::default().catch_errors(true);
+        let events = |lines, worker_line| {
+            block(
+                "events",
+                &[],
+                lines,
+                vec![simple("worker_connections", &["1024"], worker_line)],
+            )
+        };
+
+        let file = "configs/leading-open-brace/nginx.conf";
+        let payload = parse(file, &options);
+        assert_eq!(
+            payload.errors,
+            [ParseError::new("unexpected \"{\"", 1).in_file(file)]
+        );
+        assert_eq!(payload.config[0].parsed, [events((4, 6), 5)]);
+
+        let file = "configs/leading-semicolon/nginx.conf";
+        let payload = parse(file, &options);
+        assert_eq!(
+            payload.errors,
+            [
+                ParseError::new("unexpected \";\"", 1).in_file(file),
+                ParseError::new("unexpected \";\"", 3).in_file(file),
+            ]
+        );
+        assert_eq!(payload.config[0].parsed, [events((2, 5), 4)]);
+
+        let file = "configs/close-brace-semicolon/nginx.conf";
+        let payload = parse(file, &options);
+        assert_eq!(
+            payload.errors,
+            [
+                ParseError::new("unexpected '}'", 1).in_file(file),
+                ParseError::new("unexpected \";\"", 1).in_file(file),
+            ]
+        );
+        assert_eq!(payload.config[0].parsed, []);
+
+        let payload = parse(
+            "configs/leading-open-brace/nginx.conf",
+            &ParseOptions::default(),
+        );
+        assert_eq!(payload.status, Status::Failed);
+        assert_eq!(payload.config[0].parsed, []);
+    }
+
+    #[test]
+    fn test_parse_shebang() {
+        for (source, shebang) in [
+            ("#!jinja2\nevents {}\n", Some("#!jinja2")),
+            ("#%PRAGMA once\nevents {}\n", Some("#%PRAGMA once")),
+            ("# comment\nevents {}\n", None),
+            ("events {}\n#!jinja2\n", None),
+            ("\n#!jinja2\nevents {}\n", None),
+            ("\"#!jinja2\";\n", None),
+        ] {
+            for comments in [false, true] {
+                let payload = parse_source(source, &ParseOptions::default().comments(comments));
+                assert_eq!(payload.config[0].shebang(), shebang, "{:?}", source);
+            }
+        }
+
+        // the comment stream only has it when comments are kept
+        let source = "#!jinja2\nevents {}\n";
+        let payload = parse_source(source, &ParseOptions::default());
+        assert_eq!(payload.config[0].parsed[0].directive, "events");
+        let payload = parse_source(source, &ParseOptions::default().comments(true));
+        assert_eq!(
+            payload.config[0].parsed[0].comment.as_deref(),
+            Some("!jinja2")
+        );
+    }
+
+    #[test]
+    fn test_parse_capture_raw_for() {
+        let source = "http {
+    allow   10.0.0.0/8\t ;   # office
+    deny all;
+    ssl_certificate \"/etc/ssl/a b.pem\";
+    limit_except  GET {  deny  all; }  # read only
+    root /srv;  # not captured
+}
+";
+        let options = ParseOptions::default().capture_raw_for(&["allow", "deny", "limit_except"]);
+        let payload = parse_source(source, &options);
+        let captured: Vec<(&str, Option<&str>)> = payload
+            .directives()
+            .map(|d| (d.directive().directive.as_str(), d.raw_source()))
+            .collect();
+        assert_eq!(
+            captured,
+            [
+                ("http", None),
+                ("allow", Some("allow   10.0.0.0/8\t ;   # office")),
+                ("deny", Some("deny all;")),
+                ("ssl_certificate", None),
+                (
+                    "limit_except",
+                    Some("limit_except  GET {  deny  all; }  # read only")
+                ),
+                ("deny", Some("deny  all;")),
+                ("root", None),
+            ]
+        );
+
+        let crlf = parse_source("deny all; #x\r\n", &options);
+        assert_eq!(
+            crlf.config[0].parsed[0].raw_source.as_deref(),
+            Some("deny all; #x")
+        );
+        let payload = parse_source(source, &ParseOptions::default());
+        assert!(payload.directives().all(|d| d.raw_source().is_none()));
+    }
+
+    #[test]
+    fn test_parse_ignore() {
+        let source = "http {
+    add_header X-Frame-Options DENY;
+    server {
+        if ($http_x) {
+            if ($args) {
+                return 403 \"}\";
+            }
+            add_header X-If 1;
+        }
+        location / {
+            return 200;
+        }
+    }
+    add_header X-Last 1;
+}
+";
+        let names = |options: &ParseOptions| -> Vec<(String, usize)> {
+            let payload = parse_source(source, options);
+            assert_eq!(payload.errors, []);
+            payload
+                .directives()
+                .map(|d| (d.directive().directive.clone(), d.directive().line))
+                .collect()
+        };
+        let name = |name: &str, line| (name.to_string(), line);
+
+        assert_eq!(
+            names(&ParseOptions::default().ignore(&["add_header"])),
+            [
+                name("http", 1),
+                name("server", 3),
+                name("if", 4),
+                name("if", 5),
+                name("return", 6),
+                name("location", 10),
+                name("return", 11),
+            ]
+        );
+        // the whole block goes, and what follows it is parsed as usual
+        assert_eq!(
+            names(&ParseOptions::default().ignore(&["if"])),
+            [
+                name("http", 1),
+                name("add_header", 2),
+                name("server", 3),
+                name("location", 10),
+                name("return", 11),
+                name("add_header", 14),
+            ]
+        );
+        assert_eq!(
+            names(&ParseOptions::default().ignore(&["server", "add_header"])),
+            [name("http", 1)]
+        );
+    }
+
+    #[test]
+    fn test_parse_template_syntax() {
+        let source = "http {
+    upstream backend {
+        {% for host in hosts %}
+        server {{ host }}:8080 weight={{ weights[host] }};
+        {% endfor %}
+    }
+    server {
+        listen {{ port }} default_server;
+        root /srv;
+        {{ extra_directives }}
+    }
+}
+";
+        let options = ParseOptions::default()
+            .strict(true)
+            .lex_options(LexOptions::default().template_syntax(TemplateSyntax::Jinja));
+        let payload = parse_source(source, &options);
+        assert_eq!(payload.errors, []);
+
+        let directives: Vec<(&str, bool, bool)> = payload
+            .directives()
+            .map(|d| {
+                let d = d.directive();
+                (d.directive.as_str(), d.is_raw(), d.placeholder)
+            })
+            .collect();
+        assert_eq!(
+            directives,
+            [
+                ("http", false, false),
+                ("upstream", false, false),
+                (RAW_DIRECTIVE, true, true),
+                ("server", false, true),
+                (RAW_DIRECTIVE, true, true),
+                ("server", false, false),
+                ("listen", false, true),
+                ("root", false, false),
+                (RAW_DIRECTIVE, true, true),
+            ]
+        );
+        assert!(payload.lint().is_empty());
+
+        let built = crate::build::build(&payload.config[0].parsed, &Default::default());
+        assert_eq!(built + "\n", source);
+    }
+
+    #[test]
+    fn test_parse_cross_file_braces() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |name: &str, source: &str| fs::write(dir.path().join(name), source).unwrap();
+        write(
+            "nginx.conf",
+            "http {\n    include server.conf;\n    include end.conf;\n}\n",
+        );
+        write("server.conf", "server {\n    listen 80;\n");
+        write("end.conf", "    location / {\n    }\n}\ngzip on;\n");
+        let path = dir.path().join("nginx.conf");
+        let errors = |payload: &Payload| -> Vec<(String, String, Option<usize>)> {
+            payload
+                .errors
+                .iter()
+                .map(|e| {
+                    let file = Path::new(e.file.as_deref().unwrap());
+                    let name = file.file_name().unwrap().to_string_lossy();
+                    (name.to_string(), e.what.clone(), e.line)
+                })
+                .collect()
+        };
+        let names = |block: &[Directive]| -> Vec<String> {
+            block.iter().map(|d| d.directive.clone()).collect()
+        };
+
+        // balanced within each file, as crossplane does
+        let options = ParseOptions::default().catch_errors(true);
+        let payload = parse(&path, &options);
+        assert_eq!(
+            errors(&payload),
+            [
+                (
+                    "server.conf".to_string(),
+                    "unexpected end of file, expecting '}'".to_string(),
+                    Some(2)
+                ),
+                (
+                    "end.conf".to_string(),
+                    "unexpected '}', all blocks were already closed (last block closed at line 2)"
+                        .to_string(),
+                    Some(3)
+                ),
+            ]
+        );
+
+        // balanced across the files
+        let options = options.cross_file_braces(true);
+        let payload = parse(&path, &options);
+        assert_eq!(errors(&payload), []);
+        let server = &payload.config[1].parsed;
+        assert_eq!(names(server), ["server"]);
+        assert_eq!(names(server[0].block.as_ref().unwrap()), ["listen"]);
+        assert_eq!(names(&payload.config[2].parsed), ["location", "gzip"]);
+
+        // what still does not balance is reported where it is
+        write("end.conf", "}\n}\n");
+        let payload = parse(&path, &options);
+        assert_eq!(
+            errors(&payload),
+            [(
+                "nginx.conf".to_string(),
+                format!(
+                    "unexpected '}}', all blocks were already closed \
+                     (last block closed at line 2 of {})",
+                    dir.path().join("end.conf").display()
+                ),
+                Some(4)
+            )]
+        );
+        write("end.conf", "# nothing closed\n");
+        let payload = parse(&path, &options);
+        assert_eq!(
+            errors(&payload),
+            [(
+                "nginx.conf".to_string(),
+                "\"http\" block is not closed".to_string(),
+                Some(1)
+            )]
+        );
+        assert_eq!(payload.config[0].status, Status::Failed);
+
+        // a source parsed alone is still balanced on its own
+        let payload = parse_source("}\nhttp {\n", &options);
+        let lines: Vec<_> = payload.errors.iter().map(|e| e.line).collect();
+        assert_eq!(lines, [Some(1), Some(2)]);
+
+        // directives between files are checked in the blocks open around
+        // them, up to the '}' closing one of another file
+        write(
+            "nginx.conf",
+            "http {\n    include server.conf;\n    include body.conf;\n    \
+             include end.conf;\n}\n",
+        );
+        write("body.conf", "    server_name example.com;\n    if ($x) {\n");
+        write(
+            "end.conf",
+            "        return 404;\n    }\n    location / {\n    }\n}\ngzip on;\nlisten 81;\n",
+        );
+        let options = options.check_ctx(true);
+        let payload = parse(&path, &options);
+        assert_eq!(
+            errors(&payload),
+            [(
+                "end.conf".to_string(),
+                "\"listen\" directive is not allowed here".to_string(),
+                Some(7)
+            )]
+        );
+        let end = &payload.config[3].parsed;
+        assert_eq!(names(end), ["return", "location", "gzip"]);
+        assert!(end[0].in_if);
+        assert!(!end[1].in_if);
+    }
+
+    #[test]
+    fn test_parse_comment_backslash() {
+        // a backslash ending a comment is kept with it, as nginx does, and
+        // the next line is a statement of its own
+        let options = ParseOptions::default().comments(true);
+        let payload = parse("configs/comment-backslash/nginx.conf", &options);
+        assert!(payload.errors.is_empty());
+        let directives: Vec<(usize, &str, Vec<&str>, Option<&str>)> = payload
+            .directives()
+            .map(|d| {
+                let d = d.directive();
+                let args = d.args.iter().map(String::as_str).collect();
+                (d.line, d.directive.as_str(), args, d.comment.as_deref())
+            })
+            .collect();
+        assert_eq!(
+            directives,
+            [
+                (1, "#", vec![], Some(" disable gzip \\")),
+                (2, "gzip", vec!["on"], None),
+                (3, "events", vec![], None),
+                (4, "worker_connections", vec!["1024"], None),
+                (4, "#", vec![], Some(" per worker \\")),
+                (5, "multi_accept", vec!["on"], None),
+                (7, "http", vec![], None),
+                (8, "#", vec![], Some(" tab escaped \\\t")),
+                (9, "server_tokens", vec!["off"], None),
+                // inside quotes the backslash is kept
+                (10, "set", vec!["$greeting", "hello \\\nworld"], None),
+                (12, "root", vec!["/srv/www"], None),
+                (12, "#", vec![], Some(" trailing \\\\")),
+                (13, "index", vec!["index.html"], None),
+            ]
+        );
+        let events = &payload.config[0].parsed[2];
+        assert_eq!(events.block_end_line, Some(6));
+    }
+}
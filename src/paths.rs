@@ -0,0 +1,440 @@
+use std::path::{Path, PathBuf};
+
+use crate::lint::{Finding, Severity};
+use crate::parse::{has_variables, Payload};
+
+/// What nginx needs of a path a directive names.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathCheck {
+    /// A file read when the config is loaded, which `nginx -t` fails
+    /// without, like a certificate.
+    Exists,
+    /// A file created when the config is loaded, like a log, whose
+    /// directory has to exist.
+    ParentExists,
+    /// A file or directory only used when serving requests, which `nginx
+    /// -t` does not look for.
+    Advisory,
+}
+
+/// The directory nginx resolves a relative path against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathPrefix {
+    /// The directory of the config file, nginx's conf prefix, like for
+    /// `include` and certificates.
+    Config,
+    /// The prefix set with nginx's `-p`, like for logs and `root`.
+    Prefix,
+}
+
+// the directives naming a path in their first arg, with what nginx needs
+// of it and what it is resolved against when relative
+const PATH_DIRECTIVES: &[(&str, PathCheck, PathPrefix)] = &[
+    ("access_log", PathCheck::ParentExists, PathPrefix::Prefix),
+    ("alias", PathCheck::Advisory, PathPrefix::Prefix),
+    (
+        "auth_basic_user_file",
+        PathCheck::Advisory,
+        PathPrefix::Config,
+    ),
+    ("error_log", PathCheck::ParentExists, PathPrefix::Prefix),
+    (
+        "grpc_ssl_certificate",
+        PathCheck::Exists,
+        PathPrefix::Config,
+    ),
+    (
+        "grpc_ssl_certificate_key",
+        PathCheck::Exists,
+        PathPrefix::Config,
+    ),
+    ("grpc_ssl_crl", PathCheck::Exists, PathPrefix::Config),
+    (
+        "grpc_ssl_password_file",
+        PathCheck::Exists,
+        PathPrefix::Config,
+    ),
+    (
+        "grpc_ssl_trusted_certificate",
+        PathCheck::Exists,
+        PathPrefix::Config,
+    ),
+    ("load_module", PathCheck::Exists, PathPrefix::Prefix),
+    ("pid", PathCheck::ParentExists, PathPrefix::Prefix),
+    (
+        "proxy_ssl_certificate",
+        PathCheck::Exists,
+        PathPrefix::Config,
+    ),
+    (
+        "proxy_ssl_certificate_key",
+        PathCheck::Exists,
+        PathPrefix::Config,
+    ),
+    ("proxy_ssl_crl", PathCheck::Exists, PathPrefix::Config),
+    (
+        "proxy_ssl_password_file",
+        PathCheck::Exists,
+        PathPrefix::Config,
+    ),
+    (
+        "proxy_ssl_trusted_certificate",
+        PathCheck::Exists,
+        PathPrefix::Config,
+    ),
+    ("root", PathCheck::Advisory, PathPrefix::Prefix),
+    ("ssl_certificate", PathCheck::Exists, PathPrefix::Config),
+    ("ssl_certificate_key", PathCheck::Exists, PathPrefix::Config),
+    (
+        "ssl_client_certificate",
+        PathCheck::Exists,
+        PathPrefix::Config,
+    ),
+    ("ssl_crl", PathCheck::Exists, PathPrefix::Config),
+    ("ssl_dhparam", PathCheck::Exists, PathPrefix::Config),
+    ("ssl_password_file", PathCheck::Exists, PathPrefix::Config),
+    (
+        "ssl_session_ticket_key",
+        PathCheck::Exists,
+        PathPrefix::Config,
+    ),
+    ("ssl_stapling_file", PathCheck::Exists, PathPrefix::Config),
+    (
+        "ssl_trusted_certificate",
+        PathCheck::Exists,
+        PathPrefix::Config,
+    ),
+    (
+        "uwsgi_ssl_certificate",
+        PathCheck::Exists,
+        PathPrefix::Config,
+    ),
+    (
+        "uwsgi_ssl_certificate_key",
+        PathCheck::Exists,
+        PathPrefix::Config,
+    ),
+    ("uwsgi_ssl_crl", PathCheck::Exists, PathPrefix::Config),
+    (
+        "uwsgi_ssl_password_file",
+        PathCheck::Exists,
+        PathPrefix::Config,
+    ),
+    (
+        "uwsgi_ssl_trusted_certificate",
+        PathCheck::Exists,
+        PathPrefix::Config,
+    ),
+];
+
+/// How nginx checks the path named by a directive, or None for directives
+/// that name no path.
+pub fn path_check(directive: &str) -> Option<PathCheck> {
+    PATH_DIRECTIVES
+        .binary_search_by_key(&directive, |&(name, ..)| name)
+        .ok()
+        .map(|i| PATH_DIRECTIVES[i].1)
+}
+
+/// What nginx resolves the relative path named by a directive against, or
+/// None for directives that name no path.
+pub fn path_prefix(directive: &str) -> Option<PathPrefix> {
+    if directive == "include" {
+        return Some(PathPrefix::Config);
+    }
+    PATH_DIRECTIVES
+        .binary_search_by_key(&directive, |&(name, ..)| name)
+        .ok()
+        .map(|i| PATH_DIRECTIVES[i].2)
+}
+
+/// The path nginx uses for a path arg: absolute paths as they are, others
+/// relative to `prefix`.
+pub fn resolve_config_path(arg: &str, prefix: &Path) -> PathBuf {
+    let path = Path::new(arg);
+    if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        prefix.join(path)
+    }
+}
+
+/// A path named by a directive of a payload, see
+/// [`Payload::referenced_paths`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReferencedPath {
+    pub file: String,
+    pub line: usize,
+    pub directive: String,
+    /// The arg as written.
+    pub arg: String,
+    /// The arg resolved against the directory `prefix` stands for.
+    pub path: PathBuf,
+    pub check: PathCheck,
+    pub prefix: PathPrefix,
+}
+
+impl Payload {
+    /// The directory [`PathPrefix::Prefix`] paths are resolved against: the
+    /// one set with [`ParseOptions::prefix`](crate::ParseOptions::prefix),
+    /// or else the [config directory](Payload::config_dir).
+    pub fn prefix(&self) -> PathBuf {
+        match &self.prefix {
+            Some(prefix) => prefix.clone(),
+            None => self.config_dir(),
+        }
+    }
+
+    /// The directory of the root file, which includes and
+    /// [`PathPrefix::Config`] paths are resolved against.
+    pub fn config_dir(&self) -> PathBuf {
+        let root = self.config.first().map_or("", |root| root.file.as_str());
+        Path::new(root)
+            .parent()
+            .unwrap_or(Path::new(""))
+            .to_path_buf()
+    }
+
+    /// The files and directories the directives of the payload name, like
+    /// certificates and logs, resolved against the [config
+    /// directory](Payload::config_dir) or the [prefix](Payload::prefix).
+    /// Paths with variables, and logs going elsewhere than to a file, like
+    /// `access_log off` or `error_log stderr`, are left out.
+    pub fn referenced_paths(&self) -> Vec<ReferencedPath> {
+        let (config_dir, prefix) = (self.config_dir(), self.prefix());
+        let mut paths = Vec::new();
+        for d in self.directives() {
+            let directive = d.directive();
+            let (Some(check), Some(kind), Some(arg)) = (
+                path_check(&directive.directive),
+                path_prefix(&directive.directive),
+                directive.args.first(),
+            ) else {
+                continue;
+            };
+            if has_variables(arg) || !names_file(&directive.directive, arg) {
+                continue;
+            }
+            paths.push(ReferencedPath {
+                file: d.file_name().to_string(),
+                line: directive.line,
+                directive: directive.directive.clone(),
+                arg: arg.clone(),
+                path: match kind {
+                    PathPrefix::Config => resolve_config_path(arg, &config_dir),
+                    PathPrefix::Prefix => resolve_config_path(arg, &prefix),
+                },
+                check,
+                prefix: kind,
+            });
+        }
+        paths
+    }
+}
+
+// whether the arg of a path directive is a file rather than a log target
+// or a key given another way, like "syslog:server=..." or "data:..."
+fn names_file(directive: &str, arg: &str) -> bool {
+    match directive {
+        "access_log" => arg != "off" && !is_log_target(arg),
+        "error_log" => arg != "stderr" && !is_log_target(arg),
+        _ => !arg.starts_with("data:") && !arg.starts_with("engine:"),
+    }
+}
+
+fn is_log_target(arg: &str) -> bool {
+    arg.starts_with("syslog:") || arg.starts_with("memory:") || arg == "/dev/stderr"
+}
+
+// paths nginx would not find when loading the config, and advisory notes
+// for those only used when serving
+pub(crate) fn check_paths(payload: &Payload) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    for referenced in payload.referenced_paths() {
+        let (kind, severity, missing) = match referenced.check {
+            PathCheck::Exists => ("missing-path", Severity::Error, !referenced.path.exists()),
+            PathCheck::ParentExists => {
+                let parent = referenced.path.parent().unwrap_or(Path::new(""));
+                let missing = !parent.as_os_str().is_empty() && !parent.is_dir();
+                ("missing-path", Severity::Error, missing)
+            }
+            PathCheck::Advisory => (
+                "missing-served-path",
+                Severity::Info,
+                !referenced.path.exists(),
+            ),
+        };
+        if !missing {
+            continue;
+        }
+        let message = match referenced.check {
+            PathCheck::ParentExists => format!(
+                "the directory of \"{}\" in \"{}\" does not exist: {}",
+                referenced.arg,
+                referenced.directive,
+                referenced.path.display()
+            ),
+            _ => format!(
+                "\"{}\" in \"{}\" does not exist: {}",
+                referenced.arg,
+                referenced.directive,
+                referenced.path.display()
+            ),
+        };
+        findings.push(Finding::new(
+            kind,
+            severity,
+            message,
+            referenced.file,
+            referenced.line,
+        ));
+    }
+    findings
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::lint::LintOptions;
+    use crate::parse::{parse, ParseOptions};
+
+    #[test]
+    fn test_path_directives_sorted() {
+        assert!(PATH_DIRECTIVES.windows(2).all(|w| w[0].0 < w[1].0));
+        assert_eq!(path_check("ssl_certificate"), Some(PathCheck::Exists));
+        assert_eq!(path_check("listen"), None);
+        assert_eq!(path_prefix("include"), Some(PathPrefix::Config));
+        assert_eq!(path_prefix("ssl_certificate"), Some(PathPrefix::Config));
+        assert_eq!(path_prefix("error_log"), Some(PathPrefix::Prefix));
+        assert_eq!(path_prefix("listen"), None);
+    }
+
+    #[test]
+    fn test_referenced_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let conf = dir.path().join("conf");
+        let prefix = dir.path().join("prefix");
+        for sub in [&conf, &prefix, &prefix.join("logs")] {
+            fs::create_dir(sub).unwrap();
+        }
+        fs::write(conf.join("cert.pem"), "").unwrap();
+        fs::write(conf.join("extra.conf"), "").unwrap();
+        let key = dir.path().join("abs.key");
+        fs::write(&key, "").unwrap();
+        let source = format!(
+            "error_log stderr;
+http {{
+    access_log logs/access.log;
+    server {{
+        ssl_certificate cert.pem;
+        ssl_certificate_key {};
+        ssl_trusted_certificate $ssl_dir/ca.pem;
+        ssl_dhparam dh.pem;
+        root html;
+        access_log nologs/access.log;
+        access_log off;
+    }}
+}}
+include extra.conf;
+",
+            key.display()
+        );
+        fs::write(conf.join("nginx.conf"), source).unwrap();
+
+        // against the directory of the config
+        let payload = parse(conf.join("nginx.conf"), &ParseOptions::default());
+        assert_eq!(payload.prefix(), conf);
+        let paths: Vec<_> = payload
+            .referenced_paths()
+            .into_iter()
+            .map(|p| (p.line, p.directive, p.path, p.check))
+            .collect();
+        assert_eq!(
+            paths,
+            [
+                (
+                    3,
+                    "access_log".to_string(),
+                    conf.join("logs/access.log"),
+                    PathCheck::ParentExists
+                ),
+                (
+                    5,
+                    "ssl_certificate".to_string(),
+                    conf.join("cert.pem"),
+                    PathCheck::Exists
+                ),
+                (
+                    6,
+                    "ssl_certificate_key".to_string(),
+                    key.clone(),
+                    PathCheck::Exists
+                ),
+                (
+                    8,
+                    "ssl_dhparam".to_string(),
+                    conf.join("dh.pem"),
+                    PathCheck::Exists
+                ),
+                (
+                    9,
+                    "root".to_string(),
+                    conf.join("html"),
+                    PathCheck::Advisory
+                ),
+                (
+                    10,
+                    "access_log".to_string(),
+                    conf.join("nologs/access.log"),
+                    PathCheck::ParentExists
+                ),
+            ]
+        );
+
+        // against a prefix of its own
+        let options = ParseOptions::default().prefix(&prefix);
+        let payload = parse(conf.join("nginx.conf"), &options);
+        assert_eq!(payload.prefix(), prefix);
+        assert_eq!(payload.config_dir(), conf);
+        // includes and certificates stay relative to the config
+        assert_eq!(payload.errors, []);
+        assert_eq!(payload.config.len(), 2);
+        let paths: Vec<_> = payload
+            .referenced_paths()
+            .into_iter()
+            .map(|p| (p.line, p.path, p.prefix))
+            .take(2)
+            .collect();
+        assert_eq!(
+            paths,
+            [
+                (3, prefix.join("logs/access.log"), PathPrefix::Prefix),
+                (5, conf.join("cert.pem"), PathPrefix::Config),
+            ]
+        );
+        let options = LintOptions::default().check_paths(true);
+        let findings = payload.lint_with(&options);
+        let findings: Vec<_> = findings
+            .iter()
+            .filter(|f| f.kind.ends_with("-path"))
+            .map(|f| (f.line, f.kind.as_str(), f.severity))
+            .collect();
+        assert_eq!(
+            findings,
+            [
+                (8, "missing-path", Severity::Error),
+                (9, "missing-served-path", Severity::Info),
+                (10, "missing-path", Severity::Error),
+            ]
+        );
+        assert!(payload.lint().iter().all(|f| !f.kind.ends_with("-path")));
+
+        assert_eq!(
+            resolve_config_path("/etc/ssl/a.pem", &prefix),
+            PathBuf::from("/etc/ssl/a.pem")
+        );
+        assert_eq!(resolve_config_path("a.pem", &prefix), prefix.join("a.pem"));
+    }
+}
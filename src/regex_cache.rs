@@ -0,0 +1,391 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use regex::Regex;
+
+use crate::findings::Findings;
+use crate::lint::{directive_regexes, Finding, Severity};
+use crate::map_blocks::entries;
+use crate::parse::Payload;
+
+const DEFAULT_CAPACITY: usize = 1024;
+
+/// Compiled regexes of configs by pattern, so each is compiled once however
+/// often it is matched or checked. Holds at most `capacity` patterns,
+/// dropping the oldest first. Patterns that fail to compile are kept too,
+/// with their error.
+pub struct RegexCache {
+    capacity: usize,
+    inner: Mutex<Inner>,
+}
+
+#[derive(Default)]
+struct Inner {
+    compiled: HashMap<String, Result<Arc<Regex>, RegexError>>,
+    // patterns in the order they were added, to drop the oldest
+    order: VecDeque<String>,
+    hits: usize,
+    misses: usize,
+}
+
+/// A regex of a config that could not be compiled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegexError {
+    pub pattern: String,
+    pub message: String,
+    /// Whether the pattern uses a PCRE feature the regex crate lacks, like
+    /// look-around, which nginx takes but cannot be matched here.
+    pub unsupported: bool,
+}
+
+impl fmt::Display for RegexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.unsupported {
+            true => write!(
+                f,
+                "unsupported pattern \"{}\": {}",
+                self.pattern, self.message
+            ),
+            false => write!(f, "invalid regex \"{}\": {}", self.pattern, self.message),
+        }
+    }
+}
+
+impl std::error::Error for RegexError {}
+
+impl RegexCache {
+    pub fn new(capacity: usize) -> Self {
+        RegexCache {
+            capacity: capacity.max(1),
+            inner: Mutex::default(),
+        }
+    }
+
+    /// The regex of `pattern`, matching case-insensitively for `~*` and
+    /// regex server names.
+    pub fn get(&self, pattern: &str, case_insensitive: bool) -> Result<Arc<Regex>, RegexError> {
+        let key = match case_insensitive {
+            true => format!("(?i){}", pattern),
+            false => pattern.to_string(),
+        };
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(compiled) = inner.compiled.get(&key).cloned() {
+            inner.hits += 1;
+            return compiled;
+        }
+        inner.misses += 1;
+        let compiled = compile(pattern, &key);
+        if inner.order.len() >= self.capacity {
+            if let Some(oldest) = inner.order.pop_front() {
+                inner.compiled.remove(&oldest);
+            }
+        }
+        inner.order.push_back(key.clone());
+        inner.compiled.insert(key, compiled.clone());
+        compiled
+    }
+
+    /// Number of patterns held.
+    pub fn len(&self) -> usize {
+        self.inner
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .order
+            .len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Lookups answered from the cache, and those that compiled.
+    pub fn stats(&self) -> (usize, usize) {
+        let inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        (inner.hits, inner.misses)
+    }
+}
+
+impl Default for RegexCache {
+    fn default() -> Self {
+        RegexCache::new(DEFAULT_CAPACITY)
+    }
+}
+
+impl fmt::Debug for RegexCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RegexCache")
+            .field("capacity", &self.capacity)
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+fn compile(pattern: &str, source: &str) -> Result<Arc<Regex>, RegexError> {
+    if let Some(feature) = pcre_only(pattern) {
+        return Err(RegexError {
+            pattern: pattern.to_string(),
+            message: format!("{} is not supported", feature),
+            unsupported: true,
+        });
+    }
+    Regex::new(source).map(Arc::new).map_err(|e| {
+        let message = match &e {
+            // the last line of the caret diagram says what is wrong
+            regex::Error::Syntax(s) => s
+                .lines()
+                .find_map(|l| l.strip_prefix("error: "))
+                .unwrap_or(s)
+                .to_string(),
+            _ => e.to_string(),
+        };
+        RegexError {
+            pattern: pattern.to_string(),
+            message,
+            unsupported: false,
+        }
+    })
+}
+
+// the PCRE feature of a pattern that the regex crate has no equivalent of,
+// outside of escapes and classes
+fn pcre_only(pattern: &str) -> Option<&'static str> {
+    let bytes = pattern.as_bytes();
+    let mut in_class = false;
+    let mut i = 0;
+    while i < bytes.len() {
+        let rest = &pattern[i..];
+        match bytes[i] {
+            b'\\' => {
+                match bytes.get(i + 1) {
+                    Some(b'1'..=b'9' | b'g' | b'k') if !in_class => return Some("a backreference"),
+                    Some(b'K') if !in_class => return Some("\\K"),
+                    Some(b'Z' | b'G') if !in_class => return Some("an anchor like \\Z or \\G"),
+                    Some(b'Q') => return Some("quoting with \\Q...\\E"),
+                    Some(b'h' | b'H' | b'R' | b'X' | b'C' | b'N') => {
+                        return Some("an escape like \\h, \\R or \\X")
+                    }
+                    Some(b'e' | b'c' | b'o') => return Some("a character escape like \\e or \\c"),
+                    _ => {}
+                }
+                i += 2;
+                continue;
+            }
+            b'[' if !in_class => in_class = true,
+            b']' if in_class => in_class = false,
+            _ if in_class => {}
+            b'(' => {
+                for (prefix, feature) in [
+                    ("(?=", "look-ahead"),
+                    ("(?!", "look-ahead"),
+                    ("(?<=", "look-behind"),
+                    ("(?<!", "look-behind"),
+                    ("(?>", "an atomic group"),
+                    ("(?(", "a conditional group"),
+                    ("(?|", "a branch reset group"),
+                    ("(?R", "recursion"),
+                    ("(?&", "recursion"),
+                    ("(?P>", "recursion"),
+                    ("(?P=", "a backreference"),
+                    ("(?'", "a group named with quotes"),
+                    ("(?#", "a comment group"),
+                    ("(?C", "a callout"),
+                    ("(?J", "the J flag"),
+                    ("(?^", "the ^ flag"),
+                    ("(*", "a verb"),
+                ] {
+                    if rest.starts_with(prefix) {
+                        return Some(feature);
+                    }
+                }
+            }
+            b'*' | b'+' | b'?' | b'}' if bytes.get(i + 1) == Some(&b'+') => {
+                // "(?+" is no quantifier, and neither is the "}" of "${var}"
+                let before = i.checked_sub(1).map(|j| bytes[j]);
+                let quantifier = match bytes[i] {
+                    b'?' => before != Some(b'('),
+                    b'}' => before.is_some_and(|b| b.is_ascii_digit() || b == b','),
+                    _ => true,
+                };
+                if quantifier {
+                    return Some("a possessive quantifier");
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+impl Payload {
+    /// Compiles the regexes of the config through `cache`: those of
+    /// locations, ifs, rewrites, server names and map keys. Patterns that
+    /// do not compile are errors, as nginx refuses them too, and patterns
+    /// using PCRE features the regex crate lacks are warnings.
+    pub fn check_regexes(&self, cache: &RegexCache) -> Findings {
+        let mut findings = Findings::new();
+        let mut check = |pattern: &str, case_insensitive: bool, file: &str, line: usize| {
+            if let Err(e) = cache.get(pattern, case_insensitive) {
+                let severity = match e.unsupported {
+                    true => Severity::Warning,
+                    false => Severity::Error,
+                };
+                findings.push(Finding::new(
+                    "invalid-regex",
+                    severity,
+                    e.to_string(),
+                    file,
+                    line,
+                ));
+            }
+        };
+        for d in self.directives() {
+            let (stmt, file) = (d.directive(), d.file_name());
+            for (i, start, case_insensitive) in directive_regexes(stmt) {
+                check(&stmt.args[i][start..], case_insensitive, file, stmt.line);
+            }
+            let Some(block) = stmt.block.as_ref().filter(|_| stmt.directive == "map") else {
+                continue;
+            };
+            for entry in entries(self, block, file, &mut Vec::new()) {
+                let key = entry.directive.directive.as_str();
+                if let Some(pattern) = key.strip_prefix("~*") {
+                    check(pattern, true, entry.file, entry.directive.line);
+                } else if let Some(pattern) = key.strip_prefix('~') {
+                    check(pattern, false, entry.file, entry.directive.line);
+                }
+            }
+        }
+        findings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crossplane::Crossplane;
+    use crate::parse::{parse_source, ParseOptions};
+
+    #[test]
+    fn test_check_regexes() {
+        let source = r#"http {
+    map $uri $m {
+        "~^/(?<id>\d+)$"  $id;
+        "~*^/(a|b"         open;
+    }
+    server {
+        server_name ~^(www\.)?(?<domain>.+)$ example.com;
+        location ~ "^/(unclosed" {
+        }
+        location ~* "^/(?!admin)" {
+        }
+        location ~ \.php$ {
+            rewrite "^/(a)\1$" /b;
+        }
+        location ~ \.php$ {
+            if ($http_x ~ "a++") {
+            }
+        }
+    }
+}
+"#;
+        let payload = parse_source(source, &ParseOptions::default());
+        assert_eq!(payload.errors, []);
+        let cache = RegexCache::default();
+        let messages: Vec<_> = payload
+            .check_regexes(&cache)
+            .iter()
+            .map(|f| format!("{}:{} {} [{}]", f.severity, f.line, f.message, f.kind))
+            .collect();
+        assert_eq!(
+            messages,
+            [
+                "error:4 invalid regex \"^/(a|b\": unclosed group [invalid-regex]",
+                "error:8 invalid regex \"^/(unclosed\": unclosed group [invalid-regex]",
+                "warning:10 unsupported pattern \"^/(?!admin)\": look-ahead is not supported \
+                 [invalid-regex]",
+                "warning:13 unsupported pattern \"^/(a)\\1$\": a backreference is not supported \
+                 [invalid-regex]",
+                "warning:16 unsupported pattern \"a++\": a possessive quantifier is not \
+                 supported [invalid-regex]",
+            ]
+        );
+
+        // the repeated location is compiled once, and so is everything when
+        // checking again
+        assert_eq!(cache.stats(), (1, 8));
+        payload.check_regexes(&cache);
+        assert_eq!(cache.stats(), (10, 8));
+        assert_eq!(cache.len(), 8);
+    }
+
+    #[test]
+    fn test_strict_regexes() {
+        let source = "http {\n    server {\n        location ~ \"^/(a\" {\n        }\n        \
+                      location ~ \"^/(?=b)\" {\n        }\n        location ~ ^/a\\Z {\n        \
+                      }\n        location ~ \\Qa.b\\E {\n        }\n        \
+                      location ~ \"(?'n'a)\" {\n        }\n    }\n}\n";
+        let crossplane = Crossplane::builder()
+            .parse(ParseOptions::default().strict(true).catch_errors(true))
+            .build();
+        let payload = crossplane.parse_str(source, "nginx.conf");
+        let errors: Vec<_> = payload.errors.iter().map(|e| e.to_string()).collect();
+        assert_eq!(
+            errors,
+            ["invalid regex \"^/(a\": unclosed group in nginx.conf:3"]
+        );
+
+        // the location strict parsing refused is left out of the payload,
+        // lint finds the other one in the cache strict parsing filled
+        let cache = &crossplane.parse_options().regex_cache;
+        assert_eq!(cache.stats(), (0, 5));
+        let findings: Vec<_> = crossplane
+            .lint(&payload)
+            .iter()
+            .filter(|f| f.kind == "invalid-regex")
+            .map(|f| (f.severity, f.line))
+            .collect();
+        // PCRE the regex crate lacks is only a warning
+        let warnings: Vec<_> = [5, 7, 9, 11]
+            .into_iter()
+            .map(|line| (Severity::Warning, line))
+            .collect();
+        assert_eq!(findings, warnings);
+        assert_eq!(cache.stats(), (4, 5));
+    }
+
+    #[test]
+    fn test_regex_cache() {
+        let cache = RegexCache::new(2);
+        let regex = cache.get("^/API/", true).unwrap();
+        assert!(regex.is_match("/api/v1"));
+        assert!(!cache.get("^/API/", false).unwrap().is_match("/api/v1"));
+        assert!(Arc::ptr_eq(&regex, &cache.get("^/API/", true).unwrap()));
+        assert_eq!(cache.stats(), (1, 2));
+
+        // the oldest pattern is dropped for a third one
+        cache.get("x", false).unwrap();
+        assert_eq!(cache.len(), 2);
+        cache.get("^/API/", true).unwrap();
+        assert_eq!(cache.stats(), (1, 4));
+
+        for pattern in ["[(?!]", r"\(?=", r"(?P<n>a)\d+", "a{2}"] {
+            assert!(cache.get(pattern, false).is_ok(), "{}", pattern);
+        }
+        assert!(cache.get("a{2,}+", false).unwrap_err().unsupported);
+
+        // PCRE syntax the regex crate fails on is unsupported, not invalid
+        for pattern in [
+            r"^/a\Z",
+            r"\Qa.b\E",
+            "(?'n'a)",
+            r"\h+",
+            "(?#note)a",
+            "(*UTF8)a",
+        ] {
+            let error = cache.get(pattern, false).unwrap_err();
+            assert!(error.unsupported, "{}", error);
+        }
+    }
+}
@@ -0,0 +1,260 @@
+use std::collections::HashSet;
+
+use crate::parse::{Directive, Payload};
+use crate::variables::variable_refs;
+
+// directives whose first arg may name an upstream
+const PASS_DIRECTIVES: [&str; 5] = [
+    "fastcgi_pass",
+    "grpc_pass",
+    "proxy_pass",
+    "scgi_pass",
+    "uwsgi_pass",
+];
+
+/// An arg that references the renamed upstream, as it was before the rename.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UpstreamRef {
+    pub file: String,
+    pub line: usize,
+    pub directive: String,
+    pub arg: String,
+}
+
+/// What [`Payload::rename_upstream`] changed, and what it left alone because
+/// the reference could only be resolved at run time.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RenameReport {
+    pub changed: Vec<UpstreamRef>,
+    /// `map` or `set` values naming the upstream, and the `*_pass` targets
+    /// built from the variables they set.
+    pub skipped: Vec<UpstreamRef>,
+}
+
+impl Payload {
+    /// Renames the upstream `old` to `new`, updating the `upstream` block and
+    /// every `*_pass` directive pointing at it.
+    pub fn rename_upstream(&mut self, old: &str, new: &str) -> RenameReport {
+        let mut variables = HashSet::new();
+        for config in &self.config {
+            naming_variables(&config.parsed, old, &mut variables);
+        }
+        let mut renamer = Renamer {
+            old,
+            new,
+            variables,
+            file: "",
+            report: RenameReport::default(),
+        };
+        for config in &mut self.config {
+            renamer.file = &config.file;
+            renamer.rename_block(&mut config.parsed, false);
+        }
+        let report = renamer.report;
+        if !report.changed.is_empty() {
+            self.mark_changed();
+        }
+        report
+    }
+}
+
+struct Renamer<'a> {
+    old: &'a str,
+    new: &'a str,
+    // the variables set or mapped to a value naming the upstream
+    variables: HashSet<String>,
+    file: &'a str,
+    report: RenameReport,
+}
+
+impl Renamer<'_> {
+    fn rename_block(&mut self, block: &mut [Directive], in_map: bool) {
+        for directive in block {
+            if in_map {
+                // map values are strings, they only name the upstream when
+                // used in a *_pass through a variable
+                if directive.args.iter().any(|arg| mentions(arg, self.old)) {
+                    self.record_skip(directive);
+                }
+                continue;
+            }
+
+            match directive.directive.as_str() {
+                "upstream" if directive.args.first().is_some_and(|a| a == self.old) => {
+                    self.record_change(directive);
+                    directive.args[0] = self.new.to_string();
+                }
+                name if PASS_DIRECTIVES.contains(&name) => {
+                    if let Some(arg) = directive.args.first() {
+                        let (scheme, host, rest) = split_target(arg);
+                        if host == self.old {
+                            let renamed = format!("{}{}{}", scheme, self.new, rest);
+                            self.record_change(directive);
+                            directive.args[0] = renamed;
+                        } else if variable_refs(host).any(|v| self.variables.contains(v)) {
+                            self.record_skip(directive);
+                        }
+                    }
+                }
+                "set" if directive.args.get(1).is_some_and(|a| mentions(a, self.old)) => {
+                    self.record_skip(directive);
+                }
+                _ => {}
+            }
+
+            let in_map = directive.directive == "map";
+            if let Some(children) = &mut directive.block {
+                self.rename_block(children, in_map);
+            }
+        }
+    }
+
+    fn record_change(&mut self, directive: &Directive) {
+        let reference = self.reference(directive);
+        self.report.changed.push(reference);
+    }
+
+    fn record_skip(&mut self, directive: &Directive) {
+        let reference = self.reference(directive);
+        self.report.skipped.push(reference);
+    }
+
+    fn reference(&self, directive: &Directive) -> UpstreamRef {
+        UpstreamRef {
+            file: self.file.to_string(),
+            line: directive.line,
+            directive: directive.directive.clone(),
+            arg: directive.args.join(" "),
+        }
+    }
+}
+
+// collect the variables that `set` or a `map` give a value naming the
+// upstream, which a *_pass may then point at
+fn naming_variables(block: &[Directive], name: &str, variables: &mut HashSet<String>) {
+    for directive in block {
+        let variable = match directive.directive.as_str() {
+            "set" if directive.args.get(1).is_some_and(|a| mentions(a, name)) => {
+                directive.args.first()
+            }
+            "map"
+                if directive
+                    .block
+                    .iter()
+                    .flatten()
+                    .any(|entry| entry.args.iter().any(|arg| mentions(arg, name))) =>
+            {
+                directive.args.get(1)
+            }
+            _ => None,
+        };
+        if let Some(variable) = variable.and_then(|v| v.strip_prefix('$')) {
+            variables.insert(variable.to_string());
+        }
+        if let Some(children) = &directive.block {
+            naming_variables(children, name, variables);
+        }
+    }
+}
+
+// split a pass target like "http://name/uri" into scheme, host and the rest
+fn split_target(arg: &str) -> (&str, &str, &str) {
+    let (scheme, target) = match arg.find("://") {
+        Some(i) => arg.split_at(i + 3),
+        None => ("", arg),
+    };
+    let end = target.find('/').unwrap_or(target.len());
+    let (host, rest) = target.split_at(end);
+    (scheme, host, rest)
+}
+
+// whether a value names the upstream as a host, e.g. "backend" or
+// "http://backend/api"
+fn mentions(value: &str, name: &str) -> bool {
+    split_target(value).1 == name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::build::{build, BuildOptions};
+    use crate::parse::{parse_source, ParseOptions};
+
+    #[test]
+    fn test_rename_upstream() {
+        let source = "http {
+    upstream backend_v1 {
+        server 10.0.0.1:8080;
+    }
+    upstream backend_v10 {
+        server 10.0.0.2:8080;
+    }
+    map $uri $pool {
+        default backend_v1;
+        /v10 backend_v10;
+    }
+    server {
+        location / {
+            proxy_pass http://backend_v1;
+        }
+        location /api {
+            proxy_pass https://backend_v1/api/;
+        }
+        location /v10 {
+            proxy_pass http://backend_v10;
+        }
+        location ~ \\.php$ {
+            fastcgi_pass backend_v1;
+        }
+        location /dyn {
+            set $target backend_v1;
+            proxy_pass http://$pool;
+        }
+        location /target {
+            proxy_pass http://${target}/;
+        }
+        location /host {
+            proxy_pass http://$host;
+        }
+    }
+}
+";
+        let mut payload = parse_source(source, &ParseOptions::default());
+
+        let report = payload.rename_upstream("backend_v1", "backend_v2");
+        let lines = |refs: &[UpstreamRef]| {
+            refs.iter()
+                .map(|r| format!("{}:{} {} {}", r.file, r.line, r.directive, r.arg))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            lines(&report.changed),
+            [
+                "nginx.conf:2 upstream backend_v1",
+                "nginx.conf:14 proxy_pass http://backend_v1",
+                "nginx.conf:17 proxy_pass https://backend_v1/api/",
+                "nginx.conf:23 fastcgi_pass backend_v1",
+            ]
+        );
+        assert_eq!(
+            lines(&report.skipped),
+            [
+                "nginx.conf:9 default backend_v1",
+                "nginx.conf:26 set $target backend_v1",
+                "nginx.conf:27 proxy_pass http://$pool",
+                "nginx.conf:30 proxy_pass http://${target}/",
+            ]
+        );
+
+        let built = build(&payload.config[0].parsed, &BuildOptions::default());
+        assert!(built.contains("upstream backend_v2 {"));
+        assert!(built.contains("proxy_pass http://backend_v2;"));
+        assert!(built.contains("proxy_pass https://backend_v2/api/;"));
+        assert!(built.contains("fastcgi_pass backend_v2;"));
+        assert!(built.contains("upstream backend_v10 {"));
+        assert!(built.contains("proxy_pass http://backend_v10;"));
+        assert!(built.contains("default backend_v1;"));
+        assert!(built.contains("set $target backend_v1;"));
+        assert!(built.contains("proxy_pass http://$host;"));
+    }
+}
@@ -0,0 +1,325 @@
+use crate::context::ContextId;
+use crate::loader::file_contexts;
+use crate::parse::{enter_block_ctx, Directive, Payload};
+
+/// Which directives a [`RewriteRule`] applies to: those of the given name
+/// that match every other condition set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Selector {
+    pub(crate) directive: String,
+    pub(crate) args: Option<Vec<String>>,
+    pub(crate) context: Option<ContextId>,
+    pub(crate) containing: Option<String>,
+}
+
+impl Selector {
+    pub fn new(directive: impl Into<String>) -> Self {
+        Selector {
+            directive: directive.into(),
+            args: None,
+            context: None,
+            containing: None,
+        }
+    }
+
+    /// Only directives with exactly these args.
+    pub fn args(mut self, args: &[&str]) -> Self {
+        self.args = Some(args.iter().map(|arg| arg.to_string()).collect());
+        self
+    }
+
+    /// Only directives in this context, following includes back to the
+    /// blocks including their file.
+    pub fn context(mut self, context: ContextId) -> Self {
+        self.context = Some(context);
+        self
+    }
+
+    /// Only blocks with a directive of this name among their children.
+    pub fn containing(mut self, directive: impl Into<String>) -> Self {
+        self.containing = Some(directive.into());
+        self
+    }
+
+    fn matches(&self, directive: &Directive, ctx: &[String]) -> bool {
+        directive.directive == self.directive
+            && self
+                .args
+                .as_ref()
+                .is_none_or(|args| *args == directive.args)
+            && self
+                .context
+                .as_ref()
+                .is_none_or(|context| *context == ContextId::from_path(ctx))
+            && self.containing.as_ref().is_none_or(|name| {
+                directive
+                    .block
+                    .iter()
+                    .flatten()
+                    .any(|child| child.directive == *name)
+            })
+    }
+}
+
+/// What a [`RewriteRule`] does to the directives it matches.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RewriteAction {
+    ReplaceArgs(Vec<String>),
+    InsertBefore(Directive),
+    InsertAfter(Directive),
+    Delete,
+    /// Move the directive to the end of the block of the given directive,
+    /// put in its place.
+    WrapInBlock(Directive),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RewriteRule {
+    pub matcher: Selector,
+    pub action: RewriteAction,
+}
+
+/// A directive a rule applied to, as it was before the rewrite.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RewriteMatch {
+    /// Index of the rule in the rules given.
+    pub rule: usize,
+    pub file: String,
+    pub line: usize,
+    pub directive: String,
+}
+
+/// A directive several rules matched, of which only the first applied.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RewriteConflict {
+    pub file: String,
+    pub line: usize,
+    pub directive: String,
+    /// Indexes of the rules that matched, the applied one first.
+    pub rules: Vec<usize>,
+}
+
+/// What [`Payload::apply_rules`] changed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RewriteReport {
+    pub applied: Vec<RewriteMatch>,
+    pub conflicts: Vec<RewriteConflict>,
+    /// Indexes of the rules that matched no directive.
+    pub unmatched: Vec<usize>,
+}
+
+impl Payload {
+    /// Applies the rules to the directives of the payload in one pass,
+    /// file by file and each block directive before its children. The
+    /// first rule matching a directive applies to it and the others are
+    /// reported as conflicting. Rules match the directives as they were
+    /// parsed: directives a rule adds are not matched, and the children of
+    /// deleted directives are not visited.
+    pub fn apply_rules(&mut self, rules: &[RewriteRule]) -> RewriteReport {
+        let contexts = file_contexts(self);
+        let mut rewriter = Rewriter {
+            rules,
+            file: "",
+            report: RewriteReport::default(),
+        };
+        for (i, config) in self.config.iter_mut().enumerate() {
+            rewriter.file = &config.file;
+            let ctx = contexts.get(&i).cloned().unwrap_or_default();
+            rewriter.rewrite_block(&mut config.parsed, &ctx);
+        }
+
+        let mut report = rewriter.report;
+        report.unmatched = (0..rules.len())
+            .filter(|&i| !report.applied.iter().any(|m| m.rule == i))
+            .filter(|&i| !report.conflicts.iter().any(|c| c.rules.contains(&i)))
+            .collect();
+        if !report.applied.is_empty() {
+            self.mark_changed();
+        }
+        report
+    }
+}
+
+struct Rewriter<'a> {
+    rules: &'a [RewriteRule],
+    file: &'a str,
+    report: RewriteReport,
+}
+
+impl Rewriter<'_> {
+    fn rewrite_block(&mut self, block: &mut Vec<Directive>, ctx: &[String]) {
+        for mut directive in std::mem::take(block) {
+            let matched: Vec<usize> = (0..self.rules.len())
+                .filter(|&i| self.rules[i].matcher.matches(&directive, ctx))
+                .collect();
+            let Some(&rule) = matched.first() else {
+                self.rewrite_children(&mut directive, ctx);
+                block.push(directive);
+                continue;
+            };
+            self.report.applied.push(RewriteMatch {
+                rule,
+                file: self.file.to_string(),
+                line: directive.line,
+                directive: directive.directive.clone(),
+            });
+            if matched.len() > 1 {
+                self.report.conflicts.push(RewriteConflict {
+                    file: self.file.to_string(),
+                    line: directive.line,
+                    directive: directive.directive.clone(),
+                    rules: matched,
+                });
+            }
+
+            // added directives are in the same if as the matched one
+            let added = |added: &Directive| Directive {
+                in_if: directive.in_if,
+                ..added.clone()
+            };
+            match &self.rules[rule].action {
+                RewriteAction::ReplaceArgs(args) => {
+                    self.rewrite_children(&mut directive, ctx);
+                    directive.args = args.clone();
+                    block.push(directive);
+                }
+                RewriteAction::InsertBefore(before) => {
+                    block.push(added(before));
+                    self.rewrite_children(&mut directive, ctx);
+                    block.push(directive);
+                }
+                RewriteAction::InsertAfter(after) => {
+                    let after = added(after);
+                    self.rewrite_children(&mut directive, ctx);
+                    block.push(directive);
+                    block.push(after);
+                }
+                RewriteAction::Delete => {}
+                RewriteAction::WrapInBlock(wrapper) => {
+                    let mut wrapper = added(wrapper);
+                    self.rewrite_children(&mut directive, ctx);
+                    wrapper.block.get_or_insert_with(Vec::new).push(directive);
+                    block.push(wrapper);
+                }
+            }
+        }
+    }
+
+    fn rewrite_children(&mut self, directive: &mut Directive, ctx: &[String]) {
+        if let Some(children) = &mut directive.block {
+            let inner = enter_block_ctx(&directive.directive, ctx);
+            self.rewrite_block(children, &inner);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::build::{build, BuildOptions};
+    use crate::parse::{parse_source, ParseOptions};
+
+    fn simple(name: &str, args: &[&str]) -> Directive {
+        Directive {
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+            ..Directive::new(name, 0)
+        }
+    }
+
+    #[test]
+    fn test_apply_rules() {
+        let source = "http {
+    ssl_protocols TLSv1 TLSv1.1;
+    server {
+        ssl_protocols SSLv3;
+        location /api {
+            proxy_pass http://backend;
+        }
+        location /static {
+            root /srv;
+        }
+    }
+}
+";
+        let mut payload = parse_source(source, &ParseOptions::default());
+        let rules = [
+            RewriteRule {
+                matcher: Selector::new("ssl_protocols"),
+                action: RewriteAction::ReplaceArgs(vec![
+                    "TLSv1.2".to_string(),
+                    "TLSv1.3".to_string(),
+                ]),
+            },
+            RewriteRule {
+                matcher: Selector::new("gzip").args(&["on"]),
+                action: RewriteAction::Delete,
+            },
+            RewriteRule {
+                matcher: Selector::new("ssl_protocols").context(ContextId::HttpServer),
+                action: RewriteAction::Delete,
+            },
+        ];
+        let generation = payload.generation();
+        let report = payload.apply_rules(&rules);
+
+        let applied: Vec<_> = report.applied.iter().map(|m| (m.rule, m.line)).collect();
+        assert_eq!(applied, [(0, 2), (0, 4)]);
+        assert_eq!(
+            report.conflicts,
+            [RewriteConflict {
+                file: "nginx.conf".to_string(),
+                line: 4,
+                directive: "ssl_protocols".to_string(),
+                rules: vec![0, 2],
+            }]
+        );
+        assert_eq!(report.unmatched, [1]);
+        assert!(payload.generation() > generation);
+        let protocols: Vec<_> = payload
+            .find("ssl_protocols")
+            .iter()
+            .map(|d| d.directive().args.join(" "))
+            .collect();
+        assert_eq!(protocols, ["TLSv1.2 TLSv1.3", "TLSv1.2 TLSv1.3"]);
+
+        let rules = [
+            RewriteRule {
+                matcher: Selector::new("location").containing("proxy_pass"),
+                action: RewriteAction::InsertBefore(simple("include", &["headers.conf"])),
+            },
+            RewriteRule {
+                matcher: Selector::new("proxy_pass").context(ContextId::HttpLocation),
+                action: RewriteAction::InsertAfter(simple("proxy_buffering", &["off"])),
+            },
+            RewriteRule {
+                matcher: Selector::new("root"),
+                action: RewriteAction::WrapInBlock(Directive {
+                    block: Some(vec![]),
+                    ..simple("limit_except", &["GET"])
+                }),
+            },
+        ];
+        let report = payload.apply_rules(&rules);
+        assert_eq!(report.conflicts, []);
+        assert_eq!(report.unmatched, Vec::<usize>::new());
+        assert_eq!(
+            build(&payload.config[0].parsed, &BuildOptions::default()),
+            "http {
+    ssl_protocols TLSv1.2 TLSv1.3;
+    server {
+        ssl_protocols TLSv1.2 TLSv1.3;
+        include headers.conf;
+        location /api {
+            proxy_pass http://backend;
+            proxy_buffering off;
+        }
+        location /static {
+            limit_except GET {
+                root /srv;
+            }
+        }
+    }
+}"
+        );
+    }
+}
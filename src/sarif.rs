@@ -0,0 +1,180 @@
+use serde_json::{json, Value};
+
+use crate::lint::{Finding, Severity};
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+impl Finding {
+    /// The finding as a SARIF 2.1.0 `result` object.
+    pub fn to_sarif(&self) -> Value {
+        let mut region = json!({ "startLine": self.line });
+        if let Some(column) = self.column {
+            region["startColumn"] = json!(column);
+        }
+        json!({
+            "ruleId": self.kind,
+            "level": sarif_level(self.severity),
+            "message": { "text": self.message },
+            "locations": [{
+                "physicalLocation": {
+                    "artifactLocation": { "uri": self.file },
+                    "region": region,
+                },
+            }],
+        })
+    }
+}
+
+/// A SARIF 2.1.0 log with a single run holding `findings`.
+pub fn findings_to_sarif(findings: &[Finding]) -> String {
+    let mut rules: Vec<&str> = findings.iter().map(|f| f.kind.as_str()).collect();
+    rules.sort_unstable();
+    rules.dedup();
+
+    let log = json!({
+        "$schema": SARIF_SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": env!("CARGO_PKG_NAME"),
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules.iter().map(|id| json!({ "id": id })).collect::<Vec<_>>(),
+                },
+            },
+            "results": findings.iter().map(Finding::to_sarif).collect::<Vec<_>>(),
+        }],
+    });
+    serde_json::to_string_pretty(&log).unwrap()
+}
+
+fn sarif_level(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Info => "note",
+        Severity::Warning => "warning",
+        Severity::Error => "error",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn findings() -> [Finding; 2] {
+        let mut unknown = Finding::new(
+            "unknown-variable",
+            Severity::Error,
+            "unknown variable \"$requets\" in log format \"main\"",
+            "conf.d/logs.conf",
+            2,
+        );
+        unknown.column = Some(28);
+        [
+            unknown,
+            Finding::new(
+                "if-hazard",
+                Severity::Warning,
+                "\"add_header\" inside \"if\" stops the headers of enclosing blocks \
+                 from being inherited",
+                "nginx.conf",
+                7,
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_findings_to_sarif() {
+        let sarif = findings_to_sarif(&findings());
+        let log: Value = serde_json::from_str(&sarif).unwrap();
+        let schema: Value =
+            serde_json::from_str(include_str!("../schemas/sarif-2.1.0-subset.schema.json"))
+                .unwrap();
+        let validator = jsonschema::validator_for(&schema).unwrap();
+        assert!(validator.is_valid(&log));
+        assert!(!validator.is_valid(&json!({ "version": "2.0.0", "runs": [] })));
+
+        assert_eq!(
+            sarif,
+            format!(
+                r#"{{
+  "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+  "runs": [
+    {{
+      "results": [
+        {{
+          "level": "error",
+          "locations": [
+            {{
+              "physicalLocation": {{
+                "artifactLocation": {{
+                  "uri": "conf.d/logs.conf"
+                }},
+                "region": {{
+                  "startColumn": 28,
+                  "startLine": 2
+                }}
+              }}
+            }}
+          ],
+          "message": {{
+            "text": "unknown variable \"$requets\" in log format \"main\""
+          }},
+          "ruleId": "unknown-variable"
+        }},
+        {{
+          "level": "warning",
+          "locations": [
+            {{
+              "physicalLocation": {{
+                "artifactLocation": {{
+                  "uri": "nginx.conf"
+                }},
+                "region": {{
+                  "startLine": 7
+                }}
+              }}
+            }}
+          ],
+          "message": {{
+            "text": "\"add_header\" inside \"if\" stops the headers of enclosing blocks from being inherited"
+          }},
+          "ruleId": "if-hazard"
+        }}
+      ],
+      "tool": {{
+        "driver": {{
+          "name": "rust-crossplane",
+          "rules": [
+            {{
+              "id": "if-hazard"
+            }},
+            {{
+              "id": "unknown-variable"
+            }}
+          ],
+          "version": "{}"
+        }}
+      }}
+    }}
+  ],
+  "version": "2.1.0"
+}}"#,
+                env!("CARGO_PKG_VERSION")
+            )
+        );
+    }
+
+    // the subset above is checked on every run; the whole OASIS schema is
+    // not vendored, fetch it from the URL of SARIF_SCHEMA into
+    // schemas/sarif-schema-2.1.0.json and run the ignored tests
+    #[test]
+    #[ignore = "needs schemas/sarif-schema-2.1.0.json"]
+    fn test_findings_to_sarif_official_schema() {
+        let schema = std::fs::read_to_string("schemas/sarif-schema-2.1.0.json").unwrap();
+        let schema: Value = serde_json::from_str(&schema).unwrap();
+        let validator = jsonschema::validator_for(&schema).unwrap();
+        let log: Value = serde_json::from_str(&findings_to_sarif(&findings())).unwrap();
+        let errors: Vec<String> = validator.iter_errors(&log).map(|e| e.to_string()).collect();
+        assert!(errors.is_empty(), "{:?}", errors);
+    }
+}
@@ -0,0 +1,366 @@
+use std::fmt;
+
+use serde_json::{Map, Value};
+
+use crate::parse::{Directive, Payload, ARGS_ONLY_BLOCKS};
+
+// the key holding the args of a block directive
+const ARGS_KEY: &str = "@args";
+
+// directives of different names whose relative order changes what nginx
+// does, which the keys of an object cannot keep
+const ORDER_SENSITIVE_GROUPS: &[&[&str]] = &[
+    &["allow", "deny"],
+    &["break", "if", "return", "rewrite", "set"],
+];
+
+/// Why a config could not be converted to or from the simple JSON shape,
+/// see [`Payload::to_simple_json`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimpleJsonError {
+    /// Names of the directives down to the one at fault.
+    pub path: Vec<String>,
+    pub kind: SimpleJsonErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SimpleJsonErrorKind {
+    /// A comment, which the shape has no place for.
+    Comment,
+    /// A raw directive, kept as unparsed text.
+    Raw,
+    /// A block of entries rather than directives, like `map`, whose
+    /// entries can be matched in order.
+    ArgsOnlyBlock,
+    /// Directives of these names in one block, whose relative order
+    /// matters, like `allow` and `deny`.
+    OrderSensitive(Vec<String>),
+    /// A JSON value that is not a directive, with what was expected.
+    UnexpectedValue(&'static str),
+}
+
+impl fmt::Display for SimpleJsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.path.is_empty() {
+            write!(f, "{}: ", self.path.join(" > "))?;
+        }
+        match &self.kind {
+            SimpleJsonErrorKind::Comment => write!(f, "comments cannot be represented"),
+            SimpleJsonErrorKind::Raw => write!(f, "raw directives cannot be represented"),
+            SimpleJsonErrorKind::ArgsOnlyBlock => write!(
+                f,
+                "blocks of entries cannot be represented, as their order matters"
+            ),
+            SimpleJsonErrorKind::OrderSensitive(names) => write!(
+                f,
+                "{} cannot be represented together, as their order matters",
+                names.join(", ")
+            ),
+            SimpleJsonErrorKind::UnexpectedValue(expected) => write!(f, "expected {}", expected),
+        }
+    }
+}
+
+impl std::error::Error for SimpleJsonError {}
+
+impl Payload {
+    /// The directives of the root file as nested JSON objects keyed by
+    /// directive name, for templating engines: a simple directive is the
+    /// array of its args, a block an object of its children with its args,
+    /// if any, under `"@args"`, and a directive used more than once in a
+    /// block the array of its uses, like
+    /// `{"http": {"server": [{"listen": ["80"]}, {"listen": ["81"]}]}}`.
+    ///
+    /// The shape is a convenience, not a replacement for the crossplane
+    /// one: lines, includes of other files and the order of directives of
+    /// different names are lost. Configs where that order matters, like
+    /// `allow` next to `deny` or `map` blocks, fail, as do comments.
+    pub fn to_simple_json(&self) -> Result<Value, SimpleJsonError> {
+        let root = self.config.first().map_or(&[][..], |c| &c.parsed);
+        block_to_simple_json(root, &mut Vec::new())
+    }
+}
+
+impl Directive {
+    /// The directives of a value in the shape of
+    /// [`Payload::to_simple_json`], without lines and with the directives
+    /// of every block sorted by name. Numbers are taken as args.
+    pub fn from_simple_json(value: Value) -> Result<Vec<Directive>, SimpleJsonError> {
+        let Value::Object(object) = value else {
+            return Err(SimpleJsonError {
+                path: Vec::new(),
+                kind: SimpleJsonErrorKind::UnexpectedValue("an object of directives"),
+            });
+        };
+        block_from_simple_json(object, &mut Vec::new())
+    }
+}
+
+fn block_to_simple_json(
+    block: &[Directive],
+    path: &mut Vec<String>,
+) -> Result<Value, SimpleJsonError> {
+    check_order(block.iter().map(|d| d.directive.as_str()), path)?;
+    let mut object = Map::new();
+    for stmt in block {
+        path.push(stmt.directive.clone());
+        let error = |kind| SimpleJsonError {
+            path: path.clone(),
+            kind,
+        };
+        if stmt.is_comment() {
+            return Err(error(SimpleJsonErrorKind::Comment));
+        }
+        if stmt.is_raw() {
+            return Err(error(SimpleJsonErrorKind::Raw));
+        }
+        if stmt.block.is_some() && ARGS_ONLY_BLOCKS.contains(&stmt.directive.as_str()) {
+            return Err(error(SimpleJsonErrorKind::ArgsOnlyBlock));
+        }
+        let args = Value::from(stmt.args.clone());
+        let value = match &stmt.block {
+            Some(children) => {
+                let mut value = block_to_simple_json(children, path)?;
+                if !stmt.args.is_empty() {
+                    value[ARGS_KEY] = args;
+                }
+                value
+            }
+            None => args,
+        };
+        path.pop();
+
+        // a second use turns the value into the array of uses
+        match object.get_mut(&stmt.directive) {
+            Some(Value::Array(uses)) if is_repeated(uses) => uses.push(value),
+            Some(first) => *first = Value::Array(vec![first.take(), value]),
+            None => {
+                object.insert(stmt.directive.clone(), value);
+            }
+        }
+    }
+    Ok(Value::Object(object))
+}
+
+// whether an array holds the uses of a repeated directive rather than the
+// args of a single one
+fn is_repeated(values: &[Value]) -> bool {
+    values
+        .first()
+        .is_some_and(|v| v.is_array() || v.is_object())
+}
+
+fn block_from_simple_json(
+    object: Map<String, Value>,
+    path: &mut Vec<String>,
+) -> Result<Vec<Directive>, SimpleJsonError> {
+    check_order(object.keys().map(String::as_str), path)?;
+    let mut block = Vec::new();
+    for (name, value) in object {
+        path.push(name.clone());
+        if name == "#" {
+            return Err(SimpleJsonError {
+                path: path.clone(),
+                kind: SimpleJsonErrorKind::Comment,
+            });
+        }
+        let uses = match value {
+            Value::Array(values) if is_repeated(&values) => values,
+            value => vec![value],
+        };
+        for value in uses {
+            block.push(directive_from_simple_json(&name, value, path)?);
+        }
+        path.pop();
+    }
+    Ok(block)
+}
+
+fn directive_from_simple_json(
+    name: &str,
+    value: Value,
+    path: &mut Vec<String>,
+) -> Result<Directive, SimpleJsonError> {
+    let unexpected = |path: &Vec<String>, expected| SimpleJsonError {
+        path: path.clone(),
+        kind: SimpleJsonErrorKind::UnexpectedValue(expected),
+    };
+    let mut directive = Directive::new(name, 0);
+    match value {
+        Value::Array(args) => {
+            directive.args = args_from_simple_json(args)
+                .ok_or_else(|| unexpected(path, "args as strings or numbers"))?;
+        }
+        Value::Object(mut children) => {
+            if ARGS_ONLY_BLOCKS.contains(&name) {
+                return Err(SimpleJsonError {
+                    path: path.clone(),
+                    kind: SimpleJsonErrorKind::ArgsOnlyBlock,
+                });
+            }
+            if let Some(args) = children.remove(ARGS_KEY) {
+                directive.args = match args {
+                    Value::Array(args) => args_from_simple_json(args),
+                    _ => None,
+                }
+                .ok_or_else(|| unexpected(path, "block args as strings or numbers"))?;
+            }
+            directive.block = Some(block_from_simple_json(children, path)?);
+        }
+        _ => {
+            return Err(unexpected(
+                path,
+                "an array of args or an object of directives",
+            ))
+        }
+    }
+    Ok(directive)
+}
+
+fn args_from_simple_json(args: Vec<Value>) -> Option<Vec<String>> {
+    args.into_iter()
+        .map(|arg| match arg {
+            Value::String(s) => Some(s),
+            Value::Number(n) => Some(n.to_string()),
+            _ => None,
+        })
+        .collect()
+}
+
+// fails for blocks using several directives of one order-sensitive group
+fn check_order<'a>(
+    names: impl Iterator<Item = &'a str>,
+    path: &[String],
+) -> Result<(), SimpleJsonError> {
+    let names: Vec<&str> = names.collect();
+    for group in ORDER_SENSITIVE_GROUPS {
+        let used: Vec<String> = group
+            .iter()
+            .filter(|name| names.contains(name))
+            .map(|name| name.to_string())
+            .collect();
+        if used.len() > 1 {
+            return Err(SimpleJsonError {
+                path: path.to_vec(),
+                kind: SimpleJsonErrorKind::OrderSensitive(used),
+            });
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::build::{build, BuildOptions};
+    use crate::parse::{parse_source, ParseOptions};
+
+    #[test]
+    fn test_simple_json() {
+        let source = "http {
+    gzip on;
+    server {
+        listen 8080;
+        server_name example.com www.example.com;
+        location /api {
+            proxy_pass http://backend;
+        }
+        location / {
+            root /srv/www;
+        }
+    }
+}
+";
+        let payload = parse_source(source, &ParseOptions::default());
+        let value = payload.to_simple_json().unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "http": {
+                    "gzip": ["on"],
+                    "server": {
+                        "listen": ["8080"],
+                        "server_name": ["example.com", "www.example.com"],
+                        "location": [
+                            {"@args": ["/api"], "proxy_pass": ["http://backend"]},
+                            {"@args": ["/"], "root": ["/srv/www"]},
+                        ],
+                    },
+                }
+            })
+        );
+
+        // back to the same directives, in the order of the keys
+        let directives = Directive::from_simple_json(value.clone()).unwrap();
+        let rebuilt = parse_source(
+            &build(&directives, &BuildOptions::default()),
+            &ParseOptions::default(),
+        );
+        assert_eq!(rebuilt.to_simple_json().unwrap(), value);
+        let server = &directives[0].block.as_ref().unwrap()[1];
+        let names: Vec<_> = server
+            .block
+            .iter()
+            .flatten()
+            .map(|d| d.directive.as_str())
+            .collect();
+        assert_eq!(names, ["listen", "location", "location", "server_name"]);
+
+        // numbers are args and objects without args blocks
+        let value = json!({"events": {}, "worker_processes": [1]});
+        let directives = Directive::from_simple_json(value).unwrap();
+        assert_eq!(
+            build(&directives, &BuildOptions::default()),
+            "events {\n}\nworker_processes 1;"
+        );
+    }
+
+    #[test]
+    fn test_simple_json_errors() {
+        let source = "http {
+    map $host $backend {
+        default a;
+        ~^api b;
+    }
+}
+";
+        let payload = parse_source(source, &ParseOptions::default());
+        let error = payload.to_simple_json().unwrap_err();
+        assert_eq!(error.path, ["http", "map"]);
+        assert_eq!(error.kind, SimpleJsonErrorKind::ArgsOnlyBlock);
+        assert_eq!(
+            error.to_string(),
+            "http > map: blocks of entries cannot be represented, as their order matters"
+        );
+
+        let source = "location / {\n    allow 10.0.0.0/8;\n    deny all;\n}\n";
+        let payload = parse_source(source, &ParseOptions::default());
+        assert_eq!(
+            payload.to_simple_json().unwrap_err().to_string(),
+            "location: allow, deny cannot be represented together, as their order matters"
+        );
+
+        let options = ParseOptions::default().comments(true);
+        let payload = parse_source("# note\nevents {}\n", &options);
+        assert_eq!(
+            payload.to_simple_json().unwrap_err().kind,
+            SimpleJsonErrorKind::Comment
+        );
+
+        let errors = [
+            (json!(["events"]), ""),
+            (json!({"#": ["note"]}), "#"),
+            (json!({"map": {"default": ["a"]}}), "map"),
+            (json!({"listen": [true]}), "listen"),
+            (json!({"server": {"@args": "x"}}), "server"),
+            (json!({"http": {"gzip": "on"}}), "http > gzip"),
+            (json!({"set": ["$a", "1"], "return": ["200"]}), ""),
+        ];
+        for (value, path) in errors {
+            let error = Directive::from_simple_json(value.clone()).unwrap_err();
+            assert_eq!(error.path.join(" > "), path, "{}", value);
+        }
+    }
+}
@@ -0,0 +1,412 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::parse::{ConfigFile, Directive, Payload};
+
+/// What a generated config serves, see [`generate_skeleton`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SkeletonSpec {
+    pub(crate) servers: Vec<ServerSpec>,
+}
+
+impl SkeletonSpec {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a server block; servers are generated in the order added.
+    pub fn server(mut self, server: ServerSpec) -> Self {
+        self.servers.push(server);
+        self
+    }
+}
+
+/// A virtual server of a [`SkeletonSpec`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerSpec {
+    pub(crate) names: Vec<String>,
+    pub(crate) port: Option<u16>,
+    pub(crate) tls: bool,
+    pub(crate) locations: Vec<LocationSpec>,
+}
+
+impl ServerSpec {
+    pub fn new<S: AsRef<str>>(names: &[S]) -> Self {
+        ServerSpec {
+            names: names.iter().map(|n| n.as_ref().to_string()).collect(),
+            port: None,
+            tls: false,
+            locations: Vec::new(),
+        }
+    }
+
+    /// The port to listen on, 443 with TLS and 80 without by default.
+    pub fn port(mut self, port: u16) -> Self {
+        self.port = Some(port);
+        self
+    }
+
+    /// Serve over TLS, with the certificate and key of the first server
+    /// name under `/etc/nginx/ssl`.
+    pub fn tls(mut self, tls: bool) -> Self {
+        self.tls = tls;
+        self
+    }
+
+    pub fn location(mut self, location: LocationSpec) -> Self {
+        self.locations.push(location);
+        self
+    }
+}
+
+/// A location of a [`ServerSpec`], either proxied or served from disk.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocationSpec {
+    pub(crate) path: String,
+    pub(crate) target: LocationTarget,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum LocationTarget {
+    Proxy(String),
+    Root(String),
+}
+
+impl LocationSpec {
+    /// Passes requests to `url`, like `http://127.0.0.1:8080` or
+    /// `https://api.internal/v1/`.
+    pub fn proxy(path: impl Into<String>, url: impl Into<String>) -> Self {
+        LocationSpec {
+            path: path.into(),
+            target: LocationTarget::Proxy(url.into()),
+        }
+    }
+
+    /// Serves files from the directory `root`.
+    pub fn root(path: impl Into<String>, root: impl Into<String>) -> Self {
+        LocationSpec {
+            path: path.into(),
+            target: LocationTarget::Root(root.into()),
+        }
+    }
+}
+
+/// Generates a config for `spec` as a payload of a single `nginx.conf`:
+/// an `events` block and an `http` block with common defaults, an
+/// `upstream` block for each proxied host used by more than one location,
+/// and a `server` block for each server of the spec.
+pub fn generate_skeleton(spec: &SkeletonSpec) -> Payload {
+    // proxied hosts by how often they are used, to know which get upstreams
+    let mut hosts: BTreeMap<String, usize> = BTreeMap::new();
+    for location in spec.servers.iter().flat_map(|s| &s.locations) {
+        if let LocationTarget::Proxy(url) = &location.target {
+            *hosts.entry(ProxyUrl::parse(url).server()).or_default() += 1;
+        }
+    }
+    // upstream names by server; servers differing only in chars names
+    // cannot hold, like a-b and a.b, get a counter after the name
+    let mut upstreams: BTreeMap<String, String> = BTreeMap::new();
+    let mut taken = BTreeSet::new();
+    for (server, _) in hosts.into_iter().filter(|&(_, uses)| uses > 1) {
+        let base = upstream_name(&server);
+        let mut name = base.clone();
+        for n in 2.. {
+            if taken.insert(name.clone()) {
+                break;
+            }
+            name = format!("{}_{}", base, n);
+        }
+        upstreams.insert(server, name);
+    }
+    let mut names: Vec<(&String, &String)> = upstreams.iter().map(|(s, n)| (n, s)).collect();
+    names.sort();
+
+    let mut http = vec![
+        directive("default_type", &["application/octet-stream"]),
+        directive("sendfile", &["on"]),
+        directive("tcp_nopush", &["on"]),
+        directive("keepalive_timeout", &["65"]),
+    ];
+    for (name, server) in names {
+        http.push(block(
+            directive("upstream", &[name]),
+            vec![
+                directive("server", &[server]),
+                directive("keepalive", &["16"]),
+            ],
+        ));
+    }
+    http.extend(spec.servers.iter().map(|s| server_block(s, &upstreams)));
+
+    let mut parsed = vec![
+        block(
+            Directive::new("events", 0),
+            vec![directive("worker_connections", &["1024"])],
+        ),
+        block(Directive::new("http", 0), http),
+    ];
+    let mut line = 0;
+    number_lines(&mut parsed, &mut line);
+
+    let mut config = ConfigFile::new("nginx.conf");
+    config.parsed = parsed;
+    Payload::from_config(vec![config])
+}
+
+fn server_block(server: &ServerSpec, upstreams: &BTreeMap<String, String>) -> Directive {
+    let port = server.port.unwrap_or(if server.tls { 443 } else { 80 });
+    let mut listen = vec![port.to_string()];
+    if server.tls {
+        listen.push("ssl".to_string());
+    }
+    let mut children = vec![directive("listen", &listen)];
+    if !server.names.is_empty() {
+        children.push(directive("server_name", &server.names));
+    }
+    if server.tls {
+        let name = server.names.first().map_or("default", String::as_str);
+        children.push(directive(
+            "ssl_certificate",
+            &[format!("/etc/nginx/ssl/{}.crt", name)],
+        ));
+        children.push(directive(
+            "ssl_certificate_key",
+            &[format!("/etc/nginx/ssl/{}.key", name)],
+        ));
+    }
+
+    for location in &server.locations {
+        let body = match &location.target {
+            LocationTarget::Root(root) => vec![
+                directive("root", &[root]),
+                directive("try_files", &["$uri", "$uri/", "=404"]),
+            ],
+            LocationTarget::Proxy(url) => {
+                let url = ProxyUrl::parse(url);
+                let upstream = upstreams.get(&url.server());
+                let target = match upstream {
+                    Some(name) => format!("{}://{}{}", url.scheme, name, url.path),
+                    None => format!("{}://{}{}", url.scheme, url.host, url.path),
+                };
+                let mut body = vec![
+                    directive("proxy_pass", &[target]),
+                    directive("proxy_set_header", &["Host", "$host"]),
+                    directive("proxy_set_header", &["X-Real-IP", "$remote_addr"]),
+                    directive(
+                        "proxy_set_header",
+                        &["X-Forwarded-For", "$proxy_add_x_forwarded_for"],
+                    ),
+                    directive("proxy_set_header", &["X-Forwarded-Proto", "$scheme"]),
+                ];
+                if upstream.is_some() {
+                    // keepalive connections to upstreams need HTTP/1.1
+                    body.push(directive("proxy_http_version", &["1.1"]));
+                    body.push(directive("proxy_set_header", &["Connection", ""]));
+                }
+                body
+            }
+        };
+        children.push(block(directive("location", &[&location.path]), body));
+    }
+    block(Directive::new("server", 0), children)
+}
+
+// the parts of a proxied url
+struct ProxyUrl<'a> {
+    scheme: &'a str,
+    host: &'a str,
+    path: &'a str,
+}
+
+impl<'a> ProxyUrl<'a> {
+    fn parse(url: &'a str) -> Self {
+        let (scheme, rest) = url.split_once("://").unwrap_or(("http", url));
+        let (host, path) = rest.find('/').map_or((rest, ""), |i| rest.split_at(i));
+        ProxyUrl { scheme, host, path }
+    }
+
+    // the host as the server of an upstream, which needs the port of https
+    fn server(&self) -> String {
+        let has_port = self
+            .host
+            .rsplit_once(':')
+            .is_some_and(|(_, port)| !port.is_empty() && !port.contains(']'));
+        if self.scheme == "https" && !has_port {
+            format!("{}:443", self.host)
+        } else {
+            self.host.to_string()
+        }
+    }
+}
+
+fn upstream_name(server: &str) -> String {
+    server
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+fn directive<S: AsRef<str>>(name: &str, args: &[S]) -> Directive {
+    Directive {
+        args: args.iter().map(|a| a.as_ref().to_string()).collect(),
+        ..Directive::new(name, 0)
+    }
+}
+
+fn block(head: Directive, children: Vec<Directive>) -> Directive {
+    Directive {
+        block: Some(children),
+        ..head
+    }
+}
+
+// the lines the directives get when built, one per directive and closing brace
+fn number_lines(block: &mut [Directive], line: &mut usize) {
+    for directive in block {
+        *line += 1;
+        directive.line = *line;
+        if let Some(children) = &mut directive.block {
+            number_lines(children, line);
+            *line += 1;
+            directive.block_end_line = Some(*line);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::build::{build, BuildOptions};
+    use crate::parse::{parse_source, ParseOptions};
+
+    #[test]
+    fn test_generate_skeleton() {
+        let spec = SkeletonSpec::new()
+            .server(
+                ServerSpec::new(&["example.com", "www.example.com"])
+                    .tls(true)
+                    .location(LocationSpec::proxy("/", "http://127.0.0.1:8080"))
+                    .location(LocationSpec::proxy("/api/", "http://127.0.0.1:8080/v1/")),
+            )
+            .server(
+                ServerSpec::new(&["static.example.com"])
+                    .location(LocationSpec::root("/", "/srv/www")),
+            );
+        let payload = generate_skeleton(&spec);
+        let built = build(&payload.config[0].parsed, &BuildOptions::default());
+        assert_eq!(
+            built,
+            r#"events {
+    worker_connections 1024;
+}
+http {
+    default_type application/octet-stream;
+    sendfile on;
+    tcp_nopush on;
+    keepalive_timeout 65;
+    upstream 127_0_0_1_8080 {
+        server 127.0.0.1:8080;
+        keepalive 16;
+    }
+    server {
+        listen 443 ssl;
+        server_name example.com www.example.com;
+        ssl_certificate /etc/nginx/ssl/example.com.crt;
+        ssl_certificate_key /etc/nginx/ssl/example.com.key;
+        location / {
+            proxy_pass http://127_0_0_1_8080;
+            proxy_set_header Host $host;
+            proxy_set_header X-Real-IP $remote_addr;
+            proxy_set_header X-Forwarded-For $proxy_add_x_forwarded_for;
+            proxy_set_header X-Forwarded-Proto $scheme;
+            proxy_http_version 1.1;
+            proxy_set_header Connection "";
+        }
+        location /api/ {
+            proxy_pass http://127_0_0_1_8080/v1/;
+            proxy_set_header Host $host;
+            proxy_set_header X-Real-IP $remote_addr;
+            proxy_set_header X-Forwarded-For $proxy_add_x_forwarded_for;
+            proxy_set_header X-Forwarded-Proto $scheme;
+            proxy_http_version 1.1;
+            proxy_set_header Connection "";
+        }
+    }
+    server {
+        listen 80;
+        server_name static.example.com;
+        location / {
+            root /srv/www;
+            try_files $uri $uri/ =404;
+        }
+    }
+}"#
+        );
+
+        // the built config parses back to the same tree, clean of errors
+        let options = ParseOptions::default().strict(true).catch_errors(true);
+        let reparsed = parse_source(&built, &options);
+        assert_eq!(reparsed.errors, []);
+        assert_eq!(reparsed.config[0].parsed, payload.config[0].parsed);
+        assert_eq!(payload.lint().len(), 0);
+    }
+
+    #[test]
+    fn test_generate_skeleton_upstream_names() {
+        let spec = SkeletonSpec::new().server(
+            ServerSpec::new(&["example.com"])
+                .location(LocationSpec::proxy("/a/", "http://a-b:8080"))
+                .location(LocationSpec::proxy("/b/", "http://a.b:8080"))
+                .location(LocationSpec::proxy("/c/", "http://a-b:8080/c/"))
+                .location(LocationSpec::proxy("/d/", "http://a.b:8080/d/"))
+                .location(LocationSpec::proxy("/e/", "http://a_b_8080_2"))
+                .location(LocationSpec::proxy("/f/", "http://a_b_8080_2/f/")),
+        );
+        let payload = generate_skeleton(&spec);
+        let http = payload.config[0].parsed[1].block.as_deref().unwrap();
+        let upstreams: Vec<(&str, &str)> = http
+            .iter()
+            .filter(|d| d.directive == "upstream")
+            .map(|d| {
+                let server = &d.block.as_deref().unwrap()[0];
+                (d.args[0].as_str(), server.args[0].as_str())
+            })
+            .collect();
+        assert_eq!(
+            upstreams,
+            [
+                ("a_b_8080", "a-b:8080"),
+                ("a_b_8080_2", "a.b:8080"),
+                ("a_b_8080_2_2", "a_b_8080_2"),
+            ]
+        );
+        let server = http.last().unwrap().block.as_deref().unwrap();
+        let proxy_passes: Vec<&str> = server
+            .iter()
+            .filter(|d| d.directive == "location")
+            .map(|d| d.block.as_deref().unwrap()[0].args[0].as_str())
+            .collect();
+        assert_eq!(
+            proxy_passes,
+            [
+                "http://a_b_8080",
+                "http://a_b_8080_2",
+                "http://a_b_8080/c/",
+                "http://a_b_8080_2/d/",
+                "http://a_b_8080_2_2",
+                "http://a_b_8080_2_2/f/",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_proxy_url() {
+        let url = ProxyUrl::parse("https://api.internal/v1/");
+        assert_eq!(
+            (url.scheme, url.host, url.path),
+            ("https", "api.internal", "/v1/")
+        );
+        assert_eq!(url.server(), "api.internal:443");
+        assert_eq!(ProxyUrl::parse("https://[::1]").server(), "[::1]:443");
+        assert_eq!(ProxyUrl::parse("http://backend").server(), "backend");
+    }
+}
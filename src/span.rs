@@ -0,0 +1,185 @@
+use std::ops::Range;
+
+use serde::{Deserialize, Serialize};
+
+/// How the columns of a position are counted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PositionEncoding {
+    /// Bytes of the UTF-8 source, as in [`NgxToken::span`](crate::NgxToken).
+    Utf8Bytes,
+    /// UTF-16 code units, the default of the Language Server Protocol.
+    #[default]
+    Utf16Units,
+    /// Unicode scalar values.
+    CodePoints,
+}
+
+impl PositionEncoding {
+    /// The column of the byte at `byte` of `line`, counted from 0 in this
+    /// encoding. Offsets inside a char count from its start, offsets past
+    /// the end from the end of the line.
+    pub fn column(self, line: &str, byte: usize) -> usize {
+        let byte = (0..=byte.min(line.len()))
+            .rev()
+            .find(|&i| line.is_char_boundary(i))
+            .unwrap_or_default();
+        let before = &line[..byte];
+        match self {
+            PositionEncoding::Utf8Bytes => byte,
+            PositionEncoding::Utf16Units => before.chars().map(char::len_utf16).sum(),
+            PositionEncoding::CodePoints => before.chars().count(),
+        }
+    }
+}
+
+/// A range of a single line of a config, in bytes from the start of the
+/// line.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Span {
+    /// 1-based line number.
+    pub line: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// A position as the Language Server Protocol has it, both counted from 0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LspPosition {
+    pub line: u32,
+    pub character: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LspRange {
+    pub start: LspPosition,
+    pub end: LspPosition,
+}
+
+impl Span {
+    /// The span of the byte range `range` of `source`, like the span of a
+    /// token. Ranges going over several lines end with the first one.
+    pub fn new(source: &str, range: Range<usize>) -> Self {
+        let start = range.start.min(source.len());
+        let line_start = source[..start].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = source[start..]
+            .find('\n')
+            .map_or(source.len(), |i| start + i);
+        Span {
+            line: source[..start].matches('\n').count() + 1,
+            start: start - line_start,
+            end: range.end.clamp(start, line_end) - line_start,
+        }
+    }
+
+    // like new, for a range known to start on `line`
+    pub(crate) fn on_line(source: &[u8], line: usize, range: Range<usize>) -> Self {
+        let line_start = source[..range.start]
+            .iter()
+            .rposition(|&b| b == b'\n')
+            .map_or(0, |i| i + 1);
+        let line_end = source[range.start..]
+            .iter()
+            .position(|&b| b == b'\n')
+            .map_or(source.len(), |i| range.start + i);
+        Span {
+            line,
+            start: range.start - line_start,
+            end: range.end.min(line_end) - line_start,
+        }
+    }
+
+    /// The span as an LSP range in UTF-16 code units, `source_line` being
+    /// the text of its line.
+    pub fn to_lsp(&self, source_line: &str) -> LspRange {
+        self.to_lsp_with(source_line, PositionEncoding::Utf16Units)
+    }
+
+    /// Like [`to_lsp`](Span::to_lsp), with the columns counted in
+    /// `encoding`, as negotiated with the client.
+    pub fn to_lsp_with(&self, source_line: &str, encoding: PositionEncoding) -> LspRange {
+        let position = |byte| LspPosition {
+            line: self.line.saturating_sub(1) as u32,
+            character: encoding.column(source_line, byte) as u32,
+        };
+        LspRange {
+            start: position(self.start),
+            end: position(self.end),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lex::lex;
+
+    #[test]
+    fn test_span_to_lsp() {
+        let source = "events {}\n# комментарий 🚀 здесь\nuser 🦀 nobody; # ёж\n";
+        let tokens = lex(source.as_bytes()).unwrap();
+        let lines: Vec<&str> = source.lines().collect();
+
+        let columns = |value: &str| {
+            let token = tokens.iter().find(|t| t.value == value).unwrap();
+            let span = Span::new(source, token.span.clone());
+            let line = lines[span.line - 1];
+            [
+                PositionEncoding::Utf8Bytes,
+                PositionEncoding::Utf16Units,
+                PositionEncoding::CodePoints,
+            ]
+            .map(|encoding| {
+                let range = span.to_lsp_with(line, encoding);
+                assert_eq!(range.start.line, range.end.line);
+                (range.start.line, range.start.character, range.end.character)
+            })
+        };
+        assert_eq!(columns("events"), [(0, 0, 6); 3]);
+        assert_eq!(
+            columns("# комментарий 🚀 здесь"),
+            [(1, 0, 40), (1, 0, 22), (1, 0, 21)]
+        );
+        assert_eq!(columns("🦀"), [(2, 5, 9), (2, 5, 7), (2, 5, 6)]);
+        assert_eq!(columns("nobody"), [(2, 10, 16), (2, 8, 14), (2, 7, 13)]);
+        assert_eq!(columns(";"), [(2, 16, 17), (2, 14, 15), (2, 13, 14)]);
+        assert_eq!(columns("# ёж"), [(2, 18, 24), (2, 16, 20), (2, 15, 19)]);
+
+        let span = Span::new(source, 10..11);
+        assert_eq!(
+            span.to_lsp(lines[1]),
+            span.to_lsp_with(lines[1], PositionEncoding::default())
+        );
+    }
+
+    #[test]
+    fn test_position_encoding_column() {
+        let line = "a🚀b";
+        // offsets inside the rocket count from its start
+        for (byte, expected) in [
+            (0, [0, 0, 0]),
+            (1, [1, 1, 1]),
+            (3, [1, 1, 1]),
+            (5, [5, 3, 2]),
+            (9, [6, 4, 3]),
+        ] {
+            let columns = [
+                PositionEncoding::Utf8Bytes,
+                PositionEncoding::Utf16Units,
+                PositionEncoding::CodePoints,
+            ]
+            .map(|encoding| encoding.column(line, byte));
+            assert_eq!(columns, expected, "{}", byte);
+        }
+
+        // a token over several lines ends with its first one
+        let span = Span::new("set $a \"x\ny\";\n", 7..12);
+        assert_eq!(
+            span,
+            Span {
+                line: 1,
+                start: 7,
+                end: 9
+            }
+        );
+    }
+}
@@ -0,0 +1,361 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::lex::NgxToken;
+use crate::loader::ConfigLoader;
+
+// how many tokens around a mismatch are shown from each stream
+const CONTEXT: usize = 2;
+
+// values longer than this many chars are cut in token dumps
+const DUMP_VALUE_CHARS: usize = 40;
+
+/// The tokens a lexer should give, as values with their lines, for
+/// [`Expected::assert_matches`] or the [`assert_tokens!`] macro.
+///
+/// [`assert_tokens!`]: crate::assert_tokens
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Expected {
+    tokens: Vec<(String, usize)>,
+}
+
+impl Expected {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn tok(mut self, value: impl Into<String>, line: usize) -> Self {
+        self.tokens.push((value.into(), line));
+        self
+    }
+
+    pub fn brace_open(self, line: usize) -> Self {
+        self.tok("{", line)
+    }
+
+    pub fn brace_close(self, line: usize) -> Self {
+        self.tok("}", line)
+    }
+
+    pub fn semicolon(self, line: usize) -> Self {
+        self.tok(";", line)
+    }
+
+    /// Compares the values and lines of `tokens` with the expected ones.
+    pub fn check(&self, tokens: &[NgxToken]) -> Result<(), TokenMismatch> {
+        let actual: Vec<(String, usize)> =
+            tokens.iter().map(|t| (t.value.clone(), t.line)).collect();
+        let index =
+            (0..self.tokens.len().max(actual.len())).find(|&i| self.tokens.get(i) != actual.get(i));
+        match index {
+            Some(index) => Err(TokenMismatch {
+                index,
+                expected: self.tokens.clone(),
+                actual,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Panics with the first mismatching token and its surroundings in both
+    /// streams unless `tokens` are the expected ones.
+    #[track_caller]
+    pub fn assert_matches(&self, tokens: &[NgxToken]) {
+        if let Err(mismatch) = self.check(tokens) {
+            panic!("{}", mismatch);
+        }
+    }
+}
+
+/// Where a token stream first differs from the expected one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenMismatch {
+    pub index: usize,
+    pub expected: Vec<(String, usize)>,
+    pub actual: Vec<(String, usize)>,
+}
+
+impl TokenMismatch {
+    fn write_stream(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        name: &str,
+        tokens: &[(String, usize)],
+    ) -> fmt::Result {
+        writeln!(f, "{}:", name)?;
+        let start = self.index.saturating_sub(CONTEXT);
+        for i in start..=self.index + CONTEXT {
+            let marker = if i == self.index { ">" } else { " " };
+            match tokens.get(i) {
+                Some((value, line)) => writeln!(f, "  {} {}: {:?}@{}", marker, i, value, line)?,
+                None if i == self.index || i == tokens.len() => {
+                    writeln!(f, "  {} {}: <end>", marker, i)?
+                }
+                None => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for TokenMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "tokens differ at index {} ({} expected, {} lexed)",
+            self.index,
+            self.expected.len(),
+            self.actual.len()
+        )?;
+        self.write_stream(f, "expected", &self.expected)?;
+        self.write_stream(f, "actual", &self.actual)
+    }
+}
+
+impl std::error::Error for TokenMismatch {}
+
+/// The tokens one per line, with their index, line, kind, a `q` when
+/// quoted and their value, escaped and cut when long, then a summary:
+/// counts of each kind, the deepest nesting of braces and the first
+/// error. Lines of error tokens start with `!`.
+pub fn tokens_debug_dump(tokens: &[NgxToken]) -> String {
+    let index_width = tokens.len().saturating_sub(1).to_string().len();
+    let line_width = tokens
+        .iter()
+        .map(|t| t.line.to_string().len())
+        .max()
+        .unwrap_or(1);
+    let mut out = String::new();
+    let mut counts: Vec<(&str, usize)> = Vec::new();
+    let (mut depth, mut max_depth) = (0usize, 0);
+    for (i, token) in tokens.iter().enumerate() {
+        let kind = token_kind(token);
+        match counts.iter_mut().find(|(k, _)| *k == kind) {
+            Some((_, n)) => *n += 1,
+            None => counts.push((kind, 1)),
+        }
+        match kind {
+            "open" => {
+                depth += 1;
+                max_depth = max_depth.max(depth);
+            }
+            "close" => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+        let value = match &token.error {
+            Some(error) => error.what.clone(),
+            None => dump_value(&token.value),
+        };
+        out += &format!(
+            "{} {:>iw$} {:>lw$} {:<11} {} {}\n",
+            if token.error.is_some() { "!" } else { " " },
+            i,
+            token.line,
+            kind,
+            if token.is_quoted { "q" } else { "-" },
+            value,
+            iw = index_width,
+            lw = line_width,
+        );
+    }
+
+    let counts: Vec<String> = counts
+        .iter()
+        .map(|(kind, n)| format!("{} {}", n, kind))
+        .collect();
+    out += &format!(
+        "{} tokens ({}), max depth {}, ",
+        tokens.len(),
+        counts.join(", "),
+        max_depth
+    );
+    match tokens
+        .iter()
+        .find_map(|t| Some((t.error.as_ref()?, t.line)))
+    {
+        Some((error, line)) => out += &format!("first error at line {}: {}\n", line, error.what),
+        None => out += "no errors\n",
+    }
+    out
+}
+
+fn token_kind(token: &NgxToken) -> &'static str {
+    if token.error.is_some() {
+        return "error";
+    }
+    if token.is_placeholder {
+        return "placeholder";
+    }
+    if token.is_quoted {
+        return "word";
+    }
+    match token.value.as_str() {
+        "{" => "open",
+        "}" => "close",
+        ";" => "semicolon",
+        value if value.starts_with('#') => "comment",
+        _ => "word",
+    }
+}
+
+// the value quoted with control characters escaped, cut when long
+fn dump_value(value: &str) -> String {
+    if value.chars().count() <= DUMP_VALUE_CHARS {
+        return format!("{:?}", value);
+    }
+    let cut: String = value.chars().take(DUMP_VALUE_CHARS - 3).collect();
+    format!("{:?}...", cut)
+}
+
+/// Configs held in memory, keyed by path, for tests of parsing through a
+/// [`ConfigLoader`]. Include patterns match the paths like globs do.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MemoryLoader(pub HashMap<PathBuf, &'static str>);
+
+impl ConfigLoader for MemoryLoader {
+    fn read(&self, path: &Path, limit: Option<u64>) -> io::Result<Vec<u8>> {
+        self.check(path)?;
+        let source = self.0[path].as_bytes();
+        let len = limit.map_or(source.len(), |limit| source.len().min(limit as usize));
+        Ok(source[..len].to_vec())
+    }
+
+    fn check(&self, path: &Path) -> io::Result<()> {
+        match self.0.contains_key(path) {
+            true => Ok(()),
+            false => Err(io::ErrorKind::NotFound.into()),
+        }
+    }
+
+    fn glob(&self, config_dir: &Path, pattern: &str) -> Vec<PathBuf> {
+        let Ok(pattern) = glob::Pattern::new(&config_dir.join(pattern).to_string_lossy()) else {
+            return Vec::new();
+        };
+        let mut paths: Vec<_> = self
+            .0
+            .keys()
+            .filter(|p| pattern.matches_path(p))
+            .cloned()
+            .collect();
+        paths.sort();
+        paths
+    }
+}
+
+/// Asserts that a token stream has the given values on the given lines:
+/// `assert_tokens!(lex(source).unwrap(), ["events"@1, "{"@1, "}"@2])`.
+#[macro_export]
+macro_rules! assert_tokens {
+    ($tokens:expr, [$($value:literal @ $line:literal),* $(,)?]) => {
+        $crate::test_support::Expected::new()
+            $(.tok($value, $line))*
+            .assert_matches(&$tokens)
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lex::lex;
+
+    #[test]
+    fn test_expected_tokens() {
+        let tokens = lex("events {\n    worker_connections 1024;\n}\n".as_bytes()).unwrap();
+        crate::assert_tokens!(
+            tokens,
+            ["events"@1, "{"@1, "worker_connections"@2, "1024"@2, ";"@2, "}"@3]
+        );
+        Expected::new()
+            .tok("events", 1)
+            .brace_open(1)
+            .tok("worker_connections", 2)
+            .tok("1024", 2)
+            .semicolon(2)
+            .brace_close(3)
+            .assert_matches(&tokens);
+    }
+
+    #[test]
+    fn test_token_mismatch() {
+        let tokens = lex("events {\n    worker_connections 2048;\n}\n".as_bytes()).unwrap();
+        let mismatch = Expected::new()
+            .tok("events", 1)
+            .brace_open(1)
+            .tok("worker_connections", 2)
+            .tok("1024", 2)
+            .semicolon(2)
+            .brace_close(3)
+            .check(&tokens)
+            .unwrap_err();
+        assert_eq!(mismatch.index, 3);
+        assert_eq!(
+            mismatch.to_string(),
+            r#"tokens differ at index 3 (6 expected, 6 lexed)
+expected:
+    1: "{"@1
+    2: "worker_connections"@2
+  > 3: "1024"@2
+    4: ";"@2
+    5: "}"@3
+actual:
+    1: "{"@1
+    2: "worker_connections"@2
+  > 3: "2048"@2
+    4: ";"@2
+    5: "}"@3
+"#
+        );
+
+        // a stream cut short ends where the other goes on
+        let mismatch = Expected::new()
+            .tok("events", 1)
+            .brace_open(1)
+            .check(&tokens[..1])
+            .unwrap_err();
+        assert_eq!(
+            mismatch.to_string(),
+            r#"tokens differ at index 1 (2 expected, 1 lexed)
+expected:
+    0: "events"@1
+  > 1: "{"@1
+    2: <end>
+actual:
+    0: "events"@1
+  > 1: <end>
+"#
+        );
+    }
+
+    #[test]
+    fn test_tokens_debug_dump() {
+        let source = std::fs::read_to_string("configs/messy/nginx.conf").unwrap();
+        let tokens = lex(source.as_bytes()).unwrap();
+        let expected = std::fs::read_to_string("configs/messy/tokens.txt").unwrap();
+        assert_eq!(tokens_debug_dump(&tokens), expected);
+
+        let tokens = crate::lex::lex_tokens(
+            "http {\n    return 200 \"a\tb\";\n".as_bytes(),
+            &Default::default(),
+        );
+        assert_eq!(
+            tokens_debug_dump(&tokens),
+            r#"  0 1 word        - "http"
+  1 1 open        - "{"
+  2 2 word        - "return"
+  3 2 word        - "200"
+  4 2 word        q "a\tb"
+  5 2 semicolon   - ";"
+! 6 2 error       - unexpected end of file, expecting '}'
+7 tokens (4 word, 1 open, 1 semicolon, 1 error), max depth 1, first error at line 2: unexpected end of file, expecting '}'
+"#
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "tokens differ at index 0")]
+    fn test_assert_tokens_panics() {
+        crate::assert_tokens!(lex("http {}".as_bytes()).unwrap(), ["events"@1, "{"@1, "}"@1]);
+    }
+}
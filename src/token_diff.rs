@@ -0,0 +1,314 @@
+use std::fmt::Write;
+
+use crate::lex::{lex_tokens, LexOptions, NgxToken};
+
+/// What counts as a change for [`diff_tokens`].
+#[derive(Debug, Clone)]
+pub struct TokenDiffOptions {
+    pub(crate) comments: bool,
+    pub(crate) quoting: bool,
+}
+
+impl Default for TokenDiffOptions {
+    fn default() -> Self {
+        TokenDiffOptions {
+            comments: true,
+            quoting: true,
+        }
+    }
+}
+
+impl TokenDiffOptions {
+    /// Report added, removed and edited comments. On by default.
+    pub fn comments(mut self, comments: bool) -> Self {
+        self.comments = comments;
+        self
+    }
+
+    /// Report args that only gained or lost their quotes, like `on` and
+    /// `"on"`. On by default.
+    pub fn quoting(mut self, quoting: bool) -> Self {
+        self.quoting = quoting;
+        self
+    }
+}
+
+/// A token removed from the old source or added in the new one. Besides
+/// the line of the token on its own side, each change has the line of the
+/// place it goes to on the other side.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TokenChange {
+    Removed {
+        value: String,
+        quoted: bool,
+        old_line: usize,
+        new_line: usize,
+    },
+    Added {
+        value: String,
+        quoted: bool,
+        old_line: usize,
+        new_line: usize,
+    },
+}
+
+impl TokenChange {
+    /// The lines of the change in the old and the new source.
+    pub fn lines(&self) -> (usize, usize) {
+        match self {
+            TokenChange::Removed {
+                old_line, new_line, ..
+            }
+            | TokenChange::Added {
+                old_line, new_line, ..
+            } => (*old_line, *new_line),
+        }
+    }
+}
+
+/// The tokens that differ between two versions of a config file, removals
+/// before additions where tokens are replaced. Whitespace and line breaks
+/// between tokens never count.
+pub fn diff_tokens(old: &str, new: &str, options: &TokenDiffOptions) -> Vec<TokenChange> {
+    let tokens = |source: &str| -> Vec<NgxToken> {
+        lex_tokens(source.as_bytes(), &LexOptions::default())
+            .into_iter()
+            .filter(|t| options.comments || !is_comment(t))
+            .collect()
+    };
+    let (old, new) = (tokens(old), tokens(new));
+    let key = |t: &NgxToken| (t.value.clone(), options.quoting && t.is_quoted);
+    let old_keys: Vec<_> = old.iter().map(key).collect();
+    let new_keys: Vec<_> = new.iter().map(key).collect();
+
+    // the line of the token before position `i`, or of the first one
+    let line_at = |tokens: &[NgxToken], i: usize| {
+        tokens[..i]
+            .last()
+            .or_else(|| tokens.first())
+            .map_or(1, |t| t.line)
+    };
+    shortest_edit(&old_keys, &new_keys)
+        .into_iter()
+        .filter_map(|edit| match edit {
+            Edit::Keep => None,
+            Edit::Remove(i, j) => Some(TokenChange::Removed {
+                value: old[i].value.clone(),
+                quoted: old[i].is_quoted,
+                old_line: old[i].line,
+                new_line: line_at(&new, j),
+            }),
+            Edit::Insert(i, j) => Some(TokenChange::Added {
+                value: new[j].value.clone(),
+                quoted: new[j].is_quoted,
+                old_line: line_at(&old, i),
+                new_line: new[j].line,
+            }),
+        })
+        .collect()
+}
+
+fn is_comment(token: &NgxToken) -> bool {
+    !token.is_quoted && token.value.starts_with('#')
+}
+
+/// Renders changes like `git diff --word-diff`: a `@@ -old +new @@` header
+/// for each run of changes at the same lines, followed by the removed
+/// tokens in `[-…-]` and the added ones in `{+…+}`.
+pub fn render_token_diff(changes: &[TokenChange]) -> String {
+    let mut out = String::new();
+    let mut i = 0;
+    while i < changes.len() {
+        let lines = changes[i].lines();
+        let end = i + changes[i..]
+            .iter()
+            .take_while(|c| c.lines() == lines)
+            .count();
+        let run = &changes[i..end];
+        let values = |removed: bool| -> Vec<String> {
+            run.iter()
+                .filter_map(|c| match c {
+                    TokenChange::Removed { value, quoted, .. } if removed => {
+                        Some(shown(value, *quoted))
+                    }
+                    TokenChange::Added { value, quoted, .. } if !removed => {
+                        Some(shown(value, *quoted))
+                    }
+                    _ => None,
+                })
+                .collect()
+        };
+
+        let _ = write!(out, "@@ -{} +{} @@", lines.0, lines.1);
+        let removed = values(true);
+        if !removed.is_empty() {
+            let _ = write!(out, " [-{}-]", removed.join(" "));
+        }
+        let added = values(false);
+        if !added.is_empty() {
+            let _ = write!(out, " {{+{}+}}", added.join(" "));
+        }
+        out.push('\n');
+        i = end;
+    }
+    out
+}
+
+fn shown(value: &str, quoted: bool) -> String {
+    if quoted {
+        format!("\"{}\"", value)
+    } else {
+        value.to_string()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Edit {
+    Keep,
+    // the index in the old tokens and the position in the new ones
+    Remove(usize, usize),
+    // the position in the old tokens and the index in the new ones
+    Insert(usize, usize),
+}
+
+// the edits turning `a` into `b` with the fewest removals and insertions,
+// with the algorithm of Myers' "An O(ND) Difference Algorithm"
+fn shortest_edit<T: PartialEq>(a: &[T], b: &[T]) -> Vec<Edit> {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let max = n + m;
+    // the furthest x reached on each diagonal k = x - y, at index k + max
+    let mut v = vec![0isize; 2 * max as usize + 2];
+    // v at the start of each round d, for the diagonals -d..=d
+    let mut trace: Vec<Vec<isize>> = Vec::new();
+
+    'search: for d in 0..=max {
+        trace.push(v[(max - d) as usize..=(max + d) as usize].to_vec());
+        for k in (-d..=d).step_by(2) {
+            let at = |k: isize| (k + max) as usize;
+            let mut x = if k == -d || (k != d && v[at(k - 1)] < v[at(k + 1)]) {
+                v[at(k + 1)]
+            } else {
+                v[at(k - 1)] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[at(k)] = x;
+            if x >= n && y >= m {
+                break 'search;
+            }
+        }
+    }
+
+    let mut edits = Vec::new();
+    let (mut x, mut y) = (n, m);
+    for (d, v) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        let at = |k: isize| (k + d) as usize;
+        let k = x - y;
+        let prev_k = if k == -d || (k != d && v[at(k - 1)] < v[at(k + 1)]) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let prev_x = if d == 0 { 0 } else { v[at(prev_k)] };
+        let prev_y = prev_x - prev_k;
+        while x > prev_x && y > prev_y {
+            edits.push(Edit::Keep);
+            x -= 1;
+            y -= 1;
+        }
+        if d > 0 {
+            if x == prev_x {
+                edits.push(Edit::Insert(x as usize, prev_y as usize));
+            } else {
+                edits.push(Edit::Remove(prev_x as usize, y as usize));
+            }
+        }
+        x = prev_x;
+        y = prev_y;
+    }
+    edits.reverse();
+    edits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OLD: &str = "# workers
+events {
+    worker_connections 1024;
+}
+http {
+    sendfile on;
+    index index.html index.htm;
+}
+";
+
+    #[test]
+    fn test_diff_tokens() {
+        let options = TokenDiffOptions::default();
+        let reformatted = "# workers\nevents { worker_connections 1024; }\nhttp {\n\n\tsendfile on;\n    index index.html\n        index.htm;\n}\n";
+        assert_eq!(diff_tokens(OLD, reformatted, &options), []);
+
+        let new = OLD
+            .replace("1024", "4096")
+            .replace("index.html index.htm", "index.htm index.html")
+            .replace("sendfile on", "sendfile \"on\"");
+        let changes = diff_tokens(OLD, &new, &options);
+        assert_eq!(
+            changes[0],
+            TokenChange::Removed {
+                value: "1024".to_string(),
+                quoted: false,
+                old_line: 3,
+                new_line: 3
+            }
+        );
+        assert_eq!(
+            render_token_diff(&changes),
+            "@@ -3 +3 @@ [-1024-] {+4096+}
+@@ -6 +6 @@ [-on-] {+\"on\"+}
+@@ -7 +7 @@ [-index.html-] {+index.html+}
+"
+        );
+        let changes = diff_tokens(OLD, &new, &options.clone().quoting(false));
+        assert_eq!(
+            render_token_diff(&changes),
+            "@@ -3 +3 @@ [-1024-] {+4096+}\n@@ -7 +7 @@ [-index.html-] {+index.html+}\n"
+        );
+    }
+
+    #[test]
+    fn test_diff_comments() {
+        let new = OLD
+            .replace("# workers", "# worker settings")
+            .replace("sendfile on;", "sendfile on; # zero copy");
+        let changes = diff_tokens(OLD, &new, &TokenDiffOptions::default());
+        assert_eq!(
+            render_token_diff(&changes),
+            "@@ -1 +1 @@ [-# workers-] {+# worker settings+}\n@@ -6 +6 @@ {+# zero copy+}\n"
+        );
+        let ignored = TokenDiffOptions::default().comments(false);
+        assert_eq!(diff_tokens(OLD, &new, &ignored), []);
+    }
+
+    #[test]
+    fn test_shortest_edit() {
+        let count = |a: &str, b: &str| {
+            let a: Vec<char> = a.chars().collect();
+            let b: Vec<char> = b.chars().collect();
+            let edits = shortest_edit(&a, &b);
+            let keeps = edits.iter().filter(|e| **e == Edit::Keep).count();
+            (keeps, edits.len() - keeps)
+        };
+        assert_eq!(count("", ""), (0, 0));
+        assert_eq!(count("abc", ""), (0, 3));
+        assert_eq!(count("", "abc"), (0, 3));
+        assert_eq!(count("abcabba", "cbabac"), (4, 5));
+        assert_eq!(count("abc", "abc"), (3, 0));
+    }
+}
@@ -0,0 +1,184 @@
+use std::fmt;
+
+use crate::context::Context;
+use crate::loader::parse_context;
+use crate::parse::{ConfigFile, Directive, Payload};
+
+/// A directive of a payload together with where it sits: the config file
+/// and the indexes leading to it from the file's top level.
+#[derive(Clone)]
+pub struct DirectiveRef<'a> {
+    payload: &'a Payload,
+    file: usize,
+    path: Vec<usize>,
+    directive: &'a Directive,
+}
+
+// two refs are equal when they point at the same place of the same payload
+impl PartialEq for DirectiveRef<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self.payload, other.payload)
+            && self.file == other.file
+            && self.path == other.path
+    }
+}
+
+impl Eq for DirectiveRef<'_> {}
+
+impl fmt::Debug for DirectiveRef<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DirectiveRef")
+            .field("file", &self.file_name())
+            .field("path", &self.path)
+            .field("directive", &self.directive.directive)
+            .field("line", &self.directive.line)
+            .finish()
+    }
+}
+
+/// Where a directive sits in a payload, without borrowing it, for methods
+/// that change the payload.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DirectiveLocation {
+    /// Index of the file in the payload's `config`.
+    pub file: usize,
+    /// Indexes of the directive and its enclosing blocks, outermost first.
+    pub path: Vec<usize>,
+}
+
+impl<'a> DirectiveRef<'a> {
+    pub fn directive(&self) -> &'a Directive {
+        self.directive
+    }
+
+    /// The directive as written in the source, when its name was given to
+    /// [`ParseOptions::capture_raw_for`](crate::ParseOptions::capture_raw_for).
+    pub fn raw_source(&self) -> Option<&'a str> {
+        self.directive.raw_source.as_deref()
+    }
+
+    pub fn location(&self) -> DirectiveLocation {
+        DirectiveLocation {
+            file: self.file,
+            path: self.path.clone(),
+        }
+    }
+
+    pub fn file(&self) -> &'a ConfigFile {
+        &self.payload.config[self.file]
+    }
+
+    /// The file the directive was read from: the one recorded on it in
+    /// payloads parsed with [`ParseOptions::combine`](crate::ParseOptions::combine),
+    /// else the one of its config entry. Lines of the directive are lines
+    /// of this file.
+    pub fn file_name(&self) -> &'a str {
+        self.directive
+            .file
+            .as_deref()
+            .unwrap_or(&self.payload.config[self.file].file)
+    }
+
+    /// Index of the directive's file in the payload's `config`.
+    pub fn file_index(&self) -> usize {
+        self.file
+    }
+
+    /// Indexes of the directive and its enclosing blocks, outermost first.
+    pub fn path(&self) -> &[usize] {
+        &self.path
+    }
+
+    /// The block directive containing this one in the same file.
+    pub fn parent(&self) -> Option<DirectiveRef<'a>> {
+        let (_, parent) = self.path.split_last()?;
+        self.payload.directive_ref(self.file, parent)
+    }
+
+    /// The enclosing block directives, innermost first.
+    pub fn ancestors(&self) -> impl Iterator<Item = DirectiveRef<'a>> {
+        std::iter::successors(self.parent(), |d| d.parent())
+    }
+
+    /// The context the directive is in, following the includes of its file
+    /// back to the blocks including it.
+    pub fn context(&self) -> Context {
+        Context::new(&parse_context(self.payload, self))
+    }
+
+    /// The nearest `if` block around the directive in the same file.
+    pub fn enclosing_if(&self) -> Option<DirectiveRef<'a>> {
+        self.ancestors().find(|d| d.directive.directive == "if")
+    }
+}
+
+impl Payload {
+    /// The directive reached by following `path` from the top level of the
+    /// file at `file`.
+    pub fn directive_ref(&self, file: usize, path: &[usize]) -> Option<DirectiveRef<'_>> {
+        let (first, rest) = path.split_first()?;
+        let mut directive = self.config.get(file)?.parsed.get(*first)?;
+        for &i in rest {
+            directive = directive.block.as_ref()?.get(i)?;
+        }
+        Some(DirectiveRef {
+            payload: self,
+            file,
+            path: path.to_vec(),
+            directive,
+        })
+    }
+
+    /// All directives of the payload, file by file, each block directive
+    /// followed by its children.
+    pub fn directives(&self) -> Directives<'_> {
+        Directives {
+            payload: self,
+            file: 0,
+            next_file: 0,
+            stack: Vec::new(),
+        }
+    }
+}
+
+/// Iterator over the directives of a payload, see [`Payload::directives`].
+pub struct Directives<'a> {
+    payload: &'a Payload,
+    file: usize,
+    next_file: usize,
+    // the blocks being walked and the index of the next directive in each
+    stack: Vec<(&'a [Directive], usize)>,
+}
+
+impl<'a> Iterator for Directives<'a> {
+    type Item = DirectiveRef<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let Some((block, next)) = self.stack.last_mut() else {
+                let config = self.payload.config.get(self.next_file)?;
+                self.file = self.next_file;
+                self.next_file += 1;
+                self.stack.push((&config.parsed, 0));
+                continue;
+            };
+            let block: &'a [Directive] = block;
+            let Some(directive) = block.get(*next) else {
+                self.stack.pop();
+                continue;
+            };
+            *next += 1;
+
+            let path = self.stack.iter().map(|(_, next)| next - 1).collect();
+            if let Some(children) = &directive.block {
+                self.stack.push((children, 0));
+            }
+            return Some(DirectiveRef {
+                payload: self.payload,
+                file: self.file,
+                path,
+                directive,
+            });
+        }
+    }
+}
@@ -0,0 +1,501 @@
+use std::collections::{BTreeMap, HashSet};
+
+use crate::parse::{Directive, Payload};
+use crate::tree::{DirectiveLocation, DirectiveRef};
+
+// variables provided by nginx and its bundled http modules
+const BUILTIN_VARIABLES: [&str; 112] = [
+    "ancient_browser",
+    "args",
+    "binary_remote_addr",
+    "body_bytes_sent",
+    "bytes_sent",
+    "connection",
+    "connection_requests",
+    "connection_time",
+    "connections_active",
+    "connections_reading",
+    "connections_waiting",
+    "connections_writing",
+    "content_length",
+    "content_type",
+    "date_gmt",
+    "date_local",
+    "document_root",
+    "document_uri",
+    "fastcgi_path_info",
+    "fastcgi_script_name",
+    "gzip_ratio",
+    "host",
+    "hostname",
+    "http2",
+    "http3",
+    "https",
+    "invalid_referer",
+    "is_args",
+    "limit_conn_status",
+    "limit_rate",
+    "limit_req_status",
+    "memcached_key",
+    "modern_browser",
+    "msec",
+    "msie",
+    "nginx_version",
+    "pid",
+    "pipe",
+    "proxy_add_x_forwarded_for",
+    "proxy_host",
+    "proxy_port",
+    "proxy_protocol_addr",
+    "proxy_protocol_port",
+    "proxy_protocol_server_addr",
+    "proxy_protocol_server_port",
+    "query_string",
+    "realip_remote_addr",
+    "realip_remote_port",
+    "realpath_root",
+    "remote_addr",
+    "remote_port",
+    "remote_user",
+    "request",
+    "request_body",
+    "request_body_file",
+    "request_completion",
+    "request_filename",
+    "request_id",
+    "request_length",
+    "request_method",
+    "request_time",
+    "request_uri",
+    "scheme",
+    "secure_link",
+    "secure_link_expires",
+    "server_addr",
+    "server_name",
+    "server_port",
+    "server_protocol",
+    "ssl_alpn_protocol",
+    "ssl_cipher",
+    "ssl_ciphers",
+    "ssl_client_cert",
+    "ssl_client_escaped_cert",
+    "ssl_client_fingerprint",
+    "ssl_client_i_dn",
+    "ssl_client_raw_cert",
+    "ssl_client_s_dn",
+    "ssl_client_serial",
+    "ssl_client_v_end",
+    "ssl_client_v_remain",
+    "ssl_client_v_start",
+    "ssl_client_verify",
+    "ssl_curve",
+    "ssl_curves",
+    "ssl_early_data",
+    "ssl_protocol",
+    "ssl_server_name",
+    "ssl_session_id",
+    "ssl_session_reused",
+    "status",
+    "tcpinfo_rcv_space",
+    "tcpinfo_rtt",
+    "tcpinfo_rttvar",
+    "tcpinfo_snd_cwnd",
+    "time_iso8601",
+    "time_local",
+    "uid_got",
+    "uid_reset",
+    "uid_set",
+    "upstream_addr",
+    "upstream_bytes_received",
+    "upstream_bytes_sent",
+    "upstream_cache_status",
+    "upstream_connect_time",
+    "upstream_header_time",
+    "upstream_last_server_name",
+    "upstream_queue_time",
+    "upstream_response_length",
+    "upstream_response_time",
+    "upstream_status",
+    "uri",
+];
+
+// families of variables named after a header, cookie or argument
+const BUILTIN_PREFIXES: [&str; 9] = [
+    "arg_",
+    "cookie_",
+    "http_",
+    "proxy_protocol_tlv_",
+    "sent_http_",
+    "sent_trailer_",
+    "upstream_cookie_",
+    "upstream_http_",
+    "upstream_trailer_",
+];
+
+// directives defining a variable, with the index of the arg naming it or
+// None for the last one
+const VARIABLE_DEFINERS: [(&str, Option<usize>); 7] = [
+    ("auth_request_set", Some(0)),
+    ("geo", None),
+    ("js_set", Some(0)),
+    ("map", Some(1)),
+    ("perl_set", Some(0)),
+    ("set", Some(0)),
+    ("split_clients", Some(1)),
+];
+
+// the index of the arg naming the variable a directive defines
+fn defined_arg(directive: &Directive) -> Option<usize> {
+    let (_, index) = VARIABLE_DEFINERS
+        .iter()
+        .find(|(name, _)| *name == directive.directive)?;
+    index.or_else(|| directive.args.len().checked_sub(1))
+}
+
+/// Whether nginx provides `$name` without any configuration. Regex captures
+/// like `$1` count as builtin.
+pub fn is_builtin_variable(name: &str) -> bool {
+    BUILTIN_VARIABLES.binary_search(&name).is_ok()
+        || BUILTIN_PREFIXES
+            .iter()
+            .any(|prefix| name.len() > prefix.len() && name.starts_with(prefix))
+        || (!name.is_empty() && name.bytes().all(|b| b.is_ascii_digit()))
+}
+
+/// Names of the variables a config defines itself, through `set`, `map`,
+/// `geo` and similar directives or named regex captures.
+pub fn defined_variables(payload: &Payload) -> HashSet<String> {
+    let mut defined = HashSet::new();
+    for directive in payload.directives() {
+        let directive = directive.directive();
+        let variable = defined_arg(directive).and_then(|i| directive.args.get(i));
+        if let Some(name) = variable.and_then(|v| v.strip_prefix('$')) {
+            defined.insert(name.to_string());
+        }
+
+        for arg in &directive.args {
+            defined.extend(named_captures(arg));
+        }
+    }
+    defined
+}
+
+/// A directive defining or using a variable.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VariableSite {
+    /// The name of the directive, like `set` or `proxy_pass`.
+    pub directive: String,
+    pub file: String,
+    pub line: usize,
+    pub location: DirectiveLocation,
+    /// For regex captures, the block the capture can be used in, or the
+    /// directive itself for `rewrite`; None for variables of the whole
+    /// config.
+    pub scope: Option<DirectiveLocation>,
+}
+
+/// Where a variable is defined and used, in config order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VariableXref {
+    pub definitions: Vec<VariableSite>,
+    pub usages: Vec<VariableSite>,
+}
+
+impl Payload {
+    /// Every variable the config defines or uses, by name without the `$`,
+    /// with where that happens. Regex captures, named or numbered like `$1`,
+    /// are defined by the directives matching the regex.
+    pub fn variable_xref(&self) -> BTreeMap<String, VariableXref> {
+        let mut xref: BTreeMap<String, VariableXref> = BTreeMap::new();
+        for d in self.directives() {
+            let directive = d.directive();
+            let args = &directive.args;
+            let site = |scope: Option<DirectiveLocation>| VariableSite {
+                directive: directive.directive.clone(),
+                file: d.file_name().to_string(),
+                line: directive.line,
+                location: d.location(),
+                scope,
+            };
+
+            let defined = defined_arg(directive);
+            let regex = regex_arg(&d);
+            for (i, arg) in args.iter().enumerate() {
+                if Some(i) == defined {
+                    if let Some(name) = arg.strip_prefix('$') {
+                        let entry = xref.entry(name.to_string()).or_default();
+                        entry.definitions.push(site(None));
+                    }
+                    continue;
+                }
+                let scope = match regex {
+                    Some((index, ref scope)) if index == i => Some(scope.clone()),
+                    _ => None,
+                };
+                for name in named_captures(arg) {
+                    let entry = xref.entry(name).or_default();
+                    entry.definitions.push(site(scope.clone()));
+                }
+                // a regex has anchors, not variables
+                if let Some(scope) = scope {
+                    for n in 1..=capture_count(arg) {
+                        let entry = xref.entry(n.to_string()).or_default();
+                        entry.definitions.push(site(Some(scope.clone())));
+                    }
+                    continue;
+                }
+                for name in variable_refs(arg) {
+                    let entry = xref.entry(name.to_string()).or_default();
+                    if entry
+                        .usages
+                        .last()
+                        .is_none_or(|u| u.location != d.location())
+                    {
+                        entry.usages.push(site(None));
+                    }
+                }
+            }
+        }
+        xref
+    }
+}
+
+// the arg of a directive holding a regex whose captures it defines, with
+// the scope of the captures
+fn regex_arg(d: &DirectiveRef) -> Option<(usize, DirectiveLocation)> {
+    let args = &d.directive().args;
+    match d.directive().directive.as_str() {
+        "location" if matches!(args.first().map(String::as_str), Some("~" | "~*")) => {
+            Some((1, d.location()))
+        }
+        "if" if matches!(args.get(1).map(String::as_str), Some("~" | "~*")) => {
+            Some((2, d.location()))
+        }
+        "rewrite" if !args.is_empty() => Some((0, d.location())),
+        "server_name" => {
+            let i = args.iter().position(|a| a.starts_with('~'))?;
+            Some((i, d.parent()?.location()))
+        }
+        _ => None,
+    }
+}
+
+// how many groups of a regex capture, named or not
+fn capture_count(regex: &str) -> usize {
+    let bytes = regex.as_bytes();
+    let (mut count, mut i, mut class) = (0, 0, false);
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 1,
+            b'[' => class = true,
+            b']' => class = false,
+            b'(' if !class => {
+                let rest = &regex[i + 1..];
+                let named = rest.starts_with("?P<")
+                    || rest.starts_with("?'")
+                    || (rest.starts_with("?<")
+                        && !rest.starts_with("?<=")
+                        && !rest.starts_with("?<!"));
+                if !rest.starts_with('?') || named {
+                    count += 1;
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    count
+}
+
+// the names of the variables an arg refers to, as "$name" or "${name}"
+pub(crate) fn variable_refs(arg: &str) -> impl Iterator<Item = &str> + '_ {
+    let mut rest = arg;
+    std::iter::from_fn(move || loop {
+        let i = rest.find(['\\', '$'])?;
+        if rest[i..].starts_with('\\') {
+            let mut escaped = rest[i + 1..].chars();
+            escaped.next();
+            rest = escaped.as_str();
+            continue;
+        }
+        let after = &rest[i + 1..];
+        let (name, len) = match after.strip_prefix('{') {
+            Some(braced) => match braced.find('}') {
+                Some(end) => (&braced[..end], end + 2),
+                None => ("", 0),
+            },
+            None => {
+                let end = after
+                    .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+                    .unwrap_or(after.len());
+                (&after[..end], end)
+            }
+        };
+        rest = &after[len..];
+        if !name.is_empty() {
+            return Some(name);
+        }
+    })
+}
+
+// names of the captures in a regex, like "(?<name>...)" or "(?P<name>...)"
+fn named_captures(regex: &str) -> impl Iterator<Item = String> + '_ {
+    regex.match_indices("(?").filter_map(move |(i, _)| {
+        let rest = &regex[i + 2..];
+        let rest = rest.strip_prefix('P').unwrap_or(rest);
+        let (open, close) = match rest.chars().next()? {
+            '<' => ('<', '>'),
+            '\'' => ('\'', '\''),
+            _ => return None,
+        };
+        let rest = rest.strip_prefix(open)?;
+        let name = &rest[..rest.find(close)?];
+        (!name.is_empty() && !name.starts_with(['=', '!'])).then(|| name.to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    use crate::parse::{parse, parse_source, ParseOptions};
+
+    #[test]
+    fn test_builtin_variables() {
+        assert!(BUILTIN_VARIABLES.windows(2).all(|w| w[0] < w[1]));
+        for name in ["remote_addr", "uri", "http_user_agent", "arg_id", "1"] {
+            assert!(is_builtin_variable(name), "{}", name);
+        }
+        for name in ["remote_adr", "http_", "", "foo"] {
+            assert!(!is_builtin_variable(name), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_defined_variables() {
+        assert!(VARIABLE_DEFINERS.windows(2).all(|w| w[0].0 < w[1].0));
+        let source = r#"http {
+    map $uri $section {
+        default main;
+    }
+    geo $remote_addr $office {
+        default 0;
+    }
+    server {
+        location ~ ^/users/(?<user>\d+)(?P<rest>.*)$ {
+            set $target "x";
+        }
+    }
+}
+"#;
+        let payload = parse_source(source, &ParseOptions::default());
+
+        let mut defined: Vec<_> = defined_variables(&payload).into_iter().collect();
+        defined.sort();
+        assert_eq!(defined, ["office", "rest", "section", "target", "user"]);
+    }
+
+    // the sites of a variable as "directive file:line"
+    fn sites(sites: &[VariableSite]) -> Vec<String> {
+        sites
+            .iter()
+            .map(|s| format!("{} {}:{}", s.directive, s.file, s.line))
+            .collect()
+    }
+
+    #[test]
+    fn test_variable_xref() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |name: &str, source: &str| fs::write(dir.path().join(name), source).unwrap();
+        write(
+            "nginx.conf",
+            r#"http {
+    include maps.conf;
+    server {
+        server_name ~^(?<sub>\w+)\.example\.com$;
+        location ~ ^/users/(?<user>\d+)/(\w+)$ {
+            proxy_pass http://backend/$user/$2?section=$section;
+        }
+        location / {
+            rewrite ^/old/(.*)$ /new/$1 last;
+            return 200 "${sub} \$section $section";
+        }
+    }
+}
+"#,
+        );
+        write(
+            "maps.conf",
+            "map $uri $section {\n    default main;\n    ~^/a $host;\n}\n",
+        );
+        let payload = parse(dir.path().join("nginx.conf"), &ParseOptions::default());
+        let xref = payload.variable_xref();
+        let main = dir.path().join("nginx.conf").display().to_string();
+        let maps = dir.path().join("maps.conf").display().to_string();
+        let site =
+            |directive: &str, file: &str, line: usize| format!("{} {}:{}", directive, file, line);
+
+        assert_eq!(
+            xref.keys().collect::<Vec<_>>(),
+            ["1", "2", "host", "section", "sub", "uri", "user"]
+        );
+
+        // a named capture of a location used in its proxy_pass
+        let user = &xref["user"];
+        assert_eq!(sites(&user.definitions), [site("location", &main, 5)]);
+        assert_eq!(
+            user.definitions[0].scope,
+            Some(user.definitions[0].location.clone())
+        );
+        assert_eq!(sites(&user.usages), [site("proxy_pass", &main, 6)]);
+
+        // a map defined in one file and used in another
+        let section = &xref["section"];
+        assert_eq!(sites(&section.definitions), [site("map", &maps, 1)]);
+        assert_eq!(section.definitions[0].scope, None);
+        assert_eq!(
+            sites(&section.usages),
+            [site("proxy_pass", &main, 6), site("return", &main, 10)]
+        );
+        assert_eq!(sites(&xref["uri"].usages), [site("map", &maps, 1)]);
+        assert_eq!(sites(&xref["host"].usages), [site("~^/a", &maps, 3)]);
+
+        // numbered captures, scoped to what matched them
+        let one = &xref["1"];
+        assert_eq!(
+            sites(&one.definitions),
+            [
+                site("server_name", &main, 4),
+                site("location", &main, 5),
+                site("rewrite", &main, 9)
+            ]
+        );
+        assert_eq!(
+            one.definitions[2].scope,
+            Some(one.definitions[2].location.clone())
+        );
+        assert_eq!(sites(&one.usages), [site("rewrite", &main, 9)]);
+        assert_eq!(sites(&xref["2"].definitions), [site("location", &main, 5)]);
+        assert_eq!(sites(&xref["2"].usages), [site("proxy_pass", &main, 6)]);
+
+        // a server_name capture is scoped to the server
+        let sub = &xref["sub"];
+        assert_eq!(sites(&sub.definitions), [site("server_name", &main, 4)]);
+        assert_eq!(sub.definitions[0].scope.as_ref().unwrap().path, [0, 1]);
+        assert_eq!(sites(&sub.usages), [site("return", &main, 10)]);
+    }
+
+    #[test]
+    fn test_capture_count() {
+        assert_eq!(
+            capture_count(r"^/(a|b)/(?<id>\d+)(?:x)(?=y)(?<!z)[(]\(\)$"),
+            2
+        );
+        assert_eq!(capture_count(r"(?P<a>.)(?'b'.)(.)"), 3);
+        assert_eq!(
+            variable_refs(r"$a ${b}c \$d $ é$e_1").collect::<Vec<_>>(),
+            ["a", "b", "e_1"]
+        );
+    }
+}
@@ -0,0 +1,181 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NginxVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl NginxVersion {
+    pub const V1_18: NginxVersion = NginxVersion::new(1, 18, 0);
+    pub const V1_20: NginxVersion = NginxVersion::new(1, 20, 0);
+    pub const V1_22: NginxVersion = NginxVersion::new(1, 22, 0);
+    pub const V1_24: NginxVersion = NginxVersion::new(1, 24, 0);
+    pub const V1_25_1: NginxVersion = NginxVersion::new(1, 25, 1);
+    pub const V1_26: NginxVersion = NginxVersion::new(1, 26, 0);
+    pub const V1_28: NginxVersion = NginxVersion::new(1, 28, 0);
+
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        NginxVersion {
+            major,
+            minor,
+            patch,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionError {
+    pub input: String,
+}
+
+impl fmt::Display for VersionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid nginx version \"{}\"", self.input)
+    }
+}
+
+impl std::error::Error for VersionError {}
+
+impl FromStr for NginxVersion {
+    type Err = VersionError;
+
+    /// Accepts "1.25.3", "1.25" and the output of `nginx -v`, like
+    /// "nginx version: nginx/1.24.0 (Ubuntu)".
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || VersionError {
+            input: s.to_string(),
+        };
+
+        let mut version = s.trim();
+        version = version
+            .strip_prefix("nginx version:")
+            .unwrap_or(version)
+            .trim_start();
+        version = version.strip_prefix("nginx/").unwrap_or(version);
+        let version = version.split_whitespace().next().ok_or_else(error)?;
+
+        let parts = version
+            .split('.')
+            .map(|part| {
+                if part.is_empty() || !part.bytes().all(|b| b.is_ascii_digit()) {
+                    return Err(error());
+                }
+                part.parse::<u32>().map_err(|_| error())
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        match parts[..] {
+            [major, minor] => Ok(NginxVersion::new(major, minor, 0)),
+            [major, minor, patch] => Ok(NginxVersion::new(major, minor, patch)),
+            _ => Err(error()),
+        }
+    }
+}
+
+impl fmt::Display for NginxVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+impl Serialize for NginxVersion {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for NginxVersion {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// The versions a directive exists in: from `since` (inclusive) until
+/// `removed` (exclusive), unbounded where not set.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VersionRange {
+    pub since: Option<NginxVersion>,
+    pub removed: Option<NginxVersion>,
+}
+
+impl VersionRange {
+    pub fn contains(&self, version: NginxVersion) -> bool {
+        self.since.is_none_or(|since| version >= since)
+            && self.removed.is_none_or(|removed| version < removed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_version() {
+        let cases = [
+            ("1.25.3", NginxVersion::new(1, 25, 3)),
+            ("1.25", NginxVersion::new(1, 25, 0)),
+            (" 1.9.15\n", NginxVersion::new(1, 9, 15)),
+            ("nginx/1.24.0", NginxVersion::V1_24),
+            ("nginx version: nginx/1.24.0 (Ubuntu)", NginxVersion::V1_24),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(input.parse::<NginxVersion>(), Ok(expected), "{:?}", input);
+        }
+
+        for input in [
+            "", "1", "1.25.3.1", "1.x", "v1.25", "1..2", "nginx/", "1.-2",
+        ] {
+            let err = input.parse::<NginxVersion>().unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                format!("invalid nginx version \"{}\"", input)
+            );
+        }
+    }
+
+    #[test]
+    fn test_version_ordering_and_display() {
+        let mut versions: Vec<NginxVersion> = ["1.25.10", "1.9.0", "1.25.2", "1.24", "2.0"]
+            .iter()
+            .map(|v| v.parse().unwrap())
+            .collect();
+        versions.sort();
+        let versions: Vec<String> = versions.iter().map(|v| v.to_string()).collect();
+        assert_eq!(versions, ["1.9.0", "1.24.0", "1.25.2", "1.25.10", "2.0.0"]);
+
+        let json = serde_json::to_string(&NginxVersion::V1_26).unwrap();
+        assert_eq!(json, "\"1.26.0\"");
+        assert_eq!(
+            serde_json::from_str::<NginxVersion>(&json).unwrap(),
+            NginxVersion::V1_26
+        );
+        assert!(serde_json::from_str::<NginxVersion>("\"1.x\"").is_err());
+    }
+
+    #[test]
+    fn test_version_range() {
+        // the http2 directive replaced "listen ... http2" in 1.25.1
+        let http2 = VersionRange {
+            since: Some(NginxVersion::V1_25_1),
+            removed: None,
+        };
+        assert!(!http2.contains(NginxVersion::V1_24));
+        assert!(!http2.contains("1.25.0".parse().unwrap()));
+        assert!(http2.contains(NginxVersion::V1_25_1));
+        assert!(http2.contains(NginxVersion::V1_28));
+
+        let removed = VersionRange {
+            since: None,
+            removed: Some(NginxVersion::V1_26),
+        };
+        assert!(removed.contains(NginxVersion::V1_18));
+        assert!(!removed.contains(NginxVersion::V1_26));
+        assert!(VersionRange::default().contains(NginxVersion::V1_28));
+    }
+}
@@ -0,0 +1,203 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::analyze::directive_specs;
+use crate::parse::{Directive, Payload};
+use crate::version::NginxVersion;
+
+/// What a version of nginx makes of a directive.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "kebab-case")]
+pub enum VersionStatus {
+    Available,
+    /// The directive still works, but `replacement` should be used instead.
+    Deprecated {
+        since: NginxVersion,
+        replacement: String,
+    },
+    /// The directive only comes with a later version.
+    NotYetAdded {
+        since: NginxVersion,
+    },
+    Removed {
+        removed: NginxVersion,
+    },
+}
+
+impl fmt::Display for VersionStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VersionStatus::Available => write!(f, "available"),
+            VersionStatus::Deprecated { since, replacement } => {
+                write!(f, "deprecated since {}, use {}", since, replacement)
+            }
+            VersionStatus::NotYetAdded { since } => write!(f, "unknown, added in {}", since),
+            VersionStatus::Removed { removed } => write!(f, "removed in {}", removed),
+        }
+    }
+}
+
+/// A directive occurrence that not all of the analyzed versions agree on.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VersionDivergence {
+    pub directive: String,
+    pub file: String,
+    pub line: usize,
+    /// What each version makes of the directive, in the order of
+    /// [`VersionDiffReport::versions`].
+    pub statuses: Vec<VersionStatus>,
+}
+
+/// The directives of a payload whose validity differs between versions of
+/// nginx, see [`analyze_across_versions`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VersionDiffReport {
+    pub versions: Vec<NginxVersion>,
+    /// Sorted by file and line.
+    pub divergences: Vec<VersionDivergence>,
+}
+
+/// Checks every directive of the payload against each of `versions` and
+/// reports those that are available in some and unknown, removed or
+/// deprecated in others. Directives no version knows are left to the
+/// strict parse.
+pub fn analyze_across_versions(payload: &Payload, versions: &[NginxVersion]) -> VersionDiffReport {
+    let mut divergences: Vec<VersionDivergence> = Vec::new();
+    for d in payload.directives() {
+        let directive = d.directive();
+        if directive.is_comment() || directive.is_raw() || directive.placeholder {
+            continue;
+        }
+        let statuses: Option<Vec<VersionStatus>> = versions
+            .iter()
+            .map(|&version| version_status(directive, version))
+            .collect();
+        let Some(statuses) = statuses else {
+            continue;
+        };
+        if statuses.windows(2).all(|w| w[0] == w[1]) {
+            continue;
+        }
+        divergences.push(VersionDivergence {
+            directive: directive.directive.clone(),
+            file: d.file_name().to_string(),
+            line: directive.line,
+            statuses,
+        });
+    }
+
+    // the same directive twice on a line diverges the same way twice
+    divergences.sort_by(|a, b| (&a.file, a.line).cmp(&(&b.file, b.line)));
+    divergences.dedup();
+    VersionDiffReport {
+        versions: versions.to_vec(),
+        divergences,
+    }
+}
+
+// None for directives unknown to the analyzer
+fn version_status(directive: &Directive, version: NginxVersion) -> Option<VersionStatus> {
+    let specs = directive_specs(&directive.directive);
+    if specs.is_empty() {
+        return None;
+    }
+    if !specs.iter().any(|s| s.versions.contains(version)) {
+        // not added yet by any module, or removed from all of them
+        let since = specs.iter().filter_map(|s| s.versions.since).min();
+        return Some(match since.filter(|&since| version < since) {
+            Some(since) => VersionStatus::NotYetAdded { since },
+            None => VersionStatus::Removed {
+                removed: specs
+                    .iter()
+                    .filter_map(|s| s.versions.removed)
+                    .max()
+                    .unwrap_or(version),
+            },
+        });
+    }
+
+    let deprecation = specs
+        .iter()
+        .filter_map(|s| s.deprecated)
+        .find(|d| d.applies_to(directive) && version >= d.since);
+    Some(match deprecation {
+        Some(deprecation) => VersionStatus::Deprecated {
+            since: deprecation.since,
+            replacement: deprecation.replacement.to_string(),
+        },
+        None => VersionStatus::Available,
+    })
+}
+
+impl fmt::Display for VersionDiffReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for divergence in &self.divergences {
+            writeln!(
+                f,
+                "{} in {}:{}",
+                divergence.directive, divergence.file, divergence.line
+            )?;
+            for (version, status) in self.versions.iter().zip(&divergence.statuses) {
+                writeln!(f, "  {}: {}", version, status)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::{parse_source, ParseOptions};
+
+    #[test]
+    fn test_analyze_across_versions() {
+        let source = r#"http {
+    server {
+        listen 443 ssl http2;
+        http2_push /style.css; http2_push /style.css;
+        http2 on;
+        root /srv/www;
+        frobnicate on;
+    }
+}
+"#;
+        let payload = parse_source(source, &ParseOptions::default());
+        let report = analyze_across_versions(&payload, &[NginxVersion::V1_22, NginxVersion::V1_26]);
+        assert_eq!(
+            report.to_string(),
+            "listen in nginx.conf:3
+  1.22.0: available
+  1.26.0: deprecated since 1.25.1, use the \"http2\" directive
+http2_push in nginx.conf:4
+  1.22.0: available
+  1.26.0: removed in 1.25.1
+http2 in nginx.conf:5
+  1.22.0: unknown, added in 1.25.1
+  1.26.0: available
+"
+        );
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["versions"], serde_json::json!(["1.22.0", "1.26.0"]));
+        assert_eq!(
+            json["divergences"][1],
+            serde_json::json!({
+                "directive": "http2_push",
+                "file": "nginx.conf",
+                "line": 4,
+                "statuses": [
+                    {"status": "available"},
+                    {"status": "removed", "removed": "1.25.1"}
+                ]
+            })
+        );
+        let back: VersionDiffReport = serde_json::from_value(json).unwrap();
+        assert_eq!(back, report);
+
+        // versions that agree have nothing to report
+        let report = analyze_across_versions(&payload, &[NginxVersion::V1_26, NginxVersion::V1_28]);
+        assert_eq!(report.divergences, []);
+    }
+}